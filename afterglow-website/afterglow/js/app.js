@@ -10,15 +10,48 @@
   const lightboxPrev = document.getElementById("lb-prev");
   const lightboxNext = document.getElementById("lb-next");
   const lightboxDownload = document.getElementById("lb-dl");
+  const lightboxBuy = document.getElementById("lb-buy");
+
+  // ===== Site Manifest =====
+  // site-manifest.json is always revalidated; other JSON is fetched with the manifest's
+  // stamp as a ?v= parameter so caches are bypassed only when the file changed. The content
+  // hash in `files` is preferred; manifests without it fall back to lastUpdated, and sites
+  // published before the manifest existed fall back to plain URLs.
+  let manifestPromise = null;
+
+  function loadManifest() {
+    if (!manifestPromise) {
+      manifestPromise = fetch("site-manifest.json", { cache: "no-cache" })
+        .then((r) => (r.ok ? r.json() : null))
+        .catch(() => null);
+    }
+    return manifestPromise;
+  }
+
+  function withStamp(path, stamp) {
+    return stamp ? `${path}?v=${encodeURIComponent(stamp)}` : path;
+  }
+
+  async function manifestUrl(path) {
+    const m = await loadManifest();
+    if (!m) return path;
+    const stamp = m.files && m.files[path];
+    if (stamp && stamp.hash) return withStamp(path, stamp.hash);
+    if (m.galleriesIndex && m.galleriesIndex.path === path) return withStamp(path, m.galleriesIndex.lastUpdated);
+    if (m.searchIndex && m.searchIndex.path === path) return withStamp(path, m.searchIndex.lastUpdated);
+    const g = (m.galleries || []).find((g) => g.details === path || g.searchShard === path);
+    return withStamp(path, g && g.lastUpdated);
+  }
 
   // ===== Data Cache =====
   let galleriesCache = null;
   const galleryDetailCache = new Map();
   let searchIndexCache = null;
+  const searchShardCache = new Map();
 
   async function fetchGalleries() {
     if (galleriesCache) return galleriesCache;
-    const res = await fetch("galleries/galleries.json");
+    const res = await fetch(await manifestUrl("galleries/galleries.json"));
     const data = await res.json();
     galleriesCache = data.galleries.map((g) => ({
       ...g,
@@ -29,7 +62,7 @@
 
   async function fetchGalleryDetail(slug) {
     if (galleryDetailCache.has(slug)) return galleryDetailCache.get(slug);
-    const res = await fetch(`galleries/${slug}/gallery-details.json`);
+    const res = await fetch(await manifestUrl(`galleries/${slug}/gallery-details.json`));
     const data = await res.json();
     data.photos = data.photos.map((p) => ({
       ...p,
@@ -43,11 +76,34 @@
   // ===== Search Index =====
   async function loadSearchIndex() {
     if (searchIndexCache) return searchIndexCache;
-    const r = await fetch("galleries/search-index.json");
+    const r = await fetch(await manifestUrl("galleries/search-index.json"));
     searchIndexCache = await r.json();
     return searchIndexCache;
   }
 
+  function loadSearchShard(shard) {
+    if (searchShardCache.has(shard.path)) return searchShardCache.get(shard.path);
+    const pending = manifestUrl(`galleries/${shard.path}`)
+      .then((url) => fetch(url))
+      .then((r) => r.json())
+      .then((data) => data.photos || [])
+      .catch((e) => {
+        searchShardCache.delete(shard.path);
+        throw e;
+      });
+    searchShardCache.set(shard.path, pending);
+    return pending;
+  }
+
+  // v2 indexes keep photos in per-gallery shards, fetched on first search. Shards whose tag
+  // list can't satisfy the #tags in the query are skipped. v1 indexes inline all photos.
+  async function loadSearchPhotos(index, tags) {
+    if (Array.isArray(index.photos)) return index.photos;
+    const shards = (index.shards || []).filter((s) => tags.every((t) => (s.tags || []).includes(t)));
+    const photos = await Promise.all(shards.map(loadSearchShard));
+    return photos.flat();
+  }
+
   function parseQuery(q) {
     const tags = [], terms = [];
     for (const word of q.trim().split(/\s+/)) {
@@ -77,7 +133,8 @@
       const matchedGalleries = index.galleries.filter((g) =>
         matchesItem(g, [g.name, g.date, g.description || "", ...(g.tags || [])], { tags, terms })
       );
-      const matchedPhotos = index.photos.filter((p) =>
+      const photos = await loadSearchPhotos(index, tags);
+      const matchedPhotos = photos.filter((p) =>
         matchesItem(p, [p.alt, p.gallerySlug, ...(p.tags || [])], { tags, terms })
       );
 
@@ -111,7 +168,7 @@
             <div class="search-photo-grid">`;
           for (const p of matchedPhotos) {
             html += `<a class="search-photo-thumb" href="#gallery=${encodeURIComponent(p.gallerySlug)}&photo=${encodeURIComponent(p.thumbnail)}">
-              <img src="galleries/${escapeHtml(p.gallerySlug)}/${escapeHtml(p.thumbnail)}" alt="${escapeHtml(p.alt)}" loading="lazy">
+              <img src="galleries/${escapeHtml(p.gallerySlug)}/${escapeHtml(p.thumbnail)}" alt="${escapeHtml(p.alt)}"${p.width && p.height ? ` width="${p.width}" height="${p.height}"` : ""} loading="lazy">
               <div class="search-photo-caption">${escapeHtml(p.alt || p.gallerySlug)}</div>
             </a>`;
           }
@@ -426,6 +483,10 @@
 
       inner.appendChild(masonry);
 
+      if (detail.comments && detail.comments.provider) {
+        inner.appendChild(buildComments(detail.comments));
+      }
+
       wrap.appendChild(inner);
       app.innerHTML = "";
       app.appendChild(wrap);
@@ -445,6 +506,69 @@
     }
   }
 
+  // ===== Comments =====
+  // Published by the manager as `comments` in gallery-details.json: { provider, threadId, ... }.
+  function buildComments(config) {
+    const section = document.createElement("section");
+    section.className = "comments";
+    section.setAttribute("aria-label", "Comments");
+    if (config.provider === "giscus") {
+      const script = document.createElement("script");
+      script.src = "https://giscus.app/client.js";
+      script.async = true;
+      script.crossOrigin = "anonymous";
+      const attrs = {
+        repo: config.repo,
+        "repo-id": config.repoId,
+        category: config.category,
+        "category-id": config.categoryId,
+        mapping: "specific",
+        term: config.threadId,
+        "reactions-enabled": "1",
+        "input-position": "top",
+        theme: "dark",
+        loading: "lazy",
+      };
+      Object.entries(attrs).forEach(([k, v]) => script.setAttribute(`data-${k}`, v));
+      section.appendChild(script);
+    } else if (config.provider === "cactus") {
+      const node = document.createElement("div");
+      section.appendChild(node);
+      loadCactus().then(() => {
+        window.initComments({
+          node,
+          defaultHomeserverUrl: config.homeserverUrl,
+          serverName: config.serverName,
+          siteName: config.siteName,
+          commentSectionId: config.threadId,
+        });
+      }).catch(() => {
+        node.textContent = "Comments are unavailable.";
+      });
+    }
+    return section;
+  }
+
+  let cactusLoading = null;
+  function loadCactus() {
+    if (cactusLoading) return cactusLoading;
+    cactusLoading = new Promise((resolve, reject) => {
+      const css = document.createElement("link");
+      css.rel = "stylesheet";
+      css.href = "https://latest.cactus.chat/style.css";
+      document.head.appendChild(css);
+      const script = document.createElement("script");
+      script.src = "https://latest.cactus.chat/cactus.js";
+      script.onload = resolve;
+      script.onerror = () => {
+        cactusLoading = null;
+        reject();
+      };
+      document.head.appendChild(script);
+    });
+    return cactusLoading;
+  }
+
   function buildMasonry(photos) {
     const masonry = document.createElement("div");
     masonry.className = "masonry";
@@ -525,6 +649,13 @@
     if (countEl) countEl.textContent = `${index + 1} of ${currentPhotos.length}`;
 
     if (lightboxDownload) lightboxDownload.onclick = () => downloadPhoto(photo);
+    if (lightboxBuy) {
+      // externalUrl: optional per-photo link (e.g. a print store), set in the manager
+      const external = /^https?:\/\//i.test(photo.externalUrl || "") ? photo.externalUrl : "";
+      lightboxBuy.hidden = !external;
+      if (external) lightboxBuy.href = external;
+      else lightboxBuy.removeAttribute("href");
+    }
 
     const gen = ++lightboxLoadGen;
     const img = new Image();
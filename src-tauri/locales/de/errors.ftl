@@ -0,0 +1,80 @@
+# Fehlermeldungen der Backend-Befehle. Eine Meldung pro `ErrorCode` in src/i18n.rs.
+
+bucket-not-configured = Es ist kein S3-Bucket konfiguriert. Lege ihn in den Einstellungen fest.
+distribution-not-configured = Es ist keine CloudFront-Distributions-ID konfiguriert. Lege sie in den Einstellungen fest.
+site-url-not-configured = Es ist keine Website-URL konfiguriert. Lege sie in den Einstellungen fest.
+no-credentials = Keine Zugangsdaten gefunden. Konfiguriere die AWS-Zugangsdaten in den Einstellungen.
+keychain-unavailable = Kein Zugriff auf den Schlüsselbund des Systems: { $error }
+keychain-save-failed = Kein Zugriff auf den Schlüsselbund des Systems. Die Zugangsdaten können nicht gespeichert werden: { $error }
+not-a-directory = Kein Ordner: { $path }
+plan-not-found = Plan nicht gefunden. Erstelle zuerst eine Vorschau.
+galleries-json-read-failed = galleries.json konnte nicht gelesen werden: { $error }
+galleries-json-parse-failed = galleries.json konnte nicht verarbeitet werden: { $error }
+upload-failed = Hochladen von { $key } fehlgeschlagen: { $error }
+invalidation-failed = CloudFront-Invalidierung fehlgeschlagen: { $error }
+invalidation-timed-out = Zeitüberschreitung der CloudFront-Invalidierung nach { $seconds } s.
+gallery-folder-not-found = Galerieordner nicht gefunden: { $slug }
+app-data-dir-unavailable = App-Datenordner kann nicht ermittelt werden: { $error }
+listen-failed = Verbindungen auf { $address } können nicht angenommen werden: { $error }
+encryption-already-set-up = Dieser Arbeitsbereich hat bereits eine Verschlüsselungs-Passphrase.
+encryption-not-set-up = Dieser Arbeitsbereich hat keine Verschlüsselungs-Passphrase.
+encryption-settings-unsupported = Nicht unterstützte Version der Verschlüsselungseinstellungen: { $version }.
+passphrase-too-short = Die Passphrase muss mindestens { $min } Zeichen lang sein.
+wrong-passphrase = Falsche Passphrase.
+workspace-locked = Entsperre den Arbeitsbereich zuerst mit seiner Passphrase.
+galleries-locked = Entsperre den Arbeitsbereich vor dem Veröffentlichen: { $galleries } verschlüsselt.
+decryption-failed = Falsche Passphrase oder beschädigte Datei: { $path }
+companion-port-too-low = Companion-Port { $port } muss 1024 oder höher sein.
+companion-disabled = Aktiviere zuerst den Companion-Zugriff in den Einstellungen.
+no-pairing-in-progress = Keine Kopplung aktiv. Starte die Kopplung zuerst am Desktop.
+pairing-code-expired = Der Kopplungscode ist abgelaufen.
+wrong-pairing-code = Falscher Kopplungscode.
+device-not-found = Kein gekoppeltes Gerät mit der ID { $id }
+archive-bucket-not-configured = Kein Archiv-Bucket konfiguriert.
+gallery-already-archived = Diese Galerie ist bereits im Bucket { $bucket } archiviert.
+unknown-restore-tier = Unbekannte Wiederherstellungsstufe "{ $tier }" (Deep Archive unterstützt Standard oder Bulk).
+restore-start-failed = Wiederherstellung von { $name } konnte nicht gestartet werden: { $error }
+archive-status-failed = Archivstatus von { $name } konnte nicht gelesen werden: { $error }
+download-failed = Download von { $key } fehlgeschlagen: { $error }
+mirror-region-missing = Der Spiegel braucht eine Region.
+mirror-endpoint-not-https = Spiegel-Endpunkt "{ $endpoint }" muss eine https-URL sein.
+mirror-same-bucket = Der Spiegel muss ein anderer Bucket als die Website sein.
+mirror-list-failed = Spiegel kann nicht aufgelistet werden: { $error }
+mirror-credentials-save-failed = Zugangsdaten des Spiegels konnten nicht gespeichert werden: { $error }
+automation-token-write-failed = Automatisierungs-Token konnte nicht geschrieben werden: { $error }
+gallery-details-not-found = { $path } nicht gefunden
+gallery-details-changed-externally = { $path } wurde außerhalb der App geändert, während Änderungen daran ausstanden. Setze ihn zurück, um die neue Version zu laden.
+publish-delete-needs-confirmation = Diese Veröffentlichung löscht { $count } Dateien aus S3. Gib "{ $token }" ein, um zu bestätigen.
+delete-failed = Löschen von { $key } fehlgeschlagen: { $error }
+mirror-delete-needs-confirmation = Der Spiegel löscht { $count } Dateien. Gib "{ $token }" ein, um zu bestätigen.
+managed-rules-changed = Das S3-Präfix oder die verwalteten Pfade haben sich seit dieser Vorschau geändert. Erstelle die Vorschau erneut.
+deletes-outside-managed-area = Veröffentlichung verweigert: { $count } geplante Löschungen liegen außerhalb des verwalteten Bereichs (z. B. { $example }).
+site-prefix-overlap = S3-Präfix { $root } überschneidet sich mit der unter { $other } veröffentlichten Website: Das Veröffentlichen würde deren Dateien löschen. Wähle ein Präfix außerhalb ihrer Ordner galleries/ und afterglow/.
+workspace-read-only = { $path } liegt in einem schreibgeschützt geöffneten Arbeitsbereich. Öffne ihn normal, um Änderungen vorzunehmen.
+prefix-leading-slash = S3-Präfix "{ $prefix }" darf nicht mit "/" beginnen.
+prefix-illegal-character = S3-Präfix "{ $prefix }" enthält ein unzulässiges Zeichen ({ $character }).
+prefix-invalid-segment = S3-Präfix "{ $prefix }" enthält ein leeres, "."- oder ".."-Segment.
+arn-wrong-partition = { $resource ->
+    [bucket] Die Bucket-ARN
+   *[distribution] Die ARN der CloudFront-Distribution
+} liegt in der Partition { $partition }, Region { $region } aber in { $expected }.
+cloudfront-unavailable-in-govcloud = CloudFront ist in AWS GovCloud (US) nicht verfügbar. Leere die Distribution-ID.
+secret-store-write-failed = In den Schlüsselspeicher kann nicht geschrieben werden: { $error }
+secret-store-mismatch = Der Schlüsselspeicher hat einen anderen Wert zurückgegeben als gespeichert wurde.
+secret-store-read-failed = Testeintrag gespeichert, aber nicht wieder lesbar: { $error }
+companion-devices-invalid = Ungültige companion-devices.json: { $error }
+companion-request-rejected = { $status } { $reason }
+companion-no-workspace = In Afterglow Manager ist kein Arbeitsbereich geöffnet.
+companion-invalid-request = Ungültige Anfrage: { $error }
+companion-device-name-invalid = deviceName muss 1 bis 100 Zeichen lang sein.
+companion-not-an-image = Kein Bild.
+companion-photo-not-found = Foto nicht gefunden.
+companion-gallery-not-found = Galerie nicht gefunden.
+companion-not-paired = Kopple dieses Gerät zuerst.
+companion-delete-limit-exceeded = Diese Veröffentlichung löscht mehr Dateien, als der Löschschutz erlaubt. Bestätige sie am Desktop.
+companion-risky-deletes = Diese Veröffentlichung löscht entfernte Galerien oder unbekannte Dateien. Bestätige sie am Desktop.
+companion-address-invalid = Companion-Adresse "{ $address }" muss eine IP-Adresse dieses Computers sein.
+companion-address-unavailable = Die Netzwerkadresse dieses Computers wurde nicht gefunden. Wähle in den Einstellungen eine Schnittstelle für den Companion-Zugriff.
+previous-target-overlap = S3-Präfix { $root } überschneidet sich mit dem vorherigen Speicherort { $previous }, der noch die alte Website enthält. Räume zuerst den alten Speicherort in den Einstellungen auf oder wähle ein Präfix außerhalb davon.
+archive-inside-managed-area = Archiv-Präfix "{ $prefix }" liegt im verwalteten Bereich der veröffentlichten Website; wähle ein anderes Präfix oder einen anderen Bucket.
+managed-path-invalid = Verwalteter Pfad "{ $path }": { $error }
@@ -0,0 +1,80 @@
+# Error messages returned by backend commands. One message per `ErrorCode` in src/i18n.rs.
+
+bucket-not-configured = S3 bucket is not configured. Set it in Settings.
+distribution-not-configured = CloudFront distribution ID is not configured. Set it in Settings.
+site-url-not-configured = Site URL is not configured. Set it in Settings.
+no-credentials = No credentials found. Configure AWS credentials in Settings.
+keychain-unavailable = Unable to access system keychain: { $error }
+keychain-save-failed = Unable to access system keychain. Credentials cannot be saved: { $error }
+not-a-directory = Not a directory: { $path }
+plan-not-found = Plan not found. Run preview first.
+galleries-json-read-failed = Failed to read galleries.json: { $error }
+galleries-json-parse-failed = Failed to parse galleries.json: { $error }
+upload-failed = Upload failed for { $key }: { $error }
+invalidation-failed = CloudFront invalidation failed: { $error }
+invalidation-timed-out = CloudFront invalidation timed out after { $seconds }s.
+gallery-folder-not-found = Gallery folder not found: { $slug }
+app-data-dir-unavailable = Cannot determine app data directory: { $error }
+listen-failed = Failed to listen on { $address }: { $error }
+encryption-already-set-up = This workspace already has an encryption passphrase.
+encryption-not-set-up = This workspace has no encryption passphrase.
+encryption-settings-unsupported = Unsupported encryption settings version { $version }.
+passphrase-too-short = The passphrase must be at least { $min } characters.
+wrong-passphrase = Wrong passphrase.
+workspace-locked = Unlock the workspace with its passphrase first.
+galleries-locked = Unlock the workspace before publishing: { $galleries } encrypted.
+decryption-failed = Wrong passphrase or damaged file: { $path }
+companion-port-too-low = Companion port { $port } must be 1024 or higher.
+companion-disabled = Turn on companion access in settings first.
+no-pairing-in-progress = No pairing in progress. Start pairing on the desktop first.
+pairing-code-expired = The pairing code has expired.
+wrong-pairing-code = Wrong pairing code.
+device-not-found = No paired device with id { $id }
+archive-bucket-not-configured = No archive bucket configured.
+gallery-already-archived = This gallery is already archived in bucket { $bucket }.
+unknown-restore-tier = Unknown restore tier "{ $tier }" (Deep Archive supports Standard or Bulk).
+restore-start-failed = Failed to start the restore of { $name }: { $error }
+archive-status-failed = Failed to read the archive status of { $name }: { $error }
+download-failed = Failed to download { $key }: { $error }
+mirror-region-missing = The mirror needs a region.
+mirror-endpoint-not-https = Mirror endpoint "{ $endpoint }" must be an https URL.
+mirror-same-bucket = The mirror must be a different bucket than the site.
+mirror-list-failed = Couldn't list the mirror: { $error }
+mirror-credentials-save-failed = Failed to save mirror credentials: { $error }
+automation-token-write-failed = Failed to write automation token: { $error }
+gallery-details-not-found = { $path } not found
+gallery-details-changed-externally = { $path } was changed outside the app while edits to it were pending. Roll back to load the new version.
+publish-delete-needs-confirmation = This publish deletes { $count } files from S3. Type "{ $token }" to confirm.
+delete-failed = Delete failed for { $key }: { $error }
+mirror-delete-needs-confirmation = The mirror deletes { $count } files. Type "{ $token }" to confirm.
+managed-rules-changed = The S3 prefix or managed paths changed since this preview. Preview the publish again.
+deletes-outside-managed-area = Refusing to publish: { $count } planned deletes fall outside the managed area (e.g. { $example }).
+site-prefix-overlap = S3 prefix { $root } overlaps the site published at { $other }: publishing would delete the other site's files. Choose a prefix outside its galleries/ and afterglow/ folders.
+workspace-read-only = { $path } is in a workspace opened read-only. Reopen it normally to make changes.
+prefix-leading-slash = S3 prefix "{ $prefix }" must not start with "/".
+prefix-illegal-character = S3 prefix "{ $prefix }" contains an illegal character ({ $character }).
+prefix-invalid-segment = S3 prefix "{ $prefix }" contains an empty, "." or ".." segment.
+arn-wrong-partition = { $resource ->
+    [bucket] Bucket
+   *[distribution] CloudFront distribution
+} ARN is in the { $partition } partition, but region { $region } is in { $expected }.
+cloudfront-unavailable-in-govcloud = CloudFront isn't available in AWS GovCloud (US). Clear the distribution ID.
+secret-store-write-failed = Can't write to the secret store: { $error }
+secret-store-mismatch = The secret store returned a different value than was saved.
+secret-store-read-failed = Saved a test entry but couldn't read it back: { $error }
+companion-devices-invalid = Invalid companion-devices.json: { $error }
+companion-request-rejected = { $status } { $reason }
+companion-no-workspace = No workspace is open in Afterglow Manager.
+companion-invalid-request = Invalid request: { $error }
+companion-device-name-invalid = deviceName must be 1 to 100 characters.
+companion-not-an-image = Not an image.
+companion-photo-not-found = Photo not found.
+companion-gallery-not-found = Gallery not found.
+companion-not-paired = Pair this device first.
+companion-delete-limit-exceeded = This publish deletes more files than delete protection allows. Confirm it on the desktop.
+companion-risky-deletes = This publish deletes removed galleries or unrecognized files. Confirm it on the desktop.
+companion-address-invalid = Companion address "{ $address }" must be one of this computer's IP addresses.
+companion-address-unavailable = Couldn't find this computer's network address. Choose an interface for companion access in settings.
+previous-target-overlap = S3 prefix { $root } overlaps the previous location { $previous }, which still holds the old site. Clean up the old location in Settings first, or choose a prefix outside it.
+archive-inside-managed-area = Archive prefix "{ $prefix }" is inside the published site's managed area; choose another prefix or bucket.
+managed-path-invalid = Managed path "{ $path }": { $error }
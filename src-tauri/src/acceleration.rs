@@ -0,0 +1,121 @@
+use crate::i18n::{error, ErrorCode};
+use crate::publish::s3_client;
+use crate::settings::{
+    extract_bucket_name, get_credentials_from_keychain, normalize_s3_prefix, partition_for_region, read_settings,
+};
+use aws_credential_types::Credentials;
+use aws_sdk_s3::config::Region;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::BucketAccelerateStatus;
+use serde::Serialize;
+use std::time::Instant;
+
+/// Size of the object uploaded by the speed comparison.
+const SPEED_TEST_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccelerationComparison {
+    /// Transfer Acceleration is enabled on the bucket.
+    pub bucket_enabled: bool,
+    pub bytes: u64,
+    pub standard_ms: u64,
+    /// `None` when the accelerated upload wasn't attempted or failed (see `error`).
+    pub accelerated_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// S3 client that uploads through the bucket's `s3-accelerate` endpoint.
+pub(crate) fn accelerated_s3_client(key_id: &str, secret: &str, region: &str) -> aws_sdk_s3::Client {
+    let config = aws_sdk_s3::Config::builder()
+        .credentials_provider(Credentials::new(key_id, secret, None, None, "afterglow-manager"))
+        .region(Region::new(region.to_string()))
+        .accelerate(true)
+        .behavior_version_latest()
+        .build();
+    aws_sdk_s3::Client::from_conf(config)
+}
+
+/// Whether a bucket can use Transfer Acceleration: it must be in the standard partition (not
+/// China or GovCloud), and its name DNS-compliant without dots.
+pub(crate) fn supports_acceleration(bucket: &str, region: &str) -> bool {
+    partition_for_region(region) == "aws"
+        && (3..=63).contains(&bucket.len())
+        && bucket.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !bucket.starts_with('-')
+        && !bucket.ends_with('-')
+}
+
+async fn timed_upload(client: &aws_sdk_s3::Client, bucket: &str, key: &str, body: &[u8]) -> Result<u64, String> {
+    let started = Instant::now();
+    client
+        .put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(ByteStream::from(body.to_vec()))
+        .send()
+        .await
+        .map_err(|e| format!("{}", e))?;
+    Ok(started.elapsed().as_millis() as u64)
+}
+
+/// Compare upload speed with and without Transfer Acceleration by uploading a 2 MiB test object
+/// to `{s3Root}afterglow/` through each endpoint, then deleting it.
+#[tauri::command]
+pub async fn compare_transfer_acceleration(app: tauri::AppHandle) -> Result<AccelerationComparison, String> {
+    let settings = read_settings(&app)?;
+    let bucket = extract_bucket_name(&settings.bucket);
+    if bucket.is_empty() {
+        return Err(error(ErrorCode::BucketNotConfigured, &[]));
+    }
+    let s3_root = normalize_s3_prefix(&settings.s3_prefix)?;
+    let (key_id, secret) = get_credentials_from_keychain()?;
+    let standard = s3_client(&key_id, &secret, &settings.region);
+
+    // China and GovCloud don't offer Transfer Acceleration, so there is nothing to read there
+    let bucket_enabled = partition_for_region(&settings.region) == "aws"
+        && standard
+            .get_bucket_accelerate_configuration()
+            .bucket(&bucket)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to read the bucket's acceleration setting: {}", e))?
+            .status()
+            == Some(&BucketAccelerateStatus::Enabled);
+
+    // Inside the managed area, so a leftover is removed by the next publish
+    let key = format!("{}afterglow/.acceleration-test-{}", s3_root, uuid::Uuid::new_v4());
+    let body = vec![0u8; SPEED_TEST_BYTES];
+    let standard_ms = timed_upload(&standard, &bucket, &key, &body).await?;
+
+    let (accelerated_ms, error) = if !supports_acceleration(&bucket, &settings.region) {
+        (None, Some("Transfer Acceleration isn't available for this region or bucket name.".to_string()))
+    } else if !bucket_enabled {
+        (None, Some("Transfer Acceleration is not enabled on this bucket.".to_string()))
+    } else {
+        let accelerated = accelerated_s3_client(&key_id, &secret, &settings.region);
+        match timed_upload(&accelerated, &bucket, &key, &body).await {
+            Ok(ms) => (Some(ms), None),
+            Err(e) => (None, Some(e)),
+        }
+    };
+
+    let _ = standard.delete_object().bucket(&bucket).key(&key).send().await;
+    Ok(AccelerationComparison { bucket_enabled, bytes: SPEED_TEST_BYTES as u64, standard_ms, accelerated_ms, error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports_acceleration() {
+        assert!(supports_acceleration("my-photos", "us-east-1"));
+        assert!(supports_acceleration("photos2024", "us-east-1"));
+        assert!(!supports_acceleration("photos.example.com", "us-east-1"));
+        assert!(!supports_acceleration("My-Photos", "us-east-1"));
+        assert!(!supports_acceleration("-photos", "us-east-1"));
+        assert!(!supports_acceleration("ab", "us-east-1"));
+        assert!(!supports_acceleration("my-photos", "cn-north-1"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::i18n::{error, ErrorCode};
+use crate::manifest::format_utc_timestamp;
+use crate::settings::AppSettings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Entries `get_audit_log` returns when no limit is given.
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+/// Serializes appends from concurrent commands so lines never interleave.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// One significant action, as a line of `{app_data}/activity-log.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEntry {
+    /// UTC, e.g. "2024-06-01T10:00:00Z".
+    pub at: String,
+    /// "publish", "deletePhoto", "cleanupOldPrefix", "saveCredentials", "deleteCredentials",
+    /// "saveMirrorCredentials", "deleteMirrorCredentials", "saveSettings", "autoPublishSkipped",
+    /// "pairCompanionDevice" or "revokeCompanionDevice".
+    pub action: String,
+    /// OS account the app ran as.
+    pub user: String,
+    pub details: String,
+}
+
+fn log_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
+    fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    Ok(data_dir.join("activity-log.jsonl"))
+}
+
+fn os_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Append one entry. The log is only ever appended to, never rewritten; a line left
+/// unterminated by an interrupted write is ended first so it can't swallow this entry.
+fn append_entry(path: &Path, entry: &ActivityEntry) -> Result<(), String> {
+    let _guard = LOG_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    line.push('\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open activity log: {}", e))?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    if len > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::Start(len - 1)).and_then(|_| file.read_exact(&mut last)).map_err(|e| e.to_string())?;
+        if last[0] != b'\n' {
+            line.insert(0, '\n');
+        }
+    }
+    file.write_all(line.as_bytes()).map_err(|e| format!("Failed to write activity log: {}", e))
+}
+
+/// The newest `limit` entries, newest first. Lines that don't parse (e.g. a write cut short)
+/// are skipped.
+fn read_entries(path: &Path, limit: usize) -> Result<Vec<ActivityEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read activity log: {}", e))?;
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
+
+/// Record an action in the activity log. Errors are logged: logging never fails the action.
+pub(crate) fn record_activity(app: &tauri::AppHandle, action: &str, details: String) {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let entry = ActivityEntry { at: format_utc_timestamp(secs), action: action.to_string(), user: os_user(), details };
+    if let Err(e) = log_path(app).and_then(|path| append_entry(&path, &entry)) {
+        eprintln!("[activity] Failed to record {}: {}", action, e);
+    }
+}
+
+/// Names of the settings that differ between `old` and `new`, for the `saveSettings` entry.
+pub(crate) fn changed_settings(old: &AppSettings, new: &AppSettings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(value))
+        .map(|(key, _)| key.clone())
+        .chain(old.keys().filter(|key| !new.contains_key(*key)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
+
+/// Recent publishes, deletes, credential changes and settings edits, newest first.
+#[tauri::command]
+pub async fn get_audit_log(app: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<ActivityEntry>, String> {
+    read_entries(&log_path(&app)?, limit.unwrap_or(DEFAULT_LOG_LIMIT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(action: &str) -> ActivityEntry {
+        ActivityEntry {
+            at: "2024-06-01T10:00:00Z".to_string(),
+            action: action.to_string(),
+            user: "sam".to_string(),
+            details: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_entries() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("activity-log.jsonl");
+        assert!(read_entries(&path, 10).unwrap().is_empty());
+
+        append_entry(&path, &entry("publish")).unwrap();
+        append_entry(&path, &entry("saveSettings")).unwrap();
+        // A torn line is skipped rather than failing the read, and doesn't swallow the next entry
+        fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"at\":").unwrap();
+        append_entry(&path, &entry("deletePhoto")).unwrap();
+
+        let entries = read_entries(&path, 10).unwrap();
+        let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["deletePhoto", "saveSettings", "publish"]);
+        assert_eq!(read_entries(&path, 1).unwrap()[0].action, "deletePhoto");
+    }
+
+    #[test]
+    fn test_changed_settings() {
+        let old = AppSettings { bucket: "photos".to_string(), ..AppSettings::default() };
+        let new = AppSettings { bucket: "photos-2".to_string(), site_url: "https://x.example/".to_string(), ..old.clone() };
+        assert_eq!(changed_settings(&old, &new), vec!["bucket", "siteUrl"]);
+        assert!(changed_settings(&old, &old).is_empty());
+    }
+}
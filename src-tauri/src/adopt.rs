@@ -0,0 +1,301 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::i18n::{error, ErrorCode};
+use crate::publish::{list_objects, s3_client};
+use crate::settings::{extract_bucket_name, get_credentials_from_keychain, normalize_s3_prefix, read_settings};
+use crate::stamps::STAMP_FIELD;
+use crate::thumbnails::parse_galleries_array;
+use crate::workspace::{str_field, write_json_atomic};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::Emitter;
+
+/// Folder the publish step writes WebP thumbnails to, inside each gallery.
+const PUBLISHED_THUMBS_DIR: &str = ".thumbs/";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdoptResult {
+    pub galleries: usize,
+    pub photos: usize,
+    pub downloaded_bytes: u64,
+    /// Referenced files missing from the bucket and other non-fatal problems.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AdoptProgress {
+    current: usize,
+    total: usize,
+    file: String,
+}
+
+/// A relative path from remote JSON that stays inside the workspace (no `..`, no root).
+fn is_safe_relative(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Undo publish-time changes to a `gallery-details.json`: drop the publish stamp and the
+/// injected `comments` config (a per-gallery `"comments": false` opt-out is kept), and point
+/// `thumbnail`s rewritten to `.thumbs/{stem}.webp` back at the original image.
+fn restore_gallery_details(mut details: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = details.as_object_mut() {
+        obj.remove(STAMP_FIELD);
+        if obj.get("comments").is_some_and(|c| c.is_object()) {
+            obj.remove("comments");
+        }
+    }
+    if let Some(photos) = details.get_mut("photos").and_then(|p| p.as_array_mut()) {
+        for photo in photos.iter_mut() {
+            let full = str_field(photo, "full");
+            let thumbnail = str_field(photo, "thumbnail");
+            if thumbnail.starts_with(PUBLISHED_THUMBS_DIR) && !full.is_empty() {
+                photo["thumbnail"] = serde_json::Value::String(full);
+            }
+        }
+    }
+    details
+}
+
+/// Undo publish-time changes to `galleries.json`: drop the publish stamp and map covers
+/// rewritten to `{slug}/.thumbs/{stem}.webp` back to `{slug}/{full}` of the matching photo.
+/// Galleries with unsafe slugs are dropped. `photo_files(slug)` lists a gallery's `full`
+/// filenames.
+fn restore_galleries_json(
+    mut index: serde_json::Value,
+    photo_files: impl Fn(&str) -> Vec<String>,
+) -> serde_json::Value {
+    if let Some(obj) = index.as_object_mut() {
+        obj.remove(STAMP_FIELD);
+    }
+    let galleries = if index.is_array() {
+        index.as_array_mut()
+    } else {
+        index.get_mut("galleries").and_then(|g| g.as_array_mut())
+    };
+    let Some(galleries) = galleries else {
+        return index;
+    };
+    galleries.retain(|g| is_safe_relative(&str_field(g, "slug")));
+    for gallery in galleries.iter_mut() {
+        let slug = str_field(gallery, "slug");
+        let cover = str_field(gallery, "cover");
+        let Some(thumb) = cover.strip_prefix(&format!("{}/{}", slug, PUBLISHED_THUMBS_DIR)) else {
+            continue;
+        };
+        let stem = thumb.strip_suffix(".webp").unwrap_or(thumb);
+        let original = photo_files(&slug).into_iter().find(|full| {
+            Path::new(full).file_stem().and_then(|s| s.to_str()) == Some(stem)
+        });
+        if let Some(full) = original {
+            gallery["cover"] = serde_json::Value::String(format!("{}/{}", slug, full));
+        }
+    }
+    index
+}
+
+async fn download(client: &aws_sdk_s3::Client, bucket: &str, key: &str) -> Result<Vec<u8>, String> {
+    let resp = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", key, e))?;
+    let bytes = resp
+        .body
+        .collect()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", key, e))?;
+    Ok(bytes.into_bytes().to_vec())
+}
+
+async fn download_json(client: &aws_sdk_s3::Client, bucket: &str, key: &str) -> Result<serde_json::Value, String> {
+    let bytes = download(client, bucket, key).await?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Failed to parse {}: {}", key, e))
+}
+
+/// Build a local workspace from a site published by hand (or on another machine): reads
+/// `{s3Root}galleries/` from the configured bucket, downloads `galleries.json`, every
+/// `gallery-details.json` and the images they reference into `folder_path`, undoing the
+/// publish-time thumbnail/cover rewrites. Downloaded images are byte-identical to the bucket,
+/// so the next publish only uploads what changed. `folder_path` must not already contain a
+/// workspace. Emits `adopt-progress`.
+#[tauri::command]
+pub async fn adopt_site(app: tauri::AppHandle, folder_path: String) -> Result<AdoptResult, String> {
+    let root = PathBuf::from(&folder_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    if root.join("galleries.json").exists() {
+        return Err("The folder already contains a galleries.json. Choose an empty folder.".to_string());
+    }
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let settings = read_settings(&app)?;
+    let bucket = extract_bucket_name(&settings.bucket);
+    if bucket.is_empty() {
+        return Err(error(ErrorCode::BucketNotConfigured, &[]));
+    }
+    let s3_root = normalize_s3_prefix(&settings.s3_prefix)?;
+    let galleries_prefix = format!("{}galleries/", s3_root);
+
+    let (key_id, secret) = get_credentials_from_keychain()?;
+    let client = s3_client(&key_id, &secret, &settings.region);
+
+    // Everything that exists under galleries/, so missing references become warnings
+    let existing: HashSet<String> = list_objects(&client, &bucket, &galleries_prefix)
+        .await?
+        .into_iter()
+        .map(|obj| obj.key)
+        .collect();
+
+    let index_key = format!("{}galleries.json", galleries_prefix);
+    if !existing.contains(&index_key) {
+        return Err(format!("No galleries.json found at s3://{}/{}.", bucket, index_key));
+    }
+    let index = download_json(&client, &bucket, &index_key).await?;
+
+    let mut warnings = Vec::new();
+    let mut details_by_slug: Vec<(String, serde_json::Value)> = Vec::new();
+    for gallery in parse_galleries_array(&index) {
+        let slug = str_field(&gallery, "slug");
+        if !is_safe_relative(&slug) {
+            warnings.push(format!("Skipped gallery with invalid slug \"{}\".", slug));
+            continue;
+        }
+        let key = format!("{}{}/gallery-details.json", galleries_prefix, slug);
+        if !existing.contains(&key) {
+            warnings.push(format!("{}: gallery-details.json not found; created an empty gallery.", slug));
+            details_by_slug.push((slug, serde_json::json!({ "photos": [] })));
+            continue;
+        }
+        let details = download_json(&client, &bucket, &key).await?;
+        details_by_slug.push((slug, restore_gallery_details(details)));
+    }
+
+    // Images referenced by each gallery (originals only; thumbnails are regenerated on publish)
+    let mut files: Vec<(String, String)> = Vec::new(); // (workspace-relative path, s3 key)
+    let mut photos = 0;
+    for (slug, details) in &details_by_slug {
+        let entries = details.get("photos").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        photos += entries.len();
+        for photo in &entries {
+            for field in ["full", "thumbnail"] {
+                let name = str_field(photo, field);
+                if name.is_empty() {
+                    continue;
+                }
+                if !is_safe_relative(&name) {
+                    warnings.push(format!("{}: skipped unsafe path \"{}\".", slug, name));
+                    continue;
+                }
+                let rel = format!("{}/{}", slug, name);
+                if !files.iter().any(|(r, _)| *r == rel) {
+                    files.push((rel.clone(), format!("{}{}", galleries_prefix, rel)));
+                }
+            }
+        }
+    }
+
+    let total = files.len();
+    let mut downloaded_bytes = 0u64;
+    for (i, (rel, key)) in files.iter().enumerate() {
+        let _ = app.emit("adopt-progress", AdoptProgress { current: i + 1, total, file: rel.clone() });
+        if !existing.contains(key) {
+            warnings.push(format!("{}: not found in the bucket.", rel));
+            continue;
+        }
+        let dest = root.join(rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let bytes = download(&client, &bucket, key).await?;
+        downloaded_bytes += bytes.len() as u64;
+        fs::write(&dest, &bytes).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+    }
+
+    // JSON last, so an interrupted adoption never leaves a workspace pointing at missing files
+    for (slug, details) in &details_by_slug {
+        write_json_atomic(&root.join(slug).join("gallery-details.json"), details)?;
+        emit_change(&app, WorkspaceChange::new("galleryDetails", slug, "created", ChangeOrigin::Import, "adopt_site"));
+    }
+    let index = restore_galleries_json(index, |slug| {
+        details_by_slug
+            .iter()
+            .find(|(s, _)| s == slug)
+            .and_then(|(_, d)| d.get("photos").and_then(|p| p.as_array()).cloned())
+            .unwrap_or_default()
+            .iter()
+            .map(|p| str_field(p, "full"))
+            .collect()
+    });
+    write_json_atomic(&root.join("galleries.json"), &index)?;
+    emit_change(&app, WorkspaceChange::new("galleryIndex", "", "created", ChangeOrigin::Import, "adopt_site"));
+
+    Ok(AdoptResult { galleries: details_by_slug.len(), photos, downloaded_bytes, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_is_safe_relative() {
+        assert!(is_safe_relative("a/01.jpg"));
+        assert!(!is_safe_relative("../etc/passwd"));
+        assert!(!is_safe_relative("/abs.jpg"));
+        assert!(!is_safe_relative(""));
+    }
+
+    #[test]
+    fn test_restore_gallery_details() {
+        let published = json!({
+            "publishStamp": {"hash": "h", "generation": 1},
+            "photos": [
+                {"thumbnail": ".thumbs/01.webp", "full": "01.jpg", "alt": "one"},
+                {"thumbnail": "02-small.jpg", "full": "02.jpg"}
+            ]
+        });
+        let restored = restore_gallery_details(published);
+        assert!(restored.get("publishStamp").is_none());
+        assert_eq!(restored["photos"][0], json!({"thumbnail": "01.jpg", "full": "01.jpg", "alt": "one"}));
+        assert_eq!(restored["photos"][1]["thumbnail"], "02-small.jpg");
+
+        // The comments config comes from settings at publish time; an opt-out is the gallery's own
+        let with_comments = json!({
+            "commentsThreadId": "summer",
+            "comments": {"provider": "giscus", "threadId": "summer", "repo": "me/site"},
+            "photos": []
+        });
+        let restored = restore_gallery_details(with_comments);
+        assert!(restored.get("comments").is_none());
+        assert_eq!(restored["commentsThreadId"], "summer");
+        let opted_out = restore_gallery_details(json!({"comments": false, "photos": []}));
+        assert_eq!(opted_out["comments"], json!(false));
+    }
+
+    #[test]
+    fn test_restore_galleries_json_covers() {
+        let published = json!({
+            "schemaVersion": 1,
+            "publishStamp": {"hash": "h", "generation": 3},
+            "galleries": [
+                {"slug": "a", "cover": "a/.thumbs/02.webp"},
+                {"slug": "b", "cover": "b/cover.jpg"},
+                {"slug": "c", "cover": "c/.thumbs/gone.webp"},
+                {"slug": "../x", "cover": ""}
+            ]
+        });
+        let restored = restore_galleries_json(published, |slug| match slug {
+            "a" => vec!["01.jpg".to_string(), "02.JPG".to_string()],
+            _ => Vec::new(),
+        });
+        assert!(restored.get("publishStamp").is_none());
+        assert_eq!(restored["galleries"][0]["cover"], "a/02.JPG");
+        assert_eq!(restored["galleries"][1]["cover"], "b/cover.jpg");
+        assert_eq!(restored["galleries"][2]["cover"], "c/.thumbs/gone.webp");
+        assert_eq!(restored["galleries"].as_array().unwrap().len(), 3);
+    }
+}
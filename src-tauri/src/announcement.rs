@@ -0,0 +1,257 @@
+use crate::publish::WEBSITE_STYLES_CSS;
+use crate::settings::{normalize_site_url, read_settings};
+use crate::workspace::{find_gallery, read_galleries, read_gallery_details, str_field};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+    /// Self-contained HTML snippet with inlined styles, safe to paste into newsletter tools.
+    pub html: String,
+    pub gallery_url: String,
+    pub cover_url: String,
+}
+
+/// Escape text for inclusion in HTML element content or double-quoted attributes.
+pub(crate) fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Percent-encode a relative URL path, leaving `/` separators and unreserved characters intact.
+pub(crate) fn encode_url_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Build the published URL of a gallery page. The website routes galleries via the
+/// hash fragment, e.g. "https://photos.example.com/#gallery=sunset".
+pub(crate) fn gallery_page_url(site_url: &str, slug: &str) -> String {
+    format!("{}#gallery={}", site_url, encode_url_path(slug))
+}
+
+/// Value of a `--{name}` custom property declared in the `:root` block of a stylesheet.
+fn design_token(css: &str, name: &str) -> Option<String> {
+    let root = css.split_once(":root")?.1;
+    let block = root.split_once('{')?.1.split_once('}')?.0;
+    block.split(';').find_map(|declaration| {
+        let (property, value) = declaration.split_once(':')?;
+        (property.trim().strip_prefix("--")? == name).then(|| value.trim().to_string())
+    })
+}
+
+/// The website's design tokens the announcement uses, read from the bundled `styles.css`.
+/// Email clients don't resolve `var(...)`, so the values are inlined.
+struct Palette {
+    bg: String,
+    text: String,
+    text_muted: String,
+    accent: String,
+    on_accent: String,
+    radius: String,
+}
+
+impl Palette {
+    fn from_site() -> Self {
+        let css = String::from_utf8_lossy(WEBSITE_STYLES_CSS);
+        let token = |name: &str| design_token(&css, name).unwrap_or_default();
+        Palette {
+            bg: token("bg"),
+            text: token("txt"),
+            text_muted: token("txt-2"),
+            accent: token("volt"),
+            on_accent: token("on-accent"),
+            radius: token("radius"),
+        }
+    }
+}
+
+/// Render the announcement HTML. Layout is table-based with inline styles because most
+/// email clients strip `<style>` blocks and ignore flexbox/grid.
+fn render_announcement_html(
+    name: &str,
+    date: &str,
+    description: &str,
+    gallery_url: &str,
+    cover_url: &str,
+) -> String {
+    let name = escape_html(name);
+    let gallery_url = escape_html(gallery_url);
+    let cover_url = escape_html(cover_url);
+    let palette = Palette::from_site();
+
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<table role=\"presentation\" width=\"100%\" cellpadding=\"0\" cellspacing=\"0\" border=\"0\" \
+         style=\"max-width:600px;margin:0 auto;background-color:{};border-collapse:collapse;\">\n",
+        palette.bg
+    ));
+    if !cover_url.is_empty() {
+        html.push_str(&format!(
+            "  <tr>\n    <td style=\"padding:0;\">\n      <a href=\"{url}\" style=\"display:block;\">\
+             <img src=\"{cover}\" alt=\"{name}\" width=\"600\" \
+             style=\"display:block;width:100%;max-width:600px;height:auto;border:0;\"></a>\n    </td>\n  </tr>\n",
+            url = gallery_url,
+            cover = cover_url,
+            name = name,
+        ));
+    }
+    html.push_str(&format!(
+        "  <tr>\n    <td style=\"padding:24px 28px 32px;font-family:Helvetica,Arial,sans-serif;color:{};\">\n",
+        palette.text
+    ));
+    html.push_str(&format!(
+        "      <h2 style=\"margin:0 0 6px;font-size:26px;line-height:1.2;font-weight:700;color:{};\">{}</h2>\n",
+        palette.text, name
+    ));
+    if !date.is_empty() {
+        html.push_str(&format!(
+            "      <p style=\"margin:0 0 16px;font-size:13px;letter-spacing:1px;text-transform:uppercase;color:{};\">{}</p>\n",
+            palette.accent,
+            escape_html(date)
+        ));
+    }
+    for paragraph in description.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        html.push_str(&format!(
+            "      <p style=\"margin:0 0 16px;font-size:16px;line-height:1.5;color:{};\">{}</p>\n",
+            palette.text_muted,
+            escape_html(paragraph).replace('\n', "<br>")
+        ));
+    }
+    html.push_str(&format!(
+        "      <a href=\"{}\" style=\"display:inline-block;margin-top:8px;padding:12px 22px;background-color:{};\
+         color:{};font-size:14px;font-weight:700;text-decoration:none;border-radius:{};\">View the gallery</a>\n",
+        gallery_url, palette.accent, palette.on_accent, palette.radius
+    ));
+    html.push_str("    </td>\n  </tr>\n</table>\n");
+    html
+}
+
+/// Generate an email-ready HTML announcement for a gallery: cover image, title, date,
+/// description and a link to the published gallery page.
+///
+/// The cover links to the original published image (not the WebP thumbnail), since WebP
+/// is still unsupported by several desktop email clients.
+#[tauri::command]
+pub async fn generate_announcement(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+) -> Result<Announcement, String> {
+    let settings = read_settings(&app)?;
+    let site_url = normalize_site_url(&settings.site_url)?;
+    let root = PathBuf::from(&workspace_path);
+
+    let galleries = read_galleries(&root)?;
+    let gallery = find_gallery(&galleries, &slug)
+        .ok_or_else(|| format!("Gallery '{}' not found in galleries.json", slug))?;
+    let details = read_gallery_details(&root, &slug)?;
+
+    let name = str_field(&gallery, "name");
+    let date = str_field(&gallery, "date");
+    let description = details
+        .as_ref()
+        .map(|d| str_field(d, "description"))
+        .unwrap_or_default();
+    let cover = str_field(&gallery, "cover");
+
+    let gallery_url = gallery_page_url(&site_url, &slug);
+    let cover_url = if cover.is_empty() {
+        String::new()
+    } else {
+        format!("{}galleries/{}", site_url, encode_url_path(&cover))
+    };
+
+    let html = render_announcement_html(&name, &date, &description, &gallery_url, &cover_url);
+    Ok(Announcement { html, gallery_url, cover_url })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("Tom & Jerry <b>\"hi\"</b>"), "Tom &amp; Jerry &lt;b&gt;&quot;hi&quot;&lt;/b&gt;");
+        assert_eq!(escape_html("it's"), "it&#39;s");
+    }
+
+    #[test]
+    fn test_encode_url_path() {
+        assert_eq!(encode_url_path("sunset/01.jpg"), "sunset/01.jpg");
+        assert_eq!(encode_url_path("my gallery/photo 1.jpg"), "my%20gallery/photo%201.jpg");
+        assert_eq!(encode_url_path("café"), "caf%C3%A9");
+    }
+
+    #[test]
+    fn test_gallery_page_url() {
+        assert_eq!(
+            gallery_page_url("https://photos.example.com/", "sunset"),
+            "https://photos.example.com/#gallery=sunset"
+        );
+    }
+
+    #[test]
+    fn test_render_announcement_html_inlines_content() {
+        let html = render_announcement_html(
+            "Sunset & Sea",
+            "28/02/2026",
+            "First paragraph.\n\nSecond <para>.",
+            "https://example.com/#gallery=sunset",
+            "https://example.com/galleries/sunset/01.jpg",
+        );
+        assert!(html.contains("Sunset &amp; Sea"));
+        assert!(html.contains("28/02/2026"));
+        assert!(html.contains("First paragraph."));
+        assert!(html.contains("Second &lt;para&gt;."));
+        assert!(html.contains("src=\"https://example.com/galleries/sunset/01.jpg\""));
+        assert!(html.contains("href=\"https://example.com/#gallery=sunset\""));
+        assert!(!html.contains("<style"), "styles must be inlined");
+    }
+
+    #[test]
+    fn test_design_token() {
+        let css = ":root {\n  --volt: #d6ff3e;\n  --volt-deep: color-mix(in srgb, var(--volt) 82%, #000);\n}\nbody { --volt: red; }";
+        assert_eq!(design_token(css, "volt").as_deref(), Some("#d6ff3e"));
+        assert_eq!(design_token(css, "volt-deep").as_deref(), Some("color-mix(in srgb, var(--volt) 82%, #000)"));
+        assert_eq!(design_token(css, "bg"), None);
+    }
+
+    #[test]
+    fn test_render_announcement_html_uses_site_tokens() {
+        let palette = Palette::from_site();
+        for value in [&palette.bg, &palette.text, &palette.text_muted, &palette.accent, &palette.on_accent, &palette.radius] {
+            assert!(!value.is_empty() && !value.contains("var("), "unresolved token: {:?}", value);
+        }
+        let html = render_announcement_html("A", "01/03/2026", "", "https://example.com/#gallery=a", "");
+        assert!(html.contains(&format!("background-color:{};", palette.accent)));
+        assert!(html.contains(&format!("color:{};", palette.on_accent)));
+        assert!(!html.contains("#c9a84c"));
+    }
+
+    #[test]
+    fn test_render_announcement_html_without_cover() {
+        let html = render_announcement_html("A", "", "", "https://example.com/#gallery=a", "");
+        assert!(!html.contains("<img"));
+        assert!(html.contains("View the gallery"));
+    }
+}
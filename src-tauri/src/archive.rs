@@ -0,0 +1,486 @@
+use crate::i18n::{error, ErrorCode};
+use crate::encryption::{image_originals, stored_original};
+use crate::manifest::format_utc_timestamp;
+use crate::managed_area::ManagedArea;
+use crate::publish::{content_type_for_extension, s3_client};
+use crate::settings::{extract_bucket_name, get_credentials_from_keychain, normalize_s3_prefix, read_settings, AppSettings};
+use crate::workspace::write_json_atomic;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{GlacierJobParameters, RestoreRequest, StorageClass, Tier};
+use image::codecs::jpeg::JpegEncoder;
+use image::{ImageDecoder, ImageEncoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::Emitter;
+
+/// Archive prefix used when the `archivePrefix` setting is empty.
+const DEFAULT_ARCHIVE_PREFIX: &str = "afterglow-archive/";
+/// Longest edge of the web-size copy kept locally after archiving.
+const WEB_SIZE_MAX_EDGE: u32 = 2560;
+const WEB_SIZE_QUALITY: u8 = 90;
+
+/// An original uploaded to Glacier Deep Archive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ArchivedFile {
+    key: String,
+    size: u64,
+    archived_at: String,
+    /// The local file was replaced by a web-size copy.
+    web_size: bool,
+}
+
+/// `{workspace}/.data/archive/{slug}.json`: where a gallery's originals were archived.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveRecord {
+    bucket: String,
+    files: BTreeMap<String, ArchivedFile>,
+}
+
+/// Payload of the `archive-status` event, emitted per file while archiving, restoring or
+/// retrieving, and by `get_archive_status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStatusEvent {
+    pub slug: String,
+    pub filename: String,
+    pub current: usize,
+    pub total: usize,
+    /// "archiving", "archived", "restoring", "restored", "retrieved" or "failed".
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveResult {
+    pub archived: usize,
+    /// Already archived by an earlier run; never uploaded again, so a web-size copy can't
+    /// replace the archived original.
+    pub skipped: usize,
+    /// Local files replaced by a web-size copy.
+    pub reduced: usize,
+    pub bytes: u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedFileStatus {
+    pub filename: String,
+    pub key: String,
+    /// "archived" (in Deep Archive), "restoring", or "restored" (a temporary copy can be retrieved).
+    pub status: String,
+    /// When the restored copy expires, as reported by S3.
+    pub restore_expiry: Option<String>,
+    pub web_size: bool,
+}
+
+fn record_path(root: &Path, slug: &str) -> PathBuf {
+    root.join(".data").join("archive").join(format!("{}.json", slug))
+}
+
+fn load_record(root: &Path, slug: &str) -> Result<ArchiveRecord, String> {
+    let path = record_path(root, slug);
+    if !path.exists() {
+        return Ok(ArchiveRecord::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn save_record(root: &Path, slug: &str, record: &ArchiveRecord) -> Result<(), String> {
+    let value = serde_json::to_value(record).map_err(|e| e.to_string())?;
+    write_json_atomic(&record_path(root, slug), &value)
+}
+
+/// Archive bucket and prefix from settings: the site bucket and `afterglow-archive/` unless
+/// configured otherwise.
+fn archive_location(settings: &AppSettings) -> Result<(String, String), String> {
+    let bucket = match extract_bucket_name(&settings.archive_bucket) {
+        b if b.is_empty() => extract_bucket_name(&settings.bucket),
+        b => b,
+    };
+    if bucket.is_empty() {
+        return Err(error(ErrorCode::ArchiveBucketNotConfigured, &[]));
+    }
+    let prefix = match settings.archive_prefix.trim() {
+        "" => DEFAULT_ARCHIVE_PREFIX.to_string(),
+        p => normalize_s3_prefix(p)?,
+    };
+    Ok((bucket, prefix))
+}
+
+/// Check the archive settings before saving: in the site bucket, archived originals must be
+/// outside the managed area, or publishing would delete them.
+pub(crate) fn validate_archive_location(settings: &AppSettings) -> Result<(), String> {
+    if settings.archive_bucket.trim().is_empty() && settings.archive_prefix.trim().is_empty() {
+        return Ok(());
+    }
+    let (bucket, prefix) = archive_location(settings)?;
+    if bucket == extract_bucket_name(&settings.bucket) {
+        let s3_root = normalize_s3_prefix(&settings.s3_prefix)?;
+        let probe = format!("{}gallery/photo.jpg", prefix);
+        if prefix.is_empty() || ManagedArea::from_settings(settings)?.contains(&s3_root, &probe) {
+            return Err(error(ErrorCode::ArchiveInsideManagedArea, &[("prefix", &prefix)]));
+        }
+    }
+    Ok(())
+}
+
+fn archive_key(prefix: &str, slug: &str, filename: &str) -> String {
+    format!("{}{}/{}", prefix, slug, filename)
+}
+
+/// Status from a HeadObject `x-amz-restore` header: `ongoing-request="true"` while restoring,
+/// `ongoing-request="false", expiry-date="…"` once restored.
+fn parse_restore_header(restore: Option<&str>) -> (&'static str, Option<String>) {
+    let Some(restore) = restore else {
+        return ("archived", None);
+    };
+    if restore.contains("ongoing-request=\"true\"") {
+        return ("restoring", None);
+    }
+    let expiry = restore
+        .split_once("expiry-date=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(date, _)| date.to_string());
+    ("restored", expiry)
+}
+
+fn parse_tier(tier: &str) -> Result<Tier, String> {
+    match tier {
+        "Standard" => Ok(Tier::Standard),
+        "Bulk" => Ok(Tier::Bulk),
+        other => Err(error(ErrorCode::UnknownRestoreTier, &[("tier", &other)])),
+    }
+}
+
+/// Replace a JPEG with a copy whose longest edge is at most `WEB_SIZE_MAX_EDGE`, keeping its
+/// EXIF (capture time, GPS, orientation). Returns false for other formats or images already
+/// small enough, which are left untouched.
+fn reduce_to_web_size(path: &Path) -> Result<bool, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    if ext != "jpg" && ext != "jpeg" {
+        return Ok(false);
+    }
+    let mut decoder = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    let exif = decoder.exif_metadata().ok().flatten();
+    let img = image::DynamicImage::from_decoder(decoder).map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    if img.width() <= WEB_SIZE_MAX_EDGE && img.height() <= WEB_SIZE_MAX_EDGE {
+        return Ok(false);
+    }
+    let resized = img.resize(WEB_SIZE_MAX_EDGE, WEB_SIZE_MAX_EDGE, image::imageops::FilterType::Lanczos3).to_rgb8();
+
+    let tmp = path.with_extension(format!("{}.tmp", ext));
+    let file = fs::File::create(&tmp).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    let mut encoder = JpegEncoder::new_with_quality(BufWriter::new(file), WEB_SIZE_QUALITY);
+    if let Some(exif) = exif {
+        encoder.set_exif_metadata(exif).map_err(|e| e.to_string())?;
+    }
+    encoder
+        .write_image(resized.as_raw(), resized.width(), resized.height(), image::ExtendedColorType::Rgb8)
+        .map_err(|e| format!("Failed to encode {}: {}", path.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+fn now_utc() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_utc_timestamp(secs)
+}
+
+fn emit_status(app: &tauri::AppHandle, slug: &str, filename: &str, progress: (usize, usize), status: &str, error: Option<String>) {
+    let _ = app.emit(
+        "archive-status",
+        ArchiveStatusEvent {
+            slug: slug.to_string(),
+            filename: filename.to_string(),
+            current: progress.0,
+            total: progress.1,
+            status: status.to_string(),
+            error,
+        },
+    );
+}
+
+/// Client for the archive bucket; it is assumed to be in the site's region.
+fn archive_client(settings: &AppSettings) -> Result<aws_sdk_s3::Client, String> {
+    let (key_id, secret) = get_credentials_from_keychain()?;
+    Ok(s3_client(&key_id, &secret, &settings.region))
+}
+
+/// Upload a gallery folder's images to Glacier Deep Archive at `{archivePrefix}{slug}/`.
+/// Files archived by an earlier run are skipped. With `webSize`, each archived JPEG larger
+/// than 2560 px is then replaced locally by a web-size copy.
+#[tauri::command]
+pub async fn archive_gallery(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+    web_size: bool,
+) -> Result<ArchiveResult, String> {
+    crate::read_only::ensure_writable(&app, &PathBuf::from(&workspace_path))?;
+    let settings = read_settings(&app)?;
+    validate_archive_location(&settings)?;
+    let (bucket, prefix) = archive_location(&settings)?;
+    let client = archive_client(&settings)?;
+    let root = PathBuf::from(&workspace_path);
+    let dir = root.join(&slug);
+    if slug.starts_with('.') || slug.contains('/') || !dir.is_dir() {
+        return Err(error(ErrorCode::GalleryFolderNotFound, &[("slug", &slug)]));
+    }
+
+    let mut record = load_record(&root, &slug)?;
+    if !record.files.is_empty() && record.bucket != bucket {
+        return Err(error(ErrorCode::GalleryAlreadyArchived, &[("bucket", &record.bucket)]));
+    }
+    record.bucket = bucket.clone();
+
+    let names = image_originals(&dir)?;
+
+    let total = names.len();
+    let mut result = ArchiveResult { archived: 0, skipped: 0, reduced: 0, bytes: 0, errors: Vec::new() };
+    for (i, name) in names.iter().enumerate() {
+        if record.files.contains_key(name) {
+            result.skipped += 1;
+            continue;
+        }
+        emit_status(&app, &slug, name, (i + 1, total), "archiving", None);
+        let path = dir.join(name);
+        // Originals of encrypted galleries are archived as stored, encrypted
+        let stored = stored_original(&path);
+        let key = archive_key(&prefix, &slug, name);
+        let size = fs::metadata(&stored).map(|m| m.len()).unwrap_or(0);
+        let body = ByteStream::from_path(&stored).await.map_err(|e| format!("Failed to read {}: {}", stored.display(), e))?;
+        let upload = client
+            .put_object()
+            .bucket(&bucket)
+            .key(&key)
+            .content_type(content_type_for_extension(&stored))
+            .storage_class(StorageClass::DeepArchive)
+            .body(body)
+            .send()
+            .await;
+        if let Err(e) = upload {
+            let message = format!("{}: {}", name, e);
+            emit_status(&app, &slug, name, (i + 1, total), "failed", Some(message.clone()));
+            result.errors.push(message);
+            continue;
+        }
+        result.archived += 1;
+        result.bytes += size;
+
+        // Only reduce once the original is safely archived and recorded
+        let mut archived = ArchivedFile { key, size, archived_at: now_utc(), web_size: false };
+        record.files.insert(name.clone(), archived.clone());
+        save_record(&root, &slug, &record)?;
+        // Encrypted originals are kept as they are: reducing one would write it back decrypted
+        if web_size && stored == path {
+            let reduce_path = path.clone();
+            match tokio::task::spawn_blocking(move || reduce_to_web_size(&reduce_path))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            {
+                Ok(true) => {
+                    result.reduced += 1;
+                    archived.web_size = true;
+                    record.files.insert(name.clone(), archived);
+                    save_record(&root, &slug, &record)?;
+                }
+                Ok(false) => {}
+                Err(e) => result.errors.push(e),
+            }
+        }
+        emit_status(&app, &slug, name, (i + 1, total), "archived", None);
+    }
+    Ok(result)
+}
+
+/// Start restore jobs for a gallery's archived originals. `tier` is "Standard" (within 12
+/// hours) or "Bulk" (within 48 hours); restored copies stay available for `days`. Files whose
+/// restore is already in progress count as started. Returns the number of files requested.
+#[tauri::command]
+pub async fn restore_archived_gallery(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+    tier: String,
+    days: u32,
+) -> Result<usize, String> {
+    let settings = read_settings(&app)?;
+    let client = archive_client(&settings)?;
+    let record = load_record(Path::new(&workspace_path), &slug)?;
+    let tier = parse_tier(&tier)?;
+    let days = days.clamp(1, 30) as i32;
+
+    let total = record.files.len();
+    for (i, (name, file)) in record.files.iter().enumerate() {
+        let request = RestoreRequest::builder()
+            .days(days)
+            .glacier_job_parameters(
+                GlacierJobParameters::builder().tier(tier.clone()).build().map_err(|e| e.to_string())?,
+            )
+            .build();
+        let result = client.restore_object().bucket(&record.bucket).key(&file.key).restore_request(request).send().await;
+        match result {
+            Ok(_) => emit_status(&app, &slug, name, (i + 1, total), "restoring", None),
+            Err(e) if e.code() == Some("RestoreAlreadyInProgress") => {
+                emit_status(&app, &slug, name, (i + 1, total), "restoring", None)
+            }
+            Err(e) => {
+                emit_status(&app, &slug, name, (i + 1, total), "failed", Some(e.to_string()));
+                return Err(error(ErrorCode::RestoreStartFailed, &[("name", &name), ("error", &e)]));
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Restore status of each archived original in a gallery, also emitted as `archive-status`
+/// events so the frontend can poll it while restores run.
+#[tauri::command]
+pub async fn get_archive_status(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+) -> Result<Vec<ArchivedFileStatus>, String> {
+    let settings = read_settings(&app)?;
+    let client = archive_client(&settings)?;
+    let record = load_record(Path::new(&workspace_path), &slug)?;
+
+    let total = record.files.len();
+    let mut statuses = Vec::new();
+    for (i, (name, file)) in record.files.iter().enumerate() {
+        let head = client
+            .head_object()
+            .bucket(&record.bucket)
+            .key(&file.key)
+            .send()
+            .await
+            .map_err(|e| error(ErrorCode::ArchiveStatusFailed, &[("name", &name), ("error", &e)]))?;
+        let (status, restore_expiry) = parse_restore_header(head.restore());
+        emit_status(&app, &slug, name, (i + 1, total), status, None);
+        statuses.push(ArchivedFileStatus {
+            filename: name.clone(),
+            key: file.key.clone(),
+            status: status.to_string(),
+            restore_expiry,
+            web_size: file.web_size,
+        });
+    }
+    Ok(statuses)
+}
+
+/// Download restored originals back into the gallery folder, replacing their web-size copies.
+/// Files not restored yet are skipped; returns the number retrieved.
+#[tauri::command]
+pub async fn retrieve_restored_originals(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+) -> Result<usize, String> {
+    let settings = read_settings(&app)?;
+    let client = archive_client(&settings)?;
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let mut record = load_record(&root, &slug)?;
+
+    let names: Vec<String> = record.files.iter().filter(|(_, f)| f.web_size).map(|(n, _)| n.clone()).collect();
+    let total = names.len();
+    let mut retrieved = 0;
+    for (i, name) in names.iter().enumerate() {
+        let key = record.files[name].key.clone();
+        let head = client
+            .head_object()
+            .bucket(&record.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| error(ErrorCode::ArchiveStatusFailed, &[("name", &name), ("error", &e)]))?;
+        if parse_restore_header(head.restore()).0 != "restored" {
+            continue;
+        }
+        let resp = client
+            .get_object()
+            .bucket(&record.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| error(ErrorCode::DownloadFailed, &[("key", &key), ("error", &e)]))?;
+        let bytes = resp.body.collect().await.map_err(|e| error(ErrorCode::DownloadFailed, &[("key", &key), ("error", &e)]))?;
+        let path = root.join(&slug).join(name);
+        let tmp = path.with_extension("restore.tmp");
+        fs::write(&tmp, bytes.into_bytes()).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+
+        if let Some(file) = record.files.get_mut(name) {
+            file.web_size = false;
+        }
+        save_record(&root, &slug, &record)?;
+        retrieved += 1;
+        emit_status(&app, &slug, name, (i + 1, total), "retrieved", None);
+    }
+    Ok(retrieved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_restore_header() {
+        assert_eq!(parse_restore_header(None), ("archived", None));
+        assert_eq!(parse_restore_header(Some("ongoing-request=\"true\"")), ("restoring", None));
+        assert_eq!(
+            parse_restore_header(Some("ongoing-request=\"false\", expiry-date=\"Fri, 21 Dec 2012 00:00:00 GMT\"")),
+            ("restored", Some("Fri, 21 Dec 2012 00:00:00 GMT".to_string()))
+        );
+        assert!(parse_tier("Expedited").is_err());
+    }
+
+    #[test]
+    fn test_validate_archive_location() {
+        let site = AppSettings { bucket: "photos".to_string(), ..AppSettings::default() };
+        assert!(validate_archive_location(&site).is_ok());
+        assert_eq!(archive_location(&site).unwrap(), ("photos".to_string(), DEFAULT_ARCHIVE_PREFIX.to_string()));
+
+        let inside = AppSettings { archive_prefix: "galleries/originals/".to_string(), ..site.clone() };
+        assert!(validate_archive_location(&inside).is_err());
+        let elsewhere = AppSettings { archive_bucket: "cold".to_string(), ..inside };
+        assert!(validate_archive_location(&elsewhere).is_ok());
+        assert_eq!(archive_key("afterglow-archive/", "a", "01.jpg"), "afterglow-archive/a/01.jpg");
+    }
+
+    #[test]
+    fn test_reduce_to_web_size() {
+        let tmp = TempDir::new().unwrap();
+        let large = tmp.path().join("large.jpg");
+        image::RgbImage::new(3000, 2000).save(&large).unwrap();
+        assert!(reduce_to_web_size(&large).unwrap());
+        let reduced = image::open(&large).unwrap();
+        assert_eq!((reduced.width(), reduced.height()), (2560, 1707));
+
+        let small = tmp.path().join("small.jpg");
+        image::RgbImage::new(100, 50).save(&small).unwrap();
+        assert!(!reduce_to_web_size(&small).unwrap());
+        let png = tmp.path().join("large.png");
+        image::RgbImage::new(3000, 10).save(&png).unwrap();
+        assert!(!reduce_to_web_size(&png).unwrap());
+    }
+}
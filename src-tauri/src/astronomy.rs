@@ -0,0 +1,357 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::encryption::readable_original;
+use crate::journal::JournalState;
+use crate::metadata::read_exif;
+use crate::workspace::{photos_mut, read_gallery_details, str_field, update_gallery_details};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::path::PathBuf;
+
+/// Zenith angles (degrees) for the events we compute. Sunrise/sunset includes refraction
+/// and the solar disc radius; golden hour ends/starts when the sun is 6° above the horizon.
+const ZENITH_SUNRISE: f64 = 90.833;
+const ZENITH_GOLDEN_HOUR: f64 = 84.0;
+/// Reference new moon (2000-01-06 18:14 UTC) as a Julian day.
+const NEW_MOON_REFERENCE_JD: f64 = 2451550.1;
+const SYNODIC_MONTH_DAYS: f64 = 29.530588853;
+
+const MOON_PHASES: [&str; 8] = [
+    "New Moon",
+    "Waxing Crescent",
+    "First Quarter",
+    "Waxing Gibbous",
+    "Full Moon",
+    "Waning Gibbous",
+    "Last Quarter",
+    "Waning Crescent",
+];
+
+/// Sun and moon conditions at a photo's capture time and place, stored on the photo as
+/// `astronomy` in `gallery-details.json`. Times are local ("HH:MM").
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Astronomy {
+    /// `None` during polar night / midnight sun.
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+    pub golden_hour_morning_end: Option<String>,
+    pub golden_hour_evening_start: Option<String>,
+    /// Sun elevation above the horizon at capture time, in degrees.
+    pub sun_elevation: f64,
+    /// "night", "blue-hour", "golden-hour" or "day".
+    pub light_condition: String,
+    pub moon_phase: String,
+    /// Illuminated fraction of the moon (0–1).
+    pub moon_illumination: f64,
+    /// True when the camera recorded no UTC offset and it was estimated from longitude.
+    pub utc_offset_estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AstronomyEnrichResult {
+    pub enriched: usize,
+    /// Photos without an EXIF capture time or GPS position.
+    pub skipped: Vec<String>,
+    pub errors: Vec<AstronomyError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AstronomyError {
+    pub filename: String,
+    pub error: String,
+}
+
+fn sin_deg(d: f64) -> f64 {
+    (d * PI / 180.0).sin()
+}
+
+fn cos_deg(d: f64) -> f64 {
+    (d * PI / 180.0).cos()
+}
+
+fn round_to(v: f64, places: i32) -> f64 {
+    let f = 10f64.powi(places);
+    (v * f).round() / f
+}
+
+/// Julian day number of a Gregorian calendar date (at noon UTC).
+fn julian_day_number(year: i32, month: u32, day: u32) -> i64 {
+    let a = (14 - month as i64) / 12;
+    let y = year as i64 + 4800 - a;
+    let m = month as i64 + 12 * a - 3;
+    day as i64 + (153 * m + 2) / 5 + 365 * y + y / 4 - y / 100 + y / 400 - 32045
+}
+
+/// Julian day of a UTC instant.
+pub fn julian_day(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> f64 {
+    julian_day_number(year, month, day) as f64
+        + (hour as f64 - 12.0) / 24.0
+        + minute as f64 / 1440.0
+        + second as f64 / 86400.0
+}
+
+fn day_of_year(year: i32, month: u32, day: u32) -> f64 {
+    (julian_day_number(year, month, day) - julian_day_number(year, 1, 1) + 1) as f64
+}
+
+/// Time (UTC hours, 0–24) at which the sun crosses `zenith` on the given day, using the
+/// Almanac for Computers algorithm. `None` when the sun never reaches that zenith that day.
+fn sun_event_utc(doy: f64, lat: f64, lon: f64, zenith: f64, rising: bool) -> Option<f64> {
+    let lng_hour = lon / 15.0;
+    let t = doy + ((if rising { 6.0 } else { 18.0 }) - lng_hour) / 24.0;
+    let m = 0.9856 * t - 3.289;
+    let l = (m + 1.916 * sin_deg(m) + 0.020 * sin_deg(2.0 * m) + 282.634).rem_euclid(360.0);
+
+    let mut ra = (0.91764 * (l * PI / 180.0).tan()).atan() * 180.0 / PI;
+    ra = ra.rem_euclid(360.0);
+    // Right ascension must be in the same quadrant as the true longitude.
+    ra += (l / 90.0).floor() * 90.0 - (ra / 90.0).floor() * 90.0;
+    ra /= 15.0;
+
+    let sin_dec = 0.39782 * sin_deg(l);
+    let cos_dec = sin_dec.asin().cos();
+    let cos_h = (cos_deg(zenith) - sin_dec * sin_deg(lat)) / (cos_dec * cos_deg(lat));
+    if !(-1.0..=1.0).contains(&cos_h) {
+        return None;
+    }
+    let h_deg = cos_h.acos() * 180.0 / PI;
+    let h = if rising { 360.0 - h_deg } else { h_deg } / 15.0;
+    let local_mean = h + ra - 0.06571 * t - 6.622;
+    Some((local_mean - lng_hour).rem_euclid(24.0))
+}
+
+/// Sun elevation (degrees) at Julian day `jd` (UTC) for the given position.
+pub fn sun_elevation(jd: f64, lat: f64, lon: f64) -> f64 {
+    let n = jd - 2451545.0;
+    let mean_long = (280.460 + 0.9856474 * n).rem_euclid(360.0);
+    let mean_anomaly = (357.528 + 0.9856003 * n).rem_euclid(360.0);
+    let ecliptic_long =
+        mean_long + 1.915 * sin_deg(mean_anomaly) + 0.020 * sin_deg(2.0 * mean_anomaly);
+    let obliquity = 23.439 - 0.0000004 * n;
+
+    let ra = (cos_deg(obliquity) * sin_deg(ecliptic_long)).atan2(cos_deg(ecliptic_long)) * 180.0 / PI;
+    let dec = (sin_deg(obliquity) * sin_deg(ecliptic_long)).asin() * 180.0 / PI;
+    let gmst_hours = (18.697374558 + 24.06570982441908 * n).rem_euclid(24.0);
+    let hour_angle = gmst_hours * 15.0 + lon - ra;
+
+    let sin_alt = sin_deg(lat) * sin_deg(dec) + cos_deg(lat) * cos_deg(dec) * cos_deg(hour_angle);
+    sin_alt.clamp(-1.0, 1.0).asin() * 180.0 / PI
+}
+
+/// Moon phase name and illuminated fraction at Julian day `jd`.
+pub fn moon_phase(jd: f64) -> (&'static str, f64) {
+    let age = (jd - NEW_MOON_REFERENCE_JD).rem_euclid(SYNODIC_MONTH_DAYS);
+    let fraction = age / SYNODIC_MONTH_DAYS;
+    let illumination = (1.0 - (2.0 * PI * fraction).cos()) / 2.0;
+    let index = ((fraction * 8.0).round() as usize) % 8;
+    (MOON_PHASES[index], illumination)
+}
+
+fn light_condition(elevation: f64) -> &'static str {
+    if elevation < -6.0 {
+        "night"
+    } else if elevation < -0.833 {
+        "blue-hour"
+    } else if elevation < 6.0 {
+        "golden-hour"
+    } else {
+        "day"
+    }
+}
+
+fn format_local(utc_hours: f64, offset_minutes: i32) -> String {
+    let minutes = ((utc_hours * 60.0).round() as i64 + offset_minutes as i64).rem_euclid(1440);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Compute sun and moon conditions for a capture at local time `taken_at`
+/// (year, month, day, hour, minute, second) with the given UTC offset and position.
+/// When `offset_minutes` is `None` the offset is estimated from longitude (15° per hour).
+pub fn compute_astronomy(
+    taken_at: (i32, u32, u32, u32, u32, u32),
+    offset_minutes: Option<i32>,
+    lat: f64,
+    lon: f64,
+) -> Astronomy {
+    let (year, month, day, hour, minute, second) = taken_at;
+    let offset = offset_minutes.unwrap_or_else(|| ((lon / 15.0).round() * 60.0) as i32);
+    let jd = julian_day(year, month, day, hour, minute, second) - offset as f64 / 1440.0;
+    let doy = day_of_year(year, month, day);
+
+    let event = |zenith: f64, rising: bool| {
+        sun_event_utc(doy, lat, lon, zenith, rising).map(|t| format_local(t, offset))
+    };
+    let elevation = sun_elevation(jd, lat, lon);
+    let (phase, illumination) = moon_phase(jd);
+
+    Astronomy {
+        sunrise: event(ZENITH_SUNRISE, true),
+        sunset: event(ZENITH_SUNRISE, false),
+        golden_hour_morning_end: event(ZENITH_GOLDEN_HOUR, true),
+        golden_hour_evening_start: event(ZENITH_GOLDEN_HOUR, false),
+        sun_elevation: round_to(elevation, 1),
+        light_condition: light_condition(elevation).to_string(),
+        moon_phase: phase.to_string(),
+        moon_illumination: round_to(illumination, 2),
+        utc_offset_estimated: offset_minutes.is_none(),
+    }
+}
+
+/// Compute sunrise/sunset, golden hour, sun elevation and moon phase for every photo in a
+/// gallery from its EXIF capture time and GPS position, and store the result as the photo's
+/// `astronomy` object. Entirely offline. Photos without a timestamp or GPS fix are skipped
+/// and keep any existing `astronomy` value.
+#[tauri::command]
+pub async fn enrich_astronomy(
+    workspace_path: String,
+    slug: String,
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+) -> Result<AstronomyEnrichResult, String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let filenames: Vec<String> = read_gallery_details(&root, &slug)?
+        .ok_or_else(|| format!("gallery-details.json not found for '{}'", slug))?
+        .get("photos")
+        .and_then(|p| p.as_array())
+        .map(|photos| photos.iter().map(|p| str_field(p, "full")).filter(|f| !f.is_empty()).collect())
+        .unwrap_or_default();
+
+    let gallery_dir = root.join(&slug);
+    let (computed, skipped, errors) = tokio::task::spawn_blocking(move || {
+        let mut computed: HashMap<String, Astronomy> = HashMap::new();
+        let mut skipped = Vec::new();
+        let mut errors = Vec::new();
+        for filename in filenames {
+            match read_exif(&readable_original(&gallery_dir.join(&filename))) {
+                Ok(exif) => match (exif.taken_at, exif.latitude, exif.longitude) {
+                    (Some(taken_at), Some(lat), Some(lon)) => {
+                        let astro = compute_astronomy(taken_at, exif.utc_offset_minutes, lat, lon);
+                        computed.insert(filename, astro);
+                    }
+                    _ => skipped.push(filename),
+                },
+                Err(error) => errors.push(AstronomyError { filename, error }),
+            }
+        }
+        (computed, skipped, errors)
+    })
+    .await
+    .map_err(|e| format!("Astronomy enrichment panicked: {}", e))?;
+
+    let enriched = if computed.is_empty() {
+        0
+    } else {
+        let details_path = root.join(&slug).join("gallery-details.json");
+        journal.track("Add astronomy data", &[details_path], || {
+            update_gallery_details(&root, &slug, |details| {
+                let mut count = 0;
+                for photo in photos_mut(details)?.iter_mut() {
+                    let Some(astro) = computed.get(&str_field(photo, "full")) else {
+                        continue;
+                    };
+                    if let Some(obj) = photo.as_object_mut() {
+                        let value = serde_json::to_value(astro).map_err(|e| e.to_string())?;
+                        obj.insert("astronomy".to_string(), value);
+                        count += 1;
+                    }
+                }
+                Ok(count)
+            })
+        })?
+    };
+    if enriched > 0 {
+        emit_change(&app, WorkspaceChange::new("galleryDetails", &slug, "updated", ChangeOrigin::User, "enrich_astronomy"));
+    }
+
+    Ok(AstronomyEnrichResult { enriched, skipped, errors })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(hhmm: &str) -> i32 {
+        let (h, m) = hhmm.split_once(':').unwrap();
+        h.parse::<i32>().unwrap() * 60 + m.parse::<i32>().unwrap()
+    }
+
+    fn assert_near(actual: &str, expected: &str, tolerance_min: i32) {
+        let diff = (minutes(actual) - minutes(expected)).abs();
+        assert!(diff <= tolerance_min, "{} is not within {} min of {}", actual, tolerance_min, expected);
+    }
+
+    #[test]
+    fn test_julian_day_j2000() {
+        assert_eq!(julian_day(2000, 1, 1, 12, 0, 0), 2451545.0);
+    }
+
+    #[test]
+    fn test_sunrise_sunset_london_midsummer() {
+        // London, 21 June 2024 (BST, UTC+1): sunrise 04:43, sunset 21:21
+        let astro = compute_astronomy((2024, 6, 21, 12, 0, 0), Some(60), 51.5074, -0.1278);
+        assert_near(astro.sunrise.as_deref().unwrap(), "04:43", 3);
+        assert_near(astro.sunset.as_deref().unwrap(), "21:21", 3);
+        assert_eq!(astro.light_condition, "day");
+        assert!(!astro.utc_offset_estimated);
+    }
+
+    #[test]
+    fn test_sunrise_sunset_sydney_midwinter() {
+        // Sydney, 21 June 2024 (AEST, UTC+10): sunrise 07:00, sunset 16:54
+        let astro = compute_astronomy((2024, 6, 21, 12, 0, 0), Some(600), -33.8688, 151.2093);
+        assert_near(astro.sunrise.as_deref().unwrap(), "07:00", 3);
+        assert_near(astro.sunset.as_deref().unwrap(), "16:54", 3);
+    }
+
+    #[test]
+    fn test_golden_hour_brackets_sunrise_and_sunset() {
+        let astro = compute_astronomy((2024, 3, 20, 9, 0, 0), Some(0), 51.5, 0.0);
+        let sunrise = minutes(astro.sunrise.as_deref().unwrap());
+        let sunset = minutes(astro.sunset.as_deref().unwrap());
+        let morning_end = minutes(astro.golden_hour_morning_end.as_deref().unwrap());
+        let evening_start = minutes(astro.golden_hour_evening_start.as_deref().unwrap());
+        assert!(sunrise < morning_end && morning_end < evening_start && evening_start < sunset);
+    }
+
+    #[test]
+    fn test_polar_night_has_no_sunrise() {
+        // Tromsø in late December
+        let astro = compute_astronomy((2024, 12, 21, 12, 0, 0), Some(60), 69.6492, 18.9553);
+        assert!(astro.sunrise.is_none());
+        assert!(astro.sunset.is_none());
+    }
+
+    #[test]
+    fn test_light_condition_at_capture() {
+        // London midnight in June is night; 20:55 BST is just before sunset
+        let night = compute_astronomy((2024, 6, 21, 0, 30, 0), Some(60), 51.5074, -0.1278);
+        assert_eq!(night.light_condition, "night");
+        let golden = compute_astronomy((2024, 6, 21, 20, 55, 0), Some(60), 51.5074, -0.1278);
+        assert_eq!(golden.light_condition, "golden-hour");
+    }
+
+    #[test]
+    fn test_moon_phase_known_dates() {
+        // Full moon 2024-04-23 23:49 UTC; new moon 2024-04-08 18:21 UTC
+        let (phase, illum) = moon_phase(julian_day(2024, 4, 23, 23, 49, 0));
+        assert_eq!(phase, "Full Moon");
+        assert!(illum > 0.98);
+        let (phase, illum) = moon_phase(julian_day(2024, 4, 8, 18, 21, 0));
+        assert_eq!(phase, "New Moon");
+        assert!(illum < 0.02);
+        let (phase, _) = moon_phase(julian_day(2024, 4, 15, 19, 13, 0));
+        assert_eq!(phase, "First Quarter");
+    }
+
+    #[test]
+    fn test_offset_estimated_from_longitude() {
+        let astro = compute_astronomy((2024, 6, 21, 12, 0, 0), None, 51.5074, -0.1278);
+        assert!(astro.utc_offset_estimated);
+        // Offset estimated as UTC+0, so sunrise is an hour earlier than in BST
+        assert_near(astro.sunrise.as_deref().unwrap(), "03:43", 3);
+    }
+}
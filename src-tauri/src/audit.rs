@@ -0,0 +1,218 @@
+use crate::announcement::encode_url_path;
+use crate::health::site_http_client;
+use crate::publish::content_type_for_extension;
+use crate::settings::{normalize_site_url, read_settings};
+use crate::thumbnails::parse_galleries_array;
+use crate::workspace::str_field;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::Emitter;
+
+/// Concurrent HEAD requests against the site.
+const AUDIT_CONCURRENCY: usize = 8;
+
+/// A file the live site's JSON references, relative to `{siteUrl}galleries/`.
+#[derive(Debug, Clone, PartialEq)]
+struct ReferencedFile {
+    gallery_slug: String,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveAuditIssue {
+    pub url: String,
+    pub gallery_slug: String,
+    /// "missing" (403/404 — S3 answers 403 for missing keys without ListBucket),
+    /// "contentType", "httpError", "invalidJson" or "requestFailed".
+    pub kind: String,
+    pub status: Option<u16>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveSiteAudit {
+    pub galleries: usize,
+    /// Image and thumbnail URLs checked.
+    pub checked: usize,
+    pub issues: Vec<LiveAuditIssue>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditProgress {
+    current: usize,
+    total: usize,
+}
+
+/// Images referenced by a gallery's published `gallery-details.json` (`thumbnail` and `full`
+/// are relative to the gallery folder), deduplicated.
+fn referenced_photo_files(slug: &str, details: &serde_json::Value) -> Vec<ReferencedFile> {
+    let mut files: Vec<ReferencedFile> = Vec::new();
+    let photos = details.get("photos").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    for photo in &photos {
+        for field in ["thumbnail", "full"] {
+            let value = str_field(photo, field);
+            let file = ReferencedFile { gallery_slug: slug.to_string(), path: format!("{}/{}", slug, value) };
+            if !value.is_empty() && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    files
+}
+
+/// Classify a HEAD response for `path`; `None` when it's fine.
+fn classify_response(path: &str, status: u16, content_type: Option<&str>) -> Option<(String, String)> {
+    match status {
+        200..=299 => {}
+        403 | 404 => return Some(("missing".to_string(), format!("HTTP {}", status))),
+        _ => return Some(("httpError".to_string(), format!("HTTP {}", status))),
+    }
+    let expected = content_type_for_extension(Path::new(path));
+    let essence = |ct: &str| ct.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    let actual = content_type.map(essence).unwrap_or_default();
+    if actual != essence(expected) {
+        let shown = if actual.is_empty() { "none" } else { actual.as_str() };
+        return Some(("contentType".to_string(), format!("Expected {}, got {}", essence(expected), shown)));
+    }
+    None
+}
+
+async fn fetch_json(client: &reqwest::Client, url: &str) -> Result<serde_json::Value, (Option<u16>, String)> {
+    let response = client
+        .get(url)
+        .header("Cache-Control", "no-cache")
+        .send()
+        .await
+        .map_err(|e| (None, format!("{:?}", e)))?;
+    let status = response.status().as_u16();
+    if !(200..300).contains(&status) {
+        return Err((Some(status), format!("HTTP {}", status)));
+    }
+    let bytes = response.bytes().await.map_err(|e| (Some(status), e.to_string()))?;
+    serde_json::from_slice(&bytes).map_err(|e| (Some(status), format!("Invalid JSON: {}", e)))
+}
+
+/// Audit the live site for broken images: downloads the published `galleries.json` and every
+/// `gallery-details.json`, then HEADs each referenced cover, thumbnail and full image,
+/// reporting missing objects and content-type mismatches (e.g. a WebP thumbnail served as
+/// `application/octet-stream`). Emits `audit-progress` while checking.
+#[tauri::command]
+pub async fn audit_live_site(app: tauri::AppHandle) -> Result<LiveSiteAudit, String> {
+    let settings = read_settings(&app)?;
+    let site_url = normalize_site_url(&settings.site_url)?;
+    let galleries_url = format!("{}galleries/", site_url);
+    let client = site_http_client()?;
+
+    let index = fetch_json(&client, &format!("{}galleries.json", galleries_url))
+        .await
+        .map_err(|(_, e)| format!("Failed to download live galleries.json: {}", e))?;
+    let galleries = parse_galleries_array(&index);
+
+    let mut issues = Vec::new();
+    let mut files = Vec::new();
+    for gallery in &galleries {
+        let slug = str_field(gallery, "slug");
+        if slug.is_empty() {
+            continue;
+        }
+        let cover = str_field(gallery, "cover");
+        if !cover.is_empty() {
+            files.push(ReferencedFile { gallery_slug: slug.clone(), path: cover });
+        }
+        let details_url = format!("{}{}/gallery-details.json", galleries_url, encode_url_path(&slug));
+        match fetch_json(&client, &details_url).await {
+            Ok(details) => files.extend(referenced_photo_files(&slug, &details)),
+            Err((status, detail)) => issues.push(LiveAuditIssue {
+                url: details_url,
+                gallery_slug: slug,
+                kind: match status {
+                    Some(403 | 404) => "missing",
+                    Some(200..=299) => "invalidJson",
+                    Some(_) => "httpError",
+                    None => "requestFailed",
+                }
+                .to_string(),
+                status,
+                detail,
+            }),
+        }
+    }
+
+    // A cover is usually also one of its gallery's photos
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files.dedup_by(|a, b| a.path == b.path);
+
+    let total = files.len();
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(AUDIT_CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+    for file in files {
+        let client = client.clone();
+        let semaphore = semaphore.clone();
+        let url = format!("{}{}", galleries_url, encode_url_path(&file.path));
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let (kind, status, detail) = match client.head(&url).send().await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let content_type = response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    match classify_response(&file.path, status, content_type.as_deref()) {
+                        Some((kind, detail)) => (kind, Some(status), detail),
+                        None => return None,
+                    }
+                }
+                Err(e) => ("requestFailed".to_string(), None, format!("{:?}", e)),
+            };
+            Some(LiveAuditIssue { url, gallery_slug: file.gallery_slug, kind, status, detail })
+        });
+    }
+
+    let mut current = 0;
+    while let Some(result) = tasks.join_next().await {
+        current += 1;
+        let _ = app.emit("audit-progress", AuditProgress { current, total });
+        if let Some(issue) = result.map_err(|e| format!("Audit task failed: {}", e))? {
+            issues.push(issue);
+        }
+    }
+    issues.sort_by(|a, b| a.url.cmp(&b.url));
+
+    Ok(LiveSiteAudit { galleries: galleries.len(), checked: total, issues })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_photo_files() {
+        let details = serde_json::json!({"photos": [
+            {"thumbnail": ".thumbs/01.webp", "full": "01.jpg"},
+            {"thumbnail": "02.jpg", "full": "02.jpg"},
+            {"full": ""}
+        ]});
+        let paths: Vec<String> = referenced_photo_files("a", &details).into_iter().map(|f| f.path).collect();
+        assert_eq!(paths, vec!["a/.thumbs/01.webp", "a/01.jpg", "a/02.jpg"]);
+    }
+
+    #[test]
+    fn test_classify_response() {
+        assert_eq!(classify_response("a/01.jpg", 200, Some("image/jpeg")), None);
+        assert_eq!(classify_response("a/.thumbs/01.webp", 200, Some("IMAGE/WEBP; charset=binary")), None);
+        assert_eq!(classify_response("a/01.jpg", 404, None).unwrap().0, "missing");
+        assert_eq!(classify_response("a/01.jpg", 403, None).unwrap().0, "missing");
+        assert_eq!(classify_response("a/01.jpg", 500, None).unwrap().0, "httpError");
+        assert_eq!(
+            classify_response("a/.thumbs/01.webp", 200, Some("application/octet-stream")),
+            Some(("contentType".to_string(), "Expected image/webp, got application/octet-stream".to_string()))
+        );
+        assert_eq!(classify_response("a/01.png", 200, None).unwrap().1, "Expected image/png, got none");
+    }
+}
@@ -0,0 +1,318 @@
+use crate::activity::record_activity;
+use crate::i18n::split_code;
+use crate::publish::{publish_execute, publish_preview, PublishPlan, PublishState};
+use crate::settings::{read_settings, AppSettings};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// Quiet period used when `quietMinutes` isn't set.
+const DEFAULT_QUIET_MINUTES: u32 = 10;
+const MAX_QUIET_MINUTES: u32 = 24 * 60;
+/// Uploads an automatic publish may make when `maxUploads` isn't set.
+const DEFAULT_MAX_UPLOADS: u32 = 200;
+/// How often the running loop checks whether the workspace has been quiet long enough.
+const AUTO_PUBLISH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Publish automatically once the workspace has been quiet for a while after changing.
+/// Publishes over either limit, needing a delete confirmation or over budget are left for a
+/// manual publish.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutoPublishSettings {
+    pub enabled: bool,
+    /// Minutes without changes before publishing; unset uses `DEFAULT_QUIET_MINUTES`.
+    pub quiet_minutes: Option<u32>,
+    /// Most files one automatic publish uploads; unset uses `DEFAULT_MAX_UPLOADS`.
+    pub max_uploads: Option<u32>,
+    /// Most files one automatic publish deletes; unset allows none.
+    pub max_deletes: Option<u32>,
+}
+
+/// The running loop, the change it's waiting to settle, and the plan it's executing.
+#[derive(Default)]
+pub struct AutoPublish {
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+    /// Last workspace change not yet published; None when nothing is pending.
+    last_change: Option<Instant>,
+    running_plan: Option<String>,
+}
+
+pub struct AutoPublishState(pub Mutex<AutoPublish>);
+
+/// Payload of the `auto-publish` event.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoPublishEvent {
+    /// "publishing", "published", "skipped", "cancelled" or "failed".
+    pub status: String,
+    /// Why a publish was skipped or failed; empty otherwise.
+    pub message: String,
+    /// Error code when `message` is a coded error (see `i18n::error`).
+    pub code: Option<String>,
+    pub uploads: usize,
+    pub deletes: usize,
+}
+
+impl AutoPublishEvent {
+    fn new(status: &str, message: String, plan: Option<&PublishPlan>) -> Self {
+        let (code, message) = split_code(&message);
+        Self {
+            status: status.to_string(),
+            message: message.to_string(),
+            code: code.map(str::to_string),
+            uploads: plan.map_or(0, |p| p.to_upload.len()),
+            deletes: plan.map_or(0, |p| p.to_delete.len()),
+        }
+    }
+}
+
+fn quiet_period(settings: &AutoPublishSettings) -> Duration {
+    Duration::from_secs(u64::from(settings.quiet_minutes.unwrap_or(DEFAULT_QUIET_MINUTES)) * 60)
+}
+
+/// Check the auto-publish settings before saving.
+pub(crate) fn validate_auto_publish(settings: &AppSettings) -> Result<(), String> {
+    match settings.auto_publish.quiet_minutes {
+        Some(minutes) if minutes == 0 || minutes > MAX_QUIET_MINUTES => {
+            Err(format!("The auto-publish quiet period must be between 1 and {} minutes.", MAX_QUIET_MINUTES))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether changes made at `last_change` have settled by `now`.
+fn due(last_change: Option<Instant>, now: Instant, quiet: Duration) -> bool {
+    last_change.is_some_and(|at| now.saturating_duration_since(at) >= quiet)
+}
+
+/// Why `plan` needs a manual publish, or None when it can run automatically.
+fn refusal(plan: &PublishPlan, settings: &AutoPublishSettings) -> Option<String> {
+    let max_uploads = settings.max_uploads.unwrap_or(DEFAULT_MAX_UPLOADS) as usize;
+    let max_deletes = settings.max_deletes.unwrap_or(0) as usize;
+    if let Some(warning) = &plan.budget_warning {
+        Some(warning.clone())
+    } else if plan.delete_confirmation.is_some() {
+        Some(format!("{} deletions need confirming.", plan.to_delete.len()))
+    } else if let Some(mirror) = plan.mirror.as_ref().filter(|mirror| mirror.delete_confirmation.is_some()) {
+        Some(format!("{} deletions from the mirror need confirming.", mirror.to_delete.len()))
+    } else if plan.delete_categories.risky_count() > 0 {
+        Some(format!(
+            "{} deletions of removed galleries or unrecognized files need confirming.",
+            plan.delete_categories.risky_count()
+        ))
+    } else if plan.to_upload.len() > max_uploads {
+        Some(format!("{} uploads is over the limit of {}.", plan.to_upload.len(), max_uploads))
+    } else if plan.to_delete.len() > max_deletes {
+        Some(format!("{} deletions is over the limit of {}.", plan.to_delete.len(), max_deletes))
+    } else {
+        None
+    }
+}
+
+/// Note a workspace change for the running loop (see `changes::emit_change`).
+pub(crate) fn note_change(app: &tauri::AppHandle) {
+    if let Some(state) = app.try_state::<AutoPublishState>() {
+        if let Ok(mut auto) = state.0.lock() {
+            if auto.task.is_some() {
+                auto.last_change = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// Preview and, when it's within the limits, execute one publish, reporting each step as an
+/// `auto-publish` event.
+async fn auto_publish(app: &tauri::AppHandle, root: &Path) {
+    let emit = |event: AutoPublishEvent| {
+        let _ = app.emit("auto-publish", event);
+    };
+    let settings = match read_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => return emit(AutoPublishEvent::new("failed", e, None)),
+    };
+    let folder_path = root.to_string_lossy().to_string();
+    let plan = match publish_preview(app.clone(), folder_path, settings.bucket, settings.region, settings.s3_prefix).await {
+        Ok(plan) => plan,
+        Err(e) => return emit(AutoPublishEvent::new("failed", e, None)),
+    };
+    if plan.to_upload.is_empty() && plan.to_delete.is_empty() {
+        return;
+    }
+    if let Some(reason) = refusal(&plan, &settings.auto_publish) {
+        record_activity(app, "autoPublishSkipped", reason.clone());
+        return emit(AutoPublishEvent::new("skipped", reason, Some(&plan)));
+    }
+
+    let state = app.state::<AutoPublishState>();
+    if let Ok(mut auto) = state.0.lock() {
+        auto.running_plan = Some(plan.plan_id.clone());
+    }
+    emit(AutoPublishEvent::new("publishing", String::new(), Some(&plan)));
+    let result = publish_execute(app.clone(), plan.plan_id.clone(), None, None).await;
+    if let Ok(mut auto) = state.0.lock() {
+        auto.running_plan = None;
+    }
+    let cancelled = app
+        .state::<Mutex<PublishState>>()
+        .lock()
+        .is_ok_and(|publish| publish.cancelled.get(&plan.plan_id) == Some(&true));
+    emit(match result {
+        Ok(()) if cancelled => AutoPublishEvent::new("cancelled", String::new(), Some(&plan)),
+        Ok(()) => AutoPublishEvent::new("published", String::new(), Some(&plan)),
+        Err(e) => AutoPublishEvent::new("failed", e, Some(&plan)),
+    });
+}
+
+fn stop_loop(state: &AutoPublishState) -> Result<(), String> {
+    let mut auto = state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(handle) = auto.task.take() {
+        handle.abort();
+    }
+    auto.last_change = None;
+    Ok(())
+}
+
+/// (Re)start auto-publishing `workspace_path` using the `autoPublish` setting. Stops any
+/// running loop and returns false when turned off or the workspace is open read-only. Only
+/// changes made after starting are published, and only while the app is open.
+#[tauri::command]
+pub async fn start_auto_publish(
+    app: tauri::AppHandle,
+    auto_state: tauri::State<'_, AutoPublishState>,
+    workspace_path: String,
+) -> Result<bool, String> {
+    stop_loop(&auto_state)?;
+    let settings = read_settings(&app)?.auto_publish;
+    let root = PathBuf::from(&workspace_path);
+    if !settings.enabled || crate::read_only::ensure_writable(&app, &root).is_err() {
+        return Ok(false);
+    }
+    let quiet = quiet_period(&settings);
+    let app_handle = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(AUTO_PUBLISH_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let ready = {
+                let state = app_handle.state::<AutoPublishState>();
+                let mut auto = match state.0.lock() {
+                    Ok(auto) => auto,
+                    Err(_) => return,
+                };
+                let ready = due(auto.last_change, Instant::now(), quiet);
+                if ready {
+                    auto.last_change = None;
+                }
+                ready
+            };
+            if ready {
+                // Its own task, so stopping the loop cancels the publish instead of dropping it mid-file
+                let (app, root) = (app_handle.clone(), root.clone());
+                let _ = tokio::spawn(async move { auto_publish(&app, &root).await }).await;
+            }
+        }
+    });
+    auto_state.0.lock().map_err(|e| e.to_string())?.task = Some(handle);
+    Ok(true)
+}
+
+/// The kill switch: stop auto-publishing; an automatic publish in progress stops after the
+/// current file.
+#[tauri::command]
+pub async fn stop_auto_publish(
+    auto_state: tauri::State<'_, AutoPublishState>,
+    publish_state: tauri::State<'_, Mutex<PublishState>>,
+) -> Result<(), String> {
+    stop_loop(&auto_state)?;
+    let running = auto_state.0.lock().map_err(|e| e.to_string())?.running_plan.take();
+    if let Some(plan_id) = running {
+        publish_state.lock().map_err(|e| e.to_string())?.cancelled.insert(plan_id, true);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::MirrorPlan;
+    use crate::publish::{DeleteCategories, SyncFile};
+
+    fn plan(uploads: usize, deletes: usize) -> PublishPlan {
+        let file = SyncFile {
+            local_path: "/w/a/01.jpg".to_string(),
+            s3_key: "a/01.jpg".to_string(),
+            size_bytes: 1,
+            content_type: "image/jpeg".to_string(),
+        };
+        PublishPlan {
+            plan_id: "p".to_string(),
+            to_upload: vec![file; uploads],
+            to_delete: vec!["galleries/a/old.jpg".to_string(); deletes],
+            delete_categories: DeleteCategories {
+                removed_photos: vec!["galleries/a/old.jpg".to_string(); deletes],
+                ..Default::default()
+            },
+            unchanged: 0,
+            total_files: uploads,
+            delete_confirmation: None,
+            budget_warning: None,
+            mirror: None,
+            image_warnings: Vec::new(),
+            template: Default::default(),
+            simulated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_due() {
+        let now = Instant::now();
+        let quiet = Duration::from_secs(600);
+        assert!(!due(None, now, quiet));
+        assert!(!due(Some(now - Duration::from_secs(60)), now, quiet));
+        assert!(due(Some(now - Duration::from_secs(600)), now, quiet));
+    }
+
+    #[test]
+    fn test_refusal() {
+        let settings = AutoPublishSettings { enabled: true, ..Default::default() };
+        assert_eq!(refusal(&plan(3, 0), &settings), None);
+        assert!(refusal(&plan(201, 0), &settings).unwrap().contains("over the limit of 200"));
+        assert!(refusal(&plan(3, 1), &settings).unwrap().contains("deletions"));
+        assert_eq!(refusal(&plan(3, 1), &AutoPublishSettings { max_deletes: Some(5), ..settings.clone() }), None);
+
+        let confirm = PublishPlan { delete_confirmation: Some("delete 40".to_string()), ..plan(0, 40) };
+        assert!(refusal(&confirm, &AutoPublishSettings { max_deletes: Some(100), ..settings.clone() }).unwrap().contains("confirming"));
+        let mirror = MirrorPlan {
+            bucket: "photos-eu".to_string(),
+            to_upload: Vec::new(),
+            to_delete: vec!["galleries/a/old.jpg".to_string(); 40],
+            unchanged: 0,
+            delete_confirmation: Some("DELETE 40".to_string()),
+            error: None,
+        };
+        let confirm_mirror = PublishPlan { mirror: Some(mirror), ..plan(3, 0) };
+        assert!(refusal(&confirm_mirror, &settings).unwrap().contains("from the mirror need confirming"));
+        let gallery_removed = PublishPlan {
+            delete_categories: DeleteCategories { removed_galleries: vec!["galleries/b/01.jpg".to_string()], ..Default::default() },
+            ..plan(1, 1)
+        };
+        assert!(refusal(&gallery_removed, &AutoPublishSettings { max_deletes: Some(5), ..settings.clone() }).unwrap().contains("removed galleries"));
+        let over_budget = PublishPlan { budget_warning: Some("Over budget.".to_string()), ..plan(1, 0) };
+        assert_eq!(refusal(&over_budget, &settings).as_deref(), Some("Over budget."));
+    }
+
+    #[test]
+    fn test_validate_auto_publish() {
+        let with = |quiet_minutes| AppSettings {
+            auto_publish: AutoPublishSettings { quiet_minutes, ..Default::default() },
+            ..AppSettings::default()
+        };
+        assert!(validate_auto_publish(&AppSettings::default()).is_ok());
+        assert!(validate_auto_publish(&with(Some(5))).is_ok());
+        assert!(validate_auto_publish(&with(Some(0))).is_err());
+        assert!(validate_auto_publish(&with(Some(MAX_QUIET_MINUTES + 1))).is_err());
+    }
+}
@@ -0,0 +1,440 @@
+use crate::i18n::{error, split_code, ErrorCode};
+use crate::settings::{get_credentials_from_keychain, read_settings};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\afterglow-manager-automation";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The command itself failed; `message` is its error.
+const COMMAND_ERROR: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+
+/// Methods callable after `authenticate`, each mirroring the app command of the same purpose.
+const METHODS: &[&str] = &["import", "validate", "preview", "execute"];
+
+/// The running automation server task, if any.
+pub struct AutomationState(pub Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+/// Where the automation API listens and the token clients authenticate with.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationEndpoint {
+    /// Unix socket path, or the named pipe on Windows.
+    pub address: String,
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticateParams {
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportParams {
+    workspace_path: String,
+    source_path: String,
+    slug: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewParams {
+    workspace_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteParams {
+    plan_id: String,
+    confirmation: Option<String>,
+    mirror_confirmation: Option<String>,
+}
+
+/// What to do with one request line.
+#[derive(Debug, PartialEq)]
+enum Accepted {
+    /// Answer without running anything (authentication, or an error).
+    Reply(Value),
+    /// Run `method` and answer with its result.
+    Call { id: Value, method: String, params: Value },
+}
+
+fn success(id: Value, result: Value) -> Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// A JSON-RPC error response. The code of a coded app error (see `i18n::error`) goes in `data`.
+fn failure(id: Value, code: i64, message: &str) -> Value {
+    match split_code(message) {
+        (Some(error_code), message) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message, "data": { "errorCode": error_code } }
+        }),
+        (None, message) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    }
+}
+
+fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
+    fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    Ok(data_dir)
+}
+
+/// The token at `path`, or a new random one written there. The file is created readable by the
+/// current user only; it never exists with wider permissions, even briefly.
+fn read_or_create_token_at(path: &Path, reset: bool) -> Result<String, String> {
+    if !reset {
+        if let Ok(token) = fs::read_to_string(path) {
+            if !token.trim().is_empty() {
+                return Ok(token.trim().to_string());
+            }
+        }
+    }
+    let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let write_failed = |e: std::io::Error| error(ErrorCode::AutomationTokenWriteFailed, &[("error", &e)]);
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(write_failed(e)),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(write_failed)?;
+    file.write_all(token.as_bytes()).map_err(write_failed)?;
+    Ok(token)
+}
+
+/// `{app_data}/automation-token`, created with a random token on first use.
+fn read_or_create_token(app: &tauri::AppHandle, reset: bool) -> Result<String, String> {
+    read_or_create_token_at(&data_dir(app)?.join("automation-token"), reset)
+}
+
+/// `dir`, created (or narrowed) to be accessible by the current user only. The socket is bound
+/// inside it, so no other user can connect even before the socket's own mode is set.
+#[cfg(unix)]
+fn private_dir(dir: &Path) -> Result<(), String> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(dir).map_err(|e| e.to_string())?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).map_err(|e| e.to_string())
+}
+
+fn endpoint_address(app: &tauri::AppHandle) -> Result<String, String> {
+    #[cfg(unix)]
+    {
+        Ok(data_dir(app)?.join("automation").join("automation.sock").to_string_lossy().into_owned())
+    }
+    #[cfg(windows)]
+    {
+        let _ = app;
+        Ok(PIPE_NAME.to_string())
+    }
+}
+
+/// Compare without stopping at the first differing byte, so timing doesn't reveal the token.
+pub(crate) fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+/// Check one JSON-RPC 2.0 request line. The first call on a connection must be
+/// `authenticate { token }`; until it succeeds every other method is refused.
+fn accept_request(line: &str, authenticated: &mut bool, token: &str) -> Accepted {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Accepted::Reply(failure(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e))),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = match (request.get("jsonrpc").and_then(|v| v.as_str()), request.get("method").and_then(|v| v.as_str())) {
+        (Some("2.0"), Some(method)) => method.to_string(),
+        _ => return Accepted::Reply(failure(id, INVALID_REQUEST, "Expected a JSON-RPC 2.0 request")),
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    if method == "authenticate" {
+        return Accepted::Reply(match parse_params::<AuthenticateParams>(params) {
+            Ok(p) if tokens_match(&p.token, token) => {
+                *authenticated = true;
+                success(id, Value::Bool(true))
+            }
+            Ok(_) => failure(id, UNAUTHORIZED, "Invalid token"),
+            Err((code, message)) => failure(id, code, &message),
+        });
+    }
+    if !*authenticated {
+        return Accepted::Reply(failure(id, UNAUTHORIZED, "Call authenticate first"));
+    }
+    if !METHODS.contains(&method.as_str()) {
+        return Accepted::Reply(failure(id, METHOD_NOT_FOUND, &format!("Unknown method: {}", method)));
+    }
+    Accepted::Call { id, method, params }
+}
+
+/// Run an accepted method through the same code paths as the app's commands.
+async fn dispatch(app: &tauri::AppHandle, method: &str, params: Value) -> Result<Value, (i64, String)> {
+    let command_error = |e: String| (COMMAND_ERROR, e);
+    let result = match method {
+        "import" => {
+            let p: ImportParams = parse_params(params)?;
+            let result = crate::folder_import::import_folder(app.clone(), p.workspace_path, p.source_path, p.slug, p.name)
+                .await
+                .map_err(command_error)?;
+            serde_json::to_value(result)
+        }
+        "validate" => {
+            let settings = read_settings(app).map_err(command_error)?;
+            let (key_id, secret) = get_credentials_from_keychain().map_err(command_error)?;
+            let result = crate::settings::validate_credentials(key_id, secret, settings.bucket, settings.region)
+                .await
+                .map_err(command_error)?;
+            serde_json::to_value(result)
+        }
+        "preview" => {
+            let p: PreviewParams = parse_params(params)?;
+            let settings = read_settings(app).map_err(command_error)?;
+            let plan = crate::publish::publish_preview(app.clone(), p.workspace_path, settings.bucket, settings.region, settings.s3_prefix)
+                .await
+                .map_err(command_error)?;
+            serde_json::to_value(plan)
+        }
+        "execute" => {
+            // Returns once the publish finished; counts are in the activity log
+            let p: ExecuteParams = parse_params(params)?;
+            crate::publish::publish_execute(app.clone(), p.plan_id, p.confirmation, p.mirror_confirmation)
+                .await
+                .map_err(command_error)?;
+            Ok(Value::Null)
+        }
+        _ => return Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    };
+    result.map_err(|e| (COMMAND_ERROR, e.to_string()))
+}
+
+/// Serve newline-delimited JSON-RPC requests on one connection until the client disconnects.
+async fn serve_connection<S>(app: tauri::AppHandle, stream: S, token: Arc<String>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    let mut authenticated = false;
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match accept_request(&line, &mut authenticated, &token) {
+            Accepted::Reply(response) => response,
+            Accepted::Call { id, method, params } => match dispatch(&app, &method, params).await {
+                Ok(result) => success(id, result),
+                Err((code, message)) => failure(id, code, &message),
+            },
+        };
+        let mut out = response.to_string();
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn listen(app: tauri::AppHandle, address: &str, token: Arc<String>) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    let path = PathBuf::from(address);
+    private_dir(path.parent().ok_or("No parent directory")?)?;
+    // A socket left by a previous run that didn't shut down cleanly
+    let _ = fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| error(ErrorCode::ListenFailed, &[("address", &path.display()), ("error", &e)]))?;
+    Ok(tauri::async_runtime::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tauri::async_runtime::spawn(serve_connection(app.clone(), stream, token.clone()));
+        }
+    }))
+}
+
+#[cfg(windows)]
+fn listen(app: tauri::AppHandle, address: &str, token: Arc<String>) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+    let address = address.to_string();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&address)
+        .map_err(|e| error(ErrorCode::ListenFailed, &[("address", &address), ("error", &e)]))?;
+    Ok(tauri::async_runtime::spawn(async move {
+        loop {
+            if server.connect().await.is_err() {
+                break;
+            }
+            let client = server;
+            server = match ServerOptions::new().reject_remote_clients(true).create(&address) {
+                Ok(next) => next,
+                Err(e) => {
+                    eprintln!("[automation] Failed to create pipe instance: {}", e);
+                    break;
+                }
+            };
+            tauri::async_runtime::spawn(serve_connection(app.clone(), client, token.clone()));
+        }
+    }))
+}
+
+/// The endpoint and token to configure automation clients with; None when the
+/// `automationApi` setting is off.
+#[tauri::command]
+pub async fn get_automation_endpoint(app: tauri::AppHandle) -> Result<Option<AutomationEndpoint>, String> {
+    if !read_settings(&app)?.automation_api {
+        return Ok(None);
+    }
+    Ok(Some(AutomationEndpoint { address: endpoint_address(&app)?, token: read_or_create_token(&app, false)? }))
+}
+
+/// (Re)start the local automation API when the `automationApi` setting is on. Returns the
+/// endpoint, or None (after stopping any running server) when the setting is off. Only the
+/// current user can reach the endpoint, and every connection must authenticate with the token.
+#[tauri::command]
+pub async fn start_automation_server(
+    app: tauri::AppHandle,
+    automation_state: tauri::State<'_, AutomationState>,
+) -> Result<Option<AutomationEndpoint>, String> {
+    if let Some(handle) = automation_state.0.lock().map_err(|e| e.to_string())?.take() {
+        handle.abort();
+    }
+    let Some(endpoint) = get_automation_endpoint(app.clone()).await? else {
+        return Ok(None);
+    };
+    let handle = listen(app, &endpoint.address, Arc::new(endpoint.token.clone()))?;
+    *automation_state.0.lock().map_err(|e| e.to_string())? = Some(handle);
+    Ok(Some(endpoint))
+}
+
+#[tauri::command]
+pub async fn stop_automation_server(automation_state: tauri::State<'_, AutomationState>) -> Result<(), String> {
+    if let Some(handle) = automation_state.0.lock().map_err(|e| e.to_string())?.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+/// Replace the automation token. A running server keeps accepting the old one until it is
+/// restarted with `start_automation_server`.
+#[tauri::command]
+pub async fn reset_automation_token(app: tauri::AppHandle) -> Result<String, String> {
+    read_or_create_token(&app, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TOKEN: &str = "secret-token";
+
+    fn error_code(accepted: Accepted) -> i64 {
+        match accepted {
+            Accepted::Reply(response) => response["error"]["code"].as_i64().unwrap(),
+            Accepted::Call { .. } => panic!("expected an error reply"),
+        }
+    }
+
+    #[test]
+    fn test_accept_request_requires_authentication() {
+        let mut authenticated = false;
+        let preview = r#"{"jsonrpc":"2.0","id":2,"method":"preview","params":{"workspacePath":"/site"}}"#;
+        assert_eq!(error_code(accept_request(preview, &mut authenticated, TOKEN)), UNAUTHORIZED);
+
+        let wrong = r#"{"jsonrpc":"2.0","id":1,"method":"authenticate","params":{"token":"secret-tokem"}}"#;
+        assert_eq!(error_code(accept_request(wrong, &mut authenticated, TOKEN)), UNAUTHORIZED);
+        assert!(!authenticated);
+
+        let auth = r#"{"jsonrpc":"2.0","id":1,"method":"authenticate","params":{"token":"secret-token"}}"#;
+        assert_eq!(
+            accept_request(auth, &mut authenticated, TOKEN),
+            Accepted::Reply(json!({ "jsonrpc": "2.0", "id": 1, "result": true }))
+        );
+        assert!(authenticated);
+        assert_eq!(
+            accept_request(preview, &mut authenticated, TOKEN),
+            Accepted::Call { id: json!(2), method: "preview".to_string(), params: json!({ "workspacePath": "/site" }) }
+        );
+    }
+
+    #[test]
+    fn test_accept_request_errors() {
+        let mut authenticated = true;
+        assert_eq!(error_code(accept_request("{not json", &mut authenticated, TOKEN)), PARSE_ERROR);
+        assert_eq!(error_code(accept_request(r#"{"id":1,"method":"preview"}"#, &mut authenticated, TOKEN)), INVALID_REQUEST);
+        assert_eq!(
+            error_code(accept_request(r#"{"jsonrpc":"2.0","id":1,"method":"deleteEverything"}"#, &mut authenticated, TOKEN)),
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            error_code(accept_request(r#"{"jsonrpc":"2.0","id":1,"method":"authenticate"}"#, &mut authenticated, TOKEN)),
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abd", "abc"));
+        assert!(!tokens_match("ab", "abc"));
+    }
+
+    #[test]
+    fn test_token_file_is_private_and_replaced_on_reset() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("automation-token");
+        let token = read_or_create_token_at(&path, false).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_eq!(read_or_create_token_at(&path, false).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        let reset = read_or_create_token_at(&path, true).unwrap();
+        assert_ne!(reset, token);
+        assert_eq!(fs::read_to_string(&path).unwrap(), reset);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_dir_narrows_an_existing_directory() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("automation");
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        private_dir(&dir).unwrap();
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::changes::{emit_change, json_file_change, ChangeOrigin};
+use crate::i18n::{error, ErrorCode};
+use crate::journal::JournalState;
+use crate::manifest::format_utc_timestamp;
+use crate::settings::{read_settings, AppSettings};
+use crate::workspace::{read_json, write_json_atomic};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Daily backups kept per workspace when `metadataBackupRetention` isn't set.
+pub(crate) const DEFAULT_BACKUP_RETENTION: u32 = 14;
+const MAX_BACKUP_RETENTION: u32 = 365;
+const BACKUP_INTERVAL_SECS: u64 = 24 * 60 * 60;
+/// How often the running app checks whether a backup is due.
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const ARCHIVE_VERSION: u32 = 1;
+
+/// The running backup schedule, if any.
+pub struct BackupState(pub Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+/// `{app_data}/metadata-backups/{workspace id}/{id}.json`: every JSON metadata file of the
+/// workspace, keyed by path relative to the workspace root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupArchive {
+    version: u32,
+    workspace: String,
+    created_at: String,
+    files: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataBackup {
+    pub id: String,
+    pub created_at: String,
+    pub file_count: usize,
+    pub size_bytes: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Number of daily backups to keep; 0 turns backups off.
+fn retention(settings: &AppSettings) -> u32 {
+    settings.metadata_backup_retention.unwrap_or(DEFAULT_BACKUP_RETENTION)
+}
+
+/// Check the backup settings before saving.
+pub(crate) fn validate_backup_retention(settings: &AppSettings) -> Result<(), String> {
+    if retention(settings) > MAX_BACKUP_RETENTION {
+        return Err(format!("Keep at most {} metadata backups.", MAX_BACKUP_RETENTION));
+    }
+    Ok(())
+}
+
+/// Backups of each workspace go in their own folder, named by a hash of its path.
+fn backups_dir(app: &tauri::AppHandle, root: &Path) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
+    let hash = format!("{:x}", Sha256::digest(root.to_string_lossy().as_bytes()));
+    Ok(data_dir.join("metadata-backups").join(&hash[..16]))
+}
+
+/// The workspace's JSON files outside dot folders (`.data`, `.thumbs`), as parsed values.
+/// Files that don't parse are skipped: restoring them would only write the same bad JSON.
+fn collect_metadata(root: &Path) -> Result<BTreeMap<String, serde_json::Value>, String> {
+    fn walk(root: &Path, dir: &Path, files: &mut BTreeMap<String, serde_json::Value>) -> Result<(), String> {
+        for entry in fs::read_dir(dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                walk(root, &path, files)?;
+            } else if name.ends_with(".json") {
+                let Ok(rel) = path.strip_prefix(root) else { continue };
+                match read_json(&path) {
+                    Ok(value) => {
+                        files.insert(rel.to_string_lossy().replace('\\', "/"), value);
+                    }
+                    Err(e) => eprintln!("[backups] Skipping {}: {}", path.display(), e),
+                }
+            }
+        }
+        Ok(())
+    }
+    let mut files = BTreeMap::new();
+    walk(root, root, &mut files)?;
+    Ok(files)
+}
+
+/// Backup archives in `dir`, newest first. Ids sort chronologically.
+fn backup_files(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|p| {
+            let id = p.file_stem()?.to_str()?.to_string();
+            (id.starts_with("metadata-") && p.extension()? == "json").then_some((id, p))
+        })
+        .collect();
+    backups.sort_by(|a, b| b.0.cmp(&a.0));
+    backups
+}
+
+fn read_archive(path: &Path) -> Result<BackupArchive, String> {
+    serde_json::from_value(read_json(path)?).map_err(|e| format!("Invalid backup {}: {}", path.display(), e))
+}
+
+/// Write a backup of `root` into `dir` unless the newest one has the same content, then
+/// delete all but the newest `keep`. Returns the new backup's id, if one was written.
+fn back_up(root: &Path, dir: &Path, keep: u32, now: u64) -> Result<Option<String>, String> {
+    let files = collect_metadata(root)?;
+    let newest = backup_files(dir).into_iter().next();
+    if let Some((_, path)) = &newest {
+        if read_archive(path).is_ok_and(|latest| latest.files == files) {
+            return Ok(None);
+        }
+    }
+    let created_at = format_utc_timestamp(now);
+    let id = format!("metadata-{}", created_at.replace([':', '-'], ""));
+    let archive = BackupArchive {
+        version: ARCHIVE_VERSION,
+        workspace: root.to_string_lossy().into_owned(),
+        created_at,
+        files,
+    };
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    write_json_atomic(&dir.join(format!("{}.json", id)), &serde_json::to_value(&archive).map_err(|e| e.to_string())?)?;
+    for (_, old) in backup_files(dir).into_iter().skip(keep as usize) {
+        let _ = fs::remove_file(old);
+    }
+    Ok(Some(id))
+}
+
+/// Whether a day has passed since the newest backup in `dir`.
+fn backup_due(dir: &Path, now: u64) -> bool {
+    let Some((_, path)) = backup_files(dir).into_iter().next() else {
+        return true;
+    };
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    now.saturating_sub(modified) >= BACKUP_INTERVAL_SECS
+}
+
+/// A backup path relative to the workspace, refusing anything that would land outside it.
+fn restore_target(root: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel_path = Path::new(rel);
+    if rel.is_empty() || !rel_path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Backup contains an invalid path: \"{}\"", rel));
+    }
+    Ok(root.join(rel_path))
+}
+
+/// (Re)start daily metadata backups of `workspacePath` while the app runs; the first runs
+/// now when none was made in the last day. Stops any running schedule and returns false when
+/// backups are off (`metadataBackupRetention` 0).
+#[tauri::command]
+pub async fn start_metadata_backups(
+    app: tauri::AppHandle,
+    backup_state: tauri::State<'_, BackupState>,
+    workspace_path: String,
+) -> Result<bool, String> {
+    if let Some(handle) = backup_state.0.lock().map_err(|e| e.to_string())?.take() {
+        handle.abort();
+    }
+    let keep = retention(&read_settings(&app)?);
+    if keep == 0 {
+        return Ok(false);
+    }
+    let root = PathBuf::from(&workspace_path);
+    let dir = backups_dir(&app, &root)?;
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(BACKUP_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let (root, dir) = (root.clone(), dir.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                let now = now_secs();
+                if backup_due(&dir, now) { back_up(&root, &dir, keep, now) } else { Ok(None) }
+            })
+            .await;
+            if let Ok(Err(e)) = result {
+                eprintln!("[backups] Metadata backup failed: {}", e);
+            }
+        }
+    });
+    *backup_state.0.lock().map_err(|e| e.to_string())? = Some(handle);
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn stop_metadata_backups(backup_state: tauri::State<'_, BackupState>) -> Result<(), String> {
+    if let Some(handle) = backup_state.0.lock().map_err(|e| e.to_string())?.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+/// Metadata backups of a workspace, newest first.
+#[tauri::command]
+pub async fn list_metadata_backups(app: tauri::AppHandle, workspace_path: String) -> Result<Vec<MetadataBackup>, String> {
+    let dir = backups_dir(&app, &PathBuf::from(&workspace_path))?;
+    Ok(backup_files(&dir)
+        .into_iter()
+        .filter_map(|(id, path)| {
+            let size_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            match read_archive(&path) {
+                Ok(archive) => Some(MetadataBackup { id, created_at: archive.created_at, file_count: archive.files.len(), size_bytes }),
+                Err(e) => {
+                    eprintln!("[backups] {}", e);
+                    None
+                }
+            }
+        })
+        .collect())
+}
+
+/// Write every file of a backup back into the workspace as one undoable step. Files created
+/// since the backup are left alone. Returns the number of files restored.
+#[tauri::command]
+pub async fn restore_metadata_backup(
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+    workspace_path: String,
+    backup_id: String,
+) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let dir = backups_dir(&app, &root)?;
+    let (_, path) = backup_files(&dir)
+        .into_iter()
+        .find(|(id, _)| *id == backup_id)
+        .ok_or_else(|| format!("Backup {} not found", backup_id))?;
+    let archive = read_archive(&path)?;
+    let targets = archive
+        .files
+        .iter()
+        .map(|(rel, value)| Ok((restore_target(&root, rel)?, value)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let existed: Vec<bool> = targets.iter().map(|(path, _)| path.exists()).collect();
+    let paths: Vec<PathBuf> = targets.iter().map(|(path, _)| path.clone()).collect();
+    journal.track("Restore metadata backup", &paths, || {
+        for (path, value) in &targets {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            write_json_atomic(path, value)?;
+        }
+        Ok(())
+    })?;
+    for ((path, _), existed) in targets.iter().zip(existed) {
+        if let Some(change) = json_file_change(path, existed, true, ChangeOrigin::User, "restore_metadata_backup") {
+            emit_change(&app, change);
+        }
+    }
+    Ok(targets.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace(tmp: &TempDir) -> PathBuf {
+        let root = tmp.path().join("site");
+        fs::create_dir_all(root.join("final/.thumbs")).unwrap();
+        fs::create_dir_all(root.join(".data")).unwrap();
+        fs::write(root.join("galleries.json"), r#"[{"slug":"final"}]"#).unwrap();
+        fs::write(root.join("final/gallery-details.json"), r#"{"name":"Final","photos":[]}"#).unwrap();
+        fs::write(root.join("final/.thumbs/x.json"), "{}").unwrap();
+        fs::write(root.join(".data/publish-stamps.json"), "{}").unwrap();
+        fs::write(root.join("final/broken.json"), "{").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_collect_metadata() {
+        let tmp = TempDir::new().unwrap();
+        let files = collect_metadata(&workspace(&tmp)).unwrap();
+        assert_eq!(files.keys().collect::<Vec<_>>(), vec!["final/gallery-details.json", "galleries.json"]);
+    }
+
+    #[test]
+    fn test_back_up_and_retention() {
+        let tmp = TempDir::new().unwrap();
+        let root = workspace(&tmp);
+        let dir = tmp.path().join("backups");
+        let day = 24 * 60 * 60;
+
+        assert!(backup_due(&dir, 1_700_000_000));
+        let first = back_up(&root, &dir, 2, 1_700_000_000).unwrap().unwrap();
+        assert_eq!(first, "metadata-20231114T221320Z");
+        // Unchanged metadata doesn't produce another archive
+        assert_eq!(back_up(&root, &dir, 2, 1_700_000_000 + day).unwrap(), None);
+
+        fs::write(root.join("galleries.json"), r#"[{"slug":"final"},{"slug":"new"}]"#).unwrap();
+        back_up(&root, &dir, 2, 1_700_000_000 + day).unwrap().unwrap();
+        fs::write(root.join("galleries.json"), "[]").unwrap();
+        let third = back_up(&root, &dir, 2, 1_700_000_000 + 2 * day).unwrap().unwrap();
+
+        let kept: Vec<String> = backup_files(&dir).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0], third);
+        assert!(!kept.contains(&first));
+    }
+
+    #[test]
+    fn test_restore_target() {
+        let root = Path::new("/w");
+        assert_eq!(restore_target(root, "final/gallery-details.json").unwrap(), root.join("final/gallery-details.json"));
+        assert!(restore_target(root, "../etc/passwd.json").is_err());
+        assert!(restore_target(root, "/etc/x.json").is_err());
+        assert!(restore_target(root, "").is_err());
+    }
+
+    #[test]
+    fn test_validate_backup_retention() {
+        assert!(validate_backup_retention(&AppSettings::default()).is_ok());
+        assert!(validate_backup_retention(&AppSettings { metadata_backup_retention: Some(0), ..AppSettings::default() }).is_ok());
+        assert!(validate_backup_retention(&AppSettings { metadata_backup_retention: Some(1000), ..AppSettings::default() }).is_err());
+    }
+}
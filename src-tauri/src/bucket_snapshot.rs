@@ -0,0 +1,146 @@
+use crate::manifest::format_utc_timestamp;
+use crate::publish::{list_objects, object_exists, plan_publish, s3_client, PublishPlan};
+use crate::settings::{extract_bucket_name, get_credentials_from_keychain, normalize_s3_prefix, read_settings};
+use crate::site_template::{published_template, SiteTemplate};
+use crate::workspace::{read_json, write_json_atomic};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// `{workspace}/.data/bucket-snapshot.json`: the bucket listing a publish preview needs, recorded
+/// so `publish_simulate` can plan offline against it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketSnapshot {
+    pub recorded_at: String,
+    pub bucket: String,
+    pub s3_root: String,
+    /// Every object under `s3_root`: key → ETag.
+    pub objects: BTreeMap<String, String>,
+    /// `site-manifest.json` keys of enclosing site roots that existed (another site owning ours).
+    pub enclosing_manifests: Vec<String>,
+    /// The template recorded in the site's own manifest when the snapshot was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<SiteTemplate>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketSnapshotInfo {
+    pub recorded_at: String,
+    pub bucket: String,
+    pub s3_root: String,
+    pub object_count: usize,
+}
+
+impl From<&BucketSnapshot> for BucketSnapshotInfo {
+    fn from(snapshot: &BucketSnapshot) -> Self {
+        Self {
+            recorded_at: snapshot.recorded_at.clone(),
+            bucket: snapshot.bucket.clone(),
+            s3_root: snapshot.s3_root.clone(),
+            object_count: snapshot.objects.len(),
+        }
+    }
+}
+
+fn snapshot_path(root: &Path) -> PathBuf {
+    root.join(".data").join("bucket-snapshot.json")
+}
+
+fn load_snapshot(root: &Path) -> Result<Option<BucketSnapshot>, String> {
+    let path = snapshot_path(root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    serde_json::from_value(read_json(&path)?)
+        .map(Some)
+        .map_err(|e| format!("Invalid bucket-snapshot.json: {}", e))
+}
+
+/// The snapshot, checked against the bucket and site root a simulation is for.
+fn snapshot_for(root: &Path, bucket: &str, s3_root: &str) -> Result<BucketSnapshot, String> {
+    let snapshot = load_snapshot(root)?.ok_or("No bucket snapshot recorded for this workspace yet.")?;
+    if snapshot.bucket != bucket || snapshot.s3_root != s3_root {
+        return Err(format!(
+            "The bucket snapshot was recorded for {}/{}, not {}/{}. Record a new one.",
+            snapshot.bucket, snapshot.s3_root, bucket, s3_root
+        ));
+    }
+    Ok(snapshot)
+}
+
+/// List the configured bucket under the site root and save it as the workspace's bucket snapshot,
+/// replacing any earlier one. Needs AWS access; simulating against it afterwards doesn't.
+#[tauri::command]
+pub async fn record_bucket_snapshot(app: tauri::AppHandle, workspace_path: String) -> Result<BucketSnapshotInfo, String> {
+    let root = PathBuf::from(&workspace_path);
+    let settings = read_settings(&app)?;
+    let bucket = extract_bucket_name(&settings.bucket);
+    let s3_root = normalize_s3_prefix(&settings.s3_prefix)?;
+    let (key_id, secret) = get_credentials_from_keychain()?;
+    let client = s3_client(&key_id, &secret, &settings.region);
+
+    let objects = list_objects(&client, &bucket, &s3_root).await?.into_iter().map(|obj| (obj.key, obj.etag)).collect();
+    let mut enclosing_manifests = Vec::new();
+    for enclosing in crate::publish::enclosing_site_roots(&s3_root) {
+        let key = format!("{}site-manifest.json", enclosing);
+        if object_exists(&client, &bucket, &key).await? {
+            enclosing_manifests.push(key);
+        }
+    }
+    let template = published_template(&client, &bucket, &s3_root).await?;
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let snapshot = BucketSnapshot { recorded_at: format_utc_timestamp(secs), bucket, s3_root, objects, enclosing_manifests, template };
+    write_json_atomic(&snapshot_path(&root), &serde_json::to_value(&snapshot).map_err(|e| e.to_string())?)?;
+    Ok(BucketSnapshotInfo::from(&snapshot))
+}
+
+#[tauri::command]
+pub async fn get_bucket_snapshot_info(workspace_path: String) -> Result<Option<BucketSnapshotInfo>, String> {
+    Ok(load_snapshot(Path::new(&workspace_path))?.as_ref().map(BucketSnapshotInfo::from))
+}
+
+/// `publish_preview` without AWS: the plan compares the workspace against the recorded bucket
+/// snapshot. Budget and mirror checks are skipped, and the plan can't be executed. Works on
+/// workspaces opened read-only, since it only writes caches.
+#[tauri::command]
+pub async fn publish_simulate(
+    app: tauri::AppHandle,
+    folder_path: String,
+    bucket: String,
+    region: String,
+    s3_root: String,
+) -> Result<PublishPlan, String> {
+    let snapshot = snapshot_for(Path::new(&folder_path), &extract_bucket_name(&bucket), &normalize_s3_prefix(&s3_root)?)?;
+    plan_publish(app, folder_path, bucket, region, s3_root, Some(&snapshot)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_for() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        assert!(snapshot_for(root, "photos", "").unwrap_err().contains("No bucket snapshot"));
+
+        let snapshot = BucketSnapshot {
+            recorded_at: "2026-03-01T10:00:00Z".to_string(),
+            bucket: "photos".to_string(),
+            s3_root: "site/".to_string(),
+            objects: [("site/index.html".to_string(), "abc".to_string())].into_iter().collect(),
+            enclosing_manifests: Vec::new(),
+            template: None,
+        };
+        write_json_atomic(&snapshot_path(root), &serde_json::to_value(&snapshot).unwrap()).unwrap();
+        assert_eq!(snapshot_for(root, "photos", "site/").unwrap(), snapshot);
+        assert!(snapshot_for(root, "photos", "").unwrap_err().contains("Record a new one"));
+        assert_eq!(BucketSnapshotInfo::from(&snapshot).object_count, 1);
+    }
+}
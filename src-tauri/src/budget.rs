@@ -0,0 +1,142 @@
+use crate::manifest::format_utc_timestamp;
+use crate::settings::{partition_for_region, AppSettings};
+use aws_credential_types::Credentials;
+use aws_sdk_costexplorer::config::Region;
+use aws_sdk_costexplorer::types::{DateInterval, Dimension, DimensionValues, Expression, Granularity};
+use std::time::SystemTime;
+
+/// Cost Explorer service names of the spend the budget covers.
+const BUDGET_SERVICES: &[&str] = &["Amazon Simple Storage Service", "Amazon CloudFront"];
+const COST_METRIC: &str = "UnblendedCost";
+
+/// Region of the Cost Explorer API for the partition of the bucket's `region`.
+fn cost_explorer_region(region: &str) -> Result<&'static str, String> {
+    match partition_for_region(region) {
+        "aws-cn" => Ok("cn-northwest-1"),
+        "aws-us-gov" => Err("Cost Explorer isn't available in AWS GovCloud (US).".to_string()),
+        _ => Ok("us-east-1"),
+    }
+}
+
+/// (first of the month, today) as `YYYY-MM-DD` for a UTC time; `None` on the 1st, when the
+/// month has no complete day of costs yet (Cost Explorer's end date is exclusive).
+fn month_to_date(secs: u64) -> Option<(String, String)> {
+    let today = format_utc_timestamp(secs)[..10].to_string();
+    let start = format!("{}01", &today[..8]);
+    (start != today).then_some((start, today))
+}
+
+/// Month-to-date S3 and CloudFront spend in the account's billing currency.
+async fn month_to_date_spend(key_id: &str, secret: &str, region: &str) -> Result<f64, String> {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let Some((start, end)) = month_to_date(secs) else {
+        return Ok(0.0);
+    };
+    let config = aws_sdk_costexplorer::Config::builder()
+        .credentials_provider(Credentials::new(key_id, secret, None, None, "afterglow-manager"))
+        .region(Region::new(cost_explorer_region(region)?))
+        .behavior_version_latest()
+        .build();
+    let client = aws_sdk_costexplorer::Client::from_conf(config);
+
+    let services = BUDGET_SERVICES
+        .iter()
+        .fold(DimensionValues::builder().key(Dimension::Service), |builder, service| builder.values(*service))
+        .build();
+    let output = client
+        .get_cost_and_usage()
+        .time_period(
+            DateInterval::builder()
+                .start(start)
+                .end(end)
+                .build()
+                .map_err(|e| format!("Cost Explorer request error: {}", e))?,
+        )
+        .granularity(Granularity::Monthly)
+        .metrics(COST_METRIC)
+        .filter(Expression::builder().dimensions(services).build())
+        .send()
+        .await
+        .map_err(|e| format!("Cost Explorer error: {}", e))?;
+
+    let mut spend = 0.0;
+    for result in output.results_by_time() {
+        let amount = result.total().and_then(|total| total.get(COST_METRIC)).and_then(|metric| metric.amount());
+        if let Some(amount) = amount {
+            spend += amount.parse::<f64>().map_err(|e| format!("Invalid cost amount \"{}\": {}", amount, e))?;
+        }
+    }
+    Ok(spend)
+}
+
+/// Compare the spend against the budget: `Ok(None)` within it, `Ok(Some(warning))` over it,
+/// `Err` over it when `block` is set.
+fn evaluate_budget(spend: f64, budget: f64, block: bool) -> Result<Option<String>, String> {
+    if spend <= budget {
+        return Ok(None);
+    }
+    let message = format!(
+        "Month-to-date S3 and CloudFront spend is {:.2}, over the monthly budget of {:.2}.",
+        spend, budget
+    );
+    if block {
+        Err(format!("{} Publishing is blocked; raise the budget in Settings to continue.", message))
+    } else {
+        Ok(Some(message))
+    }
+}
+
+/// Pre-publish budget check, when `monthlyBudget` is set. Returns a warning for the preview,
+/// or an error when the spend is over budget and `blockOverBudget` is on. A failed Cost
+/// Explorer query only warns.
+pub(crate) async fn check_budget(settings: &AppSettings, key_id: &str, secret: &str) -> Result<Option<String>, String> {
+    let Some(budget) = settings.monthly_budget else {
+        return Ok(None);
+    };
+    match month_to_date_spend(key_id, secret, &settings.region).await {
+        Ok(spend) => evaluate_budget(spend, budget, settings.block_over_budget),
+        Err(e) => Ok(Some(format!("Couldn't check the monthly budget: {}", e))),
+    }
+}
+
+/// Check the budget settings before saving.
+pub(crate) fn validate_budget(settings: &AppSettings) -> Result<(), String> {
+    match settings.monthly_budget {
+        Some(budget) if !budget.is_finite() || budget < 0.0 => {
+            Err("The monthly budget must be a positive amount.".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_to_date() {
+        // 2024-06-15T12:00:00Z
+        assert_eq!(month_to_date(1_718_452_800), Some(("2024-06-01".to_string(), "2024-06-15".to_string())));
+        // 2024-06-01T12:00:00Z
+        assert_eq!(month_to_date(1_717_243_200), None);
+    }
+
+    #[test]
+    fn test_evaluate_budget() {
+        assert_eq!(evaluate_budget(4.5, 5.0, true), Ok(None));
+        let warning = evaluate_budget(5.25, 5.0, false).unwrap().unwrap();
+        assert!(warning.contains("5.25") && warning.contains("5.00"));
+        assert!(evaluate_budget(5.25, 5.0, true).unwrap_err().contains("blocked"));
+    }
+
+    #[test]
+    fn test_budget_settings() {
+        assert!(cost_explorer_region("us-gov-west-1").is_err());
+        assert_eq!(cost_explorer_region("cn-north-1"), Ok("cn-northwest-1"));
+        assert!(validate_budget(&AppSettings { monthly_budget: Some(10.0), ..AppSettings::default() }).is_ok());
+        assert!(validate_budget(&AppSettings { monthly_budget: Some(-1.0), ..AppSettings::default() }).is_err());
+    }
+}
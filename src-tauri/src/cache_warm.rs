@@ -0,0 +1,130 @@
+use crate::health::site_http_client;
+use crate::settings::normalize_site_url;
+use crate::thumbnails::ThumbnailSpec;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Thumbnails warmed per gallery when `cacheWarmThumbnails` is unset.
+pub(crate) const DEFAULT_WARM_THUMBNAILS: u32 = 12;
+/// How long warm-up waits for the invalidation to complete before giving up.
+const INVALIDATION_WAIT: Duration = Duration::from_secs(300);
+const INVALIDATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// URLs fetched through the distribution after an invalidation: the index page, the site
+/// manifest, then the first `per_gallery` thumbnails of each gallery (its cover first), in
+/// gallery order.
+pub(crate) fn warm_up_urls(
+    site_url: &str,
+    s3_root: &str,
+    specs: &[ThumbnailSpec],
+    per_gallery: usize,
+) -> Result<Vec<String>, String> {
+    let site_url = normalize_site_url(site_url)?;
+    let mut urls = vec![site_url.clone(), format!("{}site-manifest.json", site_url)];
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for spec in specs {
+        let count = counts.entry(spec.slug.as_str()).or_default();
+        if *count >= per_gallery {
+            continue;
+        }
+        *count += 1;
+        if let Some(rel) = spec.s3_key.strip_prefix(s3_root) {
+            urls.push(format!("{}{}", site_url, rel));
+        }
+    }
+    Ok(urls)
+}
+
+/// Wait until CloudFront reports the invalidation `Completed`, so warming fetches the new files
+/// rather than re-caching the old ones. False on error or after five minutes.
+pub(crate) async fn wait_for_invalidation(
+    client: &aws_sdk_cloudfront::Client,
+    distribution_id: &str,
+    invalidation_id: &str,
+) -> bool {
+    let started = Instant::now();
+    loop {
+        let status = client
+            .get_invalidation()
+            .distribution_id(distribution_id)
+            .id(invalidation_id)
+            .send()
+            .await
+            .map(|output| output.invalidation().map(|i| i.status().to_string()).unwrap_or_default());
+        match status {
+            Ok(status) if status == "Completed" => return true,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("[publish] Failed to read invalidation {}: {}", invalidation_id, e);
+                return false;
+            }
+        }
+        if started.elapsed() >= INVALIDATION_WAIT {
+            eprintln!("[publish] Invalidation {} still in progress; skipping cache warm-up", invalidation_id);
+            return false;
+        }
+        tokio::time::sleep(INVALIDATION_POLL_INTERVAL).await;
+    }
+}
+
+/// Request each URL once so the edge caches it, calling `on_progress(current_1based, total, url)`
+/// before each request. Failures are logged, not returned: warming never fails a publish.
+/// Returns the number of URLs that answered 2xx.
+pub(crate) async fn warm_cache(urls: &[String], on_progress: impl Fn(usize, usize, &str)) -> usize {
+    let client = match site_http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[publish] Cache warm-up skipped: {}", e);
+            return 0;
+        }
+    };
+    let mut warmed = 0;
+    for (i, url) in urls.iter().enumerate() {
+        on_progress(i + 1, urls.len(), url);
+        // Read the whole body: CloudFront only caches a response that was fully transferred
+        match client.get(url).send().await {
+            Ok(response) if response.status().is_success() => {
+                if response.bytes().await.is_ok() {
+                    warmed += 1;
+                }
+            }
+            Ok(response) => eprintln!("[publish] Cache warm-up: HTTP {} for {}", response.status(), url),
+            Err(e) => eprintln!("[publish] Cache warm-up failed for {}: {:?}", url, e),
+        }
+    }
+    warmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn spec(slug: &str, name: &str) -> ThumbnailSpec {
+        ThumbnailSpec {
+            source_path: PathBuf::new(),
+            dest_path: PathBuf::new(),
+            s3_key: format!("site/galleries/{}/.thumbs/{}", slug, name),
+            slug: slug.to_string(),
+            thumb_filename: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_warm_up_urls() {
+        let specs = vec![spec("a", "cover.webp"), spec("a", "01.webp"), spec("a", "02.webp"), spec("b", "01.webp")];
+        let urls = warm_up_urls("https://photos.example.com", "site/", &specs, 2).unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                "https://photos.example.com/",
+                "https://photos.example.com/site-manifest.json",
+                "https://photos.example.com/galleries/a/.thumbs/cover.webp",
+                "https://photos.example.com/galleries/a/.thumbs/01.webp",
+                "https://photos.example.com/galleries/b/.thumbs/01.webp",
+            ]
+        );
+        assert_eq!(warm_up_urls("https://photos.example.com/", "site/", &specs, 0).unwrap().len(), 2);
+        assert!(warm_up_urls(" ", "site/", &specs, 2).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+use std::path::Path;
+use tauri::Emitter;
+
+/// Who caused a change: an edit in the app, the filesystem watcher, or an import.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOrigin {
+    User,
+    Watcher,
+    Import,
+}
+
+/// Payload of the `workspace-changed` event, emitted once per changed entity.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceChange {
+    /// "galleryIndex" (`galleries.json`, id ""), "galleryDetails" (id = slug), "photo" and
+    /// "image" (id = "{slug}/{filename}", tracked entry vs file on disk) or "directory" (id = slug).
+    pub entity_type: String,
+    pub id: String,
+    /// "created", "updated" or "removed".
+    pub change_kind: String,
+    pub origin: ChangeOrigin,
+    /// Command that made the change ("watcher" for filesystem events), so the frontend can skip
+    /// echoes of its own writes.
+    pub command: String,
+}
+
+impl WorkspaceChange {
+    pub(crate) fn new(entity_type: &str, id: &str, change_kind: &str, origin: ChangeOrigin, command: &str) -> Self {
+        Self {
+            entity_type: entity_type.to_string(),
+            id: id.to_string(),
+            change_kind: change_kind.to_string(),
+            origin,
+            command: command.to_string(),
+        }
+    }
+}
+
+/// The change a write to a workspace JSON file represents, or `None` for other files.
+pub(crate) fn json_file_change(
+    path: &Path,
+    existed: bool,
+    exists: bool,
+    origin: ChangeOrigin,
+    command: &str,
+) -> Option<WorkspaceChange> {
+    let kind = match (existed, exists) {
+        (false, true) => "created",
+        (true, false) => "removed",
+        _ => "updated",
+    };
+    match path.file_name()?.to_str()? {
+        "galleries.json" => Some(WorkspaceChange::new("galleryIndex", "", kind, origin, command)),
+        "gallery-details.json" => {
+            let slug = path.parent()?.file_name()?.to_string_lossy();
+            Some(WorkspaceChange::new("galleryDetails", &slug, kind, origin, command))
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn emit_change(app: &tauri::AppHandle, change: WorkspaceChange) {
+    crate::auto_publish::note_change(app);
+    let _ = app.emit("workspace-changed", change);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_file_change() {
+        let change = json_file_change(Path::new("/w/galleries.json"), true, true, ChangeOrigin::User, "write_json_file");
+        assert_eq!(change, Some(WorkspaceChange::new("galleryIndex", "", "updated", ChangeOrigin::User, "write_json_file")));
+        let change = json_file_change(Path::new("/w/sunset/gallery-details.json"), false, true, ChangeOrigin::Import, "adopt_site");
+        assert_eq!(change.unwrap().id, "sunset");
+        let change = json_file_change(Path::new("/w/sunset/gallery-details.json"), true, false, ChangeOrigin::User, "undo_last");
+        assert_eq!(change.unwrap().change_kind, "removed");
+        assert!(json_file_change(Path::new("/w/.data/metadata.json"), true, true, ChangeOrigin::User, "x").is_none());
+    }
+
+    #[test]
+    fn test_workspace_change_serialization() {
+        let json = serde_json::to_value(WorkspaceChange::new("photo", "a/01.jpg", "updated", ChangeOrigin::Watcher, "watcher")).unwrap();
+        assert_eq!(json["entityType"], "photo");
+        assert_eq!(json["changeKind"], "updated");
+        assert_eq!(json["origin"], "watcher");
+    }
+}
@@ -0,0 +1,258 @@
+use crate::i18n::{error, ErrorCode};
+use crate::settings::{extract_distribution_id, get_credentials_from_keychain, partition_for_region, read_settings};
+use aws_credential_types::Credentials;
+use aws_sdk_cloudfront::types::{
+    FrameOptionsList, ReferrerPolicyList, ResponseHeadersPolicyConfig, ResponseHeadersPolicyContentTypeOptions,
+    ResponseHeadersPolicyCustomHeader, ResponseHeadersPolicyCustomHeadersConfig, ResponseHeadersPolicyFrameOptions,
+    ResponseHeadersPolicyReferrerPolicy, ResponseHeadersPolicySecurityHeadersConfig,
+    ResponseHeadersPolicyStrictTransportSecurity, ResponseHeadersPolicyType,
+};
+use aws_sdk_s3::config::Region;
+use serde::Serialize;
+
+/// One year, the minimum HSTS max-age accepted by browser preload lists.
+const DEFAULT_HSTS_MAX_AGE_SECS: i32 = 31_536_000;
+/// Five minutes: published JSON is fetched with `?v=` stamps, so a short shared max-age only
+/// delays visitors who load `index.html` / `site-manifest.json` right after a publish.
+const DEFAULT_CACHE_MAX_AGE_SECS: i32 = 300;
+
+/// Region of the CloudFront API for the partition of the bucket's `region`: CloudFront is a
+/// global service whose API lives in us-east-1, or cn-northwest-1 in the China partition.
+/// GovCloud (US) has no CloudFront.
+fn cloudfront_region(region: &str) -> Result<&'static str, String> {
+    match partition_for_region(region) {
+        "aws-cn" => Ok("cn-northwest-1"),
+        "aws-us-gov" => Err("CloudFront isn't available in AWS GovCloud (US).".to_string()),
+        _ => Ok("us-east-1"),
+    }
+}
+
+/// CloudFront client for the partition of the bucket's `region`.
+pub(crate) fn cloudfront_client(key_id: &str, secret: &str, region: &str) -> Result<aws_sdk_cloudfront::Client, String> {
+    let config = aws_sdk_cloudfront::Config::builder()
+        .credentials_provider(Credentials::new(key_id, secret, None, None, "afterglow-manager"))
+        .region(Region::new(cloudfront_region(region)?))
+        .behavior_version_latest()
+        .build();
+    Ok(aws_sdk_cloudfront::Client::from_conf(config))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseHeadersResult {
+    pub policy_id: String,
+    pub policy_name: String,
+    /// False when an existing policy of the same name was updated.
+    pub created: bool,
+    /// False when the distribution already used this policy.
+    pub attached: bool,
+}
+
+/// The headers the managed policy sets.
+#[derive(Debug, Clone, PartialEq)]
+struct ResponseHeadersSpec {
+    hsts_max_age_secs: i32,
+    cache_max_age_secs: i32,
+}
+
+impl ResponseHeadersSpec {
+    fn new(hsts_max_age_secs: Option<i32>, cache_max_age_secs: Option<i32>) -> Result<Self, String> {
+        let spec = Self {
+            hsts_max_age_secs: hsts_max_age_secs.unwrap_or(DEFAULT_HSTS_MAX_AGE_SECS),
+            cache_max_age_secs: cache_max_age_secs.unwrap_or(DEFAULT_CACHE_MAX_AGE_SECS),
+        };
+        if spec.hsts_max_age_secs <= 0 {
+            return Err("HSTS max-age must be greater than zero.".to_string());
+        }
+        if spec.cache_max_age_secs < 0 {
+            return Err("Cache max-age cannot be negative.".to_string());
+        }
+        Ok(spec)
+    }
+
+    fn cache_control(&self) -> String {
+        format!("public, max-age={}", self.cache_max_age_secs)
+    }
+}
+
+/// Policy names must be unique per account; one policy per distribution.
+fn policy_name(dist_id: &str) -> String {
+    format!("afterglow-{}", dist_id)
+}
+
+fn build_policy_config(name: &str, spec: &ResponseHeadersSpec) -> Result<ResponseHeadersPolicyConfig, String> {
+    let err = |e: aws_sdk_cloudfront::error::BuildError| format!("Invalid response headers policy: {}", e);
+    let security = ResponseHeadersPolicySecurityHeadersConfig::builder()
+        .strict_transport_security(
+            ResponseHeadersPolicyStrictTransportSecurity::builder()
+                .r#override(true)
+                .include_subdomains(false)
+                .preload(false)
+                .access_control_max_age_sec(spec.hsts_max_age_secs)
+                .build()
+                .map_err(err)?,
+        )
+        .content_type_options(ResponseHeadersPolicyContentTypeOptions::builder().r#override(true).build().map_err(err)?)
+        .frame_options(
+            ResponseHeadersPolicyFrameOptions::builder()
+                .r#override(true)
+                .frame_option(FrameOptionsList::Deny)
+                .build()
+                .map_err(err)?,
+        )
+        .referrer_policy(
+            ResponseHeadersPolicyReferrerPolicy::builder()
+                .r#override(true)
+                .referrer_policy(ReferrerPolicyList::StrictOriginWhenCrossOrigin)
+                .build()
+                .map_err(err)?,
+        )
+        .build();
+    // Not overriding: a Cache-Control set on an individual S3 object still wins.
+    let cache_control = ResponseHeadersPolicyCustomHeader::builder()
+        .header("Cache-Control")
+        .value(spec.cache_control())
+        .r#override(false)
+        .build()
+        .map_err(err)?;
+    ResponseHeadersPolicyConfig::builder()
+        .name(name)
+        .comment("Managed by AfterGlow Manager")
+        .security_headers_config(security)
+        .custom_headers_config(
+            ResponseHeadersPolicyCustomHeadersConfig::builder()
+                .quantity(1)
+                .items(cache_control)
+                .build()
+                .map_err(err)?,
+        )
+        .build()
+        .map_err(err)
+}
+
+/// Create (or update) a response headers policy with HSTS, `X-Content-Type-Options`,
+/// `X-Frame-Options`, `Referrer-Policy` and a default `Cache-Control`, and attach it to the
+/// configured distribution's default cache behavior.
+#[tauri::command]
+pub async fn configure_response_headers(
+    app: tauri::AppHandle,
+    hsts_max_age_secs: Option<i32>,
+    cache_max_age_secs: Option<i32>,
+) -> Result<ResponseHeadersResult, String> {
+    let spec = ResponseHeadersSpec::new(hsts_max_age_secs, cache_max_age_secs)?;
+    let settings = read_settings(&app)?;
+    let dist_id = extract_distribution_id(&settings.cloud_front_distribution_id);
+    if dist_id.is_empty() {
+        return Err(error(ErrorCode::DistributionNotConfigured, &[]));
+    }
+    let (key_id, secret) = get_credentials_from_keychain()?;
+    let client = cloudfront_client(&key_id, &secret, &settings.region)?;
+    let name = policy_name(&dist_id);
+    let config = build_policy_config(&name, &spec)?;
+
+    // Reuse our policy if a previous run created it
+    let existing = client
+        .list_response_headers_policies()
+        .r#type(ResponseHeadersPolicyType::Custom)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to list response headers policies: {}", e))?;
+    let existing_id = existing
+        .response_headers_policy_list()
+        .map(|list| list.items())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|summary| summary.response_headers_policy())
+        .find(|policy| policy.response_headers_policy_config().map(|c| c.name()) == Some(name.as_str()))
+        .map(|policy| policy.id().to_string());
+
+    let (policy_id, created) = match existing_id {
+        Some(id) => {
+            let current = client
+                .get_response_headers_policy()
+                .id(&id)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to read response headers policy: {}", e))?;
+            client
+                .update_response_headers_policy()
+                .id(&id)
+                .if_match(current.e_tag().unwrap_or_default())
+                .response_headers_policy_config(config)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to update response headers policy: {}", e))?;
+            (id, false)
+        }
+        None => {
+            let output = client
+                .create_response_headers_policy()
+                .response_headers_policy_config(config)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to create response headers policy: {}", e))?;
+            let id = output
+                .response_headers_policy()
+                .map(|p| p.id().to_string())
+                .ok_or("CloudFront did not return the new policy ID.")?;
+            (id, true)
+        }
+    };
+
+    // Attach to the distribution's default cache behavior
+    let dist = client
+        .get_distribution_config()
+        .id(&dist_id)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to read distribution config: {}", e))?;
+    let mut dist_config = dist
+        .distribution_config()
+        .cloned()
+        .ok_or("CloudFront returned no distribution config.")?;
+    let behavior = dist_config
+        .default_cache_behavior
+        .as_mut()
+        .ok_or("Distribution has no default cache behavior.")?;
+    let attached = behavior.response_headers_policy_id.as_deref() != Some(policy_id.as_str());
+    if attached {
+        behavior.response_headers_policy_id = Some(policy_id.clone());
+        client
+            .update_distribution()
+            .id(&dist_id)
+            .if_match(dist.e_tag().unwrap_or_default())
+            .distribution_config(dist_config)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to attach policy to distribution: {}", e))?;
+    }
+
+    Ok(ResponseHeadersResult { policy_id, policy_name: name, created, attached })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_headers_spec_defaults_and_validation() {
+        let spec = ResponseHeadersSpec::new(None, None).unwrap();
+        assert_eq!(spec.hsts_max_age_secs, 31_536_000);
+        assert_eq!(spec.cache_control(), "public, max-age=300");
+        assert_eq!(ResponseHeadersSpec::new(None, Some(0)).unwrap().cache_control(), "public, max-age=0");
+        assert!(ResponseHeadersSpec::new(Some(0), None).is_err());
+        assert!(ResponseHeadersSpec::new(None, Some(-1)).is_err());
+    }
+
+    #[test]
+    fn test_cloudfront_region_by_partition() {
+        assert_eq!(cloudfront_region("eu-west-1").unwrap(), "us-east-1");
+        assert_eq!(cloudfront_region("cn-north-1").unwrap(), "cn-northwest-1");
+        assert!(cloudfront_region("us-gov-east-1").is_err());
+    }
+
+    #[test]
+    fn test_policy_name() {
+        assert_eq!(policy_name("E1ABC2DEF3GH"), "afterglow-E1ABC2DEF3GH");
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Comment widget shown under each published gallery. `provider` is "giscus", "cactus" or
+/// empty for none; the other fields configure the chosen provider.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommentsSettings {
+    pub provider: String,
+    /// giscus: GitHub repository holding the discussions, "owner/name".
+    pub giscus_repo: String,
+    pub giscus_repo_id: String,
+    /// giscus: discussion category new threads are created in.
+    pub giscus_category: String,
+    pub giscus_category_id: String,
+    /// Cactus Comments: site name registered with the Cactus appservice.
+    pub cactus_site_name: String,
+    /// Cactus Comments: Matrix homeserver used for guest access, e.g. "https://matrix.cactus.chat:8448".
+    pub cactus_homeserver_url: String,
+    /// Cactus Comments: server name of the appservice, e.g. "cactus.chat".
+    pub cactus_server_name: String,
+}
+
+const GISCUS_ORIGIN: &str = "https://giscus.app";
+const CACTUS_ORIGIN: &str = "https://latest.cactus.chat";
+
+fn require(value: &str, label: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        return Err(format!("{} is required for comments.", label));
+    }
+    Ok(())
+}
+
+/// `https://host[:port]` of an https URL, or `None` when it isn't one.
+fn https_origin(url: &str) -> Option<String> {
+    let host = url.trim().strip_prefix("https://")?.split('/').next()?;
+    let valid = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':');
+    valid.then(|| format!("https://{}", host))
+}
+
+/// Check the provider settings before saving and publishing.
+pub(crate) fn validate_comments(comments: &CommentsSettings) -> Result<(), String> {
+    match comments.provider.as_str() {
+        "" => Ok(()),
+        "giscus" => {
+            let repo = comments.giscus_repo.trim();
+            let valid_repo = repo.split_once('/').is_some_and(|(owner, name)| {
+                [owner, name].iter().all(|part| {
+                    !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+                })
+            });
+            if !valid_repo {
+                return Err(format!("giscus repository \"{}\" must look like \"owner/name\".", repo));
+            }
+            require(&comments.giscus_repo_id, "The giscus repository ID")?;
+            require(&comments.giscus_category, "The giscus category")?;
+            require(&comments.giscus_category_id, "The giscus category ID")
+        }
+        "cactus" => {
+            require(&comments.cactus_site_name, "The Cactus site name")?;
+            if https_origin(&comments.cactus_homeserver_url).is_none() {
+                return Err(format!(
+                    "Cactus homeserver URL \"{}\" must be an https URL.",
+                    comments.cactus_homeserver_url.trim()
+                ));
+            }
+            require(&comments.cactus_server_name, "The Cactus server name")
+        }
+        other => Err(format!("Unknown comments provider \"{}\" (expected giscus or cactus).", other)),
+    }
+}
+
+/// The `comments` object published in a gallery's `gallery-details.json`: the provider, its
+/// settings and the thread id (the gallery's `commentsThreadId`, or its slug). `None` when no
+/// provider is configured or the gallery sets `"comments": false`.
+pub(crate) fn gallery_comments(
+    comments: &CommentsSettings,
+    slug: &str,
+    details: &serde_json::Value,
+) -> Option<serde_json::Value> {
+    if comments.provider.is_empty() || details.get("comments") == Some(&json!(false)) {
+        return None;
+    }
+    let thread_id = details
+        .get("commentsThreadId")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or(slug);
+    match comments.provider.as_str() {
+        "giscus" => Some(json!({
+            "provider": "giscus",
+            "threadId": thread_id,
+            "repo": comments.giscus_repo.trim(),
+            "repoId": comments.giscus_repo_id.trim(),
+            "category": comments.giscus_category.trim(),
+            "categoryId": comments.giscus_category_id.trim(),
+        })),
+        "cactus" => Some(json!({
+            "provider": "cactus",
+            "threadId": thread_id,
+            "siteName": comments.cactus_site_name.trim(),
+            "homeserverUrl": comments.cactus_homeserver_url.trim(),
+            "serverName": comments.cactus_server_name.trim(),
+        })),
+        _ => None,
+    }
+}
+
+/// Origins the comment widget loads from, added to the site's Content-Security-Policy.
+pub(crate) fn comments_csp_sources(comments: &CommentsSettings) -> Vec<String> {
+    match comments.provider.as_str() {
+        "giscus" => vec![GISCUS_ORIGIN.to_string()],
+        "cactus" => std::iter::once(CACTUS_ORIGIN.to_string())
+            .chain(https_origin(&comments.cactus_homeserver_url))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn giscus() -> CommentsSettings {
+        CommentsSettings {
+            provider: "giscus".to_string(),
+            giscus_repo: "jo/photos".to_string(),
+            giscus_repo_id: "R_kgDOabc".to_string(),
+            giscus_category: "Galleries".to_string(),
+            giscus_category_id: "DIC_kwDOabc".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_comments() {
+        assert!(validate_comments(&CommentsSettings::default()).is_ok());
+        assert!(validate_comments(&giscus()).is_ok());
+        assert!(validate_comments(&CommentsSettings { giscus_repo: "photos".to_string(), ..giscus() }).is_err());
+        assert!(validate_comments(&CommentsSettings { giscus_category_id: " ".to_string(), ..giscus() }).is_err());
+        assert!(validate_comments(&CommentsSettings { provider: "disqus".to_string(), ..giscus() }).is_err());
+
+        let cactus = CommentsSettings {
+            provider: "cactus".to_string(),
+            cactus_site_name: "photos".to_string(),
+            cactus_homeserver_url: "https://matrix.cactus.chat:8448".to_string(),
+            cactus_server_name: "cactus.chat".to_string(),
+            ..Default::default()
+        };
+        assert!(validate_comments(&cactus).is_ok());
+        assert_eq!(comments_csp_sources(&cactus), vec![CACTUS_ORIGIN, "https://matrix.cactus.chat:8448"]);
+        let insecure = CommentsSettings { cactus_homeserver_url: "http://matrix.example".to_string(), ..cactus };
+        assert!(validate_comments(&insecure).is_err());
+    }
+
+    #[test]
+    fn test_gallery_comments() {
+        let config = gallery_comments(&giscus(), "sunset", &json!({"photos": []})).unwrap();
+        assert_eq!(config["provider"], "giscus");
+        assert_eq!(config["threadId"], "sunset");
+        assert_eq!(config["repo"], "jo/photos");
+
+        let renamed = json!({"commentsThreadId": "sunset-2023"});
+        assert_eq!(gallery_comments(&giscus(), "sunset", &renamed).unwrap()["threadId"], "sunset-2023");
+        assert!(gallery_comments(&giscus(), "sunset", &json!({"comments": false})).is_none());
+        assert!(gallery_comments(&CommentsSettings::default(), "sunset", &json!({})).is_none());
+    }
+}
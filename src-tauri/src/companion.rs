@@ -0,0 +1,628 @@
+use crate::activity::record_activity;
+use crate::automation::tokens_match;
+use crate::encryption::readable_original;
+use crate::i18n::{error, split_code, ErrorCode};
+use crate::manifest::format_utc_timestamp;
+use crate::publish::content_type_for_extension;
+use crate::settings::{read_settings, AppSettings};
+use crate::workspace::{read_galleries, read_gallery_details, read_json, write_json_atomic};
+use serde::{Deserialize, Serialize};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Port the companion server listens on when `companionPort` isn't set.
+pub(crate) const DEFAULT_COMPANION_PORT: u16 = 47_800;
+/// How long a pairing code shown on the desktop can be used.
+const PAIRING_CODE_SECS: u64 = 300;
+/// Wrong codes allowed before the pairing code is discarded.
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The running companion server, the workspace it serves and the active pairing code.
+pub struct CompanionState(pub Mutex<Companion>);
+
+#[derive(Default)]
+pub struct Companion {
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+    workspace: Option<PathBuf>,
+    pairing: Option<Pairing>,
+}
+
+struct Pairing {
+    code: String,
+    expires_at: u64,
+    attempts: u32,
+}
+
+/// Shown on the desktop while a device pairs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_at: String,
+}
+
+/// Where devices on the local network reach the companion server.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionStatus {
+    pub port: u16,
+    /// e.g. "http://192.168.1.20:47800/", the address the server listens on.
+    pub url: Option<String>,
+}
+
+/// An address of this computer the companion server can listen on, for the settings dialog.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInterface {
+    /// Interface name, e.g. "en0" or "Wi-Fi".
+    pub name: String,
+    pub address: String,
+    pub loopback: bool,
+}
+
+/// A paired device, as listed in settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionDevice {
+    pub id: String,
+    pub name: String,
+    pub paired_at: String,
+}
+
+/// `{app_data}/companion-devices.json` entry; only a hash of the device's token is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredDevice {
+    #[serde(flatten)]
+    device: CompanionDevice,
+    token_sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PairRequest {
+    code: String,
+    device_name: String,
+}
+
+/// Endpoints of the companion API.
+#[derive(Debug, PartialEq)]
+enum Route {
+    /// `POST /api/pair { code, deviceName }` → `{ deviceId, token }`; no token needed.
+    Pair,
+    /// `GET /api/galleries` → the gallery index.
+    Galleries,
+    /// `GET /api/galleries/{slug}` → the gallery's details.
+    Gallery(String),
+    /// `GET /api/galleries/{slug}/photos/{file}[?full=1]` → the cached thumbnail, or the
+    /// original with `full=1` (or when no thumbnail was generated yet).
+    Photo(String, String, bool),
+    /// `POST /api/publish` → previews and starts a publish with the saved settings.
+    Publish,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(status: u16, value: &serde_json::Value) -> Self {
+        Response { status, content_type: "application/json", body: value.to_string().into_bytes() }
+    }
+
+    /// `{ "error": message }`, plus `code` for coded errors (see `i18n::error`).
+    fn error(status: u16, message: &str) -> Self {
+        match split_code(message) {
+            (Some(code), message) => Response::json(status, &serde_json::json!({ "error": message, "code": code })),
+            (None, message) => Response::json(status, &serde_json::json!({ "error": message })),
+        }
+    }
+
+    /// A request refused before it reached a handler: malformed, too large or to an unknown route.
+    fn rejected(status: u16) -> Self {
+        Response::error(status, &error(ErrorCode::CompanionRequestRejected, &[("status", &status), ("reason", &status_text(status))]))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Check the companion settings before saving.
+pub(crate) fn validate_companion(settings: &AppSettings) -> Result<(), String> {
+    if let Some(port) = settings.companion_port.filter(|port| *port < 1024) {
+        return Err(error(ErrorCode::CompanionPortTooLow, &[("port", &port)]));
+    }
+    if let Some(address) = &settings.companion_address {
+        listen_address(Some(address))?;
+    }
+    Ok(())
+}
+
+/// The address to listen on: the configured one, or the LAN address. Never all interfaces.
+fn listen_address(configured: Option<&str>) -> Result<IpAddr, String> {
+    let Some(configured) = configured.map(str::trim).filter(|a| !a.is_empty()) else {
+        return lan_address().ok_or_else(|| error(ErrorCode::CompanionAddressUnavailable, &[]));
+    };
+    match configured.parse::<IpAddr>() {
+        Ok(ip) if !ip.is_unspecified() && !ip.is_multicast() => Ok(ip),
+        _ => Err(error(ErrorCode::CompanionAddressInvalid, &[("address", &configured)])),
+    }
+}
+
+fn devices_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
+    fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    Ok(data_dir.join("companion-devices.json"))
+}
+
+fn read_devices(path: &Path) -> Result<Vec<StoredDevice>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_value(read_json(path)?).map_err(|e| error(ErrorCode::CompanionDevicesInvalid, &[("error", &e)]))
+}
+
+fn write_devices(path: &Path, devices: &[StoredDevice]) -> Result<(), String> {
+    write_json_atomic(path, &serde_json::to_value(devices).map_err(|e| e.to_string())?)
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// The paired device presenting `Authorization: Bearer {token}`, if any.
+fn authorized_device(devices: &[StoredDevice], authorization: Option<&str>) -> Option<CompanionDevice> {
+    let token = authorization?.strip_prefix("Bearer ")?.trim();
+    let hash = token_hash(token);
+    devices.iter().find(|d| tokens_match(&hash, &d.token_sha256)).map(|d| d.device.clone())
+}
+
+/// Check a pairing attempt against the code shown on the desktop. The code is single-use and
+/// discarded after too many wrong attempts.
+fn check_pairing_code(pairing: &mut Option<Pairing>, code: &str, now: u64) -> Result<(), String> {
+    let Some(active) = pairing.as_mut() else {
+        return Err(error(ErrorCode::NoPairingInProgress, &[]));
+    };
+    if now > active.expires_at {
+        *pairing = None;
+        return Err(error(ErrorCode::PairingCodeExpired, &[]));
+    }
+    if !tokens_match(code.trim(), &active.code) {
+        active.attempts += 1;
+        if active.attempts >= MAX_PAIRING_ATTEMPTS {
+            *pairing = None;
+        }
+        return Err(error(ErrorCode::WrongPairingCode, &[]));
+    }
+    *pairing = None;
+    Ok(())
+}
+
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = segment.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// A single path segment naming a file or folder inside the workspace.
+fn safe_segment(segment: &str) -> Option<String> {
+    let decoded = percent_decode(segment)?;
+    if decoded.is_empty() || decoded.starts_with('.') || decoded.contains(['/', '\\']) {
+        return None;
+    }
+    Some(decoded)
+}
+
+/// Map a request to an endpoint. `Err` carries the HTTP status for unknown paths (404) and
+/// wrong methods (405).
+fn route(method: &str, target: &str) -> Result<Route, u16> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (expected, route) = match segments.as_slice() {
+        ["api", "pair"] => ("POST", Route::Pair),
+        ["api", "publish"] => ("POST", Route::Publish),
+        ["api", "galleries"] => ("GET", Route::Galleries),
+        ["api", "galleries", slug] => ("GET", Route::Gallery(safe_segment(slug).ok_or(404u16)?)),
+        ["api", "galleries", slug, "photos", file] => {
+            let full = query.split('&').any(|p| p == "full=1");
+            ("GET", Route::Photo(safe_segment(slug).ok_or(404u16)?, safe_segment(file).ok_or(404u16)?, full))
+        }
+        _ => return Err(404),
+    };
+    if method != expected {
+        return Err(405);
+    }
+    Ok(route)
+}
+
+/// Address other devices on the network can reach this computer at. Connecting a UDP socket
+/// sends nothing; it only selects the outgoing interface.
+fn lan_address() -> Option<IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    socket.local_addr().ok().map(|a| a.ip())
+}
+
+fn workspace(app: &tauri::AppHandle) -> Result<PathBuf, Response> {
+    let state = app.state::<CompanionState>();
+    let companion = state.0.lock().map_err(|e| Response::error(500, &e.to_string()))?;
+    companion.workspace.clone().ok_or_else(|| Response::error(503, &error(ErrorCode::CompanionNoWorkspace, &[])))
+}
+
+async fn pair(app: &tauri::AppHandle, body: &[u8]) -> Result<Response, Response> {
+    let request: PairRequest =
+        serde_json::from_slice(body).map_err(|e| Response::error(400, &error(ErrorCode::CompanionInvalidRequest, &[("error", &e)])))?;
+    let name = request.device_name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(Response::error(400, &error(ErrorCode::CompanionDeviceNameInvalid, &[])));
+    }
+    {
+        let state = app.state::<CompanionState>();
+        let mut companion = state.0.lock().map_err(|e| Response::error(500, &e.to_string()))?;
+        check_pairing_code(&mut companion.pairing, &request.code, now_secs()).map_err(|e| Response::error(403, &e))?;
+    }
+    let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let device = CompanionDevice {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        paired_at: format_utc_timestamp(now_secs()),
+    };
+    let path = devices_path(app).map_err(|e| Response::error(500, &e))?;
+    let mut devices = read_devices(&path).map_err(|e| Response::error(500, &e))?;
+    devices.push(StoredDevice { device: device.clone(), token_sha256: token_hash(&token) });
+    write_devices(&path, &devices).map_err(|e| Response::error(500, &e))?;
+    record_activity(app, "pairCompanionDevice", device.name.clone());
+    let _ = app.emit("companion-paired", device.clone());
+    Ok(Response::json(200, &serde_json::json!({ "deviceId": device.id, "token": token })))
+}
+
+async fn photo(root: &Path, slug: &str, file: &str, full: bool) -> Result<Response, Response> {
+    if !crate::is_image_file(Path::new(file)) {
+        return Err(Response::error(404, &error(ErrorCode::CompanionNotAnImage, &[])));
+    }
+    let original = readable_original(&root.join(slug).join(file));
+    let thumbnail = Path::new(file)
+        .file_stem()
+        .map(|stem| root.join(".data").join("thumbnails").join(slug).join(format!("{}.webp", stem.to_string_lossy())));
+    let path = match thumbnail {
+        Some(thumbnail) if !full && thumbnail.is_file() => thumbnail,
+        _ => original,
+    };
+    let body = tokio::fs::read(&path).await.map_err(|_| Response::error(404, &error(ErrorCode::CompanionPhotoNotFound, &[])))?;
+    Ok(Response { status: 200, content_type: content_type_for_extension(&path), body })
+}
+
+/// Preview with the saved settings, then run the upload in the background. Plans that need
+/// a delete confirmation are refused: those are confirmed on the desktop.
+async fn publish(app: &tauri::AppHandle, device: &CompanionDevice) -> Result<Response, Response> {
+    let root = workspace(app)?;
+    let settings = read_settings(app).map_err(|e| Response::error(500, &e))?;
+    let plan = crate::publish::publish_preview(
+        app.clone(),
+        root.to_string_lossy().into_owned(),
+        settings.bucket,
+        settings.region,
+        settings.s3_prefix,
+    )
+    .await
+    .map_err(|e| Response::error(409, &e))?;
+    if plan.delete_confirmation.is_some() || plan.mirror.as_ref().is_some_and(|mirror| mirror.delete_confirmation.is_some()) {
+        return Err(Response::error(409, &error(ErrorCode::CompanionDeleteLimitExceeded, &[])));
+    }
+    if plan.delete_categories.risky_count() > 0 {
+        return Err(Response::error(409, &error(ErrorCode::CompanionRiskyDeletes, &[])));
+    }
+    eprintln!("[companion] {} started a publish", device.name);
+    let app_handle = app.clone();
+    let plan_id = plan.plan_id.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::publish::publish_execute(app_handle, plan_id, None, None).await {
+            eprintln!("[companion] Publish failed: {}", e);
+        }
+    });
+    Ok(Response::json(
+        202,
+        &serde_json::json!({ "planId": plan.plan_id, "toUpload": plan.to_upload.len(), "toDelete": plan.to_delete.len() }),
+    ))
+}
+
+async fn handle(app: &tauri::AppHandle, route: Route, authorization: Option<&str>, body: &[u8]) -> Result<Response, Response> {
+    let authorize = || -> Result<CompanionDevice, Response> {
+        let devices = devices_path(app).and_then(|path| read_devices(&path)).map_err(|e| Response::error(500, &e))?;
+        authorized_device(&devices, authorization).ok_or_else(|| Response::error(401, &error(ErrorCode::CompanionNotPaired, &[])))
+    };
+    match route {
+        Route::Pair => pair(app, body).await,
+        Route::Galleries => {
+            authorize()?;
+            let galleries = read_galleries(&workspace(app)?).map_err(|e| Response::error(500, &e))?;
+            Ok(Response::json(200, &serde_json::Value::Array(galleries)))
+        }
+        Route::Gallery(slug) => {
+            authorize()?;
+            match read_gallery_details(&workspace(app)?, &slug).map_err(|e| Response::error(500, &e))? {
+                Some(details) => Ok(Response::json(200, &details)),
+                None => Err(Response::error(404, &error(ErrorCode::CompanionGalleryNotFound, &[]))),
+            }
+        }
+        Route::Photo(slug, file, full) => {
+            authorize()?;
+            photo(&workspace(app)?, &slug, &file, full).await
+        }
+        Route::Publish => publish(app, &authorize()?).await,
+    }
+}
+
+async fn write_response(stream: &mut TcpStream, response: Response) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+         Connection: close\r\n\r\n",
+        response.status,
+        status_text(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await
+}
+
+/// Read one HTTP/1.1 request: method, target, `Authorization` header and body.
+async fn read_request(stream: &mut TcpStream) -> Result<(String, String, Option<String>, Vec<u8>), u16> {
+    let mut reader = BufReader::new(AsyncReadExt::take(stream, (MAX_HEAD_BYTES + MAX_BODY_BYTES) as u64));
+    let mut line = String::new();
+    let mut head_bytes = reader.read_line(&mut line).await.map_err(|_| 400u16)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(400);
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+    let mut authorization = None;
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).await.map_err(|_| 400u16)?;
+        head_bytes += read;
+        if head_bytes > MAX_HEAD_BYTES {
+            return Err(413);
+        }
+        if read == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "authorization" => authorization = Some(value.trim().to_string()),
+                "content-length" => content_length = value.trim().parse().map_err(|_| 400u16)?,
+                _ => {}
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(413);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await.map_err(|_| 400u16)?;
+    Ok((method, target, authorization, body))
+}
+
+async fn serve_connection(app: tauri::AppHandle, mut stream: TcpStream) {
+    let request = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Ok(request) => request,
+        Err(_) => Err(400),
+    };
+    let response = match request {
+        Err(status) => Response::rejected(status),
+        Ok((method, target, authorization, body)) => match route(&method, &target) {
+            Err(status) => Response::rejected(status),
+            Ok(route) => handle(&app, route, authorization.as_deref(), &body).await.unwrap_or_else(|e| e),
+        },
+    };
+    let _ = write_response(&mut stream, response).await;
+}
+
+/// (Re)start the companion server for `workspacePath` (the open workspace, if any) when the
+/// `companionApi` setting is on. Returns where devices reach it, or None (after stopping any
+/// running server) when the setting is off. Listens on every interface over plain HTTP: only
+/// paired devices can read the workspace or publish.
+#[tauri::command]
+pub async fn start_companion_server(
+    app: tauri::AppHandle,
+    companion_state: tauri::State<'_, CompanionState>,
+    workspace_path: Option<String>,
+) -> Result<Option<CompanionStatus>, String> {
+    {
+        let mut companion = companion_state.0.lock().map_err(|e| e.to_string())?;
+        if let Some(handle) = companion.task.take() {
+            handle.abort();
+        }
+        companion.workspace = workspace_path.filter(|p| !p.is_empty()).map(PathBuf::from);
+    }
+    let settings = read_settings(&app)?;
+    if !settings.companion_api {
+        return Ok(None);
+    }
+    let port = settings.companion_port.unwrap_or(DEFAULT_COMPANION_PORT);
+    let address = SocketAddr::new(listen_address(settings.companion_address.as_deref())?, port);
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .map_err(|e| error(ErrorCode::ListenFailed, &[("address", &address), ("error", &e)]))?;
+    let app_handle = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tauri::async_runtime::spawn(serve_connection(app_handle.clone(), stream));
+        }
+    });
+    companion_state.0.lock().map_err(|e| e.to_string())?.task = Some(handle);
+    Ok(Some(CompanionStatus { port, url: Some(format!("http://{}/", address)) }))
+}
+
+#[tauri::command]
+pub async fn stop_companion_server(companion_state: tauri::State<'_, CompanionState>) -> Result<(), String> {
+    let mut companion = companion_state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(handle) = companion.task.take() {
+        handle.abort();
+    }
+    companion.pairing = None;
+    Ok(())
+}
+
+/// Create a 6-digit code for pairing a device, valid for 5 minutes and replacing any earlier one.
+#[tauri::command]
+pub async fn start_companion_pairing(companion_state: tauri::State<'_, CompanionState>) -> Result<PairingCode, String> {
+    let mut companion = companion_state.0.lock().map_err(|e| e.to_string())?;
+    if companion.task.is_none() {
+        return Err(error(ErrorCode::CompanionDisabled, &[]));
+    }
+    let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+    let expires_at = now_secs() + PAIRING_CODE_SECS;
+    companion.pairing = Some(Pairing { code: code.clone(), expires_at, attempts: 0 });
+    Ok(PairingCode { code, expires_at: format_utc_timestamp(expires_at) })
+}
+
+/// Addresses of this computer's network interfaces, for choosing where the companion server
+/// listens. IPv6 link-local addresses are left out: they can't be bound without a scope.
+#[tauri::command]
+pub async fn list_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
+    let mut interfaces: Vec<NetworkInterface> = if_addrs::get_if_addrs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|interface| !matches!(interface.ip(), IpAddr::V6(ip) if ip.segments()[0] & 0xffc0 == 0xfe80))
+        .map(|interface| NetworkInterface {
+            address: interface.ip().to_string(),
+            loopback: interface.is_loopback(),
+            name: interface.name,
+        })
+        .collect();
+    interfaces.sort_by(|a, b| (a.loopback, &a.name, &a.address).cmp(&(b.loopback, &b.name, &b.address)));
+    Ok(interfaces)
+}
+
+#[tauri::command]
+pub async fn list_companion_devices(app: tauri::AppHandle) -> Result<Vec<CompanionDevice>, String> {
+    Ok(read_devices(&devices_path(&app)?)?.into_iter().map(|d| d.device).collect())
+}
+
+/// Forget a paired device; its token stops working immediately.
+#[tauri::command]
+pub async fn revoke_companion_device(app: tauri::AppHandle, device_id: String) -> Result<(), String> {
+    let path = devices_path(&app)?;
+    let mut devices = read_devices(&path)?;
+    let Some(index) = devices.iter().position(|d| d.device.id == device_id) else {
+        return Err(error(ErrorCode::DeviceNotFound, &[("id", &device_id)]));
+    };
+    let removed = devices.remove(index);
+    write_devices(&path, &devices)?;
+    record_activity(&app, "revokeCompanionDevice", removed.device.name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("GET", "/api/galleries"), Ok(Route::Galleries));
+        assert_eq!(route("GET", "/api/galleries/cup-final"), Ok(Route::Gallery("cup-final".to_string())));
+        assert_eq!(
+            route("GET", "/api/galleries/cup-final/photos/IMG%200001.jpg?full=1"),
+            Ok(Route::Photo("cup-final".to_string(), "IMG 0001.jpg".to_string(), true))
+        );
+        assert_eq!(route("POST", "/api/publish"), Ok(Route::Publish));
+        assert_eq!(route("GET", "/api/publish"), Err(405));
+        // No CORS preflight: browsers on other origins get nothing
+        assert_eq!(route("OPTIONS", "/api/galleries"), Err(405));
+        assert_eq!(route("GET", "/api/galleries/..%2Fsecrets"), Err(404));
+        assert_eq!(route("GET", "/api/galleries/.data/photos/x.jpg"), Err(404));
+        assert_eq!(route("GET", "/settings.json"), Err(404));
+    }
+
+    #[test]
+    fn test_check_pairing_code() {
+        let pairing = || Some(Pairing { code: "123456".to_string(), expires_at: 1_000, attempts: 0 });
+
+        let mut active = pairing();
+        check_pairing_code(&mut active, "123456", 999).unwrap();
+        assert!(active.is_none(), "codes are single-use");
+        assert!(check_pairing_code(&mut active, "123456", 999).is_err());
+
+        let mut expired = pairing();
+        assert!(check_pairing_code(&mut expired, "123456", 1_001).is_err());
+
+        let mut guessed = pairing();
+        for _ in 0..MAX_PAIRING_ATTEMPTS {
+            assert!(check_pairing_code(&mut guessed, "000000", 999).is_err());
+        }
+        assert!(check_pairing_code(&mut guessed, "123456", 999).is_err(), "discarded after too many attempts");
+    }
+
+    #[test]
+    fn test_authorized_device() {
+        let device = CompanionDevice { id: "d1".to_string(), name: "Phone".to_string(), paired_at: String::new() };
+        let devices = vec![StoredDevice { device: device.clone(), token_sha256: token_hash("tok") }];
+        assert_eq!(authorized_device(&devices, Some("Bearer tok")), Some(device));
+        assert_eq!(authorized_device(&devices, Some("Bearer other")), None);
+        assert_eq!(authorized_device(&devices, Some("tok")), None);
+        assert_eq!(authorized_device(&devices, None), None);
+    }
+
+    #[test]
+    fn test_validate_companion() {
+        assert!(validate_companion(&AppSettings::default()).is_ok());
+        assert!(validate_companion(&AppSettings { companion_port: Some(8080), ..AppSettings::default() }).is_ok());
+        assert!(validate_companion(&AppSettings { companion_port: Some(80), ..AppSettings::default() }).is_err());
+        let address = |a: &str| AppSettings { companion_address: Some(a.to_string()), ..AppSettings::default() };
+        assert!(validate_companion(&address("192.168.1.20")).is_ok());
+        assert!(validate_companion(&address("::1")).is_ok());
+        assert!(validate_companion(&address("0.0.0.0")).unwrap_err().starts_with("[companion-address-invalid]"));
+        assert!(validate_companion(&address("::")).is_err());
+        assert!(validate_companion(&address("my-laptop.local")).is_err());
+    }
+}
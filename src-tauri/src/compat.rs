@@ -0,0 +1,246 @@
+use crate::changes::{emit_change, json_file_change, ChangeOrigin};
+use crate::encryption::ENCRYPTION_VERSION;
+use crate::journal::JournalState;
+use crate::metadata::METADATA_INDEX_VERSION;
+use crate::settings::{settings_path, SETTINGS_SCHEMA_VERSION};
+use crate::summary::SUMMARY_CACHE_VERSION;
+use crate::workspace::{read_json, write_json_atomic, DETAILS_SCHEMA_VERSION, GALLERIES_SCHEMA_VERSION};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file whose format version differs from what this app version reads and writes.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibilityItem {
+    /// "settings", "galleries", "galleryDetails", "summaryCache", "metadataIndex" or "encryption".
+    pub component: String,
+    /// Relative to the workspace; `settings.json` for the app settings.
+    pub path: String,
+    /// Version in the file (0 = written before versioning); None when it can't be parsed.
+    pub found: Option<u32>,
+    pub supported: u32,
+    /// "older", "newer" or "invalid".
+    pub status: String,
+    /// "migrate" (offered by `migrate_workspace`), "rebuild" (a cache, discarded and rebuilt
+    /// automatically), "readOnly" (only safe to browse) or "none".
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibilityReport {
+    /// "ok", "migrate" (older files this version can upgrade) or "readOnly" (files written by a
+    /// newer version, or unreadable, that this version must not rewrite).
+    pub outcome: String,
+    /// Files that aren't at the current version; current ones are left out.
+    pub items: Vec<CompatibilityItem>,
+}
+
+/// The version stored under `key`, 0 when absent. A legacy `galleries.json` is a bare array.
+fn file_version(path: &Path, key: &str) -> Option<u32> {
+    let value = read_json(path).ok()?;
+    if value.is_array() {
+        return Some(0);
+    }
+    let object = value.as_object()?;
+    match object.get(key) {
+        Some(version) => version.as_u64().and_then(|v| u32::try_from(v).ok()),
+        None => Some(0),
+    }
+}
+
+/// Compare `path`'s version with `supported`. Returns None when the file is missing or current.
+fn check_file(root: &Path, rel: &str, component: &str, key: &str, supported: u32, cache: bool) -> Option<CompatibilityItem> {
+    let path = root.join(rel);
+    if !path.exists() {
+        return None;
+    }
+    let found = file_version(&path, key);
+    let status = match found {
+        Some(v) if v == supported => return None,
+        Some(v) if v < supported => "older",
+        Some(_) => "newer",
+        None => "invalid",
+    };
+    let action = match (cache, status) {
+        (true, _) => "rebuild",
+        (false, "older") => "migrate",
+        _ => "readOnly",
+    };
+    Some(CompatibilityItem {
+        component: component.to_string(),
+        path: rel.to_string(),
+        found,
+        supported,
+        status: status.to_string(),
+        action: action.to_string(),
+    })
+}
+
+fn workspace_items(root: &Path) -> Result<Vec<CompatibilityItem>, String> {
+    let mut items = Vec::new();
+    items.extend(check_file(root, "galleries.json", "galleries", "schemaVersion", GALLERIES_SCHEMA_VERSION, false));
+
+    let mut slugs: Vec<String> = fs::read_dir(root)
+        .map_err(|e| format!("Cannot read {}: {}", root.display(), e))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    slugs.sort();
+    for slug in slugs {
+        let rel = format!("{}/gallery-details.json", slug);
+        items.extend(check_file(root, &rel, "galleryDetails", "schemaVersion", DETAILS_SCHEMA_VERSION, false));
+    }
+
+    items.extend(check_file(root, ".data/summary.json", "summaryCache", "version", SUMMARY_CACHE_VERSION, true));
+    items.extend(check_file(root, ".data/metadata-index.json", "metadataIndex", "version", METADATA_INDEX_VERSION, true));
+    // The encryption settings can't be regenerated: a different version locks the workspace out
+    items.extend(
+        check_file(root, ".data/encryption.json", "encryption", "version", ENCRYPTION_VERSION, false)
+            .map(|item| CompatibilityItem { action: "readOnly".to_string(), ..item }),
+    );
+    Ok(items)
+}
+
+fn outcome(items: &[CompatibilityItem]) -> String {
+    if items.iter().any(|i| i.action == "readOnly") {
+        "readOnly"
+    } else if items.iter().any(|i| i.action == "migrate") {
+        "migrate"
+    } else {
+        "ok"
+    }
+    .to_string()
+}
+
+/// Check a workspace before opening it: the schema versions of the app settings, `galleries.json`,
+/// every `gallery-details.json` and the `.data` caches. The frontend offers `migrate_workspace`
+/// for "migrate" and falls back to opening read-only for "readOnly".
+#[tauri::command]
+pub async fn check_workspace_compatibility(app: tauri::AppHandle, workspace_path: String) -> Result<CompatibilityReport, String> {
+    let root = PathBuf::from(&workspace_path);
+    let mut items = Vec::new();
+    let settings = settings_path(&app)?;
+    if let Some(dir) = settings.parent() {
+        // Older settings are migrated when they load; newer ones only lose options on save
+        items.extend(
+            check_file(dir, "settings.json", "settings", "schemaVersion", SETTINGS_SCHEMA_VERSION, false)
+                .map(|item| CompatibilityItem { action: "none".to_string(), ..item }),
+        );
+    }
+    items.extend(workspace_items(&root)?);
+    Ok(CompatibilityReport { outcome: outcome(&items), items })
+}
+
+/// Upgrade `galleries.json` or a `gallery-details.json` to the current schema, as the frontend's
+/// `migrations.ts` does when it loads them.
+fn migrate_file(component: &str, value: serde_json::Value) -> Result<serde_json::Value, String> {
+    match (component, value) {
+        ("galleries", serde_json::Value::Array(galleries)) => {
+            Ok(serde_json::json!({ "schemaVersion": GALLERIES_SCHEMA_VERSION, "galleries": galleries }))
+        }
+        ("galleries", serde_json::Value::Object(object)) => {
+            let galleries = object.get("galleries").filter(|g| g.is_array()).cloned().unwrap_or(serde_json::json!([]));
+            Ok(serde_json::json!({ "schemaVersion": GALLERIES_SCHEMA_VERSION, "galleries": galleries }))
+        }
+        ("galleryDetails", serde_json::Value::Object(mut object)) => {
+            object.insert("schemaVersion".to_string(), DETAILS_SCHEMA_VERSION.into());
+            Ok(serde_json::Value::Object(object))
+        }
+        (component, _) => Err(format!("Can't migrate {}: unexpected format.", component)),
+    }
+}
+
+/// Apply the migrations offered by `check_workspace_compatibility` as one undoable step.
+/// Refuses when any file needs the read-only fallback. Returns the number of files migrated.
+#[tauri::command]
+pub async fn migrate_workspace(
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+    workspace_path: String,
+) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let items = workspace_items(&root)?;
+    if outcome(&items) == "readOnly" {
+        return Err("This workspace was written by a newer app version or has unreadable files. Open it read-only.".to_string());
+    }
+    let migrations: Vec<(PathBuf, &str)> = items
+        .iter()
+        .filter(|i| i.action == "migrate")
+        .map(|i| (root.join(&i.path), i.component.as_str()))
+        .collect();
+    let paths: Vec<PathBuf> = migrations.iter().map(|(path, _)| path.clone()).collect();
+    journal.track("Migrate workspace", &paths, || {
+        for (path, component) in &migrations {
+            write_json_atomic(path, &migrate_file(component, read_json(path)?)?)?;
+        }
+        Ok(())
+    })?;
+    for path in &paths {
+        if let Some(change) = json_file_change(path, true, true, ChangeOrigin::User, "migrate_workspace") {
+            emit_change(&app, change);
+        }
+    }
+    Ok(paths.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_workspace_items() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        write(root, "galleries.json", r#"[{"slug":"old"}]"#);
+        write(root, "old/gallery-details.json", r#"{"name":"Old","photos":[]}"#);
+        write(root, "current/gallery-details.json", r#"{"schemaVersion":1,"photos":[]}"#);
+        write(root, "future/gallery-details.json", r#"{"schemaVersion":7,"photos":[]}"#);
+        write(root, ".data/summary.json", r#"{"version":99,"summary":{}}"#);
+
+        let items = workspace_items(root).unwrap();
+        let summary: Vec<(&str, &str, &str)> =
+            items.iter().map(|i| (i.path.as_str(), i.status.as_str(), i.action.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("galleries.json", "older", "migrate"),
+                ("future/gallery-details.json", "newer", "readOnly"),
+                ("old/gallery-details.json", "older", "migrate"),
+                (".data/summary.json", "newer", "rebuild"),
+            ]
+        );
+        assert_eq!(outcome(&items), "readOnly");
+        assert_eq!(outcome(&items[2..]), "migrate");
+        assert_eq!(outcome(&items[3..]), "ok");
+    }
+
+    #[test]
+    fn test_invalid_file_is_read_only() {
+        let tmp = TempDir::new().unwrap();
+        write(tmp.path(), "galleries.json", "{ not json");
+        let items = workspace_items(tmp.path()).unwrap();
+        assert_eq!((items[0].found, items[0].status.as_str(), items[0].action.as_str()), (None, "invalid", "readOnly"));
+    }
+
+    #[test]
+    fn test_migrate_file() {
+        let galleries = migrate_file("galleries", serde_json::json!([{"slug": "a"}])).unwrap();
+        assert_eq!(galleries, serde_json::json!({"schemaVersion": 1, "galleries": [{"slug": "a"}]}));
+        let details = migrate_file("galleryDetails", serde_json::json!({"name": "A", "photos": []})).unwrap();
+        assert_eq!(details["schemaVersion"], 1);
+        assert_eq!(details["name"], "A");
+        assert!(migrate_file("galleryDetails", serde_json::json!([])).is_err());
+    }
+}
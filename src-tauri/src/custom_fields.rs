@@ -0,0 +1,143 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::journal::JournalState;
+use crate::workspace::{read_gallery_details, update_gallery_details};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+/// Key of the custom fields object in `gallery-details.json`.
+const CUSTOM_FIELDS_KEY: &str = "customFields";
+const MAX_CUSTOM_FIELDS: usize = 50;
+const MAX_KEY_LEN: usize = 64;
+const MAX_STRING_VALUE_LEN: usize = 2000;
+
+/// Field names start with a letter and use letters, digits, `_` and `-`, so templates can read
+/// them as `customFields.name`.
+fn validate_field_key(key: &str) -> Result<(), String> {
+    let mut chars = key.chars();
+    let valid = key.len() <= MAX_KEY_LEN
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!(
+            "Invalid custom field name \"{}\": use up to {} letters, digits, _ or -, starting with a letter.",
+            key, MAX_KEY_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Values are front-matter style scalars: strings, numbers and booleans.
+fn validate_field_value(key: &str, value: &Value) -> Result<(), String> {
+    match value {
+        Value::String(s) if s.chars().count() > MAX_STRING_VALUE_LEN => Err(format!(
+            "Custom field \"{}\" is longer than {} characters.",
+            key, MAX_STRING_VALUE_LEN
+        )),
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => Ok(()),
+        _ => Err(format!("Custom field \"{}\" must be text, a number or true/false.", key)),
+    }
+}
+
+/// The gallery's custom fields; empty when it has none.
+fn custom_fields(details: &Value) -> Map<String, Value> {
+    details
+        .get(CUSTOM_FIELDS_KEY)
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Set `key` to `value`, or remove it when `value` is null. The `customFields` object is
+/// dropped once empty so galleries without fields keep their JSON unchanged.
+fn apply_custom_field(details: &mut Value, key: &str, value: Value) -> Result<(), String> {
+    validate_field_key(key)?;
+    let obj = details
+        .as_object_mut()
+        .ok_or_else(|| "gallery-details.json is not an object".to_string())?;
+    let mut fields = match obj.get(CUSTOM_FIELDS_KEY) {
+        None => Map::new(),
+        Some(Value::Object(fields)) => fields.clone(),
+        Some(_) => return Err(format!("\"{}\" in gallery-details.json is not an object", CUSTOM_FIELDS_KEY)),
+    };
+    if value.is_null() {
+        fields.remove(key);
+    } else {
+        validate_field_value(key, &value)?;
+        if !fields.contains_key(key) && fields.len() >= MAX_CUSTOM_FIELDS {
+            return Err(format!("A gallery can have at most {} custom fields.", MAX_CUSTOM_FIELDS));
+        }
+        fields.insert(key.to_string(), value);
+    }
+    if fields.is_empty() {
+        obj.remove(CUSTOM_FIELDS_KEY);
+    } else {
+        obj.insert(CUSTOM_FIELDS_KEY.to_string(), Value::Object(fields));
+    }
+    Ok(())
+}
+
+/// User-defined fields of a gallery (`customFields` in its `gallery-details.json`), published
+/// as-is for site templates to use.
+#[tauri::command]
+pub async fn get_gallery_custom_fields(workspace_path: String, slug: String) -> Result<Map<String, Value>, String> {
+    let details = read_gallery_details(&PathBuf::from(&workspace_path), &slug)?
+        .ok_or_else(|| format!("gallery-details.json not found for '{}'", slug))?;
+    Ok(custom_fields(&details))
+}
+
+/// Set a gallery's custom field. Pass `value: null` to remove it.
+#[tauri::command]
+pub async fn set_gallery_custom_field(
+    workspace_path: String,
+    slug: String,
+    key: String,
+    value: Value,
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let label = if value.is_null() { "Remove custom field" } else { "Set custom field" };
+    journal.track(label, &[root.join(&slug).join("gallery-details.json")], || {
+        update_gallery_details(&root, &slug, |details| apply_custom_field(details, &key, value))
+    })?;
+    emit_change(&app, WorkspaceChange::new("galleryDetails", &slug, "updated", ChangeOrigin::User, "set_gallery_custom_field"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_custom_field() {
+        let mut details = json!({ "name": "Finals", "photos": [] });
+        apply_custom_field(&mut details, "client", json!("Riverside FC")).unwrap();
+        apply_custom_field(&mut details, "matchDay", json!(12)).unwrap();
+        apply_custom_field(&mut details, "sponsored", json!(true)).unwrap();
+        assert_eq!(details["customFields"], json!({ "client": "Riverside FC", "matchDay": 12, "sponsored": true }));
+        assert_eq!(custom_fields(&details).len(), 3);
+
+        assert!(apply_custom_field(&mut details, "1st", json!("x")).is_err());
+        assert!(apply_custom_field(&mut details, "has space", json!("x")).is_err());
+        assert!(apply_custom_field(&mut details, "nested", json!({ "a": 1 })).is_err());
+        assert!(apply_custom_field(&mut details, "list", json!(["a"])).is_err());
+
+        for key in ["client", "matchDay", "sponsored"] {
+            apply_custom_field(&mut details, key, Value::Null).unwrap();
+        }
+        assert_eq!(details, json!({ "name": "Finals", "photos": [] }));
+    }
+
+    #[test]
+    fn test_custom_field_limit() {
+        let mut details = json!({});
+        for i in 0..MAX_CUSTOM_FIELDS {
+            apply_custom_field(&mut details, &format!("f{}", i), json!(i)).unwrap();
+        }
+        assert!(apply_custom_field(&mut details, "extra", json!(1)).is_err());
+        // Updating an existing field is still allowed at the limit
+        apply_custom_field(&mut details, "f0", json!("updated")).unwrap();
+    }
+}
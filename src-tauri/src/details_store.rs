@@ -0,0 +1,513 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::i18n::{error, split_code, ErrorCode};
+use crate::journal::{edit_label, JournalState};
+use crate::read_only::ReadOnlyState;
+use crate::workspace::{photos_mut, read_gallery_details_page, read_json};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{Emitter, Manager};
+
+/// How long after the last edit a document is written; edits within it share one write.
+const FLUSH_DELAY: Duration = Duration::from_millis(1500);
+/// Most photos one page returns.
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Gallery-details documents held in memory, by path. A global rather than managed state so
+/// the shared read/write helpers (`read_json_file`, `update_gallery_details`, the journal) can
+/// see edits that aren't on disk yet without an `AppHandle`.
+static DOCUMENTS: Mutex<BTreeMap<PathBuf, Document>> = Mutex::new(BTreeMap::new());
+/// Held while a document is written, so a write of an older snapshot never lands after a newer
+/// one and a document isn't dropped mid-write.
+static WRITES: Mutex<()> = Mutex::new(());
+
+struct Document {
+    /// Current content, including edits not yet written. Shared with a write in progress, which
+    /// serializes it outside the `DOCUMENTS` lock; an edit meanwhile copies it first.
+    value: Arc<serde_json::Value>,
+    /// Size and modification time of the file when last read or written, to notice other writers.
+    stamp: Option<(u64, SystemTime)>,
+    /// Undo labels of the edits not yet written, oldest first; empty when the document is clean.
+    pending: Vec<String>,
+    /// Bumped by every edit, so a scheduled write only runs once edits stop.
+    generation: u64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoPage {
+    pub total: usize,
+    pub offset: usize,
+    pub photos: Vec<serde_json::Value>,
+    /// The document without `photos` (name, date, description, schemaVersion…).
+    pub header: serde_json::Value,
+}
+
+/// Payload of the `gallery-details-flush-failed` event: a gallery's pending edits couldn't be
+/// written, usually because the file changed on disk meanwhile. They stay pending (and are what
+/// `read_json_file` returns) until written or rolled back.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlushFailure {
+    pub workspace_path: String,
+    pub slug: String,
+    pub error: String,
+    /// Error code when `error` is a coded error (see `i18n::error`).
+    pub code: Option<String>,
+}
+
+/// Fields to set on the photo whose `full` (or `thumbnail`) ends with `filename`. A null value
+/// removes the field.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoUpdate {
+    pub filename: String,
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+fn details_path(root: &Path, slug: &str) -> PathBuf {
+    root.join(slug).join("gallery-details.json")
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// The open document for `path`, loading it on first use. A clean document changed on disk is
+/// reloaded; one with unwritten edits is a conflict the user resolves with a rollback.
+fn document<'a>(documents: &'a mut BTreeMap<PathBuf, Document>, path: &Path) -> Result<&'a mut Document, String> {
+    let stamp = file_stamp(path);
+    let stale = documents.get(path).is_some_and(|doc| doc.stamp != stamp);
+    if stale && documents.get(path).is_some_and(|doc| !doc.pending.is_empty()) {
+        return Err(error(ErrorCode::GalleryDetailsChangedExternally, &[("path", &path.display())]));
+    }
+    if stale || !documents.contains_key(path) {
+        if stamp.is_none() {
+            return Err(error(ErrorCode::GalleryDetailsNotFound, &[("path", &path.display())]));
+        }
+        let value = Arc::new(read_json(path)?);
+        documents.insert(path.to_path_buf(), Document { value, stamp, pending: Vec::new(), generation: 0 });
+    }
+    Ok(documents.get_mut(path).expect("just loaded"))
+}
+
+/// Apply `f` to the document at `path` in memory, recording it under `label` for the next write.
+/// Returns `f`'s result and the document's new generation.
+///
+/// The document is edited in place, so `f` must fail before changing anything, as
+/// `apply_updates` and `apply_removals` do. Should it fail anyway, a document without other
+/// pending edits is rolled back to the saved file by dropping it.
+fn edit<T>(
+    read_only: &ReadOnlyState,
+    path: &Path,
+    label: &str,
+    f: impl FnOnce(&mut serde_json::Value) -> Result<T, String>,
+) -> Result<(T, u64), String> {
+    read_only.ensure_writable(path)?;
+    let mut documents = DOCUMENTS.lock().map_err(|e| e.to_string())?;
+    let doc = document(&mut documents, path)?;
+    match f(Arc::make_mut(&mut doc.value)) {
+        Ok(result) => {
+            doc.pending.push(label.to_string());
+            doc.generation += 1;
+            Ok((result, doc.generation))
+        }
+        Err(e) => {
+            if doc.pending.is_empty() {
+                documents.remove(path);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Undo label for writing `path`'s unwritten edits, or None when it has none.
+fn pending_label(path: &Path) -> Result<Option<String>, String> {
+    let documents = DOCUMENTS.lock().map_err(|e| e.to_string())?;
+    Ok(documents.get(path).and_then(|doc| match doc.pending.as_slice() {
+        [] => None,
+        [only] => Some(only.clone()),
+        _ => Some(edit_label(path)),
+    }))
+}
+
+/// The file's contents before and after a write.
+type Written = (Option<Vec<u8>>, Vec<u8>);
+
+/// Report a conflict if the document at `path` has unwritten edits and the file changed on disk.
+fn check_unchanged(documents: &mut BTreeMap<PathBuf, Document>, path: &Path) -> Result<(), String> {
+    if documents.get(path).is_some_and(|doc| doc.stamp != file_stamp(path)) {
+        document(documents, path)?;
+    }
+    Ok(())
+}
+
+/// Write the document at `path` if it has unwritten edits, returning the file's previous and
+/// new contents when it did. The `DOCUMENTS` lock is held to take a snapshot and to rename the
+/// finished temp file into place; serializing and writing happen outside it, so edits and reads
+/// of open galleries don't wait for the disk. Edits made meanwhile stay pending for the next write.
+fn write_document(read_only: &ReadOnlyState, path: &Path) -> Result<Option<Written>, String> {
+    let _writing = WRITES.lock().map_err(|e| e.to_string())?;
+    let (snapshot, included) = {
+        let mut documents = DOCUMENTS.lock().map_err(|e| e.to_string())?;
+        let Some(doc) = documents.get(path).filter(|doc| !doc.pending.is_empty()) else {
+            return Ok(None);
+        };
+        let snapshot = (Arc::clone(&doc.value), doc.pending.len());
+        check_unchanged(&mut documents, path)?;
+        read_only.ensure_writable(path)?;
+        snapshot
+    };
+    let json = serde_json::to_vec_pretty(&*snapshot).map_err(|e| e.to_string())?;
+    drop(snapshot);
+    let before = fs::read(path).ok();
+    let parent = path.parent().ok_or("No parent directory")?;
+    let mut temp = tempfile::Builder::new()
+        .prefix(".gallery-details.json.")
+        .suffix(".tmp")
+        .tempfile_in(parent)
+        .map_err(|e| e.to_string())?;
+    temp.write_all(&json).map_err(|e| e.to_string())?;
+
+    let mut documents = DOCUMENTS.lock().map_err(|e| e.to_string())?;
+    check_unchanged(&mut documents, path)?;
+    temp.persist(path).map_err(|e| e.error.to_string())?;
+    // Only `close` drops a document with pending edits, and it waits for `WRITES`
+    let doc = documents.get_mut(path).expect("held while writing");
+    doc.stamp = file_stamp(path);
+    doc.pending.drain(..included);
+    Ok(Some((before, json)))
+}
+
+/// Write `path`'s unwritten edits as one undo step and report the change.
+fn flush_path(app: &tauri::AppHandle, journal: &JournalState, path: &Path) -> Result<bool, String> {
+    let Some(label) = pending_label(path)? else {
+        return Ok(false);
+    };
+    let Some((before, after)) = write_document(&app.state::<ReadOnlyState>(), path)? else {
+        return Ok(false);
+    };
+    journal.record_write(&label, path, before, after)?;
+    let slug = path.parent().and_then(|p| p.file_name()).unwrap_or_default().to_string_lossy();
+    emit_change(app, WorkspaceChange::new("galleryDetails", &slug, "updated", ChangeOrigin::User, "flush_gallery_details"));
+    Ok(true)
+}
+
+/// Write `path` once `FLUSH_DELAY` passes without another edit.
+fn schedule_flush(app: &tauri::AppHandle, path: PathBuf, generation: u64) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FLUSH_DELAY).await;
+        let current = DOCUMENTS.lock().ok().and_then(|documents| documents.get(&path).map(|doc| doc.generation));
+        if current != Some(generation) {
+            return;
+        }
+        if let Err(e) = flush_path(&app, &app.state::<JournalState>(), &path) {
+            report_flush_failure(&app, &path, &e);
+        }
+    });
+}
+
+fn report_flush_failure(app: &tauri::AppHandle, path: &Path, error: &str) {
+    eprintln!("[details_store] Failed to save {}: {}", path.display(), error);
+    let gallery = path.parent().unwrap_or(path);
+    let (code, error) = split_code(error);
+    let failure = FlushFailure {
+        workspace_path: gallery.parent().unwrap_or(gallery).to_string_lossy().into_owned(),
+        slug: gallery.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        error: error.to_string(),
+        code: code.map(str::to_string),
+    };
+    let _ = app.emit("gallery-details-flush-failed", failure);
+}
+
+/// Write every document with unwritten edits under `root`. Returns how many were written.
+pub(crate) fn flush_workspace(app: &tauri::AppHandle, root: &Path) -> Result<usize, String> {
+    flush_matching(app, |path| path.starts_with(root))
+}
+
+/// Write every document with unwritten edits, e.g. before the app exits.
+pub(crate) fn flush_all(app: &tauri::AppHandle) -> Result<usize, String> {
+    flush_matching(app, |_| true)
+}
+
+fn flush_matching(app: &tauri::AppHandle, include: impl Fn(&Path) -> bool) -> Result<usize, String> {
+    let paths: Vec<PathBuf> = DOCUMENTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter(|(path, doc)| include(path) && !doc.pending.is_empty())
+        .map(|(path, _)| path.clone())
+        .collect();
+    let journal = app.state::<JournalState>();
+    let mut written = 0;
+    let mut first_error = None;
+    // One conflicting gallery doesn't hold back the others
+    for path in paths {
+        match flush_path(app, &journal, &path) {
+            Ok(true) => written += 1,
+            Ok(false) => {}
+            Err(e) => {
+                report_flush_failure(app, &path, &e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error.map_or(Ok(written), Err)
+}
+
+/// The in-memory content of `path` when it has unwritten edits.
+pub(crate) fn pending_value(path: &Path) -> Option<serde_json::Value> {
+    let documents = DOCUMENTS.lock().ok()?;
+    documents.get(path).filter(|doc| !doc.pending.is_empty()).map(|doc| (*doc.value).clone())
+}
+
+/// Whether `path` has edits that aren't on disk yet.
+pub(crate) fn has_pending(path: &Path) -> bool {
+    DOCUMENTS.lock().is_ok_and(|documents| documents.get(path).is_some_and(|doc| !doc.pending.is_empty()))
+}
+
+/// Stop holding `path`, returning its unwritten content for a caller about to write the file
+/// itself (`update_gallery_details`, `write_json_file`).
+pub(crate) fn take_pending(path: &Path) -> Option<serde_json::Value> {
+    let doc = close(path).ok()?.filter(|doc| !doc.pending.is_empty())?;
+    Some(Arc::unwrap_or_clone(doc.value))
+}
+
+/// Stop holding `path`, after a write of it in progress has finished.
+fn close(path: &Path) -> Result<Option<Document>, String> {
+    let _writing = WRITES.lock().map_err(|e| e.to_string())?;
+    Ok(DOCUMENTS.lock().map_err(|e| e.to_string())?.remove(path))
+}
+
+/// Whether `photo`'s `full` or `thumbnail` path ends with `filename`.
+pub(crate) fn photo_matches(photo: &serde_json::Value, filename: &str) -> bool {
+    let thumb = photo.get("thumbnail").and_then(|v| v.as_str()).unwrap_or("");
+    let full = photo.get("full").and_then(|v| v.as_str()).unwrap_or("");
+    thumb.ends_with(filename) || full.ends_with(filename)
+}
+
+fn apply_updates(details: &mut serde_json::Value, updates: &[PhotoUpdate]) -> Result<usize, String> {
+    let photos = photos_mut(details)?;
+    let mut updated = 0;
+    for update in updates {
+        let Some(photo) = photos.iter_mut().find(|p| photo_matches(p, &update.filename)) else {
+            continue;
+        };
+        let Some(obj) = photo.as_object_mut() else {
+            continue;
+        };
+        for (key, value) in &update.fields {
+            if value.is_null() {
+                obj.remove(key);
+            } else {
+                obj.insert(key.clone(), value.clone());
+            }
+        }
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+fn apply_removals(details: &mut serde_json::Value, filenames: &[String]) -> Result<usize, String> {
+    let photos = photos_mut(details)?;
+    let before = photos.len();
+    photos.retain(|photo| !filenames.iter().any(|name| photo_matches(photo, name)));
+    Ok(before - photos.len())
+}
+
+fn page(details: &serde_json::Value, offset: usize, limit: usize) -> PhotoPage {
+    let photos = details.get("photos").and_then(|p| p.as_array()).map(Vec::as_slice).unwrap_or_default();
+    let header = details
+        .as_object()
+        .map(|obj| obj.iter().filter(|(key, _)| *key != "photos").map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    PhotoPage {
+        total: photos.len(),
+        offset,
+        photos: photos.iter().skip(offset).take(limit.min(MAX_PAGE_SIZE)).cloned().collect(),
+        header,
+    }
+}
+
+/// One page of a gallery's photos, including edits not yet written, so large galleries load
+/// without transferring the whole file. Without pending edits only the page is read from disk.
+#[tauri::command]
+pub async fn get_gallery_photos_page(workspace_path: String, slug: String, offset: usize, limit: usize) -> Result<PhotoPage, String> {
+    let path = details_path(Path::new(&workspace_path), &slug);
+    {
+        let documents = DOCUMENTS.lock().map_err(|e| e.to_string())?;
+        if let Some(doc) = documents.get(&path).filter(|doc| !doc.pending.is_empty()) {
+            return Ok(page(&doc.value, offset, limit));
+        }
+    }
+    if !path.is_file() {
+        return Err(error(ErrorCode::GalleryDetailsNotFound, &[("path", &path.display())]));
+    }
+    tokio::task::spawn_blocking(move || {
+        let page = read_gallery_details_page(&path, offset, limit.min(MAX_PAGE_SIZE))?;
+        Ok(PhotoPage { total: page.total, offset, photos: page.photos, header: page.header })
+    })
+    .await
+    .map_err(|e| format!("Reading {} panicked: {}", slug, e))?
+}
+
+/// Change fields of some photos in memory; the file is written shortly after the last edit.
+/// Returns the number of photos found.
+#[tauri::command]
+pub async fn update_gallery_photos(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+    updates: Vec<PhotoUpdate>,
+) -> Result<usize, String> {
+    let path = details_path(Path::new(&workspace_path), &slug);
+    let label = format!("Edit {} photos", updates.len());
+    let (updated, generation) = edit(&app.state::<ReadOnlyState>(), &path, &label, |details| apply_updates(details, &updates))?;
+    schedule_flush(&app, path, generation);
+    for update in &updates {
+        let id = format!("{}/{}", slug, update.filename);
+        emit_change(&app, WorkspaceChange::new("photo", &id, "updated", ChangeOrigin::User, "update_gallery_photos"));
+    }
+    Ok(updated)
+}
+
+/// Remove photos in memory; the file is written shortly after the last edit. Returns the
+/// number removed.
+#[tauri::command]
+pub async fn remove_gallery_photos(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    slug: String,
+    filenames: Vec<String>,
+) -> Result<usize, String> {
+    let path = details_path(Path::new(&workspace_path), &slug);
+    let label = match filenames.as_slice() {
+        [only] => format!("Remove {}", only),
+        _ => format!("Remove {} photos", filenames.len()),
+    };
+    let (removed, generation) = edit(&app.state::<ReadOnlyState>(), &path, &label, |details| apply_removals(details, &filenames))?;
+    schedule_flush(&app, path, generation);
+    for filename in &filenames {
+        let id = format!("{}/{}", slug, filename);
+        emit_change(&app, WorkspaceChange::new("photo", &id, "removed", ChangeOrigin::User, "remove_gallery_photos"));
+    }
+    Ok(removed)
+}
+
+/// Write every gallery of the workspace with pending edits now, each as one undo step.
+#[tauri::command]
+pub async fn flush_gallery_details(app: tauri::AppHandle, workspace_path: String) -> Result<usize, String> {
+    flush_workspace(&app, Path::new(&workspace_path))
+}
+
+/// Discard a gallery's pending edits and reload it from disk. Returns whether there were any.
+#[tauri::command]
+pub async fn rollback_gallery_details(app: tauri::AppHandle, workspace_path: String, slug: String) -> Result<bool, String> {
+    let path = details_path(Path::new(&workspace_path), &slug);
+    let discarded = close(&path)?.is_some_and(|doc| !doc.pending.is_empty());
+    if discarded {
+        emit_change(&app, WorkspaceChange::new("galleryDetails", &slug, "updated", ChangeOrigin::User, "rollback_gallery_details"));
+    }
+    Ok(discarded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn write_details(root: &Path, slug: &str, photos: usize) -> PathBuf {
+        let photos: Vec<serde_json::Value> = (0..photos)
+            .map(|i| json!({ "thumbnail": format!("{}/{:03}.jpg", slug, i), "full": format!("{}/{:03}.jpg", slug, i) }))
+            .collect();
+        let path = details_path(root, slug);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serde_json::to_string(&json!({ "name": slug, "photos": photos })).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_edits_are_batched_into_one_write() {
+        let tmp = TempDir::new().unwrap();
+        let path = write_details(tmp.path(), "sunset", 5);
+        let original = fs::read(&path).unwrap();
+
+        edit(&ReadOnlyState::default(), &path, "Remove 001.jpg", |d| apply_removals(d, &["001.jpg".to_string()])).unwrap();
+        let update = PhotoUpdate { filename: "002.jpg".to_string(), fields: json!({ "alt": "Dusk" }).as_object().unwrap().clone() };
+        let (_, generation) = edit(&ReadOnlyState::default(), &path, "Edit 1 photos", |d| apply_updates(d, &[update])).unwrap();
+        assert_eq!(generation, 2);
+        // Nothing on disk yet; readers of the store see the edits
+        assert_eq!(read_json(&path).unwrap()["photos"].as_array().unwrap().len(), 5);
+        assert_eq!(pending_value(&path).unwrap()["photos"].as_array().unwrap().len(), 4);
+
+        assert_eq!(pending_label(&path).unwrap().as_deref(), Some("Edit sunset/gallery-details.json"));
+        let (before, after) = write_document(&ReadOnlyState::default(), &path).unwrap().unwrap();
+        assert_eq!(before, Some(original));
+        assert_eq!(after, fs::read(&path).unwrap());
+        let written = read_json(&path).unwrap();
+        assert_eq!(written["photos"].as_array().unwrap().len(), 4);
+        assert_eq!(written["photos"][1]["alt"], "Dusk");
+        assert!(!has_pending(&path));
+        assert!(write_document(&ReadOnlyState::default(), &path).unwrap().is_none());
+        assert_eq!(pending_label(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_concurrent_edits_and_writes_end_with_the_latest_content() {
+        let tmp = TempDir::new().unwrap();
+        let path = write_details(tmp.path(), "tides", 40);
+
+        std::thread::scope(|scope| {
+            for worker in 0..4 {
+                let path = &path;
+                scope.spawn(move || {
+                    for i in 0..10 {
+                        let filename = format!("{:03}.jpg", worker * 10 + i);
+                        let update = PhotoUpdate { filename, fields: json!({ "alt": "Edited" }).as_object().unwrap().clone() };
+                        edit(&ReadOnlyState::default(), path, "Edit 1 photos", |d| apply_updates(d, &[update])).unwrap();
+                        write_document(&ReadOnlyState::default(), path).unwrap();
+                    }
+                });
+            }
+        });
+        write_document(&ReadOnlyState::default(), &path).unwrap();
+
+        assert!(!has_pending(&path));
+        let written = read_json(&path).unwrap();
+        assert!(written["photos"].as_array().unwrap().iter().all(|p| p["alt"] == "Edited"));
+        // Every write went through its own temp file, renamed into place
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("gallery-details.json")]);
+    }
+
+    #[test]
+    fn test_outside_change_conflicts_with_pending_edits() {
+        let tmp = TempDir::new().unwrap();
+        let path = write_details(tmp.path(), "harbour", 3);
+        edit(&ReadOnlyState::default(), &path, "Remove 000.jpg", |d| apply_removals(d, &["000.jpg".to_string()])).unwrap();
+
+        fs::write(&path, r#"{"name":"harbour","photos":[],"note":"edited elsewhere"}"#).unwrap();
+        assert!(write_document(&ReadOnlyState::default(), &path).unwrap_err().contains("Roll back"));
+
+        // Rolling back drops the edits; the next read sees the outside version
+        assert!(take_pending(&path).is_some());
+        let mut documents = DOCUMENTS.lock().unwrap();
+        assert_eq!(document(&mut documents, &path).unwrap().value["note"], "edited elsewhere");
+    }
+
+    #[test]
+    fn test_page() {
+        let details = json!({ "name": "Dunes", "photos": (0..25).map(|i| json!({ "full": i })).collect::<Vec<_>>() });
+        let page = page(&details, 20, 10);
+        assert_eq!((page.total, page.offset, page.photos.len()), (25, 20, 5));
+        assert_eq!(page.photos[0]["full"], 20);
+        assert_eq!(page.header, json!({ "name": "Dunes" }));
+    }
+}
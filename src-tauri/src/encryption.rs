@@ -0,0 +1,648 @@
+use crate::i18n::{error, ErrorCode};
+use crate::is_image_file;
+use crate::read_only::ReadOnlyState;
+use crate::workspace::write_json_atomic;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+pub(crate) const ENCRYPTION_VERSION: u32 = 1;
+/// Extension appended to an encrypted original: `01.jpg` is stored as `01.jpg.afgenc`.
+const ENCRYPTED_EXTENSION: &str = "afgenc";
+/// Header of an encrypted file, followed by the 24-byte nonce and the ciphertext.
+const MAGIC: &[u8] = b"AGENC1\n";
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const MIN_PASSPHRASE_LEN: usize = 8;
+/// Encrypted with the workspace key to check a passphrase without touching any photo.
+const CHECK_PLAINTEXT: &[u8] = b"afterglow-manager encryption check";
+
+/// `{app cache}/decrypted`, set at startup. Decrypted copies live outside the workspace, so
+/// sync and backup clients watching it never see plaintext.
+static DECRYPTED_CACHE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keys of the workspaces unlocked in this session, by workspace root. Never written to disk.
+pub struct EncryptionState(pub Mutex<HashMap<PathBuf, [u8; 32]>>);
+
+/// `{workspace}/.data/encryption.json`: the key derivation salt, the passphrase check and the
+/// galleries whose originals are kept encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionConfig {
+    version: u32,
+    /// Base64 Argon2id salt.
+    salt: String,
+    /// Base64 `CHECK_PLAINTEXT` sealed with the key.
+    check: String,
+    galleries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedGalleryStatus {
+    pub slug: String,
+    pub encrypted_files: usize,
+    /// Originals added since the gallery was last locked, not encrypted yet.
+    pub plaintext_files: usize,
+    /// Originals with a decrypted copy in `decryptedDir` (while unlocked), which previews read
+    /// instead of the encrypted file.
+    pub decrypted: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    /// A passphrase has been set for the workspace.
+    pub configured: bool,
+    pub unlocked: bool,
+    /// Where the decrypted copies are while unlocked, laid out like the workspace.
+    pub decrypted_dir: Option<String>,
+    pub galleries: Vec<EncryptedGalleryStatus>,
+}
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(".data").join("encryption.json")
+}
+
+/// Keep decrypted copies in `{cache_dir}/decrypted`. Nothing is unlocked at startup, so copies
+/// left there by a session that crashed or was killed are removed first.
+pub(crate) fn init_decrypted_cache(cache_dir: &Path) {
+    DECRYPTED_CACHE.get_or_init(|| {
+        let dir = cache_dir.join("decrypted");
+        if dir.is_dir() {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                eprintln!("[encryption] Failed to remove stale decrypted copies: {}", e);
+            }
+        }
+        dir
+    });
+}
+
+/// Decrypted copies of a workspace's encrypted originals while it is unlocked, laid out like
+/// the workspace (`{slug}/{name}`) in a cache folder named for the workspace path. Removed
+/// again when it is locked. None before `init_decrypted_cache`.
+fn decrypted_dir(root: &Path) -> Option<PathBuf> {
+    let root: PathBuf = root.components().collect();
+    let id = format!("{:x}", Sha256::digest(root.to_string_lossy().as_bytes()));
+    Some(DECRYPTED_CACHE.get()?.join(&id[..16]))
+}
+
+/// Create `dir` and its missing parents readable by the current user only.
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))
+}
+
+fn load_config(root: &Path) -> Result<Option<EncryptionConfig>, String> {
+    let path = config_path(root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let config: EncryptionConfig =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    if config.version != ENCRYPTION_VERSION {
+        return Err(error(ErrorCode::EncryptionSettingsUnsupported, &[("version", &config.version)]));
+    }
+    Ok(Some(config))
+}
+
+fn save_config(root: &Path, config: &EncryptionConfig) -> Result<(), String> {
+    let value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    write_json_atomic(&config_path(root), &value)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// XChaCha20-Poly1305 with a random nonce; `aad` binds the ciphertext to its file name so
+/// encrypted files can't be swapped for each other.
+fn seal(key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&Key::from(*key))
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| "Encryption failed".to_string())?;
+    Ok([MAGIC, &nonce[..], &ciphertext].concat())
+}
+
+fn open(key: &[u8; 32], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    let (nonce, ciphertext) = data
+        .strip_prefix(MAGIC)
+        .and_then(|rest| rest.split_first_chunk::<NONCE_LEN>())
+        .ok_or_else(|| "Not an encrypted file".to_string())?;
+    XChaCha20Poly1305::new(&Key::from(*key))
+        .decrypt(&XNonce::from(*nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| "Wrong passphrase or damaged file".to_string())
+}
+
+/// Write `bytes` next to `path` and rename it into place, so a crash leaves either the old
+/// file or the new one.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("afgtmp");
+    fs::write(&tmp, bytes).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn encrypted_path(original: &Path) -> PathBuf {
+    original.with_file_name(format!("{}.{}", file_name(original), ENCRYPTED_EXTENSION))
+}
+
+/// The original an encrypted `{name}.afgenc` file stands for; `None` for other files.
+pub(crate) fn encrypted_original(path: &Path) -> Option<PathBuf> {
+    path.extension().is_some_and(|ext| ext == ENCRYPTED_EXTENSION).then(|| path.with_extension(""))
+}
+
+/// An original that is stored encrypted: missing, with its `.afgenc` next to it. The watcher
+/// ignores these paths, so encrypting a gallery doesn't read as its photos being deleted.
+pub(crate) fn is_encrypted_original(path: &Path) -> bool {
+    !path.exists() && encrypted_path(path).is_file()
+}
+
+/// The file holding the original `{workspace}/{slug}/{name}` at rest: its `.afgenc` when it is
+/// stored encrypted, otherwise `path` itself. For checksums and modification times, which
+/// then don't change as the workspace is locked and unlocked.
+pub(crate) fn stored_original(path: &Path) -> PathBuf {
+    if is_encrypted_original(path) {
+        encrypted_path(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// The file to read for the original `{workspace}/{slug}/{name}`: its decrypted copy when it
+/// is stored encrypted and the workspace is unlocked, otherwise `path` itself.
+pub(crate) fn readable_original(path: &Path) -> PathBuf {
+    if !path.is_file() {
+        let copy = path.parent().and_then(|gallery| {
+            Some(decrypted_dir(gallery.parent()?)?.join(gallery.file_name()?).join(path.file_name()?))
+        });
+        if let Some(copy) = copy.filter(|copy| copy.is_file()) {
+            return copy;
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Names of the image originals in a gallery folder, including those stored encrypted, sorted.
+pub(crate) fn image_originals(dir: &Path) -> Result<Vec<String>, String> {
+    let (encrypted, plaintext) = gallery_files(dir)?;
+    let mut names: Vec<String> = encrypted
+        .iter()
+        .filter_map(|path| encrypted_original(path))
+        .filter(|original| is_image_file(original))
+        .chain(plaintext)
+        .map(|original| file_name(&original))
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Replace an original with its encrypted `{name}.afgenc`. The encrypted file is written before
+/// the original is removed, so a crash can't lose the photo.
+fn encrypt_file(key: &[u8; 32], path: &Path) -> Result<(), String> {
+    let plaintext = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let sealed = seal(key, file_name(path).as_bytes(), &plaintext)?;
+    write_atomic(&encrypted_path(path), &sealed)?;
+    fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))
+}
+
+/// Decrypt an `.afgenc` file to `dest`, leaving the encrypted file in place.
+fn decrypt_file(key: &[u8; 32], path: &Path, dest: &Path) -> Result<(), String> {
+    let original = path.with_extension("");
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let plaintext = open(key, file_name(&original).as_bytes(), &data)
+        .map_err(|_| error(ErrorCode::DecryptionFailed, &[("path", &path.display())]))?;
+    if let Some(parent) = dest.parent() {
+        create_private_dir(parent)?;
+    }
+    write_atomic(dest, &plaintext)
+}
+
+/// (encrypted files, plaintext originals) in a gallery folder.
+fn gallery_files(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>), String> {
+    let mut encrypted = Vec::new();
+    let mut plaintext = Vec::new();
+    if !dir.is_dir() {
+        return Ok((encrypted, plaintext));
+    }
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !entry.file_type().is_ok_and(|t| t.is_file()) {
+            continue;
+        }
+        if path.extension().is_some_and(|ext| ext == ENCRYPTED_EXTENSION) {
+            encrypted.push(path);
+        } else if is_image_file(&path) {
+            plaintext.push(path);
+        }
+    }
+    encrypted.sort();
+    plaintext.sort();
+    Ok((encrypted, plaintext))
+}
+
+/// Encrypt a marked gallery's plaintext originals in place. Returns the number encrypted.
+fn encrypt_originals(root: &Path, slug: &str, key: &[u8; 32]) -> Result<usize, String> {
+    let (_, plaintext) = gallery_files(&root.join(slug))?;
+    for path in &plaintext {
+        encrypt_file(key, path)?;
+    }
+    // The watcher ignores encrypted originals, so their checksums are updated here
+    crate::integrity::apply_fs_changes(root, &plaintext);
+    Ok(plaintext.len())
+}
+
+/// Decrypt a marked gallery's originals to the decrypted copies' cache folder for previews,
+/// thumbnails and publishing; the workspace keeps only the encrypted files. Returns the
+/// number decrypted.
+fn decrypt_to_cache(root: &Path, slug: &str, key: &[u8; 32]) -> Result<usize, String> {
+    let (encrypted, _) = gallery_files(&root.join(slug))?;
+    let copies = decrypted_dir(root)
+        .ok_or_else(|| error(ErrorCode::AppDataDirUnavailable, &[("error", &"no cache directory")]))?
+        .join(slug);
+    for path in &encrypted {
+        decrypt_file(key, path, &copies.join(file_name(&path.with_extension(""))))?;
+    }
+    Ok(encrypted.len())
+}
+
+/// Encrypt the originals added since the last lock, then remove the decrypted copies and the
+/// cached thumbnails, which would show the photos too. Returns the number of files encrypted.
+fn lock_galleries(root: &Path, galleries: &[String], key: &[u8; 32]) -> Result<usize, String> {
+    let mut count = 0;
+    for slug in galleries {
+        count += encrypt_originals(root, slug, key)?;
+    }
+    remove_plaintext_copies(root, galleries)?;
+    Ok(count)
+}
+
+fn remove_plaintext_copies(root: &Path, galleries: &[String]) -> Result<(), String> {
+    let decrypted = decrypted_dir(root);
+    let thumbnails = galleries.iter().map(|slug| root.join(".data").join("thumbnails").join(slug));
+    for dir in decrypted.into_iter().chain(thumbnails) {
+        if dir.is_dir() {
+            fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Decrypt a gallery's originals back in place when it is no longer marked. Returns the number
+/// of files restored.
+fn restore_gallery(root: &Path, slug: &str, key: &[u8; 32]) -> Result<usize, String> {
+    let (encrypted, _) = gallery_files(&root.join(slug))?;
+    let mut originals = Vec::new();
+    for path in &encrypted {
+        let original = path.with_extension("");
+        decrypt_file(key, path, &original)?;
+        fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        originals.push(original);
+    }
+    crate::integrity::apply_fs_changes(root, &originals);
+    if let Some(copies) = decrypted_dir(root).map(|dir| dir.join(slug)).filter(|dir| dir.is_dir()) {
+        fs::remove_dir_all(&copies).map_err(|e| format!("Failed to remove {}: {}", copies.display(), e))?;
+    }
+    Ok(encrypted.len())
+}
+
+/// Encrypted galleries with originals that have no decrypted copy (the workspace is locked);
+/// publishing them would delete their photos from the site.
+pub(crate) fn locked_galleries(root: &Path) -> Result<Vec<String>, String> {
+    let Some(config) = load_config(root)? else {
+        return Ok(Vec::new());
+    };
+    let mut locked = Vec::new();
+    for slug in config.galleries {
+        let (encrypted, _) = gallery_files(&root.join(&slug))?;
+        if encrypted.iter().any(|path| !readable_original(&path.with_extension("")).is_file()) {
+            locked.push(slug);
+        }
+    }
+    Ok(locked)
+}
+
+fn validate_slug(root: &Path, slug: &str) -> Result<(), String> {
+    if slug.is_empty() || slug.starts_with('.') || slug.contains('/') || !root.join(slug).is_dir() {
+        return Err(error(ErrorCode::GalleryFolderNotFound, &[("slug", &slug)]));
+    }
+    Ok(())
+}
+
+fn workspace_key(state: &EncryptionState, root: &Path) -> Result<Option<[u8; 32]>, String> {
+    Ok(state.0.lock().map_err(|e| e.to_string())?.get(root).copied())
+}
+
+fn unlocked_key(state: &EncryptionState, root: &Path) -> Result<[u8; 32], String> {
+    workspace_key(state, root)?.ok_or_else(|| error(ErrorCode::WorkspaceLocked, &[]))
+}
+
+/// Lock every workspace unlocked in this session. Called when the app exits so no decrypted
+/// copies stay behind after it closes; errors are logged.
+pub(crate) fn lock_all(state: &EncryptionState) {
+    let Ok(mut keys) = state.0.lock() else {
+        return;
+    };
+    for (root, key) in keys.drain() {
+        let galleries = match load_config(&root) {
+            Ok(Some(config)) => config.galleries,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("[encryption] {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = lock_galleries(&root, &galleries, &key) {
+            eprintln!("[encryption] Failed to lock {}: {}", root.display(), e);
+        }
+    }
+}
+
+/// Encrypted galleries of the workspace and whether it is unlocked in this session. Thumbnails
+/// left in the workspace by a session that ended without locking (a crash) are removed here;
+/// the decrypted copies are removed at startup.
+#[tauri::command]
+pub async fn get_encryption_status(
+    state: tauri::State<'_, EncryptionState>,
+    workspace_path: String,
+) -> Result<EncryptionStatus, String> {
+    let root = PathBuf::from(&workspace_path);
+    let unlocked = workspace_key(&state, &root)?.is_some();
+    let Some(config) = load_config(&root)? else {
+        return Ok(EncryptionStatus { configured: false, unlocked: false, decrypted_dir: None, galleries: Vec::new() });
+    };
+    if !unlocked {
+        remove_plaintext_copies(&root, &config.galleries)?;
+    }
+    let mut galleries = Vec::new();
+    for slug in config.galleries {
+        let (encrypted, plaintext) = gallery_files(&root.join(&slug))?;
+        let decrypted = encrypted
+            .iter()
+            .map(|path| path.with_extension(""))
+            .filter(|original| readable_original(original) != *original)
+            .map(|original| file_name(&original))
+            .collect();
+        galleries.push(EncryptedGalleryStatus {
+            slug,
+            encrypted_files: encrypted.len(),
+            plaintext_files: plaintext.len(),
+            decrypted,
+        });
+    }
+    let decrypted_dir = decrypted_dir(&root).filter(|_| unlocked).map(|dir| dir.to_string_lossy().into_owned());
+    Ok(EncryptionStatus { configured: true, unlocked, decrypted_dir, galleries })
+}
+
+/// Set the workspace's encryption passphrase (once; it can't be recovered) and unlock it.
+#[tauri::command]
+pub async fn enable_encryption(
+    state: tauri::State<'_, EncryptionState>,
+    read_only: tauri::State<'_, ReadOnlyState>,
+    workspace_path: String,
+    passphrase: String,
+) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    read_only.ensure_writable(&root)?;
+    if load_config(&root)?.is_some() {
+        return Err(error(ErrorCode::EncryptionAlreadySetUp, &[]));
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(error(ErrorCode::PassphraseTooShort, &[("min", &MIN_PASSPHRASE_LEN)]));
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = tokio::task::spawn_blocking(move || derive_key(&passphrase, &salt))
+        .await
+        .map_err(|e| format!("Key derivation panicked: {}", e))??;
+    let config = EncryptionConfig {
+        version: ENCRYPTION_VERSION,
+        salt: BASE64.encode(salt),
+        check: BASE64.encode(seal(&key, b"check", CHECK_PLAINTEXT)?),
+        galleries: Vec::new(),
+    };
+    save_config(&root, &config)?;
+    state.0.lock().map_err(|e| e.to_string())?.insert(root, key);
+    Ok(())
+}
+
+/// Check the passphrase, encrypt originals added while locked and decrypt copies of the
+/// encrypted galleries' originals to the app cache, which previews, thumbnails and
+/// publishing read. The workspace itself keeps only encrypted files. Returns the number of
+/// files decrypted.
+#[tauri::command]
+pub async fn unlock_workspace(
+    state: tauri::State<'_, EncryptionState>,
+    read_only: tauri::State<'_, ReadOnlyState>,
+    workspace_path: String,
+    passphrase: String,
+) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    read_only.ensure_writable(&root)?;
+    let config = load_config(&root)?.ok_or_else(|| error(ErrorCode::EncryptionNotSetUp, &[]))?;
+    let salt = BASE64.decode(&config.salt).map_err(|e| format!("Invalid encryption salt: {}", e))?;
+    let check = BASE64.decode(&config.check).map_err(|e| format!("Invalid encryption check: {}", e))?;
+    let key = tokio::task::spawn_blocking(move || derive_key(&passphrase, &salt))
+        .await
+        .map_err(|e| format!("Key derivation panicked: {}", e))??;
+    if open(&key, b"check", &check).ok().as_deref() != Some(CHECK_PLAINTEXT) {
+        return Err(error(ErrorCode::WrongPassphrase, &[]));
+    }
+    state.0.lock().map_err(|e| e.to_string())?.insert(root.clone(), key);
+    tokio::task::spawn_blocking(move || {
+        config.galleries.iter().try_fold(0, |count, slug| {
+            encrypt_originals(&root, slug, &key)?;
+            Ok::<_, String>(count + decrypt_to_cache(&root, slug, &key)?)
+        })
+    })
+    .await
+    .map_err(|e| format!("Unlock panicked: {}", e))?
+}
+
+/// Encrypt originals added to the encrypted galleries since unlocking, remove the decrypted
+/// copies and cached thumbnails and forget the key. Returns the number of files encrypted.
+#[tauri::command]
+pub async fn lock_workspace(state: tauri::State<'_, EncryptionState>, workspace_path: String) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    let key = unlocked_key(&state, &root)?;
+    let config = load_config(&root)?.ok_or_else(|| error(ErrorCode::EncryptionNotSetUp, &[]))?;
+    let locked_root = root.clone();
+    let count = tokio::task::spawn_blocking(move || lock_galleries(&locked_root, &config.galleries, &key))
+    .await
+    .map_err(|e| format!("Lock panicked: {}", e))??;
+    state.0.lock().map_err(|e| e.to_string())?.remove(&root);
+    Ok(count)
+}
+
+/// Mark or unmark a gallery as encrypted. Needs the workspace unlocked. A marked gallery's
+/// originals are encrypted now (with decrypted copies for previews); an unmarked one's are
+/// decrypted back in place. `gallery-details.json` is never touched.
+#[tauri::command]
+pub async fn set_gallery_encrypted(
+    state: tauri::State<'_, EncryptionState>,
+    read_only: tauri::State<'_, ReadOnlyState>,
+    workspace_path: String,
+    slug: String,
+    encrypted: bool,
+) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    read_only.ensure_writable(&root)?;
+    validate_slug(&root, &slug)?;
+    let key = unlocked_key(&state, &root)?;
+    let mut config = load_config(&root)?.ok_or_else(|| error(ErrorCode::EncryptionNotSetUp, &[]))?;
+    if encrypted {
+        if !config.galleries.contains(&slug) {
+            config.galleries.push(slug.clone());
+            config.galleries.sort();
+        }
+        // Marked first, so a failure part way leaves the encrypted files tracked
+        save_config(&root, &config)?;
+        tokio::task::spawn_blocking(move || {
+            encrypt_originals(&root, &slug, &key)?;
+            decrypt_to_cache(&root, &slug, &key)
+        })
+        .await
+        .map_err(|e| format!("Encryption panicked: {}", e))??;
+        Ok(())
+    } else {
+        let restore_root = root.clone();
+        let restore_slug = slug.clone();
+        tokio::task::spawn_blocking(move || restore_gallery(&restore_root, &restore_slug, &key))
+            .await
+            .map_err(|e| format!("Decryption panicked: {}", e))??;
+        config.galleries.retain(|g| *g != slug);
+        save_config(&root, &config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Decrypted copies of every test go to one cache folder, one subfolder per workspace.
+    fn init_test_cache() {
+        init_decrypted_cache(&std::env::temp_dir().join("afterglow-manager-test-cache"));
+    }
+
+    #[test]
+    fn test_seal_round_trip() {
+        let key = derive_key("correct horse", b"0123456789abcdef").unwrap();
+        let sealed = seal(&key, b"01.jpg", b"photo bytes").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert_eq!(open(&key, b"01.jpg", &sealed).unwrap(), b"photo bytes");
+        // Bound to the file name and the key
+        assert!(open(&key, b"02.jpg", &sealed).is_err());
+        let other = derive_key("wrong horse", b"0123456789abcdef").unwrap();
+        assert!(open(&other, b"01.jpg", &sealed).is_err());
+        assert!(open(&key, b"01.jpg", b"plain").is_err());
+    }
+
+    #[test]
+    fn test_locking_keeps_gallery_details() {
+        init_test_cache();
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let details = r#"{"photos":[{"thumbnail":"01.jpg","full":"01.jpg"}]}"#;
+        fs::create_dir_all(root.join("nda")).unwrap();
+        fs::create_dir_all(root.join(".data/thumbnails/nda")).unwrap();
+        fs::write(root.join("nda/01.jpg"), b"one").unwrap();
+        fs::write(root.join("nda/gallery-details.json"), details).unwrap();
+        fs::write(root.join(".data/thumbnails/nda/01.webp"), b"thumb").unwrap();
+        let key = [7u8; 32];
+        let galleries = vec!["nda".to_string()];
+        let config = EncryptionConfig {
+            version: ENCRYPTION_VERSION,
+            salt: String::new(),
+            check: String::new(),
+            galleries: galleries.clone(),
+        };
+        save_config(root, &config).unwrap();
+        let original = root.join("nda/01.jpg");
+
+        // Marking: encrypted at rest, previews and publishing read the decrypted copy
+        assert_eq!(encrypt_originals(root, "nda", &key).unwrap(), 1);
+        assert_eq!(decrypt_to_cache(root, "nda", &key).unwrap(), 1);
+        assert!(root.join("nda/01.jpg.afgenc").is_file());
+        assert!(is_encrypted_original(&original));
+        assert_eq!(encrypted_original(&root.join("nda/01.jpg.afgenc")), Some(original.clone()));
+        assert_eq!(fs::read(readable_original(&original)).unwrap(), b"one");
+        assert!(!readable_original(&original).starts_with(root));
+        assert!(locked_galleries(root).unwrap().is_empty());
+
+        // Locking encrypts the photo added while unlocked and leaves no plaintext behind
+        fs::write(root.join("nda/02.jpg"), b"two").unwrap();
+        assert_eq!(lock_galleries(root, &galleries, &key).unwrap(), 1);
+        assert!(!decrypted_dir(root).unwrap().exists());
+        assert!(!root.join(".data/thumbnails/nda").exists());
+        assert_eq!(readable_original(&original), original);
+        assert_eq!(locked_galleries(root).unwrap(), vec!["nda"]);
+        assert_eq!(fs::read_to_string(root.join("nda/gallery-details.json")).unwrap(), details);
+
+        assert!(decrypt_to_cache(root, "nda", &[8u8; 32]).is_err());
+        assert_eq!(restore_gallery(root, "nda", &key).unwrap(), 2);
+        assert_eq!(fs::read(&original).unwrap(), b"one");
+        assert!(!root.join("nda/01.jpg.afgenc").exists());
+        assert!(!is_encrypted_original(&original));
+        assert_eq!(fs::read_to_string(root.join("nda/gallery-details.json")).unwrap(), details);
+    }
+
+    #[test]
+    fn test_encrypted_gallery_features_read_originals() {
+        init_test_cache();
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("nda")).unwrap();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(1200, 900)).save(root.join("nda/01.jpg")).unwrap();
+        fs::write(root.join("galleries.json"), r#"[{"name":"NDA","slug":"nda","date":"","cover":""}]"#).unwrap();
+        fs::write(root.join("nda/gallery-details.json"), r#"{"photos":[{"thumbnail":"01.jpg","full":"01.jpg"}]}"#).unwrap();
+        let key = [7u8; 32];
+        let verify = || {
+            let report = crate::integrity::verify_workspace(root).unwrap();
+            (report.checked, report.added.len(), report.mismatched.len(), report.missing.len())
+        };
+        assert_eq!(verify(), (1, 1, 0, 0));
+
+        encrypt_originals(root, "nda", &key).unwrap();
+        decrypt_to_cache(root, "nda", &key).unwrap();
+        // Checked as stored, so neither encrypting nor locking reads as a change
+        assert_eq!(verify(), (1, 0, 0, 0));
+
+        let filenames = vec!["01.jpg".to_string()];
+        let exports = root.join(".data/exports");
+        fs::create_dir_all(&exports).unwrap();
+        let social = crate::social::export_crops(root, "nda", &[], &filenames, crate::social::SocialRatio::Square, &exports);
+        assert!(social.errors.is_empty(), "{:?}", social.errors);
+        assert_eq!(social.exported.len(), 1);
+
+        let size = crate::prints::PrintSize { width_inches: 4.0, height_inches: 3.0 };
+        let prints = crate::prints::print_size_report(root, &["nda".to_string()], size);
+        assert!(prints.errors.is_empty(), "{:?}", prints.errors);
+        assert_eq!((prints.photos[0].width_px, prints.photos[0].height_px), (1200, 900));
+
+        remove_plaintext_copies(root, &["nda".to_string()]).unwrap();
+        assert_eq!(verify(), (1, 0, 0, 0));
+        assert_eq!(image_originals(&root.join("nda")).unwrap(), vec!["01.jpg"]);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::is_image_file;
+use crate::manifest::format_utc_timestamp;
+use crate::workspace::{
+    photos_mut, read_gallery_details, read_json, str_field, write_json_atomic, DETAILS_SCHEMA_VERSION,
+    GALLERIES_SCHEMA_VERSION,
+};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub slug: String,
+    /// Images copied into the gallery folder.
+    pub copied: usize,
+    /// Images already in the gallery folder under the same name, left as they were.
+    pub skipped: usize,
+    /// The gallery was added to `galleries.json` by this import.
+    pub created: bool,
+}
+
+/// Gallery slug for a folder name: lowercase ASCII letters and digits, other runs become `-`.
+fn slug_for_folder(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+fn validate_slug(slug: &str) -> Result<(), String> {
+    if slug.is_empty() || slug.starts_with('.') || slug.contains(['/', '\\']) || slug == ".." {
+        return Err(format!("Invalid gallery slug: \"{}\"", slug));
+    }
+    Ok(())
+}
+
+/// Today as the app writes new gallery dates ("DD/MM/YYYY", UTC).
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let date = &format_utc_timestamp(secs)[..10];
+    format!("{}/{}/{}", &date[8..10], &date[5..7], &date[..4])
+}
+
+fn photo_entry(filename: &str) -> serde_json::Value {
+    let alt = Path::new(filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    serde_json::json!({ "thumbnail": filename, "full": filename, "alt": alt })
+}
+
+/// Copy the images directly in `source` into `{root}/{slug}/`, list new ones in the gallery's
+/// `gallery-details.json` (created if missing) and add the gallery to `galleries.json` unless
+/// it is already there. Re-importing the same card copies only files not imported yet.
+fn import_into(root: &Path, source: &Path, slug: &str, name: &str) -> Result<ImportResult, String> {
+    validate_slug(slug)?;
+    let mut images: Vec<PathBuf> = fs::read_dir(source)
+        .map_err(|e| format!("Cannot read {}: {}", source.display(), e))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .map(|e| e.path())
+        .filter(|p| is_image_file(p))
+        .collect();
+    images.sort();
+    if images.is_empty() {
+        return Err(format!("No images found in {}", source.display()));
+    }
+
+    let dest = root.join(slug);
+    fs::create_dir_all(&dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut copied = Vec::new();
+    let mut skipped = 0;
+    for image in &images {
+        let filename = image.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let target = dest.join(&filename);
+        if target.exists() {
+            skipped += 1;
+            continue;
+        }
+        fs::copy(image, &target).map_err(|e| format!("Failed to copy {}: {}", image.display(), e))?;
+        copied.push(filename);
+    }
+
+    let mut details = read_gallery_details(root, slug)?.unwrap_or_else(|| {
+        serde_json::json!({
+            "schemaVersion": DETAILS_SCHEMA_VERSION,
+            "name": name,
+            "slug": slug,
+            "date": today(),
+            "description": "",
+            "photos": [],
+        })
+    });
+    let photos = photos_mut(&mut details)?;
+    for filename in &copied {
+        if !photos.iter().any(|p| str_field(p, "full") == filename.as_str()) {
+            photos.push(photo_entry(filename));
+        }
+    }
+    let cover = photos.first().map(|p| format!("{}/{}", slug, str_field(p, "full")));
+    write_json_atomic(&root.join(slug).join("gallery-details.json"), &details)?;
+
+    let index_path = root.join("galleries.json");
+    let mut index = if index_path.exists() {
+        read_json(&index_path)?
+    } else {
+        serde_json::json!({ "schemaVersion": GALLERIES_SCHEMA_VERSION, "galleries": [] })
+    };
+    let galleries = match &mut index {
+        serde_json::Value::Array(galleries) => galleries,
+        serde_json::Value::Object(obj) => obj
+            .get_mut("galleries")
+            .and_then(|g| g.as_array_mut())
+            .ok_or_else(|| "galleries.json has no galleries array".to_string())?,
+        _ => return Err("galleries.json is not an array or object".to_string()),
+    };
+    let created = !galleries.iter().any(|g| str_field(g, "slug") == slug);
+    if created {
+        galleries.push(serde_json::json!({
+            "name": name,
+            "slug": slug,
+            "date": today(),
+            "cover": cover.unwrap_or_default(),
+        }));
+        write_json_atomic(&index_path, &index)?;
+    }
+
+    Ok(ImportResult { slug: slug.to_string(), copied: copied.len(), skipped, created })
+}
+
+/// Import a folder of images (e.g. an SD card's DCIM folder) as a gallery. `slug` defaults to
+/// one made from the folder name and `name` to the folder name.
+#[tauri::command]
+pub async fn import_folder(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    source_path: String,
+    slug: Option<String>,
+    name: Option<String>,
+) -> Result<ImportResult, String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let source = PathBuf::from(&source_path);
+    let folder_name = source.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let slug = slug.filter(|s| !s.trim().is_empty()).unwrap_or_else(|| slug_for_folder(&folder_name));
+    let name = name.filter(|n| !n.trim().is_empty()).unwrap_or(folder_name);
+    let import_root = root.clone();
+    let import_slug = slug.clone();
+    let result = tokio::task::spawn_blocking(move || import_into(&import_root, &source, &import_slug, &name))
+        .await
+        .map_err(|e| format!("Import panicked: {}", e))??;
+
+    if result.created {
+        emit_change(&app, WorkspaceChange::new("galleryIndex", "", "updated", ChangeOrigin::Import, "import_folder"));
+    }
+    emit_change(&app, WorkspaceChange::new("galleryDetails", &slug, "updated", ChangeOrigin::Import, "import_folder"));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_slug_for_folder() {
+        assert_eq!(slug_for_folder("2024-06-01 Cup Final"), "2024-06-01-cup-final");
+        assert_eq!(slug_for_folder("DCIM/100CANON"), "dcim-100canon");
+        assert_eq!(slug_for_folder("  Spaces  "), "spaces");
+    }
+
+    #[test]
+    fn test_import_into() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("site");
+        let card = tmp.path().join("card");
+        fs::create_dir_all(&card).unwrap();
+        fs::create_dir_all(&root).unwrap();
+        fs::write(card.join("IMG_0002.JPG"), b"2").unwrap();
+        fs::write(card.join("IMG_0001.JPG"), b"1").unwrap();
+        fs::write(card.join("notes.txt"), b"x").unwrap();
+
+        let result = import_into(&root, &card, "final", "Final").unwrap();
+        assert_eq!(result, ImportResult { slug: "final".to_string(), copied: 2, skipped: 0, created: true });
+        let details = read_gallery_details(&root, "final").unwrap().unwrap();
+        assert_eq!(details["photos"][0]["full"], "IMG_0001.JPG");
+        assert_eq!(details["photos"][0]["alt"], "IMG_0001");
+        let index = crate::workspace::read_galleries(&root).unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index[0]["cover"], "final/IMG_0001.JPG");
+
+        // Re-import after more shots: only the new one is copied and listed
+        fs::write(card.join("IMG_0003.JPG"), b"3").unwrap();
+        let again = import_into(&root, &card, "final", "Final").unwrap();
+        assert_eq!((again.copied, again.skipped, again.created), (1, 2, false));
+        assert_eq!(read_gallery_details(&root, "final").unwrap().unwrap()["photos"].as_array().unwrap().len(), 3);
+        assert_eq!(crate::workspace::read_galleries(&root).unwrap().len(), 1);
+
+        assert!(import_into(&root, &card, "../escape", "x").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::manifest::format_utc_timestamp;
+use crate::settings::{normalize_site_url, read_settings};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::Emitter;
+
+/// Shortest allowed interval, so a typo can't hammer the site (or CloudFront bill).
+const MIN_HEALTH_CHECK_INTERVAL_MINUTES: u32 = 1;
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The running periodic health check task, if any.
+pub struct HealthCheckState(pub Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub url: String,
+    /// HTTP status, absent when the request failed (DNS, TLS/certificate, timeout).
+    pub status: Option<u16>,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Payload of the `site-health` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub checked_at: String,
+    pub ok: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+/// URLs probed on each check: the site root, the manifest and the gallery index.
+fn health_check_urls(site_url: &str) -> Result<Vec<(String, bool)>, String> {
+    let site_url = normalize_site_url(site_url)?;
+    if !site_url.starts_with("https://") {
+        return Err("Health checks require an https:// site URL.".to_string());
+    }
+    Ok(vec![
+        (site_url.clone(), false),
+        (format!("{}site-manifest.json", site_url), true),
+        (format!("{}galleries/galleries.json", site_url), true),
+    ])
+}
+
+/// Interpret a response: 2xx, and for JSON endpoints a body that parses.
+fn evaluate_response(status: u16, body: Option<&[u8]>, expect_json: bool) -> Result<(), String> {
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {}", status));
+    }
+    if expect_json {
+        serde_json::from_slice::<serde_json::Value>(body.unwrap_or_default())
+            .map_err(|e| format!("Invalid JSON: {}", e))?;
+    }
+    Ok(())
+}
+
+/// HTTP client for requests against the published site.
+pub(crate) fn site_http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(HEALTH_CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+async fn check_url(client: &reqwest::Client, url: &str, expect_json: bool) -> HealthCheck {
+    let started = Instant::now();
+    let result = client.get(url).header("Cache-Control", "no-cache").send().await;
+    let (status, error) = match result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = if expect_json { response.bytes().await.ok() } else { None };
+            (Some(status), evaluate_response(status, body.as_deref(), expect_json).err())
+        }
+        // Display of reqwest errors omits the cause (e.g. the certificate problem)
+        Err(e) => (None, Some(format!("{:?}", e))),
+    };
+    HealthCheck {
+        url: url.to_string(),
+        status,
+        ok: error.is_none(),
+        error,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+async fn run_checks(site_url: &str) -> Result<HealthReport, String> {
+    let urls = health_check_urls(site_url)?;
+    let client = site_http_client()?;
+    let mut checks = Vec::new();
+    for (url, expect_json) in &urls {
+        checks.push(check_url(&client, url, *expect_json).await);
+    }
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(HealthReport {
+        checked_at: format_utc_timestamp(secs),
+        ok: checks.iter().all(|c| c.ok),
+        checks,
+    })
+}
+
+/// Check the published site once and emit the report as `site-health`.
+#[tauri::command]
+pub async fn run_health_check(app: tauri::AppHandle) -> Result<HealthReport, String> {
+    let settings = read_settings(&app)?;
+    let report = run_checks(&settings.site_url).await?;
+    let _ = app.emit("site-health", report.clone());
+    Ok(report)
+}
+
+/// (Re)start periodic health checks using the `healthCheckIntervalMinutes` setting; the first
+/// check runs immediately. Stops any running checks and returns false when disabled (0) or no
+/// site URL is configured. Checks only run while the app is open.
+#[tauri::command]
+pub async fn start_health_checks(
+    app: tauri::AppHandle,
+    health_state: tauri::State<'_, HealthCheckState>,
+) -> Result<bool, String> {
+    if let Some(handle) = health_state.0.lock().map_err(|e| e.to_string())?.take() {
+        handle.abort();
+    }
+    let settings = read_settings(&app)?;
+    if settings.health_check_interval_minutes == 0 || settings.site_url.trim().is_empty() {
+        return Ok(false);
+    }
+    health_check_urls(&settings.site_url)?;
+    let interval = settings.health_check_interval_minutes.max(MIN_HEALTH_CHECK_INTERVAL_MINUTES);
+    let site_url = settings.site_url.clone();
+    let app_handle = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(u64::from(interval) * 60));
+        loop {
+            ticker.tick().await;
+            if let Ok(report) = run_checks(&site_url).await {
+                let _ = app_handle.emit("site-health", report);
+            }
+        }
+    });
+    *health_state.0.lock().map_err(|e| e.to_string())? = Some(handle);
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn stop_health_checks(health_state: tauri::State<'_, HealthCheckState>) -> Result<(), String> {
+    if let Some(handle) = health_state.0.lock().map_err(|e| e.to_string())?.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_check_urls() {
+        let urls = health_check_urls("https://photos.example.com").unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                ("https://photos.example.com/".to_string(), false),
+                ("https://photos.example.com/site-manifest.json".to_string(), true),
+                ("https://photos.example.com/galleries/galleries.json".to_string(), true),
+            ]
+        );
+        assert!(health_check_urls("http://photos.example.com/").is_err());
+        assert!(health_check_urls("").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_response() {
+        assert!(evaluate_response(200, None, false).is_ok());
+        assert!(evaluate_response(200, Some(b"{\"galleries\":[]}"), true).is_ok());
+        assert_eq!(evaluate_response(403, None, false).unwrap_err(), "HTTP 403");
+        assert!(evaluate_response(200, Some(b"<html>"), true).unwrap_err().starts_with("Invalid JSON"));
+        assert!(evaluate_response(200, None, true).is_err());
+    }
+}
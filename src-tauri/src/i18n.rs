@@ -0,0 +1,250 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+/// Message catalogs, one Fluent file per locale. The first is the fallback.
+const CATALOGS: &[(&str, &str, &str)] = &[
+    ("en", "English", include_str!("../locales/en/errors.ftl")),
+    ("de", "Deutsch", include_str!("../locales/de/errors.ftl")),
+];
+
+/// Locale of backend error messages, from the `locale` setting.
+static LOCALE: RwLock<String> = RwLock::new(String::new());
+static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+
+/// Define `ErrorCode` from one table of variants and Fluent message ids, so `ErrorCode::ALL`
+/// (checked against every catalog in tests) and `id` can't miss a variant.
+macro_rules! error_codes {
+    ($(#[$meta:meta])* $($code:ident => $id:literal,)*) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub(crate) enum ErrorCode {
+            $($code,)*
+        }
+
+        impl ErrorCode {
+            #[cfg(test)]
+            const ALL: &'static [ErrorCode] = &[$(ErrorCode::$code,)*];
+
+            /// Fluent message id.
+            fn id(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$code => $id,)*
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    /// Errors with a stable code and a message in every catalog. `error` returns them as
+    /// `"[{code}] {message}"` so callers can tell them apart; errors without a code are still
+    /// returned in English, without a prefix.
+    BucketNotConfigured => "bucket-not-configured",
+    DistributionNotConfigured => "distribution-not-configured",
+    SiteUrlNotConfigured => "site-url-not-configured",
+    NoCredentials => "no-credentials",
+    KeychainUnavailable => "keychain-unavailable",
+    KeychainSaveFailed => "keychain-save-failed",
+    NotADirectory => "not-a-directory",
+    PlanNotFound => "plan-not-found",
+    GalleriesJsonReadFailed => "galleries-json-read-failed",
+    GalleriesJsonParseFailed => "galleries-json-parse-failed",
+    UploadFailed => "upload-failed",
+    InvalidationFailed => "invalidation-failed",
+    InvalidationTimedOut => "invalidation-timed-out",
+    GalleryFolderNotFound => "gallery-folder-not-found",
+    AppDataDirUnavailable => "app-data-dir-unavailable",
+    ListenFailed => "listen-failed",
+    EncryptionAlreadySetUp => "encryption-already-set-up",
+    EncryptionNotSetUp => "encryption-not-set-up",
+    EncryptionSettingsUnsupported => "encryption-settings-unsupported",
+    PassphraseTooShort => "passphrase-too-short",
+    WrongPassphrase => "wrong-passphrase",
+    WorkspaceLocked => "workspace-locked",
+    GalleriesLocked => "galleries-locked",
+    DecryptionFailed => "decryption-failed",
+    CompanionPortTooLow => "companion-port-too-low",
+    CompanionDisabled => "companion-disabled",
+    NoPairingInProgress => "no-pairing-in-progress",
+    PairingCodeExpired => "pairing-code-expired",
+    WrongPairingCode => "wrong-pairing-code",
+    DeviceNotFound => "device-not-found",
+    ArchiveBucketNotConfigured => "archive-bucket-not-configured",
+    GalleryAlreadyArchived => "gallery-already-archived",
+    UnknownRestoreTier => "unknown-restore-tier",
+    RestoreStartFailed => "restore-start-failed",
+    ArchiveStatusFailed => "archive-status-failed",
+    DownloadFailed => "download-failed",
+    MirrorRegionMissing => "mirror-region-missing",
+    MirrorEndpointNotHttps => "mirror-endpoint-not-https",
+    MirrorSameBucket => "mirror-same-bucket",
+    MirrorListFailed => "mirror-list-failed",
+    MirrorCredentialsSaveFailed => "mirror-credentials-save-failed",
+    AutomationTokenWriteFailed => "automation-token-write-failed",
+    GalleryDetailsNotFound => "gallery-details-not-found",
+    GalleryDetailsChangedExternally => "gallery-details-changed-externally",
+    PublishDeleteNeedsConfirmation => "publish-delete-needs-confirmation",
+    DeleteFailed => "delete-failed",
+    MirrorDeleteNeedsConfirmation => "mirror-delete-needs-confirmation",
+    ManagedRulesChanged => "managed-rules-changed",
+    DeletesOutsideManagedArea => "deletes-outside-managed-area",
+    SitePrefixOverlap => "site-prefix-overlap",
+    WorkspaceReadOnly => "workspace-read-only",
+    S3PrefixLeadingSlash => "prefix-leading-slash",
+    S3PrefixIllegalCharacter => "prefix-illegal-character",
+    S3PrefixInvalidSegment => "prefix-invalid-segment",
+    ArnWrongPartition => "arn-wrong-partition",
+    CloudFrontUnavailableInGovCloud => "cloudfront-unavailable-in-govcloud",
+    SecretStoreWriteFailed => "secret-store-write-failed",
+    SecretStoreMismatch => "secret-store-mismatch",
+    SecretStoreReadFailed => "secret-store-read-failed",
+    CompanionDevicesInvalid => "companion-devices-invalid",
+    CompanionRequestRejected => "companion-request-rejected",
+    CompanionNoWorkspace => "companion-no-workspace",
+    CompanionInvalidRequest => "companion-invalid-request",
+    CompanionDeviceNameInvalid => "companion-device-name-invalid",
+    CompanionNotAnImage => "companion-not-an-image",
+    CompanionPhotoNotFound => "companion-photo-not-found",
+    CompanionGalleryNotFound => "companion-gallery-not-found",
+    CompanionNotPaired => "companion-not-paired",
+    CompanionDeleteLimitExceeded => "companion-delete-limit-exceeded",
+    CompanionRiskyDeletes => "companion-risky-deletes",
+    CompanionAddressInvalid => "companion-address-invalid",
+    CompanionAddressUnavailable => "companion-address-unavailable",
+    PreviousTargetOverlap => "previous-target-overlap",
+    ArchiveInsideManagedArea => "archive-inside-managed-area",
+    ManagedPathInvalid => "managed-path-invalid",
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleOption {
+    pub code: String,
+    pub name: String,
+}
+
+fn bundles() -> &'static [FluentBundle<FluentResource>] {
+    BUNDLES.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .map(|(code, _, source)| {
+                let langid: LanguageIdentifier = code.parse().expect("catalog locale");
+                let resource = FluentResource::try_new(source.to_string()).expect("valid Fluent catalog");
+                let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+                // No Unicode isolation marks around arguments; messages are shown as plain text
+                bundle.set_use_isolating(false);
+                bundle.add_resource(resource).expect("no duplicate message ids");
+                bundle
+            })
+            .collect()
+    })
+}
+
+/// Index of the catalog for a locale like "de" or "de-AT"; the fallback for unknown locales.
+fn catalog_index(locale: &str) -> usize {
+    let language = locale.trim().split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+    CATALOGS.iter().position(|(code, _, _)| *code == language).unwrap_or(0)
+}
+
+/// Use `locale` for subsequent error messages. Empty or unsupported locales fall back to English.
+pub(crate) fn set_locale(locale: &str) {
+    if let Ok(mut current) = LOCALE.write() {
+        *current = locale.trim().to_string();
+    }
+}
+
+/// `code`'s message in `locale`, with `args` filled in. Falls back to the English message when
+/// the locale's catalog lacks it.
+fn message_in(locale: &str, code: ErrorCode, args: &[(&str, &dyn Display)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        // A coded error nested in this one contributes its message, not its code
+        fluent_args.set(*name, split_code(&value.to_string()).1.to_string());
+    }
+    let bundles = bundles();
+    [catalog_index(locale), 0]
+        .into_iter()
+        .find_map(|i| {
+            let bundle = &bundles[i];
+            let pattern = bundle.get_message(code.id())?.value()?;
+            let mut errors = Vec::new();
+            Some(bundle.format_pattern(pattern, Some(&fluent_args), &mut errors).into_owned())
+        })
+        .unwrap_or_else(|| code.id().to_string())
+}
+
+/// `code`'s message in the current locale, prefixed with the code. Commands return it as their
+/// `Err` string; the frontend splits it into `{ code, message }` (`BackendError` in commands.ts).
+pub(crate) fn error(code: ErrorCode, args: &[(&str, &dyn Display)]) -> String {
+    let locale = LOCALE.read().map(|l| l.clone()).unwrap_or_default();
+    format!("[{}] {}", code.id(), message_in(&locale, code, args))
+}
+
+/// Split an error string from `error` into its code and message. Uncoded errors have no code.
+pub(crate) fn split_code(error: &str) -> (Option<&str>, &str) {
+    error
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+        .filter(|(code, _)| !code.is_empty() && code.bytes().all(|b| b.is_ascii_lowercase() || b == b'-'))
+        .map_or((None, error), |(code, message)| (Some(code), message))
+}
+
+/// Locales with an error message catalog, for the settings dialog.
+#[tauri::command]
+pub async fn list_locales() -> Vec<LocaleOption> {
+    CATALOGS
+        .iter()
+        .map(|(code, name, _)| LocaleOption { code: code.to_string(), name: name.to_string() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_catalog_has_every_code() {
+        for (i, (locale, _, _)) in CATALOGS.iter().enumerate() {
+            for code in ErrorCode::ALL {
+                assert!(bundles()[i].has_message(code.id()), "{} lacks {}", locale, code.id());
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_code_is_recognized() {
+        for code in ErrorCode::ALL {
+            assert_eq!(split_code(&error(*code, &[])).0, Some(code.id()), "{} isn't split off", code.id());
+        }
+    }
+
+    #[test]
+    fn test_message_in_locale() {
+        assert_eq!(
+            message_in("en", ErrorCode::NotADirectory, &[("path", &"/tmp/x")]),
+            "Not a directory: /tmp/x"
+        );
+        assert_eq!(message_in("de-AT", ErrorCode::NotADirectory, &[("path", &"/tmp/x")]), "Kein Ordner: /tmp/x");
+        assert_eq!(
+            message_in("fr", ErrorCode::InvalidationTimedOut, &[("seconds", &30)]),
+            "CloudFront invalidation timed out after 30s."
+        );
+        assert_eq!(message_in("", ErrorCode::PlanNotFound, &[]), "Plan not found. Run preview first.");
+    }
+
+    #[test]
+    fn test_error_codes_survive_to_the_caller() {
+        let coded = error(ErrorCode::PlanNotFound, &[]);
+        assert_eq!(split_code(&coded), (Some("plan-not-found"), "Plan not found. Run preview first."));
+        assert_eq!(split_code("Failed to read x: [y] z"), (None, "Failed to read x: [y] z"));
+        assert_eq!(split_code("[Not a code] x"), (None, "[Not a code] x"));
+        // A nested coded error keeps only its message
+        let nested = error(ErrorCode::MirrorListFailed, &[("error", &error(ErrorCode::NoCredentials, &[]))]);
+        assert_eq!(split_code(&nested).0, Some("mirror-list-failed"));
+        assert!(!split_code(&nested).1.contains("[no-credentials]"));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::encryption::{is_encrypted_original, readable_original};
+use crate::workspace::{read_galleries, read_gallery_details, str_field};
+use image::codecs::jpeg::JpegEncoder;
+use image::{ColorType, DynamicImage, ImageDecoder, ImageEncoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+/// Quality of JPEGs re-encoded by `fix_image_formats`; high, since the result is the original.
+const CONVERTED_JPEG_QUALITY: u8 = 92;
+
+/// A photo likely to render poorly, or not at all, on the published site.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageLintIssue {
+    /// Relative to the workspace, e.g. "final/01.jpg".
+    pub path: String,
+    /// "cmykJpeg", "unsupportedJpeg" or "highBitDepthPng".
+    pub code: String,
+    pub message: String,
+    /// `fix_image_formats` can convert it to an 8-bit RGB file of the same format.
+    pub fixable: bool,
+}
+
+/// The start-of-frame segment of a JPEG: coding process, sample precision and component count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct JpegFrame {
+    marker: u8,
+    precision: u8,
+    components: u8,
+}
+
+/// Walk the marker segments up to the first start-of-frame, seeking past the rest (EXIF and
+/// ICC segments can be large). None when the data isn't a JPEG or has no frame header.
+fn read_jpeg_frame(reader: &mut (impl Read + Seek)) -> Option<JpegFrame> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf).ok()?;
+    if buf != [0xFF, 0xD8] {
+        return None;
+    }
+    loop {
+        reader.read_exact(&mut buf).ok()?;
+        if buf[0] != 0xFF {
+            return None;
+        }
+        let mut marker = buf[1];
+        while marker == 0xFF {
+            let mut fill = [0u8; 1];
+            reader.read_exact(&mut fill).ok()?;
+            marker = fill[0];
+        }
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            continue;
+        }
+        if matches!(marker, 0xD9 | 0xDA) {
+            return None;
+        }
+        reader.read_exact(&mut buf).ok()?;
+        let len = u16::from_be_bytes(buf) as i64;
+        if len < 2 {
+            return None;
+        }
+        // SOF0–SOF15, except DHT (C4), JPG (C8) and DAC (CC) which share the range
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let mut header = [0u8; 6];
+            reader.read_exact(&mut header).ok()?;
+            return Some(JpegFrame { marker, precision: header[0], components: header[5] });
+        }
+        reader.seek(SeekFrom::Current(len - 2)).ok()?;
+    }
+}
+
+fn jpeg_issue(frame: JpegFrame) -> Option<(&'static str, String, bool)> {
+    let unsupported = match frame.marker {
+        0xC3 => Some("Lossless JPEG"),
+        0xC5..=0xC7 => Some("Hierarchical JPEG"),
+        0xC9..=0xCB | 0xCD..=0xCF => Some("Arithmetic-coded JPEG"),
+        _ if frame.precision != 8 => Some("12-bit JPEG"),
+        _ => None,
+    };
+    if let Some(kind) = unsupported {
+        let message = format!("{}: browsers can't display it. Re-export it as a baseline or progressive 8-bit JPEG.", kind);
+        return Some(("unsupportedJpeg", message, false));
+    }
+    if frame.components == 4 {
+        let message = "CMYK JPEG: most browsers show wrong colors. Convert it to RGB.".to_string();
+        return Some(("cmykJpeg", message, true));
+    }
+    None
+}
+
+/// Bit depth from a PNG's IHDR chunk, which must come first.
+fn read_png_bit_depth(reader: &mut impl Read) -> Option<u8> {
+    let mut header = [0u8; 25];
+    reader.read_exact(&mut header).ok()?;
+    if header[..8] != *b"\x89PNG\r\n\x1a\n" || header[12..16] != *b"IHDR" {
+        return None;
+    }
+    Some(header[24])
+}
+
+/// Lint one image by its content (not its extension). None when it is fine or not a JPEG/PNG.
+fn lint_file(root: &Path, rel: &str) -> Result<Option<ImageLintIssue>, String> {
+    let path = readable_original(&root.join(rel));
+    let file = fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut reader = BufReader::new(file);
+    let issue = if let Some(frame) = read_jpeg_frame(&mut reader) {
+        jpeg_issue(frame)
+    } else {
+        reader.rewind().map_err(|e| e.to_string())?;
+        read_png_bit_depth(&mut reader).filter(|depth| *depth > 8).map(|depth| {
+            let message = format!("{}-bit PNG: browsers display 8 bits, so the extra depth only makes the file larger.", depth);
+            ("highBitDepthPng", message, true)
+        })
+    };
+    // Converting an encrypted original would write the photo back to the workspace decrypted
+    let encrypted = is_encrypted_original(&root.join(rel));
+    Ok(issue.map(|(code, message, fixable)| ImageLintIssue {
+        path: rel.to_string(),
+        code: code.to_string(),
+        message,
+        fixable: fixable && !encrypted,
+    }))
+}
+
+/// Lint `rels`, skipping files that can't be opened (missing files are reported elsewhere).
+pub(crate) fn lint_files(root: &Path, rels: &[String]) -> Vec<ImageLintIssue> {
+    rels.iter().filter_map(|rel| lint_file(root, rel).ok().flatten()).collect()
+}
+
+/// Every tracked photo as a workspace-relative path, in `galleries.json` order.
+fn tracked_photos(root: &Path) -> Result<Vec<String>, String> {
+    let mut rels = Vec::new();
+    for entry in read_galleries(root)? {
+        let slug = str_field(&entry, "slug");
+        if slug.is_empty() {
+            continue;
+        }
+        let Some(details) = read_gallery_details(root, &slug)? else { continue };
+        let photos = details.get("photos").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        rels.extend(photos.iter().map(|p| str_field(p, "full")).filter(|f| !f.is_empty()).map(|f| format!("{}/{}", slug, f)));
+    }
+    Ok(rels)
+}
+
+/// Check every tracked photo for encodings that render poorly on the web: CMYK JPEGs, JPEG
+/// codings browsers can't decode (arithmetic, lossless, hierarchical, 12-bit) and PNGs over
+/// 8 bits per channel. Reads file headers only.
+#[tauri::command]
+pub async fn lint_workspace_images(workspace_path: String) -> Result<Vec<ImageLintIssue>, String> {
+    let root = PathBuf::from(&workspace_path);
+    tokio::task::spawn_blocking(move || Ok(lint_files(&root, &tracked_photos(&root)?)))
+        .await
+        .map_err(|e| format!("Image lint panicked: {}", e))?
+}
+
+/// A workspace-relative image path that stays inside the workspace and out of dot folders.
+fn resolve(root: &Path, rel: &str) -> Result<PathBuf, String> {
+    let path = Path::new(rel);
+    let normal = path.components().all(|c| matches!(c, Component::Normal(name) if !name.to_string_lossy().starts_with('.')));
+    if !normal {
+        return Err(format!("Invalid image path: {}", rel));
+    }
+    Ok(root.join(path))
+}
+
+/// Re-encode `path` as 8-bit RGB (RGBA or gray for PNGs with alpha or no color) in its own
+/// format, keeping a JPEG's EXIF.
+fn convert_file(path: &Path, code: &str) -> Result<(), String> {
+    let mut decoder = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    let exif = decoder.exif_metadata().ok().flatten();
+    let img = DynamicImage::from_decoder(decoder).map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+
+    let tmp = path.with_file_name(format!(".{}.tmp", path.file_name().unwrap_or_default().to_string_lossy()));
+    let file = fs::File::create(&tmp).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    let written = if code == "cmykJpeg" {
+        let rgb = img.to_rgb8();
+        let mut encoder = JpegEncoder::new_with_quality(BufWriter::new(file), CONVERTED_JPEG_QUALITY);
+        if let Some(exif) = exif {
+            encoder.set_exif_metadata(exif).map_err(|e| e.to_string())?;
+        }
+        encoder.write_image(rgb.as_raw(), rgb.width(), rgb.height(), image::ExtendedColorType::Rgb8)
+    } else {
+        let converted = match img.color() {
+            ColorType::L16 => DynamicImage::ImageLuma8(img.to_luma8()),
+            ColorType::La16 => DynamicImage::ImageLumaA8(img.to_luma_alpha8()),
+            color if color.has_alpha() => DynamicImage::ImageRgba8(img.to_rgba8()),
+            _ => DynamicImage::ImageRgb8(img.to_rgb8()),
+        };
+        converted.write_to(&mut BufWriter::new(file), image::ImageFormat::Png)
+    };
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to encode {}: {}", path.display(), e));
+    }
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Convert each fixable file among `rels` in place, first copying the original to
+/// `.data/original-formats/{rel}` (kept, not overwritten, if a copy is already there).
+fn fix_files(root: &Path, rels: &[String]) -> Result<Vec<String>, String> {
+    let mut fixed = Vec::new();
+    for rel in rels {
+        let path = resolve(root, rel)?;
+        let Some(issue) = lint_file(root, rel)?.filter(|i| i.fixable) else { continue };
+        let backup = root.join(".data").join("original-formats").join(rel);
+        if !backup.exists() {
+            fs::create_dir_all(backup.parent().ok_or("No parent directory")?).map_err(|e| e.to_string())?;
+            fs::copy(&path, &backup).map_err(|e| format!("Failed to back up {}: {}", rel, e))?;
+        }
+        convert_file(&path, &issue.code)?;
+        fixed.push(rel.clone());
+    }
+    Ok(fixed)
+}
+
+/// The optional auto-fix for `lint_workspace_images`: convert CMYK JPEGs to RGB and high bit
+/// depth PNGs to 8 bits. Files without a fixable issue are skipped. Returns the number converted.
+#[tauri::command]
+pub async fn fix_image_formats(app: tauri::AppHandle, workspace_path: String, paths: Vec<String>) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let fixed = tokio::task::spawn_blocking(move || fix_files(&root, &paths))
+        .await
+        .map_err(|e| format!("Image conversion panicked: {}", e))??;
+    for rel in &fixed {
+        emit_change(&app, WorkspaceChange::new("image", rel, "updated", ChangeOrigin::User, "fix_image_formats"));
+    }
+    Ok(fixed.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    /// SOI, a 20-byte APP1 segment, then a frame header with the given marker/precision/components.
+    fn jpeg_header(marker: u8, precision: u8, components: u8) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xD8, 0xFF, 0xE1, 0x00, 0x14];
+        bytes.extend([0u8; 18]);
+        bytes.extend([0xFF, marker, 0x00, 0x14, precision, 0x00, 0x10, 0x00, 0x10, components]);
+        bytes
+    }
+
+    #[test]
+    fn test_read_jpeg_frame() {
+        let frame = read_jpeg_frame(&mut Cursor::new(jpeg_header(0xC2, 8, 4))).unwrap();
+        assert_eq!(frame, JpegFrame { marker: 0xC2, precision: 8, components: 4 });
+        assert_eq!(read_jpeg_frame(&mut Cursor::new(b"\x89PNG".to_vec())), None);
+        // A DHT segment before the frame is skipped, not mistaken for one
+        let mut with_dht = vec![0xFF, 0xD8, 0xFF, 0xC4, 0x00, 0x04, 0x00, 0x00];
+        with_dht.extend(&jpeg_header(0xC0, 8, 3)[2..]);
+        assert_eq!(read_jpeg_frame(&mut Cursor::new(with_dht)).unwrap().marker, 0xC0);
+    }
+
+    #[test]
+    fn test_jpeg_issue() {
+        let frame = |marker, precision, components| JpegFrame { marker, precision, components };
+        assert_eq!(jpeg_issue(frame(0xC0, 8, 3)), None);
+        assert_eq!(jpeg_issue(frame(0xC2, 8, 1)), None);
+        assert_eq!(jpeg_issue(frame(0xC2, 8, 4)).unwrap().0, "cmykJpeg");
+        assert_eq!(jpeg_issue(frame(0xC9, 8, 3)).unwrap().0, "unsupportedJpeg");
+        assert_eq!(jpeg_issue(frame(0xC3, 8, 3)).unwrap().0, "unsupportedJpeg");
+        // An unsupported coding outranks CMYK: converting can't fix it
+        let (code, message, fixable) = jpeg_issue(frame(0xC1, 12, 4)).unwrap();
+        assert_eq!((code, fixable), ("unsupportedJpeg", false));
+        assert!(message.starts_with("12-bit JPEG"));
+    }
+
+    #[test]
+    fn test_lint_and_fix_png() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("final")).unwrap();
+        image::ImageBuffer::<image::Rgb<u16>, Vec<u16>>::new(4, 4).save(root.join("final/deep.png")).unwrap();
+        image::RgbImage::new(4, 4).save(root.join("final/ok.png")).unwrap();
+        image::RgbImage::new(4, 4).save(root.join("final/ok.jpg")).unwrap();
+        let rels: Vec<String> = ["final/deep.png", "final/ok.png", "final/ok.jpg"].iter().map(|s| s.to_string()).collect();
+
+        let issues = lint_files(root, &rels);
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].path.as_str(), issues[0].code.as_str(), issues[0].fixable), ("final/deep.png", "highBitDepthPng", true));
+
+        assert_eq!(fix_files(root, &rels).unwrap(), vec!["final/deep.png"]);
+        assert!(lint_files(root, &rels).is_empty());
+        assert!(root.join(".data/original-formats/final/deep.png").exists());
+        assert!(fix_files(root, &["../outside.png".to_string()]).is_err());
+    }
+}
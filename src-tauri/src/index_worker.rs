@@ -0,0 +1,67 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Applies watcher events to the workspace indexes (search, integrity checksums, photo
+/// metadata) on a background thread, so a full search rebuild or hashing a folder of new originals never holds
+/// up the debouncer and the `workspace-fs-change` events behind it. Batches queued while an
+/// update runs are merged, so a burst of autosaves costs one rebuild. The thread exits once
+/// the worker is dropped and the queue is drained.
+pub(crate) struct IndexWorker(Sender<Vec<PathBuf>>);
+
+impl IndexWorker {
+    pub(crate) fn spawn(root: PathBuf) -> Result<Self, String> {
+        let (tx, rx) = channel();
+        std::thread::Builder::new()
+            .name("index-worker".to_string())
+            .spawn(move || {
+                // Catch up on changes made while the workspace wasn't watched; events keep the
+                // metadata index current from here on
+                if crate::metadata::metadata_index_path(&root).exists() {
+                    if let Err(e) = crate::metadata::refresh_metadata_index(&root) {
+                        eprintln!("[metadata] Failed to update the metadata index: {}", e);
+                    }
+                }
+                while let Some(paths) = next_batch(&rx) {
+                    crate::search::apply_fs_changes(&root, &paths);
+                    crate::integrity::apply_fs_changes(&root, &paths);
+                    crate::metadata::apply_fs_changes(&root, &paths);
+                }
+            })
+            .map_err(|e| format!("Failed to start the index worker: {}", e))?;
+        Ok(Self(tx))
+    }
+
+    /// Queue changed paths; returns immediately.
+    pub(crate) fn queue(&self, paths: Vec<PathBuf>) {
+        let _ = self.0.send(paths);
+    }
+}
+
+/// Wait for a batch, then merge in every batch already queued. None once the worker is gone.
+fn next_batch(rx: &Receiver<Vec<PathBuf>>) -> Option<Vec<PathBuf>> {
+    let mut paths: BTreeSet<PathBuf> = rx.recv().ok()?.into_iter().collect();
+    while let Ok(more) = rx.try_recv() {
+        paths.extend(more);
+    }
+    Some(paths.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_batch_merges_queued_batches() {
+        let (tx, rx) = channel();
+        tx.send(vec![PathBuf::from("galleries.json"), PathBuf::from("a/01.jpg")]).unwrap();
+        tx.send(vec![PathBuf::from("galleries.json")]).unwrap();
+        tx.send(vec![PathBuf::from("b/gallery-details.json")]).unwrap();
+        assert_eq!(
+            next_batch(&rx).unwrap(),
+            vec![PathBuf::from("a/01.jpg"), PathBuf::from("b/gallery-details.json"), PathBuf::from("galleries.json")]
+        );
+        drop(tx);
+        assert!(next_batch(&rx).is_none());
+    }
+}
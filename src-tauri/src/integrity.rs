@@ -0,0 +1,286 @@
+use crate::encryption::{image_originals, stored_original};
+use crate::is_image_file;
+use crate::workspace::write_json_atomic;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const CHECKSUMS_VERSION: u32 = 1;
+
+/// Serializes read-modify-write of checksum files between the watcher and commands.
+static CHECKSUMS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Content hash of an original as last seen, with the size and modification time it had then.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FileChecksum {
+    sha256: String,
+    size: u64,
+    /// Unix seconds.
+    modified: u64,
+}
+
+/// `{workspace}/.data/checksums/{slug}.json`: checksums of a gallery folder's images by filename.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GalleryChecksums {
+    version: u32,
+    files: BTreeMap<String, FileChecksum>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityMismatch {
+    /// `{slug}/{filename}`
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+    /// The file's size or modification time changed too, so it was likely edited while the app
+    /// wasn't watching; otherwise the content changed underneath an unchanged file (bit-rot or
+    /// a bad sync).
+    pub modified: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub checked: usize,
+    /// Images without a checksum yet; they are hashed and recorded by this check.
+    pub added: Vec<String>,
+    pub mismatched: Vec<IntegrityMismatch>,
+    /// Recorded images whose file no longer exists.
+    pub missing: Vec<String>,
+}
+
+fn checksums_path(root: &Path, slug: &str) -> PathBuf {
+    root.join(".data").join("checksums").join(format!("{}.json", slug))
+}
+
+fn load_checksums(root: &Path, slug: &str) -> Result<GalleryChecksums, String> {
+    let path = checksums_path(root, slug);
+    if !path.exists() {
+        return Ok(GalleryChecksums { version: CHECKSUMS_VERSION, files: BTreeMap::new() });
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn save_checksums(root: &Path, slug: &str, checksums: &GalleryChecksums) -> Result<(), String> {
+    let value = serde_json::to_value(checksums).map_err(|e| e.to_string())?;
+    write_json_atomic(&checksums_path(root, slug), &value)
+}
+
+/// Hash a file, streaming it rather than reading it into memory.
+fn checksum_file(path: &Path) -> Result<FileChecksum, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut file = fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(FileChecksum {
+        sha256: format!("{:x}", hasher.finalize()),
+        size: metadata.len(),
+        modified: modified_secs(&metadata),
+    })
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Gallery folders of the workspace: its non-hidden subdirectories.
+fn gallery_folders(root: &Path) -> Result<Vec<String>, String> {
+    let mut slugs: Vec<String> = fs::read_dir(root)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    slugs.sort();
+    Ok(slugs)
+}
+
+/// Re-hash one gallery folder against its checksums, recording images seen for the first time.
+fn verify_gallery(root: &Path, slug: &str, report: &mut IntegrityReport) -> Result<(), String> {
+    let _guard = CHECKSUMS_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut checksums = load_checksums(root, slug)?;
+    let dir = root.join(slug);
+    // Originals of encrypted galleries are checked as stored, encrypted
+    let names = image_originals(&dir)?;
+    let mut added = false;
+    for name in &names {
+        let actual = checksum_file(&stored_original(&dir.join(name)))?;
+        report.checked += 1;
+        match checksums.files.get(name) {
+            None => {
+                report.added.push(format!("{}/{}", slug, name));
+                checksums.files.insert(name.clone(), actual);
+                added = true;
+            }
+            Some(expected) if expected.sha256 != actual.sha256 => report.mismatched.push(IntegrityMismatch {
+                path: format!("{}/{}", slug, name),
+                expected: expected.sha256.clone(),
+                actual: actual.sha256,
+                modified: expected.size != actual.size || expected.modified != actual.modified,
+            }),
+            Some(_) => {}
+        }
+    }
+    for name in checksums.files.keys().filter(|name| !names.contains(name)) {
+        report.missing.push(format!("{}/{}", slug, name));
+    }
+    if added {
+        save_checksums(root, slug, &checksums)?;
+    }
+    Ok(())
+}
+
+/// Update the checksums of images the watcher saw created, modified or removed
+/// (`{slug}/{image}` paths); other paths are ignored. Errors are logged.
+pub(crate) fn apply_fs_changes(root: &Path, paths: &[PathBuf]) {
+    let mut by_slug: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in paths {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        if let [slug, name] = parts.as_slice() {
+            if !slug.starts_with('.') && is_image_file(path) {
+                let names = by_slug.entry(slug.clone()).or_default();
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+    }
+    for (slug, names) in by_slug {
+        if let Err(e) = update_checksums(root, &slug, &names) {
+            eprintln!("[integrity] Failed to update checksums for {}: {}", slug, e);
+        }
+    }
+}
+
+/// Re-hash `names` in a gallery folder, dropping entries of files that no longer exist.
+fn update_checksums(root: &Path, slug: &str, names: &[String]) -> Result<(), String> {
+    let _guard = CHECKSUMS_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut checksums = load_checksums(root, slug)?;
+    for name in names {
+        let path = stored_original(&root.join(slug).join(name));
+        if path.is_file() {
+            checksums.files.insert(name.clone(), checksum_file(&path)?);
+        } else {
+            checksums.files.remove(name);
+        }
+    }
+    save_checksums(root, slug, &checksums)
+}
+
+/// Re-hash every image in the workspace's gallery folders and report content that no longer
+/// matches its recorded checksum, and recorded images that are gone. The first check of a
+/// folder records its checksums.
+#[tauri::command]
+pub async fn verify_workspace_integrity(workspace_path: String) -> Result<IntegrityReport, String> {
+    let root = PathBuf::from(&workspace_path);
+    tokio::task::spawn_blocking(move || verify_workspace(&root))
+        .await
+        .map_err(|e| format!("Integrity check panicked: {}", e))?
+}
+
+/// `verify_workspace_integrity` for every gallery folder. Blocking.
+pub(crate) fn verify_workspace(root: &Path) -> Result<IntegrityReport, String> {
+    let mut report = IntegrityReport { checked: 0, added: Vec::new(), mismatched: Vec::new(), missing: Vec::new() };
+    for slug in gallery_folders(root)? {
+        verify_gallery(root, &slug, &mut report)?;
+    }
+    Ok(report)
+}
+
+/// Record the current content of `paths` (`{slug}/{filename}`, as reported by
+/// `verify_workspace_integrity`) as correct, e.g. after an intended edit.
+#[tauri::command]
+pub async fn accept_integrity_changes(workspace_path: String, paths: Vec<String>) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    let mut by_slug: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in &paths {
+        let (slug, name) = path
+            .split_once('/')
+            .filter(|(slug, name)| !slug.is_empty() && !slug.starts_with('.') && !name.is_empty() && !name.contains('/'))
+            .ok_or_else(|| format!("Invalid photo path: {}", path))?;
+        by_slug.entry(slug.to_string()).or_default().push(name.to_string());
+    }
+    tokio::task::spawn_blocking(move || {
+        by_slug.iter().try_for_each(|(slug, names)| update_checksums(&root, slug, names))
+    })
+    .await
+    .map_err(|e| format!("Integrity update panicked: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn verify(root: &Path) -> IntegrityReport {
+        verify_workspace(root).unwrap()
+    }
+
+    #[test]
+    fn test_verify_detects_changes() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir(root.join("a")).unwrap();
+        fs::write(root.join("a/01.jpg"), b"one").unwrap();
+        fs::write(root.join("a/02.jpg"), b"two").unwrap();
+        fs::write(root.join("a/notes.txt"), b"not an image").unwrap();
+
+        let first = verify(root);
+        assert_eq!(first.added, vec!["a/01.jpg", "a/02.jpg"]);
+        assert!(checksums_path(root, "a").is_file());
+
+        let clean = verify(root);
+        assert_eq!(clean.checked, 2);
+        assert!(clean.added.is_empty() && clean.mismatched.is_empty() && clean.missing.is_empty());
+
+        // Same size, content flipped: reported, and the recorded checksum is kept
+        fs::write(root.join("a/01.jpg"), b"ONE").unwrap();
+        fs::remove_file(root.join("a/02.jpg")).unwrap();
+        let report = verify(root);
+        assert_eq!(report.mismatched.len(), 1);
+        assert_eq!(report.mismatched[0].path, "a/01.jpg");
+        assert_eq!(report.missing, vec!["a/02.jpg"]);
+        assert_eq!(verify(root).mismatched.len(), 1);
+    }
+
+    #[test]
+    fn test_apply_fs_changes_updates_checksums() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir(root.join("a")).unwrap();
+        fs::write(root.join("a/01.jpg"), b"one").unwrap();
+        verify(root);
+
+        fs::write(root.join("a/01.jpg"), b"edited").unwrap();
+        fs::write(root.join("a/03.jpg"), b"three").unwrap();
+        apply_fs_changes(
+            root,
+            &[root.join("a/01.jpg"), root.join("a/03.jpg"), root.join("a/gallery-details.json"), root.join(".data/x.jpg")],
+        );
+        let checksums = load_checksums(root, "a").unwrap();
+        assert_eq!(checksums.files.len(), 2);
+        let report = verify(root);
+        assert!(report.mismatched.is_empty() && report.added.is_empty());
+
+        fs::remove_file(root.join("a/03.jpg")).unwrap();
+        apply_fs_changes(root, &[root.join("a/03.jpg")]);
+        assert!(!load_checksums(root, "a").unwrap().files.contains_key("03.jpg"));
+    }
+}
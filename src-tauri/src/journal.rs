@@ -0,0 +1,423 @@
+use crate::changes::{emit_change, json_file_change, ChangeOrigin};
+use crate::read_only::ReadOnlyState;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Undo steps kept; the oldest are dropped first.
+const MAX_JOURNAL_ENTRIES: usize = 100;
+/// Bytes of recorded edits kept across the undo steps; the oldest steps are dropped first.
+const MAX_JOURNAL_BYTES: usize = 16 * 1024 * 1024;
+/// Consecutive edits of the same file with the same label within this window are one step, so
+/// the debounced saves while typing a caption undo together.
+const COALESCE_WINDOW: Duration = Duration::from_secs(2);
+
+/// A file's contents, `None` when it doesn't exist.
+type Contents = Option<Vec<u8>>;
+
+/// A change to one file as an inverse operation: after the first `prefix` bytes, `removed` was
+/// replaced by `inserted`. Undo splices `removed` back in and redo `inserted`, so a step costs
+/// the edited region rather than two copies of the file.
+#[derive(Debug, Clone, PartialEq)]
+struct FileChange {
+    path: PathBuf,
+    /// SHA-256 of the whole file before and after (`None` = it didn't exist), checked before
+    /// splicing so a file changed outside the journal is never restored.
+    before: Option<[u8; 32]>,
+    after: Option<[u8; 32]>,
+    prefix: usize,
+    removed: Vec<u8>,
+    inserted: Vec<u8>,
+}
+
+fn digest(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+impl FileChange {
+    fn new(path: &Path, before: &Contents, after: &Contents) -> Self {
+        let old = before.as_deref().unwrap_or_default();
+        let new = after.as_deref().unwrap_or_default();
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        FileChange {
+            path: path.to_path_buf(),
+            before: before.as_deref().map(digest),
+            after: after.as_deref().map(digest),
+            prefix,
+            removed: old[prefix..old.len() - suffix].to_vec(),
+            inserted: new[prefix..new.len() - suffix].to_vec(),
+        }
+    }
+
+    /// Bytes this change keeps in memory.
+    fn size(&self) -> usize {
+        self.removed.len() + self.inserted.len()
+    }
+
+    /// The contents restoring `current` gives: the `before` side when `undo`, otherwise the
+    /// `after` side. `current` must hold the other side (see `expected`).
+    fn restore(&self, current: &[u8], undo: bool) -> Contents {
+        let (from, to, exists) = if undo {
+            (&self.inserted, &self.removed, self.before.is_some())
+        } else {
+            (&self.removed, &self.inserted, self.after.is_some())
+        };
+        exists.then(|| [&current[..self.prefix], to, &current[self.prefix + from.len()..]].concat())
+    }
+
+    /// Hash of the contents a file must hold to be restored.
+    fn expected(&self, undo: bool) -> Option<[u8; 32]> {
+        if undo {
+            self.after
+        } else {
+            self.before
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    label: String,
+    changes: Vec<FileChange>,
+    recorded_at: Instant,
+}
+
+impl JournalEntry {
+    fn size(&self) -> usize {
+        self.changes.iter().map(FileChange::size).sum()
+    }
+}
+
+/// Undo/redo stacks of metadata edits. Each step records how its files changed, so any command
+/// that rewrites JSON files can be undone without a per-command inverse.
+#[derive(Debug, Default)]
+pub struct Journal {
+    undo: VecDeque<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+pub struct JournalState(pub Mutex<Journal>);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalReplay {
+    /// Label of the step undone or redone, e.g. "Edit galleries.json".
+    pub label: String,
+    /// Files rewritten, for the frontend to reload.
+    pub paths: Vec<String>,
+    pub next_undo: Option<String>,
+    pub next_redo: Option<String>,
+    /// (path, existed before, exists after) for each file rewritten.
+    #[serde(skip)]
+    transitions: Vec<(PathBuf, bool, bool)>,
+}
+
+impl Journal {
+    /// Record the (path, before, after) contents of the files a command changed as one step.
+    fn record(&mut self, label: &str, changes: Vec<(PathBuf, Contents, Contents)>, now: Instant) {
+        let changes: Vec<(PathBuf, Contents, Contents)> = changes.into_iter().filter(|(_, before, after)| before != after).collect();
+        if changes.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        if let Some(last) = self.undo.back_mut() {
+            let same_files = last.changes.len() == changes.len()
+                && last.changes.iter().zip(&changes).all(|(a, (path, before, _))| {
+                    a.path == *path && a.after == before.as_deref().map(digest)
+                });
+            if last.label == label && same_files && now.duration_since(last.recorded_at) <= COALESCE_WINDOW {
+                for (existing, (path, before, after)) in last.changes.iter_mut().zip(changes) {
+                    // The step's original contents, recovered from this edit's starting point
+                    let original = existing.restore(before.as_deref().unwrap_or_default(), true);
+                    *existing = FileChange::new(&path, &original, &after);
+                }
+                last.recorded_at = now;
+                return;
+            }
+        }
+        let changes = changes.iter().map(|(path, before, after)| FileChange::new(path, before, after)).collect();
+        self.undo.push_back(JournalEntry { label: label.to_string(), changes, recorded_at: now });
+        let mut size: usize = self.undo.iter().map(JournalEntry::size).sum();
+        // The newest step is kept even when it alone is over the byte limit
+        while self.undo.len() > 1 && (self.undo.len() > MAX_JOURNAL_ENTRIES || size > MAX_JOURNAL_BYTES) {
+            size -= self.undo.pop_front().map_or(0, |entry| entry.size());
+        }
+    }
+
+    fn replay(&self, entry: &JournalEntry, undone: bool) -> JournalReplay {
+        JournalReplay {
+            label: entry.label.clone(),
+            paths: entry.changes.iter().map(|c| c.path.to_string_lossy().to_string()).collect(),
+            next_undo: self.undo.back().map(|e| e.label.clone()),
+            next_redo: self.redo.last().map(|e| e.label.clone()),
+            transitions: entry
+                .changes
+                .iter()
+                .map(|c| {
+                    let (from, to) = if undone { (c.after, c.before) } else { (c.before, c.after) };
+                    (c.path.clone(), from.is_some(), to.is_some())
+                })
+                .collect(),
+        }
+    }
+
+    fn undo(&mut self, read_only: &ReadOnlyState) -> Result<Option<JournalReplay>, String> {
+        let Some(entry) = self.undo.pop_back() else {
+            return Ok(None);
+        };
+        if let Err(e) = restore(&entry.changes, true, read_only) {
+            self.undo.push_back(entry);
+            return Err(e);
+        }
+        self.redo.push(entry);
+        let entry = self.redo.last().expect("just pushed");
+        Ok(Some(self.replay(entry, true)))
+    }
+
+    fn redo(&mut self, read_only: &ReadOnlyState) -> Result<Option<JournalReplay>, String> {
+        let Some(entry) = self.redo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = restore(&entry.changes, false, read_only) {
+            self.redo.push(entry);
+            return Err(e);
+        }
+        // A redone step never absorbs the next edit
+        let mut entry = entry;
+        entry.recorded_at = Instant::now().checked_sub(COALESCE_WINDOW * 2).unwrap_or(entry.recorded_at);
+        self.undo.push_back(entry);
+        let entry = self.undo.back().expect("just pushed");
+        Ok(Some(self.replay(entry, false)))
+    }
+}
+
+fn snapshot(path: &Path) -> Result<Contents, String> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+fn write_snapshot(path: &Path, content: &Contents) -> Result<(), String> {
+    match content {
+        Some(content) => {
+            let parent = path.parent().ok_or("No parent directory")?;
+            let temp_path = parent.join(format!(".{}.tmp", path.file_name().unwrap_or_default().to_string_lossy()));
+            fs::write(&temp_path, content).map_err(|e| e.to_string())?;
+            fs::rename(&temp_path, path).map_err(|e| e.to_string())
+        }
+        None if path.exists() => fs::remove_file(path).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Undo (or redo) `changes`, provided every file still holds the contents the step left (or
+/// started from). Checks all files before writing any, so a step is restored entirely or not
+/// at all.
+fn restore(changes: &[FileChange], undo: bool, read_only: &ReadOnlyState) -> Result<(), String> {
+    let mut restored = Vec::new();
+    for change in changes {
+        read_only.ensure_writable(&change.path)?;
+        if crate::details_store::has_pending(&change.path) {
+            return Err(format!("{} has edits still being saved. Try again in a moment.", change.path.display()));
+        }
+        let current = snapshot(&change.path)?;
+        if current.as_deref().map(digest) != change.expected(undo) {
+            return Err(format!("{} was changed outside this edit and can't be restored.", change.path.display()));
+        }
+        restored.push(change.restore(current.as_deref().unwrap_or_default(), undo));
+    }
+    for (change, content) in changes.iter().zip(&restored) {
+        write_snapshot(&change.path, content)?;
+    }
+    Ok(())
+}
+
+impl JournalState {
+    /// Run `op`, which may rewrite any of `paths`, and record the changes it made as one undo step.
+    pub(crate) fn track<T>(
+        &self,
+        label: &str,
+        paths: &[PathBuf],
+        op: impl FnOnce() -> Result<T, String>,
+    ) -> Result<T, String> {
+        let before = paths.iter().map(|p| snapshot(p)).collect::<Result<Vec<_>, _>>()?;
+        let result = op()?;
+        let mut changes = Vec::new();
+        for (path, before) in paths.iter().zip(before) {
+            changes.push((path.clone(), before, snapshot(path)?));
+        }
+        self.0.lock().map_err(|e| e.to_string())?.record(label, changes, Instant::now());
+        Ok(result)
+    }
+
+    /// Record a write of `path` the caller made itself, from contents it already holds, as one
+    /// undo step; spares `track` reading the file back.
+    pub(crate) fn record_write(&self, label: &str, path: &Path, before: Contents, after: Vec<u8>) -> Result<(), String> {
+        let changes = vec![(path.to_path_buf(), before, Some(after))];
+        self.0.lock().map_err(|e| e.to_string())?.record(label, changes, Instant::now());
+        Ok(())
+    }
+}
+
+/// Default undo label for an edit of `path`: the file name with its gallery folder.
+pub(crate) fn edit_label(path: &Path) -> String {
+    let file = path.file_name().unwrap_or_default().to_string_lossy();
+    match path.parent().and_then(|p| p.file_name()) {
+        Some(dir) if file != "galleries.json" => format!("Edit {}/{}", dir.to_string_lossy(), file),
+        _ => format!("Edit {}", file),
+    }
+}
+
+fn emit_replay(app: &tauri::AppHandle, replay: &Option<JournalReplay>, command: &str) {
+    for (path, existed, exists) in replay.iter().flat_map(|r| &r.transitions) {
+        if let Some(change) = json_file_change(path, *existed, *exists, ChangeOrigin::User, command) {
+            emit_change(app, change);
+        }
+    }
+}
+
+/// Undo the most recent metadata edit. Returns `None` when there is nothing to undo.
+#[tauri::command]
+pub async fn undo_last(
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+    read_only: tauri::State<'_, ReadOnlyState>,
+) -> Result<Option<JournalReplay>, String> {
+    let replay = journal.0.lock().map_err(|e| e.to_string())?.undo(&read_only)?;
+    emit_replay(&app, &replay, "undo_last");
+    Ok(replay)
+}
+
+/// Redo the most recently undone edit. Returns `None` when there is nothing to redo.
+#[tauri::command]
+pub async fn redo(
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+    read_only: tauri::State<'_, ReadOnlyState>,
+) -> Result<Option<JournalReplay>, String> {
+    let replay = journal.0.lock().map_err(|e| e.to_string())?.redo(&read_only)?;
+    emit_replay(&app, &replay, "redo");
+    Ok(replay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn change(path: &Path, before: Option<&str>, after: Option<&str>) -> (PathBuf, Contents, Contents) {
+        (path.to_path_buf(), before.map(|b| b.as_bytes().to_vec()), after.map(|a| a.as_bytes().to_vec()))
+    }
+
+    #[test]
+    fn test_undo_redo_restores_contents() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("galleries.json");
+        fs::write(&path, "v2").unwrap();
+        let mut journal = Journal::default();
+        journal.record("Edit galleries.json", vec![change(&path, Some("v1"), Some("v2"))], Instant::now());
+
+        let replay = journal.undo(&ReadOnlyState::default()).unwrap().unwrap();
+        assert_eq!(replay.label, "Edit galleries.json");
+        assert_eq!(replay.next_redo.as_deref(), Some("Edit galleries.json"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "v1");
+        assert!(journal.undo(&ReadOnlyState::default()).unwrap().is_none());
+
+        journal.redo(&ReadOnlyState::default()).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "v2");
+    }
+
+    #[test]
+    fn test_undo_of_created_file_removes_it() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("gallery-details.json");
+        fs::write(&path, "new").unwrap();
+        let mut journal = Journal::default();
+        journal.record("Edit", vec![change(&path, None, Some("new"))], Instant::now());
+        let replay = journal.undo(&ReadOnlyState::default()).unwrap().unwrap();
+        assert!(!path.exists());
+        assert_eq!(replay.transitions, vec![(path.clone(), true, false)]);
+    }
+
+    #[test]
+    fn test_undo_refuses_external_changes() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("galleries.json");
+        fs::write(&path, "edited elsewhere").unwrap();
+        let mut journal = Journal::default();
+        journal.record("Edit", vec![change(&path, Some("v1"), Some("v2"))], Instant::now());
+        assert!(journal.undo(&ReadOnlyState::default()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited elsewhere");
+        // The step stays available
+        assert_eq!(journal.undo.len(), 1);
+    }
+
+    #[test]
+    fn test_record_coalesces_and_bounds() {
+        let path = PathBuf::from("/w/galleries.json");
+        let start = Instant::now();
+        let mut journal = Journal::default();
+        journal.record("Edit", vec![change(&path, Some("a"), Some("ab"))], start);
+        journal.record("Edit", vec![change(&path, Some("ab"), Some("abc"))], start + Duration::from_millis(500));
+        assert_eq!(journal.undo.len(), 1);
+        let (_, a, abc) = change(&path, Some("a"), Some("abc"));
+        assert_eq!(journal.undo[0].changes[0], FileChange::new(&path, &a, &abc));
+
+        // Outside the window, a new step
+        journal.record("Edit", vec![change(&path, Some("abc"), Some("abcd"))], start + Duration::from_secs(10));
+        assert_eq!(journal.undo.len(), 2);
+        // No-op writes aren't recorded
+        journal.record("Edit", vec![change(&path, Some("x"), Some("x"))], start);
+        assert_eq!(journal.undo.len(), 2);
+
+        for i in 0..MAX_JOURNAL_ENTRIES + 5 {
+            let other = PathBuf::from(format!("/w/{}.json", i));
+            journal.record("Edit", vec![change(&other, None, Some("x"))], start);
+        }
+        assert_eq!(journal.undo.len(), MAX_JOURNAL_ENTRIES);
+    }
+
+    #[test]
+    fn test_large_file_edit_records_the_edited_region() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("gallery-details.json");
+        let before = format!("{{\"photos\":[{}\"alt\":\"old\"}}]}}", "\"x\",".repeat(100_000));
+        let after = before.replace("old", "new");
+        fs::write(&path, &after).unwrap();
+        let mut journal = Journal::default();
+        journal.record("Edit", vec![change(&path, Some(&before), Some(&after))], Instant::now());
+        assert_eq!(journal.undo[0].size(), 6);
+
+        journal.undo(&ReadOnlyState::default()).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+        journal.redo(&ReadOnlyState::default()).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), after);
+
+        // Steps are also dropped once they hold too many bytes
+        let big = "y".repeat(MAX_JOURNAL_BYTES / 2);
+        for i in 0..3 {
+            let other = PathBuf::from(format!("/w/{}.json", i));
+            journal.record("Edit", vec![change(&other, None, Some(&big))], Instant::now());
+        }
+        assert_eq!(journal.undo.len(), 2);
+        assert!(journal.undo.iter().map(JournalEntry::size).sum::<usize>() <= MAX_JOURNAL_BYTES);
+    }
+
+    #[test]
+    fn test_edit_label() {
+        assert_eq!(edit_label(Path::new("/w/galleries.json")), "Edit galleries.json");
+        assert_eq!(edit_label(Path::new("/w/sunset/gallery-details.json")), "Edit sunset/gallery-details.json");
+    }
+}
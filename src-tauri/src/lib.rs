@@ -1,14 +1,68 @@
+mod acceleration;
+mod activity;
+mod adopt;
+mod announcement;
+mod archive;
+mod astronomy;
+mod audit;
+mod auto_publish;
+mod automation;
+mod backups;
+mod bucket_snapshot;
+mod budget;
+mod cache_warm;
+mod changes;
+mod cloudfront;
+mod comments;
+mod companion;
+mod compat;
+mod custom_fields;
+mod details_store;
+mod encryption;
+mod folder_import;
+mod health;
+mod i18n;
+mod image_lint;
+mod index_worker;
+mod integrity;
+mod journal;
+mod locations;
+mod managed_area;
+mod manifest;
+mod metadata;
+mod mirror;
+mod palette;
+mod photos;
+mod plugins;
+mod prefix_cleanup;
+mod previews;
+mod prints;
 mod publish;
+mod read_only;
+mod search;
 mod settings;
+mod settings_impact;
+mod site_html;
+mod site_template;
+mod social;
+mod stamps;
+mod summary;
 mod thumbnails;
+mod timeline;
+mod transfers;
+mod unpublished;
+mod workspace;
 
+use changes::{ChangeOrigin, WorkspaceChange};
 use notify_debouncer_mini::Debouncer;
 use notify_debouncer_mini::notify::RecommendedWatcher;
 use serde::{Deserialize, Serialize};
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
 pub struct WatcherState(pub Mutex<Option<Debouncer<RecommendedWatcher>>>);
@@ -103,6 +157,17 @@ fn classify_fs_event(path: &Path, workspace: &Path) -> Option<FsChangeEvent> {
     }
 }
 
+/// The `workspace-changed` equivalent of a filesystem event.
+fn fs_change_to_workspace_change(event: &FsChangeEvent) -> WorkspaceChange {
+    let slug = event.gallery_slug.clone().unwrap_or_default();
+    let (entity_type, id) = match &event.filename {
+        Some(filename) => ("image", format!("{}/{}", slug, filename)),
+        None => ("directory", slug),
+    };
+    let change_kind = if event.kind.ends_with("-created") { "created" } else { "removed" };
+    WorkspaceChange::new(entity_type, &id, change_kind, ChangeOrigin::Watcher, "watcher")
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DirListing {
     pub directories: Vec<String>,
@@ -130,17 +195,12 @@ async fn open_folder_dialog(app: tauri::AppHandle) -> Result<Option<String>, Str
     Ok(folder.map(|p| p.to_string()))
 }
 
-#[tauri::command]
-async fn scan_directory(path: String) -> Result<DirListing, String> {
-    let dir_path = PathBuf::from(&path);
-    if !dir_path.is_dir() {
-        return Err(format!("Not a directory: {}", path));
-    }
-
+/// Non-hidden subdirectories and image files of `dir_path`, each sorted by name.
+pub(crate) fn list_directory(dir_path: &Path) -> Result<DirListing, String> {
     let mut directories = Vec::new();
     let mut images = Vec::new();
 
-    let entries = fs::read_dir(&dir_path).map_err(|e| e.to_string())?;
+    let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
 
     for entry in entries {
         let entry = entry.map_err(|e| e.to_string())?;
@@ -156,25 +216,51 @@ async fn scan_directory(path: String) -> Result<DirListing, String> {
             directories.push(name);
         } else if file_type.is_file() && is_image_file(&entry.path()) {
             images.push(name);
+        } else if let Some(original) = encryption::encrypted_original(&entry.path()).filter(|p| is_image_file(p)) {
+            // Originals of encrypted galleries are listed under their own names
+            images.push(original.file_name().unwrap_or_default().to_string_lossy().to_string());
         }
     }
 
     directories.sort();
     images.sort();
+    images.dedup();
 
     Ok(DirListing { directories, images })
 }
 
+#[tauri::command]
+async fn scan_directory(path: String) -> Result<DirListing, String> {
+    let dir_path = PathBuf::from(&path);
+    if !dir_path.is_dir() {
+        return Err(i18n::error(i18n::ErrorCode::NotADirectory, &[("path", &path)]));
+    }
+    list_directory(&dir_path)
+}
+
 #[tauri::command]
 async fn read_json_file(path: String) -> Result<serde_json::Value, String> {
+    // Include gallery edits still waiting to be written
+    if let Some(value) = details_store::pending_value(Path::new(&path)) {
+        return Ok(value);
+    }
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
     let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
     Ok(value)
 }
 
 #[tauri::command]
-async fn write_json_file(path: String, data: serde_json::Value) -> Result<(), String> {
+async fn write_json_file(
+    app: tauri::AppHandle,
+    path: String,
+    data: serde_json::Value,
+    journal: tauri::State<'_, journal::JournalState>,
+) -> Result<(), String> {
     let target = PathBuf::from(&path);
+    read_only::ensure_writable(&app, &target)?;
+    // The frontend read any pending gallery edits with the file, so its copy replaces them
+    details_store::take_pending(&target);
+    let existed = target.exists();
     let parent = target.parent().ok_or("No parent directory")?;
 
     // Ensure parent directory exists
@@ -190,8 +276,13 @@ async fn write_json_file(path: String, data: serde_json::Value) -> Result<(), St
     ));
 
     let json_string = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    fs::write(&temp_path, &json_string).map_err(|e| e.to_string())?;
-    fs::rename(&temp_path, &target).map_err(|e| e.to_string())?;
+    journal.track(&journal::edit_label(&target), std::slice::from_ref(&target), || {
+        fs::write(&temp_path, &json_string).map_err(|e| e.to_string())?;
+        fs::rename(&temp_path, &target).map_err(|e| e.to_string())
+    })?;
+    if let Some(change) = changes::json_file_change(&target, existed, true, ChangeOrigin::User, "write_json_file") {
+        changes::emit_change(&app, change);
+    }
 
     Ok(())
 }
@@ -297,16 +388,26 @@ async fn start_watching(
     let workspace = PathBuf::from(&workspace_path);
     let workspace_for_closure = workspace.clone();
     let app_handle = app.clone();
+    let index_worker = index_worker::IndexWorker::spawn(workspace.clone())?;
 
     let mut debouncer = notify_debouncer_mini::new_debouncer(
         Duration::from_millis(500),
         move |result: notify_debouncer_mini::DebounceEventResult| {
             if let Ok(events) = result {
-                for event in events {
-                    if let Some(payload) = classify_fs_event(&event.path, &workspace_for_closure) {
+                // Encrypting a gallery replaces its originals with `.afgenc` files; reporting
+                // them as removed would drop the photos from `gallery-details.json`
+                let paths: Vec<PathBuf> = events
+                    .into_iter()
+                    .map(|e| e.path)
+                    .filter(|path| !encryption::is_encrypted_original(path))
+                    .collect();
+                for path in &paths {
+                    if let Some(payload) = classify_fs_event(path, &workspace_for_closure) {
+                        changes::emit_change(&app_handle, fs_change_to_workspace_change(&payload));
                         let _ = app_handle.emit("workspace-fs-change", payload);
                     }
                 }
+                index_worker.queue(paths);
             }
         },
     )
@@ -329,6 +430,7 @@ async fn stop_watching(watcher_state: tauri::State<'_, WatcherState>) -> Result<
 
 #[tauri::command]
 async fn remove_photo_from_gallery_details(
+    app: tauri::AppHandle,
     workspace_path: String,
     slug: String,
     filename: String,
@@ -336,31 +438,16 @@ async fn remove_photo_from_gallery_details(
     let path = PathBuf::from(&workspace_path)
         .join(&slug)
         .join("gallery-details.json");
+    read_only::ensure_writable(&app, &path)?;
 
     if !path.exists() {
         return Ok(());
     }
 
-    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let mut value: serde_json::Value =
-        serde_json::from_str(&content).map_err(|e| e.to_string())?;
-
-    if let Some(photos) = value.get_mut("photos").and_then(|p| p.as_array_mut()) {
-        photos.retain(|photo| {
-            let thumb = photo.get("thumbnail").and_then(|v| v.as_str()).unwrap_or("");
-            let full = photo.get("full").and_then(|v| v.as_str()).unwrap_or("");
-            !thumb.ends_with(&*filename) && !full.ends_with(&*filename)
-        });
-    }
-
-    let parent = path.parent().ok_or("No parent directory")?;
-    let temp_path = parent.join(format!(
-        ".{}.tmp",
-        path.file_name().unwrap_or_default().to_string_lossy()
-    ));
-    let json_string = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
-    fs::write(&temp_path, &json_string).map_err(|e| e.to_string())?;
-    fs::rename(&temp_path, &path).map_err(|e| e.to_string())?;
+    // Batched with other removals (e.g. a folder of deleted files) into one write
+    details_store::remove_gallery_photos(app.clone(), workspace_path, slug.clone(), vec![filename.clone()]).await?;
+    let id = format!("{}/{}", slug, filename);
+    activity::record_activity(&app, "deletePhoto", id);
 
     Ok(())
 }
@@ -374,6 +461,20 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .manage(Mutex::new(publish::PublishState::new()))
         .manage(WatcherState(Mutex::new(None)))
+        .manage(health::HealthCheckState(Mutex::new(None)))
+        .manage(automation::AutomationState(Mutex::new(None)))
+        .manage(backups::BackupState(Mutex::new(None)))
+        .manage(auto_publish::AutoPublishState(Mutex::new(auto_publish::AutoPublish::default())))
+        .manage(companion::CompanionState(Mutex::new(companion::Companion::default())))
+        .manage(journal::JournalState(Mutex::new(journal::Journal::default())))
+        .manage(previews::PrefetchState(Arc::new(AtomicU64::new(0))))
+        .manage(encryption::EncryptionState(Mutex::new(HashMap::new())))
+        .manage(read_only::ReadOnlyState::default())
+        .setup(|app| {
+            // Decrypted copies of encrypted galleries live in the app cache, outside any workspace
+            encryption::init_decrypted_cache(&app.path().app_cache_dir()?);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             open_folder_dialog,
             scan_directory,
@@ -392,11 +493,107 @@ pub fn run() {
             settings::has_credentials,
             settings::get_credential_hint,
             settings::delete_credentials,
+            settings::check_credential_store,
             settings::validate_credentials,
             publish::publish_preview,
             publish::publish_execute,
             publish::publish_cancel,
+            announcement::generate_announcement,
+            social::export_social_crops,
+            prints::get_print_size_report,
+            photos::pair_photos,
+            photos::set_photo_external_url,
+            astronomy::enrich_astronomy,
+            timeline::get_workspace_timeline,
+            locations::get_photos_by_location,
+            search::search_workspace,
+            search::suggest_search_terms,
+            cloudfront::configure_response_headers,
+            health::run_health_check,
+            health::start_health_checks,
+            health::stop_health_checks,
+            audit::audit_live_site,
+            adopt::adopt_site,
+            settings_impact::preview_settings_change,
+            prefix_cleanup::list_old_prefix_objects,
+            prefix_cleanup::cleanup_old_prefix,
+            journal::undo_last,
+            journal::redo,
+            summary::load_workspace_summary,
+            summary::load_cached_workspace_summary,
+            previews::prefetch_previews,
+            publish::audit_publish_plan,
+            acceleration::compare_transfer_acceleration,
+            i18n::list_locales,
+            transfers::get_transfer_history,
+            integrity::verify_workspace_integrity,
+            integrity::accept_integrity_changes,
+            archive::archive_gallery,
+            archive::restore_archived_gallery,
+            archive::get_archive_status,
+            archive::retrieve_restored_originals,
+            mirror::save_mirror_credentials,
+            mirror::delete_mirror_credentials,
+            encryption::get_encryption_status,
+            encryption::enable_encryption,
+            encryption::unlock_workspace,
+            encryption::lock_workspace,
+            encryption::set_gallery_encrypted,
+            activity::get_audit_log,
+            custom_fields::get_gallery_custom_fields,
+            custom_fields::set_gallery_custom_field,
+            plugins::list_plugins,
+            folder_import::import_folder,
+            automation::get_automation_endpoint,
+            automation::start_automation_server,
+            automation::stop_automation_server,
+            automation::reset_automation_token,
+            companion::start_companion_server,
+            companion::stop_companion_server,
+            companion::start_companion_pairing,
+            companion::list_network_interfaces,
+            companion::list_companion_devices,
+            companion::revoke_companion_device,
+            unpublished::get_unpublished_changes,
+            backups::start_metadata_backups,
+            backups::stop_metadata_backups,
+            backups::list_metadata_backups,
+            backups::restore_metadata_backup,
+            read_only::set_workspace_read_only,
+            compat::check_workspace_compatibility,
+            compat::migrate_workspace,
+            image_lint::lint_workspace_images,
+            image_lint::fix_image_formats,
+            thumbnails::compare_thumbnail_settings,
+            bucket_snapshot::record_bucket_snapshot,
+            bucket_snapshot::get_bucket_snapshot_info,
+            bucket_snapshot::publish_simulate,
+            auto_publish::start_auto_publish,
+            auto_publish::stop_auto_publish,
+            details_store::get_gallery_photos_page,
+            details_store::update_gallery_photos,
+            details_store::remove_gallery_photos,
+            details_store::flush_gallery_details,
+            details_store::rollback_gallery_details,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| {
+            // Keep the window open when pending gallery edits can't be written, so the
+            // `gallery-details-flush-failed` prompt can offer a rollback
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if details_store::flush_all(window.app_handle()).is_err() {
+                    api.prevent_close();
+                }
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Don't leave encrypted galleries readable after the app closes
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = details_store::flush_all(app) {
+                    eprintln!("[details_store] Failed to save pending edits on exit: {}", e);
+                }
+                encryption::lock_all(&app.state::<encryption::EncryptionState>());
+            }
+        });
 }
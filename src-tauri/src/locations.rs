@@ -0,0 +1,188 @@
+use crate::metadata::{current_metadata_index, split_index_key, PhotoRef};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Default clustering radius. Roughly "same town / same venue cluster" at map zoom levels
+/// where individual pins would overlap.
+const DEFAULT_CLUSTER_RADIUS_KM: f64 = 5.0;
+/// Maximum number of representative photos returned per cluster.
+const CLUSTER_SAMPLES: usize = 4;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationCluster {
+    /// Centroid of the photos in the cluster.
+    pub latitude: f64,
+    pub longitude: f64,
+    pub count: usize,
+    /// Slugs of galleries with photos in this cluster.
+    pub galleries: Vec<String>,
+    /// Up to four photos, each from a different gallery where possible.
+    pub samples: Vec<PhotoRef>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoLocations {
+    /// Largest cluster first.
+    pub clusters: Vec<LocationCluster>,
+    /// Photos without a GPS position.
+    pub unlocated: usize,
+}
+
+/// Great-circle distance in kilometres between two points.
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (p1, p2) = (lat1.to_radians(), lat2.to_radians());
+    let dp = (lat2 - lat1).to_radians();
+    let dl = (lon2 - lon1).to_radians();
+    let a = (dp / 2.0).sin().powi(2) + p1.cos() * p2.cos() * (dl / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+struct ClusterBuilder {
+    lat_sum: f64,
+    lon_sum: f64,
+    cluster: LocationCluster,
+}
+
+impl ClusterBuilder {
+    fn centroid(&self) -> (f64, f64) {
+        let n = self.cluster.count as f64;
+        (self.lat_sum / n, self.lon_sum / n)
+    }
+}
+
+/// Greedy leader clustering: each photo joins the nearest cluster whose centroid is within
+/// `radius_km`, otherwise starts a new one. Deterministic for a given input order.
+fn cluster_photos(photos: Vec<(PhotoRef, f64, f64)>, radius_km: f64) -> Vec<LocationCluster> {
+    let mut builders: Vec<ClusterBuilder> = Vec::new();
+    for (photo, lat, lon) in photos {
+        let nearest = builders
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let (clat, clon) = b.centroid();
+                (i, haversine_km(lat, lon, clat, clon))
+            })
+            .filter(|(_, d)| *d <= radius_km)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i);
+
+        let builder = match nearest {
+            Some(i) => &mut builders[i],
+            None => {
+                builders.push(ClusterBuilder {
+                    lat_sum: 0.0,
+                    lon_sum: 0.0,
+                    cluster: LocationCluster {
+                        latitude: 0.0,
+                        longitude: 0.0,
+                        count: 0,
+                        galleries: Vec::new(),
+                        samples: Vec::new(),
+                    },
+                });
+                builders.last_mut().unwrap()
+            }
+        };
+        builder.lat_sum += lat;
+        builder.lon_sum += lon;
+        builder.cluster.count += 1;
+        if !builder.cluster.galleries.contains(&photo.gallery_slug) {
+            builder.cluster.galleries.push(photo.gallery_slug.clone());
+            if builder.cluster.samples.len() < CLUSTER_SAMPLES {
+                builder.cluster.samples.push(photo);
+            }
+        }
+    }
+
+    let mut clusters: Vec<LocationCluster> = builders
+        .into_iter()
+        .map(|b| {
+            let (lat, lon) = b.centroid();
+            LocationCluster {
+                latitude: (lat * 1e6).round() / 1e6,
+                longitude: (lon * 1e6).round() / 1e6,
+                ..b.cluster
+            }
+        })
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.count));
+    clusters
+}
+
+/// Cluster photos by EXIF GPS position (from the metadata index) for a map view.
+/// `radius_km` defaults to 5 km.
+#[tauri::command]
+pub async fn get_photos_by_location(
+    workspace_path: String,
+    radius_km: Option<f64>,
+) -> Result<PhotoLocations, String> {
+    let radius_km = radius_km.unwrap_or(DEFAULT_CLUSTER_RADIUS_KM);
+    if radius_km.is_nan() || radius_km <= 0.0 {
+        return Err("Cluster radius must be greater than zero.".to_string());
+    }
+    let root = PathBuf::from(&workspace_path);
+    tokio::task::spawn_blocking(move || {
+        let index = current_metadata_index(&root)?;
+        let mut located = Vec::new();
+        let mut unlocated = 0;
+        for (key, meta) in &index.photos {
+            match (meta.latitude, meta.longitude) {
+                (Some(lat), Some(lon)) => {
+                    let (slug, filename) = split_index_key(key);
+                    let photo = PhotoRef { gallery_slug: slug.to_string(), filename: filename.to_string() };
+                    located.push((photo, lat, lon));
+                }
+                _ => unlocated += 1,
+            }
+        }
+        Ok(PhotoLocations { clusters: cluster_photos(located, radius_km), unlocated })
+    })
+    .await
+    .map_err(|e| format!("Location clustering panicked: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn photo(slug: &str, file: &str, lat: f64, lon: f64) -> (PhotoRef, f64, f64) {
+        (PhotoRef { gallery_slug: slug.into(), filename: file.into() }, lat, lon)
+    }
+
+    #[test]
+    fn test_haversine_london_paris() {
+        let d = haversine_km(51.5074, -0.1278, 48.8566, 2.3522);
+        assert!((d - 343.5).abs() < 1.0, "got {}", d);
+    }
+
+    #[test]
+    fn test_cluster_photos_groups_nearby_points() {
+        let clusters = cluster_photos(
+            vec![
+                photo("a", "1.jpg", 51.5074, -0.1278),
+                photo("b", "1.jpg", 48.8566, 2.3522),
+                photo("a", "2.jpg", 51.5080, -0.1300),
+                photo("c", "1.jpg", 51.5000, -0.1200),
+            ],
+            5.0,
+        );
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].count, 3);
+        assert_eq!(clusters[0].galleries, vec!["a", "c"]);
+        assert_eq!(clusters[0].samples.len(), 2);
+        assert!((clusters[0].latitude - 51.5051).abs() < 0.001);
+        assert_eq!(clusters[1].count, 1);
+        assert_eq!(clusters[1].galleries, vec!["b"]);
+    }
+
+    #[test]
+    fn test_cluster_photos_radius() {
+        let points = vec![photo("a", "1.jpg", 0.0, 0.0), photo("a", "2.jpg", 0.0, 0.1)];
+        // 0.1° of longitude at the equator is ~11 km
+        assert_eq!(cluster_photos(points.clone(), 5.0).len(), 2);
+        assert_eq!(cluster_photos(points, 20.0).len(), 1);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::i18n::{error, ErrorCode};
+use crate::settings::{normalize_s3_prefix, AppSettings};
+use serde::Serialize;
+
+/// Folders under the site root that publishing owns.
+const DEFAULT_MANAGED_PREFIXES: &[&str] = &["galleries/", "afterglow/"];
+/// Single files at the site root that publishing owns.
+const DEFAULT_MANAGED_FILES: &[&str] = &["index.html", "favicon.ico", "favicon.png", "site-manifest.json"];
+
+/// The keyspace a site owns below its S3 root: what publishing uploads to and may delete from,
+/// and what counts as left behind when the site moves. The built-in folders and files plus any
+/// `managedExtraPaths` from settings.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ManagedArea {
+    /// Folder prefixes, ending in `/`.
+    prefixes: Vec<String>,
+    /// Exact file names.
+    files: Vec<String>,
+}
+
+impl Default for ManagedArea {
+    fn default() -> Self {
+        Self {
+            prefixes: DEFAULT_MANAGED_PREFIXES.iter().map(|p| p.to_string()).collect(),
+            files: DEFAULT_MANAGED_FILES.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl ManagedArea {
+    /// The built-in area extended with the validated `managedExtraPaths` setting.
+    pub(crate) fn from_settings(settings: &AppSettings) -> Result<Self, String> {
+        let mut area = Self::default();
+        for path in &settings.managed_extra_paths {
+            if path.trim().is_empty() {
+                continue;
+            }
+            match validate_managed_path(path)? {
+                ManagedPath::Prefix(prefix) if !area.prefixes.contains(&prefix) => area.prefixes.push(prefix),
+                ManagedPath::File(file) if !area.files.contains(&file) => area.files.push(file),
+                _ => {}
+            }
+        }
+        Ok(area)
+    }
+
+    /// Whether `key` is in the area of a site rooted at `s3_root`.
+    pub(crate) fn contains(&self, s3_root: &str, key: &str) -> bool {
+        let Some(rel) = key.strip_prefix(s3_root) else {
+            return false;
+        };
+        self.prefixes.iter().any(|p| rel.starts_with(p.as_str())) || self.files.iter().any(|f| rel == f)
+    }
+
+    /// Folders (with a trailing `/`) and files of the area, relative to the site root.
+    pub(crate) fn entries(&self) -> Vec<String> {
+        self.prefixes.iter().chain(&self.files).cloned().collect()
+    }
+}
+
+/// Result of checking a publish plan's deletes against the managed area it was previewed with
+/// and the one `publish_execute` would use now.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedKeyAudit {
+    pub preview_root: String,
+    pub execute_root: String,
+    /// Managed area entries at execute time, relative to `executeRoot`.
+    pub managed_entries: Vec<String>,
+    /// The S3 root and managed area are unchanged since the preview.
+    pub rules_match: bool,
+    pub checked: usize,
+    /// Planned deletes outside the preview-time or execute-time managed area.
+    pub outside: Vec<String>,
+    /// Safe to execute: the rules match and every delete is inside both areas.
+    pub ok: bool,
+}
+
+impl ManagedKeyAudit {
+    /// Why executing the plan is refused, or `None` when it's safe.
+    pub(crate) fn refusal(&self) -> Option<String> {
+        if !self.rules_match {
+            return Some(error(ErrorCode::ManagedRulesChanged, &[]));
+        }
+        let first = self.outside.first()?;
+        Some(error(ErrorCode::DeletesOutsideManagedArea, &[("count", &self.outside.len()), ("example", first)]))
+    }
+}
+
+/// Check `to_delete` against the preview-time and execute-time `(s3_root, area)`.
+pub(crate) fn audit_deletes(
+    preview: (&str, &ManagedArea),
+    execute: (&str, &ManagedArea),
+    to_delete: &[String],
+) -> ManagedKeyAudit {
+    let rules_match = preview.0 == execute.0 && preview.1 == execute.1;
+    let outside: Vec<String> = to_delete
+        .iter()
+        .filter(|key| !(preview.1.contains(preview.0, key) && execute.1.contains(execute.0, key)))
+        .cloned()
+        .collect();
+    ManagedKeyAudit {
+        preview_root: preview.0.to_string(),
+        execute_root: execute.0.to_string(),
+        managed_entries: execute.1.entries(),
+        rules_match,
+        checked: to_delete.len(),
+        ok: rules_match && outside.is_empty(),
+        outside,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ManagedPath {
+    Prefix(String),
+    File(String),
+}
+
+/// Validate a `managedExtraPaths` entry: a relative folder (`downloads/`) or file (`robots.txt`)
+/// under the site root, following the S3 prefix rules.
+fn validate_managed_path(path: &str) -> Result<ManagedPath, String> {
+    let trimmed = path.trim();
+    let normalized = normalize_s3_prefix(trimmed).map_err(|e| error(ErrorCode::ManagedPathInvalid, &[("path", &trimmed), ("error", &e)]))?;
+    if trimmed.ends_with('/') {
+        Ok(ManagedPath::Prefix(normalized))
+    } else {
+        Ok(ManagedPath::File(normalized.trim_end_matches('/').to_string()))
+    }
+}
+
+/// Check the `managedExtraPaths` setting before saving.
+pub(crate) fn validate_managed_extra_paths(paths: &[String]) -> Result<(), String> {
+    for path in paths.iter().filter(|p| !p.trim().is_empty()) {
+        validate_managed_path(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_area() {
+        let area = ManagedArea::default();
+        assert!(area.contains("", "galleries/a/01.jpg"));
+        assert!(area.contains("", "afterglow/js/app.js"));
+        assert!(area.contains("site/", "site/index.html"));
+        assert!(area.contains("site/", "site/favicon.ico"));
+        assert!(area.contains("site/", "site/favicon.png"));
+        assert!(area.contains("site/", "site/site-manifest.json"));
+        assert!(!area.contains("site/", "site/notes.txt"));
+        assert!(!area.contains("site/", "site/index.html.bak"));
+        assert!(!area.contains("site/", "other/galleries/a/01.jpg"));
+        assert!(!area.contains("", "blog/index.html"));
+    }
+
+    #[test]
+    fn test_area_from_settings() {
+        let settings = AppSettings {
+            managed_extra_paths: vec!["downloads/".to_string(), "robots.txt".to_string(), " ".to_string()],
+            ..AppSettings::default()
+        };
+        let area = ManagedArea::from_settings(&settings).unwrap();
+        assert!(area.contains("site/", "site/downloads/a.zip"));
+        assert!(area.contains("site/", "site/robots.txt"));
+        assert!(!area.contains("site/", "site/robots.txt/x"));
+        assert_eq!(area.prefixes.len(), DEFAULT_MANAGED_PREFIXES.len() + 1);
+
+        assert!(area.entries().contains(&"robots.txt".to_string()));
+
+        for bad in ["/abs/", "../up/", "a//b", "."] {
+            let settings = AppSettings { managed_extra_paths: vec![bad.to_string()], ..AppSettings::default() };
+            assert!(ManagedArea::from_settings(&settings).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_invalid_managed_path_names_the_entry() {
+        let err = validate_managed_extra_paths(&["downloads/".to_string(), "/robots.txt".to_string()]).unwrap_err();
+        assert_eq!(err, "[managed-path-invalid] Managed path \"/robots.txt\": S3 prefix \"/robots.txt\" must not start with \"/\".");
+    }
+
+    #[test]
+    fn test_audit_deletes() {
+        let area = ManagedArea::default();
+        let deletes = vec!["site/favicon.ico".to_string(), "site/galleries/a/01.jpg".to_string()];
+        let audit = audit_deletes(("site/", &area), ("site/", &area), &deletes);
+        assert!(audit.ok);
+        assert!(audit.refusal().is_none());
+
+        // A key outside the area is reported, not skipped
+        let mut overreach = deletes.clone();
+        overreach.push("site/blog/post.html".to_string());
+        let audit = audit_deletes(("site/", &area), ("site/", &area), &overreach);
+        assert_eq!(audit.outside, vec!["site/blog/post.html"]);
+        assert!(audit.refusal().unwrap().contains("1 planned deletes"));
+
+        // Settings changed between preview and execute
+        let audit = audit_deletes(("site/", &area), ("", &area), &deletes);
+        assert!(!audit.rules_match);
+        assert_eq!(audit.outside.len(), 2);
+        assert!(audit.refusal().unwrap().contains("Preview the publish again"));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::encryption::stored_original;
+use crate::site_template::SiteTemplate;
+use crate::stamps::StampMap;
+use crate::workspace::{read_galleries, read_gallery_details, str_field};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Site manifest schema version.
+const SITE_MANIFEST_VERSION: u32 = 1;
+
+/// A published JSON file the website can fetch, with the stamp it should use to bust caches.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFile {
+    /// Path relative to the site root (the folder containing `index.html`).
+    pub path: String,
+    pub last_updated: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestGallery {
+    pub slug: String,
+    pub name: String,
+    pub date: String,
+    pub photo_count: usize,
+    pub last_updated: String,
+    pub details: String,
+    /// Photo search shard, absent for galleries without photos.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_shard: Option<String>,
+}
+
+/// `{s3Root}site-manifest.json`: the website's single entry point. Lists every published
+/// JSON file with a last-updated stamp so the site can fetch the manifest with revalidation
+/// and everything else with a cache-busting `?v=` parameter.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteManifest {
+    pub version: u32,
+    /// Version of the published website (the app version it shipped with).
+    pub theme_version: String,
+    pub template: SiteTemplate,
+    pub last_updated: String,
+    pub galleries_index: ManifestFile,
+    pub search_index: ManifestFile,
+    pub galleries: Vec<ManifestGallery>,
+    /// Content stamp of every published JSON file, keyed by path relative to the site root.
+    pub files: StampMap,
+}
+
+fn mtime_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format seconds since the epoch as an RFC 3339 UTC timestamp ("2026-02-28T14:05:09Z").
+pub fn format_utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Build `site-manifest.json`. Timestamps come from workspace file mtimes (a gallery is as
+/// new as its `gallery-details.json` or newest photo), so an unchanged workspace produces a
+/// byte-identical manifest and publishing doesn't re-upload it.
+///
+/// `search_shards` maps gallery slug → shard path relative to `galleries/`; `stamps` are the
+/// publish stamps of the other published JSON files.
+pub fn build_site_manifest(
+    root: &Path,
+    search_shards: &HashMap<String, String>,
+    stamps: &StampMap,
+    template: &SiteTemplate,
+) -> Result<Vec<u8>, String> {
+    let galleries = if root.join("galleries.json").exists() { read_galleries(root)? } else { Vec::new() };
+    let mut site_updated = mtime_secs(&root.join("galleries.json"));
+    let mut galleries_out = Vec::new();
+
+    for gallery in &galleries {
+        let slug = str_field(gallery, "slug");
+        if slug.is_empty() {
+            continue;
+        }
+        let details_path = root.join(&slug).join("gallery-details.json");
+        let mut updated = mtime_secs(&details_path);
+        let photos = read_gallery_details(root, &slug)
+            .ok()
+            .flatten()
+            .and_then(|d| d.get("photos").and_then(|p| p.as_array()).cloned())
+            .unwrap_or_default();
+        for photo in &photos {
+            let full = str_field(photo, "full");
+            if !full.is_empty() {
+                updated = updated.max(mtime_secs(&stored_original(&root.join(&slug).join(&full))));
+            }
+        }
+        site_updated = site_updated.max(updated);
+        galleries_out.push(ManifestGallery {
+            name: str_field(gallery, "name"),
+            date: str_field(gallery, "date"),
+            photo_count: photos.len(),
+            last_updated: format_utc_timestamp(updated),
+            details: format!("galleries/{}/gallery-details.json", slug),
+            search_shard: search_shards.get(&slug).map(|p| format!("galleries/{}", p)),
+            slug,
+        });
+    }
+
+    let last_updated = format_utc_timestamp(site_updated);
+    let manifest = SiteManifest {
+        version: SITE_MANIFEST_VERSION,
+        theme_version: template.version.clone(),
+        template: template.clone(),
+        galleries_index: ManifestFile {
+            path: "galleries/galleries.json".to_string(),
+            last_updated: last_updated.clone(),
+        },
+        search_index: ManifestFile {
+            path: "galleries/search-index.json".to_string(),
+            last_updated: last_updated.clone(),
+        },
+        last_updated,
+        galleries: galleries_out,
+        files: stamps.clone(),
+    };
+    serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_format_utc_timestamp() {
+        assert_eq!(format_utc_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc_timestamp(1_772_287_509), "2026-02-28T14:05:09Z");
+    }
+
+    #[test]
+    fn test_build_site_manifest() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "galleries.json",
+            r#"{"schemaVersion":1,"galleries":[
+                {"name":"A","slug":"a","date":"01/02/2026","cover":"a/1.jpg"},
+                {"name":"B","slug":"b","date":"","cover":""}
+            ]}"#,
+        );
+        write(root, "a/gallery-details.json", r#"{"photos":[{"full":"1.jpg"},{"full":"2.jpg"}]}"#);
+        write(root, "a/1.jpg", "x");
+
+        let shards = HashMap::from([("a".to_string(), ".search/a.json".to_string())]);
+        let mut stamps = StampMap::new();
+        stamps.insert(
+            "galleries/galleries.json".to_string(),
+            crate::stamps::PublishStamp { hash: "abc".to_string(), generation: 2 },
+        );
+        let template = crate::site_template::bundled_template();
+        let bytes = build_site_manifest(root, &shards, &stamps, &template).unwrap();
+        let manifest: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(manifest["version"], 1);
+        assert_eq!(manifest["themeVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest["template"]["hash"], template.hash);
+        assert_eq!(manifest["galleriesIndex"]["path"], "galleries/galleries.json");
+        assert_eq!(manifest["searchIndex"]["path"], "galleries/search-index.json");
+        let a = &manifest["galleries"][0];
+        assert_eq!(a["slug"], "a");
+        assert_eq!(a["photoCount"], 2);
+        assert_eq!(a["details"], "galleries/a/gallery-details.json");
+        assert_eq!(a["searchShard"], "galleries/.search/a.json");
+        assert!(manifest["galleries"][1].get("searchShard").is_none());
+        assert_eq!(manifest["galleries"][1]["photoCount"], 0);
+        assert_eq!(manifest["files"]["galleries/galleries.json"]["hash"], "abc");
+        assert_eq!(manifest["files"]["galleries/galleries.json"]["generation"], 2);
+
+        // Deterministic for an unchanged workspace
+        assert_eq!(build_site_manifest(root, &shards, &stamps, &template).unwrap(), bytes);
+    }
+}
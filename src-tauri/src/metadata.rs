@@ -0,0 +1,412 @@
+use crate::encryption::{readable_original, stored_original};
+use crate::workspace::{read_galleries, read_gallery_details, str_field, write_json_atomic};
+use exif::{In, Tag, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// v2: `width`/`height` are the displayed size, after EXIF rotation.
+pub(crate) const METADATA_INDEX_VERSION: u32 = 2;
+
+/// Serializes writers of the index (the index worker and commands refreshing it).
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Capture metadata read from a photo's EXIF block.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhotoExif {
+    /// Local capture time as (year, month, day, hour, minute, second).
+    pub taken_at: Option<(i32, u32, u32, u32, u32, u32)>,
+    /// UTC offset of `taken_at` in minutes, from `OffsetTimeOriginal` when the camera wrote it.
+    pub utc_offset_minutes: Option<i32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// EXIF `Orientation` (1–8); 5–8 are displayed turned a quarter turn.
+    pub orientation: Option<u32>,
+}
+
+fn ascii_field(exif: &exif::Exif, tag: Tag) -> Option<Vec<u8>> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(parts) => parts.first().cloned(),
+        _ => None,
+    }
+}
+
+/// Convert a GPS degrees/minutes/seconds triple plus its N/S/E/W reference to signed degrees.
+fn gps_coordinate(exif: &exif::Exif, tag: Tag, ref_tag: Tag, negative_ref: u8) -> Option<f64> {
+    let dms = match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(parts) if parts.len() >= 3 => {
+            parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0
+        }
+        _ => return None,
+    };
+    if !dms.is_finite() {
+        return None;
+    }
+    let negative = ascii_field(exif, ref_tag)
+        .and_then(|r| r.first().copied())
+        .map(|c| c.eq_ignore_ascii_case(&negative_ref))
+        .unwrap_or(false);
+    Some(if negative { -dms } else { dms })
+}
+
+/// Read capture time and GPS position from an image's EXIF block.
+/// Images without EXIF (or with unreadable EXIF) yield an empty `PhotoExif`.
+pub fn read_exif(path: &Path) -> Result<PhotoExif, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let exif = match exif::Reader::new().read_from_container(&mut BufReader::new(file)) {
+        Ok(exif) => exif,
+        Err(_) => return Ok(PhotoExif::default()),
+    };
+
+    let mut out = PhotoExif::default();
+    let datetime = ascii_field(&exif, Tag::DateTimeOriginal)
+        .or_else(|| ascii_field(&exif, Tag::DateTime))
+        .and_then(|raw| exif::DateTime::from_ascii(&raw).ok());
+    if let Some(mut dt) = datetime {
+        if let Some(offset) = ascii_field(&exif, Tag::OffsetTimeOriginal) {
+            let _ = dt.parse_offset(&offset);
+        }
+        out.taken_at = Some((
+            dt.year as i32,
+            dt.month as u32,
+            dt.day as u32,
+            dt.hour as u32,
+            dt.minute as u32,
+            dt.second as u32,
+        ));
+        out.utc_offset_minutes = dt.offset.map(|o| o as i32);
+    }
+    out.latitude = gps_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S');
+    out.longitude = gps_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W');
+    out.orientation = exif.get_field(Tag::Orientation, In::PRIMARY).and_then(|f| f.value.get_uint(0));
+    Ok(out)
+}
+
+/// Reference to a photo by gallery and `full` filename, returned by browsing APIs so the
+/// frontend can resolve a thumbnail via `get_image_uri`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoRef {
+    pub gallery_slug: String,
+    pub filename: String,
+}
+
+/// Cached per-photo metadata, keyed by `{slug}/{full}` in the metadata index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoMetadata {
+    /// Source file mtime (seconds since the epoch) the entry was read at.
+    pub modified: u64,
+    /// Displayed size in pixels: the stored size, swapped when EXIF says the photo is rotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Local capture time, "YYYY-MM-DDTHH:MM:SS".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taken_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+}
+
+impl PhotoMetadata {
+    /// (year, month) of the capture time, if known.
+    pub fn taken_year_month(&self) -> Option<(i32, u32)> {
+        let taken = self.taken_at.as_deref()?;
+        let year = taken.get(0..4)?.parse().ok()?;
+        let month = taken.get(5..7)?.parse().ok()?;
+        Some((year, month))
+    }
+
+    /// Width divided by height, rounded to 4 decimals.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        let (width, height) = (self.width?, self.height?);
+        (height > 0).then(|| (width as f64 / height as f64 * 10_000.0).round() / 10_000.0)
+    }
+
+    /// "portrait", "landscape" or "square", from the displayed size.
+    pub fn orientation(&self) -> Option<&'static str> {
+        let (width, height) = (self.width?, self.height?);
+        Some(match width.cmp(&height) {
+            std::cmp::Ordering::Less => "portrait",
+            std::cmp::Ordering::Greater => "landscape",
+            std::cmp::Ordering::Equal => "square",
+        })
+    }
+}
+
+/// `{workspace}/.data/metadata-index.json`: EXIF and dimension data for every tracked photo,
+/// so browsing views don't have to open image files.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataIndex {
+    pub version: u32,
+    pub photos: BTreeMap<String, PhotoMetadata>,
+}
+
+impl Default for MetadataIndex {
+    fn default() -> Self {
+        MetadataIndex { version: METADATA_INDEX_VERSION, photos: BTreeMap::new() }
+    }
+}
+
+pub(crate) fn metadata_index_path(root: &Path) -> std::path::PathBuf {
+    root.join(".data").join("metadata-index.json")
+}
+
+fn file_mtime_secs(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+fn read_photo_metadata(path: &Path, modified: u64) -> PhotoMetadata {
+    let exif = read_exif(path).unwrap_or_default();
+    let (width, height) = match image::image_dimensions(path) {
+        Ok((w, h)) if matches!(exif.orientation, Some(5..=8)) => (Some(h), Some(w)),
+        Ok((w, h)) => (Some(w), Some(h)),
+        Err(_) => (None, None),
+    };
+    PhotoMetadata {
+        modified,
+        width,
+        height,
+        taken_at: exif.taken_at.map(|(y, mo, d, h, mi, s)| {
+            format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", y, mo, d, h, mi, s)
+        }),
+        utc_offset_minutes: exif.utc_offset_minutes,
+        latitude: exif.latitude,
+        longitude: exif.longitude,
+    }
+}
+
+/// Load the metadata index from disk. A missing, unreadable or outdated index is treated
+/// as empty (it is a cache and is rebuilt by `refresh_metadata_index`).
+pub fn load_metadata_index(root: &Path) -> MetadataIndex {
+    fs::read_to_string(metadata_index_path(root))
+        .ok()
+        .and_then(|content| serde_json::from_str::<MetadataIndex>(&content).ok())
+        .filter(|index| index.version == METADATA_INDEX_VERSION)
+        .unwrap_or_default()
+}
+
+/// Index entries for the photos of gallery `slug`, reusing `previous` entries whose file is
+/// unchanged. Photos whose file is missing are left out.
+fn gallery_entries(root: &Path, slug: &str, previous: &MetadataIndex) -> Vec<(String, PhotoMetadata)> {
+    let Ok(Some(details)) = read_gallery_details(root, slug) else {
+        return Vec::new();
+    };
+    let photos = details.get("photos").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    let mut entries = Vec::new();
+    for photo in &photos {
+        let full = str_field(photo, "full");
+        if full.is_empty() {
+            continue;
+        }
+        let path = root.join(slug).join(&full);
+        // Originals of encrypted galleries are dated as stored and read from their decrypted copies
+        let Some(modified) = file_mtime_secs(&stored_original(&path)) else {
+            continue;
+        };
+        let key = format!("{}/{}", slug, full);
+        let source = readable_original(&path);
+        let entry = match previous.photos.get(&key) {
+            Some(cached) if cached.modified == modified => cached.clone(),
+            // Encrypted and locked: nothing to read until it is unlocked
+            _ if !source.is_file() => continue,
+            _ => read_photo_metadata(&source, modified),
+        };
+        entries.push((key, entry));
+    }
+    entries
+}
+
+fn save_if_changed(root: &Path, index: &MetadataIndex, previous: &MetadataIndex) -> Result<(), String> {
+    if index != previous {
+        let value = serde_json::to_value(index).map_err(|e| e.to_string())?;
+        write_json_atomic(&metadata_index_path(root), &value)?;
+    }
+    Ok(())
+}
+
+/// Bring the metadata index in line with the workspace: read EXIF/dimensions for photos
+/// that are new or whose mtime changed, drop photos no longer referenced by any
+/// `gallery-details.json`, and save the index if anything changed. Blocking.
+pub fn refresh_metadata_index(root: &Path) -> Result<MetadataIndex, String> {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = load_metadata_index(root);
+    let mut index = MetadataIndex::default();
+    for gallery in read_galleries(root)? {
+        let slug = str_field(&gallery, "slug");
+        if !slug.is_empty() {
+            index.photos.extend(gallery_entries(root, &slug, &previous));
+        }
+    }
+    save_if_changed(root, &index, &previous)?;
+    Ok(index)
+}
+
+/// Refresh only the entries of `slugs`, for changes within those galleries.
+fn refresh_galleries(root: &Path, slugs: &BTreeSet<String>) -> Result<(), String> {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let tracked: BTreeSet<String> = read_galleries(root)?.iter().map(|g| str_field(g, "slug")).collect();
+    let previous = load_metadata_index(root);
+    let mut index = previous.clone();
+    index.photos.retain(|key, _| !slugs.contains(split_index_key(key).0));
+    for slug in slugs.intersection(&tracked) {
+        index.photos.extend(gallery_entries(root, slug, &previous));
+    }
+    save_if_changed(root, &index, &previous)
+}
+
+/// The metadata index for browsing views (timeline, map). While the workspace is watched the
+/// stored index is kept current by `apply_fs_changes`, so it is served as is; it is only built
+/// here when there is none yet.
+pub fn current_metadata_index(root: &Path) -> Result<MetadataIndex, String> {
+    if metadata_index_path(root).exists() {
+        Ok(load_metadata_index(root))
+    } else {
+        refresh_metadata_index(root)
+    }
+}
+
+/// Keep an existing index current after watcher events (see `index_worker`): a `galleries.json`
+/// change refreshes everything, any other change within a gallery folder refreshes that gallery.
+pub(crate) fn apply_fs_changes(root: &Path, paths: &[PathBuf]) {
+    if !metadata_index_path(root).exists() {
+        return;
+    }
+    let mut full_refresh = false;
+    let mut slugs = BTreeSet::new();
+    for path in paths {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        match parts.as_slice() {
+            [name] if name == "galleries.json" => full_refresh = true,
+            [slug, name] if !slug.starts_with('.') && !name.starts_with('.') => {
+                slugs.insert(slug.clone());
+            }
+            _ => {}
+        }
+    }
+    let result = if full_refresh {
+        refresh_metadata_index(root).map(|_| ())
+    } else if slugs.is_empty() {
+        Ok(())
+    } else {
+        refresh_galleries(root, &slugs)
+    };
+    if let Err(e) = result {
+        eprintln!("[metadata] Failed to update the metadata index: {}", e);
+    }
+}
+
+/// Split a metadata index key into (slug, filename).
+pub fn split_index_key(key: &str) -> (&str, &str) {
+    key.split_once('/').unwrap_or(("", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_read_exif_without_exif_block() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("plain.png");
+        image::RgbImage::new(4, 4).save(&path).unwrap();
+        assert_eq!(read_exif(&path).unwrap(), PhotoExif::default());
+    }
+
+    #[test]
+    fn test_refresh_metadata_index_adds_and_prunes() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        write(root, "galleries.json", r#"{"schemaVersion":1,"galleries":[{"slug":"a"}]}"#);
+        write(
+            root,
+            "a/gallery-details.json",
+            r#"{"photos":[{"full":"one.png"},{"full":"missing.png"}]}"#,
+        );
+        image::RgbImage::new(30, 20).save(root.join("a/one.png")).unwrap();
+
+        let index = refresh_metadata_index(root).unwrap();
+        assert_eq!(index.photos.len(), 1);
+        let entry = &index.photos["a/one.png"];
+        assert_eq!((entry.width, entry.height), (Some(30), Some(20)));
+        assert!(entry.taken_at.is_none());
+        assert_eq!(load_metadata_index(root), index, "index is persisted");
+
+        write(root, "a/gallery-details.json", r#"{"photos":[]}"#);
+        assert!(refresh_metadata_index(root).unwrap().photos.is_empty());
+    }
+
+    #[test]
+    fn test_apply_fs_changes_updates_one_gallery() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        write(root, "galleries.json", r#"{"schemaVersion":1,"galleries":[{"slug":"a"},{"slug":"b"}]}"#);
+        write(root, "a/gallery-details.json", r#"{"photos":[{"full":"one.png"}]}"#);
+        write(root, "b/gallery-details.json", r#"{"photos":[{"full":"two.png"}]}"#);
+        image::RgbImage::new(30, 20).save(root.join("a/one.png")).unwrap();
+        image::RgbImage::new(10, 10).save(root.join("b/two.png")).unwrap();
+
+        // No index yet: nothing to keep current
+        apply_fs_changes(root, &[root.join("a/one.png")]);
+        assert!(!metadata_index_path(root).exists());
+        assert_eq!(current_metadata_index(root).unwrap().photos.len(), 2);
+
+        image::RgbImage::new(40, 20).save(root.join("a/three.png")).unwrap();
+        write(root, "a/gallery-details.json", r#"{"photos":[{"full":"three.png"}]}"#);
+        fs::remove_file(root.join("b/two.png")).unwrap();
+        // Only gallery a was reported; b's entry stays until its own event
+        apply_fs_changes(root, &[root.join("a/gallery-details.json"), root.join(".data/metadata-index.json")]);
+        let index = current_metadata_index(root).unwrap();
+        assert_eq!(index.photos.keys().collect::<Vec<_>>(), vec!["a/three.png", "b/two.png"]);
+        assert_eq!(index.photos["a/three.png"].width, Some(40));
+
+        apply_fs_changes(root, &[root.join("b/two.png")]);
+        assert_eq!(current_metadata_index(root).unwrap().photos.keys().collect::<Vec<_>>(), vec!["a/three.png"]);
+
+        write(root, "galleries.json", r#"{"schemaVersion":1,"galleries":[]}"#);
+        apply_fs_changes(root, &[root.join("galleries.json")]);
+        assert!(current_metadata_index(root).unwrap().photos.is_empty());
+    }
+
+    #[test]
+    fn test_taken_year_month_and_key_split() {
+        let meta = PhotoMetadata { taken_at: Some("2024-06-21T20:55:00".to_string()), ..Default::default() };
+        assert_eq!(meta.taken_year_month(), Some((2024, 6)));
+        assert_eq!(PhotoMetadata::default().taken_year_month(), None);
+        let meta = PhotoMetadata { width: Some(4000), height: Some(6000), ..Default::default() };
+        assert_eq!((meta.aspect_ratio(), meta.orientation()), (Some(0.6667), Some("portrait")));
+        let meta = PhotoMetadata { width: Some(30), height: Some(20), ..Default::default() };
+        assert_eq!((meta.aspect_ratio(), meta.orientation()), (Some(1.5), Some("landscape")));
+        assert_eq!(PhotoMetadata::default().orientation(), None);
+        assert_eq!(split_index_key("sunset/01.jpg"), ("sunset", "01.jpg"));
+    }
+
+    #[test]
+    fn test_read_exif_missing_file() {
+        assert!(read_exif(Path::new("/nonexistent/photo.jpg")).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+use crate::activity::record_activity;
+use crate::i18n::{error, ErrorCode};
+use crate::managed_area::{ManagedArea, ManagedKeyAudit};
+use crate::publish::{
+    delete_confirmation_token, enclosing_site_roots, files_to_upload, keys_to_delete, list_objects, nested_site_roots,
+    object_exists, prefix_overlap_error, upload_file, PublishError, PublishProgress, SyncFile,
+    DEFAULT_DELETE_PROTECTION_MAX_COUNT, DEFAULT_DELETE_PROTECTION_MAX_FRACTION,
+};
+use crate::site_template::is_template_key;
+use crate::settings::{extract_bucket_name, get_credentials_from_keychain, key_id_hint, AppSettings, KEYRING_SERVICE};
+use crate::transfers::{record_target_transfer, TransferRecord};
+use aws_credential_types::Credentials;
+use aws_sdk_s3::config::Region;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::Emitter;
+
+const KEYRING_MIRROR_KEY_ID: &str = "mirror-access-key-id";
+const KEYRING_MIRROR_SECRET: &str = "mirror-secret-access-key";
+/// Errors listed in `mirror-complete`; the rest are only counted.
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Second bucket that receives the same site on every publish. Empty `bucket` disables it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MirrorTarget {
+    pub bucket: String,
+    pub region: String,
+    /// Endpoint of an S3-compatible provider (e.g. "https://<account>.r2.cloudflarestorage.com");
+    /// empty for AWS.
+    pub endpoint_url: String,
+}
+
+/// The mirror's side of a publish plan: what it needs to match the same local files, diffed
+/// against the mirror bucket's own listing so files a failed mirror run missed are retried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorPlan {
+    pub bucket: String,
+    pub to_upload: Vec<SyncFile>,
+    pub to_delete: Vec<String>,
+    pub unchanged: usize,
+    /// Like `PublishPlan.delete_confirmation`, against the mirror's own listing: `publish_execute`
+    /// skips the mirror unless given this token as `mirrorConfirmation`.
+    pub delete_confirmation: Option<String>,
+    /// Listing the mirror failed or it holds another site; it is skipped by this publish.
+    pub error: Option<String>,
+}
+
+/// Payload of the `mirror-complete` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorResult {
+    pub bucket: String,
+    pub uploaded: usize,
+    pub deleted: usize,
+    pub failed: usize,
+    /// The first errors, in order.
+    pub errors: Vec<String>,
+}
+
+fn mirror_enabled(target: &MirrorTarget) -> bool {
+    !target.bucket.trim().is_empty()
+}
+
+/// Check the mirror settings before saving.
+pub(crate) fn validate_mirror(settings: &AppSettings) -> Result<(), String> {
+    let mirror = &settings.mirror;
+    if !mirror_enabled(mirror) {
+        return Ok(());
+    }
+    let endpoint = mirror.endpoint_url.trim();
+    if endpoint.is_empty() && mirror.region.trim().is_empty() {
+        return Err(error(ErrorCode::MirrorRegionMissing, &[]));
+    }
+    if !endpoint.is_empty() && !endpoint.starts_with("https://") {
+        return Err(error(ErrorCode::MirrorEndpointNotHttps, &[("endpoint", &endpoint)]));
+    }
+    // S3 bucket names are global, so the same name in another region is still the site's bucket
+    if endpoint.is_empty() && extract_bucket_name(&mirror.bucket) == extract_bucket_name(&settings.bucket) {
+        return Err(error(ErrorCode::MirrorSameBucket, &[]));
+    }
+    Ok(())
+}
+
+/// Credentials saved for the mirror, or the site's when none are.
+fn mirror_credentials(site_key_id: &str, site_secret: &str) -> (String, String) {
+    let saved = |name: &str| keyring::Entry::new(KEYRING_SERVICE, name).ok()?.get_password().ok();
+    match (saved(KEYRING_MIRROR_KEY_ID), saved(KEYRING_MIRROR_SECRET)) {
+        (Some(key_id), Some(secret)) => (key_id, secret),
+        _ => (site_key_id.to_string(), site_secret.to_string()),
+    }
+}
+
+/// S3 client for the mirror target, path-style when it is an S3-compatible provider.
+pub(crate) fn mirror_client(target: &MirrorTarget, site_key_id: &str, site_secret: &str) -> aws_sdk_s3::Client {
+    let (key_id, secret) = mirror_credentials(site_key_id, site_secret);
+    let region = match target.region.trim() {
+        // S3-compatible providers often ignore the region but the SDK requires one
+        "" => "auto".to_string(),
+        region => region.to_string(),
+    };
+    let mut config = aws_sdk_s3::Config::builder()
+        .credentials_provider(Credentials::new(key_id, secret, None, None, "afterglow-manager"))
+        .region(Region::new(region))
+        .behavior_version_latest();
+    if !target.endpoint_url.trim().is_empty() {
+        config = config.endpoint_url(target.endpoint_url.trim()).force_path_style(true);
+    }
+    aws_sdk_s3::Client::from_conf(config.build())
+}
+
+/// Diff the mirror bucket against the publish's local files. `None` when no mirror is
+/// configured; a failure is recorded in the plan rather than failing the preview.
+pub(crate) async fn plan_mirror(
+    settings: &AppSettings,
+    local_map: &HashMap<String, (PathBuf, String)>,
+    s3_root: &str,
+    managed_area: &ManagedArea,
+    template_pinned: bool,
+) -> Option<MirrorPlan> {
+    if !mirror_enabled(&settings.mirror) {
+        return None;
+    }
+    let bucket = extract_bucket_name(&settings.mirror.bucket);
+    Some(match diff_mirror(settings, &bucket, local_map, s3_root, managed_area, template_pinned).await {
+        Ok(plan) => plan,
+        Err(e) => MirrorPlan {
+            bucket,
+            to_upload: Vec::new(),
+            to_delete: Vec::new(),
+            unchanged: 0,
+            delete_confirmation: None,
+            error: Some(e),
+        },
+    })
+}
+
+/// The mirror gets the site bucket's delete safety: refused when another site shares the
+/// keyspace there, and a confirmation token when it deletes too much of its own listing.
+async fn diff_mirror(
+    settings: &AppSettings,
+    bucket: &str,
+    local_map: &HashMap<String, (PathBuf, String)>,
+    s3_root: &str,
+    managed_area: &ManagedArea,
+    template_pinned: bool,
+) -> Result<MirrorPlan, String> {
+    let (key_id, secret) = get_credentials_from_keychain().map_err(|e| error(ErrorCode::MirrorListFailed, &[("error", &e)]))?;
+    let client = mirror_client(&settings.mirror, &key_id, &secret);
+    let remote: HashMap<String, String> = list_objects(&client, bucket, s3_root)
+        .await
+        .map_err(|e| error(ErrorCode::MirrorListFailed, &[("error", &e)]))?
+        .into_iter()
+        .map(|obj| (obj.key, obj.etag))
+        .collect();
+
+    if let Some(nested) = nested_site_roots(s3_root, managed_area, remote.keys()).first() {
+        return Err(prefix_overlap_error(s3_root, nested));
+    }
+    for enclosing in enclosing_site_roots(s3_root) {
+        if object_exists(&client, bucket, &format!("{}site-manifest.json", enclosing)).await? {
+            return Err(prefix_overlap_error(s3_root, &enclosing));
+        }
+    }
+
+    let (to_upload, unchanged) = files_to_upload(local_map, &remote)?;
+    let mut to_delete = keys_to_delete(local_map, &remote, s3_root, managed_area);
+    if template_pinned {
+        to_delete.retain(|key| !is_template_key(key, s3_root));
+    }
+    let delete_confirmation = delete_confirmation_token(
+        to_delete.len(),
+        remote.len(),
+        settings.delete_protection_max_count.unwrap_or(DEFAULT_DELETE_PROTECTION_MAX_COUNT),
+        settings.delete_protection_max_fraction.unwrap_or(DEFAULT_DELETE_PROTECTION_MAX_FRACTION),
+    );
+    Ok(MirrorPlan { bucket: bucket.to_string(), to_upload, to_delete, unchanged, delete_confirmation, error: None })
+}
+
+impl MirrorPlan {
+    /// Why the mirror must not run: its deletes need a confirmation that wasn't given, or
+    /// `audit` (of `to_delete`) failed. A refused mirror is skipped; the publish goes on.
+    pub(crate) fn refusal(&self, confirmation: Option<&str>, audit: &ManagedKeyAudit) -> Option<String> {
+        if let Some(token) = &self.delete_confirmation {
+            if confirmation.map(str::trim) != Some(token.as_str()) {
+                return Some(error(
+                    ErrorCode::MirrorDeleteNeedsConfirmation,
+                    &[("count", &self.to_delete.len()), ("token", token)],
+                ));
+            }
+        }
+        audit.refusal()
+    }
+}
+
+/// Apply the mirror plan, emitting `mirror-progress` and `mirror-error` events and finally
+/// `mirror-complete`. A failed file is reported and skipped; nothing here fails the publish.
+/// Stops early when `cancelled` returns true.
+pub(crate) async fn run_mirror(
+    app: &tauri::AppHandle,
+    target: &MirrorTarget,
+    plan: &MirrorPlan,
+    s3_root: &str,
+    client: &aws_sdk_s3::Client,
+    cancelled: impl Fn() -> bool,
+) -> MirrorResult {
+    let mut result =
+        MirrorResult { bucket: plan.bucket.clone(), uploaded: 0, deleted: 0, failed: 0, errors: Vec::new() };
+    let fail = |result: &mut MirrorResult, file: &str, error: String| {
+        let _ = app.emit("mirror-error", PublishError { error: error.clone(), file: file.to_string() });
+        result.failed += 1;
+        if result.errors.len() < MAX_REPORTED_ERRORS {
+            result.errors.push(format!("{}: {}", file, error));
+        }
+    };
+    if let Some(error) = &plan.error {
+        fail(&mut result, "", error.clone());
+        let _ = app.emit("mirror-complete", result.clone());
+        return result;
+    }
+
+    let total = plan.to_upload.len() + plan.to_delete.len();
+    let mut bytes_uploaded = 0;
+    let mut current = 0;
+    for file in &plan.to_upload {
+        if cancelled() {
+            break;
+        }
+        current += 1;
+        let progress = PublishProgress { current, total, file: file.s3_key.clone(), action: "upload".to_string() };
+        let _ = app.emit("mirror-progress", progress);
+        match upload_file(client, &plan.bucket, file).await {
+            Ok(Ok(())) => {
+                result.uploaded += 1;
+                bytes_uploaded += file.size_bytes;
+            }
+            Ok(Err(e)) | Err(e) => fail(&mut result, &file.s3_key, e),
+        }
+    }
+    for key in &plan.to_delete {
+        if cancelled() {
+            break;
+        }
+        current += 1;
+        let progress = PublishProgress { current, total, file: key.clone(), action: "delete".to_string() };
+        let _ = app.emit("mirror-progress", progress);
+        match client.delete_object().bucket(&plan.bucket).key(key).send().await {
+            Ok(_) => result.deleted += 1,
+            Err(e) => fail(&mut result, key, e.to_string()),
+        }
+    }
+
+    let completed = result.failed == 0 && current == total;
+    let record = TransferRecord::now(bytes_uploaded, result.uploaded, result.deleted, completed);
+    record_target_transfer(app, &plan.bucket, &target.region, s3_root, record);
+    let _ = app.emit("mirror-complete", result.clone());
+    result
+}
+
+/// Save separate credentials for the mirror, e.g. for another provider. Without them the
+/// mirror uses the site's AWS credentials.
+#[tauri::command]
+pub async fn save_mirror_credentials(app: tauri::AppHandle, key_id: String, secret: String) -> Result<(), String> {
+    for (name, value) in [(KEYRING_MIRROR_KEY_ID, &key_id), (KEYRING_MIRROR_SECRET, &secret)] {
+        keyring::Entry::new(KEYRING_SERVICE, name)
+            .and_then(|entry| entry.set_password(value))
+            .map_err(|e| error(ErrorCode::MirrorCredentialsSaveFailed, &[("error", &e)]))?;
+    }
+    record_activity(&app, "saveMirrorCredentials", format!("Access key ending {}", key_id_hint(&key_id)));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_mirror_credentials(app: tauri::AppHandle) -> Result<(), String> {
+    for name in [KEYRING_MIRROR_KEY_ID, KEYRING_MIRROR_SECRET] {
+        if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, name) {
+            let _ = entry.delete_credential();
+        }
+    }
+    record_activity(&app, "deleteMirrorCredentials", String::new());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(mirror: MirrorTarget) -> AppSettings {
+        AppSettings { bucket: "photos".to_string(), region: "us-east-1".to_string(), mirror, ..AppSettings::default() }
+    }
+
+    #[test]
+    fn test_validate_mirror() {
+        assert!(validate_mirror(&site(MirrorTarget::default())).is_ok());
+        let eu = MirrorTarget { bucket: "photos-eu".to_string(), region: "eu-west-1".to_string(), ..Default::default() };
+        assert!(validate_mirror(&site(eu.clone())).is_ok());
+        let same = MirrorTarget { bucket: "photos".to_string(), region: "us-east-1".to_string(), ..Default::default() };
+        assert!(validate_mirror(&site(same)).is_err());
+        let same_elsewhere = MirrorTarget { bucket: "photos".to_string(), region: "eu-west-1".to_string(), ..Default::default() };
+        assert!(validate_mirror(&site(same_elsewhere)).is_err());
+        assert!(validate_mirror(&site(MirrorTarget { region: String::new(), ..eu.clone() })).is_err());
+
+        let r2 = MirrorTarget {
+            bucket: "photos".to_string(),
+            endpoint_url: "https://abc.r2.cloudflarestorage.com".to_string(),
+            ..Default::default()
+        };
+        assert!(validate_mirror(&site(r2.clone())).is_ok());
+        let insecure = MirrorTarget { endpoint_url: "http://minio.local:9000".to_string(), ..r2 };
+        assert!(validate_mirror(&site(insecure)).is_err());
+    }
+
+    #[test]
+    fn test_mirror_refusal() {
+        let area = ManagedArea::default();
+        let plan = MirrorPlan {
+            bucket: "photos-eu".to_string(),
+            to_upload: Vec::new(),
+            to_delete: vec!["galleries/a/01.jpg".to_string()],
+            unchanged: 0,
+            delete_confirmation: None,
+            error: None,
+        };
+        let audit = |keys: &[String]| crate::managed_area::audit_deletes(("", &area), ("", &area), keys);
+        assert_eq!(plan.refusal(None, &audit(&plan.to_delete)), None);
+
+        let outside = vec!["notes.txt".to_string()];
+        assert!(plan.refusal(None, &audit(&outside)).unwrap().contains("outside the managed area"));
+
+        let confirm = MirrorPlan { delete_confirmation: Some("DELETE 1".to_string()), ..plan };
+        assert!(confirm.refusal(None, &audit(&confirm.to_delete)).unwrap().contains("Type \"DELETE 1\""));
+        assert!(confirm.refusal(Some("DELETE 2"), &audit(&confirm.to_delete)).is_some());
+        assert_eq!(confirm.refusal(Some(" DELETE 1 "), &audit(&confirm.to_delete)), None);
+    }
+}
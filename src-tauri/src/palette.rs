@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Number of colours published per gallery.
+pub const PALETTE_SIZE: usize = 6;
+/// Images are downsampled to at most this many pixels on the longest side before sampling.
+const SAMPLE_MAX_DIM: u32 = 64;
+/// Minimum squared RGB distance between two palette entries, so near-identical shades
+/// don't crowd out distinct colours.
+const MIN_COLOR_DISTANCE_SQ: i32 = 40 * 40;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PaletteColor {
+    /// "#rrggbb"
+    pub hex: String,
+    /// Share of sampled pixels represented by this colour (0–1).
+    pub weight: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GalleryPalette {
+    pub version: u32,
+    pub colors: Vec<PaletteColor>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Bucket {
+    count: u64,
+    r: u64,
+    g: u64,
+    b: u64,
+}
+
+/// Quantise to 4 bits per channel (4096 buckets).
+fn bucket_key(r: u8, g: u8, b: u8) -> u16 {
+    ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4)
+}
+
+fn accumulate(buckets: &mut HashMap<u16, Bucket>, r: u8, g: u8, b: u8) {
+    let bucket = buckets.entry(bucket_key(r, g, b)).or_default();
+    bucket.count += 1;
+    bucket.r += r as u64;
+    bucket.g += g as u64;
+    bucket.b += b as u64;
+}
+
+/// Pick the `n` most populated buckets, skipping colours too close to ones already chosen.
+fn select_palette(buckets: &HashMap<u16, Bucket>, n: usize) -> Vec<PaletteColor> {
+    let total: u64 = buckets.values().map(|b| b.count).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut sorted: Vec<(u16, Bucket)> = buckets.iter().map(|(k, v)| (*k, *v)).collect();
+    // Tie-break on key so output is deterministic (keeps publish diffs quiet).
+    sorted.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(&b.0)));
+
+    let mut chosen: Vec<([u8; 3], u64)> = Vec::new();
+    for (_, bucket) in sorted {
+        let rgb = [
+            (bucket.r / bucket.count) as u8,
+            (bucket.g / bucket.count) as u8,
+            (bucket.b / bucket.count) as u8,
+        ];
+        let too_close = chosen.iter().any(|(c, _)| {
+            let dr = c[0] as i32 - rgb[0] as i32;
+            let dg = c[1] as i32 - rgb[1] as i32;
+            let db = c[2] as i32 - rgb[2] as i32;
+            dr * dr + dg * dg + db * db < MIN_COLOR_DISTANCE_SQ
+        });
+        if too_close {
+            continue;
+        }
+        chosen.push((rgb, bucket.count));
+        if chosen.len() == n {
+            break;
+        }
+    }
+
+    chosen
+        .into_iter()
+        .map(|(c, count)| PaletteColor {
+            hex: format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]),
+            weight: ((count as f64 / total as f64) * 1000.0).round() / 1000.0,
+        })
+        .collect()
+}
+
+/// Compute the `n` dominant colours across a set of images. Unreadable images are skipped.
+///
+/// Intended to run on the generated WebP thumbnails rather than the originals — the result
+/// is the same at palette resolution and decoding is much cheaper.
+pub fn compute_palette(images: &[PathBuf], n: usize) -> Vec<PaletteColor> {
+    let mut buckets: HashMap<u16, Bucket> = HashMap::new();
+    for path in images {
+        let img = match image::open(path) {
+            Ok(img) => img,
+            Err(e) => {
+                eprintln!("[palette] Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let small = img.thumbnail(SAMPLE_MAX_DIM, SAMPLE_MAX_DIM).to_rgba8();
+        for px in small.pixels() {
+            // Ignore (mostly) transparent pixels
+            if px[3] < 128 {
+                continue;
+            }
+            accumulate(&mut buckets, px[0], px[1], px[2]);
+        }
+    }
+    select_palette(&buckets, n)
+}
+
+/// Serialize a gallery's `palette.json`.
+pub fn palette_json(colors: Vec<PaletteColor>) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(&GalleryPalette { version: 1, colors }).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn solid_png(path: &std::path::Path, w: u32, h: u32, rgb: [u8; 3]) {
+        let img = image::RgbImage::from_pixel(w, h, image::Rgb(rgb));
+        img.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_select_palette_orders_by_population() {
+        let mut buckets = HashMap::new();
+        for _ in 0..30 {
+            accumulate(&mut buckets, 250, 10, 10);
+        }
+        for _ in 0..70 {
+            accumulate(&mut buckets, 10, 10, 250);
+        }
+        let palette = select_palette(&buckets, 6);
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette[0].hex, "#0a0afa");
+        assert_eq!(palette[0].weight, 0.7);
+        assert_eq!(palette[1].hex, "#fa0a0a");
+    }
+
+    #[test]
+    fn test_select_palette_skips_near_duplicates() {
+        let mut buckets = HashMap::new();
+        for _ in 0..50 {
+            accumulate(&mut buckets, 100, 100, 100);
+        }
+        for _ in 0..40 {
+            accumulate(&mut buckets, 112, 112, 112); // different bucket, visually the same grey
+        }
+        for _ in 0..10 {
+            accumulate(&mut buckets, 255, 255, 0);
+        }
+        let palette = select_palette(&buckets, 6);
+        let hexes: Vec<&str> = palette.iter().map(|c| c.hex.as_str()).collect();
+        assert_eq!(hexes, vec!["#646464", "#ffff00"]);
+    }
+
+    #[test]
+    fn test_select_palette_empty() {
+        assert!(select_palette(&HashMap::new(), 6).is_empty());
+    }
+
+    #[test]
+    fn test_compute_palette_across_images() {
+        let tmp = TempDir::new().unwrap();
+        let a = tmp.path().join("a.png");
+        let b = tmp.path().join("b.png");
+        solid_png(&a, 40, 40, [200, 0, 0]);
+        solid_png(&b, 20, 20, [0, 0, 200]);
+        let missing = tmp.path().join("missing.png");
+        let palette = compute_palette(&[a, b, missing], PALETTE_SIZE);
+        let hexes: Vec<&str> = palette.iter().map(|c| c.hex.as_str()).collect();
+        assert_eq!(hexes.len(), 2);
+        assert!(hexes.contains(&"#c80000"));
+        assert!(hexes.contains(&"#0000c8"));
+    }
+}
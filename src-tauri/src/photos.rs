@@ -0,0 +1,226 @@
+use crate::changes::{emit_change, ChangeOrigin, WorkspaceChange};
+use crate::journal::JournalState;
+use crate::workspace::{photos_mut, str_field, update_gallery_details};
+use std::path::PathBuf;
+
+fn find_photo_index(photos: &[serde_json::Value], filename: &str) -> Option<usize> {
+    photos.iter().position(|p| str_field(p, "full") == filename)
+}
+
+fn clear_related(photos: &mut [serde_json::Value], index: usize) {
+    if let Some(obj) = photos[index].as_object_mut() {
+        obj.remove("relatedTo");
+    }
+}
+
+/// Pair `filename` with `related_to` (both `full` filenames in the same gallery), or unpair
+/// `filename` when `related_to` is `None`.
+///
+/// Pairing is mutual: both photos get a `relatedTo` pointing at the other. Any previous
+/// partner of either photo is unpaired first so a photo is never in two pairs.
+pub(crate) fn apply_pairing(
+    photos: &mut [serde_json::Value],
+    filename: &str,
+    related_to: Option<&str>,
+) -> Result<(), String> {
+    let a = find_photo_index(photos, filename)
+        .ok_or_else(|| format!("Photo '{}' not found in gallery", filename))?;
+    let b = match related_to {
+        Some(other) => {
+            if other == filename {
+                return Err("A photo cannot be paired with itself.".to_string());
+            }
+            Some(
+                find_photo_index(photos, other)
+                    .ok_or_else(|| format!("Photo '{}' not found in gallery", other))?,
+            )
+        }
+        None => None,
+    };
+
+    // Break existing pairs involving either photo.
+    for idx in [Some(a), b].into_iter().flatten() {
+        let partner = str_field(&photos[idx], "relatedTo");
+        if !partner.is_empty() {
+            if let Some(p) = find_photo_index(photos, &partner) {
+                clear_related(photos, p);
+            }
+        }
+        clear_related(photos, idx);
+    }
+
+    if let Some(b) = b {
+        let name_a = str_field(&photos[a], "full");
+        let name_b = str_field(&photos[b], "full");
+        if let Some(obj) = photos[a].as_object_mut() {
+            obj.insert("relatedTo".to_string(), serde_json::Value::String(name_b));
+        }
+        if let Some(obj) = photos[b].as_object_mut() {
+            obj.insert("relatedTo".to_string(), serde_json::Value::String(name_a));
+        }
+    }
+    Ok(())
+}
+
+/// Validate an external link for a photo: an absolute http(s) URL without whitespace or
+/// characters that would need escaping in HTML. Returns it trimmed.
+pub(crate) fn normalize_external_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| format!("External link \"{}\" must start with https:// or http://.", url))?;
+    if rest.is_empty() || rest.starts_with('/') {
+        return Err(format!("External link \"{}\" has no host.", url));
+    }
+    if url.chars().any(|c| c.is_whitespace() || c.is_control() || "\"'<>`".contains(c)) {
+        return Err(format!("External link \"{}\" contains an invalid character.", url));
+    }
+    Ok(url.to_string())
+}
+
+/// Set or clear (`None` or empty) the `externalUrl` of the photo whose `full` is `filename`.
+pub(crate) fn apply_external_url(
+    photos: &mut [serde_json::Value],
+    filename: &str,
+    external_url: Option<&str>,
+) -> Result<(), String> {
+    let index = find_photo_index(photos, filename)
+        .ok_or_else(|| format!("Photo '{}' not found in gallery", filename))?;
+    let url = external_url.filter(|u| !u.trim().is_empty()).map(normalize_external_url).transpose()?;
+    if let Some(obj) = photos[index].as_object_mut() {
+        match url {
+            Some(url) => obj.insert("externalUrl".to_string(), serde_json::Value::String(url)),
+            None => obj.remove("externalUrl"),
+        };
+    }
+    Ok(())
+}
+
+/// Set the link published with a photo (e.g. where to buy a print), shown as a button in the
+/// website's lightbox. Pass `external_url: null` or "" to remove it.
+#[tauri::command]
+pub async fn set_photo_external_url(
+    workspace_path: String,
+    slug: String,
+    filename: String,
+    external_url: Option<String>,
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let clearing = external_url.as_deref().is_none_or(|u| u.trim().is_empty());
+    let label = if clearing { "Remove external link" } else { "Set external link" };
+    journal.track(label, &[root.join(&slug).join("gallery-details.json")], || {
+        update_gallery_details(&root, &slug, |details| {
+            apply_external_url(photos_mut(details)?, &filename, external_url.as_deref())
+        })
+    })?;
+    let id = format!("{}/{}", slug, filename);
+    emit_change(&app, WorkspaceChange::new("photo", &id, "updated", ChangeOrigin::User, "set_photo_external_url"));
+    Ok(())
+}
+
+/// Pair two photos in a gallery (e.g. edited vs straight-out-of-camera) so the website can
+/// render a before/after slider. Pass `related_to: null` to remove an existing pairing.
+#[tauri::command]
+pub async fn pair_photos(
+    workspace_path: String,
+    slug: String,
+    filename: String,
+    related_to: Option<String>,
+    app: tauri::AppHandle,
+    journal: tauri::State<'_, JournalState>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    crate::read_only::ensure_writable(&app, &root)?;
+    let label = if related_to.is_some() { "Pair photos" } else { "Unpair photos" };
+    let previous = journal.track(label, &[root.join(&slug).join("gallery-details.json")], || {
+        update_gallery_details(&root, &slug, |details| {
+            let photos = photos_mut(details)?;
+            // The old partners lose their pairing too
+            let previous: Vec<String> = [Some(filename.as_str()), related_to.as_deref()]
+                .into_iter()
+                .flatten()
+                .filter_map(|name| find_photo_index(photos, name))
+                .map(|i| str_field(&photos[i], "relatedTo"))
+                .filter(|name| !name.is_empty())
+                .collect();
+            apply_pairing(photos, &filename, related_to.as_deref())?;
+            Ok(previous)
+        })
+    })?;
+    let mut changed: Vec<String> = [filename.clone()].into_iter().chain(related_to).chain(previous).collect();
+    changed.sort();
+    changed.dedup();
+    for name in changed {
+        let id = format!("{}/{}", slug, name);
+        emit_change(&app, WorkspaceChange::new("photo", &id, "updated", ChangeOrigin::User, "pair_photos"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn photos() -> Vec<serde_json::Value> {
+        vec![
+            json!({ "thumbnail": "a.jpg", "full": "a.jpg", "alt": "" }),
+            json!({ "thumbnail": "b.jpg", "full": "b.jpg", "alt": "" }),
+            json!({ "thumbnail": "c.jpg", "full": "c.jpg", "alt": "" }),
+        ]
+    }
+
+    #[test]
+    fn test_pairing_is_mutual() {
+        let mut p = photos();
+        apply_pairing(&mut p, "a.jpg", Some("b.jpg")).unwrap();
+        assert_eq!(p[0]["relatedTo"], "b.jpg");
+        assert_eq!(p[1]["relatedTo"], "a.jpg");
+        assert!(p[2].get("relatedTo").is_none());
+    }
+
+    #[test]
+    fn test_repairing_breaks_previous_pair() {
+        let mut p = photos();
+        apply_pairing(&mut p, "a.jpg", Some("b.jpg")).unwrap();
+        apply_pairing(&mut p, "c.jpg", Some("a.jpg")).unwrap();
+        assert_eq!(p[0]["relatedTo"], "c.jpg");
+        assert_eq!(p[2]["relatedTo"], "a.jpg");
+        assert!(p[1].get("relatedTo").is_none(), "old partner must be unpaired");
+    }
+
+    #[test]
+    fn test_unpair() {
+        let mut p = photos();
+        apply_pairing(&mut p, "a.jpg", Some("b.jpg")).unwrap();
+        apply_pairing(&mut p, "b.jpg", None).unwrap();
+        assert!(p[0].get("relatedTo").is_none());
+        assert!(p[1].get("relatedTo").is_none());
+    }
+
+    #[test]
+    fn test_external_url() {
+        let mut p = photos();
+        apply_external_url(&mut p, "b.jpg", Some(" https://prints.example.com/b ")).unwrap();
+        assert_eq!(p[1]["externalUrl"], "https://prints.example.com/b");
+        apply_external_url(&mut p, "b.jpg", Some("")).unwrap();
+        assert!(p[1].get("externalUrl").is_none());
+
+        assert!(apply_external_url(&mut p, "missing.jpg", Some("https://x.com")).is_err());
+        for bad in ["javascript:alert(1)", "prints.example.com", "https://", "https://x.com/a b", "https://x.com/\"x"] {
+            assert!(normalize_external_url(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_pairing_errors() {
+        let mut p = photos();
+        assert!(apply_pairing(&mut p, "a.jpg", Some("a.jpg")).is_err());
+        assert!(apply_pairing(&mut p, "missing.jpg", Some("a.jpg")).is_err());
+        assert!(apply_pairing(&mut p, "a.jpg", Some("missing.jpg")).is_err());
+    }
+}
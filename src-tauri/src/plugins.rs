@@ -0,0 +1,254 @@
+use crate::i18n::{error, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::io::AsyncWriteExt;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+const MAX_TIMEOUT_SECS: u64 = 600;
+/// Characters of a failed plugin's stderr kept in the error.
+const MAX_ERROR_LEN: usize = 500;
+
+/// Points in the publish pipeline where plugins run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PluginHook {
+    /// Before `publish_execute` uploads anything, with the plan. A failing plugin stops the
+    /// publish.
+    PrePublish,
+    /// After `publish_preview` generated thumbnails, with the ones written by this run; plugins
+    /// may modify them in place (e.g. watermark) before they are hashed and uploaded.
+    PostThumbnail,
+    /// After a publish completed, with the plan and the result.
+    PostPublish,
+}
+
+impl PluginHook {
+    fn blocks_on_failure(self) -> bool {
+        self == PluginHook::PrePublish
+    }
+}
+
+/// `{app_data}/plugins/{name}/plugin.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginManifest {
+    name: String,
+    /// Executable to run: a path relative to the plugin folder when it contains a `/`,
+    /// otherwise a program on the PATH (e.g. "node" with `args: ["index.js"]`).
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    hooks: Vec<PluginHook>,
+    timeout_secs: Option<u64>,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone)]
+struct Plugin {
+    dir: PathBuf,
+    manifest: PluginManifest,
+}
+
+/// A discovered plugin, for the settings UI. `error` is set when its `plugin.json` is invalid.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub name: String,
+    pub dir: String,
+    pub hooks: Vec<PluginHook>,
+    pub enabled: bool,
+    pub error: Option<String>,
+}
+
+/// Payload of the `plugin-error` event, for failures that don't stop the pipeline.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginError {
+    pub plugin: String,
+    pub hook: PluginHook,
+    pub error: String,
+}
+
+fn plugins_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
+    Ok(data_dir.join("plugins"))
+}
+
+/// Plugin folders under `dir` in name order, which is also the order they run in.
+fn discover_plugins(dir: &Path) -> Vec<(PathBuf, Result<PluginManifest, String>)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join("plugin.json").is_file())
+        .collect();
+    dirs.sort();
+    dirs.into_iter()
+        .map(|dir| {
+            let manifest = fs::read_to_string(dir.join("plugin.json"))
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str(&content).map_err(|e| format!("Invalid plugin.json: {}", e)));
+            (dir, manifest)
+        })
+        .collect()
+}
+
+fn plugins_for_hook(dir: &Path, hook: PluginHook) -> Vec<Plugin> {
+    discover_plugins(dir)
+        .into_iter()
+        .filter_map(|(dir, manifest)| match manifest {
+            Ok(manifest) => Some(Plugin { dir, manifest }),
+            Err(e) => {
+                eprintln!("[plugins] Skipping {}: {}", dir.display(), e);
+                None
+            }
+        })
+        .filter(|p| p.manifest.enabled && p.manifest.hooks.contains(&hook))
+        .collect()
+}
+
+fn program_path(plugin: &Plugin) -> PathBuf {
+    let command = &plugin.manifest.command;
+    if command.contains('/') || command.contains('\\') {
+        plugin.dir.join(command)
+    } else {
+        PathBuf::from(command)
+    }
+}
+
+/// Run one plugin with `payload` on stdin, in its own folder. Non-zero exit, a timeout or a
+/// failure to start is an error carrying the end of its stderr.
+async fn run_plugin(plugin: &Plugin, payload: &[u8]) -> Result<(), String> {
+    let timeout = plugin.manifest.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).min(MAX_TIMEOUT_SECS);
+    let mut child = tokio::process::Command::new(program_path(plugin))
+        .args(&plugin.manifest.args)
+        .current_dir(&plugin.dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A plugin that doesn't read its input closes the pipe early; that's not an error
+        let _ = stdin.write_all(payload).await;
+    }
+    let output = tokio::time::timeout(Duration::from_secs(timeout), child.wait_with_output())
+        .await
+        .map_err(|_| format!("Timed out after {} s", timeout))?
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    let tail: String = stderr.chars().rev().take(MAX_ERROR_LEN).collect::<Vec<_>>().into_iter().rev().collect();
+    Err(if tail.is_empty() { format!("Exited with {}", output.status) } else { tail })
+}
+
+/// Run the enabled plugins registered for `hook` in order, passing
+/// `{ hook, workspacePath, data }` as JSON on stdin. A failing `prePublish` plugin returns an
+/// error; other failures are logged and emitted as `plugin-error`.
+pub(crate) async fn run_hook(
+    app: &tauri::AppHandle,
+    hook: PluginHook,
+    workspace_path: Option<&Path>,
+    data: serde_json::Value,
+) -> Result<(), String> {
+    let plugins = plugins_for_hook(&plugins_dir(app)?, hook);
+    if plugins.is_empty() {
+        return Ok(());
+    }
+    let payload = serde_json::json!({ "hook": hook, "workspacePath": workspace_path, "data": data });
+    let payload = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
+    for plugin in &plugins {
+        if let Err(e) = run_plugin(plugin, &payload).await {
+            let name = &plugin.manifest.name;
+            if hook.blocks_on_failure() {
+                return Err(format!("Plugin \"{}\" stopped the publish: {}", name, e));
+            }
+            eprintln!("[plugins] {} failed: {}", name, e);
+            let _ = app.emit("plugin-error", PluginError { plugin: name.clone(), hook, error: e });
+        }
+    }
+    Ok(())
+}
+
+/// Plugins installed in `{app_data}/plugins`, including ones whose `plugin.json` is invalid.
+#[tauri::command]
+pub async fn list_plugins(app: tauri::AppHandle) -> Result<Vec<PluginInfo>, String> {
+    Ok(discover_plugins(&plugins_dir(&app)?)
+        .into_iter()
+        .map(|(dir, manifest)| {
+            let dir_name = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let dir = dir.to_string_lossy().into_owned();
+            match manifest {
+                Ok(m) => PluginInfo { name: m.name, dir, hooks: m.hooks, enabled: m.enabled, error: None },
+                Err(e) => PluginInfo { name: dir_name, dir, hooks: Vec::new(), enabled: false, error: Some(e) },
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn install(root: &Path, dir: &str, manifest: &str) {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(root.join(dir).join("plugin.json"), manifest).unwrap();
+    }
+
+    #[test]
+    fn test_discover_plugins() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        install(root, "b-watermark", r#"{"name":"Watermark","command":"./run.sh","hooks":["postThumbnail"]}"#);
+        install(root, "a-notify", r#"{"name":"Notify","command":"node","args":["index.js"],"hooks":["postPublish","prePublish"]}"#);
+        install(root, "c-off", r#"{"name":"Off","command":"x","hooks":["prePublish"],"enabled":false}"#);
+        install(root, "d-broken", r#"{"name":"Broken","hooks":["sometimes"]}"#);
+        fs::create_dir_all(root.join("e-no-manifest")).unwrap();
+
+        let found = discover_plugins(root);
+        assert_eq!(found.len(), 4);
+        assert!(found[3].1.is_err());
+
+        let pre: Vec<String> = plugins_for_hook(root, PluginHook::PrePublish).into_iter().map(|p| p.manifest.name).collect();
+        assert_eq!(pre, vec!["Notify"]);
+        let thumbs = plugins_for_hook(root, PluginHook::PostThumbnail);
+        assert_eq!(program_path(&thumbs[0]), root.join("b-watermark").join("./run.sh"));
+        assert_eq!(program_path(&plugins_for_hook(root, PluginHook::PostPublish)[0]), PathBuf::from("node"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_plugin() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        install(root, "p", r#"{"name":"P","command":"sh","args":["-c","cat > received.json"],"hooks":["prePublish"]}"#);
+        install(root, "q", r#"{"name":"Q","command":"sh","args":["-c","echo no uploads today >&2; exit 3"],"hooks":["prePublish"]}"#);
+        let plugins = plugins_for_hook(root, PluginHook::PrePublish);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(run_plugin(&plugins[0], br#"{"hook":"prePublish"}"#)).unwrap();
+        assert_eq!(fs::read_to_string(root.join("p/received.json")).unwrap(), r#"{"hook":"prePublish"}"#);
+        let error = runtime.block_on(run_plugin(&plugins[1], b"{}")).unwrap_err();
+        assert_eq!(error, "no uploads today");
+    }
+}
@@ -0,0 +1,141 @@
+use crate::activity::record_activity;
+use crate::managed_area::ManagedArea;
+use crate::publish::{list_objects, s3_client, ListedObject};
+use crate::settings::{get_credentials_from_keychain, read_settings, write_settings, AppSettings};
+use crate::settings_impact::{orphaned_objects, Target};
+use serde::Serialize;
+use std::collections::HashSet;
+use tauri::Emitter;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OldPrefixObjects {
+    pub bucket: String,
+    pub s3_prefix: String,
+    /// Managed-looking objects still at the old location, sorted.
+    pub keys: Vec<String>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupFailure {
+    pub key: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OldPrefixCleanupReport {
+    pub deleted: usize,
+    pub deleted_bytes: u64,
+    /// Confirmed keys that were no longer at the old location (or are now managed by the
+    /// current target) and were left alone.
+    pub skipped: Vec<String>,
+    pub failed: Vec<CleanupFailure>,
+    /// True when nothing managed is left at the old location and it was forgotten.
+    pub complete: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CleanupProgress {
+    current: usize,
+    total: usize,
+    key: String,
+}
+
+/// The old target and its stale objects; never includes keys the current target manages.
+async fn stale_objects(settings: &AppSettings) -> Result<(Target, aws_sdk_s3::Client, Vec<ListedObject>), String> {
+    let previous = settings
+        .previous_target
+        .as_ref()
+        .ok_or("The S3 prefix hasn't changed; there is no old location to clean up.")?;
+    let old = Target::from_previous(previous)?;
+    let current = Target::from_settings(settings)?;
+    let area = ManagedArea::from_settings(settings)?;
+    let (key_id, secret) = get_credentials_from_keychain()?;
+    let client = s3_client(&key_id, &secret, &old.region);
+    let objects = list_objects(&client, &old.bucket, &old.s3_root).await?;
+    let stale = orphaned_objects(&objects, &old, &current, &area).into_iter().cloned().collect();
+    Ok((old, client, stale))
+}
+
+/// Split confirmed keys into those still stale (to delete) and the rest (skipped).
+fn partition_confirmed(confirmed: &[String], stale: &[ListedObject]) -> (Vec<ListedObject>, Vec<String>) {
+    let confirmed: HashSet<&str> = confirmed.iter().map(String::as_str).collect();
+    let to_delete: Vec<ListedObject> = stale.iter().filter(|obj| confirmed.contains(obj.key.as_str())).cloned().collect();
+    let deleting: HashSet<&str> = to_delete.iter().map(|obj| obj.key.as_str()).collect();
+    let mut skipped: Vec<String> = confirmed.into_iter().filter(|key| !deleting.contains(key)).map(str::to_string).collect();
+    skipped.sort();
+    (to_delete, skipped)
+}
+
+/// List the managed-looking objects (galleries, website files, manifest) left at the previous
+/// bucket/prefix after the publish target changed, for the user to confirm.
+#[tauri::command]
+pub async fn list_old_prefix_objects(app: tauri::AppHandle) -> Result<OldPrefixObjects, String> {
+    let settings = read_settings(&app)?;
+    let (old, _, stale) = stale_objects(&settings).await?;
+    let mut keys: Vec<String> = stale.iter().map(|obj| obj.key.clone()).collect();
+    keys.sort();
+    Ok(OldPrefixObjects {
+        bucket: old.bucket,
+        s3_prefix: old.s3_root,
+        keys,
+        total_bytes: stale.iter().map(|obj| obj.size).sum(),
+    })
+}
+
+/// Delete the confirmed `keys` from the old location. Keys are re-checked against a fresh
+/// listing so nothing the current target manages is touched. Emits `cleanup-progress`; once the
+/// old location is empty the previous target is forgotten.
+#[tauri::command]
+pub async fn cleanup_old_prefix(app: tauri::AppHandle, keys: Vec<String>) -> Result<OldPrefixCleanupReport, String> {
+    let mut settings = read_settings(&app)?;
+    let (old, client, stale) = stale_objects(&settings).await?;
+    let (to_delete, skipped) = partition_confirmed(&keys, &stale);
+
+    let total = to_delete.len();
+    let mut deleted = 0;
+    let mut deleted_bytes = 0;
+    let mut failed = Vec::new();
+    for (i, obj) in to_delete.iter().enumerate() {
+        let _ = app.emit("cleanup-progress", CleanupProgress { current: i + 1, total, key: obj.key.clone() });
+        match client.delete_object().bucket(&old.bucket).key(&obj.key).send().await {
+            Ok(_) => {
+                deleted += 1;
+                deleted_bytes += obj.size;
+            }
+            Err(e) => failed.push(CleanupFailure { key: obj.key.clone(), error: format!("{}", e) }),
+        }
+    }
+
+    if deleted > 0 {
+        record_activity(&app, "cleanupOldPrefix", format!("{} objects deleted from s3://{}/{}", deleted, old.bucket, old.s3_root));
+    }
+    let complete = failed.is_empty() && deleted == stale.len();
+    if complete {
+        settings.previous_target = None;
+        write_settings(&app, &settings)?;
+    }
+    Ok(OldPrefixCleanupReport { deleted, deleted_bytes, skipped, failed, complete })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_confirmed() {
+        let stale = vec![
+            ListedObject { key: "old/galleries/a/01.jpg".to_string(), etag: String::new(), size: 10 },
+            ListedObject { key: "old/index.html".to_string(), etag: String::new(), size: 1 },
+        ];
+        let confirmed = vec!["old/index.html".to_string(), "old/galleries/gone.jpg".to_string()];
+        let (to_delete, skipped) = partition_confirmed(&confirmed, &stale);
+        assert_eq!(to_delete.len(), 1);
+        assert_eq!(to_delete[0].key, "old/index.html");
+        assert_eq!(skipped, vec!["old/galleries/gone.jpg"]);
+    }
+}
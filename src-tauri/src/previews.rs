@@ -0,0 +1,129 @@
+use crate::encryption::readable_original;
+use crate::thumbnails::{generate_thumbnail, is_thumbnail_fresh};
+use crate::workspace::{read_gallery_details, str_field};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Most photos one request may warm, so a bad range can't queue a whole gallery.
+const MAX_PREFETCH: usize = 60;
+
+/// Counter of prefetch requests; a request stops early once a newer one has started, so
+/// scrolling quickly only warms what is near the current viewport.
+pub struct PrefetchState(pub Arc<AtomicU64>);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoPreview {
+    pub index: usize,
+    /// `full` of the photo entry.
+    pub filename: String,
+    /// Absolute path of the cached WebP preview; `None` when it couldn't be generated.
+    pub preview_path: Option<String>,
+}
+
+/// Source image and cache path for a photo entry: the same `.data/thumbnails/{slug}/{stem}.webp`
+/// publishing uploads as the thumbnail, so warming a preview also saves work at publish time.
+fn preview_paths(root: &Path, slug: &str, photo: &serde_json::Value) -> Option<(PathBuf, PathBuf)> {
+    let thumbnail = str_field(photo, "thumbnail");
+    let source_name = if thumbnail.is_empty() { str_field(photo, "full") } else { thumbnail };
+    if source_name.is_empty() {
+        return None;
+    }
+    let stem = Path::new(&source_name).file_stem()?.to_str()?.to_string();
+    let source = readable_original(&root.join(slug).join(&source_name));
+    let dest = root.join(".data").join("thumbnails").join(slug).join(format!("{}.webp", stem));
+    Some((source, dest))
+}
+
+/// Generate any missing or stale previews for `photos[start..start + count]`, stopping when
+/// `superseded` returns true. Failures leave `preview_path` empty.
+fn warm_previews(
+    root: &Path,
+    slug: &str,
+    photos: &[serde_json::Value],
+    start: usize,
+    count: usize,
+    superseded: impl Fn() -> bool,
+) -> Vec<PhotoPreview> {
+    let end = start.saturating_add(count.min(MAX_PREFETCH)).min(photos.len());
+    let mut previews = Vec::new();
+    for (index, photo) in photos.iter().enumerate().take(end).skip(start) {
+        if superseded() {
+            break;
+        }
+        let preview_path = preview_paths(root, slug, photo).and_then(|(source, dest)| {
+            if !source.is_file() {
+                return None;
+            }
+            if !is_thumbnail_fresh(&source, &dest) {
+                generate_thumbnail(&source, &dest).ok()?;
+            }
+            Some(dest.to_string_lossy().to_string())
+        });
+        previews.push(PhotoPreview { index, filename: str_field(photo, "full"), preview_path });
+    }
+    previews
+}
+
+/// Warm the previews of the photos at `start..start + count` in a gallery (the rows the user is
+/// about to scroll to), generating cached WebP versions where missing. At most 60 per call;
+/// returns early with what it has when a newer prefetch starts.
+#[tauri::command]
+pub async fn prefetch_previews(
+    workspace_path: String,
+    slug: String,
+    start: usize,
+    count: usize,
+    prefetch: tauri::State<'_, PrefetchState>,
+) -> Result<Vec<PhotoPreview>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let details = read_gallery_details(&root, &slug)?
+        .ok_or_else(|| format!("gallery-details.json not found for '{}'", slug))?;
+    let photos = details.get("photos").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    let generation = prefetch.0.fetch_add(1, Ordering::SeqCst) + 1;
+    let latest = prefetch.0.clone();
+    let task = tokio::task::spawn_blocking(move || {
+        warm_previews(&root, &slug, &photos, start, count, || latest.load(Ordering::SeqCst) != generation)
+    });
+    task.await.map_err(|e| format!("Preview prefetch panicked: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn make_jpeg(path: &Path) {
+        image::RgbImage::new(4, 4).save(path).unwrap();
+    }
+
+    #[test]
+    fn test_warm_previews_range() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir(root.join("a")).unwrap();
+        make_jpeg(&root.join("a/01.jpg"));
+        make_jpeg(&root.join("a/02.jpg"));
+        let photos = vec![
+            json!({"thumbnail": "01.jpg", "full": "01.jpg"}),
+            json!({"thumbnail": "02.jpg", "full": "02.jpg"}),
+            json!({"thumbnail": "missing.jpg", "full": "missing.jpg"}),
+        ];
+
+        let previews = warm_previews(root, "a", &photos, 1, 10, || false);
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].index, 1);
+        let expected = root.join(".data/thumbnails/a/02.webp");
+        assert_eq!(previews[0].preview_path, Some(expected.to_string_lossy().to_string()));
+        assert!(expected.is_file());
+        assert!(!root.join(".data/thumbnails/a/01.webp").exists());
+        assert_eq!(previews[1].preview_path, None);
+
+        assert!(warm_previews(root, "a", &photos, 5, 10, || false).is_empty());
+        assert!(warm_previews(root, "a", &photos, 0, 10, || true).is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::encryption::readable_original;
+use crate::workspace::{read_galleries, read_gallery_details, str_field};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Resolution for gallery-quality prints.
+const PRINT_DPI_HIGH: f64 = 300.0;
+/// Lowest resolution generally acceptable for photo prints viewed at arm's length.
+const PRINT_DPI_MIN: f64 = 240.0;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintSize {
+    pub width_inches: f64,
+    pub height_inches: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoPrintInfo {
+    pub gallery_slug: String,
+    pub filename: String,
+    pub width_px: u32,
+    pub height_px: u32,
+    pub max_print_300_dpi: PrintSize,
+    pub max_print_240_dpi: PrintSize,
+    /// True when the photo covers the target print size at 300 dpi.
+    pub meets_target_at_300_dpi: bool,
+    /// True when the photo covers the target print size at 240 dpi. Photos where this is
+    /// false are flagged as unsuitable for the chosen size.
+    pub meets_target: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintSizeReport {
+    pub target: PrintSize,
+    pub photos: Vec<PhotoPrintInfo>,
+    /// Number of photos below the target size at 240 dpi.
+    pub flagged: usize,
+    pub errors: Vec<PrintSizeError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintSizeError {
+    /// "{slug}/{filename}" of the photo, or just the slug when its details file failed to load.
+    pub file: String,
+    pub error: String,
+}
+
+/// Maximum print size (in inches) for a `width`×`height` pixel image at `dpi`.
+pub fn max_print_size(width: u32, height: u32, dpi: f64) -> PrintSize {
+    PrintSize {
+        width_inches: round2(width as f64 / dpi),
+        height_inches: round2(height as f64 / dpi),
+    }
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// True when a `width`×`height` image can be printed at `target` size and `dpi`.
+/// Orientation-agnostic: the long edge is compared with the target's long edge.
+pub fn covers_print_size(width: u32, height: u32, target: PrintSize, dpi: f64) -> bool {
+    let (img_long, img_short) = if width >= height { (width, height) } else { (height, width) };
+    let (t_long, t_short) = if target.width_inches >= target.height_inches {
+        (target.width_inches, target.height_inches)
+    } else {
+        (target.height_inches, target.width_inches)
+    };
+    img_long as f64 >= (t_long * dpi).ceil() && img_short as f64 >= (t_short * dpi).ceil()
+}
+
+/// Report the maximum print size at 300 and 240 dpi for every photo in one gallery (or all
+/// galleries when `slug` is omitted), flagging photos too small for the chosen print size.
+///
+/// Only image headers are read, so this is cheap even for large galleries.
+#[tauri::command]
+pub async fn get_print_size_report(
+    workspace_path: String,
+    slug: Option<String>,
+    target_width_inches: f64,
+    target_height_inches: f64,
+) -> Result<PrintSizeReport, String> {
+    if target_width_inches <= 0.0 || target_height_inches <= 0.0 {
+        return Err("Print size must be greater than zero.".to_string());
+    }
+    let target = PrintSize {
+        width_inches: target_width_inches,
+        height_inches: target_height_inches,
+    };
+    let root = PathBuf::from(&workspace_path);
+
+    let slugs: Vec<String> = match slug {
+        Some(s) => vec![s],
+        None => read_galleries(&root)?
+            .iter()
+            .map(|g| str_field(g, "slug"))
+            .filter(|s| !s.is_empty())
+            .collect(),
+    };
+
+    tokio::task::spawn_blocking(move || print_size_report(&root, &slugs, target))
+        .await
+        .map_err(|e| format!("Print size report panicked: {}", e))
+}
+
+/// Print sizes of the photos of `slugs`, reading originals of encrypted galleries from their
+/// decrypted copies. Blocking.
+pub(crate) fn print_size_report(root: &Path, slugs: &[String], target: PrintSize) -> PrintSizeReport {
+    let mut photos_out = Vec::new();
+    let mut errors = Vec::new();
+    for slug in slugs {
+        let details = match read_gallery_details(root, slug) {
+            Ok(Some(d)) => d,
+            Ok(None) => continue,
+            Err(e) => {
+                errors.push(PrintSizeError { file: slug.clone(), error: e });
+                continue;
+            }
+        };
+        let photos = details.get("photos").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        for photo in &photos {
+            let filename = str_field(photo, "full");
+            if filename.is_empty() {
+                continue;
+            }
+            let path = readable_original(&root.join(slug).join(&filename));
+            match image::image_dimensions(&path) {
+                Ok((w, h)) => photos_out.push(PhotoPrintInfo {
+                    gallery_slug: slug.clone(),
+                    filename,
+                    width_px: w,
+                    height_px: h,
+                    max_print_300_dpi: max_print_size(w, h, PRINT_DPI_HIGH),
+                    max_print_240_dpi: max_print_size(w, h, PRINT_DPI_MIN),
+                    meets_target_at_300_dpi: covers_print_size(w, h, target, PRINT_DPI_HIGH),
+                    meets_target: covers_print_size(w, h, target, PRINT_DPI_MIN),
+                }),
+                Err(e) => errors.push(PrintSizeError {
+                    file: format!("{}/{}", slug, filename),
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+    let flagged = photos_out.iter().filter(|p| !p.meets_target).count();
+    PrintSizeReport { target, photos: photos_out, flagged, errors }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_print_size() {
+        // 6000×4000 (24 MP) → 20×13.33" at 300 dpi, 25×16.67" at 240 dpi
+        assert_eq!(
+            max_print_size(6000, 4000, 300.0),
+            PrintSize { width_inches: 20.0, height_inches: 13.33 }
+        );
+        assert_eq!(
+            max_print_size(6000, 4000, 240.0),
+            PrintSize { width_inches: 25.0, height_inches: 16.67 }
+        );
+    }
+
+    #[test]
+    fn test_covers_print_size_orientation_agnostic() {
+        let eight_by_ten = PrintSize { width_inches: 8.0, height_inches: 10.0 };
+        // 3000×2400 landscape covers a portrait 8×10 at 300 dpi (needs 3000×2400)
+        assert!(covers_print_size(3000, 2400, eight_by_ten, 300.0));
+        assert!(covers_print_size(2400, 3000, eight_by_ten, 300.0));
+        // One pixel short fails
+        assert!(!covers_print_size(2999, 2400, eight_by_ten, 300.0));
+    }
+
+    #[test]
+    fn test_covers_print_size_240_is_more_lenient() {
+        let a3 = PrintSize { width_inches: 11.7, height_inches: 16.5 };
+        // 12 MP (4000×3000): not enough for A3 at 300 dpi (needs 4950×3510), fine at 240 (3960×2808)
+        assert!(!covers_print_size(4000, 3000, a3, 300.0));
+        assert!(covers_print_size(4000, 3000, a3, 240.0));
+    }
+}
@@ -1,5 +1,30 @@
-use crate::settings::{extract_bucket_name, extract_distribution_id, get_credentials_from_keychain};
+use crate::acceleration::{accelerated_s3_client, supports_acceleration};
+use crate::archive::validate_archive_location;
+use crate::bucket_snapshot::BucketSnapshot;
+use crate::budget::check_budget;
+use crate::cache_warm::{wait_for_invalidation, warm_cache, warm_up_urls, DEFAULT_WARM_THUMBNAILS};
+use crate::cloudfront::cloudfront_client;
+use crate::comments::{gallery_comments, validate_comments, CommentsSettings};
+use crate::encryption::{locked_galleries, readable_original};
+use crate::i18n::{error, ErrorCode};
+use crate::image_lint::{lint_files, ImageLintIssue};
+use crate::managed_area::{audit_deletes, ManagedArea, ManagedKeyAudit};
+use crate::manifest::build_site_manifest;
+use crate::metadata::{load_metadata_index, refresh_metadata_index};
+use crate::mirror::{mirror_client, plan_mirror, run_mirror, validate_mirror, MirrorPlan};
+use crate::palette::{compute_palette, palette_json, PALETTE_SIZE};
+use crate::plugins::{run_hook, PluginHook};
+use crate::settings::{
+    extract_bucket_name, extract_distribution_id, get_credentials_from_keychain, normalize_s3_prefix, read_settings,
+    AppSettings,
+};
+use crate::site_html::{process_index_html, SiteHtmlOptions};
+use crate::site_template::{is_template_key, plan_template, published_template, TemplatePlan};
+use crate::stamps::{load_stamps, next_stamp, save_stamps, stamp_json, StampMap};
 use crate::thumbnails::{build_thumbnail_specs, cleanup_stale_thumbnails, ensure_thumbnails_with_progress, parse_galleries_array};
+use crate::transfers::{record_publish_transfer, TransferRecord};
+use crate::unpublished::{build_snapshot, save_snapshot, GallerySnapshot};
+use crate::workspace::read_json;
 use aws_credential_types::Credentials;
 use aws_sdk_s3::config::Region;
 use aws_sdk_s3::primitives::ByteStream;
@@ -29,7 +54,7 @@ fn is_syncable_file(path: &Path) -> bool {
         || ext == "js"
 }
 
-fn content_type_for_extension(path: &Path) -> &'static str {
+pub(crate) fn content_type_for_extension(path: &Path) -> &'static str {
     let ext = path
         .extension()
         .and_then(|e| e.to_str())
@@ -52,7 +77,7 @@ fn content_type_for_extension(path: &Path) -> &'static str {
     }
 }
 
-fn compute_md5(path: &Path) -> Result<String, String> {
+pub(crate) fn compute_md5(path: &Path) -> Result<String, String> {
     let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
     let mut hasher = Md5::new();
     hasher.update(&data);
@@ -96,7 +121,7 @@ fn walk_dir_recursive(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Resu
 ///   galleries.json → each gallery entry's slug → {slug}/gallery-details.json → photos
 ///
 /// Only files explicitly referenced are included. Untracked folders/files are excluded.
-fn collect_referenced_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+pub(crate) fn collect_referenced_files(root: &Path) -> Result<Vec<PathBuf>, String> {
     let mut files: HashSet<PathBuf> = HashSet::new();
 
     // Always include galleries.json
@@ -111,9 +136,9 @@ fn collect_referenced_files(root: &Path) -> Result<Vec<PathBuf>, String> {
 
     // Parse galleries.json (supports both wrapped and legacy formats)
     let galleries_content =
-        fs::read_to_string(&galleries_path).map_err(|e| format!("Failed to read galleries.json: {}", e))?;
+        fs::read_to_string(&galleries_path).map_err(|e| error(ErrorCode::GalleriesJsonReadFailed, &[("error", &e)]))?;
     let raw: serde_json::Value =
-        serde_json::from_str(&galleries_content).map_err(|e| format!("Failed to parse galleries.json: {}", e))?;
+        serde_json::from_str(&galleries_content).map_err(|e| error(ErrorCode::GalleriesJsonParseFailed, &[("error", &e)]))?;
     let galleries = if let Some(arr) = raw.as_array() {
         // Legacy format: plain array
         arr.clone()
@@ -138,7 +163,7 @@ fn collect_referenced_files(root: &Path) -> Result<Vec<PathBuf>, String> {
         if let Some(cover) = gallery.get("cover").and_then(|v| v.as_str()) {
             if !cover.is_empty() {
                 let cover_path = root.join(cover);
-                if cover_path.exists() && cover_path.is_file() {
+                if readable_original(&cover_path).is_file() {
                     files.insert(cover_path);
                 }
             }
@@ -149,18 +174,16 @@ fn collect_referenced_files(root: &Path) -> Result<Vec<PathBuf>, String> {
         if details_path.exists() {
             files.insert(details_path.clone());
 
-            if let Ok(details_content) = fs::read_to_string(&details_path) {
-                if let Ok(details) = serde_json::from_str::<serde_json::Value>(&details_content) {
-                    if let Some(photos) = details.get("photos").and_then(|v| v.as_array()) {
-                        for photo in photos {
-                            for field in &["thumbnail", "full"] {
-                                if let Some(path_str) = photo.get(field).and_then(|v| v.as_str()) {
-                                    if !path_str.is_empty() {
-                                        // Photo path is relative to gallery dir (e.g. "01.jpg")
-                                        let photo_path = root.join(slug).join(path_str);
-                                        if photo_path.exists() && photo_path.is_file() {
-                                            files.insert(photo_path);
-                                        }
+            if let Ok(details) = read_json(&details_path) {
+                if let Some(photos) = details.get("photos").and_then(|v| v.as_array()) {
+                    for photo in photos {
+                        for field in &["thumbnail", "full"] {
+                            if let Some(path_str) = photo.get(field).and_then(|v| v.as_str()) {
+                                if !path_str.is_empty() {
+                                    // Photo path is relative to gallery dir (e.g. "01.jpg")
+                                    let photo_path = root.join(slug).join(path_str);
+                                    if readable_original(&photo_path).is_file() {
+                                        files.insert(photo_path);
                                     }
                                 }
                             }
@@ -178,19 +201,24 @@ fn collect_referenced_files(root: &Path) -> Result<Vec<PathBuf>, String> {
 
 // Website source files embedded at compile time so they work in dev and production alike.
 const WEBSITE_INDEX_HTML: &[u8] = include_bytes!("../../afterglow-website/index.html");
-const WEBSITE_STYLES_CSS: &[u8] = include_bytes!("../../afterglow-website/afterglow/css/styles.css");
+pub(crate) const WEBSITE_STYLES_CSS: &[u8] = include_bytes!("../../afterglow-website/afterglow/css/styles.css");
 const WEBSITE_APP_JS: &[u8] = include_bytes!("../../afterglow-website/afterglow/js/app.js");
 const WEBSITE_FAVICON_ICO: &[u8] = include_bytes!("../../afterglow-website/favicon.ico");
 const WEBSITE_FAVICON_PNG: &[u8] = include_bytes!("../../afterglow-website/favicon.png");
+/// Every bundled website file, unprocessed: what the site template hash covers.
+pub(crate) const WEBSITE_TEMPLATE: [&[u8]; 5] =
+    [WEBSITE_INDEX_HTML, WEBSITE_STYLES_CSS, WEBSITE_APP_JS, WEBSITE_FAVICON_ICO, WEBSITE_FAVICON_PNG];
 
 /// Write the embedded website files to a temporary directory and return
 /// (local_path, s3_key) pairs for the five files:
-///   - index.html at the site root
+///   - index.html at the site root, processed with the site's HTML options
 ///   - afterglow/css/styles.css
 ///   - afterglow/js/app.js
 ///   - favicon.ico
 ///   - favicon.png
-fn collect_website_files(s3_root: &str) -> Result<Vec<(PathBuf, String)>, String> {
+fn collect_website_files(s3_root: &str, html_options: &SiteHtmlOptions) -> Result<Vec<(PathBuf, String)>, String> {
+    let index_html = std::str::from_utf8(WEBSITE_INDEX_HTML).map_err(|e| format!("Bundled index.html is not UTF-8: {}", e))?;
+    let index_html = process_index_html(index_html, html_options)?;
     let tmp = std::env::temp_dir().join("afterglow-manager-website");
     let css_dir = tmp.join("afterglow").join("css");
     let js_dir = tmp.join("afterglow").join("js");
@@ -198,7 +226,7 @@ fn collect_website_files(s3_root: &str) -> Result<Vec<(PathBuf, String)>, String
     fs::create_dir_all(&js_dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
 
     let entries = [
-        (WEBSITE_INDEX_HTML, tmp.join("index.html"), format!("{}index.html", s3_root)),
+        (index_html.as_bytes(), tmp.join("index.html"), format!("{}index.html", s3_root)),
         (WEBSITE_STYLES_CSS, css_dir.join("styles.css"), format!("{}afterglow/css/styles.css", s3_root)),
         (WEBSITE_APP_JS, js_dir.join("app.js"), format!("{}afterglow/js/app.js", s3_root)),
         (WEBSITE_FAVICON_ICO, tmp.join("favicon.ico"), format!("{}favicon.ico", s3_root)),
@@ -229,8 +257,240 @@ pub struct PublishPlan {
     pub plan_id: String,
     pub to_upload: Vec<SyncFile>,
     pub to_delete: Vec<String>,
+    /// `to_delete` split by what each deletion is, so the preview can ask to confirm only the
+    /// risky ones.
+    #[serde(default)]
+    pub delete_categories: DeleteCategories,
     pub unchanged: usize,
     pub total_files: usize,
+    /// Set when the plan deletes more than the delete protection threshold allows:
+    /// `publish_execute` then requires this exact token as its confirmation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_confirmation: Option<String>,
+    /// Set when the month-to-date spend is over the configured budget (or couldn't be checked).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_warning: Option<String>,
+    /// What the mirror target needs to match the same local files (see `mirror.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<MirrorPlan>,
+    /// Photos about to be uploaded in encodings that render poorly on the web (see `image_lint.rs`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub image_warnings: Vec<ImageLintIssue>,
+    /// The live site template against the bundled one (see `site_template.rs`).
+    #[serde(default)]
+    pub template: TemplatePlan,
+    /// Set when the plan was simulated against a bucket snapshot (see `bucket_snapshot.rs`):
+    /// when that snapshot was recorded. Simulated plans can't be executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulated_at: Option<String>,
+}
+
+/// A plan's deletions by category, each sorted. Removed galleries and unknown keys are the risky
+/// ones: they're what a mistake (a renamed folder, a wrong prefix) looks like.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteCategories {
+    /// Photos removed from galleries that are still published.
+    pub removed_photos: Vec<String>,
+    /// Every file of galleries that are no longer published.
+    pub removed_galleries: Vec<String>,
+    /// Thumbnails of galleries that are still published.
+    pub stale_thumbnails: Vec<String>,
+    /// Other keys in the managed area this publish doesn't write (site files, extra paths).
+    pub unknown: Vec<String>,
+}
+
+impl DeleteCategories {
+    /// Deletions that need explicit confirmation.
+    pub(crate) fn risky_count(&self) -> usize {
+        self.removed_galleries.len() + self.unknown.len()
+    }
+}
+
+/// Sort `to_delete` into categories. A gallery counts as still published when this publish
+/// writes any key under `{s3_root}galleries/{slug}/`.
+fn categorize_deletes<'a>(to_delete: &[String], local_keys: impl Iterator<Item = &'a String>, s3_root: &str) -> DeleteCategories {
+    let galleries_prefix = format!("{}galleries/", s3_root);
+    let live: HashSet<&str> = local_keys
+        .filter_map(|key| key.strip_prefix(&galleries_prefix)?.split_once('/'))
+        .map(|(slug, _)| slug)
+        .collect();
+    let mut categories = DeleteCategories::default();
+    for key in to_delete {
+        let (slug, file) = match key.strip_prefix(&galleries_prefix).and_then(|rest| rest.split_once('/')) {
+            Some(parts) => parts,
+            None => {
+                categories.unknown.push(key.clone());
+                continue;
+            }
+        };
+        // Search shards are named after their gallery: galleries/.search/{slug}.json
+        let gallery = match (slug, file.strip_suffix(".json")) {
+            (SEARCH_SHARD_DIR, Some(shard)) => shard,
+            _ => slug,
+        };
+        let category = if !live.contains(gallery) {
+            &mut categories.removed_galleries
+        } else if file.starts_with(".thumbs/") {
+            &mut categories.stale_thumbnails
+        } else if !file.contains('/') && is_image_key(file) {
+            &mut categories.removed_photos
+        } else {
+            &mut categories.unknown
+        };
+        category.push(key.clone());
+    }
+    for keys in [
+        &mut categories.removed_photos,
+        &mut categories.removed_galleries,
+        &mut categories.stale_thumbnails,
+        &mut categories.unknown,
+    ] {
+        keys.sort();
+    }
+    categories
+}
+
+fn is_image_key(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// S3 client for a bucket in `region`.
+pub(crate) fn s3_client(key_id: &str, secret: &str, region: &str) -> aws_sdk_s3::Client {
+    let config = aws_sdk_s3::Config::builder()
+        .credentials_provider(Credentials::new(key_id, secret, None, None, "afterglow-manager"))
+        .region(Region::new(region.to_string()))
+        .behavior_version_latest()
+        .build();
+    aws_sdk_s3::Client::from_conf(config)
+}
+
+/// An object returned by `list_objects`.
+#[derive(Debug, Clone)]
+pub(crate) struct ListedObject {
+    pub key: String,
+    /// ETag without quotes; the MD5 of the content unless uploaded in parts (contains `-`).
+    pub etag: String,
+    pub size: u64,
+}
+
+/// List every object under `prefix`, following continuation tokens.
+pub(crate) async fn list_objects(
+    client: &aws_sdk_s3::Client,
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<ListedObject>, String> {
+    let mut objects = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    loop {
+        let mut req = client.list_objects_v2().bucket(bucket).prefix(prefix);
+        if let Some(token) = &continuation_token {
+            req = req.continuation_token(token);
+        }
+        let resp = req.send().await.map_err(|e| format!("{}", e))?;
+
+        for obj in resp.contents() {
+            let key = obj.key().unwrap_or_default();
+            if key.is_empty() {
+                continue;
+            }
+            objects.push(ListedObject {
+                key: key.to_string(),
+                etag: obj.e_tag().unwrap_or_default().trim_matches('"').to_string(),
+                size: obj.size().unwrap_or(0).max(0) as u64,
+            });
+        }
+
+        if resp.is_truncated() == Some(true) {
+            continuation_token = resp.next_continuation_token().map(|s| s.to_string());
+        } else {
+            break;
+        }
+    }
+    Ok(objects)
+}
+
+/// Roots of other sites (found by their `site-manifest.json`) inside the keyspace managed
+/// from `s3_root`.
+pub(crate) fn nested_site_roots<'a>(s3_root: &str, area: &ManagedArea, keys: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut roots: Vec<String> = keys
+        .filter_map(|key| key.strip_suffix("site-manifest.json"))
+        .filter(|root| (root.is_empty() || root.ends_with('/')) && *root != s3_root && area.contains(s3_root, root))
+        .map(str::to_string)
+        .collect();
+    roots.sort();
+    roots
+}
+
+/// Ancestor roots whose managed keyspace would contain a site rooted at `s3_root`. Another
+/// site's extra managed paths aren't known, so only the built-in area is considered.
+pub(crate) fn enclosing_site_roots(s3_root: &str) -> Vec<String> {
+    let ancestors = std::iter::once(0).chain(s3_root.match_indices('/').map(|(i, _)| i + 1));
+    let area = ManagedArea::default();
+    ancestors
+        .map(|end| &s3_root[..end])
+        .filter(|root| *root != s3_root && area.contains(root, s3_root))
+        .map(str::to_string)
+        .collect()
+}
+
+fn display_root(root: &str) -> String {
+    if root.is_empty() {
+        "(bucket root)".to_string()
+    } else {
+        root.to_string()
+    }
+}
+
+pub(crate) fn prefix_overlap_error(s3_root: &str, other_root: &str) -> String {
+    error(ErrorCode::SitePrefixOverlap, &[("root", &display_root(s3_root)), ("other", &display_root(other_root))])
+}
+
+/// Whether a site at `s3_root` (managing `area`) and one at `other_root` share keys: the same
+/// root, or either nested in the other's managed area.
+fn roots_overlap(s3_root: &str, area: &ManagedArea, other_root: &str) -> bool {
+    s3_root == other_root || area.contains(s3_root, other_root) || ManagedArea::default().contains(other_root, s3_root)
+}
+
+/// Refuse a site root that shares its keyspace with another target configured in settings: the
+/// mirror bucket, the archive location, or the previous location while it awaits cleanup. Other
+/// sites nobody configured here are found through their `site-manifest.json` instead.
+fn check_configured_targets(settings: &AppSettings, bucket: &str, s3_root: &str) -> Result<(), String> {
+    validate_mirror(settings)?;
+    validate_archive_location(settings)?;
+    if let Some(previous) = &settings.previous_target {
+        let old_root = normalize_s3_prefix(&previous.s3_prefix)?;
+        let area = ManagedArea::from_settings(settings)?;
+        if extract_bucket_name(&previous.bucket) == bucket && roots_overlap(s3_root, &area, &old_root) {
+            return Err(error(
+                ErrorCode::PreviousTargetOverlap,
+                &[("root", &display_root(s3_root)), ("previous", &display_root(&old_root))],
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Default for the `deleteProtectionMaxCount` setting.
+pub(crate) const DEFAULT_DELETE_PROTECTION_MAX_COUNT: u32 = 100;
+/// Default for the `deleteProtectionMaxFraction` setting.
+pub(crate) const DEFAULT_DELETE_PROTECTION_MAX_FRACTION: f64 = 0.5;
+/// The fraction check ignores small deletes so a tiny site can still drop a gallery freely.
+const DELETE_PROTECTION_MIN_FRACTION_COUNT: usize = 10;
+/// How long publishing waits for CloudFront to accept the invalidation.
+const INVALIDATION_TIMEOUT_SECS: u64 = 30;
+
+/// Confirmation token required to delete `to_delete` of the `existing` objects under the S3
+/// prefix, or `None` when within the limits. Guards against a mistyped prefix wiping a site.
+pub(crate) fn delete_confirmation_token(to_delete: usize, existing: usize, max_count: u32, max_fraction: f64) -> Option<String> {
+    let over_count = to_delete > max_count as usize;
+    let over_fraction = to_delete >= DELETE_PROTECTION_MIN_FRACTION_COUNT
+        && existing > 0
+        && to_delete as f64 / existing as f64 > max_fraction;
+    (over_count || over_fraction).then(|| format!("DELETE {}", to_delete))
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -281,9 +541,9 @@ fn rewrite_galleries_json_for_publish(
 ) -> Result<Vec<u8>, String> {
     let path = root.join("galleries.json");
     let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read galleries.json: {}", e))?;
+        .map_err(|e| error(ErrorCode::GalleriesJsonReadFailed, &[("error", &e)]))?;
     let mut raw: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse galleries.json: {}", e))?;
+        .map_err(|e| error(ErrorCode::GalleriesJsonParseFailed, &[("error", &e)]))?;
 
     let galleries = if let Some(obj) = raw.as_object_mut() {
         obj.get_mut("galleries").and_then(|v| v.as_array_mut())
@@ -321,11 +581,15 @@ fn rewrite_gallery_details_json_for_publish(
     root: &Path,
     slug: &str,
     photo_thumb_map: &HashMap<PathBuf, String>,
+    comments: &CommentsSettings,
 ) -> Result<Vec<u8>, String> {
-    let content = fs::read_to_string(details_path)
-        .map_err(|e| format!("Failed to read {}: {}", details_path.display(), e))?;
-    let mut raw: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse {}: {}", details_path.display(), e))?;
+    let mut raw = read_json(details_path)?;
+
+    if let Some(config) = gallery_comments(comments, slug, &raw) {
+        if let Some(obj) = raw.as_object_mut() {
+            obj.insert("comments".to_string(), config);
+        }
+    }
 
     if let Some(photos) = raw.get_mut("photos").and_then(|v| v.as_array_mut()) {
         for photo in photos.iter_mut() {
@@ -370,31 +634,91 @@ struct SearchIndexPhoto {
     full: String,
     alt: String,
     tags: Vec<String>,
+    /// `full` filename of the paired before/after photo, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    related_to: Option<String>,
+    /// Sun/moon conditions at capture time, written by `enrich_astronomy`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    astronomy: Option<serde_json::Value>,
+    /// Link published with the photo (e.g. a print store), set by `set_photo_external_url`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_url: Option<String>,
+    /// Displayed pixel size of the full image, so the website can lay out results before
+    /// loading them. Omitted when the image couldn't be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    /// Width / height.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aspect_ratio: Option<f64>,
+    /// "portrait", "landscape" or "square".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    orientation: Option<&'static str>,
+}
+
+/// v2 splits photos out of `search-index.json` into per-gallery shards so large sites don't
+/// download every photo entry up front.
+const SEARCH_INDEX_VERSION: u32 = 2;
+/// Shard folder under `galleries/` (dot-prefixed like `.thumbs` so it can't clash with a slug).
+const SEARCH_SHARD_DIR: &str = ".search";
+
+/// Gallery index entry pointing at a gallery's photo shard.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchIndexShardRef {
+    gallery_slug: String,
+    /// Path relative to `galleries/`, e.g. ".search/{slug}.json".
+    path: String,
+    photo_count: usize,
+    /// Lowercased photo tags in the shard, so `#tag` searches can skip shards that can't match.
+    tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct SearchIndex {
     version: u32,
     galleries: Vec<SearchIndexGallery>,
+    shards: Vec<SearchIndexShardRef>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchIndexShard {
+    version: u32,
+    gallery_slug: String,
     photos: Vec<SearchIndexPhoto>,
 }
 
+/// Serialized search index: the gallery index plus one photo shard per non-empty gallery.
+struct GeneratedSearchIndex {
+    index: Vec<u8>,
+    /// (path relative to `galleries/`, bytes)
+    shards: Vec<(String, Vec<u8>)>,
+    /// Gallery slug → shard path relative to `galleries/`.
+    shard_paths: HashMap<String, String>,
+}
+
 fn generate_search_index(
     root: &Path,
     photo_thumb_map: &HashMap<PathBuf, String>,
-) -> Result<Vec<u8>, String> {
+) -> Result<GeneratedSearchIndex, String> {
     let mut galleries_out: Vec<SearchIndexGallery> = Vec::new();
-    let mut photos_out: Vec<SearchIndexPhoto> = Vec::new();
+    let mut shard_refs: Vec<SearchIndexShardRef> = Vec::new();
+    let mut shards_out: Vec<(String, Vec<u8>)> = Vec::new();
 
     let galleries_path = root.join("galleries.json");
     if !galleries_path.exists() {
-        let index = SearchIndex { version: 1, galleries: vec![], photos: vec![] };
-        return serde_json::to_vec(&index).map_err(|e| e.to_string());
+        let index = SearchIndex { version: SEARCH_INDEX_VERSION, galleries: vec![], shards: vec![] };
+        let index = serde_json::to_vec(&index).map_err(|e| e.to_string())?;
+        return Ok(GeneratedSearchIndex { index, shards: vec![], shard_paths: HashMap::new() });
     }
 
     let content = fs::read_to_string(&galleries_path).map_err(|e| e.to_string())?;
     let raw: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
     let galleries = parse_galleries_array(&raw);
+    // Dimensions come from the metadata cache, so only new or changed photos are opened
+    let metadata = refresh_metadata_index(root).unwrap_or_else(|_| load_metadata_index(root));
 
     for gallery in &galleries {
         let slug = match gallery.get("slug").and_then(|v| v.as_str()) {
@@ -411,6 +735,7 @@ fn generate_search_index(
 
         let details_path = root.join(&slug).join("gallery-details.json");
         let mut description = String::new();
+        let mut photos_out: Vec<SearchIndexPhoto> = Vec::new();
 
         if details_path.exists() {
             if let Ok(dc) = fs::read_to_string(&details_path) {
@@ -436,12 +761,31 @@ fn generate_search_index(
                                 .and_then(|v| v.as_array())
                                 .map(|arr| arr.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
                                 .unwrap_or_default();
+                            let related_to = photo
+                                .get("relatedTo")
+                                .and_then(|v| v.as_str())
+                                .filter(|s| !s.is_empty())
+                                .map(|s| s.to_string());
+                            let astronomy = photo.get("astronomy").filter(|v| v.is_object()).cloned();
+                            let external_url = photo
+                                .get("externalUrl")
+                                .and_then(|v| v.as_str())
+                                .filter(|s| !s.is_empty())
+                                .map(|s| s.to_string());
+                            let size = metadata.photos.get(&format!("{}/{}", slug, full)).cloned().unwrap_or_default();
                             photos_out.push(SearchIndexPhoto {
                                 gallery_slug: slug.clone(),
                                 thumbnail,
                                 full,
                                 alt,
                                 tags: photo_tags,
+                                related_to,
+                                astronomy,
+                                external_url,
+                                width: size.width,
+                                height: size.height,
+                                aspect_ratio: size.aspect_ratio(),
+                                orientation: size.orientation(),
                             });
                         }
                     }
@@ -449,6 +793,28 @@ fn generate_search_index(
             }
         }
 
+        if !photos_out.is_empty() {
+            let mut shard_tags: Vec<String> = photos_out
+                .iter()
+                .flat_map(|p| p.tags.iter().map(|t| t.to_lowercase()))
+                .collect();
+            shard_tags.sort();
+            shard_tags.dedup();
+            let path = format!("{}/{}.json", SEARCH_SHARD_DIR, slug);
+            shard_refs.push(SearchIndexShardRef {
+                gallery_slug: slug.clone(),
+                path: path.clone(),
+                photo_count: photos_out.len(),
+                tags: shard_tags,
+            });
+            let shard = SearchIndexShard {
+                version: SEARCH_INDEX_VERSION,
+                gallery_slug: slug.clone(),
+                photos: photos_out,
+            };
+            shards_out.push((path, serde_json::to_vec_pretty(&shard).map_err(|e| e.to_string())?));
+        }
+
         galleries_out.push(SearchIndexGallery {
             slug,
             name,
@@ -459,16 +825,60 @@ fn generate_search_index(
     }
 
     let index = SearchIndex {
-        version: 1,
+        version: SEARCH_INDEX_VERSION,
         galleries: galleries_out,
-        photos: photos_out,
+        shards: shard_refs,
     };
-    serde_json::to_vec_pretty(&index).map_err(|e| e.to_string())
+    let shard_paths = index
+        .shards
+        .iter()
+        .map(|s| (s.gallery_slug.clone(), s.path.clone()))
+        .collect();
+    let index = serde_json::to_vec_pretty(&index).map_err(|e| e.to_string())?;
+    Ok(GeneratedSearchIndex { index, shards: shards_out, shard_paths })
+}
+
+/// Stamp every JSON file in `local_map` whose key is under `s3_root`: the stamped copy is
+/// written to `tmp_dir` and replaces the map entry. Returns the stamps keyed by path relative
+/// to `s3_root`. A file whose content is unchanged keeps its previous stamp, so its stamped
+/// copy is byte-identical and it isn't re-uploaded.
+fn stamp_published_json(
+    local_map: &mut HashMap<String, (PathBuf, String)>,
+    s3_root: &str,
+    previous: &StampMap,
+    tmp_dir: &Path,
+) -> Result<StampMap, String> {
+    let mut stamps = StampMap::new();
+    for (s3_key, (path, md5)) in local_map.iter_mut() {
+        let rel = match s3_key.strip_prefix(s3_root) {
+            Some(rel) if rel.ends_with(".json") => rel,
+            _ => continue,
+        };
+        let content = fs::read(&*path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let stamp = next_stamp(previous.get(rel), &content);
+        let stamped_path = tmp_dir.join(rel);
+        if let Some(parent) = stamped_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        }
+        fs::write(&stamped_path, stamp_json(&content, &stamp).map_err(|e| format!("{}: {}", rel, e))?)
+            .map_err(|e| format!("Failed to write {}: {}", rel, e))?;
+        *md5 = compute_md5(&stamped_path)?;
+        *path = stamped_path;
+        stamps.insert(rel.to_string(), stamp);
+    }
+    Ok(stamps)
 }
 
 pub struct PublishState {
     pub plans: HashMap<String, PublishPlan>,
     pub cancelled: HashMap<String, bool>,
+    /// Publish stamps computed by a preview, saved to the workspace once that plan's
+    /// execution succeeds: plan_id → (workspace root, stamps).
+    pub stamps: HashMap<String, (PathBuf, StampMap)>,
+    /// S3 root and managed area each plan was previewed with: plan_id → (s3_root, area).
+    pub(crate) scopes: HashMap<String, (String, ManagedArea)>,
+    /// Gallery metadata each plan was previewed from, recorded as published once it succeeds.
+    pub(crate) snapshots: HashMap<String, Vec<(String, GallerySnapshot)>>,
 }
 
 impl PublishState {
@@ -476,10 +886,56 @@ impl PublishState {
         Self {
             plans: HashMap::new(),
             cancelled: HashMap::new(),
+            stamps: HashMap::new(),
+            scopes: HashMap::new(),
+            snapshots: HashMap::new(),
         }
     }
 }
 
+/// Local files whose S3 object is missing or differs, and the number already up to date.
+/// `local_map`: s3_key → (local path, MD5); `remote`: s3_key → ETag.
+pub(crate) fn files_to_upload(
+    local_map: &HashMap<String, (PathBuf, String)>,
+    remote: &HashMap<String, String>,
+) -> Result<(Vec<SyncFile>, usize), String> {
+    let mut to_upload = Vec::new();
+    let mut unchanged: usize = 0;
+
+    for (s3_key, (local_path, local_md5)) in local_map {
+        if let Some(etag) = remote.get(s3_key) {
+            // If ETag contains a hyphen (multipart upload), treat as changed
+            if !etag.contains('-') && etag == local_md5 {
+                unchanged += 1;
+                continue;
+            }
+        }
+
+        let metadata = fs::metadata(local_path).map_err(|e| e.to_string())?;
+        to_upload.push(SyncFile {
+            local_path: local_path.to_string_lossy().to_string(),
+            s3_key: s3_key.clone(),
+            size_bytes: metadata.len(),
+            content_type: content_type_for_extension(local_path).to_string(),
+        });
+    }
+    Ok((to_upload, unchanged))
+}
+
+/// Remote keys with no local file, restricted to the managed area.
+pub(crate) fn keys_to_delete(
+    local_map: &HashMap<String, (PathBuf, String)>,
+    remote: &HashMap<String, String>,
+    s3_root: &str,
+    managed_area: &ManagedArea,
+) -> Vec<String> {
+    remote
+        .keys()
+        .filter(|key| !local_map.contains_key(*key) && managed_area.contains(s3_root, key))
+        .cloned()
+        .collect()
+}
+
 #[tauri::command]
 pub async fn publish_preview(
     app: tauri::AppHandle,
@@ -488,36 +944,82 @@ pub async fn publish_preview(
     region: String,
     s3_root: String,
 ) -> Result<PublishPlan, String> {
-    let (key_id, secret) = get_credentials_from_keychain()?;
+    crate::read_only::ensure_writable(&app, Path::new(&folder_path))?;
+    crate::details_store::flush_workspace(&app, Path::new(&folder_path))?;
+    plan_publish(app, folder_path, bucket, region, s3_root, None).await
+}
 
-    let creds = Credentials::new(&key_id, &secret, None, None, "afterglow-manager");
-    let region = Region::new(region);
+/// Whether `key` (a `site-manifest.json`) exists in the bucket.
+pub(crate) async fn object_exists(client: &aws_sdk_s3::Client, bucket: &str, key: &str) -> Result<bool, String> {
+    let resp = client
+        .list_objects_v2()
+        .bucket(bucket)
+        .prefix(key)
+        .max_keys(1)
+        .send()
+        .await
+        .map_err(|e| format!("{}", e))?;
+    Ok(resp.contents().iter().any(|obj| obj.key() == Some(key)))
+}
 
-    let s3_config = aws_sdk_s3::Config::builder()
-        .credentials_provider(creds)
-        .region(region)
-        .behavior_version_latest()
-        .build();
-    let s3_client = aws_sdk_s3::Client::from_conf(s3_config);
+/// Where a preview gets the bucket's current contents from.
+enum Remote<'a> {
+    Live(aws_sdk_s3::Client),
+    Snapshot(&'a BucketSnapshot),
+}
 
-    let bucket = extract_bucket_name(&bucket);
+/// Build a publish plan. With a bucket `snapshot` nothing touches AWS: the remote listing comes
+/// from the snapshot, the budget and mirror are skipped, and the plan is returned without being
+/// stored, so it can't be executed.
+pub(crate) async fn plan_publish(
+    app: tauri::AppHandle,
+    folder_path: String,
+    bucket: String,
+    region: String,
+    s3_root: String,
+    snapshot: Option<&BucketSnapshot>,
+) -> Result<PublishPlan, String> {
     let root = PathBuf::from(&folder_path);
+    let (remote, credentials) = match snapshot {
+        Some(snapshot) => (Remote::Snapshot(snapshot), None),
+        None => {
+            let (key_id, secret) = get_credentials_from_keychain()?;
+            (Remote::Live(s3_client(&key_id, &secret, &region)), Some((key_id, secret)))
+        }
+    };
+
+    let bucket = extract_bucket_name(&bucket);
 
     // Normalise s3_root: must be empty or end with /
-    let s3_root = if s3_root.is_empty() || s3_root.ends_with('/') {
-        s3_root
-    } else {
-        format!("{}/", s3_root)
+    let s3_root = normalize_s3_prefix(&s3_root)?;
+    let settings = read_settings(&app)?;
+    validate_comments(&settings.comments)?;
+    check_configured_targets(&settings, &bucket, &s3_root)?;
+    let locked = locked_galleries(&root)?;
+    if !locked.is_empty() {
+        return Err(error(ErrorCode::GalleriesLocked, &[("galleries", &locked.join(", "))]));
+    }
+    // Before any heavy work, so an over-budget block fails fast
+    let budget_warning = match &credentials {
+        Some((key_id, secret)) => check_budget(&settings, key_id, secret).await?,
+        None => None,
     };
+    // The live template decides which one the manifest records, and a pin that can't be
+    // honoured should fail before the thumbnails are generated
+    let published_template = match &remote {
+        Remote::Live(client) => published_template(client, &bucket, &s3_root).await?,
+        Remote::Snapshot(snapshot) => snapshot.template.clone(),
+    };
+    let template = plan_template(published_template, &settings.site_template_pin)?;
 
     // ===== Thumbnail generation =====
     // Parse galleries.json to build thumbnail specs before any network I/O.
     let galleries_json: serde_json::Value = {
         let path = root.join("galleries.json");
         let content = fs::read_to_string(&path)
-            .map_err(|e| format!("Failed to read galleries.json: {}", e))?;
+            .map_err(|e| error(ErrorCode::GalleriesJsonReadFailed, &[("error", &e)]))?;
         serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse galleries.json: {}", e))?
+            .map_err(|e| error(ErrorCode::GalleriesJsonParseFailed, &[("error", &e)]))?
     };
 
     let specs = build_thumbnail_specs(&root, &galleries_json, &s3_root);
@@ -546,7 +1048,7 @@ pub async fn publish_preview(
             "publish-thumbnail-progress",
             ThumbnailProgress { current: 0, total: 0, filename: String::new() },
         );
-        crate::thumbnails::ThumbnailResults { generated: 0, skipped: 0, errors: vec![] }
+        crate::thumbnails::ThumbnailResults { generated: 0, skipped: 0, generated_paths: vec![], errors: vec![] }
     };
 
     if !thumb_results.errors.is_empty() {
@@ -555,6 +1057,16 @@ pub async fn publish_preview(
         }
     }
 
+    // Plugins may post-process the new thumbnails in place before they are hashed below
+    if !thumb_results.generated_paths.is_empty() {
+        let thumbnails: Vec<serde_json::Value> = specs
+            .iter()
+            .filter(|spec| thumb_results.generated_paths.contains(&spec.dest_path))
+            .map(|spec| serde_json::json!({ "source": spec.source_path, "path": spec.dest_path, "s3Key": spec.s3_key }))
+            .collect();
+        run_hook(&app, PluginHook::PostThumbnail, Some(&root), serde_json::json!({ "thumbnails": thumbnails })).await?;
+    }
+
     // Clean up stale local thumbnail cache entries (non-fatal).
     let thumbnail_cache_root = root.join(".data").join("thumbnails");
     let _cleaned = cleanup_stale_thumbnails(&thumbnail_cache_root, &specs);
@@ -595,8 +1107,10 @@ pub async fn publish_preview(
             .to_string_lossy()
             .replace('\\', "/");
         let s3_key = format!("{}{}", galleries_prefix, relative);
-        let md5 = compute_md5(file_path)?;
-        local_map.insert(s3_key, (file_path.clone(), md5));
+        // Originals of encrypted galleries are uploaded from their decrypted copies
+        let source = readable_original(file_path);
+        let md5 = compute_md5(&source)?;
+        local_map.insert(s3_key, (source, md5));
     }
 
     // Rewrite galleries.json with thumbnail cover paths (if any thumbnails generated)
@@ -610,8 +1124,8 @@ pub async fn publish_preview(
         local_map.insert(s3_key, (tmp_path, md5));
     }
 
-    // Rewrite each gallery-details.json with thumbnail paths
-    if !photo_thumb_map.is_empty() {
+    // Rewrite each gallery-details.json with thumbnail paths and the comments config
+    if !photo_thumb_map.is_empty() || !settings.comments.provider.is_empty() {
         let galleries = parse_galleries_array(&galleries_json);
         for gallery in &galleries {
             let slug = match gallery.get("slug").and_then(|v| v.as_str()) {
@@ -627,6 +1141,7 @@ pub async fn publish_preview(
                 &root,
                 slug,
                 &photo_thumb_map,
+                &settings.comments,
             )?;
             let tmp_dir = rewrite_tmp.join(slug);
             fs::create_dir_all(&tmp_dir)
@@ -648,125 +1163,200 @@ pub async fn publish_preview(
         }
     }
 
-    // Search index goes at {s3_root}galleries/search-index.json
-    let search_index_bytes = generate_search_index(&root, &photo_thumb_map)?;
-    let tmp_dir = std::env::temp_dir().join("afterglow-manager-search");
-    fs::create_dir_all(&tmp_dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
-    let search_index_path = tmp_dir.join("search-index.json");
-    fs::write(&search_index_path, &search_index_bytes)
-        .map_err(|e| format!("Failed to write search index: {}", e))?;
-    let search_index_key = format!("{}search-index.json", galleries_prefix);
-    let search_index_md5 = compute_md5(&search_index_path)?;
-    local_map.insert(search_index_key, (search_index_path, search_index_md5));
-
-    // Website files go at {s3_root}index.html, {s3_root}afterglow/...
-    let website_files = collect_website_files(&s3_root)?;
-    for (file_path, s3_key) in &website_files {
-        let md5 = compute_md5(file_path)?;
-        local_map.insert(s3_key.clone(), (file_path.clone(), md5));
+    // Per-gallery colour palettes go at {s3_root}galleries/{slug}/palette.json.
+    // Sampled from the generated thumbnails; galleries without thumbnails get no palette.
+    let mut palette_inputs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for spec in &specs {
+        if spec.dest_path.exists() {
+            palette_inputs
+                .entry(spec.slug.clone())
+                .or_default()
+                .push(spec.dest_path.clone());
+        }
     }
-
-    // List all S3 objects under s3_root
-    let mut s3_objects: HashMap<String, String> = HashMap::new(); // key -> etag
-    let mut continuation_token: Option<String> = None;
-    loop {
-        let mut req = s3_client.list_objects_v2().bucket(&bucket).prefix(&s3_root);
-        if let Some(token) = &continuation_token {
-            req = req.continuation_token(token);
+    let palettes = tokio::task::spawn_blocking(move || {
+        palette_inputs
+            .into_iter()
+            .map(|(slug, images)| (slug, compute_palette(&images, PALETTE_SIZE)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Palette generation panicked: {}", e))?;
+    for (slug, colors) in palettes {
+        if colors.is_empty() {
+            continue;
         }
-        let resp = req.send().await.map_err(|e| format!("{}", e))?;
+        let tmp_dir = rewrite_tmp.join(&slug);
+        fs::create_dir_all(&tmp_dir)
+            .map_err(|e| format!("Failed to create rewrite tmp dir: {}", e))?;
+        let tmp_path = tmp_dir.join("palette.json");
+        fs::write(&tmp_path, palette_json(colors)?)
+            .map_err(|e| format!("Failed to write palette.json: {}", e))?;
+        let md5 = compute_md5(&tmp_path)?;
+        let s3_key = format!("{}{}/palette.json", galleries_prefix, slug);
+        local_map.insert(s3_key, (tmp_path, md5));
+    }
 
-        for obj in resp.contents() {
-            let key = obj.key().unwrap_or_default();
-            if key.is_empty() {
-                continue;
-            }
-            let etag = obj
-                .e_tag()
-                .unwrap_or_default()
-                .trim_matches('"')
-                .to_string();
-            s3_objects.insert(key.to_string(), etag);
-        }
+    // Search index goes at {s3_root}galleries/search-index.json, photo shards at
+    // {s3_root}galleries/.search/{slug}.json
+    let search_index = generate_search_index(&root, &photo_thumb_map)?;
+    let tmp_dir = std::env::temp_dir().join("afterglow-manager-search");
+    if tmp_dir.exists() {
+        // Clear shards of galleries removed since the last preview
+        let _ = fs::remove_dir_all(&tmp_dir);
+    }
+    fs::create_dir_all(tmp_dir.join(SEARCH_SHARD_DIR)).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let search_files = std::iter::once(("search-index.json".to_string(), search_index.index))
+        .chain(search_index.shards);
+    for (rel_path, bytes) in search_files {
+        let path = tmp_dir.join(&rel_path);
+        fs::write(&path, &bytes).map_err(|e| format!("Failed to write {}: {}", rel_path, e))?;
+        let md5 = compute_md5(&path)?;
+        local_map.insert(format!("{}{}", galleries_prefix, rel_path), (path, md5));
+    }
 
-        if resp.is_truncated() == Some(true) {
-            continuation_token = resp.next_continuation_token().map(|s| s.to_string());
-        } else {
-            break;
+    // Stamp every published JSON file with a content hash + generation counter, continuing
+    // from the stamps of the last successful publish.
+    let previous_stamps = load_stamps(&root);
+    let stamp_tmp = std::env::temp_dir().join("afterglow-manager-stamped");
+    if stamp_tmp.exists() {
+        let _ = fs::remove_dir_all(&stamp_tmp);
+    }
+    let mut stamps = stamp_published_json(&mut local_map, &s3_root, &previous_stamps, &stamp_tmp)?;
+
+    // Site manifest goes at {s3_root}site-manifest.json, listing the stamps and stamped itself
+    let manifest_path = tmp_dir.join("site-manifest.json");
+    fs::write(&manifest_path, build_site_manifest(&root, &search_index.shard_paths, &stamps, template.published_after())?)
+        .map_err(|e| format!("Failed to write site manifest: {}", e))?;
+    let manifest_md5 = compute_md5(&manifest_path)?;
+    let manifest_key = format!("{}site-manifest.json", s3_root);
+    let mut manifest_map = HashMap::from([(manifest_key, (manifest_path, manifest_md5))]);
+    stamps.extend(stamp_published_json(&mut manifest_map, &s3_root, &previous_stamps, &stamp_tmp)?);
+    local_map.extend(manifest_map);
+
+    // Website files go at {s3_root}index.html, {s3_root}afterglow/..., unless a pin keeps the live ones
+    let managed_area = ManagedArea::from_settings(&settings)?;
+    if !template.pinned {
+        let html_options = SiteHtmlOptions::from_settings(&settings);
+        let website_files = collect_website_files(&s3_root, &html_options)?;
+        for (file_path, s3_key) in &website_files {
+            let md5 = compute_md5(file_path)?;
+            local_map.insert(s3_key.clone(), (file_path.clone(), md5));
         }
     }
 
+    // List all S3 objects under s3_root
+    let s3_objects: HashMap<String, String> = match &remote {
+        Remote::Live(client) => list_objects(client, &bucket, &s3_root)
+            .await?
+            .into_iter()
+            .map(|obj| (obj.key, obj.etag))
+            .collect(),
+        Remote::Snapshot(snapshot) => snapshot.objects.clone().into_iter().collect(),
+    };
+
     // Compare
-    let mut to_upload = Vec::new();
-    let mut unchanged: usize = 0;
+    let (to_upload, unchanged) = files_to_upload(&local_map, &s3_objects)?;
 
-    for (s3_key, (local_path, local_md5)) in &local_map {
-        if let Some(etag) = s3_objects.get(s3_key) {
-            // If ETag contains a hyphen (multipart upload), treat as changed
-            if !etag.contains('-') && etag == local_md5 {
-                unchanged += 1;
-                continue;
-            }
+    // Refuse to publish when another site shares our keyspace: its files would be deleted
+    if let Some(nested) = nested_site_roots(&s3_root, &managed_area, s3_objects.keys()).first() {
+        return Err(prefix_overlap_error(&s3_root, nested));
+    }
+    for enclosing in enclosing_site_roots(&s3_root) {
+        let manifest_key = format!("{}site-manifest.json", enclosing);
+        let exists = match &remote {
+            Remote::Live(client) => object_exists(client, &bucket, &manifest_key).await?,
+            Remote::Snapshot(snapshot) => snapshot.enclosing_manifests.contains(&manifest_key),
+        };
+        if exists {
+            return Err(prefix_overlap_error(&s3_root, &enclosing));
         }
-
-        let metadata = fs::metadata(local_path).map_err(|e| e.to_string())?;
-        to_upload.push(SyncFile {
-            local_path: local_path.to_string_lossy().to_string(),
-            s3_key: s3_key.clone(),
-            size_bytes: metadata.len(),
-            content_type: content_type_for_extension(local_path).to_string(),
-        });
     }
 
     // Files to delete: in S3 but not in local map, restricted to managed areas only.
-    // We only manage: {s3_root}galleries/*, {s3_root}afterglow/*, {s3_root}index.html, {s3_root}favicon.*
-    let afterglow_prefix = format!("{}afterglow/", s3_root);
-    let index_key = format!("{}index.html", s3_root);
-    let favicon_ico_key = format!("{}favicon.ico", s3_root);
-    let favicon_png_key = format!("{}favicon.png", s3_root);
-    let to_delete: Vec<String> = s3_objects
-        .keys()
-        .filter(|key| {
-            !local_map.contains_key(*key)
-                && (key.starts_with(&galleries_prefix)
-                    || key.starts_with(&afterglow_prefix)
-                    || **key == index_key
-                    || **key == favicon_ico_key
-                    || **key == favicon_png_key)
-        })
-        .cloned()
-        .collect();
+    let mut to_delete = keys_to_delete(&local_map, &s3_objects, &s3_root, &managed_area);
+    let mirror = match remote {
+        Remote::Live(_) => plan_mirror(&settings, &local_map, &s3_root, &managed_area, template.pinned).await,
+        Remote::Snapshot(_) => None,
+    };
+    if template.pinned {
+        to_delete.retain(|key| !is_template_key(key, &s3_root));
+    }
+    let delete_categories = categorize_deletes(&to_delete, local_map.keys(), &s3_root);
 
     let total_files = to_upload.len() + to_delete.len() + unchanged;
     let plan_id = uuid::Uuid::new_v4().to_string();
+    let uploaded_photos: Vec<String> = to_upload
+        .iter()
+        .filter_map(|file| Path::new(&file.local_path).strip_prefix(&root).ok())
+        .filter(|rel| !rel.starts_with(".data"))
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        .collect();
+    let image_warnings = lint_files(&root, &uploaded_photos);
+    let delete_confirmation = delete_confirmation_token(
+        to_delete.len(),
+        s3_objects.len(),
+        settings.delete_protection_max_count.unwrap_or(DEFAULT_DELETE_PROTECTION_MAX_COUNT),
+        settings.delete_protection_max_fraction.unwrap_or(DEFAULT_DELETE_PROTECTION_MAX_FRACTION),
+    );
 
     let plan = PublishPlan {
         plan_id: plan_id.clone(),
         to_upload,
         to_delete,
+        delete_categories,
         unchanged,
         total_files,
+        delete_confirmation,
+        budget_warning,
+        mirror,
+        image_warnings,
+        template,
+        simulated_at: snapshot.map(|s| s.recorded_at.clone()),
     };
+    if snapshot.is_some() {
+        return Ok(plan);
+    }
+
+    let gallery_snapshot = build_snapshot(&root);
 
     // Store the plan
     let state = app.state::<Mutex<PublishState>>();
     let mut state = state.lock().map_err(|e| e.to_string())?;
     state.plans.insert(plan_id.clone(), plan.clone());
+    if let Ok(gallery_snapshot) = gallery_snapshot {
+        state.snapshots.insert(plan_id.clone(), gallery_snapshot);
+    }
+    state.stamps.insert(plan_id.clone(), (root, stamps));
+    state.scopes.insert(plan_id.clone(), (s3_root, managed_area));
     state.cancelled.insert(plan_id, false);
 
     Ok(plan)
 }
 
 #[tauri::command]
-pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(), String> {
+pub async fn publish_execute(
+    app: tauri::AppHandle,
+    plan_id: String,
+    confirmation: Option<String>,
+    mirror_confirmation: Option<String>,
+) -> Result<(), String> {
     let (plan, key_id, secret) = {
         let state = app.state::<Mutex<PublishState>>();
         let state = state.lock().map_err(|e| e.to_string())?;
         let plan = state
             .plans
             .get(&plan_id)
-            .ok_or("Plan not found. Run preview first.")?
+            .ok_or_else(|| error(ErrorCode::PlanNotFound, &[]))?
             .clone();
+        if let Some(token) = &plan.delete_confirmation {
+            if confirmation.as_deref().map(str::trim) != Some(token.as_str()) {
+                return Err(error(
+                    ErrorCode::PublishDeleteNeedsConfirmation,
+                    &[("count", &plan.to_delete.len()), ("token", token)],
+                ));
+            }
+        }
         let (key_id, secret) = get_credentials_from_keychain()?;
         (plan, key_id, secret)
     };
@@ -777,27 +1367,48 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
     let settings_path = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Cannot determine app data directory: {}", e))?
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?
         .join("settings.json");
     let settings_content = fs::read_to_string(&settings_path).map_err(|e| e.to_string())?;
     let settings: crate::settings::AppSettings =
         serde_json::from_str(&settings_content).map_err(|e| e.to_string())?;
+    let s3_root = normalize_s3_prefix(&settings.s3_prefix)?;
 
-    let bucket_name = extract_bucket_name(&settings.bucket);
-    let creds = Credentials::new(&key_id, &secret, None, None, "afterglow-manager");
-    let region = Region::new(settings.region.clone());
+    // Safety: refuse (rather than skip deletes) unless every delete is in the managed area
+    // under both the preview-time and the current rules
+    if let Some(reason) = audit_plan(&app, &plan_id, &settings)?.refusal() {
+        return Err(reason);
+    }
+    // The mirror gets the same checks, but refusing it only skips the mirror
+    let mirror_plan = match &plan.mirror {
+        Some(mirror) => {
+            let audit = audit_plan_deletes(&app, &plan_id, &settings, &mirror.to_delete)?;
+            Some(match mirror.refusal(mirror_confirmation.as_deref(), &audit) {
+                Some(reason) => MirrorPlan { to_upload: Vec::new(), to_delete: Vec::new(), error: Some(reason), ..mirror.clone() },
+                None => mirror.clone(),
+            })
+        }
+        None => None,
+    };
 
-    let s3_config = aws_sdk_s3::Config::builder()
-        .credentials_provider(creds)
-        .region(region)
-        .behavior_version_latest()
-        .build();
-    let s3_client = aws_sdk_s3::Client::from_conf(s3_config);
+    let workspace_root = {
+        let state = app.state::<Mutex<PublishState>>();
+        let state = state.lock().map_err(|e| e.to_string())?;
+        state.stamps.get(&plan_id).map(|(root, _)| root.clone())
+    };
+    let plan_json = serde_json::to_value(&plan).map_err(|e| e.to_string())?;
+    run_hook(&app, PluginHook::PrePublish, workspace_root.as_deref(), plan_json.clone()).await?;
+
+    let bucket_name = extract_bucket_name(&settings.bucket);
+    let s3_client = s3_client(&key_id, &secret, &settings.region);
+    let mut accelerated_client = (settings.transfer_acceleration && supports_acceleration(&bucket_name, &settings.region))
+        .then(|| accelerated_s3_client(&key_id, &secret, &settings.region));
 
     let total = plan.to_upload.len() + plan.to_delete.len();
     let mut current: usize = 0;
     let mut uploaded: usize = 0;
     let mut deleted: usize = 0;
+    let mut bytes_uploaded: u64 = 0;
     let _start = Instant::now();
 
     // Upload files
@@ -807,6 +1418,7 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
             let state = app.state::<Mutex<PublishState>>();
             let state = state.lock().map_err(|e| e.to_string())?;
             if state.cancelled.get(&plan_id) == Some(&true) {
+                record_publish_transfer(&app, &settings, TransferRecord::now(bytes_uploaded, uploaded, deleted, false));
                 let _ = app.emit("publish-complete", PublishResult {
                     uploaded,
                     deleted,
@@ -827,54 +1439,39 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
             },
         );
 
-        let body = ByteStream::from_path(&file.local_path)
-            .await
-            .map_err(|e| format!("Failed to read {}: {}", file.local_path, e))?;
-
-        match s3_client
-            .put_object()
-            .bucket(&bucket_name)
-            .key(&file.s3_key)
-            .content_type(&file.content_type)
-            .body(body)
-            .send()
-            .await
-        {
-            Ok(_) => uploaded += 1,
+        let mut result = upload_file(accelerated_client.as_ref().unwrap_or(&s3_client), &bucket_name, file).await?;
+        if result.is_err() && accelerated_client.is_some() {
+            // Retry on the regular endpoint and keep using it for the remaining files
+            accelerated_client = None;
+            result = upload_file(&s3_client, &bucket_name, file).await?;
+        }
+        match result {
+            Ok(()) => {
+                uploaded += 1;
+                bytes_uploaded += file.size_bytes;
+            }
             Err(e) => {
+                record_publish_transfer(&app, &settings, TransferRecord::now(bytes_uploaded, uploaded, deleted, false));
                 let _ = app.emit(
                     "publish-error",
                     PublishError {
-                        error: format!("{}", e),
+                        error: e.clone(),
                         file: file.s3_key.clone(),
                     },
                 );
-                return Err(format!("Upload failed for {}: {}", file.s3_key, e));
+                return Err(error(ErrorCode::UploadFailed, &[("key", &file.s3_key), ("error", &e)]));
             }
         }
     }
 
-    // Delete files
-    // Safety: only delete keys in the managed areas (galleries/, afterglow/, index.html)
-    let s3_root = &settings.s3_prefix;
-    let galleries_prefix = format!("{}galleries/", s3_root);
-    let afterglow_prefix = format!("{}afterglow/", s3_root);
-    let index_key = format!("{}index.html", s3_root);
-
+    // Delete files (all checked against the managed area above)
     for s3_key in &plan.to_delete {
-        // Safety: only delete keys within managed areas
-        if !s3_key.starts_with(&galleries_prefix)
-            && !s3_key.starts_with(&afterglow_prefix)
-            && s3_key.as_str() != index_key.as_str()
-        {
-            continue;
-        }
-
         // Check cancellation
         {
             let state = app.state::<Mutex<PublishState>>();
             let state = state.lock().map_err(|e| e.to_string())?;
             if state.cancelled.get(&plan_id) == Some(&true) {
+                record_publish_transfer(&app, &settings, TransferRecord::now(bytes_uploaded, uploaded, deleted, false));
                 let _ = app.emit("publish-complete", PublishResult {
                     uploaded,
                     deleted,
@@ -904,6 +1501,7 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
         {
             Ok(_) => deleted += 1,
             Err(e) => {
+                record_publish_transfer(&app, &settings, TransferRecord::now(bytes_uploaded, uploaded, deleted, false));
                 let _ = app.emit(
                     "publish-error",
                     PublishError {
@@ -911,7 +1509,38 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
                         file: s3_key.clone(),
                     },
                 );
-                return Err(format!("Delete failed for {}: {}", s3_key, e));
+                return Err(error(ErrorCode::DeleteFailed, &[("key", s3_key), ("error", &e)]));
+            }
+        }
+    }
+
+    record_publish_transfer(&app, &settings, TransferRecord::now(bytes_uploaded, uploaded, deleted, true));
+
+    // Mirror: same files to the second target, reporting its own progress; its failures
+    // never fail the publish
+    if let Some(mirror_plan) = &mirror_plan {
+        let client = mirror_client(&settings.mirror, &key_id, &secret);
+        let cancelled = || {
+            let state = app.state::<Mutex<PublishState>>();
+            let state = state.lock();
+            state.is_ok_and(|state| state.cancelled.get(&plan_id) == Some(&true))
+        };
+        run_mirror(&app, &settings.mirror, mirror_plan, &s3_root, &client, cancelled).await;
+    }
+
+    // Every upload and delete succeeded: the stamps now describe what's live.
+    let (stamps, snapshot) = {
+        let state = app.state::<Mutex<PublishState>>();
+        let state = state.lock().map_err(|e| e.to_string())?;
+        (state.stamps.get(&plan_id).cloned(), state.snapshots.get(&plan_id).cloned())
+    };
+    if let Some((root, stamps)) = &stamps {
+        if let Err(e) = save_stamps(root, stamps) {
+            eprintln!("[publish] Failed to save publish stamps: {}", e);
+        }
+        if let Some(snapshot) = &snapshot {
+            if let Err(e) = save_snapshot(root, snapshot) {
+                eprintln!("[publish] Failed to save published snapshot: {}", e);
             }
         }
     }
@@ -929,16 +1558,11 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
             },
         );
 
-        let cf_config = aws_sdk_cloudfront::Config::builder()
-            .credentials_provider(Credentials::new(&key_id, &secret, None, None, "afterglow-manager"))
-            .region(Region::new("us-east-1"))
-            .behavior_version_latest()
-            .build();
-        let cf_client = aws_sdk_cloudfront::Client::from_conf(cf_config);
+        let cf_client = cloudfront_client(&key_id, &secret, &settings.region)?;
 
         let invalidation_path = format!("/{}*", s3_root);
         let invalidation_result = tokio::time::timeout(
-            std::time::Duration::from_secs(30),
+            std::time::Duration::from_secs(INVALIDATION_TIMEOUT_SECS),
             cf_client
                 .create_invalidation()
                 .distribution_id(&dist_id)
@@ -959,50 +1583,151 @@ pub async fn publish_execute(app: tauri::AppHandle, plan_id: String) -> Result<(
         )
         .await;
 
-        match invalidation_result {
-            Ok(Ok(_)) => {
+        let invalidation_id = match invalidation_result {
+            Ok(Ok(output)) => {
                 eprintln!("[publish] CloudFront invalidation created for path: {}", invalidation_path);
+                output.invalidation().map(|i| i.id().to_string())
             }
             Ok(Err(e)) => {
                 let _ = app.emit(
                     "publish-error",
                     PublishError {
-                        error: format!("CloudFront invalidation failed: {}", e),
+                        error: error(ErrorCode::InvalidationFailed, &[("error", &e)]),
                         file: "".to_string(),
                     },
                 );
-                return Err(format!("CloudFront invalidation failed: {}", e));
+                return Err(error(ErrorCode::InvalidationFailed, &[("error", &e)]));
             }
             Err(_) => {
                 let _ = app.emit(
                     "publish-error",
                     PublishError {
-                        error: "CloudFront invalidation timed out after 30s.".to_string(),
+                        error: error(ErrorCode::InvalidationTimedOut, &[("seconds", &INVALIDATION_TIMEOUT_SECS)]),
                         file: "".to_string(),
                     },
                 );
-                return Err("CloudFront invalidation timed out after 30s.".to_string());
+                return Err(error(ErrorCode::InvalidationTimedOut, &[("seconds", &INVALIDATION_TIMEOUT_SECS)]));
+            }
+        };
+
+        // Optional warm-up: fetch the entry points and first thumbnails through the
+        // distribution so the first visitor doesn't hit a cold cache
+        let workspace_root = stamps.as_ref().map(|(root, _)| root);
+        if let (true, Some(root), Some(invalidation_id)) = (settings.cache_warm_up, workspace_root, invalidation_id) {
+            if !settings.site_url.trim().is_empty() {
+                let _ = app.emit(
+                    "publish-progress",
+                    PublishProgress { current: 0, total: 0, file: "".to_string(), action: "warm".to_string() },
+                );
+                if wait_for_invalidation(&cf_client, &dist_id, &invalidation_id).await {
+                    let specs = read_json(&root.join("galleries.json"))
+                        .map(|galleries_json| build_thumbnail_specs(root, &galleries_json, &s3_root))
+                        .unwrap_or_default();
+                    let per_gallery = settings.cache_warm_thumbnails.unwrap_or(DEFAULT_WARM_THUMBNAILS) as usize;
+                    match warm_up_urls(&settings.site_url, &s3_root, &specs, per_gallery) {
+                        Ok(urls) => {
+                            let warmed = warm_cache(&urls, |current, total, url| {
+                                let _ = app.emit(
+                                    "publish-progress",
+                                    PublishProgress {
+                                        current,
+                                        total,
+                                        file: url.to_string(),
+                                        action: "warm".to_string(),
+                                    },
+                                );
+                            })
+                            .await;
+                            eprintln!("[publish] Warmed {} of {} URLs", warmed, urls.len());
+                        }
+                        Err(e) => eprintln!("[publish] Cache warm-up skipped: {}", e),
+                    }
+                }
             }
         }
     }
 
-    let _ = app.emit("publish-complete", PublishResult {
+    let result = PublishResult {
         uploaded,
         deleted,
         unchanged: plan.unchanged,
-    });
+    };
+    let _ = app.emit("publish-complete", result.clone());
+    let data = serde_json::json!({ "plan": plan_json, "result": result });
+    if let Err(e) = run_hook(&app, PluginHook::PostPublish, workspace_root.as_deref(), data).await {
+        eprintln!("[plugins] {}", e);
+    }
 
     // Clean up plan
     {
         let state = app.state::<Mutex<PublishState>>();
         let mut state = state.lock().map_err(|e| e.to_string())?;
         state.plans.remove(&plan_id);
+        state.stamps.remove(&plan_id);
+        state.scopes.remove(&plan_id);
+        state.snapshots.remove(&plan_id);
         state.cancelled.remove(&plan_id);
     }
 
     Ok(())
 }
 
+/// Upload one planned file. The outer error is a local read failure; the inner one an S3 error.
+pub(crate) async fn upload_file(
+    client: &aws_sdk_s3::Client,
+    bucket: &str,
+    file: &SyncFile,
+) -> Result<Result<(), String>, String> {
+    let body = ByteStream::from_path(&file.local_path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", file.local_path, e))?;
+    let result = client
+        .put_object()
+        .bucket(bucket)
+        .key(&file.s3_key)
+        .content_type(&file.content_type)
+        .body(body)
+        .send()
+        .await;
+    Ok(result.map(|_| ()).map_err(|e| format!("{}", e)))
+}
+
+/// Audit a previewed plan's deletes against the managed area it was previewed with and the one
+/// the current settings define.
+fn audit_plan(app: &tauri::AppHandle, plan_id: &str, settings: &AppSettings) -> Result<ManagedKeyAudit, String> {
+    let to_delete = {
+        let state = app.state::<Mutex<PublishState>>();
+        let state = state.lock().map_err(|e| e.to_string())?;
+        state.plans.get(plan_id).ok_or_else(|| error(ErrorCode::PlanNotFound, &[]))?.to_delete.clone()
+    };
+    audit_plan_deletes(app, plan_id, settings, &to_delete)
+}
+
+/// Audit `to_delete` (the plan's own or its mirror's) against the plan's preview-time scope and
+/// the current settings.
+fn audit_plan_deletes(
+    app: &tauri::AppHandle,
+    plan_id: &str,
+    settings: &AppSettings,
+    to_delete: &[String],
+) -> Result<ManagedKeyAudit, String> {
+    let execute_root = normalize_s3_prefix(&settings.s3_prefix)?;
+    let execute_area = ManagedArea::from_settings(settings)?;
+    let state = app.state::<Mutex<PublishState>>();
+    let state = state.lock().map_err(|e| e.to_string())?;
+    let (preview_root, preview_area) = state.scopes.get(plan_id).ok_or_else(|| error(ErrorCode::PlanNotFound, &[]))?;
+    Ok(audit_deletes((preview_root, preview_area), (&execute_root, &execute_area), to_delete))
+}
+
+/// Check a previewed plan before executing it: whether every planned delete lies in the managed
+/// area, and whether the S3 prefix or managed paths changed since the preview.
+/// `publish_execute` refuses to run when this audit fails.
+#[tauri::command]
+pub async fn audit_publish_plan(app: tauri::AppHandle, plan_id: String) -> Result<ManagedKeyAudit, String> {
+    let settings = read_settings(&app)?;
+    audit_plan(&app, &plan_id, &settings)
+}
+
 #[tauri::command]
 pub async fn publish_cancel(app: tauri::AppHandle, plan_id: String) -> Result<(), String> {
     let state = app.state::<Mutex<PublishState>>();
@@ -1071,17 +1796,8 @@ mod tests {
 
     #[test]
     fn test_managed_area_safety_check() {
-        // Managed areas: galleries/, afterglow/, index.html
-        let s3_root = "";
-        let galleries_prefix = format!("{}galleries/", s3_root);
-        let afterglow_prefix = format!("{}afterglow/", s3_root);
-        let index_key = format!("{}index.html", s3_root);
-
-        let is_managed = |key: &str| -> bool {
-            key.starts_with(&galleries_prefix)
-                || key.starts_with(&afterglow_prefix)
-                || key == index_key.as_str()
-        };
+        let area = ManagedArea::default();
+        let is_managed = |key: &str| -> bool { area.contains("", key) };
 
         assert!(is_managed("galleries/coastal-sunset/01.jpg"));
         assert!(is_managed("galleries/galleries.json"));
@@ -1094,16 +1810,8 @@ mod tests {
 
     #[test]
     fn test_managed_area_safety_check_with_s3_root() {
-        let s3_root = "my-site/";
-        let galleries_prefix = format!("{}galleries/", s3_root);
-        let afterglow_prefix = format!("{}afterglow/", s3_root);
-        let index_key = format!("{}index.html", s3_root);
-
-        let is_managed = |key: &str| -> bool {
-            key.starts_with(&galleries_prefix)
-                || key.starts_with(&afterglow_prefix)
-                || key == index_key.as_str()
-        };
+        let area = ManagedArea::default();
+        let is_managed = |key: &str| -> bool { area.contains("my-site/", key) };
 
         assert!(is_managed("my-site/galleries/photo.jpg"));
         assert!(is_managed("my-site/afterglow/css/styles.css"));
@@ -1137,14 +1845,121 @@ mod tests {
                 content_type: "image/jpeg".to_string(),
             }],
             to_delete: vec!["galleries/old.jpg".to_string()],
+            delete_categories: DeleteCategories::default(),
             unchanged: 5,
             total_files: 7,
+            delete_confirmation: None,
+            budget_warning: None,
+            mirror: None,
+            image_warnings: Vec::new(),
+            template: Default::default(),
+            simulated_at: None,
         };
         let json = serde_json::to_string(&plan).unwrap();
         assert!(json.contains("planId"));
         assert!(json.contains("toUpload"));
         assert!(json.contains("toDelete"));
         assert!(json.contains("totalFiles"));
+        assert!(!json.contains("deleteConfirmation"));
+    }
+
+    #[test]
+    fn test_categorize_deletes() {
+        let local: Vec<String> = ["site/galleries/sunset/01.jpg", "site/galleries/sunset/.thumbs/01.webp", "site/index.html"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        let to_delete: Vec<String> = [
+            "site/galleries/sunset/02.jpg",
+            "site/galleries/sunset/.thumbs/02.webp",
+            "site/galleries/sunset/notes.txt",
+            "site/galleries/old/01.jpg",
+            "site/galleries/old/gallery-details.json",
+            "site/galleries/.search/old.json",
+            "site/galleries/.search/sunset.json",
+            "site/robots.txt",
+        ]
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+        let categories = categorize_deletes(&to_delete, local.iter(), "site/");
+        assert_eq!(categories.removed_photos, vec!["site/galleries/sunset/02.jpg"]);
+        assert_eq!(
+            categories.removed_galleries,
+            vec!["site/galleries/.search/old.json", "site/galleries/old/01.jpg", "site/galleries/old/gallery-details.json"]
+        );
+        assert_eq!(categories.stale_thumbnails, vec!["site/galleries/sunset/.thumbs/02.webp"]);
+        assert_eq!(
+            categories.unknown,
+            vec!["site/galleries/.search/sunset.json", "site/galleries/sunset/notes.txt", "site/robots.txt"]
+        );
+        assert_eq!(categories.risky_count(), 6);
+    }
+
+    #[test]
+    fn test_nested_site_roots() {
+        let keys = [
+            "site-manifest.json".to_string(),
+            "galleries/old/site-manifest.json".to_string(),
+            "blog/site-manifest.json".to_string(),
+            "galleries/a/01.jpg".to_string(),
+        ];
+        assert_eq!(nested_site_roots("", &ManagedArea::default(), keys.iter()), vec!["galleries/old/"]);
+        assert!(nested_site_roots("blog/", &ManagedArea::default(), keys.iter()).is_empty());
+    }
+
+    #[test]
+    fn test_check_configured_targets() {
+        let site = |s3_prefix: &str, previous: Option<(&str, &str)>| AppSettings {
+            bucket: "photos".to_string(),
+            region: "us-east-1".to_string(),
+            s3_prefix: s3_prefix.to_string(),
+            previous_target: previous.map(|(bucket, s3_prefix)| crate::settings::PreviousTarget {
+                bucket: bucket.to_string(),
+                region: "us-east-1".to_string(),
+                s3_prefix: s3_prefix.to_string(),
+            }),
+            ..Default::default()
+        };
+        let check = |settings: &AppSettings| {
+            check_configured_targets(settings, "photos", &normalize_s3_prefix(&settings.s3_prefix).unwrap())
+        };
+        assert!(check(&site("", None)).is_ok());
+        assert!(check(&site("", Some(("photos", "blog/")))).is_ok());
+        assert!(check(&site("", Some(("archive", "galleries/old/")))).is_ok());
+        // The old site inside the new one's galleries/, or the new one inside the old one's
+        assert!(check(&site("", Some(("photos", "galleries/old/")))).unwrap_err().contains("previous location galleries/old/"));
+        assert!(check(&site("site/galleries/v2/", Some(("photos", "site/")))).unwrap_err().contains("Clean up the old location"));
+
+        let mirrored = AppSettings {
+            mirror: crate::mirror::MirrorTarget { bucket: "photos".to_string(), region: "eu-west-1".to_string(), endpoint_url: String::new() },
+            ..site("", None)
+        };
+        assert!(check(&mirrored).unwrap_err().contains("different bucket"));
+        let archived = AppSettings { archive_prefix: "galleries/".to_string(), ..site("", None) };
+        assert!(check(&archived).unwrap_err().contains("Archive prefix"));
+    }
+
+    #[test]
+    fn test_enclosing_site_roots() {
+        assert!(enclosing_site_roots("").is_empty());
+        assert!(enclosing_site_roots("photos/").is_empty());
+        assert_eq!(enclosing_site_roots("galleries/x/"), vec![""]);
+        assert_eq!(enclosing_site_roots("a/afterglow/b/"), vec!["a/"]);
+    }
+
+    #[test]
+    fn test_delete_confirmation_token() {
+        // Within limits
+        assert_eq!(delete_confirmation_token(0, 0, 100, 0.5), None);
+        assert_eq!(delete_confirmation_token(100, 1000, 100, 0.5), None);
+        // Small deletes never trip the fraction check
+        assert_eq!(delete_confirmation_token(5, 6, 100, 0.5), None);
+        // Over the count
+        assert_eq!(delete_confirmation_token(101, 1000, 100, 0.5), Some("DELETE 101".to_string()));
+        // Over the fraction (e.g. a typo'd prefix leaving most of the site unmatched)
+        assert_eq!(delete_confirmation_token(40, 60, 100, 0.5), Some("DELETE 40".to_string()));
+        assert_eq!(delete_confirmation_token(30, 60, 100, 0.5), None);
     }
 
     // --- collect_referenced_files tests ---
@@ -1169,6 +1984,29 @@ mod tests {
         fs::write(&path, b"\xff").unwrap();
     }
 
+    #[test]
+    fn test_rewrite_gallery_details_keeps_custom_fields() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        create_file(
+            root,
+            "sunset/gallery-details.json",
+            r#"{"slug":"sunset","customFields":{"client":"Riverside FC","round":3},"photos":[{"thumbnail":"01.jpg","full":"01.jpg"}]}"#,
+        );
+        let thumbs = HashMap::from([(root.join("sunset/01.jpg"), ".thumbs/01.webp".to_string())]);
+        let bytes = rewrite_gallery_details_json_for_publish(
+            &root.join("sunset/gallery-details.json"),
+            root,
+            "sunset",
+            &thumbs,
+            &CommentsSettings::default(),
+        )
+        .unwrap();
+        let published: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(published["customFields"], serde_json::json!({ "client": "Riverside FC", "round": 3 }));
+        assert_eq!(published["photos"][0]["thumbnail"], ".thumbs/01.webp");
+    }
+
     #[test]
     fn test_collect_referenced_files_basic() {
         let tmp = TempDir::new().unwrap();
@@ -1403,13 +2241,15 @@ mod tests {
     #[test]
     fn test_collect_website_files_bucket_root() {
         // Files are embedded at compile time; just verify s3 keys and that paths exist after collection.
-        let files = collect_website_files("").unwrap();
-        assert_eq!(files.len(), 3);
+        let files = collect_website_files("", &SiteHtmlOptions::default()).unwrap();
+        assert_eq!(files.len(), 5);
 
         let s3_keys: Vec<&str> = files.iter().map(|(_, k)| k.as_str()).collect();
         assert!(s3_keys.contains(&"index.html"));
         assert!(s3_keys.contains(&"afterglow/css/styles.css"));
         assert!(s3_keys.contains(&"afterglow/js/app.js"));
+        assert!(s3_keys.contains(&"favicon.ico"));
+        assert!(s3_keys.contains(&"favicon.png"));
 
         for (path, _) in &files {
             assert!(path.exists(), "temp file should exist: {}", path.display());
@@ -1418,13 +2258,34 @@ mod tests {
 
     #[test]
     fn test_collect_website_files_with_s3_root() {
-        let files = collect_website_files("my-site/").unwrap();
-        assert_eq!(files.len(), 3);
+        let files = collect_website_files("my-site/", &SiteHtmlOptions::default()).unwrap();
+        assert_eq!(files.len(), 5);
 
         let s3_keys: Vec<&str> = files.iter().map(|(_, k)| k.as_str()).collect();
         assert!(s3_keys.contains(&"my-site/index.html"));
         assert!(s3_keys.contains(&"my-site/afterglow/css/styles.css"));
         assert!(s3_keys.contains(&"my-site/afterglow/js/app.js"));
+        assert!(s3_keys.contains(&"my-site/favicon.ico"));
+        assert!(s3_keys.contains(&"my-site/favicon.png"));
+    }
+
+    #[test]
+    fn test_collect_website_files_processes_index_html() {
+        let options = SiteHtmlOptions {
+            language: "en-AU".to_string(),
+            description: "Photos".to_string(),
+            accessibility: true,
+            content_security_policy: true,
+            csp_extra_sources: Vec::new(),
+        };
+        let files = collect_website_files("", &options).unwrap();
+        let (path, _) = files.iter().find(|(_, k)| k == "index.html").unwrap();
+        let html = fs::read_to_string(path).unwrap();
+        assert!(html.contains("<html lang=\"en-AU\">"));
+        assert!(html.contains("<meta name=\"description\" content=\"Photos\">"));
+        assert!(html.contains("class=\"skip-link\""));
+        assert!(html.contains("<main id=\"app\" tabindex=\"-1\""));
+        assert!(html.contains("<meta http-equiv=\"Content-Security-Policy\""));
     }
 
     #[test]
@@ -1437,6 +2298,102 @@ mod tests {
         assert!(!html.contains("src=\"js/"), "index.html should not have old js/ reference");
     }
 
+    #[test]
+    fn test_search_index_includes_related_to() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        create_file(
+            root,
+            "galleries.json",
+            r#"{"schemaVersion":1,"galleries":[{"name":"A","slug":"a","date":"","cover":""}]}"#,
+        );
+        create_file(
+            root,
+            "a/gallery-details.json",
+            r#"{"name":"A","slug":"a","date":"","description":"","photos":[
+                {"thumbnail":"edit.jpg","full":"edit.jpg","alt":"","relatedTo":"sooc.jpg"},
+                {"thumbnail":"other.jpg","full":"other.jpg","alt":"","astronomy":{"moonPhase":"Full Moon"},"externalUrl":"https://prints.example.com/other"}
+            ]}"#,
+        );
+
+        let generated = generate_search_index(root, &HashMap::new()).unwrap();
+        let shard: serde_json::Value = serde_json::from_slice(&generated.shards[0].1).unwrap();
+        assert_eq!(shard["photos"][0]["relatedTo"], "sooc.jpg");
+        assert!(shard["photos"][1].get("relatedTo").is_none());
+        assert!(shard["photos"][0].get("astronomy").is_none());
+        assert_eq!(shard["photos"][1]["astronomy"]["moonPhase"], "Full Moon");
+        assert!(shard["photos"][0].get("externalUrl").is_none());
+        assert_eq!(shard["photos"][1]["externalUrl"], "https://prints.example.com/other");
+    }
+
+    #[test]
+    fn test_search_index_includes_photo_size() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        create_file(root, "galleries.json", r#"{"schemaVersion":1,"galleries":[{"name":"A","slug":"a"}]}"#);
+        create_file(
+            root,
+            "a/gallery-details.json",
+            r#"{"name":"A","slug":"a","photos":[
+                {"thumbnail":"tall.png","full":"tall.png","alt":""},
+                {"thumbnail":"broken.jpg","full":"broken.jpg","alt":""}
+            ]}"#,
+        );
+        image::RgbImage::new(20, 30).save(root.join("a/tall.png")).unwrap();
+        create_file(root, "a/broken.jpg", "not an image");
+
+        let generated = generate_search_index(root, &HashMap::new()).unwrap();
+        let shard: serde_json::Value = serde_json::from_slice(&generated.shards[0].1).unwrap();
+        let tall = &shard["photos"][0];
+        assert_eq!((tall["width"].as_u64(), tall["height"].as_u64()), (Some(20), Some(30)));
+        assert_eq!(tall["aspectRatio"], 0.6667);
+        assert_eq!(tall["orientation"], "portrait");
+        assert!(shard["photos"][1].get("width").is_none(), "unreadable images have no size");
+        assert!(shard["photos"][1].get("orientation").is_none());
+    }
+
+    #[test]
+    fn test_search_index_split_into_gallery_shards() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        create_file(
+            root,
+            "galleries.json",
+            r#"{"schemaVersion":1,"galleries":[
+                {"name":"A","slug":"a","date":"","cover":""},
+                {"name":"Empty","slug":"empty","date":"","cover":""}
+            ]}"#,
+        );
+        create_file(
+            root,
+            "a/gallery-details.json",
+            r#"{"name":"A","slug":"a","date":"","description":"Desc","photos":[
+                {"thumbnail":"1.jpg","full":"1.jpg","alt":"","tags":["Goal","goal"]},
+                {"thumbnail":"2.jpg","full":"2.jpg","alt":"","tags":["Save"]}
+            ]}"#,
+        );
+        create_file(root, "empty/gallery-details.json", r#"{"name":"Empty","slug":"empty","photos":[]}"#);
+
+        let generated = generate_search_index(root, &HashMap::new()).unwrap();
+        let index: serde_json::Value = serde_json::from_slice(&generated.index).unwrap();
+        assert_eq!(index["version"], 2);
+        assert!(index.get("photos").is_none(), "photos live in shards only");
+        assert_eq!(index["galleries"].as_array().unwrap().len(), 2);
+        assert_eq!(index["galleries"][0]["description"], "Desc");
+        let shards = index["shards"].as_array().unwrap();
+        assert_eq!(shards.len(), 1, "galleries without photos get no shard");
+        assert_eq!(shards[0]["gallerySlug"], "a");
+        assert_eq!(shards[0]["path"], ".search/a.json");
+        assert_eq!(shards[0]["photoCount"], 2);
+        assert_eq!(shards[0]["tags"], serde_json::json!(["goal", "save"]));
+
+        assert_eq!(generated.shards.len(), 1);
+        assert_eq!(generated.shards[0].0, ".search/a.json");
+        let shard: serde_json::Value = serde_json::from_slice(&generated.shards[0].1).unwrap();
+        assert_eq!(shard["gallerySlug"], "a");
+        assert_eq!(shard["photos"].as_array().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_collect_referenced_files_no_galleries_json() {
         let tmp = TempDir::new().unwrap();
@@ -1446,4 +2403,42 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("galleries.json not found"));
     }
+
+    #[test]
+    fn test_stamp_published_json() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("src");
+        let out = tmp.path().join("out");
+        create_file(&src, "galleries.json", r#"{"galleries":[]}"#);
+        create_file(&src, "photo.jpg", "jpeg");
+        let mut local_map: HashMap<String, (PathBuf, String)> = HashMap::from([
+            ("site/galleries/galleries.json".to_string(), (src.join("galleries.json"), String::new())),
+            ("site/galleries/a/photo.jpg".to_string(), (src.join("photo.jpg"), "m".to_string())),
+        ]);
+
+        let stamps = stamp_published_json(&mut local_map, "site/", &StampMap::new(), &out).unwrap();
+        assert_eq!(stamps.len(), 1);
+        let stamp = &stamps["galleries/galleries.json"];
+        assert_eq!(stamp.generation, 1);
+        let (path, md5) = &local_map["site/galleries/galleries.json"];
+        assert_eq!(path, &out.join("galleries/galleries.json"));
+        assert_eq!(md5, &compute_md5(path).unwrap());
+        let value: serde_json::Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        assert_eq!(value["publishStamp"]["hash"], stamp.hash.as_str());
+        assert_eq!(local_map["site/galleries/a/photo.jpg"].1, "m");
+
+        // Unchanged content keeps its generation; changed content bumps it
+        let mut again = HashMap::from([(
+            "site/galleries/galleries.json".to_string(),
+            (src.join("galleries.json"), String::new()),
+        )]);
+        assert_eq!(stamp_published_json(&mut again, "site/", &stamps, &out).unwrap(), stamps);
+        create_file(&src, "galleries.json", r#"{"galleries":[{"slug":"a"}]}"#);
+        let mut changed = HashMap::from([(
+            "site/galleries/galleries.json".to_string(),
+            (src.join("galleries.json"), String::new()),
+        )]);
+        let bumped = stamp_published_json(&mut changed, "site/", &stamps, &out).unwrap();
+        assert_eq!(bumped["galleries/galleries.json"].generation, 2);
+    }
 }
@@ -0,0 +1,75 @@
+use crate::i18n::{error, ErrorCode};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+/// Workspaces opened read-only. Commands that change a workspace check it before writing,
+/// passing it on to the shared writers that need it (`details_store`, the journal).
+#[derive(Default)]
+pub struct ReadOnlyState(Mutex<BTreeSet<PathBuf>>);
+
+impl ReadOnlyState {
+    /// Refuse writing `path` when it belongs to a workspace opened read-only.
+    pub(crate) fn ensure_writable(&self, path: &Path) -> Result<(), String> {
+        let roots = self.0.lock().map_err(|e| e.to_string())?;
+        if is_protected(&roots, path) {
+            return Err(error(ErrorCode::WorkspaceReadOnly, &[("path", &path.display())]));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` is inside a workspace opened read-only. `.data` caches (thumbnails,
+/// search index, summaries) still update: they never diverge from the metadata.
+fn is_protected(roots: &BTreeSet<PathBuf>, path: &Path) -> bool {
+    roots
+        .iter()
+        .any(|root| path.starts_with(root) && !path.starts_with(root.join(".data")))
+}
+
+/// `ensure_writable` for commands holding an `AppHandle` rather than the state.
+pub(crate) fn ensure_writable(app: &tauri::AppHandle, path: &Path) -> Result<(), String> {
+    app.state::<ReadOnlyState>().ensure_writable(path)
+}
+
+/// Mark a workspace read-only (or writable again). While read-only, every command that
+/// would change its metadata, images or published site fails, and so do undo/redo.
+#[tauri::command]
+pub async fn set_workspace_read_only(
+    read_only_state: tauri::State<'_, ReadOnlyState>,
+    workspace_path: String,
+    read_only: bool,
+) -> Result<(), String> {
+    let mut roots = read_only_state.0.lock().map_err(|e| e.to_string())?;
+    let root = PathBuf::from(&workspace_path);
+    if read_only {
+        roots.insert(root);
+    } else {
+        roots.remove(&root);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_protected() {
+        let roots: BTreeSet<PathBuf> = [PathBuf::from("/archive/site")].into_iter().collect();
+        assert!(is_protected(&roots, Path::new("/archive/site/galleries.json")));
+        assert!(is_protected(&roots, Path::new("/archive/site/final/01.jpg")));
+        assert!(!is_protected(&roots, Path::new("/archive/site/.data/summary.json")));
+        assert!(!is_protected(&roots, Path::new("/archive/site-2/galleries.json")));
+        assert!(!is_protected(&BTreeSet::new(), Path::new("/archive/site/galleries.json")));
+    }
+
+    #[test]
+    fn test_ensure_writable() {
+        let state = ReadOnlyState::default();
+        state.0.lock().unwrap().insert(PathBuf::from("/archive/site"));
+        assert!(state.ensure_writable(Path::new("/archive/site/galleries.json")).unwrap_err().starts_with("[workspace-read-only]"));
+        assert!(state.ensure_writable(Path::new("/archive/other/galleries.json")).is_ok());
+    }
+}
@@ -0,0 +1,632 @@
+use crate::workspace::{read_galleries, read_gallery_details, str_field};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+/// Default number of search results returned.
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+/// Default number of search-as-you-type suggestions returned.
+const DEFAULT_SUGGEST_LIMIT: usize = 10;
+/// Indexing memory budget. The workspace index is small; one thread is plenty.
+const WRITER_MEMORY_BYTES: usize = 20_000_000;
+
+/// Serializes writers: tantivy allows a single `IndexWriter` per index directory, and the
+/// watcher can trigger reindexing while a search command is rebuilding.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    /// "gallery" or "photo".
+    pub kind: String,
+    pub gallery_slug: String,
+    /// `full` filename for photo hits; empty for gallery hits.
+    pub filename: String,
+    /// Gallery name or photo alt text.
+    pub title: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSuggestion {
+    /// "tag", "gallery" or "location".
+    pub kind: String,
+    pub text: String,
+    /// Number of galleries and photos carrying this value.
+    pub count: usize,
+}
+
+/// Fields of the workspace search index.
+#[derive(Clone, Copy)]
+struct SearchFields {
+    kind: Field,
+    slug: Field,
+    filename: Field,
+    title: Field,
+    body: Field,
+    tags: Field,
+    location: Field,
+    /// Untokenized `{lowercase}\0{original}` keys used for suggestions.
+    tag_key: Field,
+    name_key: Field,
+    location_key: Field,
+    /// The `galleries.json` entry a gallery doc was built from, compared on list changes.
+    entry: Field,
+}
+
+fn build_schema() -> (Schema, SearchFields) {
+    let mut builder = Schema::builder();
+    let fields = SearchFields {
+        kind: builder.add_text_field("kind", STRING | STORED),
+        slug: builder.add_text_field("slug", STRING | STORED),
+        filename: builder.add_text_field("filename", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        body: builder.add_text_field("body", TEXT),
+        tags: builder.add_text_field("tags", TEXT | STORED),
+        location: builder.add_text_field("location", TEXT),
+        tag_key: builder.add_text_field("tag_key", STRING),
+        name_key: builder.add_text_field("name_key", STRING),
+        location_key: builder.add_text_field("location_key", STRING),
+        entry: builder.add_text_field("entry", STORED),
+    };
+    (builder.build(), fields)
+}
+
+/// `{workspace}/.data/search-index/` — the manager's own full-text index. Not to be
+/// confused with the published `galleries/search-index.json` used by the website.
+fn index_dir(root: &Path) -> PathBuf {
+    root.join(".data").join("search-index")
+}
+
+/// Open the workspace index, creating it when missing. An index written with a different
+/// schema (older app version) is discarded and recreated; callers rebuild when it is empty.
+fn open_index(root: &Path) -> Result<(Index, SearchFields), String> {
+    let dir = index_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let (schema, fields) = build_schema();
+    let open = |dir: &Path| {
+        let mmap = tantivy::directory::MmapDirectory::open(dir).map_err(|e| e.to_string())?;
+        Index::open_or_create(mmap, schema.clone()).map_err(|e| e.to_string())
+    };
+    let index = match open(&dir) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("[search] Recreating search index: {}", e);
+            fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            open(&dir)?
+        }
+    };
+    Ok((index, fields))
+}
+
+fn writer(index: &Index) -> Result<IndexWriter, String> {
+    index
+        .writer_with_num_threads(1, WRITER_MEMORY_BYTES)
+        .map_err(|e| format!("Failed to open search index writer: {}", e))
+}
+
+/// Suggestion key: lowercase first so prefix scans are case-insensitive, original casing kept
+/// after the separator for display.
+fn suggestion_key(value: &str) -> String {
+    format!("{}\0{}", value.to_lowercase(), value)
+}
+
+fn string_array(value: &serde_json::Value, key: &str) -> Vec<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
+}
+
+/// Add the document for a `galleries.json` entry plus one document per photo in its details.
+fn add_gallery_docs(
+    writer: &IndexWriter,
+    fields: SearchFields,
+    root: &Path,
+    gallery: &serde_json::Value,
+) -> Result<(), String> {
+    let slug = str_field(gallery, "slug");
+    let details = read_gallery_details(root, &slug).ok().flatten();
+    let description = details.as_ref().map(|d| str_field(d, "description")).unwrap_or_default();
+
+    let name = str_field(gallery, "name");
+    let location = str_field(gallery, "location");
+    // Gallery name and location are added to photo docs too, so suggestion counts reflect
+    // how many photos a completion would find.
+    let add_gallery_keys = |doc: &mut TantivyDocument| {
+        if !name.is_empty() {
+            doc.add_text(fields.name_key, suggestion_key(&name));
+        }
+        if !location.is_empty() {
+            doc.add_text(fields.location, &location);
+            doc.add_text(fields.location_key, suggestion_key(&location));
+        }
+    };
+    let add_tags = |doc: &mut TantivyDocument, item: &serde_json::Value| {
+        for tag in string_array(item, "tags") {
+            doc.add_text(fields.tag_key, suggestion_key(&tag));
+            doc.add_text(fields.tags, tag);
+        }
+    };
+
+    let mut gallery_doc = doc!(
+        fields.kind => "gallery",
+        fields.slug => slug.as_str(),
+        fields.filename => "",
+        fields.title => name.as_str(),
+        fields.body => description,
+        fields.entry => gallery.to_string(),
+    );
+    add_gallery_keys(&mut gallery_doc);
+    add_tags(&mut gallery_doc, gallery);
+    writer.add_document(gallery_doc).map_err(|e| e.to_string())?;
+
+    let photos = details
+        .as_ref()
+        .and_then(|d| d.get("photos"))
+        .and_then(|p| p.as_array())
+        .cloned()
+        .unwrap_or_default();
+    for photo in &photos {
+        let mut photo_doc = doc!(
+            fields.kind => "photo",
+            fields.slug => slug.as_str(),
+            fields.filename => str_field(photo, "full"),
+            fields.title => str_field(photo, "alt"),
+        );
+        add_gallery_keys(&mut photo_doc);
+        add_tags(&mut photo_doc, photo);
+        writer.add_document(photo_doc).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Rebuild the whole index from `galleries.json` and every `gallery-details.json`.
+pub fn rebuild_index(root: &Path) -> Result<(), String> {
+    let galleries = read_galleries(root)?;
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (index, fields) = open_index(root)?;
+    let mut writer = writer(&index)?;
+    writer.delete_all_documents().map_err(|e| e.to_string())?;
+    for gallery in galleries.iter().filter(|g| !str_field(g, "slug").is_empty()) {
+        add_gallery_docs(&writer, fields, root, gallery)?;
+    }
+    writer.commit().map_err(|e| format!("Failed to commit search index: {}", e))?;
+    Ok(())
+}
+
+/// Replace the documents of a single gallery. Removes them when the gallery is no longer
+/// listed in `galleries.json`. An empty index (e.g. just recreated after a schema change) is
+/// rebuilt in full instead, so it never ends up holding a single gallery.
+pub fn reindex_gallery(root: &Path, slug: &str) -> Result<(), String> {
+    let galleries = read_galleries(root)?;
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (index, fields) = open_index(root)?;
+    let empty = open_reader(&index)?.searcher().num_docs() == 0;
+    let mut writer = writer(&index)?;
+    if empty {
+        for gallery in galleries.iter().filter(|g| !str_field(g, "slug").is_empty()) {
+            add_gallery_docs(&writer, fields, root, gallery)?;
+        }
+    } else {
+        writer.delete_term(Term::from_field_text(fields.slug, slug));
+        if let Some(gallery) = galleries.iter().find(|g| str_field(g, "slug") == slug) {
+            add_gallery_docs(&writer, fields, root, gallery)?;
+        }
+    }
+    writer.commit().map_err(|e| format!("Failed to commit search index: {}", e))?;
+    Ok(())
+}
+
+/// `galleries.json` entry of every indexed gallery, by slug.
+fn indexed_entries(searcher: &tantivy::Searcher, fields: SearchFields) -> Result<HashMap<String, String>, String> {
+    let query = TermQuery::new(Term::from_field_text(fields.kind, "gallery"), IndexRecordOption::Basic);
+    let limit = (searcher.num_docs() as usize).max(1);
+    let top = searcher.search(&query, &TopDocs::with_limit(limit)).map_err(|e| e.to_string())?;
+    let mut entries = HashMap::new();
+    for (_, address) in top {
+        let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+        let text = |field: Field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        entries.insert(text(fields.slug), text(fields.entry));
+    }
+    Ok(entries)
+}
+
+/// Bring the index in line with `galleries.json`: reindex the galleries that were added,
+/// removed or whose entry changed, leaving the rest alone. Returns the reindexed slugs.
+/// An empty index is filled in full, as in `reindex_gallery`.
+fn sync_gallery_list(root: &Path) -> Result<Vec<String>, String> {
+    let galleries = read_galleries(root)?;
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (index, fields) = open_index(root)?;
+    let mut indexed = indexed_entries(&open_reader(&index)?.searcher(), fields)?;
+    let mut writer = writer(&index)?;
+    let mut changed = Vec::new();
+    for gallery in galleries.iter().filter(|g| !str_field(g, "slug").is_empty()) {
+        let slug = str_field(gallery, "slug");
+        let entry = gallery.to_string();
+        if indexed.remove(&slug).is_some_and(|indexed| indexed == entry) || changed.contains(&slug) {
+            continue;
+        }
+        writer.delete_term(Term::from_field_text(fields.slug, &slug));
+        add_gallery_docs(&writer, fields, root, gallery)?;
+        changed.push(slug);
+    }
+    for slug in indexed.into_keys() {
+        writer.delete_term(Term::from_field_text(fields.slug, &slug));
+        changed.push(slug);
+    }
+    if !changed.is_empty() {
+        writer.commit().map_err(|e| format!("Failed to commit search index: {}", e))?;
+    }
+    Ok(changed)
+}
+
+/// Keep an existing index current after watcher events: `galleries.json` changes reindex
+/// the galleries added to, removed from or edited in the list, `{slug}/gallery-details.json`
+/// changes reindex that gallery. Does nothing when the workspace has never been searched
+/// (the index is built lazily on first search).
+pub(crate) fn apply_fs_changes(root: &Path, paths: &[PathBuf]) {
+    if !index_dir(root).exists() {
+        return;
+    }
+    let mut list_changed = false;
+    let mut slugs: Vec<String> = Vec::new();
+    for path in paths {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        match parts.as_slice() {
+            [name] if name == "galleries.json" => list_changed = true,
+            [slug, name]
+                if name == "gallery-details.json" && !slug.starts_with('.') && !slugs.contains(slug) =>
+            {
+                slugs.push(slug.clone());
+            }
+            _ => {}
+        }
+    }
+    let result = (|| {
+        if list_changed {
+            let synced = sync_gallery_list(root)?;
+            slugs.retain(|slug| !synced.contains(slug));
+        }
+        slugs.iter().try_for_each(|slug| reindex_gallery(root, slug))
+    })();
+    if let Err(e) = result {
+        eprintln!("[search] Failed to update search index: {}", e);
+    }
+}
+
+/// Split a query into lowercase alphanumeric terms, matching tantivy's default tokenizer.
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Allowed edit distance for a query term: exact for very short terms, otherwise one typo.
+fn fuzzy_distance(term: &str) -> u8 {
+    if term.chars().count() <= 3 {
+        0
+    } else {
+        1
+    }
+}
+
+/// Every query term must match at least one field, exactly (boosted), as a prefix, or
+/// within the fuzzy edit distance.
+fn build_query(fields: SearchFields, terms: &[String]) -> BooleanQuery {
+    let mut musts: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for text in terms {
+        let mut shoulds: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for field in [fields.title, fields.body, fields.tags, fields.location] {
+            let term = Term::from_field_text(field, text);
+            let exact = TermQuery::new(term.clone(), IndexRecordOption::WithFreqs);
+            shoulds.push((Occur::Should, Box::new(BoostQuery::new(Box::new(exact), 2.0))));
+            let distance = fuzzy_distance(text);
+            shoulds.push((Occur::Should, Box::new(FuzzyTermQuery::new_prefix(term.clone(), distance, true))));
+            if distance > 0 {
+                shoulds.push((Occur::Should, Box::new(FuzzyTermQuery::new(term, distance, true))));
+            }
+        }
+        musts.push((Occur::Must, Box::new(BooleanQuery::new(shoulds))));
+    }
+    BooleanQuery::new(musts)
+}
+
+fn open_reader(index: &Index) -> Result<tantivy::IndexReader, String> {
+    index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()
+        .map_err(|e: tantivy::TantivyError| e.to_string())
+}
+
+/// Open the index for reading, building it first when it is empty.
+fn open_populated(root: &Path) -> Result<(Index, SearchFields, tantivy::Searcher), String> {
+    let (index, fields) = {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        open_index(root)?
+    };
+    let mut reader = open_reader(&index)?;
+    if reader.searcher().num_docs() == 0 {
+        rebuild_index(root)?;
+        reader = open_reader(&index)?;
+    }
+    let searcher = reader.searcher();
+    Ok((index, fields, searcher))
+}
+
+/// Search the workspace index. Builds the index first when it is empty.
+pub fn search(root: &Path, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (_index, fields, searcher) = open_populated(root)?;
+    let top = searcher
+        .search(&build_query(fields, &terms), &TopDocs::with_limit(limit))
+        .map_err(|e| format!("Search failed: {}", e))?;
+    let text = |doc: &TantivyDocument, field: Field| {
+        doc.get_first(field).and_then(|v| v.as_str()).unwrap_or("").to_string()
+    };
+    top.into_iter()
+        .map(|(score, address)| {
+            let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+            Ok(SearchHit {
+                kind: text(&doc, fields.kind),
+                gallery_slug: text(&doc, fields.slug),
+                filename: text(&doc, fields.filename),
+                title: text(&doc, fields.title),
+                score,
+            })
+        })
+        .collect()
+}
+
+/// Completions for `prefix` (case-insensitive, matched against the start of the value) from
+/// tags, gallery names and gallery locations, most frequent first. Casing variants of the
+/// same value are merged and shown in their most common form.
+pub fn suggest(root: &Path, prefix: &str, limit: usize) -> Result<Vec<SearchSuggestion>, String> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (_index, fields, searcher) = open_populated(root)?;
+    let mut suggestions = Vec::new();
+    for (kind, field) in [("tag", fields.tag_key), ("gallery", fields.name_key), ("location", fields.location_key)] {
+        // Candidate keys from every segment's term dictionary.
+        let mut keys: BTreeSet<String> = BTreeSet::new();
+        for segment in searcher.segment_readers() {
+            let inverted = segment.inverted_index(field).map_err(|e| e.to_string())?;
+            let mut stream = inverted.terms().range().ge(prefix.as_bytes()).into_stream().map_err(|e| e.to_string())?;
+            while stream.advance() {
+                if !stream.key().starts_with(prefix.as_bytes()) {
+                    break;
+                }
+                keys.insert(String::from_utf8_lossy(stream.key()).into_owned());
+            }
+        }
+        // Term dictionaries still list deleted documents, so count live matches instead of
+        // using doc_freq. lowercase → (total, (best variant count, best variant text))
+        let mut merged: HashMap<String, (usize, (usize, String))> = HashMap::new();
+        for key in keys {
+            let query = TermQuery::new(Term::from_field_text(field, &key), IndexRecordOption::Basic);
+            let count = searcher.search(&query, &Count).map_err(|e| e.to_string())?;
+            if count == 0 {
+                continue;
+            }
+            let (lower, original) = key.split_once('\0').unwrap_or((key.as_str(), key.as_str()));
+            let entry = merged.entry(lower.to_string()).or_insert((0, (0, String::new())));
+            entry.0 += count;
+            if count > entry.1 .0 {
+                entry.1 = (count, original.to_string());
+            }
+        }
+        suggestions.extend(merged.into_values().map(|(count, (_, text))| SearchSuggestion {
+            kind: kind.to_string(),
+            text,
+            count,
+        }));
+    }
+    suggestions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.text.cmp(&b.text)));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+/// Full-text search over gallery names, descriptions, photo alt text and tags, with prefix
+/// and typo-tolerant (fuzzy) matching. Backed by a tantivy index under `.data/search-index/`
+/// that is built on first use and kept current by the fs watcher.
+#[tauri::command]
+pub async fn search_workspace(
+    workspace_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).max(1);
+    tokio::task::spawn_blocking(move || search(&root, &query, limit))
+        .await
+        .map_err(|e| format!("Search panicked: {}", e))?
+}
+
+/// Search-as-you-type completions (tags, gallery names, locations) ranked by frequency.
+#[tauri::command]
+pub async fn suggest_search_terms(
+    workspace_path: String,
+    prefix: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchSuggestion>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let limit = limit.unwrap_or(DEFAULT_SUGGEST_LIMIT).max(1);
+    tokio::task::spawn_blocking(move || suggest(&root, &prefix, limit))
+        .await
+        .map_err(|e| format!("Suggest panicked: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn workspace() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "galleries.json",
+            r#"{"schemaVersion":1,"galleries":[
+                {"name":"Harbour Sunset","slug":"harbour","date":"","cover":"","tags":["Coast"],"location":"Whitby"},
+                {"name":"Cup Final","slug":"final","date":"","cover":"","location":"Wembley"}
+            ]}"#,
+        );
+        write(
+            root,
+            "harbour/gallery-details.json",
+            r#"{"name":"Harbour Sunset","slug":"harbour","description":"Fishing boats at dusk","photos":[
+                {"thumbnail":"01.jpg","full":"01.jpg","alt":"Lighthouse silhouette","tags":["Lighthouse"]}
+            ]}"#,
+        );
+        write(
+            root,
+            "final/gallery-details.json",
+            r#"{"name":"Cup Final","slug":"final","description":"Under-12s trophy match","photos":[
+                {"thumbnail":"01.jpg","full":"01.jpg","alt":"Goalkeeper diving save","tags":["Wet"]},
+                {"thumbnail":"02.jpg","full":"02.jpg","alt":"Trophy lift","tags":["wet"]},
+                {"thumbnail":"03.jpg","full":"03.jpg","alt":"Celebration","tags":["Wet"]}
+            ]}"#,
+        );
+        tmp
+    }
+
+    fn slugs(hits: &[SearchHit]) -> Vec<(String, String)> {
+        hits.iter().map(|h| (h.gallery_slug.clone(), h.filename.clone())).collect()
+    }
+
+    #[test]
+    fn test_search_exact_prefix_and_fuzzy() {
+        let tmp = workspace();
+        let root = tmp.path();
+        // Exact word in a description
+        let hits = search(root, "boats", 10).unwrap();
+        assert_eq!(slugs(&hits), vec![("harbour".to_string(), String::new())]);
+        // Prefix
+        let hits = search(root, "goalk", 10).unwrap();
+        assert_eq!(slugs(&hits), vec![("final".to_string(), "01.jpg".to_string())]);
+        // One typo
+        let hits = search(root, "lighthuose", 10).unwrap();
+        assert_eq!(hits[0].kind, "photo");
+        assert_eq!(hits[0].gallery_slug, "harbour");
+        // Every term must match
+        assert!(search(root, "boats goalkeeper", 10).unwrap().is_empty());
+        assert!(search(root, "  ", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reindex_gallery_and_fs_changes() {
+        let tmp = workspace();
+        let root = tmp.path();
+        assert!(search(root, "penalty", 10).unwrap().is_empty());
+
+        write(
+            root,
+            "final/gallery-details.json",
+            r#"{"name":"Cup Final","slug":"final","description":"Won on penalty kicks","photos":[]}"#,
+        );
+        apply_fs_changes(root, &[root.join("final/gallery-details.json")]);
+        let hits = search(root, "penalty", 10).unwrap();
+        assert_eq!(slugs(&hits), vec![("final".to_string(), String::new())]);
+        // The removed photo is gone
+        assert!(search(root, "goalkeeper", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gallery_list_changes_reindex_only_the_affected_galleries() {
+        let tmp = workspace();
+        let root = tmp.path();
+        assert_eq!(search(root, "lighthouse", 10).unwrap().len(), 1);
+        assert!(sync_gallery_list(root).unwrap().is_empty());
+
+        // A details change the watcher hasn't reported yet stays out of the index, showing
+        // that the untouched gallery was not rebuilt.
+        write(
+            root,
+            "harbour/gallery-details.json",
+            r#"{"name":"Harbour Sunset","slug":"harbour","description":"Moored yachts","photos":[]}"#,
+        );
+        write(root, "pier/gallery-details.json", r#"{"name":"Pier","slug":"pier","description":"Rusty pilings","photos":[]}"#);
+        write(
+            root,
+            "galleries.json",
+            r#"{"schemaVersion":1,"galleries":[
+                {"name":"Harbour Sunset","slug":"harbour","date":"","cover":"","tags":["Coast"],"location":"Whitby"},
+                {"name":"Pier","slug":"pier","date":"","cover":"","location":"Saltburn"}
+            ]}"#,
+        );
+        apply_fs_changes(root, &[root.join("galleries.json")]);
+        assert_eq!(slugs(&search(root, "pilings", 10).unwrap()), vec![("pier".to_string(), String::new())]);
+        assert!(search(root, "goalkeeper", 10).unwrap().is_empty());
+        assert_eq!(search(root, "lighthouse", 10).unwrap().len(), 1);
+        assert!(search(root, "yachts", 10).unwrap().is_empty());
+
+        // Editing an entry reindexes that gallery.
+        write(
+            root,
+            "galleries.json",
+            r#"{"schemaVersion":1,"galleries":[
+                {"name":"Harbour at Dusk","slug":"harbour","date":"","cover":"","tags":["Coast"],"location":"Whitby"},
+                {"name":"Pier","slug":"pier","date":"","cover":"","location":"Saltburn"}
+            ]}"#,
+        );
+        apply_fs_changes(root, &[root.join("galleries.json")]);
+        assert_eq!(slugs(&search(root, "yachts", 10).unwrap()), vec![("harbour".to_string(), String::new())]);
+        assert!(search(root, "lighthouse", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_suggest_ranks_by_frequency_and_merges_casing() {
+        let tmp = workspace();
+        let suggestions = suggest(tmp.path(), "W", 10).unwrap();
+        let got: Vec<(&str, &str, usize)> =
+            suggestions.iter().map(|s| (s.kind.as_str(), s.text.as_str(), s.count)).collect();
+        assert_eq!(
+            got,
+            vec![
+                // Gallery doc + 3 photos
+                ("location", "Wembley", 4),
+                ("tag", "Wet", 3),
+                ("location", "Whitby", 2),
+            ]
+        );
+        let names = suggest(tmp.path(), "cup", 10).unwrap();
+        assert_eq!(names[0].kind, "gallery");
+        assert_eq!(names[0].text, "Cup Final");
+        assert!(suggest(tmp.path(), "", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_fs_changes_without_index_is_noop() {
+        let tmp = workspace();
+        apply_fs_changes(tmp.path(), &[tmp.path().join("galleries.json")]);
+        assert!(!index_dir(tmp.path()).exists());
+    }
+}
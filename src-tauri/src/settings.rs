@@ -1,20 +1,71 @@
+use crate::activity::{changed_settings, record_activity};
+use crate::archive::validate_archive_location;
+use crate::auto_publish::{validate_auto_publish, AutoPublishSettings};
+use crate::backups::validate_backup_retention;
+use crate::budget::validate_budget;
+use crate::comments::{validate_comments, CommentsSettings};
+use crate::companion::validate_companion;
+use crate::i18n::{error, set_locale, ErrorCode};
+use crate::managed_area::validate_managed_extra_paths;
+use crate::mirror::{validate_mirror, MirrorTarget};
+use crate::site_template::validate_template_pin;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
-const SETTINGS_SCHEMA_VERSION: u32 = 1;
-const KEYRING_SERVICE: &str = "com.afterglow.manager";
+pub(crate) const SETTINGS_SCHEMA_VERSION: u32 = 1;
+pub(crate) const KEYRING_SERVICE: &str = "com.afterglow.manager";
 const KEYRING_KEY_ID: &str = "aws-access-key-id";
 const KEYRING_SECRET: &str = "aws-secret-access-key";
 
-/// Extract the distribution ID from a CloudFront ARN or return the input as-is.
+/// The AWS partition a region belongs to: "aws-cn" for China, "aws-us-gov" for GovCloud (US),
+/// "aws" otherwise.
+pub fn partition_for_region(region: &str) -> &'static str {
+    let region = region.trim();
+    if region.starts_with("cn-") {
+        "aws-cn"
+    } else if region.starts_with("us-gov-") {
+        "aws-us-gov"
+    } else {
+        "aws"
+    }
+}
+
+/// The partition of an ARN ("aws", "aws-cn", "aws-us-gov"), or `None` when the input isn't one.
+pub fn arn_partition(input: &str) -> Option<&str> {
+    let partition = input.trim().strip_prefix("arn:")?.split(':').next()?;
+    (!partition.is_empty()).then_some(partition)
+}
+
+/// Check that bucket and distribution ARNs are in the region's partition, and that no
+/// CloudFront distribution is configured in GovCloud, where CloudFront isn't offered.
+pub(crate) fn validate_partition(settings: &AppSettings) -> Result<(), String> {
+    let expected = partition_for_region(&settings.region);
+    for (resource, value) in [("bucket", &settings.bucket), ("distribution", &settings.cloud_front_distribution_id)] {
+        if let Some(partition) = arn_partition(value) {
+            if partition != expected {
+                return Err(error(
+                    ErrorCode::ArnWrongPartition,
+                    &[("resource", &resource), ("partition", &partition), ("region", &settings.region), ("expected", &expected)],
+                ));
+            }
+        }
+    }
+    if expected == "aws-us-gov" && !extract_distribution_id(&settings.cloud_front_distribution_id).is_empty() {
+        return Err(error(ErrorCode::CloudFrontUnavailableInGovCloud, &[]));
+    }
+    Ok(())
+}
+
+/// Extract the distribution ID from a CloudFront ARN (any partition) or return the input as-is.
 /// Handles formats like:
-///   "arn:aws:cloudfront::123456:distribution/E1ABC2DEF3GH" -> "E1ABC2DEF3GH"
-///   "E1ABC2DEF3GH"                                         -> "E1ABC2DEF3GH"
+///   "arn:aws:cloudfront::123456:distribution/E1ABC2DEF3GH"    -> "E1ABC2DEF3GH"
+///   "arn:aws-cn:cloudfront::123456:distribution/E1ABC2DEF3GH" -> "E1ABC2DEF3GH"
+///   "E1ABC2DEF3GH"                                            -> "E1ABC2DEF3GH"
 pub fn extract_distribution_id(input: &str) -> String {
     let trimmed = input.trim();
     if trimmed.starts_with("arn:") {
-        // ARN format: arn:aws:cloudfront::ACCOUNT:distribution/DIST_ID
+        // ARN format: arn:PARTITION:cloudfront::ACCOUNT:distribution/DIST_ID
         // Split on '/' and take the last segment
         if let Some(last) = trimmed.rsplit('/').next() {
             if !last.is_empty() {
@@ -25,11 +76,12 @@ pub fn extract_distribution_id(input: &str) -> String {
     trimmed.to_string()
 }
 
-/// Extract the bucket name from an S3 ARN or return the input as-is.
+/// Extract the bucket name from an S3 ARN (any partition) or return the input as-is.
 /// Handles formats like:
-///   "arn:aws:s3:::my-bucket"       -> "my-bucket"
+///   "arn:aws:s3:::my-bucket"        -> "my-bucket"
 ///   "arn:aws:s3:::my-bucket/prefix" -> "my-bucket"
-///   "my-bucket"                    -> "my-bucket"
+///   "arn:aws-cn:s3:::my-bucket"     -> "my-bucket"
+///   "my-bucket"                     -> "my-bucket"
 pub fn extract_bucket_name(input: &str) -> String {
     let trimmed = input.trim();
     if let Some(rest) = trimmed.strip_prefix("arn:") {
@@ -63,6 +115,119 @@ pub struct AppSettings {
     pub cloud_front_distribution_id: String,
     #[serde(default)]
     pub schema_version: u32,
+    /// Public URL the published site is served from (e.g. "https://photos.example.com/").
+    /// Used to build absolute links to galleries and images.
+    #[serde(default)]
+    pub site_url: String,
+    /// Language of the published site (BCP 47, e.g. "en-AU"), set as `<html lang>`.
+    /// Empty keeps the bundled template's language.
+    #[serde(default)]
+    pub site_language: String,
+    /// Published as `<meta name="description">` on the site's index page.
+    #[serde(default)]
+    pub site_description: String,
+    /// Inject a skip link and ARIA labels into the published index page.
+    #[serde(default = "default_true")]
+    pub accessibility_enhancements: bool,
+    /// Publish a Content-Security-Policy `<meta>` tag on the index page. Resources the page
+    /// references itself (analytics snippet, web fonts) are allowed automatically.
+    #[serde(default)]
+    pub content_security_policy: bool,
+    /// Additional https origins allowed by the Content-Security-Policy.
+    #[serde(default)]
+    pub csp_extra_sources: Vec<String>,
+    /// Minutes between automatic health checks of the published site while the app runs.
+    /// 0 disables them.
+    #[serde(default)]
+    pub health_check_interval_minutes: u32,
+    /// Publishing more deletes than this requires typing a confirmation token.
+    /// Absent uses the default (see `publish::DEFAULT_DELETE_PROTECTION_MAX_COUNT`).
+    #[serde(default)]
+    pub delete_protection_max_count: Option<u32>,
+    /// Publishing deletes of more than this fraction (0–1) of the objects under the S3
+    /// prefix requires typing a confirmation token. Absent uses the default.
+    #[serde(default)]
+    pub delete_protection_max_fraction: Option<f64>,
+    /// Folders (ending in `/`) and files below the S3 prefix that the site owns in addition to
+    /// the built-in ones (see `managed_area::ManagedArea`). Publishing deletes objects there that
+    /// it no longer uploads.
+    #[serde(default)]
+    pub managed_extra_paths: Vec<String>,
+    /// Upload through the bucket's S3 Transfer Acceleration endpoint. Publishing falls back to
+    /// the regular endpoint if an accelerated upload fails.
+    #[serde(default)]
+    pub transfer_acceleration: bool,
+    /// After the CloudFront invalidation completes, fetch the index page, site manifest and first
+    /// thumbnails of each gallery through the site URL so they are cached at the edge.
+    #[serde(default)]
+    pub cache_warm_up: bool,
+    /// Thumbnails per gallery fetched by the warm-up. Absent uses the default
+    /// (see `cache_warm::DEFAULT_WARM_THUMBNAILS`).
+    #[serde(default)]
+    pub cache_warm_thumbnails: Option<u32>,
+    /// Monthly budget for S3 and CloudFront spend. When set, previewing a publish checks the
+    /// month-to-date spend in Cost Explorer (see `budget::check_budget`).
+    #[serde(default)]
+    pub monthly_budget: Option<f64>,
+    /// Block publishing, rather than warn, when the month-to-date spend is over the budget.
+    #[serde(default)]
+    pub block_over_budget: bool,
+    /// Bucket that `archive::archive_gallery` uploads originals to; empty uses the site bucket.
+    #[serde(default)]
+    pub archive_bucket: String,
+    /// Prefix for archived originals (`{prefix}{slug}/{file}`); empty uses "afterglow-archive/".
+    #[serde(default)]
+    pub archive_prefix: String,
+    /// Second bucket that receives every publish too (see `mirror::MirrorTarget`).
+    #[serde(default)]
+    pub mirror: MirrorTarget,
+    /// Language of error messages from the backend (e.g. "de"); empty or unsupported means
+    /// English. See `i18n::list_locales`.
+    #[serde(default)]
+    pub locale: String,
+    /// Comment widget published with each gallery (see `comments::CommentsSettings`).
+    #[serde(default)]
+    pub comments: CommentsSettings,
+    /// Serve the local automation API (see `automation::start_automation_server`). Off by default.
+    #[serde(default)]
+    pub automation_api: bool,
+    /// Serve the LAN companion API to paired devices (see `companion::start_companion_server`).
+    #[serde(default)]
+    pub companion_api: bool,
+    /// Port of the companion API; unset uses `companion::DEFAULT_COMPANION_PORT`.
+    #[serde(default)]
+    pub companion_port: Option<u16>,
+    /// Local IP address the companion API listens on, one of `companion::list_network_interfaces`;
+    /// unset uses the address of the interface with the default route.
+    #[serde(default)]
+    pub companion_address: Option<String>,
+    /// Daily metadata backups kept per workspace (see `backups::start_metadata_backups`).
+    /// Absent uses `backups::DEFAULT_BACKUP_RETENTION`; 0 turns backups off.
+    #[serde(default)]
+    pub metadata_backup_retention: Option<u32>,
+    /// Publish automatically after the workspace settles (see `auto_publish::AutoPublishSettings`).
+    #[serde(default)]
+    pub auto_publish: AutoPublishSettings,
+    /// Template version to keep on the live site (see `site_template::plan_template`); empty
+    /// publishes the one bundled with the app.
+    #[serde(default)]
+    pub site_template_pin: String,
+    /// Where the site was published before the bucket or S3 prefix last changed, until
+    /// `cleanup_old_prefix` removes what's left there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_target: Option<PreviousTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousTarget {
+    pub bucket: String,
+    pub region: String,
+    pub s3_prefix: String,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,16 +237,91 @@ pub struct ValidationResult {
     pub arn: String,
 }
 
-fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     use tauri::Manager;
     let data_dir = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Cannot determine app data directory: {}", e))?;
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
     fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
     Ok(data_dir.join("settings.json"))
 }
 
+/// Read the saved settings from disk. Returns defaults when nothing has been saved yet.
+pub(crate) fn read_settings(app: &tauri::AppHandle) -> Result<AppSettings, String> {
+    let path = settings_path(app)?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+pub(crate) fn write_settings(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())
+}
+
+/// Normalise a site URL so it always ends with `/`. Returns an error when no URL is configured.
+pub(crate) fn normalize_site_url(site_url: &str) -> Result<String, String> {
+    let trimmed = site_url.trim();
+    if trimmed.is_empty() {
+        return Err(error(ErrorCode::SiteUrlNotConfigured, &[]));
+    }
+    if trimmed.ends_with('/') {
+        Ok(trimmed.to_string())
+    } else {
+        Ok(format!("{}/", trimmed))
+    }
+}
+
+/// Characters S3 recommends avoiding in keys; they break URLs on the published site.
+const ILLEGAL_PREFIX_CHARS: &[char] = &['\\', '{', '}', '^', '%', '`', '[', ']', '"', '<', '>', '~', '#', '|'];
+
+/// Validate an S3 site prefix and normalise it to be empty or end with `/`.
+pub(crate) fn normalize_s3_prefix(prefix: &str) -> Result<String, String> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        return Ok(String::new());
+    }
+    if prefix.starts_with('/') {
+        return Err(error(ErrorCode::S3PrefixLeadingSlash, &[("prefix", &prefix)]));
+    }
+    if let Some(c) = prefix.chars().find(|c| c.is_control() || ILLEGAL_PREFIX_CHARS.contains(c)) {
+        return Err(error(ErrorCode::S3PrefixIllegalCharacter, &[("prefix", &prefix), ("character", &format!("{:?}", c))]));
+    }
+    let trimmed = prefix.strip_suffix('/').unwrap_or(prefix);
+    if trimmed.split('/').any(|segment| segment.is_empty() || segment == "." || segment == "..") {
+        return Err(error(ErrorCode::S3PrefixInvalidSegment, &[("prefix", &prefix)]));
+    }
+    Ok(format!("{}/", trimmed))
+}
+
+/// Remember the saved publish target in `settings.previous_target` when the bucket or prefix
+/// changes, so the old location can be cleaned up.
+fn record_previous_target(saved: &AppSettings, settings: &mut AppSettings) {
+    let target = |s: &AppSettings| PreviousTarget {
+        bucket: extract_bucket_name(&s.bucket),
+        region: s.region.trim().to_string(),
+        s3_prefix: normalize_s3_prefix(&s.s3_prefix).unwrap_or_else(|_| s.s3_prefix.clone()),
+    };
+    let (old, new) = (target(saved), target(settings));
+    if old.bucket.is_empty() || (old.bucket == new.bucket && old.s3_prefix == new.s3_prefix) {
+        // Unchanged: keep whatever the dialog round-tripped from the saved settings
+        settings.previous_target = saved.previous_target.clone();
+    } else {
+        settings.previous_target = Some(old);
+    }
+    if settings
+        .previous_target
+        .as_ref()
+        .is_some_and(|p| p.bucket == new.bucket && p.s3_prefix == new.s3_prefix)
+    {
+        settings.previous_target = None;
+    }
+}
+
 #[tauri::command]
 pub async fn load_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
     let path = settings_path(&app)?;
@@ -90,6 +330,7 @@ pub async fn load_settings(app: tauri::AppHandle) -> Result<AppSettings, String>
             region: "ap-southeast-2".to_string(),
             s3_prefix: "".to_string(),
             schema_version: SETTINGS_SCHEMA_VERSION,
+            accessibility_enhancements: true,
             ..Default::default()
         });
     }
@@ -107,31 +348,49 @@ pub async fn load_settings(app: tauri::AppHandle) -> Result<AppSettings, String>
         fs::write(&path, json).map_err(|e| e.to_string())?;
     }
 
+    set_locale(&settings.locale);
     Ok(settings)
 }
 
 #[tauri::command]
-pub async fn save_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
-    let path = settings_path(&app)?;
-    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| e.to_string())?;
+pub async fn save_settings(app: tauri::AppHandle, mut settings: AppSettings) -> Result<(), String> {
+    settings.s3_prefix = normalize_s3_prefix(&settings.s3_prefix)?;
+    validate_managed_extra_paths(&settings.managed_extra_paths)?;
+    validate_partition(&settings)?;
+    validate_comments(&settings.comments)?;
+    validate_budget(&settings)?;
+    validate_archive_location(&settings)?;
+    validate_mirror(&settings)?;
+    validate_companion(&settings)?;
+    validate_backup_retention(&settings)?;
+    validate_auto_publish(&settings)?;
+    validate_template_pin(&settings)?;
+    let previous = read_settings(&app)?;
+    record_previous_target(&previous, &mut settings);
+    write_settings(&app, &settings)?;
+    set_locale(&settings.locale);
+    let changed = changed_settings(&previous, &settings);
+    if !changed.is_empty() {
+        record_activity(&app, "saveSettings", format!("Changed {}", changed.join(", ")));
+    }
     Ok(())
 }
 
 #[tauri::command]
-pub async fn save_credentials(key_id: String, secret: String) -> Result<(), String> {
+pub async fn save_credentials(app: tauri::AppHandle, key_id: String, secret: String) -> Result<(), String> {
     let entry_id = keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_ID)
-        .map_err(|e| format!("Unable to access system keychain: {}", e))?;
+        .map_err(|e| error(ErrorCode::KeychainUnavailable, &[("error", &e)]))?;
     entry_id
         .set_password(&key_id)
-        .map_err(|e| format!("Unable to access system keychain. Credentials cannot be saved: {}", e))?;
+        .map_err(|e| error(ErrorCode::KeychainSaveFailed, &[("error", &e)]))?;
 
     let entry_secret = keyring::Entry::new(KEYRING_SERVICE, KEYRING_SECRET)
-        .map_err(|e| format!("Unable to access system keychain: {}", e))?;
+        .map_err(|e| error(ErrorCode::KeychainUnavailable, &[("error", &e)]))?;
     entry_secret
         .set_password(&secret)
-        .map_err(|e| format!("Unable to access system keychain. Credentials cannot be saved: {}", e))?;
+        .map_err(|e| error(ErrorCode::KeychainSaveFailed, &[("error", &e)]))?;
 
+    record_activity(&app, "saveCredentials", format!("Access key ending {}", key_id_hint(&key_id)));
     Ok(())
 }
 
@@ -148,40 +407,109 @@ pub async fn has_credentials() -> bool {
 pub async fn get_credential_hint() -> Option<String> {
     let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_ID).ok()?;
     let key_id = entry.get_password().ok()?;
+    Some(key_id_hint(&key_id))
+}
+
+/// Last four characters of an access key ID, safe to show and log.
+pub(crate) fn key_id_hint(key_id: &str) -> String {
     if key_id.len() >= 4 {
-        Some(key_id[key_id.len() - 4..].to_string())
+        key_id[key_id.len() - 4..].to_string()
     } else {
-        Some(key_id)
+        key_id.to_string()
     }
 }
 
 #[tauri::command]
-pub async fn delete_credentials() -> Result<(), String> {
+pub async fn delete_credentials(app: tauri::AppHandle) -> Result<(), String> {
     if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_ID) {
         let _ = entry.delete_credential();
     }
     if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, KEYRING_SECRET) {
         let _ = entry.delete_credential();
     }
+    record_activity(&app, "deleteCredentials", String::new());
     Ok(())
 }
 
 pub fn get_credentials_from_keychain() -> Result<(String, String), String> {
     let entry_id = keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_ID)
-        .map_err(|e| format!("Keychain error: {}", e))?;
+        .map_err(|e| error(ErrorCode::KeychainUnavailable, &[("error", &e)]))?;
     let key_id = entry_id
         .get_password()
-        .map_err(|_| "No credentials found. Configure AWS credentials in Settings.".to_string())?;
+        .map_err(|_| error(ErrorCode::NoCredentials, &[]))?;
 
     let entry_secret = keyring::Entry::new(KEYRING_SERVICE, KEYRING_SECRET)
-        .map_err(|e| format!("Keychain error: {}", e))?;
+        .map_err(|e| error(ErrorCode::KeychainUnavailable, &[("error", &e)]))?;
     let secret = entry_secret
         .get_password()
-        .map_err(|_| "No credentials found. Configure AWS credentials in Settings.".to_string())?;
+        .map_err(|_| error(ErrorCode::NoCredentials, &[]))?;
 
     Ok((key_id, secret))
 }
 
+/// Which platform secret store holds the AWS credentials, and whether it works.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialStoreStatus {
+    /// "macosKeychain", "windowsCredentialManager", "secretService", "keyutils" or "memory".
+    pub backend: String,
+    /// False when saved credentials won't outlive the app (or, for keyutils, a reboot).
+    pub persistent: bool,
+    /// Whether a test entry could be written, read back through a new handle and deleted.
+    pub writable: bool,
+    /// What the store reported when the test entry failed.
+    pub error: Option<String>,
+}
+
+const KEYRING_PROBE: &str = "credential-store-check";
+
+/// The store `keyring` picked at build time for this platform, and whether it keeps credentials
+/// across launches. Linux builds without a secret-store feature fall back to an in-memory mock.
+fn store_backend(persistence: keyring::credential::CredentialPersistence) -> (&'static str, bool) {
+    use keyring::credential::CredentialPersistence;
+    if cfg!(target_os = "macos") {
+        return ("macosKeychain", true);
+    }
+    if cfg!(target_os = "windows") {
+        return ("windowsCredentialManager", true);
+    }
+    match persistence {
+        CredentialPersistence::UntilDelete => ("secretService", true),
+        CredentialPersistence::UntilReboot => ("keyutils", false),
+        _ => ("memory", false),
+    }
+}
+
+/// Write a test secret, read it back through a fresh entry (as `get_credentials_from_keychain`
+/// does) and delete it.
+fn probe_store(entry: impl Fn() -> keyring::Result<keyring::Entry>) -> Result<(), String> {
+    let value = format!("probe-{}", std::process::id());
+    entry()
+        .and_then(|e| e.set_password(&value))
+        .map_err(|e| error(ErrorCode::SecretStoreWriteFailed, &[("error", &e)]))?;
+    let read = entry().and_then(|e| e.get_password());
+    let _ = entry().and_then(|e| e.delete_credential());
+    match read {
+        Ok(read) if read == value => Ok(()),
+        Ok(_) => Err(error(ErrorCode::SecretStoreMismatch, &[])),
+        Err(e) => Err(error(ErrorCode::SecretStoreReadFailed, &[("error", &e)])),
+    }
+}
+
+/// Check the secret store credentials are saved in, so setup problems (a locked keyring, no
+/// Secret Service on a Linux desktop) show up in Settings rather than as a failed publish.
+#[tauri::command]
+pub async fn check_credential_store() -> CredentialStoreStatus {
+    let (backend, persistent) = store_backend(keyring::default::default_credential_builder().persistence());
+    let probe = probe_store(|| keyring::Entry::new(KEYRING_SERVICE, KEYRING_PROBE));
+    CredentialStoreStatus {
+        backend: backend.to_string(),
+        persistent,
+        writable: probe.is_ok(),
+        error: probe.err(),
+    }
+}
+
 #[tauri::command]
 pub async fn validate_credentials(
     key_id: String,
@@ -198,6 +526,7 @@ pub async fn validate_credentials(
     }
 
     eprintln!("[validate] Starting validation, region={}, bucket={}", region, bucket);
+    validate_partition(&AppSettings { bucket: bucket.clone(), region: region.clone(), ..AppSettings::default() })?;
 
     let creds = Credentials::new(&key_id, &secret, None, None, "afterglow-manager");
     let region = Region::new(region);
@@ -268,6 +597,34 @@ mod tests {
             last_validated_arn: "arn:aws:iam::123456789012:user/test".to_string(),
             cloud_front_distribution_id: "".to_string(),
             schema_version: 1,
+            site_url: "https://photos.example.com/".to_string(),
+            site_language: "en-AU".to_string(),
+            site_description: "Match photos".to_string(),
+            accessibility_enhancements: false,
+            content_security_policy: true,
+            csp_extra_sources: vec!["https://cdn.example.com".to_string()],
+            health_check_interval_minutes: 15,
+            delete_protection_max_count: Some(20),
+            delete_protection_max_fraction: Some(0.1),
+            managed_extra_paths: vec!["robots.txt".to_string()],
+            transfer_acceleration: true,
+            cache_warm_up: true,
+            cache_warm_thumbnails: Some(6),
+            monthly_budget: Some(5.0),
+            block_over_budget: true,
+            archive_bucket: "cold-photos".to_string(),
+            archive_prefix: "originals/".to_string(),
+            mirror: MirrorTarget { bucket: "photos-eu".to_string(), region: "eu-west-1".to_string(), ..Default::default() },
+            locale: "de".to_string(),
+            comments: CommentsSettings { provider: "giscus".to_string(), ..Default::default() },
+            automation_api: true,
+            companion_api: true,
+            companion_port: Some(8800),
+            companion_address: Some("192.168.1.20".to_string()),
+            metadata_backup_retention: Some(30),
+            auto_publish: AutoPublishSettings { enabled: true, quiet_minutes: Some(5), ..Default::default() },
+            site_template_pin: "2.4.0".to_string(),
+            previous_target: None,
         };
         let json = serde_json::to_string(&settings).unwrap();
         let parsed: AppSettings = serde_json::from_str(&json).unwrap();
@@ -276,6 +633,34 @@ mod tests {
         assert_eq!(parsed.s3_prefix, "");
         assert_eq!(parsed.last_validated_user, "AIDA123");
         assert_eq!(parsed.schema_version, 1);
+        assert_eq!(parsed.site_url, "https://photos.example.com/");
+        assert_eq!(parsed.site_language, "en-AU");
+        assert_eq!(parsed.site_description, "Match photos");
+        assert!(!parsed.accessibility_enhancements);
+        assert!(parsed.content_security_policy);
+        assert_eq!(parsed.csp_extra_sources, vec!["https://cdn.example.com"]);
+        assert_eq!(parsed.health_check_interval_minutes, 15);
+        assert_eq!(parsed.delete_protection_max_count, Some(20));
+        assert_eq!(parsed.delete_protection_max_fraction, Some(0.1));
+        assert_eq!(parsed.managed_extra_paths, vec!["robots.txt"]);
+        assert!(parsed.transfer_acceleration);
+        assert!(parsed.cache_warm_up);
+        assert_eq!(parsed.cache_warm_thumbnails, Some(6));
+        assert_eq!(parsed.monthly_budget, Some(5.0));
+        assert!(parsed.block_over_budget);
+        assert_eq!(parsed.archive_bucket, "cold-photos");
+        assert_eq!(parsed.archive_prefix, "originals/");
+        assert_eq!(parsed.mirror.bucket, "photos-eu");
+        assert_eq!(parsed.locale, "de");
+        assert_eq!(parsed.comments.provider, "giscus");
+        assert!(parsed.automation_api);
+        assert!(parsed.companion_api);
+        assert_eq!(parsed.companion_port, Some(8800));
+        assert_eq!(parsed.companion_address.as_deref(), Some("192.168.1.20"));
+        assert_eq!(parsed.metadata_backup_retention, Some(30));
+        assert!(parsed.auto_publish.enabled);
+        assert_eq!(parsed.auto_publish.quiet_minutes, Some(5));
+        assert_eq!(parsed.site_template_pin, "2.4.0");
     }
 
     #[test]
@@ -294,6 +679,7 @@ mod tests {
         assert_eq!(settings.last_validated_user, "USER");
         // schema_version defaults to 0 when missing from JSON
         assert_eq!(settings.schema_version, 0);
+        assert!(settings.accessibility_enhancements, "accessibility defaults on for existing settings");
     }
 
     #[test]
@@ -436,6 +822,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_from_china_partition_arns() {
+        assert_eq!(extract_bucket_name("arn:aws-cn:s3:::cn-bucket/prefix"), "cn-bucket");
+        assert_eq!(
+            extract_distribution_id("arn:aws-cn:cloudfront::123456789012:distribution/E1ABC2DEF3GH"),
+            "E1ABC2DEF3GH"
+        );
+        assert_eq!(arn_partition("arn:aws-cn:s3:::cn-bucket"), Some("aws-cn"));
+        assert_eq!(arn_partition("cn-bucket"), None);
+    }
+
+    #[test]
+    fn test_partition_for_region() {
+        assert_eq!(partition_for_region("us-east-1"), "aws");
+        assert_eq!(partition_for_region("eu-west-2"), "aws");
+        assert_eq!(partition_for_region("cn-north-1"), "aws-cn");
+        assert_eq!(partition_for_region("us-gov-west-1"), "aws-us-gov");
+    }
+
+    #[test]
+    fn test_validate_partition() {
+        let settings = |bucket: &str, region: &str, dist: &str| AppSettings {
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            cloud_front_distribution_id: dist.to_string(),
+            ..AppSettings::default()
+        };
+        assert!(validate_partition(&settings("arn:aws-cn:s3:::b", "cn-north-1", "E1ABC")).is_ok());
+        assert!(validate_partition(&settings("b", "us-gov-west-1", "")).is_ok());
+        assert_eq!(
+            validate_partition(&settings("arn:aws:s3:::b", "cn-north-1", "")).unwrap_err(),
+            "[arn-wrong-partition] Bucket ARN is in the aws partition, but region cn-north-1 is in aws-cn."
+        );
+        assert!(validate_partition(&settings("b", "cn-north-1", "arn:aws:cloudfront::1:distribution/E1"))
+            .unwrap_err()
+            .contains("CloudFront distribution ARN"));
+        assert!(validate_partition(&settings("b", "us-gov-west-1", "E1ABC")).is_err());
+    }
+
     #[test]
     fn test_extract_distribution_id_plain() {
         assert_eq!(extract_distribution_id("E1ABC2DEF3GH"), "E1ABC2DEF3GH");
@@ -458,6 +883,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_site_url() {
+        assert_eq!(normalize_site_url("https://example.com").unwrap(), "https://example.com/");
+        assert_eq!(normalize_site_url(" https://example.com/ ").unwrap(), "https://example.com/");
+        assert!(normalize_site_url("  ").is_err());
+    }
+
+    #[test]
+    fn test_record_previous_target() {
+        let saved = AppSettings {
+            bucket: "my-bucket".to_string(),
+            region: "us-east-1".to_string(),
+            s3_prefix: "old".to_string(),
+            ..Default::default()
+        };
+        let mut moved = AppSettings { s3_prefix: "new/".to_string(), ..saved.clone() };
+        record_previous_target(&saved, &mut moved);
+        let previous = moved.previous_target.clone().unwrap();
+        assert_eq!(previous.s3_prefix, "old/");
+        assert_eq!(previous.bucket, "my-bucket");
+
+        // Unrelated edits keep the recorded target
+        let mut edited = AppSettings { site_url: "https://example.com/".to_string(), ..moved.clone() };
+        record_previous_target(&moved, &mut edited);
+        assert_eq!(edited.previous_target, Some(previous));
+
+        // Moving back makes the location just left the stale one
+        let mut back = AppSettings { s3_prefix: "old/".to_string(), ..moved.clone() };
+        record_previous_target(&moved, &mut back);
+        assert_eq!(back.previous_target.unwrap().s3_prefix, "new/");
+
+        // Never points at the current location
+        let mut current = AppSettings { previous_target: moved.previous_target.clone(), ..saved.clone() };
+        record_previous_target(&saved, &mut current);
+        assert_eq!(current.previous_target, None);
+
+        // First-time setup records nothing
+        let mut first = saved.clone();
+        record_previous_target(&AppSettings::default(), &mut first);
+        assert_eq!(first.previous_target, None);
+    }
+
+    #[test]
+    fn test_normalize_s3_prefix() {
+        assert_eq!(normalize_s3_prefix("").unwrap(), "");
+        assert_eq!(normalize_s3_prefix("  ").unwrap(), "");
+        assert_eq!(normalize_s3_prefix("my-site").unwrap(), "my-site/");
+        assert_eq!(normalize_s3_prefix("sites/photos/").unwrap(), "sites/photos/");
+        assert!(normalize_s3_prefix("/my-site/").is_err());
+        assert!(normalize_s3_prefix("my site\\").is_err());
+        assert!(normalize_s3_prefix("a#b/").is_err());
+        assert!(normalize_s3_prefix("a//b/").is_err());
+        assert!(normalize_s3_prefix("a/../b").is_err());
+        assert!(normalize_s3_prefix("a\nb").is_err());
+        assert_eq!(
+            normalize_s3_prefix("a#b/").unwrap_err(),
+            "[prefix-illegal-character] S3 prefix \"a#b/\" contains an illegal character ('#')."
+        );
+    }
+
     #[test]
     fn test_validation_result_serialization() {
         let result = ValidationResult {
@@ -470,4 +955,23 @@ mod tests {
         let parsed: ValidationResult = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.user, "AIDA123");
     }
+
+    #[test]
+    fn test_probe_store() {
+        // Mock entries keep their secret in the entry, like a store that saves nothing
+        let mock = || {
+            keyring::mock::default_credential_builder()
+                .build(None, KEYRING_SERVICE, KEYRING_PROBE)
+                .map(keyring::Entry::new_with_credential)
+        };
+        assert!(probe_store(mock).unwrap_err().contains("couldn't read it back"));
+        let failing = || Err(keyring::Error::PlatformFailure("locked".into()));
+        assert!(probe_store(failing).unwrap_err().contains("Can't write"));
+
+        if cfg!(target_os = "linux") {
+            use keyring::credential::CredentialPersistence;
+            assert_eq!(store_backend(CredentialPersistence::UntilDelete), ("secretService", true));
+            assert_eq!(store_backend(CredentialPersistence::EntryOnly), ("memory", false));
+        }
+    }
 }
@@ -0,0 +1,189 @@
+use crate::encryption::readable_original;
+use crate::i18n::{error, ErrorCode};
+use crate::managed_area::ManagedArea;
+use crate::publish::{collect_referenced_files, compute_md5, list_objects, s3_client, ListedObject};
+use crate::settings::{
+    extract_bucket_name, get_credentials_from_keychain, normalize_s3_prefix, read_settings, AppSettings, PreviousTarget,
+};
+use crate::thumbnails::{build_thumbnail_specs, is_thumbnail_fresh};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChangeImpact {
+    /// Bucket, region or S3 prefix differ from the saved settings.
+    pub target_changed: bool,
+    /// Managed objects at the old location that no publish will update or delete any more.
+    pub orphaned_objects: usize,
+    pub orphaned_bytes: u64,
+    /// Thumbnails missing or out of date in the local cache.
+    pub thumbnails_to_regenerate: usize,
+    /// Photos and cached thumbnails the next publish to the new target would upload. Published
+    /// JSON and thumbnails still to be generated are not included.
+    pub upload_files: usize,
+    pub upload_bytes: u64,
+}
+
+/// A publish target: where the site's objects live.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Target {
+    pub bucket: String,
+    pub region: String,
+    pub s3_root: String,
+}
+
+impl Target {
+    pub(crate) fn from_settings(settings: &AppSettings) -> Result<Self, String> {
+        Ok(Self {
+            bucket: extract_bucket_name(&settings.bucket),
+            region: settings.region.trim().to_string(),
+            s3_root: normalize_s3_prefix(&settings.s3_prefix)?,
+        })
+    }
+
+    pub(crate) fn from_previous(previous: &PreviousTarget) -> Result<Self, String> {
+        Ok(Self {
+            bucket: extract_bucket_name(&previous.bucket),
+            region: previous.region.trim().to_string(),
+            s3_root: normalize_s3_prefix(&previous.s3_prefix)?,
+        })
+    }
+}
+
+/// Managed objects at `old` that publishing to `new` will no longer reach: everything in the
+/// old managed keyspace unless it's also in the new one.
+pub(crate) fn orphaned_objects<'a>(
+    objects: &'a [ListedObject],
+    old: &Target,
+    new: &Target,
+    area: &ManagedArea,
+) -> Vec<&'a ListedObject> {
+    objects
+        .iter()
+        .filter(|obj| area.contains(&old.s3_root, &obj.key))
+        .filter(|obj| !(old.bucket == new.bucket && area.contains(&new.s3_root, &obj.key)))
+        .collect()
+}
+
+/// Local files (s3 key → (md5, size)) not already present with the same content remotely.
+fn files_to_upload(local: &HashMap<String, (String, u64)>, remote: &HashMap<String, String>) -> (usize, u64) {
+    local
+        .iter()
+        .filter(|(key, (md5, _))| remote.get(*key).is_none_or(|etag| etag.contains('-') || etag != md5))
+        .fold((0, 0), |(count, bytes), (_, (_, size))| (count + 1, bytes + size))
+}
+
+/// Preview what saving `settings` would mean for the published site before saving: objects
+/// orphaned at the old bucket/prefix, thumbnails to regenerate and the size of the first upload
+/// to the new target. `folder_path` is the open workspace, if any.
+#[tauri::command]
+pub async fn preview_settings_change(
+    app: tauri::AppHandle,
+    folder_path: Option<String>,
+    settings: AppSettings,
+) -> Result<SettingsChangeImpact, String> {
+    let new = Target::from_settings(&settings)?;
+    let old = Target::from_settings(&read_settings(&app)?)?;
+    let area = ManagedArea::from_settings(&settings)?;
+    let mut impact = SettingsChangeImpact { target_changed: old != new, ..Default::default() };
+    if new.bucket.is_empty() {
+        return Err(error(ErrorCode::BucketNotConfigured, &[]));
+    }
+    let (key_id, secret) = get_credentials_from_keychain()?;
+
+    if impact.target_changed && !old.bucket.is_empty() {
+        let client = s3_client(&key_id, &secret, &old.region);
+        let objects = list_objects(&client, &old.bucket, &old.s3_root).await?;
+        let orphaned = orphaned_objects(&objects, &old, &new, &area);
+        impact.orphaned_objects = orphaned.len();
+        impact.orphaned_bytes = orphaned.iter().map(|obj| obj.size).sum();
+    }
+
+    let Some(folder_path) = folder_path else {
+        return Ok(impact);
+    };
+    let root = PathBuf::from(folder_path);
+    let galleries_json: serde_json::Value = fs::read_to_string(root.join("galleries.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or(serde_json::Value::Null);
+    let galleries_prefix = format!("{}galleries/", new.s3_root);
+
+    let mut local: HashMap<String, (String, u64)> = HashMap::new();
+    for file_path in collect_referenced_files(&root)? {
+        let relative = file_path
+            .strip_prefix(&root)
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let source = readable_original(&file_path);
+        let size = fs::metadata(&source).map_err(|e| e.to_string())?.len();
+        local.insert(format!("{}{}", galleries_prefix, relative), (compute_md5(&source)?, size));
+    }
+    for spec in build_thumbnail_specs(&root, &galleries_json, &new.s3_root) {
+        if !is_thumbnail_fresh(&readable_original(&spec.source_path), &spec.dest_path) {
+            impact.thumbnails_to_regenerate += 1;
+            continue;
+        }
+        let size = fs::metadata(&spec.dest_path).map_err(|e| e.to_string())?.len();
+        local.insert(spec.s3_key, (compute_md5(&spec.dest_path)?, size));
+    }
+
+    let client = s3_client(&key_id, &secret, &new.region);
+    let remote: HashMap<String, String> = list_objects(&client, &new.bucket, &galleries_prefix)
+        .await?
+        .into_iter()
+        .map(|obj| (obj.key, obj.etag))
+        .collect();
+    (impact.upload_files, impact.upload_bytes) = files_to_upload(&local, &remote);
+    Ok(impact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(key: &str, size: u64) -> ListedObject {
+        ListedObject { key: key.to_string(), etag: String::new(), size }
+    }
+
+    fn target(bucket: &str, s3_root: &str) -> Target {
+        Target { bucket: bucket.to_string(), region: "ap-southeast-2".to_string(), s3_root: s3_root.to_string() }
+    }
+
+    #[test]
+    fn test_orphaned_objects() {
+        let objects = vec![
+            object("old/galleries/a/01.jpg", 100),
+            object("old/index.html", 10),
+            object("old/notes.txt", 5),
+            object("old/new/galleries/a/01.jpg", 100),
+        ];
+        let keys = |old: &Target, new: &Target| -> Vec<String> {
+            orphaned_objects(&objects, old, new, &ManagedArea::default()).into_iter().map(|o| o.key.clone()).collect()
+        };
+        assert_eq!(keys(&target("b", "old/"), &target("b", "site/")), vec!["old/galleries/a/01.jpg", "old/index.html"]);
+        // Nothing is orphaned when the target doesn't move
+        assert!(keys(&target("b", "old/"), &target("b", "old/")).is_empty());
+        // A different bucket orphans the whole managed keyspace
+        assert_eq!(keys(&target("b", "old/"), &target("c", "old/")).len(), 2);
+    }
+
+    #[test]
+    fn test_files_to_upload() {
+        let local = HashMap::from([
+            ("galleries/a/01.jpg".to_string(), ("aaa".to_string(), 100)),
+            ("galleries/a/02.jpg".to_string(), ("bbb".to_string(), 200)),
+            ("galleries/a/03.jpg".to_string(), ("ccc".to_string(), 400)),
+        ]);
+        let remote = HashMap::from([
+            ("galleries/a/01.jpg".to_string(), "aaa".to_string()),
+            ("galleries/a/02.jpg".to_string(), "old".to_string()),
+        ]);
+        assert_eq!(files_to_upload(&local, &remote), (2, 600));
+        assert_eq!(files_to_upload(&local, &HashMap::new()), (3, 700));
+    }
+}
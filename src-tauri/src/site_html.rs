@@ -0,0 +1,335 @@
+use crate::announcement::escape_html;
+use crate::comments::comments_csp_sources;
+use crate::settings::AppSettings;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+/// Per-site values injected into the bundled `index.html` at publish time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SiteHtmlOptions {
+    /// BCP 47 language tag for `<html lang>`. Empty keeps the template's value.
+    pub language: String,
+    /// `<meta name="description">` content. Empty adds no tag.
+    pub description: String,
+    /// Add a skip link, a focusable main landmark and ARIA labels.
+    pub accessibility: bool,
+    /// Add a Content-Security-Policy `<meta>` tag (see `build_content_security_policy`).
+    pub content_security_policy: bool,
+    /// Extra origins allowed for every fetch directive of the policy.
+    pub csp_extra_sources: Vec<String>,
+}
+
+impl SiteHtmlOptions {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            language: settings.site_language.trim().to_string(),
+            description: settings.site_description.trim().to_string(),
+            accessibility: settings.accessibility_enhancements,
+            content_security_policy: settings.content_security_policy,
+            csp_extra_sources: settings
+                .csp_extra_sources
+                .iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .chain(comments_csp_sources(&settings.comments))
+                .collect(),
+        }
+    }
+}
+
+const SKIP_LINK: &str = "<a class=\"skip-link\" href=\"#app\">Skip to content</a>";
+
+/// A language tag is 1–8 alphanumeric subtags separated by `-` (e.g. "en", "en-AU", "zh-Hant").
+pub fn is_valid_language_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .split('-')
+            .all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Byte range of the first start tag named `name` (from `<` to just past `>`).
+fn find_start_tag(html: &str, name: &str) -> Option<(usize, usize)> {
+    let needle = format!("<{}", name);
+    let mut from = 0;
+    while let Some(pos) = html[from..].find(&needle) {
+        let start = from + pos;
+        let after = start + needle.len();
+        // Skip longer tag names sharing the prefix (<header> when looking for <head>)
+        if html[after..].starts_with(|c: char| c == '>' || c.is_ascii_whitespace()) {
+            let end = after + html[after..].find('>')? + 1;
+            return Some((start, end));
+        }
+        from = after;
+    }
+    None
+}
+
+/// Set (or replace) a double-quoted attribute on the first `name` start tag. Returns the
+/// document unchanged when the tag is missing.
+fn set_attribute(html: &str, name: &str, attr: &str, value: &str) -> String {
+    let Some((start, end)) = find_start_tag(html, name) else {
+        return html.to_string();
+    };
+    let tag = &html[start..end];
+    let needle = format!(" {}=\"", attr);
+    let new_tag = match tag.find(&needle) {
+        Some(pos) => {
+            let value_start = pos + needle.len();
+            let value_end = value_start + tag[value_start..].find('"').unwrap_or(0);
+            format!("{}{}{}", &tag[..value_start], value, &tag[value_end..])
+        }
+        None => {
+            let insert_at = tag.len() - if tag.ends_with("/>") { 2 } else { 1 };
+            format!("{} {}=\"{}\"{}", &tag[..insert_at], attr, value, &tag[insert_at..])
+        }
+    };
+    format!("{}{}{}", &html[..start], new_tag, &html[end..])
+}
+
+/// Set an attribute only when the tag doesn't already have it (template values win).
+fn ensure_attribute(html: &str, name: &str, attr: &str, value: &str) -> String {
+    match find_start_tag(html, name) {
+        Some((start, end)) if !html[start..end].contains(&format!(" {}=", attr)) => {
+            set_attribute(html, name, attr, value)
+        }
+        _ => html.to_string(),
+    }
+}
+
+/// Insert `snippet` on its own line immediately before `</head>`.
+fn insert_before_head_end(html: &str, snippet: &str) -> String {
+    match html.find("</head>") {
+        Some(pos) => format!("{}  {}\n{}", &html[..pos], snippet, &html[pos..]),
+        None => html.to_string(),
+    }
+}
+
+/// Insert `snippet` on its own line immediately after the `<head>` start tag.
+fn insert_after_head_start(html: &str, snippet: &str) -> String {
+    match find_start_tag(html, "head") {
+        Some((_, end)) => format!("{}\n  {}{}", &html[..end], snippet, &html[end..]),
+        None => html.to_string(),
+    }
+}
+
+/// `https://host[:port]` origins of every absolute https URL in `text`.
+fn https_origins(text: &str) -> BTreeSet<String> {
+    let mut origins = BTreeSet::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find("https://") {
+        let after = &rest[pos + "https://".len()..];
+        let host_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':'))
+            .unwrap_or(after.len());
+        let host = &after[..host_len];
+        if !host.is_empty() {
+            origins.insert(format!("https://{}", host));
+        }
+        rest = after;
+    }
+    origins
+}
+
+/// Every start tag named `name`, with its element content for non-void elements.
+fn elements<'a>(html: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let close = format!("</{}>", name);
+    let mut out = Vec::new();
+    let mut from = 0;
+    while let Some((start, end)) = find_start_tag(&html[from..], name) {
+        let (start, end) = (from + start, from + end);
+        let content_end = html[end..].find(&close).map(|p| end + p).unwrap_or(end);
+        out.push((&html[start..end], &html[end..content_end]));
+        from = end;
+    }
+    out
+}
+
+/// Hosted analytics loaders fetch their bundle from a sibling assets host
+/// (PostHog: `us.i.posthog.com` → `us-assets.i.posthog.com`).
+fn companion_origins(origin: &str) -> Option<String> {
+    let host = origin.strip_prefix("https://")?;
+    let (region, rest) = host.split_once('.')?;
+    (rest == "i.posthog.com").then(|| format!("https://{}-assets.{}", region, rest))
+}
+
+/// A CSP source expression we accept from settings: an https origin (optionally with a
+/// `*.` wildcard host) — nothing that could break out of the policy or the attribute.
+pub fn is_valid_csp_source(source: &str) -> bool {
+    match source.strip_prefix("https://") {
+        Some(host) => {
+            let host = host.strip_prefix("*.").unwrap_or(host);
+            !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':')
+        }
+        None => false,
+    }
+}
+
+/// Build a Content-Security-Policy for `html`. Everything the page itself references is
+/// allowed automatically: inline scripts (the analytics snippet) by SHA-256 hash, origins
+/// they mention (analytics API host, plus its assets host) for scripts and connections,
+/// and origins of `<link>` tags (web fonts) for styles and fonts. `extra` origins are added
+/// to every fetch directive. Inline styles stay allowed because the site renders
+/// `style=""` attributes.
+pub fn build_content_security_policy(html: &str, extra: &[String]) -> Result<String, String> {
+    if let Some(bad) = extra.iter().find(|s| !is_valid_csp_source(s)) {
+        return Err(format!("Invalid CSP source \"{}\" (expected e.g. \"https://cdn.example.com\").", bad));
+    }
+
+    let mut script_hashes = Vec::new();
+    let mut script_origins = BTreeSet::new();
+    for (tag, content) in elements(html, "script") {
+        if tag.contains(" src=") {
+            script_origins.extend(https_origins(tag));
+        } else if !content.is_empty() {
+            let digest = Sha256::digest(content.as_bytes());
+            script_hashes.push(format!("'sha256-{}'", base64::engine::general_purpose::STANDARD.encode(digest)));
+            script_origins.extend(https_origins(content));
+        }
+    }
+    let companions: Vec<String> = script_origins.iter().filter_map(|o| companion_origins(o)).collect();
+    script_origins.extend(companions);
+    let link_origins: BTreeSet<String> = elements(html, "link").iter().flat_map(|(tag, _)| https_origins(tag)).collect();
+
+    let join = |parts: Vec<&str>| parts.into_iter().filter(|p| !p.is_empty()).collect::<Vec<_>>().join(" ");
+    let extra: Vec<&str> = extra.iter().map(String::as_str).collect();
+    let scripts: Vec<&str> = script_origins.iter().map(String::as_str).collect();
+    let links: Vec<&str> = link_origins.iter().map(String::as_str).collect();
+    let hashes: Vec<&str> = script_hashes.iter().map(String::as_str).collect();
+
+    let directives = [
+        ("default-src", join(vec!["'self'"])),
+        ("script-src", join([vec!["'self'"], hashes, scripts.clone(), extra.clone()].concat())),
+        ("style-src", join([vec!["'self'", "'unsafe-inline'"], links.clone(), extra.clone()].concat())),
+        ("font-src", join([vec!["'self'"], links, extra.clone()].concat())),
+        ("img-src", join([vec!["'self'", "data:", "blob:"], extra.clone()].concat())),
+        ("frame-src", join([vec!["'self'"], extra.clone()].concat())),
+        ("connect-src", join([vec!["'self'"], scripts, extra].concat())),
+        ("object-src", join(vec!["'none'"])),
+        ("base-uri", join(vec!["'self'"])),
+    ];
+    Ok(directives.iter().map(|(name, value)| format!("{} {}", name, value)).collect::<Vec<_>>().join("; "))
+}
+
+/// Apply the site's language, description and accessibility options to `index.html`.
+/// Idempotent: processing an already-processed document changes nothing.
+pub fn process_index_html(html: &str, options: &SiteHtmlOptions) -> Result<String, String> {
+    let mut out = html.to_string();
+
+    if !options.language.is_empty() {
+        if !is_valid_language_tag(&options.language) {
+            return Err(format!("Invalid site language \"{}\" (expected e.g. \"en\" or \"en-AU\").", options.language));
+        }
+        out = set_attribute(&out, "html", "lang", &options.language);
+    }
+
+    if !options.description.is_empty() && !out.contains("<meta name=\"description\"") {
+        let meta = format!("<meta name=\"description\" content=\"{}\">", escape_html(&options.description));
+        out = insert_before_head_end(&out, &meta);
+    }
+
+    if options.accessibility {
+        if !out.contains("class=\"skip-link\"") {
+            if let Some((_, body_end)) = find_start_tag(&out, "body") {
+                out = format!("{}\n  {}{}", &out[..body_end], SKIP_LINK, &out[body_end..]);
+            }
+        }
+        // The skip link's target must be focusable for screen readers to land on it
+        out = ensure_attribute(&out, "main", "tabindex", "-1");
+        out = ensure_attribute(&out, "main", "aria-label", "Galleries");
+        out = ensure_attribute(&out, "input", "aria-label", "Search galleries and photos");
+    }
+
+    // Last, so the policy covers everything injected above. Must precede every script.
+    if options.content_security_policy && !out.contains("http-equiv=\"Content-Security-Policy\"") {
+        let policy = build_content_security_policy(&out, &options.csp_extra_sources)?;
+        let meta = format!("<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">", escape_html(&policy));
+        out = insert_after_head_start(&out, &meta);
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n  <title>T</title>\n</head>\n<body class=\"motion\">\n  <header class=\"site-header\"><input id=\"search-input\" type=\"search\"></header>\n  <main id=\"app\"></main>\n</body>\n</html>\n";
+
+    fn options(language: &str, description: &str, accessibility: bool) -> SiteHtmlOptions {
+        SiteHtmlOptions { language: language.into(), description: description.into(), accessibility, ..Default::default() }
+    }
+
+    #[test]
+    fn test_defaults_leave_template_unchanged() {
+        assert_eq!(process_index_html(TEMPLATE, &SiteHtmlOptions::default()).unwrap(), TEMPLATE);
+    }
+
+    #[test]
+    fn test_language_and_description() {
+        let out = process_index_html(TEMPLATE, &options("en-AU", "Match photos & \"more\"", false)).unwrap();
+        assert!(out.contains("<html lang=\"en-AU\">"));
+        assert!(out.contains("  <meta name=\"description\" content=\"Match photos &amp; &quot;more&quot;\">\n</head>"));
+        // <header> must not be mistaken for <head>
+        assert!(out.contains("<header class=\"site-header\">"));
+        assert!(process_index_html(TEMPLATE, &options("en_AU", "", false)).is_err());
+    }
+
+    #[test]
+    fn test_accessibility_enhancements_idempotent() {
+        let opts = options("", "", true);
+        let out = process_index_html(TEMPLATE, &opts).unwrap();
+        assert!(out.contains("<body class=\"motion\">\n  <a class=\"skip-link\" href=\"#app\">Skip to content</a>"));
+        assert!(out.contains("<main id=\"app\" tabindex=\"-1\" aria-label=\"Galleries\">"));
+        assert!(out.contains("<input id=\"search-input\" type=\"search\" aria-label=\"Search galleries and photos\">"));
+        assert_eq!(process_index_html(&out, &opts).unwrap(), out);
+    }
+
+    #[test]
+    fn test_is_valid_language_tag() {
+        assert!(is_valid_language_tag("en"));
+        assert!(is_valid_language_tag("zh-Hant-TW"));
+        assert!(!is_valid_language_tag(""));
+        assert!(!is_valid_language_tag("en-"));
+        assert!(!is_valid_language_tag("en\" onload=\"x"));
+    }
+
+    #[test]
+    fn test_content_security_policy_allows_page_resources() {
+        let html = "<html>\n<head>\n  <link href=\"https://fonts.googleapis.com/css2?family=X\" rel=\"stylesheet\">\n  <script>posthog.init('k', { api_host: 'https://us.i.posthog.com' });</script>\n</head>\n<body><script src=\"afterglow/js/app.js\"></script></body>\n</html>\n";
+        let policy = build_content_security_policy(html, &[]).unwrap();
+        let hash = base64::engine::general_purpose::STANDARD
+            .encode(Sha256::digest(b"posthog.init('k', { api_host: 'https://us.i.posthog.com' });"));
+        assert!(policy.starts_with("default-src 'self'; "));
+        assert!(policy.contains(&format!(
+            "script-src 'self' 'sha256-{}' https://us-assets.i.posthog.com https://us.i.posthog.com;",
+            hash
+        )));
+        assert!(policy.contains("style-src 'self' 'unsafe-inline' https://fonts.googleapis.com;"));
+        assert!(policy.contains("connect-src 'self' https://us-assets.i.posthog.com https://us.i.posthog.com;"));
+        assert!(policy.contains("object-src 'none'"));
+
+        let extra = vec!["https://cdn.example.com".to_string()];
+        let policy = build_content_security_policy(html, &extra).unwrap();
+        assert!(policy.contains("img-src 'self' data: blob: https://cdn.example.com;"));
+        assert!(policy.contains("frame-src 'self' https://cdn.example.com;"));
+        assert!(build_content_security_policy(html, &["https://x.com; script-src *".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_csp_meta_injected_first_in_head() {
+        let opts = SiteHtmlOptions { content_security_policy: true, accessibility: true, ..Default::default() };
+        let out = process_index_html(TEMPLATE, &opts).unwrap();
+        assert!(out.contains("<head>\n  <meta http-equiv=\"Content-Security-Policy\" content=\"default-src &#39;self&#39;;"));
+        assert_eq!(process_index_html(&out, &opts).unwrap(), out);
+    }
+
+    #[test]
+    fn test_bundled_template_policy_whitelists_analytics() {
+        let html = std::str::from_utf8(include_bytes!("../../afterglow-website/index.html")).unwrap();
+        let policy = build_content_security_policy(html, &[]).unwrap();
+        assert!(policy.contains("https://us.i.posthog.com"));
+        assert!(policy.contains("https://fonts.gstatic.com"));
+        assert!(policy.contains("'sha256-"));
+    }
+}
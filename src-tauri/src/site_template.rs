@@ -0,0 +1,168 @@
+use crate::publish::WEBSITE_TEMPLATE;
+use crate::settings::AppSettings;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+/// The website template a site was published with, recorded as `template` in `site-manifest.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteTemplate {
+    /// App version the template shipped with.
+    pub version: String,
+    /// MD5 of the unprocessed template files; empty for manifests that only had `themeVersion`.
+    #[serde(default)]
+    pub hash: String,
+}
+
+/// What a publish does to the live site's template.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePlan {
+    /// From the live `site-manifest.json`; None on a first publish.
+    pub published: Option<SiteTemplate>,
+    pub bundled: SiteTemplate,
+    /// The live template differs from the bundled one and this publish replaces it.
+    pub upgrade: bool,
+    /// `siteTemplatePin` matches the live template, so its files are left as they are.
+    pub pinned: bool,
+}
+
+impl TemplatePlan {
+    /// The template the published manifest records.
+    pub(crate) fn published_after(&self) -> &SiteTemplate {
+        match (&self.published, self.pinned) {
+            (Some(published), true) => published,
+            _ => &self.bundled,
+        }
+    }
+}
+
+/// The template embedded in this build.
+pub fn bundled_template() -> SiteTemplate {
+    let mut hasher = Md5::new();
+    for file in WEBSITE_TEMPLATE {
+        hasher.update(file);
+    }
+    SiteTemplate { version: env!("CARGO_PKG_VERSION").to_string(), hash: format!("{:x}", hasher.finalize()) }
+}
+
+/// The template a published manifest records. Manifests from before `template` fall back to
+/// `themeVersion`, which was always the app version.
+pub(crate) fn template_from_manifest(manifest: &serde_json::Value) -> Option<SiteTemplate> {
+    if let Some(template) = manifest.get("template") {
+        return serde_json::from_value(template.clone()).ok();
+    }
+    let version = manifest.get("themeVersion")?.as_str()?;
+    Some(SiteTemplate { version: version.to_string(), hash: String::new() })
+}
+
+/// Read the template from `{s3_root}site-manifest.json`; None when there's no manifest yet.
+pub(crate) async fn published_template(
+    client: &aws_sdk_s3::Client,
+    bucket: &str,
+    s3_root: &str,
+) -> Result<Option<SiteTemplate>, String> {
+    let key = format!("{}site-manifest.json", s3_root);
+    let resp = match client.get_object().bucket(bucket).key(&key).send().await {
+        Ok(resp) => resp,
+        Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", key, e)),
+    };
+    let bytes = resp.body.collect().await.map_err(|e| format!("Failed to read {}: {}", key, e))?;
+    Ok(serde_json::from_slice(&bytes.into_bytes()).ok().as_ref().and_then(template_from_manifest))
+}
+
+/// Compare the live template with the bundled one under the `siteTemplatePin` setting. A pin
+/// can only keep the live template or accept the bundled one; any other version is refused,
+/// since this build can't produce it.
+pub(crate) fn plan_template(published: Option<SiteTemplate>, pin: &str) -> Result<TemplatePlan, String> {
+    let bundled = bundled_template();
+    let differs = published.as_ref().is_some_and(|p| {
+        if p.hash.is_empty() {
+            p.version != bundled.version
+        } else {
+            p.hash != bundled.hash
+        }
+    });
+    let pin = pin.trim();
+    let pinned = differs && !pin.is_empty() && published.as_ref().is_some_and(|p| p.version == pin);
+    if !pin.is_empty() && !pinned && pin != bundled.version && (differs || published.is_none()) {
+        let live = published.as_ref().map_or("no recorded template".to_string(), |p| format!("v{}", p.version));
+        return Err(format!(
+            "The site template is pinned to v{}, but the site has {} and this app bundles v{}. Unpin it in Settings to publish v{}.",
+            pin, live, bundled.version, bundled.version
+        ));
+    }
+    Ok(TemplatePlan { published, bundled, upgrade: differs && !pinned, pinned })
+}
+
+/// Template files under `s3_root`: left in place (and out of the delete list) while pinned.
+pub(crate) fn is_template_key(key: &str, s3_root: &str) -> bool {
+    key.strip_prefix(s3_root)
+        .is_some_and(|rel| matches!(rel, "index.html" | "favicon.ico" | "favicon.png") || rel.starts_with("afterglow/"))
+}
+
+/// `siteTemplatePin` is empty (follow the app) or a version like `2.4.0`.
+pub fn validate_template_pin(settings: &AppSettings) -> Result<(), String> {
+    let pin = settings.site_template_pin.trim();
+    if pin.len() > 64 || !pin.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')) {
+        return Err(format!("Invalid site template version \"{}\".", pin));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(version: &str, hash: &str) -> SiteTemplate {
+        SiteTemplate { version: version.to_string(), hash: hash.to_string() }
+    }
+
+    #[test]
+    fn test_template_from_manifest() {
+        let manifest = serde_json::json!({ "themeVersion": "2.0.0", "template": { "version": "2.0.0", "hash": "abc" } });
+        assert_eq!(template_from_manifest(&manifest), Some(template("2.0.0", "abc")));
+        let legacy = serde_json::json!({ "themeVersion": "1.9.0" });
+        assert_eq!(template_from_manifest(&legacy), Some(template("1.9.0", "")));
+        assert_eq!(template_from_manifest(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn test_plan_template() {
+        let bundled = bundled_template();
+        let first = plan_template(None, "").unwrap();
+        assert!(!first.upgrade && !first.pinned);
+        assert_eq!(first.published_after(), &bundled);
+
+        let current = plan_template(Some(bundled.clone()), "1.0.0").unwrap();
+        assert!(!current.upgrade && !current.pinned);
+
+        let old = template("1.0.0", "abc");
+        let upgrade = plan_template(Some(old.clone()), "").unwrap();
+        assert!(upgrade.upgrade && !upgrade.pinned);
+        assert_eq!(upgrade.published_after(), &bundled);
+
+        let pinned = plan_template(Some(old.clone()), "1.0.0").unwrap();
+        assert!(!pinned.upgrade && pinned.pinned);
+        assert_eq!(pinned.published_after(), &old);
+
+        // A legacy manifest at the bundled version counts as current
+        let legacy = plan_template(Some(template(&bundled.version, "")), "").unwrap();
+        assert!(!legacy.upgrade);
+
+        let err = plan_template(Some(old), "0.9.0").unwrap_err();
+        assert!(err.contains("pinned to v0.9.0") && err.contains("has v1.0.0"));
+        assert!(plan_template(None, "0.9.0").unwrap_err().contains("no recorded template"));
+        assert!(plan_template(None, &bundled.version).is_ok());
+    }
+
+    #[test]
+    fn test_is_template_key() {
+        assert!(is_template_key("site/index.html", "site/"));
+        assert!(is_template_key("site/afterglow/js/old.js", "site/"));
+        assert!(is_template_key("favicon.png", ""));
+        assert!(!is_template_key("site/galleries/index.html", "site/"));
+        assert!(!is_template_key("site/site-manifest.json", "site/"));
+    }
+}
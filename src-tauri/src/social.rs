@@ -0,0 +1,257 @@
+use crate::encryption::readable_original;
+use crate::workspace::read_gallery_details;
+use image::codecs::jpeg::JpegEncoder;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Width of exported social crops. 1080 px is the width Instagram displays for all
+/// three aspect ratios; larger exports are downscaled server-side anyway.
+const SOCIAL_EXPORT_WIDTH: u32 = 1080;
+const SOCIAL_EXPORT_QUALITY: u8 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SocialRatio {
+    /// 1:1 feed post
+    Square,
+    /// 4:5 portrait feed post
+    Portrait,
+    /// 9:16 story / reel
+    Story,
+}
+
+impl SocialRatio {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        match input.trim() {
+            "1:1" => Ok(SocialRatio::Square),
+            "4:5" => Ok(SocialRatio::Portrait),
+            "9:16" => Ok(SocialRatio::Story),
+            other => Err(format!("Unsupported crop ratio '{}'. Use 1:1, 4:5 or 9:16.", other)),
+        }
+    }
+
+    /// (width, height) parts of the ratio.
+    pub fn parts(self) -> (u32, u32) {
+        match self {
+            SocialRatio::Square => (1, 1),
+            SocialRatio::Portrait => (4, 5),
+            SocialRatio::Story => (9, 16),
+        }
+    }
+
+    /// Filename suffix, e.g. "4x5".
+    pub fn label(self) -> String {
+        let (w, h) = self.parts();
+        format!("{}x{}", w, h)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocialExportResult {
+    pub output_dir: String,
+    pub exported: Vec<String>,
+    pub errors: Vec<SocialExportError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocialExportError {
+    pub filename: String,
+    pub error: String,
+}
+
+/// Compute the largest crop rectangle `(x, y, width, height)` with the given aspect ratio
+/// that fits inside a `width`×`height` image, centred as close to the focal point
+/// `(fx, fy)` (normalised 0–1) as the image bounds allow.
+pub fn compute_crop(width: u32, height: u32, ratio: SocialRatio, fx: f64, fy: f64) -> (u32, u32, u32, u32) {
+    let (rw, rh) = ratio.parts();
+    // Try full width first; fall back to full height when that would be too tall.
+    let (crop_w, crop_h) = if (width as u64) * (rh as u64) / (rw as u64) <= height as u64 {
+        (width, ((width as u64) * (rh as u64) / (rw as u64)) as u32)
+    } else {
+        (((height as u64) * (rw as u64) / (rh as u64)) as u32, height)
+    };
+
+    let fx = fx.clamp(0.0, 1.0);
+    let fy = fy.clamp(0.0, 1.0);
+    let centre_x = fx * width as f64;
+    let centre_y = fy * height as f64;
+    let max_x = (width - crop_w) as f64;
+    let max_y = (height - crop_h) as f64;
+    let x = (centre_x - crop_w as f64 / 2.0).clamp(0.0, max_x).round() as u32;
+    let y = (centre_y - crop_h as f64 / 2.0).clamp(0.0, max_y).round() as u32;
+    (x, y, crop_w, crop_h)
+}
+
+/// Read a photo's focal point from its `gallery-details.json` entry.
+/// `focalPoint` is optional (`{ "x": 0.0–1.0, "y": 0.0–1.0 }`); the centre is used when absent.
+fn focal_point(photo: &serde_json::Value) -> (f64, f64) {
+    let fp = photo.get("focalPoint");
+    let x = fp.and_then(|f| f.get("x")).and_then(|v| v.as_f64()).unwrap_or(0.5);
+    let y = fp.and_then(|f| f.get("y")).and_then(|v| v.as_f64()).unwrap_or(0.5);
+    (x, y)
+}
+
+/// Crop `source` to `ratio` around the focal point, downscale to the export width and
+/// write a JPEG to `dest`.
+fn export_crop(source: &Path, dest: &Path, ratio: SocialRatio, focal: (f64, f64)) -> Result<(), String> {
+    let img = image::open(source)
+        .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    let (x, y, w, h) = compute_crop(img.width(), img.height(), ratio, focal.0, focal.1);
+    let mut cropped = img.crop_imm(x, y, w, h);
+    if cropped.width() > SOCIAL_EXPORT_WIDTH {
+        let (rw, rh) = ratio.parts();
+        let target_h = SOCIAL_EXPORT_WIDTH * rh / rw;
+        cropped = cropped.resize_exact(
+            SOCIAL_EXPORT_WIDTH,
+            target_h,
+            image::imageops::FilterType::Lanczos3,
+        );
+    }
+
+    let tmp = dest.with_extension("jpg.tmp");
+    let file = fs::File::create(&tmp)
+        .map_err(|e| format!("Failed to create {}: {}", tmp.display(), e))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let encoder = JpegEncoder::new_with_quality(&mut writer, SOCIAL_EXPORT_QUALITY);
+    cropped
+        .to_rgb8()
+        .write_with_encoder(encoder)
+        .map_err(|e| format!("Failed to encode {}: {}", dest.display(), e))?;
+    drop(writer);
+    fs::rename(&tmp, dest)
+        .map_err(|e| format!("Failed to rename to {}: {}", dest.display(), e))?;
+    Ok(())
+}
+
+/// Export social-media crops (1:1, 4:5 or 9:16) of the given photos in a gallery.
+///
+/// Crops honour each photo's optional `focalPoint`. Output goes to `output_dir`, or
+/// `{workspace}/.data/exports/social/{slug}/` when omitted, as `{stem}-{ratio}.jpg`.
+/// Per-file failures are non-fatal and reported in `errors`.
+#[tauri::command]
+pub async fn export_social_crops(
+    workspace_path: String,
+    slug: String,
+    filenames: Vec<String>,
+    ratio: String,
+    output_dir: Option<String>,
+) -> Result<SocialExportResult, String> {
+    let ratio = SocialRatio::parse(&ratio)?;
+    let root = PathBuf::from(&workspace_path);
+    let output_dir = match output_dir {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => root.join(".data").join("exports").join("social").join(&slug),
+    };
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    let photos: Vec<serde_json::Value> = read_gallery_details(&root, &slug)?
+        .and_then(|d| d.get("photos").and_then(|p| p.as_array()).cloned())
+        .unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || export_crops(&root, &slug, &photos, &filenames, ratio, &output_dir))
+        .await
+        .map_err(|e| format!("Social export panicked: {}", e))
+}
+
+/// Crop `filenames` of gallery `slug` into `output_dir`, reading originals of encrypted
+/// galleries from their decrypted copies. Blocking.
+pub(crate) fn export_crops(
+    root: &Path,
+    slug: &str,
+    photos: &[serde_json::Value],
+    filenames: &[String],
+    ratio: SocialRatio,
+    output_dir: &Path,
+) -> SocialExportResult {
+    let mut exported = Vec::new();
+    let mut errors = Vec::new();
+    for filename in filenames {
+        let photo = photos.iter().find(|p| {
+            p.get("full").and_then(|v| v.as_str()) == Some(filename.as_str())
+        });
+        let focal = photo.map(focal_point).unwrap_or((0.5, 0.5));
+        let source = readable_original(&root.join(slug).join(filename));
+        let stem = Path::new(filename)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| filename.clone());
+        let dest = output_dir.join(format!("{}-{}.jpg", stem, ratio.label()));
+        match export_crop(&source, &dest, ratio, focal) {
+            Ok(()) => exported.push(dest.to_string_lossy().to_string()),
+            Err(error) => errors.push(SocialExportError { filename: filename.clone(), error }),
+        }
+    }
+    SocialExportResult {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        exported,
+        errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_ratio() {
+        assert_eq!(SocialRatio::parse("1:1").unwrap(), SocialRatio::Square);
+        assert_eq!(SocialRatio::parse("4:5").unwrap(), SocialRatio::Portrait);
+        assert_eq!(SocialRatio::parse(" 9:16 ").unwrap(), SocialRatio::Story);
+        assert!(SocialRatio::parse("16:9").is_err());
+        assert_eq!(SocialRatio::Portrait.label(), "4x5");
+    }
+
+    #[test]
+    fn test_compute_crop_landscape_square_centred() {
+        // 3:2 landscape → full-height square in the middle
+        assert_eq!(compute_crop(3000, 2000, SocialRatio::Square, 0.5, 0.5), (500, 0, 2000, 2000));
+    }
+
+    #[test]
+    fn test_compute_crop_follows_focal_point() {
+        // Focal point at the far left clamps the crop to the left edge
+        assert_eq!(compute_crop(3000, 2000, SocialRatio::Square, 0.0, 0.5), (0, 0, 2000, 2000));
+        // Focal point at 80% across shifts the crop right, clamped to the image edge
+        assert_eq!(compute_crop(3000, 2000, SocialRatio::Square, 0.8, 0.5), (1000, 0, 2000, 2000));
+    }
+
+    #[test]
+    fn test_compute_crop_portrait_source_story() {
+        // 2:3 portrait → 9:16 uses full height
+        let (x, y, w, h) = compute_crop(2000, 3000, SocialRatio::Story, 0.5, 0.5);
+        assert_eq!((w, h), (1687, 3000));
+        assert_eq!(y, 0);
+        assert_eq!(x, 157);
+    }
+
+    #[test]
+    fn test_compute_crop_portrait_4x5_on_tall_image_uses_full_width() {
+        let (x, y, w, h) = compute_crop(1000, 3000, SocialRatio::Portrait, 0.5, 0.1);
+        assert_eq!((x, w, h), (0, 1000, 1250));
+        assert_eq!(y, 0); // focal point near top clamps to the top edge
+    }
+
+    #[test]
+    fn test_focal_point_defaults_to_centre() {
+        let photo = serde_json::json!({ "full": "01.jpg" });
+        assert_eq!(focal_point(&photo), (0.5, 0.5));
+        let photo = serde_json::json!({ "full": "01.jpg", "focalPoint": { "x": 0.25, "y": 0.75 } });
+        assert_eq!(focal_point(&photo), (0.25, 0.75));
+    }
+
+    #[test]
+    fn test_export_crop_writes_resized_jpeg() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("wide.jpg");
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::new(3000, 2000));
+        img.save(&src).unwrap();
+        let dest = tmp.path().join("wide-4x5.jpg");
+        export_crop(&src, &dest, SocialRatio::Portrait, (0.5, 0.5)).unwrap();
+        let out = image::open(&dest).unwrap();
+        assert_eq!((out.width(), out.height()), (1080, 1350));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::workspace::write_json_atomic;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STAMP_STORE_VERSION: u32 = 1;
+/// Key injected into published JSON objects.
+pub const STAMP_FIELD: &str = "publishStamp";
+
+/// Version stamp of a published JSON file: a hash of its content (without the stamp) and a
+/// generation counter that increments each time a publish changes that content.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishStamp {
+    pub hash: String,
+    pub generation: u64,
+}
+
+/// Stamps of the last successful publish, keyed by path relative to the S3 root
+/// (e.g. "galleries/sunset/gallery-details.json").
+pub type StampMap = BTreeMap<String, PublishStamp>;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StampStore {
+    version: u32,
+    files: StampMap,
+}
+
+/// `{workspace}/.data/publish-stamps.json`
+fn stamp_store_path(root: &Path) -> PathBuf {
+    root.join(".data").join("publish-stamps.json")
+}
+
+/// Load the stamps recorded by the last successful publish. Missing/unreadable → empty,
+/// which simply restarts every generation at 1.
+pub fn load_stamps(root: &Path) -> StampMap {
+    fs::read_to_string(stamp_store_path(root))
+        .ok()
+        .and_then(|c| serde_json::from_str::<StampStore>(&c).ok())
+        .map(|s| s.files)
+        .unwrap_or_default()
+}
+
+pub fn save_stamps(root: &Path, files: &StampMap) -> Result<(), String> {
+    let store = StampStore { version: STAMP_STORE_VERSION, files: files.clone() };
+    let value = serde_json::to_value(&store).map_err(|e| e.to_string())?;
+    write_json_atomic(&stamp_store_path(root), &value)
+}
+
+fn md5_hex(content: &[u8]) -> String {
+    format!("{:x}", Md5::digest(content))
+}
+
+/// Stamp for `content` given the previously published stamp: same generation when the
+/// content hash is unchanged, otherwise the next one.
+pub fn next_stamp(previous: Option<&PublishStamp>, content: &[u8]) -> PublishStamp {
+    let hash = md5_hex(content);
+    let generation = match previous {
+        Some(p) if p.hash == hash => p.generation,
+        Some(p) => p.generation + 1,
+        None => 1,
+    };
+    PublishStamp { hash, generation }
+}
+
+/// Inject `stamp` into a JSON document as `publishStamp`. Documents whose root is not an
+/// object (legacy array-format `galleries.json`) are returned unchanged.
+pub fn stamp_json(content: &[u8], stamp: &PublishStamp) -> Result<Vec<u8>, String> {
+    let mut value: serde_json::Value = serde_json::from_slice(content).map_err(|e| e.to_string())?;
+    match value.as_object_mut() {
+        Some(obj) => {
+            let stamp_value = serde_json::to_value(stamp).map_err(|e| e.to_string())?;
+            obj.insert(STAMP_FIELD.to_string(), stamp_value);
+            serde_json::to_vec_pretty(&value).map_err(|e| e.to_string())
+        }
+        None => Ok(content.to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_next_stamp_generation() {
+        let first = next_stamp(None, b"{}");
+        assert_eq!(first.generation, 1);
+        assert_eq!(first.hash, "99914b932bd37a50b983c5e7c90ae93b");
+        assert_eq!(next_stamp(Some(&first), b"{}"), first, "unchanged content keeps its stamp");
+        let second = next_stamp(Some(&first), b"{\"a\":1}");
+        assert_eq!(second.generation, 2);
+        assert_ne!(second.hash, first.hash);
+    }
+
+    #[test]
+    fn test_stamp_json_object_and_array() {
+        let stamp = PublishStamp { hash: "abc".to_string(), generation: 3 };
+        let out = stamp_json(br#"{"galleries":[]}"#, &stamp).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["publishStamp"]["hash"], "abc");
+        assert_eq!(value["publishStamp"]["generation"], 3);
+        assert_eq!(value["galleries"], serde_json::json!([]));
+
+        let legacy = br#"[{"slug":"a"}]"#;
+        assert_eq!(stamp_json(legacy, &stamp).unwrap(), legacy.to_vec());
+        assert!(stamp_json(b"not json", &stamp).is_err());
+    }
+
+    #[test]
+    fn test_stamps_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        assert!(load_stamps(tmp.path()).is_empty());
+        let mut files = StampMap::new();
+        files.insert("galleries/galleries.json".to_string(), PublishStamp { hash: "h".into(), generation: 2 });
+        save_stamps(tmp.path(), &files).unwrap();
+        assert_eq!(load_stamps(tmp.path()), files);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::i18n::{error, ErrorCode};
+use crate::list_directory;
+use crate::thumbnails::parse_galleries_array;
+use crate::workspace::{read_gallery_details, str_field, write_json_atomic};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Photos included per gallery when the caller doesn't ask for a count.
+const DEFAULT_PREVIEW_PHOTOS: usize = 6;
+/// Bumped when `WorkspaceSummary` changes shape; older caches are ignored.
+pub(crate) const SUMMARY_CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GallerySummary {
+    pub slug: String,
+    /// `name` from `gallery-details.json`, falling back to the `galleries.json` entry.
+    pub name: String,
+    pub cover: String,
+    /// Absolute path of the cover's cached WebP thumbnail, when one has been generated.
+    #[serde(default)]
+    pub cover_thumbnail: Option<String>,
+    /// Photos listed in `gallery-details.json` (0 when it doesn't exist yet).
+    pub tracked: usize,
+    /// Image files in the gallery folder.
+    pub total: usize,
+    pub has_details: bool,
+    /// The first photo entries of `gallery-details.json`, as stored.
+    pub photos: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    /// `galleries.json` as stored (legacy array or current format); `None` when it doesn't exist.
+    pub galleries_json: Option<serde_json::Value>,
+    /// Non-hidden folders of the workspace root.
+    pub subdirectories: Vec<String>,
+    /// One entry per gallery whose folder and details could be read, in `galleries.json` order.
+    pub galleries: Vec<GallerySummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryCache {
+    version: u32,
+    summary: WorkspaceSummary,
+}
+
+/// `{workspace}/.data/summary.json`
+fn summary_cache_path(root: &Path) -> PathBuf {
+    root.join(".data").join("summary.json")
+}
+
+/// The summary saved by the last scan. Missing, unreadable or outdated → `None`.
+fn load_summary_cache(root: &Path) -> Option<WorkspaceSummary> {
+    let content = fs::read_to_string(summary_cache_path(root)).ok()?;
+    let cache: SummaryCache = serde_json::from_str(&content).ok()?;
+    (cache.version == SUMMARY_CACHE_VERSION).then_some(cache.summary)
+}
+
+fn save_summary_cache(root: &Path, summary: &WorkspaceSummary) -> Result<(), String> {
+    let cache = SummaryCache { version: SUMMARY_CACHE_VERSION, summary: summary.clone() };
+    let value = serde_json::to_value(&cache).map_err(|e| e.to_string())?;
+    write_json_atomic(&summary_cache_path(root), &value)
+}
+
+/// `.data/thumbnails/{dir}/{stem}.webp` for a cover like `sunset/01.jpg`, if it exists.
+fn cached_cover_thumbnail(root: &Path, cover: &str) -> Option<String> {
+    let cover = Path::new(cover);
+    let stem = cover.file_stem()?.to_str()?;
+    let path = root.join(".data").join("thumbnails").join(cover.parent()?).join(format!("{}.webp", stem));
+    path.is_file().then(|| path.to_string_lossy().to_string())
+}
+
+/// Summarise one gallery; `Err` when its folder or `gallery-details.json` can't be read.
+fn summarize_gallery(root: &Path, entry: &serde_json::Value, preview_photos: usize) -> Result<GallerySummary, String> {
+    let slug = str_field(entry, "slug");
+    let total = list_directory(&root.join(&slug))?.images.len();
+    let details = read_gallery_details(root, &slug)?;
+    let photos = details
+        .as_ref()
+        .and_then(|d| d.get("photos"))
+        .and_then(|p| p.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let name = details.as_ref().map(|d| str_field(d, "name")).filter(|n| !n.is_empty());
+    let cover = str_field(entry, "cover");
+    Ok(GallerySummary {
+        name: name.unwrap_or_else(|| str_field(entry, "name")),
+        cover_thumbnail: cached_cover_thumbnail(root, &cover),
+        cover,
+        tracked: photos.len(),
+        total,
+        has_details: details.is_some(),
+        photos: photos.into_iter().take(preview_photos).collect(),
+        slug,
+    })
+}
+
+fn build_summary(root: &Path, preview_photos: usize) -> Result<WorkspaceSummary, String> {
+    let subdirectories = list_directory(root)?.directories;
+    let path = root.join("galleries.json");
+    let galleries_json: Option<serde_json::Value> = if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| error(ErrorCode::GalleriesJsonReadFailed, &[("error", &e)]))?;
+        Some(serde_json::from_str(&content).map_err(|e| error(ErrorCode::GalleriesJsonParseFailed, &[("error", &e)]))?)
+    } else {
+        None
+    };
+    let galleries = galleries_json
+        .as_ref()
+        .map(parse_galleries_array)
+        .unwrap_or_default()
+        .iter()
+        .filter(|g| !str_field(g, "slug").is_empty())
+        // A gallery whose folder is missing or whose details don't parse is left out, as before
+        .filter_map(|g| summarize_gallery(root, g, preview_photos).ok())
+        .collect();
+    Ok(WorkspaceSummary { galleries_json, subdirectories, galleries })
+}
+
+/// Everything the sidebar needs when a workspace opens, in one call: the raw `galleries.json`,
+/// the root's subdirectories and per-gallery counts, cover and the first `preview_photos`
+/// photos (default 6), replacing a directory scan and JSON read per gallery. The result is
+/// saved to `.data/summary.json` for `load_cached_workspace_summary`.
+#[tauri::command]
+pub async fn load_workspace_summary(
+    workspace_path: String,
+    preview_photos: Option<usize>,
+) -> Result<WorkspaceSummary, String> {
+    let root = Path::new(&workspace_path);
+    if !root.is_dir() {
+        return Err(error(ErrorCode::NotADirectory, &[("path", &workspace_path)]));
+    }
+    let summary = build_summary(root, preview_photos.unwrap_or(DEFAULT_PREVIEW_PHOTOS))?;
+    // The cache only speeds up the next start; failing to write it isn't an error
+    let _ = save_summary_cache(root, &summary);
+    Ok(summary)
+}
+
+/// The summary saved by the last `load_workspace_summary`, without touching the galleries, so a
+/// large workspace can be shown instantly while a fresh scan runs. `None` when there is no
+/// usable cache.
+#[tauri::command]
+pub async fn load_cached_workspace_summary(workspace_path: String) -> Result<Option<WorkspaceSummary>, String> {
+    Ok(load_summary_cache(Path::new(&workspace_path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build_summary() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("galleries.json"),
+            json!({"schemaVersion": 1, "galleries": [
+                {"slug": "a", "name": "Index A", "cover": "a/01.jpg"},
+                {"slug": "b", "name": "Index B", "cover": ""},
+                {"slug": "gone", "name": "Gone", "cover": ""}
+            ]})
+            .to_string(),
+        )
+        .unwrap();
+        for dir in ["a", "b", "untracked", ".hidden"] {
+            fs::create_dir(root.join(dir)).unwrap();
+        }
+        for file in ["01.jpg", "02.jpg", "03.png", "notes.txt"] {
+            fs::write(root.join("a").join(file), "x").unwrap();
+        }
+        fs::write(
+            root.join("a/gallery-details.json"),
+            json!({"name": "Details A", "photos": [{"full": "01.jpg"}, {"full": "02.jpg"}]}).to_string(),
+        )
+        .unwrap();
+
+        let summary = build_summary(root, 1).unwrap();
+        assert_eq!(summary.subdirectories, vec!["a", "b", "untracked"]);
+        assert_eq!(summary.galleries_json.unwrap()["schemaVersion"], 1);
+        assert_eq!(summary.galleries.len(), 2);
+        let a = &summary.galleries[0];
+        assert_eq!((a.name.as_str(), a.tracked, a.total, a.has_details), ("Details A", 2, 3, true));
+        assert_eq!(a.photos, vec![json!({"full": "01.jpg"})]);
+        let b = &summary.galleries[1];
+        assert_eq!((b.name.as_str(), b.tracked, b.total, b.has_details), ("Index B", 0, 0, false));
+    }
+
+    #[test]
+    fn test_build_summary_without_galleries_json() {
+        let tmp = TempDir::new().unwrap();
+        let summary = build_summary(tmp.path(), DEFAULT_PREVIEW_PHOTOS).unwrap();
+        assert!(summary.galleries_json.is_none());
+        assert!(summary.galleries.is_empty());
+    }
+
+    #[test]
+    fn test_summary_cache_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        assert!(load_summary_cache(root).is_none());
+        fs::create_dir(root.join("a")).unwrap();
+        fs::write(root.join("galleries.json"), json!([{"slug": "a", "cover": "a/01.jpg"}]).to_string()).unwrap();
+        fs::create_dir_all(root.join(".data/thumbnails/a")).unwrap();
+        fs::write(root.join(".data/thumbnails/a/01.webp"), "x").unwrap();
+
+        let summary = build_summary(root, DEFAULT_PREVIEW_PHOTOS).unwrap();
+        let thumb = root.join(".data/thumbnails/a/01.webp").to_string_lossy().to_string();
+        assert_eq!(summary.galleries[0].cover_thumbnail, Some(thumb));
+        save_summary_cache(root, &summary).unwrap();
+        let cached = load_summary_cache(root).unwrap();
+        assert_eq!(cached.galleries, summary.galleries);
+        assert_eq!(cached.subdirectories, vec!["a"]);
+
+        // A cache from another version is ignored
+        let stale = json!({"version": SUMMARY_CACHE_VERSION + 1, "summary": cached});
+        write_json_atomic(&summary_cache_path(root), &stale).unwrap();
+        assert!(load_summary_cache(root).is_none());
+    }
+}
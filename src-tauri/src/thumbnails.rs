@@ -1,10 +1,21 @@
-use std::collections::HashSet;
+use crate::encryption::readable_original;
+use image::codecs::jpeg::JpegEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// Longest edge of published thumbnails, in pixels.
+const THUMBNAIL_MAX_EDGE: u32 = 800;
+/// WebP quality of published thumbnails.
+const THUMBNAIL_QUALITY: u8 = 85;
+/// Most candidates `compare_thumbnail_settings` encodes in one call.
+const MAX_COMPARE_CANDIDATES: usize = 8;
+
 #[derive(Debug, Clone)]
 pub struct ThumbnailSpec {
+    /// Original in the workspace; read through `readable_original` for encrypted galleries.
     pub source_path: PathBuf,
     pub dest_path: PathBuf,
     /// S3 key, e.g. "galleries/sunset/.thumbs/01.webp"
@@ -20,6 +31,8 @@ pub struct ThumbnailResults {
     pub generated: usize,
     #[allow(dead_code)]
     pub skipped: usize,
+    /// Thumbnails written by this run (`dest_path`s); fresh ones aren't included.
+    pub generated_paths: Vec<PathBuf>,
     pub errors: Vec<(PathBuf, String)>,
 }
 
@@ -64,7 +77,7 @@ pub fn build_thumbnail_specs(
         if let Some(cover) = gallery.get("cover").and_then(|v| v.as_str()) {
             if !cover.is_empty() {
                 let source_path = root.join(cover);
-                if source_path.exists() && source_path.is_file() {
+                if readable_original(&source_path).is_file() {
                     let cover_path = Path::new(cover);
                     // parent dir of cover path (e.g. "sunset" for "sunset/01.jpg")
                     let cover_dir = cover_path
@@ -104,7 +117,7 @@ pub fn build_thumbnail_specs(
                             {
                                 if !thumbnail.is_empty() {
                                     let source_path = root.join(slug).join(thumbnail);
-                                    if source_path.exists() && source_path.is_file() {
+                                    if readable_original(&source_path).is_file() {
                                         let thumb_path = Path::new(thumbnail);
                                         if let Some(stem) =
                                             thumb_path.file_stem().and_then(|s| s.to_str())
@@ -155,6 +168,16 @@ pub fn is_thumbnail_fresh(source: &Path, dest: &Path) -> bool {
     dest_mtime >= source_mtime
 }
 
+/// Resize `img` to fit within `max_edge` × `max_edge`, preserving aspect ratio. Images already
+/// within the limit are returned unchanged.
+fn downscale(img: image::DynamicImage, max_edge: u32) -> image::DynamicImage {
+    if img.width() > max_edge || img.height() > max_edge {
+        img.resize(max_edge, max_edge, image::imageops::FilterType::Lanczos3)
+    } else {
+        img
+    }
+}
+
 /// Generate a lossy WebP thumbnail from `source` and write it atomically to `dest`.
 ///
 /// Downscales to a maximum of 800 px on the longest side (preserving aspect ratio).
@@ -168,15 +191,11 @@ pub fn generate_thumbnail(source: &Path, dest: &Path) -> Result<(), String> {
     let img = image::open(source)
         .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
 
-    let resized = if img.width() > 800 || img.height() > 800 {
-        img.resize(800, 800, image::imageops::FilterType::Lanczos3)
-    } else {
-        img
-    };
+    let resized = downscale(img, THUMBNAIL_MAX_EDGE);
 
     let encoder = webp::Encoder::from_image(&resized)
         .map_err(|e| format!("WebP encoder error for {}: {}", source.display(), e))?;
-    let webp_data = encoder.encode(85.0);
+    let webp_data = encoder.encode(THUMBNAIL_QUALITY as f32);
 
     // Atomic write: .tmp → rename
     let tmp = dest.with_extension("webp.tmp");
@@ -205,20 +224,25 @@ where
     let mut generated = 0;
     let mut skipped = 0;
     let mut errors = Vec::new();
+    let mut generated_paths = Vec::new();
 
     for (i, spec) in specs.iter().enumerate() {
-        if is_thumbnail_fresh(&spec.source_path, &spec.dest_path) {
+        let source = readable_original(&spec.source_path);
+        if is_thumbnail_fresh(&source, &spec.dest_path) {
             skipped += 1;
         } else {
-            match generate_thumbnail(&spec.source_path, &spec.dest_path) {
-                Ok(()) => generated += 1,
+            match generate_thumbnail(&source, &spec.dest_path) {
+                Ok(()) => {
+                    generated += 1;
+                    generated_paths.push(spec.dest_path.clone());
+                }
                 Err(e) => errors.push((spec.source_path.clone(), e)),
             }
         }
         on_progress(i + 1, total, spec);
     }
 
-    ThumbnailResults { generated, skipped, errors }
+    ThumbnailResults { generated, skipped, generated_paths, errors }
 }
 
 /// Delete any `.webp` files in `thumbnail_cache_root` that are not listed in `specs`.
@@ -276,6 +300,118 @@ pub fn cleanup_stale_thumbnails(thumbnail_cache_root: &Path, specs: &[ThumbnailS
     deleted
 }
 
+/// One size/quality/format combination to try in `compare_thumbnail_settings`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailCandidate {
+    pub max_edge: u32,
+    /// 1–100; ignored for PNG.
+    pub quality: u8,
+    /// "webp", "jpeg" or "png".
+    pub format: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailComparison {
+    pub candidate: ThumbnailCandidate,
+    /// The settings publishing uses today.
+    pub current: bool,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u64,
+    /// Encoded preview in the temp directory, for the frontend to show side by side.
+    pub preview_path: String,
+    /// Set instead of the fields above when this candidate couldn't be encoded.
+    pub error: Option<String>,
+}
+
+fn validate_candidate(candidate: &ThumbnailCandidate) -> Result<&'static str, String> {
+    if !(16..=4096).contains(&candidate.max_edge) {
+        return Err(format!("Thumbnail size must be between 16 and 4096 px, not {}.", candidate.max_edge));
+    }
+    if !(1..=100).contains(&candidate.quality) {
+        return Err(format!("Thumbnail quality must be between 1 and 100, not {}.", candidate.quality));
+    }
+    match candidate.format.as_str() {
+        "webp" => Ok("webp"),
+        "jpeg" => Ok("jpg"),
+        "png" => Ok("png"),
+        other => Err(format!("Unknown thumbnail format \"{}\" (use webp, jpeg or png).", other)),
+    }
+}
+
+fn encode_candidate(img: &image::DynamicImage, candidate: &ThumbnailCandidate) -> Result<Vec<u8>, String> {
+    match candidate.format.as_str() {
+        "webp" => {
+            let encoder = webp::Encoder::from_image(img).map_err(|e| format!("WebP encoder error: {}", e))?;
+            Ok(encoder.encode(candidate.quality as f32).to_vec())
+        }
+        "jpeg" => {
+            let mut bytes = Vec::new();
+            JpegEncoder::new_with_quality(&mut bytes, candidate.quality)
+                .encode_image(&img.to_rgb8())
+                .map_err(|e| format!("JPEG encoder error: {}", e))?;
+            Ok(bytes)
+        }
+        _ => {
+            let mut bytes = std::io::Cursor::new(Vec::new());
+            img.write_to(&mut bytes, image::ImageFormat::Png).map_err(|e| format!("PNG encoder error: {}", e))?;
+            Ok(bytes.into_inner())
+        }
+    }
+}
+
+/// Encode `source` with each candidate into `out_dir`, resizing once per distinct size.
+fn compare_candidates(source: &Path, candidates: &[ThumbnailCandidate], out_dir: &Path) -> Result<Vec<ThumbnailComparison>, String> {
+    if candidates.is_empty() || candidates.len() > MAX_COMPARE_CANDIDATES {
+        return Err(format!("Compare between 1 and {} thumbnail settings at a time.", MAX_COMPARE_CANDIDATES));
+    }
+    let extensions = candidates.iter().map(validate_candidate).collect::<Result<Vec<_>, _>>()?;
+    let img = image::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+
+    let mut resized: HashMap<u32, image::DynamicImage> = HashMap::new();
+    let mut results = Vec::new();
+    for (i, (candidate, ext)) in candidates.iter().zip(extensions).enumerate() {
+        let img = resized.entry(candidate.max_edge).or_insert_with(|| downscale(img.clone(), candidate.max_edge));
+        let preview_path = out_dir.join(format!("candidate-{}.{}", i + 1, ext));
+        let current = candidate.format == "webp" && candidate.max_edge == THUMBNAIL_MAX_EDGE && candidate.quality == THUMBNAIL_QUALITY;
+        let written = encode_candidate(img, candidate).and_then(|bytes| {
+            fs::write(&preview_path, &bytes).map_err(|e| format!("Failed to write {}: {}", preview_path.display(), e))?;
+            Ok(bytes.len() as u64)
+        });
+        let (size_bytes, error) = match written {
+            Ok(size) => (size, None),
+            Err(e) => (0, Some(e)),
+        };
+        results.push(ThumbnailComparison {
+            candidate: candidate.clone(),
+            current,
+            width: img.width(),
+            height: img.height(),
+            size_bytes,
+            preview_path: preview_path.to_string_lossy().to_string(),
+            error,
+        });
+    }
+    Ok(results)
+}
+
+/// Encode the image at `path` with each candidate size/quality/format and return the file sizes
+/// plus preview files to judge by eye. Previews go to a fresh folder under the temp directory;
+/// the previous comparison's folder is removed, so its paths stop working.
+#[tauri::command]
+pub async fn compare_thumbnail_settings(path: String, candidates: Vec<ThumbnailCandidate>) -> Result<Vec<ThumbnailComparison>, String> {
+    let base = std::env::temp_dir().join("afterglow-manager-thumbnail-compare");
+    let _ = fs::remove_dir_all(&base);
+    // A new folder per call so the webview doesn't show cached images under reused names
+    let out_dir = base.join(uuid::Uuid::new_v4().to_string());
+    tokio::task::spawn_blocking(move || compare_candidates(Path::new(&path), &candidates, &out_dir))
+        .await
+        .map_err(|e| format!("Thumbnail comparison panicked: {}", e))?
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -514,4 +650,29 @@ mod tests {
         assert_eq!(deleted, 1);
         assert!(!slug_dir.exists());
     }
+
+    #[test]
+    fn compare_candidates_encodes_each_setting() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("big.jpg");
+        make_jpeg(&src, 1600, 1200);
+        let candidate = |max_edge, quality, format: &str| ThumbnailCandidate { max_edge, quality, format: format.to_string() };
+        let candidates = vec![candidate(800, 85, "webp"), candidate(400, 60, "jpeg"), candidate(400, 100, "png")];
+
+        let out = tmp.path().join("out");
+        let results = compare_candidates(&src, &candidates, &out).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].current && !results[1].current);
+        assert_eq!((results[0].width, results[0].height), (800, 600));
+        assert_eq!((results[1].width, results[1].height), (400, 300));
+        for result in &results {
+            assert_eq!(result.error, None);
+            assert_eq!(fs::metadata(&result.preview_path).unwrap().len(), result.size_bytes);
+        }
+        assert!(results[2].preview_path.ends_with("candidate-3.png"));
+
+        assert!(compare_candidates(&src, &[candidate(800, 85, "avif")], &out).is_err());
+        assert!(compare_candidates(&src, &[candidate(800, 0, "webp")], &out).is_err());
+        assert!(compare_candidates(&src, &[], &out).is_err());
+    }
 }
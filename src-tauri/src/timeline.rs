@@ -0,0 +1,143 @@
+use crate::metadata::{current_metadata_index, split_index_key, PhotoRef};
+use crate::workspace::{read_galleries, str_field};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Maximum number of representative photos returned per timeline bucket.
+const TIMELINE_COVERS_PER_BUCKET: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineBucket {
+    pub year: i32,
+    pub month: u32,
+    pub count: usize,
+    /// Slugs of galleries with photos in this month.
+    pub galleries: Vec<String>,
+    /// Up to four photos, each from a different gallery where possible.
+    pub covers: Vec<PhotoRef>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceTimeline {
+    /// Newest month first.
+    pub buckets: Vec<TimelineBucket>,
+    /// Photos with neither an EXIF capture date nor a parseable gallery date.
+    pub undated: usize,
+}
+
+/// Parse a gallery `date` ("dd/MM/yyyy") to (year, month). Free-text dates return `None`.
+pub fn parse_gallery_date(date: &str) -> Option<(i32, u32)> {
+    let mut parts = date.trim().split('/');
+    let _day: u32 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let year: i32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+    Some((year, month))
+}
+
+/// (gallery slug, filename, (year, month)).
+type DatedPhoto = (String, String, (i32, u32));
+
+/// Bucket dated photos by (year, month). Input is expected in capture order so covers are
+/// the earliest shots of each gallery.
+fn build_buckets(photos: Vec<DatedPhoto>) -> Vec<TimelineBucket> {
+    let mut buckets: BTreeMap<(i32, u32), TimelineBucket> = BTreeMap::new();
+    for (slug, filename, (year, month)) in photos {
+        let bucket = buckets.entry((year, month)).or_insert_with(|| TimelineBucket {
+            year,
+            month,
+            count: 0,
+            galleries: Vec::new(),
+            covers: Vec::new(),
+        });
+        bucket.count += 1;
+        if !bucket.galleries.contains(&slug) {
+            bucket.galleries.push(slug.clone());
+            if bucket.covers.len() < TIMELINE_COVERS_PER_BUCKET {
+                bucket.covers.push(PhotoRef { gallery_slug: slug, filename });
+            }
+        }
+    }
+    buckets.into_values().rev().collect()
+}
+
+/// Photos bucketed by capture month for a chronological browsing view. Uses the EXIF capture
+/// time from the metadata index, falling back to the gallery's `date` for photos without one.
+/// Only the stored index and `galleries.json` are consulted; the index worker keeps the index
+/// current as the workspace changes.
+#[tauri::command]
+pub async fn get_workspace_timeline(workspace_path: String) -> Result<WorkspaceTimeline, String> {
+    let root = PathBuf::from(&workspace_path);
+    tokio::task::spawn_blocking(move || {
+        let gallery_dates: HashMap<String, Option<(i32, u32)>> = read_galleries(&root)?
+            .iter()
+            .map(|g| (str_field(g, "slug"), parse_gallery_date(&str_field(g, "date"))))
+            .collect();
+        let index = current_metadata_index(&root)?;
+
+        // (sort key, photo): undated-by-EXIF photos sort first within their gallery month.
+        let mut dated: Vec<(String, DatedPhoto)> = Vec::new();
+        let mut undated = 0;
+        for (key, meta) in &index.photos {
+            let (slug, filename) = split_index_key(key);
+            let year_month = meta
+                .taken_year_month()
+                .or_else(|| gallery_dates.get(slug).copied().flatten());
+            match year_month {
+                Some(ym) => {
+                    let sort_key = meta.taken_at.clone().unwrap_or_default();
+                    dated.push((sort_key, (slug.to_string(), filename.to_string(), ym)));
+                }
+                None => undated += 1,
+            }
+        }
+        dated.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(WorkspaceTimeline {
+            buckets: build_buckets(dated.into_iter().map(|(_, p)| p).collect()),
+            undated,
+        })
+    })
+    .await
+    .map_err(|e| format!("Timeline panicked: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gallery_date() {
+        assert_eq!(parse_gallery_date("28/02/2026"), Some((2026, 2)));
+        assert_eq!(parse_gallery_date("February 2026"), None);
+        assert_eq!(parse_gallery_date("01/13/2026"), None);
+        assert_eq!(parse_gallery_date(""), None);
+    }
+
+    #[test]
+    fn test_build_buckets_newest_first_with_distinct_gallery_covers() {
+        let p = |slug: &str, file: &str, ym: (i32, u32)| (slug.to_string(), file.to_string(), ym);
+        let buckets = build_buckets(vec![
+            p("a", "1.jpg", (2024, 5)),
+            p("a", "2.jpg", (2024, 5)),
+            p("b", "1.jpg", (2024, 5)),
+            p("c", "1.jpg", (2025, 1)),
+        ]);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!((buckets[0].year, buckets[0].month, buckets[0].count), (2025, 1, 1));
+        assert_eq!((buckets[1].year, buckets[1].month, buckets[1].count), (2024, 5, 3));
+        assert_eq!(buckets[1].galleries, vec!["a", "b"]);
+        assert_eq!(
+            buckets[1].covers,
+            vec![
+                PhotoRef { gallery_slug: "a".into(), filename: "1.jpg".into() },
+                PhotoRef { gallery_slug: "b".into(), filename: "1.jpg".into() },
+            ]
+        );
+    }
+}
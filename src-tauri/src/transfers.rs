@@ -0,0 +1,193 @@
+use crate::activity::record_activity;
+use crate::i18n::{error, ErrorCode};
+use crate::manifest::format_utc_timestamp;
+use crate::settings::{extract_bucket_name, normalize_s3_prefix, AppSettings};
+use crate::workspace::write_json_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Publishes kept per target; the totals still count older ones.
+const MAX_RECORDS_PER_TARGET: usize = 500;
+
+/// Bytes and files one publish transferred.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferRecord {
+    /// UTC, e.g. "2024-06-01T10:00:00Z".
+    pub published_at: String,
+    /// Size of the files whose upload completed.
+    pub bytes_uploaded: u64,
+    pub files_uploaded: usize,
+    pub files_deleted: usize,
+    /// False when the publish was cancelled or stopped on an error part way.
+    pub completed: bool,
+}
+
+impl TransferRecord {
+    pub(crate) fn now(bytes_uploaded: u64, files_uploaded: usize, files_deleted: usize, completed: bool) -> Self {
+        let secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self { published_at: format_utc_timestamp(secs), bytes_uploaded, files_uploaded, files_deleted, completed }
+    }
+}
+
+/// Upload history of one publish target (bucket, region and S3 prefix), newest publish last.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetTransfers {
+    pub bucket: String,
+    pub region: String,
+    pub s3_prefix: String,
+    pub total_bytes: u64,
+    pub total_files: u64,
+    pub publishes: Vec<TransferRecord>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferHistory {
+    targets: Vec<TargetTransfers>,
+}
+
+fn history_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| error(ErrorCode::AppDataDirUnavailable, &[("error", &e)]))?;
+    fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    Ok(data_dir.join("transfer-history.json"))
+}
+
+fn load_history(path: &Path) -> Result<TransferHistory, String> {
+    if !path.exists() {
+        return Ok(TransferHistory::default());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read transfer history: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse transfer history: {}", e))
+}
+
+/// Add `record` to the target's history, creating the target on its first publish.
+fn record_transfer(history: &mut TransferHistory, bucket: &str, region: &str, s3_prefix: &str, record: TransferRecord) {
+    let index = match history
+        .targets
+        .iter()
+        .position(|t| t.bucket == bucket && t.region == region && t.s3_prefix == s3_prefix)
+    {
+        Some(index) => index,
+        None => {
+            history.targets.push(TargetTransfers {
+                bucket: bucket.to_string(),
+                region: region.to_string(),
+                s3_prefix: s3_prefix.to_string(),
+                total_bytes: 0,
+                total_files: 0,
+                publishes: Vec::new(),
+            });
+            history.targets.len() - 1
+        }
+    };
+    let target = &mut history.targets[index];
+    target.total_bytes += record.bytes_uploaded;
+    target.total_files += record.files_uploaded as u64;
+    target.publishes.push(record);
+    let excess = target.publishes.len().saturating_sub(MAX_RECORDS_PER_TARGET);
+    target.publishes.drain(..excess);
+}
+
+/// Add a publish to the saved history of the current target and to the activity log. Errors
+/// are logged: accounting never fails a publish.
+pub(crate) fn record_publish_transfer(app: &tauri::AppHandle, settings: &AppSettings, record: TransferRecord) {
+    let s3_prefix = normalize_s3_prefix(&settings.s3_prefix).unwrap_or_else(|_| settings.s3_prefix.clone());
+    let bucket = extract_bucket_name(&settings.bucket);
+    let details = format!(
+        "{} uploaded ({} bytes), {} deleted in s3://{}/{}{}",
+        record.files_uploaded,
+        record.bytes_uploaded,
+        record.files_deleted,
+        bucket,
+        s3_prefix,
+        if record.completed { "" } else { "; stopped before finishing" }
+    );
+    record_target_transfer(app, &bucket, &settings.region, &s3_prefix, record);
+    record_activity(app, "publish", details);
+}
+
+/// Add a publish to the saved history of any target, e.g. the mirror. Errors are logged.
+pub(crate) fn record_target_transfer(
+    app: &tauri::AppHandle,
+    bucket: &str,
+    region: &str,
+    s3_prefix: &str,
+    record: TransferRecord,
+) {
+    let result = history_path(app).and_then(|path| {
+        let mut history = load_history(&path)?;
+        record_transfer(&mut history, bucket, region, s3_prefix, record);
+        let value = serde_json::to_value(&history).map_err(|e| e.to_string())?;
+        write_json_atomic(&path, &value)
+    });
+    if let Err(e) = result {
+        eprintln!("[publish] Failed to record transfer history: {}", e);
+    }
+}
+
+/// Bytes and files uploaded per publish target, from every publish this app has run.
+#[tauri::command]
+pub async fn get_transfer_history(app: tauri::AppHandle) -> Result<Vec<TargetTransfers>, String> {
+    Ok(load_history(&history_path(&app)?)?.targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(bytes: u64) -> TransferRecord {
+        TransferRecord {
+            published_at: "2024-06-01T10:00:00Z".to_string(),
+            bytes_uploaded: bytes,
+            files_uploaded: 2,
+            files_deleted: 1,
+            completed: true,
+        }
+    }
+
+    #[test]
+    fn test_record_transfer_per_target() {
+        let mut history = TransferHistory::default();
+        record_transfer(&mut history, "photos", "us-east-1", "", record(100));
+        record_transfer(&mut history, "photos", "us-east-1", "", record(50));
+        record_transfer(&mut history, "photos", "us-east-1", "site/", record(7));
+        assert_eq!(history.targets.len(), 2);
+        assert_eq!(history.targets[0].total_bytes, 150);
+        assert_eq!(history.targets[0].total_files, 4);
+        assert_eq!(history.targets[0].publishes.len(), 2);
+        assert_eq!(history.targets[1].total_bytes, 7);
+
+        for _ in 0..MAX_RECORDS_PER_TARGET {
+            record_transfer(&mut history, "photos", "us-east-1", "", record(1));
+        }
+        assert_eq!(history.targets[0].publishes.len(), MAX_RECORDS_PER_TARGET);
+        assert_eq!(history.targets[0].total_bytes, 150 + MAX_RECORDS_PER_TARGET as u64);
+    }
+
+    #[test]
+    fn test_load_history_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("transfer-history.json");
+        assert!(load_history(&path).unwrap().targets.is_empty());
+
+        let mut history = TransferHistory::default();
+        record_transfer(&mut history, "photos", "us-east-1", "", record(100));
+        write_json_atomic(&path, &serde_json::to_value(&history).unwrap()).unwrap();
+        assert_eq!(load_history(&path).unwrap().targets, history.targets);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(load_history(&path).is_err());
+    }
+}
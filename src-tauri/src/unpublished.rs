@@ -0,0 +1,275 @@
+use crate::manifest::format_utc_timestamp;
+use crate::workspace::{read_galleries, read_gallery_details, read_json, str_field, write_json_atomic};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A photo as last published: its file and caption.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoSnapshot {
+    pub full: String,
+    pub alt: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GallerySnapshot {
+    pub name: String,
+    pub date: String,
+    pub description: String,
+    pub photos: Vec<PhotoSnapshot>,
+}
+
+/// `{workspace}/.data/published-snapshot.json`: the gallery metadata of the last successful
+/// publish, keyed by slug. Recorded next to the publish stamps, which only hold hashes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedSnapshot {
+    pub published_at: String,
+    pub galleries: BTreeMap<String, GallerySnapshot>,
+}
+
+/// How a gallery differs from what was last published.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryChanges {
+    pub slug: String,
+    pub name: String,
+    /// "added", "removed" or "modified".
+    pub status: String,
+    /// Name, date or description changed.
+    pub details_changed: bool,
+    pub photos_added: Vec<String>,
+    pub photos_removed: Vec<String>,
+    /// Photos (by `full` filename) whose caption changed.
+    pub captions_changed: Vec<String>,
+    /// Same photos in a different order.
+    pub reordered: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnpublishedChanges {
+    /// When the compared publish happened; None when the workspace was never published from
+    /// this computer, in which case every gallery is reported as added.
+    pub published_at: Option<String>,
+    /// Changed galleries only, in `galleries.json` order followed by removed ones.
+    pub galleries: Vec<GalleryChanges>,
+}
+
+fn snapshot_path(root: &Path) -> PathBuf {
+    root.join(".data").join("published-snapshot.json")
+}
+
+/// The workspace's gallery metadata now, in `galleries.json` order.
+pub(crate) fn build_snapshot(root: &Path) -> Result<Vec<(String, GallerySnapshot)>, String> {
+    let mut galleries = Vec::new();
+    for entry in read_galleries(root)? {
+        let slug = str_field(&entry, "slug");
+        if slug.is_empty() {
+            continue;
+        }
+        let details = read_gallery_details(root, &slug)?.unwrap_or(serde_json::Value::Null);
+        let field = |key: &str| {
+            let value = str_field(&details, key);
+            if value.is_empty() { str_field(&entry, key) } else { value }
+        };
+        let photos = details
+            .get("photos")
+            .and_then(|p| p.as_array())
+            .map(|photos| {
+                photos
+                    .iter()
+                    .map(|p| PhotoSnapshot { full: str_field(p, "full"), alt: str_field(p, "alt") })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let snapshot = GallerySnapshot { name: field("name"), date: field("date"), description: field("description"), photos };
+        galleries.push((slug, snapshot));
+    }
+    Ok(galleries)
+}
+
+/// Record `galleries` as published, once a publish of them succeeded.
+pub(crate) fn save_snapshot(root: &Path, galleries: &[(String, GallerySnapshot)]) -> Result<(), String> {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let snapshot = PublishedSnapshot { published_at: format_utc_timestamp(secs), galleries: galleries.iter().cloned().collect() };
+    std::fs::create_dir_all(root.join(".data")).map_err(|e| e.to_string())?;
+    write_json_atomic(&snapshot_path(root), &serde_json::to_value(&snapshot).map_err(|e| e.to_string())?)
+}
+
+fn load_snapshot(root: &Path) -> Result<Option<PublishedSnapshot>, String> {
+    let path = snapshot_path(root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    serde_json::from_value(read_json(&path)?)
+        .map(Some)
+        .map_err(|e| format!("Invalid published-snapshot.json: {}", e))
+}
+
+fn compare_gallery(slug: &str, published: &GallerySnapshot, current: &GallerySnapshot) -> Option<GalleryChanges> {
+    let details_changed = published.name != current.name
+        || published.date != current.date
+        || published.description != current.description;
+    let published_alts: BTreeMap<&str, &str> = published.photos.iter().map(|p| (p.full.as_str(), p.alt.as_str())).collect();
+    let current_alts: BTreeMap<&str, &str> = current.photos.iter().map(|p| (p.full.as_str(), p.alt.as_str())).collect();
+    let photos_added: Vec<String> = current
+        .photos
+        .iter()
+        .filter(|p| !published_alts.contains_key(p.full.as_str()))
+        .map(|p| p.full.clone())
+        .collect();
+    let photos_removed: Vec<String> = published
+        .photos
+        .iter()
+        .filter(|p| !current_alts.contains_key(p.full.as_str()))
+        .map(|p| p.full.clone())
+        .collect();
+    let captions_changed: Vec<String> = current
+        .photos
+        .iter()
+        .filter(|p| published_alts.get(p.full.as_str()).is_some_and(|alt| *alt != p.alt))
+        .map(|p| p.full.clone())
+        .collect();
+    let kept = |photos: &[PhotoSnapshot], other: &BTreeMap<&str, &str>| -> Vec<String> {
+        photos.iter().filter(|p| other.contains_key(p.full.as_str())).map(|p| p.full.clone()).collect()
+    };
+    let reordered = kept(&published.photos, &current_alts) != kept(&current.photos, &published_alts);
+
+    if !details_changed && photos_added.is_empty() && photos_removed.is_empty() && captions_changed.is_empty() && !reordered {
+        return None;
+    }
+    Some(GalleryChanges {
+        slug: slug.to_string(),
+        name: current.name.clone(),
+        status: "modified".to_string(),
+        details_changed,
+        photos_added,
+        photos_removed,
+        captions_changed,
+        reordered,
+    })
+}
+
+fn whole_gallery(slug: &str, gallery: &GallerySnapshot, status: &str) -> GalleryChanges {
+    let files: Vec<String> = gallery.photos.iter().map(|p| p.full.clone()).collect();
+    let added = status == "added";
+    GalleryChanges {
+        slug: slug.to_string(),
+        name: gallery.name.clone(),
+        status: status.to_string(),
+        details_changed: false,
+        photos_added: if added { files.clone() } else { Vec::new() },
+        photos_removed: if added { Vec::new() } else { files },
+        captions_changed: Vec::new(),
+        reordered: false,
+    }
+}
+
+fn diff_snapshots(published: &BTreeMap<String, GallerySnapshot>, current: &[(String, GallerySnapshot)]) -> Vec<GalleryChanges> {
+    let mut changes: Vec<GalleryChanges> = current
+        .iter()
+        .filter_map(|(slug, gallery)| match published.get(slug) {
+            Some(before) => compare_gallery(slug, before, gallery),
+            None => Some(whole_gallery(slug, gallery, "added")),
+        })
+        .collect();
+    changes.extend(
+        published
+            .iter()
+            .filter(|(slug, _)| !current.iter().any(|(s, _)| s == *slug))
+            .map(|(slug, gallery)| whole_gallery(slug, gallery, "removed")),
+    );
+    changes
+}
+
+/// Galleries whose metadata differs from the last successful publish from this workspace:
+/// photos added or removed, captions, reordering and name/date/description edits. Compares
+/// local files only, so it is cheap enough for an "unpublished changes" badge; edits to
+/// image files themselves are found by a publish preview.
+#[tauri::command]
+pub async fn get_unpublished_changes(workspace_path: String) -> Result<UnpublishedChanges, String> {
+    let root = PathBuf::from(&workspace_path);
+    let current = build_snapshot(&root)?;
+    let published = load_snapshot(&root)?;
+    let galleries = diff_snapshots(published.as_ref().map(|s| &s.galleries).unwrap_or(&BTreeMap::new()), &current);
+    Ok(UnpublishedChanges { published_at: published.map(|s| s.published_at), galleries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gallery(name: &str, photos: &[(&str, &str)]) -> GallerySnapshot {
+        GallerySnapshot {
+            name: name.to_string(),
+            date: "01/06/2024".to_string(),
+            description: String::new(),
+            photos: photos.iter().map(|(full, alt)| PhotoSnapshot { full: full.to_string(), alt: alt.to_string() }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let published: BTreeMap<String, GallerySnapshot> = [
+            ("final".to_string(), gallery("Final", &[("01.jpg", "Kick-off"), ("02.jpg", ""), ("03.jpg", "Goal")])),
+            ("same".to_string(), gallery("Same", &[("a.jpg", "")])),
+            ("gone".to_string(), gallery("Gone", &[("x.jpg", "")])),
+        ]
+        .into_iter()
+        .collect();
+        let current = vec![
+            ("final".to_string(), gallery("Final", &[("01.jpg", "Kick-off!"), ("03.jpg", "Goal"), ("04.jpg", "")])),
+            ("same".to_string(), gallery("Same", &[("a.jpg", "")])),
+            ("new".to_string(), gallery("New", &[("n.jpg", "")])),
+        ];
+
+        let changes = diff_snapshots(&published, &current);
+        assert_eq!(changes.len(), 3);
+        let fin = &changes[0];
+        assert_eq!(fin.status, "modified");
+        assert_eq!(fin.photos_added, vec!["04.jpg"]);
+        assert_eq!(fin.photos_removed, vec!["02.jpg"]);
+        assert_eq!(fin.captions_changed, vec!["01.jpg"]);
+        assert!(!fin.reordered && !fin.details_changed);
+        assert_eq!((changes[1].slug.as_str(), changes[1].status.as_str()), ("new", "added"));
+        assert_eq!(changes[1].photos_added, vec!["n.jpg"]);
+        assert_eq!((changes[2].slug.as_str(), changes[2].status.as_str()), ("gone", "removed"));
+    }
+
+    #[test]
+    fn test_compare_gallery_reorder_and_details() {
+        let before = gallery("Final", &[("01.jpg", ""), ("02.jpg", "")]);
+        let swapped = gallery("Final", &[("02.jpg", ""), ("01.jpg", "")]);
+        assert!(compare_gallery("final", &before, &swapped).unwrap().reordered);
+        let renamed = GallerySnapshot { name: "Cup Final".to_string(), ..before.clone() };
+        assert!(compare_gallery("final", &before, &renamed).unwrap().details_changed);
+        assert_eq!(compare_gallery("final", &before, &before), None);
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("galleries.json"), r#"[{"name":"Final","slug":"final","date":"01/06/2024","cover":""}]"#).unwrap();
+        std::fs::create_dir_all(root.join("final")).unwrap();
+        std::fs::write(
+            root.join("final/gallery-details.json"),
+            r#"{"name":"Final","slug":"final","date":"01/06/2024","description":"Cup","photos":[{"thumbnail":"01.jpg","full":"01.jpg","alt":"one"}]}"#,
+        )
+        .unwrap();
+
+        let current = build_snapshot(root).unwrap();
+        assert_eq!(current[0].1.description, "Cup");
+        assert!(load_snapshot(root).unwrap().is_none());
+        save_snapshot(root, &current).unwrap();
+        let published = load_snapshot(root).unwrap().unwrap();
+        assert!(diff_snapshots(&published.galleries, &current).is_empty());
+    }
+}
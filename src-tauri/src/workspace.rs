@@ -0,0 +1,235 @@
+use crate::i18n::{error, ErrorCode};
+use crate::thumbnails::parse_galleries_array;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+
+/// `schemaVersion` written to `galleries.json`; matches `CURRENT_GALLERIES_SCHEMA` in the frontend.
+pub(crate) const GALLERIES_SCHEMA_VERSION: u32 = 1;
+/// `schemaVersion` written to `gallery-details.json`; matches `CURRENT_DETAILS_SCHEMA`.
+pub(crate) const DETAILS_SCHEMA_VERSION: u32 = 1;
+
+/// Read `galleries.json` from the workspace root and return its gallery entries.
+/// Supports both the legacy (plain array) and current (`{ schemaVersion, galleries }`) formats.
+pub(crate) fn read_galleries(root: &Path) -> Result<Vec<serde_json::Value>, String> {
+    let path = root.join("galleries.json");
+    let content = fs::read_to_string(&path)
+        .map_err(|e| error(ErrorCode::GalleriesJsonReadFailed, &[("error", &e)]))?;
+    let raw: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| error(ErrorCode::GalleriesJsonParseFailed, &[("error", &e)]))?;
+    Ok(parse_galleries_array(&raw))
+}
+
+/// Read and parse a JSON file, streaming it through a buffered reader so a multi-MB file is
+/// never held in memory next to its parsed value. Workspace files can be changed by sync
+/// clients and editors at any time, so they aren't memory-mapped.
+pub(crate) fn read_json(path: &Path) -> Result<serde_json::Value, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// One page of a `gallery-details.json` file's photos.
+#[derive(Debug, PartialEq)]
+pub(crate) struct DetailsPage {
+    /// Photos in the whole file.
+    pub total: usize,
+    pub photos: Vec<serde_json::Value>,
+    /// The document without `photos`.
+    pub header: serde_json::Value,
+}
+
+/// Read photos `offset..offset + limit` of a `gallery-details.json` file, streaming it: photos
+/// outside the page are skipped without being built, so memory use follows the page size
+/// rather than the file size.
+pub(crate) fn read_gallery_details_page(path: &Path, offset: usize, limit: usize) -> Result<DetailsPage, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+    let page = PageSeed { offset, limit }
+        .deserialize(&mut deserializer)
+        .and_then(|page| deserializer.end().map(|()| page))
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(page)
+}
+
+/// Deserializes a gallery-details object into a `DetailsPage`.
+struct PageSeed {
+    offset: usize,
+    limit: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for PageSeed {
+    type Value = DetailsPage;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<DetailsPage, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PageSeed {
+    type Value = DetailsPage;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a gallery-details object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<DetailsPage, A::Error> {
+        let mut header = serde_json::Map::new();
+        let mut page = (0, Vec::new());
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "photos" {
+                page = map.next_value_seed(PhotosSeed { offset: self.offset, limit: self.limit })?;
+            } else {
+                header.insert(key, map.next_value()?);
+            }
+        }
+        Ok(DetailsPage { total: page.0, photos: page.1, header: serde_json::Value::Object(header) })
+    }
+}
+
+/// Deserializes a photos array into (length, the photos of the page).
+struct PhotosSeed {
+    offset: usize,
+    limit: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for PhotosSeed {
+    type Value = (usize, Vec<serde_json::Value>);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PhotosSeed {
+    type Value = (usize, Vec<serde_json::Value>);
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a photos array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut photos = Vec::new();
+        let mut total = 0;
+        loop {
+            let in_page = total >= self.offset && photos.len() < self.limit;
+            let more = if in_page {
+                seq.next_element::<serde_json::Value>()?.map(|photo| photos.push(photo)).is_some()
+            } else {
+                seq.next_element::<IgnoredAny>()?.is_some()
+            };
+            if !more {
+                return Ok((total, photos));
+            }
+            total += 1;
+        }
+    }
+}
+
+/// Read `{slug}/gallery-details.json`. Returns `Ok(None)` when the file does not exist.
+pub(crate) fn read_gallery_details(root: &Path, slug: &str) -> Result<Option<serde_json::Value>, String> {
+    let path = root.join(slug).join("gallery-details.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    read_json(&path).map(Some)
+}
+
+/// Find the `galleries.json` entry with the given slug.
+pub(crate) fn find_gallery(galleries: &[serde_json::Value], slug: &str) -> Option<serde_json::Value> {
+    galleries
+        .iter()
+        .find(|g| g.get("slug").and_then(|v| v.as_str()) == Some(slug))
+        .cloned()
+}
+
+/// Return a string field of a JSON object, or "" when missing or not a string.
+pub(crate) fn str_field(value: &serde_json::Value, key: &str) -> String {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string()
+}
+
+/// Atomic write: serialize `value` as pretty JSON to a temp file, then rename over `path`.
+/// Doesn't check `ReadOnlyState`; commands changing workspace metadata do that up front.
+pub(crate) fn write_json_atomic(path: &Path, value: &serde_json::Value) -> Result<(), String> {
+    let parent = path.parent().ok_or("No parent directory")?;
+    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let temp_path = parent.join(format!(
+        ".{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let json_string = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(&temp_path, &json_string).map_err(|e| e.to_string())?;
+    fs::rename(&temp_path, path).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Load `{slug}/gallery-details.json`, apply `f` to it and write the result back atomically.
+/// Edits still pending in `details_store` are written along with it. Errors when the file does
+/// not exist; nothing is written when `f` fails.
+pub(crate) fn update_gallery_details<T>(
+    root: &Path,
+    slug: &str,
+    f: impl FnOnce(&mut serde_json::Value) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = root.join(slug).join("gallery-details.json");
+    let pending = crate::details_store::take_pending(&path);
+    let mut details = match pending {
+        Some(details) => details,
+        None => read_gallery_details(root, slug)?.ok_or_else(|| format!("gallery-details.json not found for '{}'", slug))?,
+    };
+    let result = f(&mut details)?;
+    write_json_atomic(&path, &details)?;
+    Ok(result)
+}
+
+/// Mutable access to the `photos` array of a gallery-details value.
+pub(crate) fn photos_mut(details: &mut serde_json::Value) -> Result<&mut Vec<serde_json::Value>, String> {
+    details
+        .get_mut("photos")
+        .and_then(|p| p.as_array_mut())
+        .ok_or_else(|| "gallery-details.json has no photos array".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_json() {
+        let tmp = TempDir::new().unwrap();
+        let small = tmp.path().join("small.json");
+        fs::write(&small, r#"{"photos": []}"#).unwrap();
+        assert_eq!(read_json(&small).unwrap(), serde_json::json!({"photos": []}));
+
+        let photos: Vec<serde_json::Value> = (0..20_000)
+            .map(|i| serde_json::json!({"full": format!("{:05}.jpg", i), "alt": "x".repeat(40)}))
+            .collect();
+        let large = tmp.path().join("large.json");
+        fs::write(&large, serde_json::to_vec(&serde_json::json!({ "photos": photos })).unwrap()).unwrap();
+        let value = read_json(&large).unwrap();
+        assert_eq!(value["photos"].as_array().unwrap().len(), 20_000);
+
+        fs::write(&small, "{").unwrap();
+        assert!(read_json(&small).unwrap_err().starts_with("Failed to parse"));
+        assert!(read_json(&tmp.path().join("missing.json")).unwrap_err().starts_with("Failed to read"));
+    }
+
+    #[test]
+    fn test_read_gallery_details_page() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("gallery-details.json");
+        let photos: Vec<serde_json::Value> = (0..25).map(|i| serde_json::json!({ "full": i, "tags": ["a"] })).collect();
+        let details = serde_json::json!({ "name": "Dunes", "photos": photos, "schemaVersion": 1 });
+        fs::write(&path, serde_json::to_vec_pretty(&details).unwrap()).unwrap();
+
+        let page = read_gallery_details_page(&path, 20, 10).unwrap();
+        assert_eq!((page.total, page.photos.len()), (25, 5));
+        assert_eq!(page.photos[0], serde_json::json!({ "full": 20, "tags": ["a"] }));
+        assert_eq!(page.header, serde_json::json!({ "name": "Dunes", "schemaVersion": 1 }));
+        assert_eq!(read_gallery_details_page(&path, 30, 10).unwrap().photos, Vec::<serde_json::Value>::new());
+
+        fs::write(&path, r#"{"photos":[{"full":1}]} trailing"#).unwrap();
+        assert!(read_gallery_details_page(&path, 0, 10).unwrap_err().starts_with("Failed to parse"));
+    }
+}
@@ -1,5 +1,38 @@
-import { invoke } from "@tauri-apps/api/core";
-import type { DirListing, AppSettings, ValidationResult, PublishPlan } from "./types";
+import { invoke as tauriInvoke, type InvokeArgs } from "@tauri-apps/api/core";
+import type { DirListing, AppSettings, ValidationResult, PublishPlan, ManagedKeyAudit, Announcement, SocialCropRatio, SocialExportResult, PrintSizeReport, AstronomyEnrichResult, WorkspaceTimeline, PhotoLocations, SearchHit, SearchSuggestion, ResponseHeadersResult, HealthReport, LiveSiteAudit, AdoptResult, SettingsChangeImpact, OldPrefixObjects, OldPrefixCleanupReport, JournalReplay, WorkspaceSummary, PhotoPreview, AccelerationComparison, LocaleOption, TargetTransfers, IntegrityReport, ArchiveResult, ArchivedFileStatus, EncryptionStatus, ActivityEntry, CustomFields, PluginInfo, AutomationEndpoint, ImportResult, CompanionStatus, NetworkInterface, PairingCode, CompanionDevice, UnpublishedChanges, MetadataBackup, CompatibilityReport, ImageLintIssue, ThumbnailCandidate, ThumbnailComparison, BucketSnapshotInfo, PhotoPage, PhotoUpdate, CredentialStoreStatus } from "./types";
+
+/**
+ * A coded backend error: commands return them as `"[code] message"` (src-tauri/src/i18n.rs).
+ * `String(error)` is just the message, so code that only displays errors is unaffected.
+ */
+export class BackendError extends Error {
+  constructor(
+    readonly code: string,
+    message: string
+  ) {
+    super(message);
+    this.name = "BackendError";
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+/** A `BackendError` for coded error strings; anything else is returned unchanged. */
+export function parseBackendError(error: unknown): unknown {
+  if (typeof error !== "string") return error;
+  const match = /^\[([a-z-]+)\] ([\s\S]*)$/.exec(error);
+  return match ? new BackendError(match[1], match[2]) : error;
+}
+
+async function invoke<T>(command: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await tauriInvoke<T>(command, args);
+  } catch (e) {
+    throw parseBackendError(e);
+  }
+}
 
 export async function openFolderDialog(): Promise<string | null> {
   return invoke<string | null>("open_folder_dialog");
@@ -50,6 +83,11 @@ export async function deleteCredentials(): Promise<void> {
   return invoke("delete_credentials");
 }
 
+/** Probes the platform secret store the AWS credentials are saved in. */
+export async function checkCredentialStore(): Promise<CredentialStoreStatus> {
+  return invoke<CredentialStoreStatus>("check_credential_store");
+}
+
 export async function validateCredentials(
   keyId: string,
   secret: string,
@@ -73,14 +111,425 @@ export async function publishPreview(
   return invoke<PublishPlan>("publish_preview", { folderPath, bucket, region, s3Root });
 }
 
-export async function publishExecute(planId: string): Promise<void> {
-  return invoke("publish_execute", { planId });
+export async function publishExecute(planId: string, confirmation?: string, mirrorConfirmation?: string): Promise<void> {
+  return invoke("publish_execute", { planId, confirmation: confirmation ?? null, mirrorConfirmation });
+}
+
+/** Check a previewed plan's deletes against the managed area; publishExecute refuses plans that fail. */
+export async function auditPublishPlan(planId: string): Promise<ManagedKeyAudit> {
+  return invoke<ManagedKeyAudit>("audit_publish_plan", { planId });
 }
 
 export async function publishCancel(planId: string): Promise<void> {
   return invoke("publish_cancel", { planId });
 }
 
+export async function generateAnnouncement(workspacePath: string, slug: string): Promise<Announcement> {
+  return invoke<Announcement>("generate_announcement", { workspacePath, slug });
+}
+
+export async function exportSocialCrops(
+  workspacePath: string,
+  slug: string,
+  filenames: string[],
+  ratio: SocialCropRatio,
+  outputDir?: string
+): Promise<SocialExportResult> {
+  return invoke<SocialExportResult>("export_social_crops", { workspacePath, slug, filenames, ratio, outputDir });
+}
+
+export async function getPrintSizeReport(
+  workspacePath: string,
+  slug: string | null,
+  targetWidthInches: number,
+  targetHeightInches: number
+): Promise<PrintSizeReport> {
+  return invoke<PrintSizeReport>("get_print_size_report", {
+    workspacePath,
+    slug,
+    targetWidthInches,
+    targetHeightInches,
+  });
+}
+
+export async function pairPhotos(
+  workspacePath: string,
+  slug: string,
+  filename: string,
+  relatedTo: string | null
+): Promise<void> {
+  return invoke("pair_photos", { workspacePath, slug, filename, relatedTo });
+}
+
+export async function setPhotoExternalUrl(
+  workspacePath: string,
+  slug: string,
+  filename: string,
+  externalUrl: string | null
+): Promise<void> {
+  return invoke("set_photo_external_url", { workspacePath, slug, filename, externalUrl });
+}
+
+export async function enrichAstronomy(
+  workspacePath: string,
+  slug: string
+): Promise<AstronomyEnrichResult> {
+  return invoke<AstronomyEnrichResult>("enrich_astronomy", { workspacePath, slug });
+}
+
+export async function getWorkspaceTimeline(workspacePath: string): Promise<WorkspaceTimeline> {
+  return invoke<WorkspaceTimeline>("get_workspace_timeline", { workspacePath });
+}
+
+export async function getPhotosByLocation(
+  workspacePath: string,
+  radiusKm?: number
+): Promise<PhotoLocations> {
+  return invoke<PhotoLocations>("get_photos_by_location", { workspacePath, radiusKm: radiusKm ?? null });
+}
+
+export async function searchWorkspace(
+  workspacePath: string,
+  query: string,
+  limit?: number
+): Promise<SearchHit[]> {
+  return invoke<SearchHit[]>("search_workspace", { workspacePath, query, limit: limit ?? null });
+}
+
+export async function suggestSearchTerms(
+  workspacePath: string,
+  prefix: string,
+  limit?: number
+): Promise<SearchSuggestion[]> {
+  return invoke<SearchSuggestion[]>("suggest_search_terms", { workspacePath, prefix, limit: limit ?? null });
+}
+
+export async function configureResponseHeaders(
+  hstsMaxAgeSecs?: number,
+  cacheMaxAgeSecs?: number
+): Promise<ResponseHeadersResult> {
+  return invoke<ResponseHeadersResult>("configure_response_headers", {
+    hstsMaxAgeSecs: hstsMaxAgeSecs ?? null,
+    cacheMaxAgeSecs: cacheMaxAgeSecs ?? null,
+  });
+}
+
+export async function runHealthCheck(): Promise<HealthReport> {
+  return invoke<HealthReport>("run_health_check");
+}
+
+/** Returns false when health checks are disabled or no site URL is set. */
+export async function startHealthChecks(): Promise<boolean> {
+  return invoke<boolean>("start_health_checks");
+}
+
+export async function stopHealthChecks(): Promise<void> {
+  return invoke("stop_health_checks");
+}
+
+export async function auditLiveSite(): Promise<LiveSiteAudit> {
+  return invoke<LiveSiteAudit>("audit_live_site");
+}
+
+/** Download the configured bucket's published galleries into an empty folder as a new workspace. */
+export async function adoptSite(folderPath: string): Promise<AdoptResult> {
+  return invoke<AdoptResult>("adopt_site", { folderPath });
+}
+
+/** Preview the effect of saving changed bucket/prefix settings on the published site. */
+export async function previewSettingsChange(folderPath: string | null, settings: AppSettings): Promise<SettingsChangeImpact> {
+  return invoke<SettingsChangeImpact>("preview_settings_change", { folderPath, settings });
+}
+
+/** List managed-looking objects left at the previous bucket/prefix. */
+export async function listOldPrefixObjects(): Promise<OldPrefixObjects> {
+  return invoke<OldPrefixObjects>("list_old_prefix_objects");
+}
+
+/** Delete confirmed objects from the previous bucket/prefix. */
+export async function cleanupOldPrefix(keys: string[]): Promise<OldPrefixCleanupReport> {
+  return invoke<OldPrefixCleanupReport>("cleanup_old_prefix", { keys });
+}
+
+/** Undo the last metadata edit; null when there is nothing to undo. */
+export async function undoLast(): Promise<JournalReplay | null> {
+  return invoke<JournalReplay | null>("undo_last");
+}
+
+/** Redo the last undone metadata edit; null when there is nothing to redo. */
+export async function redo(): Promise<JournalReplay | null> {
+  return invoke<JournalReplay | null>("redo");
+}
+
+/** galleries.json, subdirectories and per-gallery counts/cover/first photos in one call. */
+export async function loadWorkspaceSummary(workspacePath: string, previewPhotos?: number): Promise<WorkspaceSummary> {
+  return invoke<WorkspaceSummary>("load_workspace_summary", { workspacePath, previewPhotos: previewPhotos ?? null });
+}
+
+/** The summary saved by the last scan (.data/summary.json); null when there is none. */
+export async function loadCachedWorkspaceSummary(workspacePath: string): Promise<WorkspaceSummary | null> {
+  return invoke<WorkspaceSummary | null>("load_cached_workspace_summary", { workspacePath });
+}
+
+/** Generate cached previews for photos[start..start+count]; a newer call cuts an older one short. */
+export async function prefetchPreviews(workspacePath: string, slug: string, start: number, count: number): Promise<PhotoPreview[]> {
+  return invoke<PhotoPreview[]>("prefetch_previews", { workspacePath, slug, start, count });
+}
+
+export async function compareTransferAcceleration(): Promise<AccelerationComparison> {
+  return invoke<AccelerationComparison>("compare_transfer_acceleration");
+}
+
+export async function listLocales(): Promise<LocaleOption[]> {
+  return invoke<LocaleOption[]>("list_locales");
+}
+
+export async function getTransferHistory(): Promise<TargetTransfers[]> {
+  return invoke<TargetTransfers[]>("get_transfer_history");
+}
+
+export async function verifyWorkspaceIntegrity(workspacePath: string): Promise<IntegrityReport> {
+  return invoke<IntegrityReport>("verify_workspace_integrity", { workspacePath });
+}
+
+export async function acceptIntegrityChanges(workspacePath: string, paths: string[]): Promise<void> {
+  return invoke("accept_integrity_changes", { workspacePath, paths });
+}
+
+export async function archiveGallery(workspacePath: string, slug: string, webSize: boolean): Promise<ArchiveResult> {
+  return invoke<ArchiveResult>("archive_gallery", { workspacePath, slug, webSize });
+}
+
+export async function restoreArchivedGallery(
+  workspacePath: string,
+  slug: string,
+  tier: "Standard" | "Bulk",
+  days: number
+): Promise<number> {
+  return invoke<number>("restore_archived_gallery", { workspacePath, slug, tier, days });
+}
+
+export async function getArchiveStatus(workspacePath: string, slug: string): Promise<ArchivedFileStatus[]> {
+  return invoke<ArchivedFileStatus[]>("get_archive_status", { workspacePath, slug });
+}
+
+export async function retrieveRestoredOriginals(workspacePath: string, slug: string): Promise<number> {
+  return invoke<number>("retrieve_restored_originals", { workspacePath, slug });
+}
+
+export async function saveMirrorCredentials(keyId: string, secret: string): Promise<void> {
+  return invoke("save_mirror_credentials", { keyId, secret });
+}
+
+export async function deleteMirrorCredentials(): Promise<void> {
+  return invoke("delete_mirror_credentials");
+}
+
+export async function getEncryptionStatus(workspacePath: string): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>("get_encryption_status", { workspacePath });
+}
+
+export async function enableEncryption(workspacePath: string, passphrase: string): Promise<void> {
+  return invoke("enable_encryption", { workspacePath, passphrase });
+}
+
+export async function unlockWorkspace(workspacePath: string, passphrase: string): Promise<number> {
+  return invoke<number>("unlock_workspace", { workspacePath, passphrase });
+}
+
+export async function lockWorkspace(workspacePath: string): Promise<number> {
+  return invoke<number>("lock_workspace", { workspacePath });
+}
+
+export async function setGalleryEncrypted(workspacePath: string, slug: string, encrypted: boolean): Promise<void> {
+  return invoke("set_gallery_encrypted", { workspacePath, slug, encrypted });
+}
+
+export async function getAuditLog(limit?: number): Promise<ActivityEntry[]> {
+  return invoke<ActivityEntry[]>("get_audit_log", { limit: limit ?? null });
+}
+
+export async function getGalleryCustomFields(workspacePath: string, slug: string): Promise<CustomFields> {
+  return invoke<CustomFields>("get_gallery_custom_fields", { workspacePath, slug });
+}
+
+/** Pass `value: null` to remove the field. */
+export async function setGalleryCustomField(
+  workspacePath: string,
+  slug: string,
+  key: string,
+  value: string | number | boolean | null
+): Promise<void> {
+  return invoke("set_gallery_custom_field", { workspacePath, slug, key, value });
+}
+
+export async function listPlugins(): Promise<PluginInfo[]> {
+  return invoke<PluginInfo[]>("list_plugins");
+}
+
+/** Copy a folder's images into a gallery (created if needed); slug and name default from the folder name. */
+export async function importFolder(
+  workspacePath: string,
+  sourcePath: string,
+  slug?: string,
+  name?: string
+): Promise<ImportResult> {
+  return invoke<ImportResult>("import_folder", { workspacePath, sourcePath, slug: slug ?? null, name: name ?? null });
+}
+
+/** Null when the automation API is turned off in settings. */
+export async function getAutomationEndpoint(): Promise<AutomationEndpoint | null> {
+  return invoke<AutomationEndpoint | null>("get_automation_endpoint");
+}
+
+/** (Re)starts the automation API; null (and stopped) when it is turned off. */
+export async function startAutomationServer(): Promise<AutomationEndpoint | null> {
+  return invoke<AutomationEndpoint | null>("start_automation_server");
+}
+
+export async function stopAutomationServer(): Promise<void> {
+  return invoke("stop_automation_server");
+}
+
+export async function resetAutomationToken(): Promise<string> {
+  return invoke<string>("reset_automation_token");
+}
+
+/** (Re)starts the LAN companion API for the open workspace; null (and stopped) when it is turned off. */
+export async function startCompanionServer(workspacePath: string | null): Promise<CompanionStatus | null> {
+  return invoke<CompanionStatus | null>("start_companion_server", { workspacePath });
+}
+
+export async function stopCompanionServer(): Promise<void> {
+  return invoke("stop_companion_server");
+}
+
+export async function startCompanionPairing(): Promise<PairingCode> {
+  return invoke<PairingCode>("start_companion_pairing");
+}
+
+/** This computer's interface addresses, for choosing where the companion API listens. */
+export async function listNetworkInterfaces(): Promise<NetworkInterface[]> {
+  return invoke<NetworkInterface[]>("list_network_interfaces");
+}
+
+export async function listCompanionDevices(): Promise<CompanionDevice[]> {
+  return invoke<CompanionDevice[]>("list_companion_devices");
+}
+
+export async function revokeCompanionDevice(deviceId: string): Promise<void> {
+  return invoke("revoke_companion_device", { deviceId });
+}
+
+/** Metadata changes since the last publish, from local files only (no S3 calls). */
+export async function getUnpublishedChanges(workspacePath: string): Promise<UnpublishedChanges> {
+  return invoke<UnpublishedChanges>("get_unpublished_changes", { workspacePath });
+}
+
+/** Returns false when metadata backups are turned off. */
+export async function startMetadataBackups(workspacePath: string): Promise<boolean> {
+  return invoke<boolean>("start_metadata_backups", { workspacePath });
+}
+
+export async function stopMetadataBackups(): Promise<void> {
+  return invoke("stop_metadata_backups");
+}
+
+export async function listMetadataBackups(workspacePath: string): Promise<MetadataBackup[]> {
+  return invoke<MetadataBackup[]>("list_metadata_backups", { workspacePath });
+}
+
+/** Restores every file in the backup as one undoable step; returns the number of files written. */
+export async function restoreMetadataBackup(workspacePath: string, backupId: string): Promise<number> {
+  return invoke<number>("restore_metadata_backup", { workspacePath, backupId });
+}
+
+/** Marks the workspace read-only (or writable again); while read-only every command that would
+ *  change its metadata, images or published site fails. */
+export async function setWorkspaceReadOnly(workspacePath: string, readOnly: boolean): Promise<void> {
+  return invoke("set_workspace_read_only", { workspacePath, readOnly });
+}
+
+export async function checkWorkspaceCompatibility(workspacePath: string): Promise<CompatibilityReport> {
+  return invoke<CompatibilityReport>("check_workspace_compatibility", { workspacePath });
+}
+
+/** Applies the migrations a compatibility report offers as one undoable step; returns the files migrated. */
+export async function migrateWorkspace(workspacePath: string): Promise<number> {
+  return invoke<number>("migrate_workspace", { workspacePath });
+}
+
+export async function lintWorkspaceImages(workspacePath: string): Promise<ImageLintIssue[]> {
+  return invoke<ImageLintIssue[]>("lint_workspace_images", { workspacePath });
+}
+
+/** Converts the fixable photos among `paths` in place, keeping the originals under .data/original-formats. */
+export async function fixImageFormats(workspacePath: string, paths: string[]): Promise<number> {
+  return invoke<number>("fix_image_formats", { workspacePath, paths });
+}
+
+export async function compareThumbnailSettings(path: string, candidates: ThumbnailCandidate[]): Promise<ThumbnailComparison[]> {
+  return invoke<ThumbnailComparison[]>("compare_thumbnail_settings", { path, candidates });
+}
+
+/** Lists the bucket under the site root and saves it for offline publish simulations. */
+export async function recordBucketSnapshot(workspacePath: string): Promise<BucketSnapshotInfo> {
+  return invoke<BucketSnapshotInfo>("record_bucket_snapshot", { workspacePath });
+}
+
+export async function getBucketSnapshotInfo(workspacePath: string): Promise<BucketSnapshotInfo | null> {
+  return invoke<BucketSnapshotInfo | null>("get_bucket_snapshot_info", { workspacePath });
+}
+
+/** A publish preview against the recorded bucket snapshot, without any AWS calls. */
+export async function publishSimulate(
+  folderPath: string,
+  bucket: string,
+  region: string,
+  s3Root: string
+): Promise<PublishPlan> {
+  return invoke<PublishPlan>("publish_simulate", { folderPath, bucket, region, s3Root });
+}
+
+export async function getGalleryPhotosPage(
+  workspacePath: string,
+  slug: string,
+  offset: number,
+  limit: number
+): Promise<PhotoPage> {
+  return invoke<PhotoPage>("get_gallery_photos_page", { workspacePath, slug, offset, limit });
+}
+
+/** Edits photos in memory; the file is written (as one undo step) shortly after the last edit. */
+export async function updateGalleryPhotos(workspacePath: string, slug: string, updates: PhotoUpdate[]): Promise<number> {
+  return invoke<number>("update_gallery_photos", { workspacePath, slug, updates });
+}
+
+/** Removes photos in memory; the file is written (as one undo step) shortly after the last edit. */
+export async function removeGalleryPhotos(workspacePath: string, slug: string, filenames: string[]): Promise<number> {
+  return invoke<number>("remove_gallery_photos", { workspacePath, slug, filenames });
+}
+
+/** Writes every gallery with pending in-memory edits now; returns how many were written. */
+export async function flushGalleryDetails(workspacePath: string): Promise<number> {
+  return invoke<number>("flush_gallery_details", { workspacePath });
+}
+
+/** Discards a gallery's pending in-memory edits; returns whether there were any. */
+export async function rollbackGalleryDetails(workspacePath: string, slug: string): Promise<boolean> {
+  return invoke<boolean>("rollback_gallery_details", { workspacePath, slug });
+}
+
+/** Returns false when auto-publish is turned off or the workspace is open read-only. */
+export async function startAutoPublish(workspacePath: string): Promise<boolean> {
+  return invoke<boolean>("start_auto_publish", { workspacePath });
+}
+
+/** Kill switch: stops auto-publishing and cancels an automatic publish in progress. */
+export async function stopAutoPublish(): Promise<void> {
+  return invoke("stop_auto_publish");
+}
+
 export async function startWatching(workspacePath: string): Promise<void> {
   return invoke("start_watching", { workspacePath });
 }
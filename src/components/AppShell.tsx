@@ -1,7 +1,15 @@
 import { useEffect, useCallback, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { useWorkspace } from "../context/WorkspaceContext";
-import { getAllTags, startWatching, stopWatching, removePhotoFromGalleryDetails } from "../commands";
+import { useAutomationServer } from "../hooks/useAutomationServer";
+import { useAutoPublish } from "../hooks/useAutoPublish";
+import { useCompanionServer } from "../hooks/useCompanionServer";
+import { useGalleryDetailsConflicts } from "../hooks/useGalleryDetailsConflicts";
+import { useMetadataBackups } from "../hooks/useMetadataBackups";
+import { useSiteHealth } from "../hooks/useSiteHealth";
+import { useUndoShortcuts } from "../hooks/useUndoShortcuts";
+import { useWorkspaceChanges } from "../hooks/useWorkspaceChanges";
+import { getAllTags, startWatching, stopWatching, removePhotoFromGalleryDetails, flushGalleryDetails } from "../commands";
 import { TreeView } from "./TreeView";
 import { GalleriesView } from "./GalleriesView";
 import { GalleryDetailView } from "./GalleryDetailView";
@@ -21,24 +29,31 @@ export function AppShell() {
     loadDirImages,
     dispatch,
     debouncedSaveGalleries,
-    debouncedSaveGalleryDetails,
+    removePhoto,
     refreshGalleryCount,
   } = useWorkspace();
   const { viewMode, selectedTreeNode } = state;
+  useSiteHealth();
+  useAutomationServer();
+  useCompanionServer(state.folderPath);
+  useMetadataBackups(state.folderPath);
+  useAutoPublish(state.folderPath, state.readOnly);
+  useUndoShortcuts();
+  useWorkspaceChanges();
+  useGalleryDetailsConflicts();
 
   const stateRef = useRef(state);
   stateRef.current = state;
 
-  // Load galleries and subdirectories when folder opens
+  // Load galleries and subdirectories (one summary call) when folder opens
   useEffect(() => {
     if (state.folderPath) {
       loadGalleries();
-      loadSubdirectories();
       getAllTags(state.folderPath)
         .then((tags) => dispatch({ type: "SET_KNOWN_TAGS", tags }))
         .catch(() => {});
     }
-  }, [state.folderPath, loadGalleries, loadSubdirectories, dispatch]);
+  }, [state.folderPath, loadGalleries, dispatch]);
 
   // Load gallery details when a subdirectory is selected
   useEffect(() => {
@@ -65,7 +80,8 @@ export function AppShell() {
           loadSubdirectories();
           if (slug) {
             const galleryIndex = s.galleries.findIndex((g) => g.slug === slug);
-            if (galleryIndex !== -1) {
+            // Read-only workspaces mirror the disk; galleries.json keeps the entry
+            if (galleryIndex !== -1 && !s.readOnly) {
               dispatch({ type: "DELETE_GALLERY", index: galleryIndex });
               debouncedSaveGalleries();
             }
@@ -87,16 +103,15 @@ export function AppShell() {
         case "image-removed":
           if (slug && slug === s.selectedTreeNode) {
             loadDirImages(slug);
-            if (s.galleryDetails && filename) {
+            if (s.galleryDetails && filename && !s.readOnly) {
               const photoIndex = s.galleryDetails.photos.findIndex(
                 (p) => p.full.endsWith(filename) || p.thumbnail.endsWith(filename)
               );
               if (photoIndex !== -1) {
-                dispatch({ type: "DELETE_PHOTO", index: photoIndex });
-                debouncedSaveGalleryDetails();
+                removePhoto(photoIndex);
               }
             }
-          } else if (slug && filename) {
+          } else if (slug && filename && !s.readOnly) {
             removePhotoFromGalleryDetails(s.folderPath, slug, filename).catch(() => {});
           }
           if (slug) {
@@ -110,7 +125,7 @@ export function AppShell() {
       loadDirImages,
       dispatch,
       debouncedSaveGalleries,
-      debouncedSaveGalleryDetails,
+      removePhoto,
       refreshGalleryCount,
     ]
   );
@@ -128,6 +143,15 @@ export function AppShell() {
     };
   }, [state.folderPath, handleFsChange]);
 
+  // Write gallery edits still batched in memory before the workspace closes
+  useEffect(() => {
+    const folderPath = state.folderPath;
+    if (!folderPath) return;
+    return () => {
+      flushGalleryDetails(folderPath).catch(() => {});
+    };
+  }, [state.folderPath]);
+
   // Keyboard shortcuts
   const handleKeyDown = useCallback(
     (e: KeyboardEvent) => {
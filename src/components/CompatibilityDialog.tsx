@@ -0,0 +1,95 @@
+import { useState } from "react";
+import { useWorkspace } from "../context/WorkspaceContext";
+import type { CompatibilityItem } from "../types";
+
+function describeItem(item: CompatibilityItem): string {
+  switch (item.status) {
+    case "older":
+      return `older format (v${item.found} → v${item.supported})`;
+    case "newer":
+      return `written by a newer app version (v${item.found}; this app reads v${item.supported})`;
+    case "invalid":
+      return "unreadable";
+  }
+}
+
+const ACTION_NOTES: Record<CompatibilityItem["action"], string> = {
+  migrate: "will be upgraded",
+  rebuild: "cache, rebuilt automatically",
+  readOnly: "can only be browsed",
+  none: "",
+};
+
+export function CompatibilityDialog() {
+  const { pendingCompatibility, resolveCompatibility } = useWorkspace();
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  if (!pendingCompatibility) return null;
+  const { name, report } = pendingCompatibility;
+  const canMigrate = report.outcome === "migrate";
+
+  const choose = async (choice: "migrate" | "readOnly" | "cancel") => {
+    setBusy(true);
+    setError(null);
+    try {
+      await resolveCompatibility(choice);
+    } catch (e) {
+      setError(`Migration failed: ${e}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center">
+      <div className="fixed inset-0 bg-black/50" onClick={() => choose("cancel")} />
+      <div className="relative bg-background border border-border rounded-lg shadow-lg p-6 max-w-lg w-full mx-4">
+        <h3 className="text-lg font-semibold mb-2">
+          {canMigrate ? `${name} needs a migration` : `${name} can only be opened read-only`}
+        </h3>
+        <p className="text-muted-foreground text-sm mb-3">
+          {canMigrate
+            ? "Some files use an older format. Migrating upgrades them in place; the change can be undone."
+            : "Some files were written by a newer version of AfterGlowManager or can't be read. Editing them here could lose data."}
+        </p>
+        <ul className="text-xs space-y-1 mb-4 max-h-48 overflow-y-auto">
+          {report.items.map((item) => (
+            <li key={item.path}>
+              <span className="font-mono">{item.path}</span>: {describeItem(item)}
+              {ACTION_NOTES[item.action] && (
+                <span className="text-muted-foreground"> — {ACTION_NOTES[item.action]}</span>
+              )}
+            </li>
+          ))}
+        </ul>
+        {error && <p className="text-destructive text-sm mb-4">{error}</p>}
+        <div className="flex justify-end gap-3">
+          <button
+            onClick={() => choose("cancel")}
+            disabled={busy}
+            className="px-4 py-2 text-sm rounded-md border border-border hover:bg-accent transition-colors"
+          >
+            Cancel
+          </button>
+          <button
+            onClick={() => choose("readOnly")}
+            disabled={busy}
+            className="px-4 py-2 text-sm rounded-md border border-border hover:bg-accent transition-colors"
+          >
+            Open Read-Only
+          </button>
+          {canMigrate && (
+            <button
+              onClick={() => choose("migrate")}
+              disabled={busy}
+              className="px-4 py-2 text-sm rounded-md bg-primary text-primary-foreground hover:opacity-90 transition-opacity"
+            >
+              Migrate
+            </button>
+          )}
+        </div>
+      </div>
+    </div>
+  );
+}
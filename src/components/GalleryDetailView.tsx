@@ -3,6 +3,7 @@ import { useWorkspace } from "../context/WorkspaceContext";
 import { GalleryHeader } from "./GalleryHeader";
 import { ImageTile } from "./ImageTile";
 import { ImageInfoPane } from "./ImageInfoPane";
+import { usePreviewPrefetch } from "../hooks/usePreviewPrefetch";
 import { ConfirmDialog } from "./ConfirmDialog";
 import {
   DndContext,
@@ -28,9 +29,12 @@ interface SortableImageTileProps {
   onClick: () => void;
   onContextMenu: (e: React.MouseEvent) => void;
   id: string;
+  previewSrc?: string;
+  onVisibilityChange: (index: number, visible: boolean) => void;
 }
 
-function SortableImageTile({ entry, index, isSelected, isCover, onClick, onContextMenu, id }: SortableImageTileProps) {
+function SortableImageTile({ entry, index, isSelected, isCover, onClick, onContextMenu, id, previewSrc, onVisibilityChange }: SortableImageTileProps) {
+  const handleVisibility = useCallback((visible: boolean) => onVisibilityChange(index, visible), [onVisibilityChange, index]);
   const { attributes, listeners, setNodeRef, transform, transition, isDragging } = useSortable({ id });
 
   const style = {
@@ -49,16 +53,23 @@ function SortableImageTile({ entry, index, isSelected, isCover, onClick, onConte
         isCover={isCover}
         onClick={onClick}
         onContextMenu={onContextMenu}
+        previewSrc={previewSrc}
+        onVisibilityChange={handleVisibility}
       />
     </div>
   );
 }
 
 export function GalleryDetailView() {
-  const { state, dispatch, saveGalleries, saveGalleryDetails } = useWorkspace();
+  const { state, dispatch, saveGalleries, saveGalleryDetails, removePhoto } = useWorkspace();
   const { galleryDetails, selectedImageIndex } = state;
   const [contextMenu, setContextMenu] = useState<{ x: number; y: number; index: number } | null>(null);
   const [confirmDeleteIndex, setConfirmDeleteIndex] = useState<number | null>(null);
+  const { previews, onTileVisible } = usePreviewPrefetch(
+    state.folderPath,
+    galleryDetails?.slug ?? null,
+    galleryDetails?.photos.length ?? 0
+  );
 
   const sensors = useSensors(
     useSensor(PointerSensor, { activationConstraint: { distance: 8 } })
@@ -131,10 +142,9 @@ export function GalleryDetailView() {
 
   const handleConfirmDelete = useCallback(async () => {
     if (confirmDeleteIndex === null) return;
-    dispatch({ type: "DELETE_PHOTO", index: confirmDeleteIndex });
     setConfirmDeleteIndex(null);
-    setTimeout(() => saveGalleryDetails(), 50);
-  }, [confirmDeleteIndex, dispatch, saveGalleryDetails]);
+    await removePhoto(confirmDeleteIndex);
+  }, [confirmDeleteIndex, removePhoto]);
 
   if (!galleryDetails) {
     return (
@@ -176,6 +186,8 @@ export function GalleryDetailView() {
                     isCover={coverPath === `${galleryDetails.slug}/${entry.full}`}
                     onClick={() => handleTileClick(i)}
                     onContextMenu={(e) => handleContextMenu(e, i)}
+                    previewSrc={previews[entry.full]}
+                    onVisibilityChange={onTileVisible}
                   />
                 ))}
               </div>
@@ -86,6 +86,16 @@ export function GalleryInfoPane() {
             />
           </div>
 
+          <label className="block text-xs text-muted-foreground mb-1">Location</label>
+          <input
+            type="text"
+            value={selectedGallery.location ?? ""}
+            onChange={(e) => handleFieldChange("location", e.target.value)}
+            onBlur={handleBlur}
+            placeholder="e.g. Wembley Stadium"
+            className="w-full px-3 py-1.5 text-sm rounded-md border border-input bg-background mb-3 focus:outline-none focus:ring-1 focus:ring-ring"
+          />
+
           <label className="block text-xs text-muted-foreground mb-1">Slug</label>
           <p className="text-sm text-foreground/70 mb-4">{selectedGallery.slug}</p>
 
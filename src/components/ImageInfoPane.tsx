@@ -1,22 +1,27 @@
-import { useCallback, useRef, useState } from "react";
+import { useCallback, useEffect, useRef, useState } from "react";
 import { useWorkspace } from "../context/WorkspaceContext";
 import { UntrackedImageGrid } from "./UntrackedImageGrid";
 import { ConfirmDialog } from "./ConfirmDialog";
 import { TagInput } from "./TagInput";
+import { ThumbnailCompareDialog } from "./ThumbnailCompareDialog";
+import { setPhotoExternalUrl } from "../commands";
 
 export function ImageInfoPane() {
   const {
     state,
     dispatch,
-    debouncedSaveGalleryDetails,
+    savePhoto,
+    removePhoto,
     addUntrackedImage,
     addAllUntrackedImages,
-    saveGalleryDetails,
     setCoverPhoto,
   } = useWorkspace();
   const { galleryDetails, selectedImageIndex, currentDirImages, knownTags } = state;
   const [confirmDelete, setConfirmDelete] = useState(false);
+  const [comparing, setComparing] = useState(false);
   const altInputRef = useRef<HTMLInputElement>(null);
+  const [externalUrl, setExternalUrl] = useState("");
+  const [externalUrlError, setExternalUrlError] = useState<string | null>(null);
 
   const selectedPhoto =
     galleryDetails && selectedImageIndex !== null
@@ -29,6 +34,13 @@ export function ImageInfoPane() {
     ? currentCover === `${galleryDetails?.slug}/${selectedPhoto.full}`
     : false;
 
+  // The link is saved through the backend (validated there), not the debounced details save
+  const savedExternalUrl = selectedPhoto?.externalUrl ?? "";
+  useEffect(() => {
+    setExternalUrl(savedExternalUrl);
+    setExternalUrlError(null);
+  }, [selectedPhoto?.full, savedExternalUrl]);
+
   // Untracked images
   const trackedFilenames = new Set(
     (galleryDetails?.photos ?? []).map((p) => p.full.split("/").pop()?.toLowerCase() ?? "")
@@ -49,21 +61,34 @@ export function ImageInfoPane() {
     (tags: string[]) => {
       if (selectedImageIndex === null) return;
       dispatch({ type: "UPDATE_PHOTO", index: selectedImageIndex, entry: { tags } });
-      debouncedSaveGalleryDetails();
+      savePhoto(selectedImageIndex, { tags });
     },
-    [selectedImageIndex, dispatch, debouncedSaveGalleryDetails]
+    [selectedImageIndex, dispatch, savePhoto]
   );
 
   const handleBlur = useCallback(() => {
-    debouncedSaveGalleryDetails();
-  }, [debouncedSaveGalleryDetails]);
+    if (selectedImageIndex === null || !selectedPhoto) return;
+    savePhoto(selectedImageIndex, { alt: selectedPhoto.alt });
+  }, [selectedImageIndex, selectedPhoto, savePhoto]);
+
+  const handleExternalUrlBlur = useCallback(async () => {
+    if (!state.folderPath || !galleryDetails || !selectedPhoto || selectedImageIndex === null) return;
+    const value = externalUrl.trim();
+    if (value === savedExternalUrl) return;
+    try {
+      await setPhotoExternalUrl(state.folderPath, galleryDetails.slug, selectedPhoto.full, value || null);
+      dispatch({ type: "UPDATE_PHOTO", index: selectedImageIndex, entry: { externalUrl: value || undefined } });
+      setExternalUrlError(null);
+    } catch (e) {
+      setExternalUrlError(String(e));
+    }
+  }, [state.folderPath, galleryDetails, selectedPhoto, selectedImageIndex, externalUrl, savedExternalUrl, dispatch]);
 
   const handleDelete = useCallback(async () => {
     if (selectedImageIndex === null) return;
-    dispatch({ type: "DELETE_PHOTO", index: selectedImageIndex });
     setConfirmDelete(false);
-    setTimeout(() => saveGalleryDetails(), 50);
-  }, [selectedImageIndex, dispatch, saveGalleryDetails]);
+    await removePhoto(selectedImageIndex);
+  }, [selectedImageIndex, removePhoto]);
 
   const handleSetAsCover = useCallback(async () => {
     if (!selectedPhoto || galleryIndex < 0 || !galleryDetails) return;
@@ -103,6 +128,19 @@ export function ImageInfoPane() {
             />
           </div>
 
+          <label className="block text-xs text-muted-foreground mb-1">External Link</label>
+          <input
+            type="url"
+            value={externalUrl}
+            onChange={(e) => setExternalUrl(e.target.value)}
+            onBlur={handleExternalUrlBlur}
+            placeholder="https://prints.example.com/…"
+            className="w-full px-3 py-1.5 text-sm rounded-md border border-input bg-background mb-1 focus:outline-none focus:ring-1 focus:ring-ring"
+          />
+          <p className={`text-xs mb-3 ${externalUrlError ? "text-destructive" : "text-muted-foreground"}`}>
+            {externalUrlError ?? "Shown as a Buy Print button on the published site."}
+          </p>
+
           <button
             onClick={handleSetAsCover}
             disabled={isCurrentCover}
@@ -111,6 +149,13 @@ export function ImageInfoPane() {
             {isCurrentCover ? "Current Cover" : "Set as Cover"}
           </button>
 
+          <button
+            onClick={() => setComparing(true)}
+            className="w-full px-3 py-2 text-sm rounded-md border border-border hover:bg-muted transition-colors mb-2"
+          >
+            Compare Thumbnail Settings
+          </button>
+
           <button
             onClick={() => setConfirmDelete(true)}
             className="w-full px-3 py-2 text-sm rounded-md bg-destructive text-destructive-foreground hover:opacity-90 transition-opacity"
@@ -126,6 +171,12 @@ export function ImageInfoPane() {
             onConfirm={handleDelete}
             onCancel={() => setConfirmDelete(false)}
           />
+
+          <ThumbnailCompareDialog
+            open={comparing}
+            path={`${state.folderPath}/${galleryDetails?.slug}/${selectedPhoto.full}`}
+            onClose={() => setComparing(false)}
+          />
         </>
       ) : (
         <p className="text-sm text-muted-foreground">Select an image to view details.</p>
@@ -1,4 +1,4 @@
-import { useState } from "react";
+import { useEffect, useRef, useState } from "react";
 import type React from "react";
 import type { PhotoEntry } from "../types";
 import { useWorkspace } from "../context/WorkspaceContext";
@@ -11,15 +11,31 @@ interface ImageTileProps {
   isCover?: boolean;
   onClick: () => void;
   onContextMenu?: (e: React.MouseEvent) => void;
+  /** Cached preview URL, used instead of the original when available. */
+  previewSrc?: string;
+  /** Called when the tile scrolls into or out of view. */
+  onVisibilityChange?: (visible: boolean) => void;
 }
 
-export function ImageTile({ entry, index, isSelected, isCover, onClick, onContextMenu }: ImageTileProps) {
+export function ImageTile({ entry, index, isSelected, isCover, onClick, onContextMenu, previewSrc, onVisibilityChange }: ImageTileProps) {
   const { state, resolveImagePath } = useWorkspace();
   const [imgError, setImgError] = useState(false);
-  const src = entry.full ? resolveImagePath(entry.full, state.galleryDetails?.slug) : "";
+  const tileRef = useRef<HTMLDivElement>(null);
+  const src = previewSrc ?? (entry.full ? resolveImagePath(entry.full, state.galleryDetails?.slug) : "");
+
+  useEffect(() => {
+    if (!onVisibilityChange || !tileRef.current || typeof IntersectionObserver === "undefined") return;
+    const observer = new IntersectionObserver(([e]) => onVisibilityChange(e.isIntersecting));
+    observer.observe(tileRef.current);
+    return () => {
+      observer.disconnect();
+      onVisibilityChange(false);
+    };
+  }, [onVisibilityChange]);
 
   return (
     <div
+      ref={tileRef}
       data-testid={`image-tile-${index}`}
       onClick={onClick}
       onContextMenu={onContextMenu}
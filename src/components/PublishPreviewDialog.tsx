@@ -1,8 +1,23 @@
 import { useState, useEffect, useRef, useCallback } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { Loader2, Upload, Trash2, CheckCircle, AlertCircle } from "lucide-react";
-import type { PublishPlan, PublishProgress, PublishResult, PublishError, ThumbnailProgress } from "../types";
-import { publishPreview, publishExecute, publishCancel } from "../commands";
+import type { DeleteCategories, PublishPlan, PublishProgress, PublishResult, PublishError, ThumbnailProgress, ManagedKeyAudit, MirrorResult } from "../types";
+import { publishPreview, publishExecute, publishCancel, auditPublishPlan, fixImageFormats, publishSimulate, recordBucketSnapshot, loadSettings, saveSettings } from "../commands";
+
+/** Delete categories in display order; risky ones must be confirmed before publishing. */
+const DELETE_CATEGORIES: { key: keyof DeleteCategories; label: string; risky: boolean }[] = [
+  { key: "removedPhotos", label: "removed photos", risky: false },
+  { key: "staleThumbnails", label: "stale thumbnails", risky: false },
+  { key: "removedGalleries", label: "files of removed galleries", risky: true },
+  { key: "unknown", label: "other files in the managed area", risky: true },
+];
+
+/** Whether `plan` has risky delete categories that haven't been confirmed yet. */
+function unconfirmedDeletes(plan: PublishPlan, confirmed: Set<keyof DeleteCategories>): boolean {
+  return DELETE_CATEGORIES.some(
+    ({ key, risky }) => risky && (plan.deleteCategories?.[key].length ?? 0) > 0 && !confirmed.has(key)
+  );
+}
 
 interface PublishPreviewDialogProps {
   open: boolean;
@@ -11,6 +26,8 @@ interface PublishPreviewDialogProps {
   bucket: string;
   region: string;
   s3Root: string;
+  /** Plan against the recorded bucket snapshot instead of the live bucket; nothing is published. */
+  simulate?: boolean;
 }
 
 type DialogPhase =
@@ -29,23 +46,55 @@ export function PublishPreviewDialog({
   bucket,
   region,
   s3Root,
+  simulate = false,
 }: PublishPreviewDialogProps) {
   const [state, setState] = useState<DialogPhase>({ phase: "loading", status: "thumbnails", thumbProgress: null });
   const [elapsed, setElapsed] = useState(0);
+  const [confirmText, setConfirmText] = useState("");
+  const [mirrorConfirmText, setMirrorConfirmText] = useState("");
+  const [confirmedDeletes, setConfirmedDeletes] = useState<Set<keyof DeleteCategories>>(new Set());
+  const [audit, setAudit] = useState<ManagedKeyAudit | null>(null);
+  const [mirrorProgress, setMirrorProgress] = useState<PublishProgress | null>(null);
+  const [mirrorResult, setMirrorResult] = useState<MirrorResult | null>(null);
+  const [converting, setConverting] = useState(false);
+  const [pinning, setPinning] = useState(false);
   const timerRef = useRef<ReturnType<typeof setInterval> | null>(null);
   const planIdRef = useRef<string | null>(null);
 
   const loadPreview = useCallback(async () => {
     setState({ phase: "loading", status: "thumbnails", thumbProgress: null });
+    setConfirmText("");
+    setMirrorConfirmText("");
+    setConfirmedDeletes(new Set());
+    setAudit(null);
+    setMirrorProgress(null);
+    setMirrorResult(null);
     try {
-      const plan = await publishPreview(folderPath, bucket, region, s3Root);
+      const plan = simulate
+        ? await publishSimulate(folderPath, bucket, region, s3Root)
+        : await publishPreview(folderPath, bucket, region, s3Root);
       planIdRef.current = plan.planId;
+      if (plan.toDelete.length > 0 && !plan.simulatedAt) {
+        setAudit((await auditPublishPlan(plan.planId).catch(() => null)) ?? null);
+      }
       setState({ phase: "preview", plan });
     } catch (e) {
       const message = e instanceof Error ? e.message : String(e);
       setState({ phase: "error", message, file: "", uploaded: 0, deleted: 0, plan: { planId: "", toUpload: [], toDelete: [], unchanged: 0, totalFiles: 0 } });
     }
-  }, [folderPath, bucket, region, s3Root]);
+  }, [folderPath, bucket, region, s3Root, simulate]);
+
+  /** Pin the live site template so publishing leaves its files alone, then plan again. */
+  const handleKeepTemplate = async (version: string) => {
+    setPinning(true);
+    try {
+      const settings = await loadSettings();
+      await saveSettings({ ...settings, siteTemplatePin: version });
+    } finally {
+      setPinning(false);
+    }
+    await loadPreview();
+  };
 
   useEffect(() => {
     if (open) {
@@ -103,11 +152,22 @@ export function PublishPreviewDialog({
       });
     });
 
+    // The mirror reports separately: its failures never end the publish
+    const unlistenMirrorProgress = listen<PublishProgress>("mirror-progress", (event) => {
+      setMirrorProgress(event.payload);
+    });
+
+    const unlistenMirrorComplete = listen<MirrorResult>("mirror-complete", (event) => {
+      setMirrorResult(event.payload);
+    });
+
     return () => {
       unlistenThumbnails.then((fn) => fn());
       unlistenProgress.then((fn) => fn());
       unlistenComplete.then((fn) => fn());
       unlistenError.then((fn) => fn());
+      unlistenMirrorProgress.then((fn) => fn());
+      unlistenMirrorComplete.then((fn) => fn());
     };
   }, [open]);
 
@@ -123,7 +183,11 @@ export function PublishPreviewDialog({
     }, 1000);
 
     try {
-      await publishExecute(plan.planId);
+      await publishExecute(
+        plan.planId,
+        plan.deleteConfirmation ? confirmText : undefined,
+        plan.mirror?.deleteConfirmation ? mirrorConfirmText : undefined
+      );
     } catch (e) {
       if (timerRef.current) clearInterval(timerRef.current);
       const message = e instanceof Error ? e.message : String(e);
@@ -131,6 +195,48 @@ export function PublishPreviewDialog({
     }
   };
 
+  const handleConvertImages = async () => {
+    if (state.phase !== "preview" || !state.plan.imageWarnings) return;
+    const paths = state.plan.imageWarnings.filter((w) => w.fixable).map((w) => w.path);
+    setConverting(true);
+    try {
+      await fixImageFormats(folderPath, paths);
+    } catch (e) {
+      const message = e instanceof Error ? e.message : String(e);
+      setState({ phase: "error", message, file: "", uploaded: 0, deleted: 0, plan: state.plan });
+      return;
+    } finally {
+      setConverting(false);
+    }
+    // The converted files have new hashes; plan again
+    await loadPreview();
+  };
+
+  const [recording, setRecording] = useState(false);
+  const handleRecordSnapshot = async () => {
+    setRecording(true);
+    try {
+      await recordBucketSnapshot(folderPath);
+    } catch (e) {
+      const message = e instanceof Error ? e.message : String(e);
+      setState({ phase: "error", message: `Couldn't record a bucket snapshot: ${message}`, file: "", uploaded: 0, deleted: 0, plan: { planId: "", toUpload: [], toDelete: [], unchanged: 0, totalFiles: 0 } });
+      return;
+    } finally {
+      setRecording(false);
+    }
+    await loadPreview();
+  };
+
+  const recordSnapshotButton = simulate && (
+    <button
+      onClick={handleRecordSnapshot}
+      disabled={recording}
+      className="px-4 py-2 text-sm rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50"
+    >
+      {recording ? "Recording…" : "Record New Snapshot"}
+    </button>
+  );
+
   const handleCancel = async () => {
     if (planIdRef.current) {
       await publishCancel(planIdRef.current);
@@ -148,7 +254,11 @@ export function PublishPreviewDialog({
         setElapsed(Math.floor((Date.now() - startTime) / 1000));
       }, 1000);
 
-      publishExecute(plan.planId).catch((e) => {
+      publishExecute(
+        plan.planId,
+        plan.deleteConfirmation ? confirmText : undefined,
+        plan.mirror?.deleteConfirmation ? mirrorConfirmText : undefined
+      ).catch((e) => {
         if (timerRef.current) clearInterval(timerRef.current);
         const message = e instanceof Error ? e.message : String(e);
         setState({ phase: "error", message, file: "", uploaded: 0, deleted: 0, plan });
@@ -173,7 +283,7 @@ export function PublishPreviewDialog({
         onClick={canDismiss ? onClose : undefined}
       />
       <div className="relative bg-background border border-border rounded-lg shadow-lg p-6 max-w-lg w-full mx-4">
-        <h2 className="text-lg font-semibold mb-4">Publish to S3</h2>
+        <h2 className="text-lg font-semibold mb-4">{simulate ? "Simulate Publish" : "Publish to S3"}</h2>
 
         {state.phase === "loading" && (
           <div className="py-4">
@@ -214,6 +324,35 @@ export function PublishPreviewDialog({
 
         {state.phase === "preview" && (
           <div>
+            {state.plan.simulatedAt && (
+              <div className="mb-4 text-sm text-muted-foreground" data-testid="simulation-notice">
+                Simulated against the bucket snapshot recorded {state.plan.simulatedAt}. Nothing will be uploaded or deleted.
+              </div>
+            )}
+            {state.plan.template?.upgrade && state.plan.template.published && (
+              <div className="mb-4 text-sm flex items-center gap-2" data-testid="template-notice">
+                <AlertCircle className="w-4 h-4 text-amber-500 shrink-0" />
+                <span>
+                  Site template will be upgraded from v{state.plan.template.published.version} to v
+                  {state.plan.template.bundled.version}.
+                </span>
+                {!state.plan.simulatedAt && (
+                  <button
+                    onClick={() => handleKeepTemplate(state.plan.template!.published!.version)}
+                    disabled={pinning}
+                    className="ml-auto px-2 py-1 text-xs rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50 shrink-0"
+                  >
+                    Keep v{state.plan.template.published.version}
+                  </button>
+                )}
+              </div>
+            )}
+            {state.plan.template?.pinned && state.plan.template.published && (
+              <div className="mb-4 text-sm text-muted-foreground" data-testid="template-notice">
+                Site template pinned at v{state.plan.template.published.version}; the bundled v
+                {state.plan.template.bundled.version} won't be published. Unpin it in Settings.
+              </div>
+            )}
             <div className="space-y-2 mb-6" data-testid="preview-summary">
               <div className="text-sm">
                 <span className="text-muted-foreground">Total files:</span>{" "}
@@ -236,12 +375,168 @@ export function PublishPreviewDialog({
               </div>
             </div>
 
+            {state.plan.deleteCategories && state.plan.toDelete.length > 0 && (
+              <div className="mb-6 space-y-1.5 text-sm" data-testid="delete-categories">
+                {DELETE_CATEGORIES.map(({ key, label, risky }) => {
+                  const keys = state.plan.deleteCategories?.[key] ?? [];
+                  if (keys.length === 0) return null;
+                  return (
+                    <details key={key} className="ml-5">
+                      <summary className="cursor-pointer">
+                        {risky ? (
+                          <label className="inline-flex items-center gap-1.5 text-destructive" onClick={(e) => e.stopPropagation()}>
+                            <input
+                              type="checkbox"
+                              checked={confirmedDeletes.has(key)}
+                              onChange={(e) =>
+                                setConfirmedDeletes((prev) => {
+                                  const next = new Set(prev);
+                                  if (e.target.checked) next.add(key);
+                                  else next.delete(key);
+                                  return next;
+                                })
+                              }
+                              aria-label={`Confirm deleting ${label}`}
+                            />
+                            Delete {keys.length} {label}
+                          </label>
+                        ) : (
+                          <span className="text-muted-foreground">
+                            {keys.length} {label}
+                          </span>
+                        )}
+                      </summary>
+                      <ul className="ml-4 mt-1 text-xs text-muted-foreground font-mono space-y-0.5 max-h-24 overflow-y-auto">
+                        {keys.map((k) => (
+                          <li key={k}>{k}</li>
+                        ))}
+                      </ul>
+                    </details>
+                  );
+                })}
+              </div>
+            )}
+
             {state.plan.toUpload.length === 0 && state.plan.toDelete.length === 0 ? (
               <div className="text-sm text-muted-foreground mb-6">
                 Everything is up to date. Nothing to sync.
               </div>
             ) : null}
 
+            {state.plan.mirror && (
+              <div className="mb-6 text-sm" data-testid="mirror-plan">
+                {state.plan.mirror.error ? (
+                  <div className="flex items-start gap-2 text-amber-600">
+                    <AlertCircle className="w-4 h-4 shrink-0 mt-0.5" />
+                    <span>Mirror {state.plan.mirror.bucket} will be skipped: {state.plan.mirror.error}</span>
+                  </div>
+                ) : (
+                  <>
+                    <span className="text-muted-foreground">
+                      Mirror {state.plan.mirror.bucket}: {state.plan.mirror.toUpload.length} to upload,{" "}
+                      {state.plan.mirror.toDelete.length} to delete, {state.plan.mirror.unchanged} up to date.
+                    </span>
+                    {state.plan.mirror.toDelete.length > 0 && (
+                      <details className="mt-1" data-testid="mirror-deletes">
+                        <summary className="cursor-pointer text-muted-foreground">
+                          {state.plan.mirror.toDelete.length} files to delete from the mirror
+                        </summary>
+                        <ul className="ml-4 mt-1 text-xs text-muted-foreground font-mono space-y-0.5 max-h-24 overflow-y-auto">
+                          {state.plan.mirror.toDelete.map((k) => (
+                            <li key={k}>{k}</li>
+                          ))}
+                        </ul>
+                      </details>
+                    )}
+                    {state.plan.mirror.deleteConfirmation && (
+                      <div className="mt-2" data-testid="mirror-delete-confirmation">
+                        <div className="flex items-center gap-2 text-destructive mb-2">
+                          <AlertCircle className="w-4 h-4 shrink-0" />
+                          <span>
+                            This will delete {state.plan.mirror.toDelete.length} files from the mirror. Check the mirror
+                            bucket in Settings, then type{" "}
+                            <span className="font-mono font-medium">{state.plan.mirror.deleteConfirmation}</span> to
+                            continue.
+                          </span>
+                        </div>
+                        <input
+                          type="text"
+                          value={mirrorConfirmText}
+                          onChange={(e) => setMirrorConfirmText(e.target.value)}
+                          placeholder={state.plan.mirror.deleteConfirmation}
+                          aria-label="Mirror delete confirmation"
+                          className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                        />
+                      </div>
+                    )}
+                  </>
+                )}
+              </div>
+            )}
+
+            {state.plan.imageWarnings && state.plan.imageWarnings.length > 0 && (
+              <div className="mb-6 text-sm" data-testid="image-warnings">
+                <div className="flex items-start gap-2 text-amber-600 mb-1">
+                  <AlertCircle className="w-4 h-4 shrink-0 mt-0.5" />
+                  <span>{state.plan.imageWarnings.length} photos may not display well on the web:</span>
+                </div>
+                <ul className="ml-6 text-xs text-muted-foreground space-y-0.5 max-h-24 overflow-y-auto">
+                  {state.plan.imageWarnings.map((w) => (
+                    <li key={w.path}>
+                      <span className="font-mono">{w.path}</span>: {w.message}
+                    </li>
+                  ))}
+                </ul>
+                {state.plan.imageWarnings.some((w) => w.fixable) && (
+                  <button
+                    onClick={handleConvertImages}
+                    disabled={converting}
+                    className="ml-6 mt-2 px-3 py-1 text-xs rounded-md border border-border hover:bg-accent transition-colors"
+                  >
+                    {converting ? "Converting…" : "Convert and Rescan"}
+                  </button>
+                )}
+              </div>
+            )}
+
+            {state.plan.budgetWarning && (
+              <div className="mb-6 flex items-start gap-2 text-sm text-amber-600" data-testid="budget-warning">
+                <AlertCircle className="w-4 h-4 shrink-0 mt-0.5" />
+                <span>{state.plan.budgetWarning}</span>
+              </div>
+            )}
+
+            {state.plan.deleteConfirmation && (
+              <div className="mb-6" data-testid="delete-confirmation">
+                <div className="flex items-center gap-2 text-sm text-destructive mb-2">
+                  <AlertCircle className="w-4 h-4 shrink-0" />
+                  <span>
+                    This will delete {state.plan.toDelete.length} files from S3. Check the S3 prefix in Settings,
+                    then type <span className="font-mono font-medium">{state.plan.deleteConfirmation}</span> to continue.
+                  </span>
+                </div>
+                <input
+                  type="text"
+                  value={confirmText}
+                  onChange={(e) => setConfirmText(e.target.value)}
+                  placeholder={state.plan.deleteConfirmation}
+                  aria-label="Delete confirmation"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm font-mono focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+              </div>
+            )}
+
+            {audit && !audit.ok && (
+              <div className="mb-6 flex items-start gap-2 text-sm text-destructive" data-testid="managed-key-audit">
+                <AlertCircle className="w-4 h-4 shrink-0 mt-0.5" />
+                <span>
+                  {audit.rulesMatch
+                    ? `${audit.outside.length} planned deletes fall outside the managed area (e.g. ${audit.outside[0]}). Publishing is blocked.`
+                    : "The S3 prefix or managed paths changed since this preview. Close and preview the publish again."}
+                </span>
+              </div>
+            )}
+
             <div className="flex justify-end gap-3">
               <button
                 onClick={onClose}
@@ -249,13 +544,23 @@ export function PublishPreviewDialog({
               >
                 Cancel
               </button>
-              <button
-                onClick={handlePublish}
-                disabled={state.plan.toUpload.length === 0 && state.plan.toDelete.length === 0}
-                className="px-4 py-2 text-sm rounded-md bg-primary text-primary-foreground hover:opacity-90 transition-opacity disabled:opacity-50"
-              >
-                Publish Now
-              </button>
+              {recordSnapshotButton}
+              {!state.plan.simulatedAt && (
+                <button
+                  onClick={handlePublish}
+                  disabled={
+                    (state.plan.toUpload.length === 0 && state.plan.toDelete.length === 0) ||
+                    (!!audit && !audit.ok) ||
+                    (!!state.plan.deleteConfirmation && confirmText.trim() !== state.plan.deleteConfirmation) ||
+                    (!!state.plan.mirror?.deleteConfirmation &&
+                      mirrorConfirmText.trim() !== state.plan.mirror.deleteConfirmation) ||
+                    unconfirmedDeletes(state.plan, confirmedDeletes)
+                  }
+                  className="px-4 py-2 text-sm rounded-md bg-primary text-primary-foreground hover:opacity-90 transition-opacity disabled:opacity-50"
+                >
+                  Publish Now
+                </button>
+              )}
             </div>
           </div>
         )}
@@ -275,7 +580,7 @@ export function PublishPreviewDialog({
                     <div
                       className="bg-primary h-2 rounded-full transition-all"
                       style={{
-                        width: `${Math.round((state.progress.current / state.progress.total) * 100)}%`,
+                        width: `${state.progress.total ? Math.round((state.progress.current / state.progress.total) * 100) : 0}%`,
                       }}
                       role="progressbar"
                       aria-valuenow={state.progress.current}
@@ -284,7 +589,7 @@ export function PublishPreviewDialog({
                     />
                   </div>
                   <div className="text-sm text-muted-foreground truncate" data-testid="current-file">
-                    {state.progress.action === "invalidate" ? "Invalidating CloudFront cache..." : state.progress.action === "upload" ? "Uploading" : "Deleting"}{" "}
+                    {state.progress.action === "invalidate" ? "Invalidating CloudFront cache..." : state.progress.action === "warm" ? "Warming CloudFront cache..." : state.progress.action === "upload" ? "Uploading" : "Deleting"}{" "}
                     {state.progress.file}
                   </div>
                 </>
@@ -295,6 +600,12 @@ export function PublishPreviewDialog({
                   Starting...
                 </div>
               )}
+              {mirrorProgress && !mirrorResult && (
+                <div className="mt-3 text-sm text-muted-foreground truncate" data-testid="mirror-progress">
+                  Mirror: {mirrorProgress.current} / {mirrorProgress.total} —{" "}
+                  {mirrorProgress.action === "upload" ? "Uploading" : "Deleting"} {mirrorProgress.file}
+                </div>
+              )}
             </div>
             <div className="flex justify-end">
               <button
@@ -317,6 +628,15 @@ export function PublishPreviewDialog({
               {state.result.uploaded} uploaded, {state.result.deleted} deleted,{" "}
               {state.result.unchanged} unchanged.
             </div>
+            {mirrorResult && (
+              <div
+                className={`text-sm mb-6 ${mirrorResult.failed > 0 ? "text-amber-600" : "text-muted-foreground"}`}
+                data-testid="mirror-result"
+              >
+                Mirror {mirrorResult.bucket}: {mirrorResult.uploaded} uploaded, {mirrorResult.deleted} deleted
+                {mirrorResult.failed > 0 && `, ${mirrorResult.failed} failed (${mirrorResult.errors[0]})`}.
+              </div>
+            )}
             <div className="flex justify-end">
               <button
                 onClick={onClose}
@@ -352,6 +672,7 @@ export function PublishPreviewDialog({
               >
                 Close
               </button>
+              {recordSnapshotButton}
               {state.plan.planId && (
                 <button
                   onClick={handleRetry}
@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { Loader2, CheckCircle, AlertCircle, RefreshCw } from "lucide-react";
-import type { AppSettings, ValidationResult } from "../types";
+import type { AccelerationComparison, AppSettings, AutomationEndpoint, AutoPublishSettings, CompanionDevice, CommentsSettings, CredentialStoreStatus, EncryptionStatus, LocaleOption, MirrorTarget, NetworkInterface, PairingCode, OldPrefixCleanupReport, OldPrefixObjects, PreviousTarget, SettingsChangeImpact, TargetTransfers, ValidationResult } from "../types";
 import {
   loadSettings,
   saveSettings,
@@ -8,13 +8,38 @@ import {
   hasCredentials as hasCredentialsCmd,
   getCredentialHint,
   deleteCredentials,
+  checkCredentialStore,
   validateCredentials,
+  startHealthChecks,
+  previewSettingsChange,
+  listOldPrefixObjects,
+  cleanupOldPrefix,
+  compareTransferAcceleration,
+  listLocales,
+  getTransferHistory,
+  getAutomationEndpoint,
+  startAutomationServer,
+  resetAutomationToken,
+  startCompanionServer,
+  startCompanionPairing,
+  startAutoPublish,
+  listCompanionDevices,
+  listNetworkInterfaces,
+  revokeCompanionDevice,
+  getEncryptionStatus,
+  enableEncryption,
+  unlockWorkspace,
+  lockWorkspace,
+  setGalleryEncrypted,
 } from "../commands";
 import { useUpdate } from "../context/UpdateContext";
+import { useWorkspace } from "../context/WorkspaceContext";
 
 interface SettingsDialogProps {
   open: boolean;
   onClose: () => void;
+  /** Open workspace, used to estimate the upload when the publish target changes. */
+  folderPath?: string | null;
 }
 
 type ValidationState =
@@ -23,7 +48,46 @@ type ValidationState =
   | { status: "success"; result: ValidationResult }
   | { status: "error"; message: string };
 
-export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
+const NO_COMMENTS: CommentsSettings = {
+  provider: "",
+  giscusRepo: "",
+  giscusRepoId: "",
+  giscusCategory: "",
+  giscusCategoryId: "",
+  cactusSiteName: "",
+  cactusHomeserverUrl: "",
+  cactusServerName: "",
+};
+
+const NO_MIRROR: MirrorTarget = { bucket: "", region: "", endpointUrl: "" };
+
+const CREDENTIAL_STORES: Record<string, string> = {
+  macosKeychain: "macOS Keychain",
+  windowsCredentialManager: "Windows Credential Manager",
+  secretService: "Secret Service",
+  keyutils: "Linux kernel keyring",
+  memory: "In-memory only",
+};
+
+type AccelerationState =
+  | { status: "idle" }
+  | { status: "loading" }
+  | { status: "done"; result: AccelerationComparison }
+  | { status: "error"; message: string };
+
+type ImpactState =
+  | { status: "idle" }
+  | { status: "loading" }
+  | { status: "ready"; impact: SettingsChangeImpact }
+  | { status: "error"; message: string };
+
+type PublishTarget = Pick<AppSettings, "bucket" | "region" | "s3Prefix">;
+
+function formatMegabytes(bytes: number): string {
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+export function SettingsDialog({ open, onClose, folderPath }: SettingsDialogProps) {
   const [settings, setSettings] = useState<AppSettings>({
     bucket: "",
     region: "ap-southeast-2",
@@ -42,12 +106,27 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
   const [isChangingCreds, setIsChangingCreds] = useState(false);
   const [validation, setValidation] = useState<ValidationState>({ status: "idle" });
   const [credentialsValidated, setCredentialsValidated] = useState(false);
+  const [credentialStore, setCredentialStore] = useState<CredentialStoreStatus | "checking" | null>(null);
   const [saving, setSaving] = useState(false);
+  const [savedTarget, setSavedTarget] = useState<PublishTarget | null>(null);
+  const [impact, setImpact] = useState<ImpactState>({ status: "idle" });
+  const [acceleration, setAcceleration] = useState<AccelerationState>({ status: "idle" });
+  const [locales, setLocales] = useState<LocaleOption[]>([]);
+  const [automationEndpoint, setAutomationEndpoint] = useState<AutomationEndpoint | null>(null);
+  const [companionDevices, setCompanionDevices] = useState<CompanionDevice[]>([]);
+  const [networkInterfaces, setNetworkInterfaces] = useState<NetworkInterface[]>([]);
+  const [pairingCode, setPairingCode] = useState<PairingCode | null>(null);
+  const [companionError, setCompanionError] = useState<string | null>(null);
 
   const loadCurrentSettings = useCallback(async () => {
     try {
       const s = await loadSettings();
       setSettings(s);
+      setSavedTarget({ bucket: s.bucket, region: s.region, s3Prefix: s.s3Prefix });
+      setLocales((await listLocales()) ?? []);
+      setAutomationEndpoint(await getAutomationEndpoint());
+      setCompanionDevices(await listCompanionDevices());
+      setNetworkInterfaces((await listNetworkInterfaces().catch(() => [])) ?? []);
       const has = await hasCredentialsCmd();
       setHasCreds(has);
       if (has) {
@@ -79,6 +158,30 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
     }
   }, [open, loadCurrentSettings]);
 
+  // A previewed impact only applies to the target it was computed for
+  useEffect(() => {
+    setImpact({ status: "idle" });
+  }, [settings.bucket, settings.region, settings.s3Prefix]);
+
+  const targetChanged =
+    !!savedTarget?.bucket &&
+    (settings.bucket !== savedTarget.bucket ||
+      settings.region !== savedTarget.region ||
+      settings.s3Prefix !== savedTarget.s3Prefix);
+
+  const comments = { ...NO_COMMENTS, ...settings.comments };
+  const setComments = (patch: Partial<CommentsSettings>) =>
+    setSettings((s) => ({ ...s, comments: { ...NO_COMMENTS, ...s.comments, ...patch } }));
+
+  const handleCompareAcceleration = async () => {
+    setAcceleration({ status: "loading" });
+    try {
+      setAcceleration({ status: "done", result: await compareTransferAcceleration() });
+    } catch (e) {
+      setAcceleration({ status: "error", message: String(e) });
+    }
+  };
+
   const handleValidate = async () => {
     // Credential inputs are visible when there are no saved creds OR user clicked "Change Credentials"
     const isEnteringCreds = !hasCreds || isChangingCreds;
@@ -149,9 +252,24 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
   };
 
   const handleSave = async () => {
+    // Show what moving the publish target means before saving it
+    if (targetChanged && impact.status === "idle") {
+      setImpact({ status: "loading" });
+      try {
+        setImpact({ status: "ready", impact: await previewSettingsChange(folderPath ?? null, settings) });
+      } catch (e) {
+        const message = e instanceof Error ? e.message : String(e);
+        setImpact({ status: "error", message });
+      }
+      return;
+    }
     setSaving(true);
     try {
       await saveSettings(settings);
+      startHealthChecks().catch(() => {});
+      startAutomationServer().catch(() => {});
+      startCompanionServer(folderPath ?? null).catch(() => {});
+      if (folderPath) startAutoPublish(folderPath).catch(() => {});
 
       if ((!hasCreds || isChangingCreds) && credentialsValidated && keyId && secret) {
         await saveCredentials(keyId, secret);
@@ -194,6 +312,11 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
     }));
   };
 
+  const handleCheckCredentialStore = async () => {
+    setCredentialStore("checking");
+    setCredentialStore(await checkCredentialStore());
+  };
+
   const isEnteringCredsForSave = !hasCreds || isChangingCreds;
   const canSaveCredentials = !isEnteringCredsForSave || credentialsValidated;
 
@@ -276,6 +399,39 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
               )}
             </div>
           )}
+
+          <div className="mt-3">
+            <button
+              onClick={handleCheckCredentialStore}
+              disabled={credentialStore === "checking"}
+              className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50"
+            >
+              {credentialStore === "checking" ? "Checking..." : "Check Credential Store"}
+            </button>
+            {credentialStore && credentialStore !== "checking" && (
+              <div className="mt-2 text-xs" data-testid="credential-store-status">
+                <div className="flex items-center gap-1.5">
+                  {credentialStore.writable && credentialStore.persistent ? (
+                    <CheckCircle className="h-3.5 w-3.5 text-green-600" />
+                  ) : (
+                    <AlertCircle className="h-3.5 w-3.5 text-destructive" />
+                  )}
+                  <span>{CREDENTIAL_STORES[credentialStore.backend] ?? credentialStore.backend}</span>
+                  <span className="text-muted-foreground">
+                    {credentialStore.writable ? "· writable" : "· not writable"}
+                  </span>
+                </div>
+                {!credentialStore.persistent && (
+                  <p className="mt-1 text-muted-foreground">
+                    {credentialStore.backend === "keyutils"
+                      ? "Credentials saved here are lost when the computer restarts."
+                      : "No system keyring is available to this build, so credentials aren't kept between launches."}
+                  </p>
+                )}
+                {credentialStore.error && <p className="mt-1 text-destructive">{credentialStore.error}</p>}
+              </div>
+            )}
+          </div>
         </div>
 
         {/* S3 Configuration */}
@@ -325,6 +481,652 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
                 className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
               />
             </div>
+            <div>
+              <label className="block text-sm mb-1">Site URL</label>
+              <input
+                type="text"
+                value={settings.siteUrl ?? ""}
+                onChange={(e) => setSettings((s) => ({ ...s, siteUrl: e.target.value }))}
+                placeholder="https://photos.example.com/"
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+              <p className="mt-1 text-xs text-muted-foreground">
+                Public address of the published site. Used for gallery links in announcements.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Health Check Interval (minutes)</label>
+              <input
+                type="number"
+                min={0}
+                value={settings.healthCheckIntervalMinutes ?? 0}
+                onChange={(e) =>
+                  setSettings((s) => ({ ...s, healthCheckIntervalMinutes: Math.max(0, Number(e.target.value) || 0) }))
+                }
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+              <p className="mt-1 text-xs text-muted-foreground">
+                While the app is open, periodically fetches the site and its JSON over HTTPS and warns about failures. 0 disables.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Delete Protection (files / % of site)</label>
+              <div className="flex gap-2">
+                <input
+                  type="number"
+                  min={0}
+                  value={settings.deleteProtectionMaxCount ?? ""}
+                  onChange={(e) =>
+                    setSettings((s) => ({
+                      ...s,
+                      deleteProtectionMaxCount: e.target.value === "" ? undefined : Math.max(0, Number(e.target.value) || 0),
+                    }))
+                  }
+                  placeholder="100"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+                <input
+                  type="number"
+                  min={0}
+                  max={100}
+                  value={settings.deleteProtectionMaxFraction === undefined ? "" : Math.round(settings.deleteProtectionMaxFraction * 100)}
+                  onChange={(e) =>
+                    setSettings((s) => ({
+                      ...s,
+                      deleteProtectionMaxFraction:
+                        e.target.value === "" ? undefined : Math.min(100, Math.max(0, Number(e.target.value) || 0)) / 100,
+                    }))
+                  }
+                  placeholder="50"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+              </div>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Publishes that would delete more files than this from S3 must be confirmed by typing a token.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Additional Managed Paths</label>
+              <input
+                type="text"
+                value={(settings.managedExtraPaths ?? []).join(" ")}
+                onChange={(e) =>
+                  setSettings((s) => ({ ...s, managedExtraPaths: e.target.value.split(" ") }))
+                }
+                placeholder="robots.txt downloads/"
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+              <p className="mt-1 text-xs text-muted-foreground">
+                Space-separated files and folders (ending in /) under the S3 prefix that this site owns, besides
+                galleries/, afterglow/, index.html, the favicons and site-manifest.json. Publishing deletes files there
+                that it no longer uploads.
+              </p>
+            </div>
+            <div>
+              <label className="flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  checked={settings.transferAcceleration ?? false}
+                  onChange={(e) => setSettings((s) => ({ ...s, transferAcceleration: e.target.checked }))}
+                />
+                Use S3 Transfer Acceleration
+              </label>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Uploads through the accelerated endpoint when the bucket has it enabled, falling back to the regular
+                endpoint if an upload fails. Accelerated transfers cost extra.
+              </p>
+              <div className="mt-2 flex items-center gap-2">
+                <button
+                  onClick={handleCompareAcceleration}
+                  disabled={!hasCreds || acceleration.status === "loading"}
+                  className="flex items-center gap-2 px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50"
+                >
+                  {acceleration.status === "loading" && <Loader2 className="w-3 h-3 animate-spin" />}
+                  Compare Speed
+                </button>
+                {acceleration.status === "done" && (
+                  <span className="text-xs text-muted-foreground" data-testid="acceleration-result">
+                    Standard {acceleration.result.standardMs} ms
+                    {acceleration.result.acceleratedMs != null
+                      ? `, accelerated ${acceleration.result.acceleratedMs} ms`
+                      : ` — ${acceleration.result.error ?? "accelerated upload not available"}`}
+                  </span>
+                )}
+                {acceleration.status === "error" && (
+                  <span className="text-xs text-destructive">{acceleration.message}</span>
+                )}
+              </div>
+            </div>
+            <div>
+              <label className="flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  checked={settings.cacheWarmUp ?? false}
+                  onChange={(e) => setSettings((s) => ({ ...s, cacheWarmUp: e.target.checked }))}
+                />
+                Warm CloudFront cache after publishing
+              </label>
+              <input
+                type="number"
+                min={0}
+                value={settings.cacheWarmThumbnails ?? ""}
+                onChange={(e) =>
+                  setSettings((s) => ({
+                    ...s,
+                    cacheWarmThumbnails: e.target.value === "" ? undefined : Math.max(0, Number(e.target.value) || 0),
+                  }))
+                }
+                disabled={!settings.cacheWarmUp}
+                placeholder="12"
+                aria-label="Thumbnails warmed per gallery"
+                className="mt-2 w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring disabled:opacity-50"
+              />
+              <p className="mt-1 text-xs text-muted-foreground">
+                Once the invalidation completes, fetches the index page, site manifest and this many thumbnails per
+                gallery through the Site URL so the first visitor gets cached copies. Requires a Site URL.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Monthly Budget</label>
+              <input
+                type="number"
+                min={0}
+                step="0.01"
+                value={settings.monthlyBudget ?? ""}
+                onChange={(e) =>
+                  setSettings((s) => ({
+                    ...s,
+                    monthlyBudget: e.target.value === "" ? undefined : Math.max(0, Number(e.target.value) || 0),
+                  }))
+                }
+                placeholder="No budget check"
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+              <label className="mt-2 flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  checked={settings.blockOverBudget ?? false}
+                  onChange={(e) => setSettings((s) => ({ ...s, blockOverBudget: e.target.checked }))}
+                  disabled={settings.monthlyBudget === undefined}
+                />
+                Block publishing when over budget
+              </label>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Previewing a publish checks this month's S3 and CloudFront spend in Cost Explorer and warns when it is
+                over the budget. Needs the ce:GetCostAndUsage permission; AWS charges $0.01 per check.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Archive Location (bucket / prefix)</label>
+              <div className="flex gap-2">
+                <input
+                  type="text"
+                  value={settings.archiveBucket ?? ""}
+                  onChange={(e) => setSettings((s) => ({ ...s, archiveBucket: e.target.value }))}
+                  placeholder={settings.bucket || "Site bucket"}
+                  aria-label="Archive bucket"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+                <input
+                  type="text"
+                  value={settings.archivePrefix ?? ""}
+                  onChange={(e) => setSettings((s) => ({ ...s, archivePrefix: e.target.value }))}
+                  placeholder="afterglow-archive/"
+                  aria-label="Archive prefix"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+              </div>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Where gallery originals are archived to Glacier Deep Archive. Must be outside the published site's
+                files when using the site bucket.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Metadata Backups Kept</label>
+              <input
+                type="number"
+                min={0}
+                max={365}
+                value={settings.metadataBackupRetention ?? ""}
+                onChange={(e) =>
+                  setSettings((s) => ({
+                    ...s,
+                    metadataBackupRetention: e.target.value === "" ? null : Math.max(0, Number(e.target.value) || 0),
+                  }))
+                }
+                placeholder="14"
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+              <p className="mt-1 text-xs text-muted-foreground">
+                While a workspace is open, its galleries.json and gallery-details.json files are backed up daily to the
+                app's data folder. 0 turns backups off.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Mirror Bucket (bucket / region / endpoint)</label>
+              <div className="flex gap-2">
+                <input
+                  type="text"
+                  value={settings.mirror?.bucket ?? ""}
+                  onChange={(e) => setSettings((s) => ({ ...s, mirror: { ...NO_MIRROR, ...s.mirror, bucket: e.target.value } }))}
+                  placeholder="None"
+                  aria-label="Mirror bucket"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+                <input
+                  type="text"
+                  value={settings.mirror?.region ?? ""}
+                  onChange={(e) => setSettings((s) => ({ ...s, mirror: { ...NO_MIRROR, ...s.mirror, region: e.target.value } }))}
+                  placeholder="eu-west-1"
+                  aria-label="Mirror region"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+                <input
+                  type="text"
+                  value={settings.mirror?.endpointUrl ?? ""}
+                  onChange={(e) => setSettings((s) => ({ ...s, mirror: { ...NO_MIRROR, ...s.mirror, endpointUrl: e.target.value } }))}
+                  placeholder="AWS"
+                  aria-label="Mirror endpoint"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+              </div>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Every publish also uploads to this bucket, in another region or on an S3-compatible provider
+                (set its https endpoint). Mirror failures are reported but never fail the publish.
+              </p>
+            </div>
+          </div>
+        </div>
+
+        {/* Automation */}
+        <div className="mb-6">
+          <h3 className="text-sm font-medium mb-3 text-muted-foreground">Automation</h3>
+          <div className="space-y-3">
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                checked={settings.automationApi ?? false}
+                onChange={(e) => setSettings((s) => ({ ...s, automationApi: e.target.checked }))}
+              />
+              Local automation API
+            </label>
+            <p className="text-xs text-muted-foreground">
+              Lets scripts on this computer import, validate, preview and publish through JSON-RPC while the app is
+              open. Clients must authenticate with the token first.
+            </p>
+            {settings.automationApi && automationEndpoint && (
+              <div className="space-y-1 text-xs">
+                <div>
+                  Endpoint: <code>{automationEndpoint.address}</code>
+                </div>
+                <div className="flex items-center gap-2">
+                  <span>
+                    Token: <code className="break-all">{automationEndpoint.token}</code>
+                  </span>
+                  <button
+                    onClick={async () => {
+                      const token = await resetAutomationToken();
+                      setAutomationEndpoint((ep) => (ep ? { ...ep, token } : ep));
+                      startAutomationServer().catch(() => {});
+                    }}
+                    className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors"
+                  >
+                    New Token
+                  </button>
+                </div>
+              </div>
+            )}
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                checked={settings.autoPublish?.enabled ?? false}
+                onChange={(e) => setSettings((s) => ({ ...s, autoPublish: { ...s.autoPublish, enabled: e.target.checked } }))}
+              />
+              Auto-publish
+            </label>
+            <p className="text-xs text-muted-foreground">
+              Publishes the open workspace once it has been quiet for a while after changing, for when the folder is the
+              source of truth. Publishes over these limits, needing a delete confirmation or over budget are left for
+              you to publish manually.
+            </p>
+            {settings.autoPublish?.enabled && (
+              <div className="flex gap-2">
+                {(
+                  [
+                    ["quietMinutes", "Quiet minutes", "10"],
+                    ["maxUploads", "Max uploads", "200"],
+                    ["maxDeletes", "Max deletions", "0"],
+                  ] as [keyof Omit<AutoPublishSettings, "enabled">, string, string][]
+                ).map(([field, label, placeholder]) => (
+                  <div key={field} className="flex-1">
+                    <label className="block text-sm mb-1">{label}</label>
+                    <input
+                      type="number"
+                      min={field === "quietMinutes" ? 1 : 0}
+                      value={settings.autoPublish?.[field] ?? ""}
+                      onChange={(e) =>
+                        setSettings((s) => ({
+                          ...s,
+                          autoPublish: {
+                            enabled: true,
+                            ...s.autoPublish,
+                            [field]: e.target.value === "" ? null : Math.max(0, Number(e.target.value) || 0),
+                          },
+                        }))
+                      }
+                      placeholder={placeholder}
+                      className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                    />
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+        </div>
+
+        {/* Companion */}
+        <div className="mb-6">
+          <h3 className="text-sm font-medium mb-3 text-muted-foreground">Companion Devices</h3>
+          <div className="space-y-3">
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                checked={settings.companionApi ?? false}
+                onChange={(e) => setSettings((s) => ({ ...s, companionApi: e.target.checked }))}
+              />
+              Allow paired devices on the local network
+            </label>
+            <p className="text-xs text-muted-foreground">
+              Paired phones and tablets can browse the open workspace and start a publish. Traffic is plain HTTP, not
+              encrypted: anyone on the same network can read the pairing code, the device tokens and your photos, and a
+              captured token lets them start publishes. Only use this on a network you trust, such as your home Wi-Fi,
+              never on public or shared networks.
+            </p>
+            {settings.companionApi && (
+              <>
+                <div>
+                  <label className="block text-sm mb-1">Port</label>
+                  <input
+                    type="number"
+                    min={1024}
+                    max={65535}
+                    value={settings.companionPort ?? ""}
+                    onChange={(e) =>
+                      setSettings((s) => ({ ...s, companionPort: e.target.value ? Number(e.target.value) : null }))
+                    }
+                    placeholder="47800"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm mb-1">Listen on</label>
+                  <select
+                    value={settings.companionAddress ?? ""}
+                    onChange={(e) => setSettings((s) => ({ ...s, companionAddress: e.target.value || null }))}
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  >
+                    <option value="">Default network (LAN address)</option>
+                    {networkInterfaces.map((i) => (
+                      <option key={`${i.name}-${i.address}`} value={i.address}>
+                        {i.loopback ? `This computer only (${i.address})` : `${i.name} (${i.address})`}
+                      </option>
+                    ))}
+                    {settings.companionAddress &&
+                      !networkInterfaces.some((i) => i.address === settings.companionAddress) && (
+                        <option value={settings.companionAddress}>{settings.companionAddress} (not available)</option>
+                      )}
+                  </select>
+                  <p className="text-xs text-muted-foreground mt-1">
+                    The companion API only accepts connections on this interface.
+                  </p>
+                </div>
+                <div className="flex items-center gap-2">
+                  <button
+                    onClick={async () => {
+                      setCompanionError(null);
+                      try {
+                        setPairingCode(await startCompanionPairing());
+                      } catch (e) {
+                        setCompanionError(e instanceof Error ? e.message : String(e));
+                      }
+                    }}
+                    className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors"
+                  >
+                    Pair Device
+                  </button>
+                  {pairingCode && (
+                    <span className="text-sm">
+                      Code <code className="text-base font-medium">{pairingCode.code}</code>
+                      <span className="text-xs text-muted-foreground"> (expires {new Date(pairingCode.expiresAt).toLocaleTimeString()})</span>
+                    </span>
+                  )}
+                </div>
+                {companionError && <p className="text-xs text-destructive">{companionError}</p>}
+                {companionDevices.length > 0 && (
+                  <ul className="space-y-1 text-sm">
+                    {companionDevices.map((d) => (
+                      <li key={d.id} className="flex items-center justify-between gap-2">
+                        <span>
+                          {d.name}{" "}
+                          <span className="text-xs text-muted-foreground">paired {new Date(d.pairedAt).toLocaleDateString()}</span>
+                        </span>
+                        <button
+                          onClick={async () => {
+                            await revokeCompanionDevice(d.id);
+                            setCompanionDevices(await listCompanionDevices());
+                          }}
+                          className="px-3 py-1.5 text-sm rounded-md border border-border text-destructive hover:bg-destructive/10 transition-colors"
+                        >
+                          Revoke
+                        </button>
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </>
+            )}
+          </div>
+        </div>
+
+        {folderPath && <EncryptionSection open={open} folderPath={folderPath} />}
+
+        {/* Language */}
+        <div className="mb-6">
+          <h3 className="text-sm font-medium mb-3 text-muted-foreground">Language</h3>
+          <div>
+            <label className="block text-sm mb-1">Error Messages</label>
+            <select
+              value={settings.locale ?? ""}
+              onChange={(e) => setSettings((s) => ({ ...s, locale: e.target.value }))}
+              className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+            >
+              <option value="">Default (English)</option>
+              {locales.map((l) => (
+                <option key={l.code} value={l.code}>
+                  {l.name}
+                </option>
+              ))}
+            </select>
+          </div>
+        </div>
+
+        {/* Published Site */}
+        <div className="mb-6">
+          <h3 className="text-sm font-medium mb-3 text-muted-foreground">Published Site</h3>
+          <div className="space-y-3">
+            <div>
+              <label className="block text-sm mb-1">Site Language</label>
+              <input
+                type="text"
+                value={settings.siteLanguage ?? ""}
+                onChange={(e) => setSettings((s) => ({ ...s, siteLanguage: e.target.value }))}
+                placeholder="en-AU"
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Site Description</label>
+              <textarea
+                value={settings.siteDescription ?? ""}
+                onChange={(e) => setSettings((s) => ({ ...s, siteDescription: e.target.value }))}
+                placeholder="Match and event photos from…"
+                rows={2}
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+              <p className="mt-1 text-xs text-muted-foreground">
+                Shown by search engines. Published as the index page's meta description.
+              </p>
+            </div>
+            <div>
+              <label className="block text-sm mb-1">Pinned Site Template</label>
+              <div className="flex gap-2">
+                <input
+                  type="text"
+                  value={settings.siteTemplatePin ?? ""}
+                  onChange={(e) => setSettings((s) => ({ ...s, siteTemplatePin: e.target.value }))}
+                  placeholder="Follow app updates"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+                {settings.siteTemplatePin && (
+                  <button
+                    onClick={() => setSettings((s) => ({ ...s, siteTemplatePin: "" }))}
+                    className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors"
+                  >
+                    Unpin
+                  </button>
+                )}
+              </div>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Version of the website files to keep on the live site. Publishing leaves them as they are instead of upgrading to the template bundled with this app.
+              </p>
+            </div>
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                checked={settings.accessibilityEnhancements ?? true}
+                onChange={(e) => setSettings((s) => ({ ...s, accessibilityEnhancements: e.target.checked }))}
+              />
+              Accessibility enhancements (skip link, ARIA labels)
+            </label>
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                checked={settings.contentSecurityPolicy ?? false}
+                onChange={(e) => setSettings((s) => ({ ...s, contentSecurityPolicy: e.target.checked }))}
+              />
+              Content Security Policy
+            </label>
+            {settings.contentSecurityPolicy && (
+              <div>
+                <label className="block text-sm mb-1">Additional Allowed Origins</label>
+                <input
+                  type="text"
+                  value={(settings.cspExtraSources ?? []).join(" ")}
+                  onChange={(e) =>
+                    setSettings((s) => ({ ...s, cspExtraSources: e.target.value.split(" ") }))
+                  }
+                  placeholder="https://cdn.example.com"
+                  className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                />
+                <p className="mt-1 text-xs text-muted-foreground">
+                  Space-separated. The site's own analytics snippet, web fonts and comment widget are allowed automatically.
+                </p>
+              </div>
+            )}
+            <div>
+              <label className="block text-sm mb-1">Gallery Comments</label>
+              <select
+                value={comments.provider}
+                onChange={(e) => setComments({ provider: e.target.value })}
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              >
+                <option value="">None</option>
+                <option value="giscus">giscus (GitHub Discussions)</option>
+                <option value="cactus">Cactus Comments (Matrix)</option>
+              </select>
+              <p className="mt-1 text-xs text-muted-foreground">
+                Adds a comment thread under each gallery, keyed by its slug (or <code>commentsThreadId</code> in its
+                details). Set <code>"comments": false</code> in a gallery's details to turn it off there.
+              </p>
+            </div>
+            {comments.provider === "giscus" && (
+              <>
+                <div>
+                  <label className="block text-sm mb-1">Repository</label>
+                  <input
+                    type="text"
+                    value={comments.giscusRepo}
+                    onChange={(e) => setComments({ giscusRepo: e.target.value })}
+                    placeholder="owner/photos"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm mb-1">Repository ID</label>
+                  <input
+                    type="text"
+                    value={comments.giscusRepoId}
+                    onChange={(e) => setComments({ giscusRepoId: e.target.value })}
+                    placeholder="R_kgDO…"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm mb-1">Category</label>
+                  <input
+                    type="text"
+                    value={comments.giscusCategory}
+                    onChange={(e) => setComments({ giscusCategory: e.target.value })}
+                    placeholder="Galleries"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm mb-1">Category ID</label>
+                  <input
+                    type="text"
+                    value={comments.giscusCategoryId}
+                    onChange={(e) => setComments({ giscusCategoryId: e.target.value })}
+                    placeholder="DIC_kwDO…"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+              </>
+            )}
+            {comments.provider === "cactus" && (
+              <>
+                <div>
+                  <label className="block text-sm mb-1">Site Name</label>
+                  <input
+                    type="text"
+                    value={comments.cactusSiteName}
+                    onChange={(e) => setComments({ cactusSiteName: e.target.value })}
+                    placeholder="my-photos"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm mb-1">Homeserver URL</label>
+                  <input
+                    type="text"
+                    value={comments.cactusHomeserverUrl}
+                    onChange={(e) => setComments({ cactusHomeserverUrl: e.target.value })}
+                    placeholder="https://matrix.cactus.chat:8448"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm mb-1">Server Name</label>
+                  <input
+                    type="text"
+                    value={comments.cactusServerName}
+                    onChange={(e) => setComments({ cactusServerName: e.target.value })}
+                    placeholder="cactus.chat"
+                    className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+                  />
+                </div>
+              </>
+            )}
           </div>
         </div>
 
@@ -349,8 +1151,37 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
         </div>
 
         {/* About */}
+        {settings.previousTarget && (
+          <OldLocationSection
+            previous={settings.previousTarget}
+            onCleaned={() => setSettings((s) => ({ ...s, previousTarget: undefined }))}
+          />
+        )}
+
+        <DataUsageSection open={open} />
+
         <AboutSection />
 
+        {impact.status === "ready" && (
+          <div className="mb-4 rounded-md border border-border p-3 text-sm space-y-1" data-testid="settings-impact">
+            <div className="font-medium">Changing the publish target</div>
+            <div className="text-muted-foreground">
+              {impact.impact.orphanedObjects} files ({formatMegabytes(impact.impact.orphanedBytes)}) will be left at the
+              old location.
+            </div>
+            <div className="text-muted-foreground">
+              The next publish uploads about {impact.impact.uploadFiles} files (
+              {formatMegabytes(impact.impact.uploadBytes)}) and regenerates {impact.impact.thumbnailsToRegenerate}{" "}
+              thumbnails.
+            </div>
+          </div>
+        )}
+        {impact.status === "error" && (
+          <div className="mb-4 text-sm text-muted-foreground" data-testid="settings-impact">
+            Could not preview the effect of this change: {impact.message}
+          </div>
+        )}
+
         {/* Actions */}
         <div className="flex justify-end gap-3">
           <button
@@ -361,10 +1192,10 @@ export function SettingsDialog({ open, onClose }: SettingsDialogProps) {
           </button>
           <button
             onClick={handleSave}
-            disabled={saving || !canSaveCredentials}
+            disabled={saving || impact.status === "loading" || !canSaveCredentials}
             className="px-4 py-2 text-sm rounded-md bg-primary text-primary-foreground hover:opacity-90 transition-opacity disabled:opacity-50"
           >
-            {saving ? "Saving..." : "Save"}
+            {saving ? "Saving..." : impact.status === "loading" ? "Checking..." : impact.status === "idle" ? "Save" : "Save Anyway"}
           </button>
         </div>
       </div>
@@ -402,6 +1233,238 @@ function ValidationStatus({ validation }: { validation: ValidationState }) {
   );
 }
 
+type CleanupState =
+  | { status: "idle" }
+  | { status: "loading" }
+  | { status: "review"; objects: OldPrefixObjects }
+  | { status: "deleting"; objects: OldPrefixObjects }
+  | { status: "done"; report: OldPrefixCleanupReport }
+  | { status: "error"; message: string };
+
+function OldLocationSection({ previous, onCleaned }: { previous: PreviousTarget; onCleaned: () => void }) {
+  const [state, setState] = useState<CleanupState>({ status: "idle" });
+  const location = `s3://${previous.bucket}/${previous.s3Prefix}`;
+
+  const handleReview = async () => {
+    setState({ status: "loading" });
+    try {
+      setState({ status: "review", objects: await listOldPrefixObjects() });
+    } catch (e) {
+      setState({ status: "error", message: e instanceof Error ? e.message : String(e) });
+    }
+  };
+
+  const handleDelete = async (objects: OldPrefixObjects) => {
+    setState({ status: "deleting", objects });
+    try {
+      const report = await cleanupOldPrefix(objects.keys);
+      setState({ status: "done", report });
+      if (report.complete) onCleaned();
+    } catch (e) {
+      setState({ status: "error", message: e instanceof Error ? e.message : String(e) });
+    }
+  };
+
+  return (
+    <div className="mb-6" data-testid="old-location">
+      <h3 className="text-sm font-medium mb-3 text-muted-foreground">Previous Location</h3>
+      <p className="text-sm mb-2">
+        The site was previously published to <span className="font-mono">{location}</span>, which may still serve stale
+        content.
+      </p>
+      {(state.status === "idle" || state.status === "loading") && (
+        <button
+          onClick={handleReview}
+          disabled={state.status === "loading"}
+          className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50"
+        >
+          {state.status === "loading" ? "Checking..." : "Review Old Files"}
+        </button>
+      )}
+      {(state.status === "review" || state.status === "deleting") && (
+        <div className="space-y-2">
+          <p className="text-sm text-muted-foreground">
+            {state.objects.keys.length} files ({formatMegabytes(state.objects.totalBytes)}) remain at the old location.
+          </p>
+          {state.objects.keys.length > 0 && (
+            <button
+              onClick={() => handleDelete(state.objects)}
+              disabled={state.status === "deleting"}
+              className="px-3 py-1.5 text-sm rounded-md bg-destructive text-destructive-foreground hover:opacity-90 transition-opacity disabled:opacity-50"
+            >
+              {state.status === "deleting" ? "Deleting..." : `Delete ${state.objects.keys.length} Files`}
+            </button>
+          )}
+        </div>
+      )}
+      {state.status === "done" && (
+        <p className="text-sm text-muted-foreground">
+          Deleted {state.report.deleted} files ({formatMegabytes(state.report.deletedBytes)})
+          {state.report.skipped.length > 0 && `, skipped ${state.report.skipped.length}`}
+          {state.report.failed.length > 0 && `, ${state.report.failed.length} failed (${state.report.failed[0].error})`}.
+        </p>
+      )}
+      {state.status === "error" && <p className="text-sm text-destructive">{state.message}</p>}
+    </div>
+  );
+}
+
+/** Passphrase, unlocking and per-gallery marking for the open workspace's encrypted galleries. */
+function EncryptionSection({ open, folderPath }: { open: boolean; folderPath: string }) {
+  const { state, refreshEncryption } = useWorkspace();
+  const [status, setStatus] = useState<EncryptionStatus | null>(null);
+  const [passphrase, setPassphrase] = useState("");
+  const [repeated, setRepeated] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const reload = useCallback(async () => {
+    setStatus((await getEncryptionStatus(folderPath)) ?? null);
+    await refreshEncryption();
+  }, [folderPath, refreshEncryption]);
+
+  useEffect(() => {
+    if (open) reload().catch(() => setStatus(null));
+  }, [open, reload]);
+
+  const run = async (action: () => Promise<unknown>) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await action();
+      setPassphrase("");
+      setRepeated("");
+      await reload();
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (state.readOnly) return null;
+  const configured = status?.configured ?? false;
+  const marked = new Map((status?.galleries ?? []).map((g) => [g.slug, g]));
+
+  return (
+    <div className="mb-6" data-testid="encryption">
+      <h3 className="text-sm font-medium mb-3 text-muted-foreground">Encrypted Galleries</h3>
+      <div className="space-y-3">
+        <p className="text-xs text-muted-foreground">
+          Originals of encrypted galleries stay encrypted in the workspace folder. While the workspace is unlocked,
+          previews and publishing read decrypted copies kept in the app&apos;s cache folder, outside the workspace, which
+          are removed when it is locked or the app closes. The passphrase can&apos;t be recovered.
+        </p>
+        {status?.unlocked ? (
+          <>
+            <ul className="space-y-1 text-sm">
+              {state.galleries.map((g) => {
+                const gallery = marked.get(g.slug);
+                return (
+                  <li key={g.slug}>
+                    <label className="flex items-center gap-2">
+                      <input
+                        type="checkbox"
+                        checked={gallery !== undefined}
+                        disabled={busy}
+                        onChange={(e) => run(() => setGalleryEncrypted(folderPath, g.slug, e.target.checked))}
+                      />
+                      {g.name || g.slug}
+                      {gallery && gallery.plaintextFiles > 0 && (
+                        <span className="text-xs text-muted-foreground">
+                          ({gallery.plaintextFiles} new, encrypted when locked)
+                        </span>
+                      )}
+                    </label>
+                  </li>
+                );
+              })}
+            </ul>
+            <button
+              onClick={() => run(() => lockWorkspace(folderPath))}
+              disabled={busy}
+              className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50"
+            >
+              Lock
+            </button>
+          </>
+        ) : (
+          <>
+            <input
+              type="password"
+              aria-label="Encryption passphrase"
+              placeholder="Passphrase"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+              className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+            />
+            {!configured && (
+              <input
+                type="password"
+                aria-label="Repeat encryption passphrase"
+                placeholder="Repeat passphrase"
+                value={repeated}
+                onChange={(e) => setRepeated(e.target.value)}
+                className="w-full px-3 py-2 rounded-md border border-input bg-background text-sm focus:outline-none focus:ring-2 focus:ring-ring"
+              />
+            )}
+            <button
+              onClick={() =>
+                run(() => (configured ? unlockWorkspace(folderPath, passphrase) : enableEncryption(folderPath, passphrase)))
+              }
+              disabled={busy || !passphrase || (!configured && passphrase !== repeated)}
+              className="px-3 py-1.5 text-sm rounded-md border border-border hover:bg-accent transition-colors disabled:opacity-50"
+            >
+              {configured ? "Unlock" : "Set Passphrase"}
+            </button>
+          </>
+        )}
+        {error && <p className="text-xs text-destructive">{error}</p>}
+      </div>
+    </div>
+  );
+}
+
+const THIRTY_DAYS_MS = 30 * 24 * 60 * 60 * 1000;
+
+function DataUsageSection({ open }: { open: boolean }) {
+  const [targets, setTargets] = useState<TargetTransfers[]>([]);
+
+  useEffect(() => {
+    if (!open) return;
+    getTransferHistory()
+      .then((history) => setTargets(history ?? []))
+      .catch(() => setTargets([]));
+  }, [open]);
+
+  if (targets.length === 0) return null;
+
+  const since = Date.now() - THIRTY_DAYS_MS;
+  return (
+    <div className="mb-6" data-testid="data-usage">
+      <h3 className="text-sm font-medium mb-3 text-muted-foreground">Data Usage</h3>
+      <div className="space-y-2">
+        {targets.map((target) => {
+          const recent = target.publishes
+            .filter((p) => Date.parse(p.publishedAt) >= since)
+            .reduce((sum, p) => sum + p.bytesUploaded, 0);
+          return (
+            <div key={`${target.bucket}/${target.region}/${target.s3Prefix}`} className="text-sm">
+              <div className="font-mono text-xs">
+                s3://{target.bucket}/{target.s3Prefix} ({target.region})
+              </div>
+              <div className="text-muted-foreground">
+                {formatMegabytes(target.totalBytes)} in {target.totalFiles} files uploaded in total,{" "}
+                {formatMegabytes(recent)} in the last 30 days.
+              </div>
+            </div>
+          );
+        })}
+      </div>
+    </div>
+  );
+}
+
 function AboutSection() {
   const { status, currentVersion, checkForUpdate, downloadAndInstall } = useUpdate();
 
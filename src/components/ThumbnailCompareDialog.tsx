@@ -0,0 +1,92 @@
+import { useEffect, useState } from "react";
+import { convertFileSrc } from "@tauri-apps/api/core";
+import { Loader2 } from "lucide-react";
+import type { ThumbnailCandidate, ThumbnailComparison } from "../types";
+import { compareThumbnailSettings } from "../commands";
+
+const DEFAULT_CANDIDATES: ThumbnailCandidate[] = [
+  { maxEdge: 800, quality: 85, format: "webp" },
+  { maxEdge: 800, quality: 70, format: "webp" },
+  { maxEdge: 1200, quality: 85, format: "webp" },
+  { maxEdge: 800, quality: 85, format: "jpeg" },
+];
+
+function formatKb(bytes: number): string {
+  return `${(bytes / 1024).toFixed(1)} KB`;
+}
+
+interface ThumbnailCompareDialogProps {
+  open: boolean;
+  /** Absolute path of the source image. */
+  path: string;
+  onClose: () => void;
+}
+
+export function ThumbnailCompareDialog({ open, path, onClose }: ThumbnailCompareDialogProps) {
+  const [results, setResults] = useState<ThumbnailComparison[] | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    let cancelled = false;
+    setResults(null);
+    setError(null);
+    compareThumbnailSettings(path, DEFAULT_CANDIDATES)
+      .then((r) => !cancelled && setResults(r))
+      .catch((e) => !cancelled && setError(String(e)));
+    return () => {
+      cancelled = true;
+    };
+  }, [open, path]);
+
+  if (!open) return null;
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center">
+      <div className="fixed inset-0 bg-black/50" onClick={onClose} />
+      <div className="relative bg-background border border-border rounded-lg shadow-lg p-6 max-w-5xl w-full mx-4 max-h-[90vh] overflow-y-auto">
+        <h3 className="text-lg font-semibold mb-4">Compare Thumbnail Settings</h3>
+        {error && <p className="text-destructive text-sm mb-4">{error}</p>}
+        {!results && !error && (
+          <div className="flex items-center gap-2 text-sm text-muted-foreground mb-4">
+            <Loader2 className="w-4 h-4 animate-spin" />
+            Encoding…
+          </div>
+        )}
+        {results && (
+          <div className="grid grid-cols-2 gap-4 mb-4">
+            {results.map((r) => (
+              <figure key={r.previewPath} className="border border-border rounded-md p-2">
+                {r.error ? (
+                  <p className="text-destructive text-xs">{r.error}</p>
+                ) : (
+                  <img src={convertFileSrc(r.previewPath)} alt="" className="w-full h-auto" />
+                )}
+                <figcaption className="text-xs mt-2">
+                  <span className="font-medium">
+                    {r.candidate.format.toUpperCase()} {r.candidate.maxEdge}px
+                    {r.candidate.format !== "png" && `, quality ${r.candidate.quality}`}
+                  </span>
+                  {r.current && <span className="text-muted-foreground"> (current)</span>}
+                  {!r.error && (
+                    <span className="block text-muted-foreground">
+                      {r.width}×{r.height}, {formatKb(r.sizeBytes)}
+                    </span>
+                  )}
+                </figcaption>
+              </figure>
+            ))}
+          </div>
+        )}
+        <div className="flex justify-end">
+          <button
+            onClick={onClose}
+            className="px-4 py-2 text-sm rounded-md border border-border hover:bg-accent transition-colors"
+          >
+            Close
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
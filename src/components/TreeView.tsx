@@ -1,8 +1,9 @@
 import { useState, useEffect } from "react";
 import { useWorkspace } from "../context/WorkspaceContext";
 import { cn } from "../lib/utils";
-import { FolderOpen, Folder, Settings, Upload } from "lucide-react";
-import { hasCredentials, loadSettings } from "../commands";
+import { FolderOpen, Folder, Settings, Upload, FlaskConical, Zap } from "lucide-react";
+import { toast } from "sonner";
+import { hasCredentials, loadSettings, saveSettings, stopAutoPublish } from "../commands";
 import type { AppSettings } from "../types";
 import { SettingsDialog } from "./SettingsDialog";
 import { PublishPreviewDialog } from "./PublishPreviewDialog";
@@ -10,10 +11,11 @@ import { PublishPreviewDialog } from "./PublishPreviewDialog";
 export function TreeView() {
   const { state } = useWorkspace();
   const { dispatch } = useWorkspace();
-  const { folderName, subdirectories, selectedTreeNode, folderPath } = state;
+  const { folderName, subdirectories, selectedTreeNode, folderPath, readOnly } = state;
 
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [publishOpen, setPublishOpen] = useState(false);
+  const [simulateOpen, setSimulateOpen] = useState(false);
   const [hasCreds, setHasCreds] = useState(false);
   const [settings, setSettings] = useState<AppSettings | null>(null);
 
@@ -34,6 +36,21 @@ export function TreeView() {
   }, [settingsOpen]); // Re-check when settings dialog closes
 
   const publishEnabled = !!folderPath && hasCreds && !!settings?.lastValidatedUser;
+  const autoPublishing = !!folderPath && !readOnly && !!settings?.autoPublish?.enabled;
+
+  // Kill switch: stop right away, then keep it off
+  const turnOffAutoPublish = async () => {
+    if (!settings) return;
+    const next = { ...settings, autoPublish: { ...settings.autoPublish, enabled: false } };
+    try {
+      await stopAutoPublish();
+      await saveSettings(next);
+      setSettings(next);
+      toast.success("Auto-publish turned off.");
+    } catch (e) {
+      toast.error("Failed to turn off auto-publish", { description: e instanceof Error ? e.message : String(e) });
+    }
+  };
 
   return (
     <>
@@ -50,6 +67,9 @@ export function TreeView() {
           >
             <FolderOpen className="w-4 h-4 text-afterglow-accent flex-shrink-0" />
             <span className="truncate">{folderName}</span>
+            {readOnly && (
+              <span className="ml-auto text-[10px] uppercase tracking-wide text-muted-foreground">Read-only</span>
+            )}
           </button>
           <div className="ml-2 mt-0.5">
             {subdirectories.map((dir) => (
@@ -70,6 +90,20 @@ export function TreeView() {
           </div>
         </div>
 
+        {autoPublishing && (
+          <div className="border-t border-border px-3 py-1.5 flex items-center gap-2 text-xs text-muted-foreground">
+            <Zap className="w-3.5 h-3.5 text-afterglow-accent flex-shrink-0" />
+            <span className="flex-1">Auto-publish on</span>
+            <button
+              onClick={turnOffAutoPublish}
+              className="px-2 py-0.5 rounded border border-border hover:bg-accent transition-colors text-foreground"
+              data-testid="auto-publish-off"
+            >
+              Turn Off
+            </button>
+          </div>
+        )}
+
         {/* Sidebar Footer */}
         <div className="border-t border-border p-2 flex items-center gap-2">
           <button
@@ -99,12 +133,22 @@ export function TreeView() {
             <Upload className="w-4 h-4" />
             Publish
           </button>
+          <button
+            onClick={() => setSimulateOpen(true)}
+            disabled={!folderPath || !settings}
+            className="p-2 rounded-md hover:bg-accent/50 transition-colors text-muted-foreground hover:text-foreground disabled:opacity-50"
+            title="Simulate a publish against a recorded bucket snapshot, offline"
+            data-testid="simulate-button"
+          >
+            <FlaskConical className="w-4 h-4" />
+          </button>
         </div>
       </div>
 
       <SettingsDialog
         open={settingsOpen}
         onClose={() => setSettingsOpen(false)}
+        folderPath={folderPath}
       />
 
       {publishOpen && settings && folderPath && (
@@ -117,6 +161,18 @@ export function TreeView() {
           s3Root={settings.s3Prefix}
         />
       )}
+
+      {simulateOpen && settings && folderPath && (
+        <PublishPreviewDialog
+          open={simulateOpen}
+          onClose={() => setSimulateOpen(false)}
+          folderPath={folderPath}
+          bucket={settings.bucket}
+          region={settings.region}
+          s3Root={settings.s3Prefix}
+          simulate
+        />
+      )}
     </>
   );
 }
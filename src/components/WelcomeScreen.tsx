@@ -1,4 +1,5 @@
 import { useWorkspace } from "../context/WorkspaceContext";
+import { CompatibilityDialog } from "./CompatibilityDialog";
 
 export function WelcomeScreen() {
   const { openFolder } = useWorkspace();
@@ -9,13 +10,23 @@ export function WelcomeScreen() {
         <div className="text-6xl font-bold text-afterglow-accent tracking-tight">AG</div>
         <h1 className="text-2xl font-semibold">AfterGlowManager</h1>
         <p className="text-muted-foreground">Manage your galleries</p>
-        <button
-          onClick={openFolder}
-          className="px-6 py-3 bg-primary text-primary-foreground rounded-lg hover:opacity-90 transition-opacity font-medium"
-        >
-          Open Folder
-        </button>
+        <div className="flex flex-col items-center gap-2">
+          <button
+            onClick={() => openFolder()}
+            className="px-6 py-3 bg-primary text-primary-foreground rounded-lg hover:opacity-90 transition-opacity font-medium"
+          >
+            Open Folder
+          </button>
+          <button
+            onClick={() => openFolder(true)}
+            className="text-sm text-muted-foreground hover:text-foreground transition-colors"
+            title="Browse an archive or a synced copy without changing it"
+          >
+            Open Read-Only
+          </button>
+        </div>
       </div>
+      <CompatibilityDialog />
     </div>
   );
 }
@@ -1,17 +1,29 @@
-import React, { createContext, useContext, useReducer, useCallback, useRef } from "react";
+import React, { createContext, useContext, useReducer, useCallback, useRef, useState } from "react";
 import type {
   WorkspaceState,
   WorkspaceAction,
   GalleryEntry,
   GalleryDetails,
   PhotoEntry,
+  WorkspaceSummary,
+  CompatibilityReport,
 } from "../types";
 import {
   openFolderDialog,
   scanDirectory,
   readJsonFile,
   writeJsonFile,
+  getGalleryPhotosPage,
+  updateGalleryPhotos,
+  removeGalleryPhotos,
+  flushGalleryDetails,
+  setWorkspaceReadOnly,
+  checkWorkspaceCompatibility,
+  migrateWorkspace,
   fileExists,
+  loadWorkspaceSummary,
+  loadCachedWorkspaceSummary,
+  getEncryptionStatus,
 } from "../commands";
 import {
   migrateGalleries,
@@ -21,6 +33,9 @@ import {
 } from "../migrations";
 import { convertFileSrc } from "@tauri-apps/api/core";
 
+/** Photos per `getGalleryPhotosPage` call when loading a gallery. */
+const DETAILS_PAGE_SIZE = 500;
+
 const IMAGE_EXTENSIONS = ["jpg", "jpeg", "png", "gif", "webp", "avif", "bmp", "tiff", "tif"];
 
 function isImageFile(filename: string): boolean {
@@ -57,6 +72,9 @@ const initialState: WorkspaceState = {
   viewMode: "welcome",
   error: null,
   knownTags: [],
+  readOnly: false,
+  decryptedOriginals: {},
+  decryptedDir: null,
 };
 
 function mergeKnownTags(existing: string[], incoming: string[]): string[] {
@@ -78,6 +96,7 @@ function workspaceReducer(state: WorkspaceState, action: WorkspaceAction): Works
         folderName: action.name,
         viewMode: "galleries",
         selectedTreeNode: null,
+        readOnly: action.readOnly ?? false,
       };
     case "SET_GALLERIES":
       return { ...state, galleries: action.galleries, galleriesLastModified: action.lastModified };
@@ -238,6 +257,8 @@ function workspaceReducer(state: WorkspaceState, action: WorkspaceAction): Works
       return { ...state, error: action.error };
     case "SET_KNOWN_TAGS":
       return { ...state, knownTags: action.tags };
+    case "SET_DECRYPTED_ORIGINALS":
+      return { ...state, decryptedOriginals: action.originals, decryptedDir: action.dir };
     case "RESET":
       return initialState;
     default:
@@ -248,11 +269,19 @@ function workspaceReducer(state: WorkspaceState, action: WorkspaceAction): Works
 interface WorkspaceContextValue {
   state: WorkspaceState;
   dispatch: React.Dispatch<WorkspaceAction>;
-  openFolder: () => Promise<void>;
+  openFolder: (readOnly?: boolean) => Promise<void>;
+  /** Set while a folder chosen by `openFolder` waits for the user to pick a migration or read-only. */
+  pendingCompatibility: { path: string; name: string; report: CompatibilityReport } | null;
+  /** "migrate" throws (leaving the prompt open) when the migration fails. */
+  resolveCompatibility: (choice: "migrate" | "readOnly" | "cancel") => Promise<void>;
   loadGalleries: () => Promise<void>;
   saveGalleries: () => Promise<void>;
   loadGalleryDetails: (slug: string) => Promise<void>;
   saveGalleryDetails: () => Promise<void>;
+  /** Save fields of one photo through the backend's batched gallery store (not a whole-file write). */
+  savePhoto: (index: number, fields: Partial<PhotoEntry>) => Promise<void>;
+  /** Remove one photo from the open gallery, through the batched gallery store. */
+  removePhoto: (index: number) => Promise<void>;
   loadSubdirectories: () => Promise<void>;
   loadDirImages: (slug: string) => Promise<void>;
   addUntrackedGallery: (dirName: string) => Promise<void>;
@@ -260,8 +289,12 @@ interface WorkspaceContextValue {
   addAllUntrackedImages: () => Promise<void>;
   setCoverPhoto: (galleryIndex: number, coverPath: string) => Promise<void>;
   resolveImagePath: (jsonPath: string, slug?: string) => string;
+  /** Reload which originals of encrypted galleries have decrypted copies, after unlocking, locking or marking. */
+  refreshEncryption: () => Promise<void>;
   debouncedSaveGalleries: () => void;
   debouncedSaveGalleryDetails: () => void;
+  /** Run debounced saves now, wait for saves in progress and write photo edits the backend batches, so disk matches the UI state. */
+  flushPendingSaves: () => Promise<void>;
   refreshGalleryCount: (slug: string) => Promise<void>;
   syncGalleryDateToDetails: (galleryIndex: number) => Promise<void>;
 }
@@ -275,6 +308,18 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
 
   const saveTimerGalleries = useRef<ReturnType<typeof setTimeout> | null>(null);
   const saveTimerDetails = useRef<ReturnType<typeof setTimeout> | null>(null);
+  const savesInFlight = useRef(new Set<Promise<void>>());
+  /** Pages of the open gallery still loading; whole-file saves wait for them. */
+  const detailsLoading = useRef<Promise<void> | null>(null);
+  /** Bumped by each `loadGalleryDetails`, so pages of a gallery no longer open are dropped. */
+  const detailsLoadId = useRef(0);
+
+  /** Remember a save until it settles, for `flushPendingSaves`. Saves report their own errors. */
+  const trackSave = useCallback((save: Promise<void>) => {
+    savesInFlight.current.add(save);
+    save.finally(() => savesInFlight.current.delete(save));
+    return save;
+  }, []);
 
   const galleriesJsonPath = useCallback(() => {
     return `${stateRef.current.folderPath}/galleries.json`;
@@ -293,12 +338,34 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
       // Without slug: jsonPath is relative to workspace root (e.g. "sunset/01.jpg" for covers)
       // With slug: jsonPath is relative to the gallery dir (e.g. "01.jpg" for photos)
       const relativePath = slug ? `${slug}/${jsonPath}` : jsonPath;
-      const absPath = `${stateRef.current.folderPath}/${relativePath}`;
+      // Originals of encrypted galleries are only readable from their decrypted copies
+      const [gallery, ...rest] = relativePath.split("/");
+      const { decryptedDir, decryptedOriginals } = stateRef.current;
+      const decrypted = decryptedDir && rest.length === 1 && decryptedOriginals[gallery]?.includes(rest[0]);
+      const absPath = decrypted ? `${decryptedDir}/${relativePath}` : `${stateRef.current.folderPath}/${relativePath}`;
       return convertFileSrc(absPath);
     },
     []
   );
 
+  const loadEncryption = useCallback(async (folderPath: string) => {
+    try {
+      const status = await getEncryptionStatus(folderPath);
+      if (stateRef.current.folderPath !== folderPath) return;
+      const originals: Record<string, string[]> = {};
+      for (const gallery of status?.galleries ?? []) {
+        if (status.unlocked && gallery.decrypted.length > 0) originals[gallery.slug] = gallery.decrypted;
+      }
+      dispatch({ type: "SET_DECRYPTED_ORIGINALS", originals, dir: status?.unlocked ? status.decryptedDir : null });
+    } catch {
+      // No encryption settings to read; nothing is decrypted
+    }
+  }, []);
+
+  const refreshEncryption = useCallback(async () => {
+    if (stateRef.current.folderPath) await loadEncryption(stateRef.current.folderPath);
+  }, [loadEncryption]);
+
   const loadSubdirectories = useCallback(async () => {
     if (!stateRef.current.folderPath) return;
     const listing = await scanDirectory(stateRef.current.folderPath);
@@ -311,33 +378,6 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
     dispatch({ type: "SET_DIR_IMAGES", images: listing.images });
   }, []);
 
-  const loadGalleryCounts = useCallback(async (galleries: GalleryEntry[]) => {
-    if (!stateRef.current.folderPath) return;
-    const counts: Record<string, { tracked: number; total: number }> = {};
-    await Promise.all(
-      galleries.map(async (g) => {
-        try {
-          const dirPath = `${stateRef.current.folderPath}/${g.slug}`;
-          const [listing, detailsExist] = await Promise.all([
-            scanDirectory(dirPath),
-            fileExists(`${dirPath}/gallery-details.json`),
-          ]);
-          const total = listing.images.filter(isImageFile).length;
-          let tracked = 0;
-          if (detailsExist) {
-            const raw = await readJsonFile(`${dirPath}/gallery-details.json`);
-            const photos = (raw as { photos?: unknown[] }).photos ?? [];
-            tracked = photos.length;
-          }
-          counts[g.slug] = { tracked, total };
-        } catch {
-          // If unreadable, omit the count
-        }
-      })
-    );
-    dispatch({ type: "SET_GALLERY_COUNTS", counts });
-  }, []);
-
   const refreshGalleryCount = useCallback(async (slug: string) => {
     if (!stateRef.current.folderPath) return;
     try {
@@ -362,30 +402,54 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
     }
   }, []);
 
+  const applySummary = useCallback((summary: WorkspaceSummary, galleries: GalleryEntry[]) => {
+    dispatch({ type: "SET_SUBDIRECTORIES", subdirectories: summary.subdirectories });
+    dispatch({ type: "SET_GALLERIES", galleries, lastModified: Date.now() });
+    const counts: Record<string, { tracked: number; total: number }> = {};
+    for (const g of summary.galleries) {
+      counts[g.slug] = { tracked: g.tracked, total: g.total };
+    }
+    dispatch({ type: "SET_GALLERY_COUNTS", counts });
+  }, []);
+
   const loadGalleries = useCallback(async () => {
-    if (!stateRef.current.folderPath) return;
+    const folderPath = stateRef.current.folderPath;
+    if (!folderPath) return;
     const path = galleriesJsonPath();
+    // Show the last scan's snapshot straight away; the fresh scan below reconciles it
+    if (stateRef.current.galleries.length === 0) {
+      try {
+        const cached = await loadCachedWorkspaceSummary(folderPath);
+        if (cached && cached.galleriesJson !== null && stateRef.current.folderPath === folderPath) {
+          applySummary(cached, migrateGalleries(cached.galleriesJson).data.galleries);
+        }
+      } catch {
+        // No usable cache; wait for the scan
+      }
+    }
     try {
-      const exists = await fileExists(path);
-      if (!exists) {
-        await writeJsonFile(path, { schemaVersion: CURRENT_GALLERIES_SCHEMA, galleries: [] });
+      const summary = await loadWorkspaceSummary(folderPath);
+      if (stateRef.current.folderPath !== folderPath) return;
+      if (summary.galleriesJson === null) {
+        dispatch({ type: "SET_SUBDIRECTORIES", subdirectories: summary.subdirectories });
+        if (!stateRef.current.readOnly) {
+          await writeJsonFile(path, { schemaVersion: CURRENT_GALLERIES_SCHEMA, galleries: [] });
+        }
         dispatch({ type: "SET_GALLERIES", galleries: [], lastModified: null });
         return;
       }
-      const raw = await readJsonFile(path);
-      const { data, migrated } = migrateGalleries(raw);
-      if (migrated) {
+      const { data, migrated } = migrateGalleries(summary.galleriesJson, stateRef.current.readOnly);
+      if (migrated && !stateRef.current.readOnly) {
         await writeJsonFile(path, data);
       }
-      dispatch({ type: "SET_GALLERIES", galleries: data.galleries, lastModified: Date.now() });
-      loadGalleryCounts(data.galleries);
+      applySummary(summary, data.galleries);
     } catch (e) {
       dispatch({ type: "SET_ERROR", error: `Failed to read galleries.json: ${e}` });
     }
-  }, [galleriesJsonPath, loadGalleryCounts]);
+  }, [galleriesJsonPath, applySummary]);
 
   const saveGalleries = useCallback(async () => {
-    if (!stateRef.current.folderPath) return;
+    if (!stateRef.current.folderPath || stateRef.current.readOnly) return;
     try {
       await writeJsonFile(galleriesJsonPath(), {
         schemaVersion: CURRENT_GALLERIES_SCHEMA,
@@ -400,14 +464,41 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
   const debouncedSaveGalleries = useCallback(() => {
     if (saveTimerGalleries.current) clearTimeout(saveTimerGalleries.current);
     saveTimerGalleries.current = setTimeout(() => {
-      saveGalleries();
+      saveTimerGalleries.current = null;
+      trackSave(saveGalleries());
     }, 300);
-  }, [saveGalleries]);
+  }, [saveGalleries, trackSave]);
+
+  /** Append the open gallery's photos from `offset` on, a page at a time, until another load starts. */
+  const loadRemainingPhotos = useCallback(
+    async (loadId: number, folderPath: string, slug: string, offset: number, total: number) => {
+      while (offset < total) {
+        const page = await getGalleryPhotosPage(folderPath, slug, offset, DETAILS_PAGE_SIZE);
+        if (detailsLoadId.current !== loadId) return;
+        if (page.total !== total) {
+          // Photos were added or removed meanwhile, shifting the pages: start over
+          const again = await getGalleryPhotosPage(folderPath, slug, 0, DETAILS_PAGE_SIZE);
+          if (detailsLoadId.current !== loadId) return;
+          const { data } = migrateGalleryDetails({ ...again.header, photos: again.photos }, true);
+          const { schemaVersion: _, ...details } = data;
+          dispatch({ type: "SET_GALLERY_DETAILS", details, lastModified: Date.now() });
+          offset = again.photos.length;
+          total = again.total;
+          continue;
+        }
+        if (page.photos.length === 0) return;
+        dispatch({ type: "ADD_PHOTOS", entries: page.photos });
+        offset += page.photos.length;
+      }
+    },
+    []
+  );
 
   const loadGalleryDetails = useCallback(
     async (slug: string) => {
       if (!stateRef.current.folderPath) return;
       const path = galleryDetailsJsonPath(slug);
+      const loadId = ++detailsLoadId.current;
       try {
         const exists = await fileExists(path);
         if (!exists) {
@@ -425,26 +516,42 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
               alt: filenameWithoutExtension(filename),
             })),
           };
-          await writeJsonFile(path, { schemaVersion: CURRENT_DETAILS_SCHEMA, ...details });
+          if (!stateRef.current.readOnly) {
+            await writeJsonFile(path, { schemaVersion: CURRENT_DETAILS_SCHEMA, ...details });
+          }
           dispatch({ type: "SET_GALLERY_DETAILS", details, lastModified: Date.now() });
           return;
         }
-        const raw = await readJsonFile(path);
-        const { data, migrated } = migrateGalleryDetails(raw);
-        if (migrated) {
-          await writeJsonFile(path, data);
+        // Paged through the backend's gallery store, so a large gallery shows after its first page
+        const folderPath = stateRef.current.folderPath;
+        const first = await getGalleryPhotosPage(folderPath, slug, 0, DETAILS_PAGE_SIZE);
+        const firstPage = migrateGalleryDetails({ ...first.header, photos: first.photos }, stateRef.current.readOnly);
+        if (firstPage.migrated) {
+          // Older schema: migrate and rewrite the whole file once
+          const { data } = migrateGalleryDetails(await readJsonFile(path), stateRef.current.readOnly);
+          if (!stateRef.current.readOnly) {
+            await writeJsonFile(path, data);
+          }
+          const { schemaVersion: _, ...details } = data;
+          dispatch({ type: "SET_GALLERY_DETAILS", details, lastModified: Date.now() });
+          return;
         }
-        const { schemaVersion: _, ...details } = data;
+        const { schemaVersion: _, ...details } = firstPage.data;
         dispatch({ type: "SET_GALLERY_DETAILS", details, lastModified: Date.now() });
+        const rest = loadRemainingPhotos(loadId, folderPath, slug, first.photos.length, first.total);
+        detailsLoading.current = rest.catch(() => {});
+        await rest;
       } catch (e) {
         dispatch({ type: "SET_ERROR", error: `Failed to read gallery-details.json: ${e}` });
       }
     },
-    [galleryDetailsJsonPath]
+    [galleryDetailsJsonPath, loadRemainingPhotos]
   );
 
   const saveGalleryDetails = useCallback(async () => {
-    if (!stateRef.current.folderPath || !stateRef.current.galleryDetails) return;
+    // Writing before every page has loaded would drop the rest
+    await detailsLoading.current;
+    if (!stateRef.current.folderPath || !stateRef.current.galleryDetails || stateRef.current.readOnly) return;
     const slug = stateRef.current.galleryDetails.slug;
     try {
       await writeJsonFile(galleryDetailsJsonPath(slug), {
@@ -464,12 +571,71 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
   const debouncedSaveGalleryDetails = useCallback(() => {
     if (saveTimerDetails.current) clearTimeout(saveTimerDetails.current);
     saveTimerDetails.current = setTimeout(() => {
-      saveGalleryDetails();
+      saveTimerDetails.current = null;
+      trackSave(saveGalleryDetails());
     }, 300);
-  }, [saveGalleryDetails]);
+  }, [saveGalleryDetails, trackSave]);
+
+  const savePhoto = useCallback(
+    async (index: number, fields: Partial<PhotoEntry>) => {
+      const { folderPath, galleryDetails, readOnly } = stateRef.current;
+      const photo = galleryDetails?.photos[index];
+      if (!folderPath || !galleryDetails || !photo || readOnly) return;
+      // Cleared fields (e.g. the last tag removed) are removed from the file
+      const updates = Object.fromEntries(
+        Object.entries(fields).map(([key, value]) => [
+          key,
+          value === undefined || (Array.isArray(value) && value.length === 0) ? null : value,
+        ])
+      );
+      const update = { filename: photo.full, fields: updates };
+      await trackSave(
+        updateGalleryPhotos(folderPath, galleryDetails.slug, [update]).then(
+          () => {},
+          (e) => dispatch({ type: "SET_ERROR", error: `Failed to save gallery-details.json: ${e}` })
+        )
+      );
+    },
+    [trackSave]
+  );
+
+  const removePhoto = useCallback(
+    async (index: number) => {
+      const { folderPath, galleryDetails, readOnly } = stateRef.current;
+      const photo = galleryDetails?.photos[index];
+      if (!folderPath || !galleryDetails || !photo || readOnly) return;
+      dispatch({ type: "DELETE_PHOTO", index });
+      await trackSave(
+        removeGalleryPhotos(folderPath, galleryDetails.slug, [photo.full]).then(
+          () => {},
+          (e) => dispatch({ type: "SET_ERROR", error: `Failed to save gallery-details.json: ${e}` })
+        )
+      );
+    },
+    [trackSave]
+  );
+
+  const flushPendingSaves = useCallback(async () => {
+    if (saveTimerGalleries.current) {
+      clearTimeout(saveTimerGalleries.current);
+      saveTimerGalleries.current = null;
+      trackSave(saveGalleries());
+    }
+    if (saveTimerDetails.current) {
+      clearTimeout(saveTimerDetails.current);
+      saveTimerDetails.current = null;
+      trackSave(saveGalleryDetails());
+    }
+    await Promise.all([...savesInFlight.current]);
+    // Photo edits batched in the backend's gallery store
+    const { folderPath, readOnly } = stateRef.current;
+    if (folderPath && !readOnly) {
+      await flushGalleryDetails(folderPath);
+    }
+  }, [saveGalleries, saveGalleryDetails, trackSave]);
 
   const syncGalleryDateToDetails = useCallback(async (galleryIndex: number) => {
-    if (!stateRef.current.folderPath) return;
+    if (!stateRef.current.folderPath || stateRef.current.readOnly) return;
     const gallery = stateRef.current.galleries[galleryIndex];
     if (!gallery) return;
     if (stateRef.current.galleryDetails?.slug === gallery.slug) {
@@ -485,16 +651,54 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
     }
   }, [galleryDetailsJsonPath, debouncedSaveGalleryDetails]);
 
-  const openFolder = useCallback(async () => {
+  const [pendingCompatibility, setPendingCompatibility] =
+    useState<WorkspaceContextValue["pendingCompatibility"]>(null);
+
+  const openWorkspace = useCallback(async (path: string, name: string, readOnly: boolean) => {
+    try {
+      await setWorkspaceReadOnly(path, readOnly);
+    } catch (e) {
+      dispatch({ type: "SET_ERROR", error: `Failed to open ${name}: ${e}` });
+      return;
+    }
+    dispatch({ type: "SET_FOLDER", path, name, readOnly });
+    // Also clears thumbnails of encrypted galleries a crashed session left behind
+    loadEncryption(path);
+  }, [loadEncryption]);
+
+  const openFolder = useCallback(async (readOnly = false) => {
     const path = await openFolderDialog();
     if (!path) return;
     const name = path.split("/").pop() ?? path.split("\\").pop() ?? path;
-    dispatch({ type: "SET_FOLDER", path, name });
-  }, []);
+    if (!readOnly) {
+      try {
+        const report = await checkWorkspaceCompatibility(path);
+        if (report.outcome !== "ok") {
+          setPendingCompatibility({ path, name, report });
+          return;
+        }
+      } catch {
+        // Unreadable folder; loading it reports the error
+      }
+    }
+    await openWorkspace(path, name, readOnly);
+  }, [openWorkspace]);
+
+  const resolveCompatibility = useCallback(async (choice: "migrate" | "readOnly" | "cancel") => {
+    const pending = pendingCompatibility;
+    if (!pending) return;
+    if (choice === "migrate") {
+      await migrateWorkspace(pending.path);
+    }
+    setPendingCompatibility(null);
+    if (choice !== "cancel") {
+      await openWorkspace(pending.path, pending.name, choice === "readOnly");
+    }
+  }, [pendingCompatibility, openWorkspace]);
 
   const addUntrackedGallery = useCallback(
     async (dirName: string) => {
-      if (!stateRef.current.folderPath) return;
+      if (!stateRef.current.folderPath || stateRef.current.readOnly) return;
 
       // Get images in the directory for cover
       const listing = await scanDirectory(`${stateRef.current.folderPath}/${dirName}`);
@@ -550,7 +754,8 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
 
   const addUntrackedImage = useCallback(
     async (filename: string) => {
-      if (!stateRef.current.galleryDetails) return;
+      await detailsLoading.current;
+      if (!stateRef.current.galleryDetails || stateRef.current.readOnly) return;
       const { slug } = stateRef.current.galleryDetails;
       const entry: PhotoEntry = {
         thumbnail: filename,
@@ -578,7 +783,7 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
   );
 
   const setCoverPhoto = useCallback(async (galleryIndex: number, coverPath: string) => {
-    if (!stateRef.current.folderPath) return;
+    if (!stateRef.current.folderPath || stateRef.current.readOnly) return;
     const galleries = [...stateRef.current.galleries];
     galleries[galleryIndex] = { ...galleries[galleryIndex], cover: coverPath };
     dispatch({ type: "UPDATE_GALLERY", index: galleryIndex, entry: { cover: coverPath } });
@@ -590,7 +795,8 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
   }, [galleriesJsonPath]);
 
   const addAllUntrackedImages = useCallback(async () => {
-    if (!stateRef.current.galleryDetails) return;
+    await detailsLoading.current;
+    if (!stateRef.current.galleryDetails || stateRef.current.readOnly) return;
     const { slug, photos } = stateRef.current.galleryDetails;
 
     // Get tracked filenames
@@ -632,10 +838,14 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
     state,
     dispatch,
     openFolder,
+    pendingCompatibility,
+    resolveCompatibility,
     loadGalleries,
     saveGalleries,
     loadGalleryDetails,
     saveGalleryDetails,
+    savePhoto,
+    removePhoto,
     loadSubdirectories,
     loadDirImages,
     addUntrackedGallery,
@@ -643,8 +853,10 @@ export function WorkspaceProvider({ children }: { children: React.ReactNode }) {
     addAllUntrackedImages,
     setCoverPhoto,
     resolveImagePath,
+    refreshEncryption,
     debouncedSaveGalleries,
     debouncedSaveGalleryDetails,
+    flushPendingSaves,
     refreshGalleryCount,
     syncGalleryDateToDetails,
   };
@@ -0,0 +1,32 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { startAutoPublish, stopAutoPublish } from "../commands";
+import type { AutoPublishEvent } from "../types";
+
+/** Publishes the open workspace after it settles, when turned on in settings, and toasts each automatic publish. */
+export function useAutoPublish(folderPath: string | null, readOnly: boolean) {
+  useEffect(() => {
+    if (!folderPath || readOnly) return;
+    startAutoPublish(folderPath).catch(() => {});
+    const unlistenPromise = listen<AutoPublishEvent>("auto-publish", (event) => {
+      const { status, message, uploads, deletes } = event.payload;
+      const counts = `${uploads} upload${uploads === 1 ? "" : "s"}, ${deletes} deletion${deletes === 1 ? "" : "s"}`;
+      if (status === "publishing") {
+        toast.info(`Auto-publishing (${counts})…`);
+      } else if (status === "published") {
+        toast.success(`Auto-published (${counts}).`);
+      } else if (status === "cancelled") {
+        toast.info("Auto-publish cancelled.");
+      } else if (status === "skipped") {
+        toast.warning("Auto-publish skipped: publish manually to review the changes.", { description: message, duration: 10000 });
+      } else {
+        toast.error("Auto-publish failed", { description: message, duration: 10000 });
+      }
+    });
+    return () => {
+      stopAutoPublish().catch(() => {});
+      unlistenPromise.then((fn) => fn());
+    };
+  }, [folderPath, readOnly]);
+}
@@ -0,0 +1,12 @@
+import { useEffect } from "react";
+import { startAutomationServer, stopAutomationServer } from "../commands";
+
+/** Serves the local automation API while mounted, when it is turned on in settings. */
+export function useAutomationServer() {
+  useEffect(() => {
+    startAutomationServer().catch(() => {});
+    return () => {
+      stopAutomationServer().catch(() => {});
+    };
+  }, []);
+}
@@ -0,0 +1,15 @@
+import { useEffect } from "react";
+import { startCompanionServer, stopCompanionServer } from "../commands";
+
+/** Serves the open workspace to paired devices while mounted, when companion access is turned on. */
+export function useCompanionServer(folderPath: string | null) {
+  useEffect(() => {
+    startCompanionServer(folderPath).catch(() => {});
+  }, [folderPath]);
+
+  useEffect(() => {
+    return () => {
+      stopCompanionServer().catch(() => {});
+    };
+  }, []);
+}
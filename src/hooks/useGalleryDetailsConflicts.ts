@@ -0,0 +1,34 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { rollbackGalleryDetails } from "../commands";
+import type { GalleryDetailsFlushFailure } from "../types";
+
+/**
+ * Offers a rollback when batched photo edits couldn't be saved. Until then the edits stay
+ * pending (and keep the window open on close); rolling back drops them and reloads the file.
+ */
+export function useGalleryDetailsConflicts() {
+  useEffect(() => {
+    const unlistenPromise = listen<GalleryDetailsFlushFailure>("gallery-details-flush-failed", (event) => {
+      const { workspacePath, slug, error } = event.payload;
+      toast.error(`Couldn't save changes to ${slug}`, {
+        id: `gallery-details-flush-failed-${workspacePath}/${slug}`,
+        description: error,
+        duration: Infinity,
+        action: {
+          label: "Roll Back",
+          onClick: () => {
+            // The `workspace-changed` event reloads the gallery when it is open
+            rollbackGalleryDetails(workspacePath, slug).catch((e) =>
+              toast.error(`Couldn't roll back ${slug}`, { description: String(e) })
+            );
+          },
+        },
+      });
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+}
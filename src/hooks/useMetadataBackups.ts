@@ -0,0 +1,13 @@
+import { useEffect } from "react";
+import { startMetadataBackups, stopMetadataBackups } from "../commands";
+
+/** Backs up the open workspace's metadata daily while it is open. */
+export function useMetadataBackups(folderPath: string | null) {
+  useEffect(() => {
+    if (!folderPath) return;
+    startMetadataBackups(folderPath).catch(() => {});
+    return () => {
+      stopMetadataBackups().catch(() => {});
+    };
+  }, [folderPath]);
+}
@@ -0,0 +1,55 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+import { convertFileSrc } from "@tauri-apps/api/core";
+import { prefetchPreviews } from "../commands";
+
+/** Photos warmed beyond the first visible tile: roughly the next few rows of the grid. */
+const PREFETCH_AHEAD = 24;
+/** Wait for scrolling to settle before asking for previews. */
+const PREFETCH_DELAY_MS = 150;
+
+/**
+ * Tracks which grid tiles are on screen and warms previews for the photos from the first visible
+ * one onwards. Returns preview URLs by photo filename (stable across reordering) and a callback
+ * for tiles to report visibility.
+ */
+export function usePreviewPrefetch(folderPath: string | null, slug: string | null, photoCount: number) {
+  const [previews, setPreviews] = useState<Record<string, string>>({});
+  const visibleRef = useRef<Set<number>>(new Set());
+  const timerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+  useEffect(() => {
+    setPreviews({});
+    visibleRef.current = new Set();
+    return () => {
+      if (timerRef.current) clearTimeout(timerRef.current);
+    };
+  }, [folderPath, slug]);
+
+  const onTileVisible = useCallback(
+    (index: number, visible: boolean) => {
+      if (visible) visibleRef.current.add(index);
+      else visibleRef.current.delete(index);
+      if (!folderPath || !slug || visibleRef.current.size === 0) return;
+      if (timerRef.current) clearTimeout(timerRef.current);
+      timerRef.current = setTimeout(() => {
+        const start = Math.min(...visibleRef.current);
+        const count = Math.min(PREFETCH_AHEAD, photoCount - start);
+        if (count <= 0) return;
+        prefetchPreviews(folderPath, slug, start, count)
+          .then((results) => {
+            setPreviews((prev) => {
+              const next = { ...prev };
+              for (const r of results) {
+                if (r.previewPath) next[r.filename] = convertFileSrc(r.previewPath);
+              }
+              return next;
+            });
+          })
+          .catch(() => {});
+      }, PREFETCH_DELAY_MS);
+    },
+    [folderPath, slug, photoCount]
+  );
+
+  return { previews, onTileVisible };
+}
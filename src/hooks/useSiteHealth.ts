@@ -0,0 +1,31 @@
+import { useEffect, useRef } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { startHealthChecks, stopHealthChecks } from "../commands";
+import type { HealthReport } from "../types";
+
+/** Runs the periodic site health check while mounted and toasts when the site goes down or recovers. */
+export function useSiteHealth() {
+  const lastOkRef = useRef<boolean | null>(null);
+
+  useEffect(() => {
+    startHealthChecks().catch(() => {});
+    const unlistenPromise = listen<HealthReport>("site-health", (event) => {
+      const report = event.payload;
+      if (!report.ok && lastOkRef.current !== false) {
+        const failed = report.checks.find((c) => !c.ok);
+        toast.error("Published site health check failed", {
+          description: failed ? `${failed.url}: ${failed.error ?? "unreachable"}` : undefined,
+          duration: 10000,
+        });
+      } else if (report.ok && lastOkRef.current === false) {
+        toast.success("Published site is healthy again.");
+      }
+      lastOkRef.current = report.ok;
+    });
+    return () => {
+      stopHealthChecks().catch(() => {});
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+}
@@ -0,0 +1,50 @@
+import { useEffect } from "react";
+import { toast } from "sonner";
+import { undoLast, redo } from "../commands";
+import { useWorkspace } from "../context/WorkspaceContext";
+import type { JournalReplay } from "../types";
+
+function isTextInput(target: EventTarget | null): boolean {
+  if (!(target instanceof HTMLElement)) return false;
+  return target.isContentEditable || ["INPUT", "TEXTAREA", "SELECT"].includes(target.tagName);
+}
+
+/**
+ * Ctrl/Cmd+Z undoes the last metadata edit and Ctrl/Cmd+Shift+Z (or Ctrl+Y) redoes it; the
+ * rewritten JSON is reloaded via `workspace-changed`. Text fields keep their native undo.
+ * Pending autosaves are written first, so undo applies to the latest edit and a debounced
+ * save can't overwrite the undone file with stale state afterwards.
+ */
+export function useUndoShortcuts() {
+  const { state, flushPendingSaves } = useWorkspace();
+  const { folderPath } = state;
+
+  useEffect(() => {
+    if (!folderPath) return;
+
+    const apply = async (action: () => Promise<JournalReplay | null>, verb: string) => {
+      try {
+        await flushPendingSaves();
+        const replay = await action();
+        if (!replay) return;
+        toast(`${verb}: ${replay.label}`);
+      } catch (e) {
+        toast.error(`${verb} failed`, { description: e instanceof Error ? e.message : String(e) });
+      }
+    };
+
+    const onKeyDown = (e: KeyboardEvent) => {
+      if (!(e.ctrlKey || e.metaKey) || isTextInput(e.target)) return;
+      const key = e.key.toLowerCase();
+      if (key === "z" && !e.shiftKey) {
+        e.preventDefault();
+        apply(undoLast, "Undo");
+      } else if ((key === "z" && e.shiftKey) || key === "y") {
+        e.preventDefault();
+        apply(redo, "Redo");
+      }
+    };
+    window.addEventListener("keydown", onKeyDown);
+    return () => window.removeEventListener("keydown", onKeyDown);
+  }, [folderPath, flushPendingSaves]);
+}
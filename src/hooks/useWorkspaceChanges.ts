@@ -0,0 +1,36 @@
+import { useEffect, useRef } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useWorkspace } from "../context/WorkspaceContext";
+import type { WorkspaceChange } from "../types";
+
+/** Commands the frontend runs to save its own state; their changes are already shown. */
+const OWN_SAVES = ["write_json_file", "update_gallery_photos", "remove_gallery_photos", "flush_gallery_details"];
+
+/**
+ * Reloads galleries.json / the open gallery when a backend command (undo, pairing, astronomy,
+ * import, rollback) changes them. The frontend's own saves and watcher events (handled by
+ * `workspace-fs-change`) are skipped.
+ */
+export function useWorkspaceChanges() {
+  const { state, loadGalleries, loadGalleryDetails } = useWorkspace();
+  const selectedRef = useRef(state.selectedTreeNode);
+  selectedRef.current = state.selectedTreeNode;
+
+  useEffect(() => {
+    const unlistenPromise = listen<WorkspaceChange>("workspace-changed", (event) => {
+      const change = event.payload;
+      if (change.origin === "watcher" || OWN_SAVES.includes(change.command)) return;
+      if (change.entityType === "galleryIndex") {
+        loadGalleries();
+        return;
+      }
+      const slug = change.entityType === "photo" ? change.id.split("/")[0] : change.id;
+      if ((change.entityType === "galleryDetails" || change.entityType === "photo") && slug === selectedRef.current) {
+        loadGalleryDetails(slug);
+      }
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, [loadGalleries, loadGalleryDetails]);
+}
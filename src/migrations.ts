@@ -33,9 +33,13 @@ const galleriesMigrations: Record<number, (data: unknown) => GalleriesFile> = {
   0: migrateGalleriesV0toV1,
 };
 
-export function migrateGalleries(raw: unknown): { data: GalleriesFile; migrated: boolean } {
+/** `allowNewer` reads a file from a newer app version as-is, for workspaces opened read-only. */
+export function migrateGalleries(raw: unknown, allowNewer = false): { data: GalleriesFile; migrated: boolean } {
   let version = getSchemaVersion(raw);
 
+  if (version > CURRENT_GALLERIES_SCHEMA && allowNewer && isRecord(raw) && Array.isArray(raw.galleries)) {
+    return { data: raw as unknown as GalleriesFile, migrated: false };
+  }
   if (version > CURRENT_GALLERIES_SCHEMA) {
     throw new Error(
       `galleries.json has schema version ${version}, but this app only supports up to ${CURRENT_GALLERIES_SCHEMA}. Please update the app.`
@@ -73,9 +77,12 @@ const detailsMigrations: Record<number, (data: unknown) => GalleryDetailsFile> =
   0: migrateDetailsV0toV1,
 };
 
-export function migrateGalleryDetails(raw: unknown): { data: GalleryDetailsFile; migrated: boolean } {
+export function migrateGalleryDetails(raw: unknown, allowNewer = false): { data: GalleryDetailsFile; migrated: boolean } {
   let version = getSchemaVersion(raw);
 
+  if (version > CURRENT_DETAILS_SCHEMA && allowNewer && isRecord(raw)) {
+    return { data: raw as unknown as GalleryDetailsFile, migrated: false };
+  }
   if (version > CURRENT_DETAILS_SCHEMA) {
     throw new Error(
       `gallery-details.json has schema version ${version}, but this app only supports up to ${CURRENT_DETAILS_SCHEMA}. Please update the app.`
@@ -1,5 +1,5 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { screen, fireEvent } from "@testing-library/react";
+import { screen, fireEvent, waitFor } from "@testing-library/react";
 import { renderWithProviders } from "./test-utils";
 import { WelcomeScreen } from "../components/WelcomeScreen";
 import { ConfirmDialog } from "../components/ConfirmDialog";
@@ -8,6 +8,8 @@ import { GalleryTile } from "../components/GalleryTile";
 import { ImageTile } from "../components/ImageTile";
 import { TagInput } from "../components/TagInput";
 import { DateInput } from "../components/DateInput";
+import { useWorkspace } from "../context/WorkspaceContext";
+import { useUndoShortcuts } from "../hooks/useUndoShortcuts";
 
 // Mock invoke for all tests
 const mockInvoke = vi.fn();
@@ -458,3 +460,105 @@ describe("ImageTile", () => {
     expect(onClick).toHaveBeenCalledTimes(1);
   });
 });
+
+describe("useUndoShortcuts", () => {
+  function UndoHarness() {
+    const { dispatch, debouncedSaveGalleries } = useWorkspace();
+    useUndoShortcuts();
+    return (
+      <>
+        <button onClick={() => dispatch({ type: "SET_FOLDER", path: "/ws", name: "ws" })}>Open</button>
+        <button onClick={debouncedSaveGalleries}>Edit</button>
+      </>
+    );
+  }
+
+  it("writes a pending autosave before undoing", async () => {
+    mockInvoke.mockResolvedValue(null);
+    renderWithProviders(<UndoHarness />);
+    fireEvent.click(screen.getByText("Open"));
+    fireEvent.click(screen.getByText("Edit"));
+
+    fireEvent.keyDown(window, { key: "z", ctrlKey: true });
+
+    await waitFor(() => {
+      expect(mockInvoke.mock.calls.map(([cmd]) => cmd)).toEqual(["write_json_file", "flush_gallery_details", "undo_last"]);
+    });
+  });
+});
+
+describe("gallery details store", () => {
+  function DetailsHarness() {
+    const { state, dispatch, loadGalleryDetails, savePhoto, removePhoto } = useWorkspace();
+    return (
+      <>
+        <button onClick={() => dispatch({ type: "SET_FOLDER", path: "/ws", name: "ws" })}>Open</button>
+        <button onClick={() => loadGalleryDetails("dunes")}>Load</button>
+        <button onClick={() => savePhoto(1, { tags: [] })}>Clear Tags</button>
+        <button onClick={() => removePhoto(0)}>Remove</button>
+        <span data-testid="photo-count">{state.galleryDetails?.photos.length ?? 0}</span>
+      </>
+    );
+  }
+
+  const photo = (i: number) => ({ thumbnail: `${i}.jpg`, full: `${i}.jpg`, alt: `${i}` });
+
+  function mockGallery(total: number) {
+    mockInvoke.mockImplementation((cmd: string, args: { offset?: number; limit?: number }) => {
+      if (cmd === "file_exists") return Promise.resolve(true);
+      if (cmd === "get_gallery_photos_page") {
+        const offset = args.offset ?? 0;
+        const end = Math.min(total, offset + (args.limit ?? 0));
+        return Promise.resolve({
+          total,
+          offset,
+          photos: Array.from({ length: end - offset }, (_, i) => photo(offset + i)),
+          header: { schemaVersion: 1, name: "Dunes", slug: "dunes", date: "01/02/2024", description: "" },
+        });
+      }
+      return Promise.resolve(0);
+    });
+  }
+
+  it("loads a gallery a page at a time", async () => {
+    mockGallery(1200);
+    renderWithProviders(<DetailsHarness />);
+    fireEvent.click(screen.getByText("Open"));
+    fireEvent.click(screen.getByText("Load"));
+
+    await waitFor(() => {
+      expect(screen.getByTestId("photo-count")).toHaveTextContent("1200");
+    });
+    const offsets = mockInvoke.mock.calls
+      .filter(([cmd]) => cmd === "get_gallery_photos_page")
+      .map(([, args]) => (args as { offset: number }).offset);
+    expect(offsets).toEqual([0, 500, 1000]);
+    expect(mockInvoke.mock.calls.map(([cmd]) => cmd)).not.toContain("read_json_file");
+  });
+
+  it("saves photo edits and removals through the store", async () => {
+    mockGallery(3);
+    renderWithProviders(<DetailsHarness />);
+    fireEvent.click(screen.getByText("Open"));
+    fireEvent.click(screen.getByText("Load"));
+    await waitFor(() => {
+      expect(screen.getByTestId("photo-count")).toHaveTextContent("3");
+    });
+
+    fireEvent.click(screen.getByText("Clear Tags"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("update_gallery_photos", {
+        workspacePath: "/ws",
+        slug: "dunes",
+        updates: [{ filename: "1.jpg", fields: { tags: null } }],
+      });
+    });
+
+    fireEvent.click(screen.getByText("Remove"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("remove_gallery_photos", { workspacePath: "/ws", slug: "dunes", filenames: ["0.jpg"] });
+    });
+    expect(screen.getByTestId("photo-count")).toHaveTextContent("2");
+    expect(mockInvoke.mock.calls.map(([cmd]) => cmd)).not.toContain("write_json_file");
+  });
+});
@@ -51,6 +51,13 @@ describe("migrateGalleries", () => {
     );
   });
 
+  it("reads a future schema version as-is when allowed", () => {
+    const future = { schemaVersion: 999, galleries: [{ name: "A", slug: "a", date: "", cover: "" }] };
+    const { data, migrated } = migrateGalleries(future, true);
+    expect(migrated).toBe(false);
+    expect(data.galleries).toHaveLength(1);
+  });
+
   it("preserves tags on round-trip (no migration needed)", () => {
     const current = {
       schemaVersion: 1,
@@ -3,6 +3,7 @@ import { screen, fireEvent, waitFor, act } from "@testing-library/react";
 import { renderWithProviders } from "./test-utils";
 import { SettingsDialog } from "../components/SettingsDialog";
 import { PublishPreviewDialog } from "../components/PublishPreviewDialog";
+import { publishPreview, readJsonFile } from "../commands";
 import { listen } from "@tauri-apps/api/event";
 
 // Mock invoke for all tests
@@ -166,6 +167,62 @@ describe("SettingsDialog", () => {
     });
   });
 
+  it("reports a credential store that doesn't keep credentials", async () => {
+    defaultMocks();
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation((cmd: string, args?: unknown) =>
+      cmd === "check_credential_store"
+        ? Promise.resolve({
+            backend: "memory",
+            persistent: false,
+            writable: false,
+            error: "Saved a test entry but couldn't read it back: No matching entry found in secure storage",
+          })
+        : base(cmd, args)
+    );
+    renderWithProviders(
+      <SettingsDialog open={true} onClose={() => {}} />
+    );
+
+    fireEvent.click(screen.getByText("Check Credential Store"));
+
+    await waitFor(() => {
+      expect(screen.getByTestId("credential-store-status")).toHaveTextContent("In-memory only");
+    });
+    expect(screen.getByTestId("credential-store-status")).toHaveTextContent("not writable");
+    expect(screen.getByText(/couldn't read it back/)).toBeInTheDocument();
+  });
+
+  it("unlocks encrypted galleries from the settings", async () => {
+    defaultMocks();
+    const base = mockInvoke.getMockImplementation()!;
+    mockInvoke.mockImplementation((cmd: string, args?: unknown) =>
+      cmd === "get_encryption_status"
+        ? Promise.resolve({ configured: true, unlocked: false, galleries: [] })
+        : cmd === "unlock_workspace"
+          ? Promise.resolve(0)
+          : base(cmd, args)
+    );
+    renderWithProviders(
+      <SettingsDialog open={true} onClose={() => {}} folderPath="/ws" />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByText("Unlock")).toBeInTheDocument();
+    });
+    fireEvent.change(screen.getByLabelText("Encryption passphrase"), {
+      target: { value: "correct horse battery" },
+    });
+    fireEvent.click(screen.getByText("Unlock"));
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("unlock_workspace", {
+        workspacePath: "/ws",
+        passphrase: "correct horse battery",
+      });
+    });
+  });
+
   it("calls onClose when Cancel is clicked", async () => {
     defaultMocks();
     const onClose = vi.fn();
@@ -261,6 +318,59 @@ describe("SettingsDialog", () => {
       expect(onClose).toHaveBeenCalled();
     });
   });
+
+  it("previews the impact of changing the S3 prefix before saving", async () => {
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "load_settings") {
+        return Promise.resolve({
+          bucket: "my-bucket",
+          region: "us-east-1",
+          s3Prefix: "old/",
+          lastValidatedUser: "USER",
+          lastValidatedAccount: "123",
+          lastValidatedArn: "arn",
+          cloudFrontDistributionId: "",
+        });
+      }
+      if (cmd === "has_credentials") return Promise.resolve(true);
+      if (cmd === "get_credential_hint") return Promise.resolve("ABCD");
+      if (cmd === "preview_settings_change") {
+        return Promise.resolve({
+          targetChanged: true,
+          orphanedObjects: 42,
+          orphanedBytes: 3 * 1024 * 1024,
+          thumbnailsToRegenerate: 0,
+          uploadFiles: 40,
+          uploadBytes: 2 * 1024 * 1024,
+        });
+      }
+      if (cmd === "save_settings") return Promise.resolve();
+      return Promise.resolve(null);
+    });
+
+    const onClose = vi.fn();
+    renderWithProviders(
+      <SettingsDialog open={true} onClose={onClose} folderPath="/test" />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByDisplayValue("old/")).toBeInTheDocument();
+    });
+    fireEvent.change(screen.getByDisplayValue("old/"), { target: { value: "new/" } });
+    fireEvent.click(screen.getByText("Save"));
+
+    await waitFor(() => {
+      expect(screen.getByTestId("settings-impact")).toBeInTheDocument();
+    });
+    expect(screen.getByText(/42 files \(3\.0 MB\)/)).toBeInTheDocument();
+    expect(mockInvoke).not.toHaveBeenCalledWith("save_settings", expect.any(Object));
+
+    fireEvent.click(screen.getByText("Save Anyway"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("save_settings", expect.any(Object));
+      expect(onClose).toHaveBeenCalled();
+    });
+  });
 });
 
 describe("PublishPreviewDialog", () => {
@@ -434,6 +544,226 @@ describe("PublishPreviewDialog", () => {
     expect(screen.getByText("Publish Now")).toBeDisabled();
   });
 
+  it("requires the confirmation token for large deletes", async () => {
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "publish_preview") {
+        return Promise.resolve({
+          planId: "test-plan",
+          toUpload: [],
+          toDelete: Array.from({ length: 120 }, (_, i) => `galleries/${i}.jpg`),
+          unchanged: 0,
+          totalFiles: 120,
+          deleteConfirmation: "DELETE 120",
+        });
+      }
+      return Promise.resolve(null);
+    });
+
+    renderWithProviders(
+      <PublishPreviewDialog
+        open={true}
+        onClose={() => {}}
+        folderPath="/test"
+        bucket="bucket"
+        region="us-east-1"
+        s3Root="galleries/"
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByTestId("delete-confirmation")).toBeInTheDocument();
+    });
+    expect(screen.getByText("Publish Now")).toBeDisabled();
+
+    fireEvent.change(screen.getByLabelText("Delete confirmation"), { target: { value: "DELETE 120" } });
+    expect(screen.getByText("Publish Now")).not.toBeDisabled();
+
+    fireEvent.click(screen.getByText("Publish Now"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("publish_execute", { planId: "test-plan", confirmation: "DELETE 120" });
+    });
+  });
+
+  it("lists mirror deletes and requires the mirror's confirmation token", async () => {
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "publish_preview") {
+        return Promise.resolve({
+          planId: "test-plan",
+          toUpload: [{ localPath: "/test/photo.jpg", s3Key: "galleries/photo.jpg", sizeBytes: 1024, contentType: "image/jpeg" }],
+          toDelete: [],
+          unchanged: 3,
+          totalFiles: 4,
+          mirror: {
+            bucket: "photos-eu",
+            toUpload: [],
+            toDelete: Array.from({ length: 120 }, (_, i) => `galleries/${i}.jpg`),
+            unchanged: 3,
+            deleteConfirmation: "DELETE 120",
+            error: null,
+          },
+        });
+      }
+      return Promise.resolve(null);
+    });
+
+    renderWithProviders(
+      <PublishPreviewDialog
+        open={true}
+        onClose={() => {}}
+        folderPath="/test"
+        bucket="bucket"
+        region="us-east-1"
+        s3Root="galleries/"
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByTestId("mirror-delete-confirmation")).toBeInTheDocument();
+    });
+    expect(screen.getByTestId("mirror-deletes")).toHaveTextContent("galleries/0.jpg");
+    expect(screen.getByText("Publish Now")).toBeDisabled();
+
+    fireEvent.change(screen.getByLabelText("Mirror delete confirmation"), { target: { value: "DELETE 120" } });
+    expect(screen.getByText("Publish Now")).not.toBeDisabled();
+
+    fireEvent.click(screen.getByText("Publish Now"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("publish_execute", {
+        planId: "test-plan",
+        confirmation: null,
+        mirrorConfirmation: "DELETE 120",
+      });
+    });
+  });
+
+  it("requires confirming only the risky delete categories", async () => {
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "publish_preview") {
+        return Promise.resolve({
+          planId: "test-plan",
+          toUpload: [],
+          toDelete: ["galleries/sunset/02.jpg", "galleries/old/01.jpg"],
+          deleteCategories: {
+            removedPhotos: ["galleries/sunset/02.jpg"],
+            removedGalleries: ["galleries/old/01.jpg"],
+            staleThumbnails: [],
+            unknown: [],
+          },
+          unchanged: 0,
+          totalFiles: 2,
+        });
+      }
+      return Promise.resolve(null);
+    });
+
+    renderWithProviders(
+      <PublishPreviewDialog
+        open={true}
+        onClose={() => {}}
+        folderPath="/test"
+        bucket="bucket"
+        region="us-east-1"
+        s3Root=""
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByTestId("delete-categories")).toBeInTheDocument();
+    });
+    expect(screen.getByText("1 removed photos")).toBeInTheDocument();
+    expect(screen.getByText("Publish Now")).toBeDisabled();
+
+    fireEvent.click(screen.getByLabelText("Confirm deleting files of removed galleries"));
+    expect(screen.getByText("Publish Now")).not.toBeDisabled();
+  });
+
+  it("offers to keep the live site template when it would be upgraded", async () => {
+    const settings = { bucket: "bucket", region: "us-east-1", s3Prefix: "" };
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "publish_preview") {
+        return Promise.resolve({
+          planId: "test-plan",
+          toUpload: [],
+          toDelete: [],
+          unchanged: 5,
+          totalFiles: 5,
+          template: {
+            published: { version: "2.3.0", hash: "abc" },
+            bundled: { version: "2.4.0", hash: "def" },
+            upgrade: true,
+            pinned: false,
+          },
+        });
+      }
+      if (cmd === "load_settings") return Promise.resolve(settings);
+      return Promise.resolve(null);
+    });
+
+    renderWithProviders(
+      <PublishPreviewDialog
+        open={true}
+        onClose={() => {}}
+        folderPath="/test"
+        bucket="bucket"
+        region="us-east-1"
+        s3Root=""
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByTestId("template-notice")).toHaveTextContent("upgraded from v2.3.0 to v2.4.0");
+    });
+    fireEvent.click(screen.getByText("Keep v2.3.0"));
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("save_settings", { settings: { ...settings, siteTemplatePin: "2.3.0" } });
+    });
+    await waitFor(() => {
+      expect(mockInvoke.mock.calls.filter(([cmd]) => cmd === "publish_preview")).toHaveLength(2);
+    });
+  });
+
+  it("blocks publishing when the managed-key audit fails", async () => {
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "publish_preview") {
+        return Promise.resolve({
+          planId: "test-plan",
+          toUpload: [],
+          toDelete: ["blog/post.html"],
+          unchanged: 0,
+          totalFiles: 1,
+        });
+      }
+      if (cmd === "audit_publish_plan") {
+        return Promise.resolve({
+          previewRoot: "",
+          executeRoot: "",
+          managedEntries: ["galleries/", "afterglow/", "index.html"],
+          rulesMatch: true,
+          checked: 1,
+          outside: ["blog/post.html"],
+          ok: false,
+        });
+      }
+      return Promise.resolve(null);
+    });
+
+    renderWithProviders(
+      <PublishPreviewDialog
+        open={true}
+        onClose={() => {}}
+        folderPath="/test"
+        bucket="bucket"
+        region="us-east-1"
+        s3Root=""
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByTestId("managed-key-audit")).toHaveTextContent("blog/post.html");
+    });
+    expect(screen.getByText("Publish Now")).toBeDisabled();
+  });
+
   it("shows error when preview fails", async () => {
     mockInvoke.mockImplementation((cmd: string) => {
       if (cmd === "publish_preview") {
@@ -459,6 +789,36 @@ describe("PublishPreviewDialog", () => {
     expect(screen.getByText("AccessDenied")).toBeInTheDocument();
   });
 
+  it("splits coded backend errors into code and message", async () => {
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === "publish_preview") {
+        return Promise.reject("[no-credentials] Keine Zugangsdaten gefunden.");
+      }
+      return Promise.resolve(null);
+    });
+    await expect(publishPreview("/test", "bucket", "us-east-1", "galleries/")).rejects.toMatchObject({
+      code: "no-credentials",
+      message: "Keine Zugangsdaten gefunden.",
+    });
+    await expect(readJsonFile("/test/galleries.json")).resolves.toBeNull();
+
+    renderWithProviders(
+      <PublishPreviewDialog
+        open={true}
+        onClose={() => {}}
+        folderPath="/test"
+        bucket="bucket"
+        region="us-east-1"
+        s3Root="galleries/"
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByTestId("publish-error")).toBeInTheDocument();
+    });
+    expect(screen.getByText("Keine Zugangsdaten gefunden.")).toBeInTheDocument();
+  });
+
   it("calls onClose when Cancel is clicked in preview", async () => {
     mockInvoke.mockImplementation((cmd: string) => {
       if (cmd === "publish_preview") {
@@ -22,6 +22,7 @@ function workspaceReducer(state: WorkspaceState, action: WorkspaceAction): Works
         folderName: action.name,
         viewMode: "galleries",
         selectedTreeNode: null,
+        readOnly: action.readOnly ?? false,
       };
     case "SET_GALLERIES":
       return { ...state, galleries: action.galleries, galleriesLastModified: action.lastModified };
@@ -172,6 +173,9 @@ function makeInitialState(): WorkspaceState {
     viewMode: "welcome",
     error: null,
     knownTags: [],
+    readOnly: false,
+    decryptedOriginals: {},
+    decryptedDir: null,
   };
 }
 
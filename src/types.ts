@@ -5,6 +5,8 @@ export interface GalleryEntry {
   date: string;
   cover: string;
   tags?: string[];
+  /** Free-text place name, used for search and location suggestions. */
+  location?: string;
 }
 
 export type GalleriesJson = GalleryEntry[];
@@ -22,14 +24,44 @@ export interface GalleryDetailsFile {
   date: string;
   description: string;
   photos: PhotoEntry[];
+  /** Thread the published comment widget uses; defaults to the slug (keep it when renaming). */
+  commentsThreadId?: string;
+  /** false hides the site's comment widget on this gallery. */
+  comments?: boolean;
+  /** User-defined fields, published as-is for site templates. */
+  customFields?: CustomFields;
 }
 
+/** Front-matter style gallery fields: names start with a letter and use letters, digits, _ or -. */
+export type CustomFields = Record<string, string | number | boolean>;
+
 // gallery-details.json photo entry
 export interface PhotoEntry {
   thumbnail: string;
   full: string;
   alt: string;
   tags?: string[];
+  /** Normalised (0–1) point of interest used when cropping for social exports. Defaults to the centre. */
+  focalPoint?: { x: number; y: number };
+  /** `full` filename of the paired photo (e.g. edit vs SOOC) for before/after display. */
+  relatedTo?: string;
+  /** Sun/moon conditions at capture time, written by `enrichAstronomy`. */
+  astronomy?: PhotoAstronomy;
+  /** Link published with the photo (e.g. a print store), shown as "Buy Print" on the site. */
+  externalUrl?: string;
+}
+
+export interface PhotoAstronomy {
+  /** Local "HH:MM"; null during polar night / midnight sun. */
+  sunrise: string | null;
+  sunset: string | null;
+  goldenHourMorningEnd: string | null;
+  goldenHourEveningStart: string | null;
+  sunElevation: number;
+  lightCondition: "night" | "blue-hour" | "golden-hour" | "day";
+  moonPhase: string;
+  moonIllumination: number;
+  utcOffsetEstimated: boolean;
 }
 
 // gallery-details.json root
@@ -39,6 +71,12 @@ export interface GalleryDetails {
   date: string;
   description: string;
   photos: PhotoEntry[];
+  /** Thread the published comment widget uses; defaults to the slug (keep it when renaming). */
+  commentsThreadId?: string;
+  /** false hides the site's comment widget on this gallery. */
+  comments?: boolean;
+  /** User-defined fields, published as-is for site templates. */
+  customFields?: CustomFields;
 }
 
 // Rust backend types
@@ -66,6 +104,12 @@ export interface WorkspaceState {
   viewMode: ViewMode;
   error: string | null;
   knownTags: string[];
+  /** Opened with "Open Read-Only": nothing in the workspace is written. */
+  readOnly: boolean;
+  /** Originals of encrypted galleries readable from their decrypted copies, by gallery slug. */
+  decryptedOriginals: Record<string, string[]>;
+  /** App cache folder holding those copies, laid out like the workspace. */
+  decryptedDir: string | null;
 }
 
 // Settings & Publishing types
@@ -80,6 +124,109 @@ export interface AppSettings {
   lastValidatedArn: string;
   cloudFrontDistributionId: string;
   schemaVersion: number;
+  /** Public URL the published site is served from (e.g. "https://photos.example.com/"). */
+  siteUrl?: string;
+  /** Language of the published site (e.g. "en-AU"), set as `<html lang>`. Empty keeps the template's. */
+  siteLanguage?: string;
+  /** Published as the index page's meta description. */
+  siteDescription?: string;
+  /** Inject a skip link and ARIA labels into the published index page (default true). */
+  accessibilityEnhancements?: boolean;
+  /** Publish a Content-Security-Policy meta tag; the page's own analytics/fonts are allowed automatically. */
+  contentSecurityPolicy?: boolean;
+  /** Additional https origins allowed by the Content-Security-Policy. */
+  cspExtraSources?: string[];
+  /** Minutes between automatic health checks of the published site (0 or unset = off). */
+  healthCheckIntervalMinutes?: number;
+  /** Publishes deleting more files than this require a typed confirmation (unset = 100). */
+  deleteProtectionMaxCount?: number;
+  /** Publishes deleting more than this fraction (0–1) of the site's files require a typed confirmation (unset = 0.5). */
+  deleteProtectionMaxFraction?: number;
+  /** Extra folders (ending in "/") and files below the S3 prefix the site owns; publishing deletes stale objects there. */
+  managedExtraPaths?: string[];
+  /** Upload through S3 Transfer Acceleration, falling back to the regular endpoint on failure. */
+  transferAcceleration?: boolean;
+  /** After the CloudFront invalidation completes, fetch the index page, manifest and first thumbnails to warm the cache. */
+  cacheWarmUp?: boolean;
+  /** Thumbnails per gallery fetched by the cache warm-up (unset = 12). */
+  cacheWarmThumbnails?: number;
+  /** Monthly S3 + CloudFront budget; when set, publish previews check month-to-date spend in Cost Explorer. */
+  monthlyBudget?: number;
+  /** Block publishing instead of warning when over the monthly budget. */
+  blockOverBudget?: boolean;
+  /** Bucket for archived originals (Glacier Deep Archive); empty uses the site bucket. */
+  archiveBucket?: string;
+  /** Prefix for archived originals; empty uses "afterglow-archive/". */
+  archivePrefix?: string;
+  /** Second bucket that receives every publish too; empty bucket disables it. */
+  mirror?: MirrorTarget;
+  /** Language of backend error messages; empty for English. */
+  locale?: string;
+  /** Comment widget published with each gallery. */
+  comments?: CommentsSettings;
+  /** Serve the local automation API (token-authenticated JSON-RPC over a socket/named pipe). */
+  automationApi?: boolean;
+  /** Serve read-only browsing and publish triggering to paired devices on the local network. */
+  companionApi?: boolean;
+  /** Port of the companion API; unset uses 47800. */
+  companionPort?: number | null;
+  /** Local IP address the companion API listens on; unset uses the LAN address. Never all interfaces. */
+  companionAddress?: string | null;
+  /** Daily metadata backups kept per workspace; unset keeps 14, 0 turns backups off. */
+  metadataBackupRetention?: number | null;
+  /** Publish automatically once the open workspace has been quiet for a while after changing. */
+  autoPublish?: AutoPublishSettings;
+  /** Site template version to keep on the live site; empty publishes the one bundled with the app. */
+  siteTemplatePin?: string;
+  /** Where the site was published before the bucket/prefix last changed, until cleaned up. */
+  previousTarget?: PreviousTarget;
+}
+
+export interface AutoPublishSettings {
+  enabled: boolean;
+  /** Minutes without changes before publishing; unset uses 10. */
+  quietMinutes?: number | null;
+  /** Most uploads one automatic publish makes; unset uses 200. */
+  maxUploads?: number | null;
+  /** Most deletions one automatic publish makes; unset allows none. */
+  maxDeletes?: number | null;
+}
+
+/** Payload of the `auto-publish` event. */
+export interface AutoPublishEvent {
+  status: "publishing" | "published" | "skipped" | "cancelled" | "failed";
+  /** Why a publish was skipped or failed. */
+  message: string;
+  /** Error code when the failure is a coded backend error. */
+  code: string | null;
+  uploads: number;
+  deletes: number;
+}
+
+export interface CommentsSettings {
+  /** "giscus", "cactus", or "" for no comments. */
+  provider: string;
+  /** "owner/name" of the GitHub repository holding the discussions. */
+  giscusRepo: string;
+  giscusRepoId: string;
+  giscusCategory: string;
+  giscusCategoryId: string;
+  cactusSiteName: string;
+  cactusHomeserverUrl: string;
+  cactusServerName: string;
+}
+
+export interface MirrorTarget {
+  bucket: string;
+  region: string;
+  /** Endpoint of an S3-compatible provider; empty for AWS. */
+  endpointUrl: string;
+}
+
+export interface PreviousTarget {
+  bucket: string;
+  region: string;
+  s3Prefix: string;
 }
 
 export interface ValidationResult {
@@ -99,15 +246,108 @@ export interface PublishPlan {
   planId: string;
   toUpload: SyncFile[];
   toDelete: string[];
+  /** `toDelete` by category; removed galleries and unknown keys need explicit confirmation. */
+  deleteCategories?: DeleteCategories;
   unchanged: number;
   totalFiles: number;
+  /** Token the user must type before publishing, set when the plan deletes unusually many files. */
+  deleteConfirmation?: string;
+  /** Set when the month-to-date S3/CloudFront spend is over the budget, or couldn't be checked. */
+  budgetWarning?: string;
+  /** What the mirror target needs to match the same files, when a mirror is configured. */
+  mirror?: MirrorPlan;
+  /** Photos about to be uploaded in encodings that render poorly on the web. */
+  imageWarnings?: ImageLintIssue[];
+  /** The live site template against the one bundled with the app. */
+  template?: TemplatePlan;
+  /** Set for plans simulated against a bucket snapshot: when it was recorded. Can't be executed. */
+  simulatedAt?: string;
+}
+
+export interface SiteTemplate {
+  /** App version the template shipped with. */
+  version: string;
+  /** Empty for sites published before the template was recorded. */
+  hash: string;
+}
+
+export interface TemplatePlan {
+  /** From the live site manifest; null on a first publish. */
+  published: SiteTemplate | null;
+  bundled: SiteTemplate;
+  /** This publish replaces the live template with the bundled one. */
+  upgrade: boolean;
+  /** The `siteTemplatePin` setting keeps the live template's files. */
+  pinned: boolean;
+}
+
+export interface DeleteCategories {
+  /** Photos removed from galleries that are still published. */
+  removedPhotos: string[];
+  /** Every file of galleries no longer published. */
+  removedGalleries: string[];
+  /** Thumbnails of galleries that are still published. */
+  staleThumbnails: string[];
+  /** Other keys in the managed area the publish doesn't write. */
+  unknown: string[];
+}
+
+/** The bucket listing recorded for offline publish simulations. */
+export interface BucketSnapshotInfo {
+  recordedAt: string;
+  bucket: string;
+  s3Root: string;
+  objectCount: number;
+}
+
+/** A photo likely to render poorly, or not at all, on the published site. */
+export interface ImageLintIssue {
+  /** Relative to the workspace, e.g. "final/01.jpg". */
+  path: string;
+  code: "cmykJpeg" | "unsupportedJpeg" | "highBitDepthPng";
+  message: string;
+  /** fixImageFormats can convert it. */
+  fixable: boolean;
+}
+
+export interface MirrorPlan {
+  bucket: string;
+  toUpload: SyncFile[];
+  toDelete: string[];
+  unchanged: number;
+  /** Like `PublishPlan.deleteConfirmation`, for the mirror's deletes; without it the mirror is skipped. */
+  deleteConfirmation: string | null;
+  /** Listing the mirror failed or it holds another site; it is skipped by this publish. */
+  error: string | null;
+}
+
+/** Payload of the `mirror-complete` event. */
+export interface MirrorResult {
+  bucket: string;
+  uploaded: number;
+  deleted: number;
+  failed: number;
+  errors: string[];
+}
+
+export interface ManagedKeyAudit {
+  previewRoot: string;
+  executeRoot: string;
+  /** Managed folders (ending in "/") and files at execute time, relative to executeRoot. */
+  managedEntries: string[];
+  /** The S3 prefix and managed paths are unchanged since the preview. */
+  rulesMatch: boolean;
+  checked: number;
+  /** Planned deletes outside the managed area. */
+  outside: string[];
+  ok: boolean;
 }
 
 export interface PublishProgress {
   current: number;
   total: number;
   file: string;
-  action: "upload" | "delete" | "invalidate";
+  action: "upload" | "delete" | "invalidate" | "warm";
 }
 
 export interface PublishResult {
@@ -121,6 +361,499 @@ export interface PublishError {
   file: string;
 }
 
+export interface Announcement {
+  /** Self-contained HTML snippet with inlined styles. */
+  html: string;
+  galleryUrl: string;
+  coverUrl: string;
+}
+
+export type SocialCropRatio = "1:1" | "4:5" | "9:16";
+
+export interface SocialExportResult {
+  outputDir: string;
+  exported: string[];
+  errors: { filename: string; error: string }[];
+}
+
+export interface PrintSize {
+  widthInches: number;
+  heightInches: number;
+}
+
+export interface PhotoPrintInfo {
+  gallerySlug: string;
+  filename: string;
+  widthPx: number;
+  heightPx: number;
+  maxPrint300Dpi: PrintSize;
+  maxPrint240Dpi: PrintSize;
+  meetsTargetAt300Dpi: boolean;
+  /** False when the photo is too small for the target size even at 240 dpi. */
+  meetsTarget: boolean;
+}
+
+export interface PrintSizeReport {
+  target: PrintSize;
+  photos: PhotoPrintInfo[];
+  flagged: number;
+  errors: { file: string; error: string }[];
+}
+
+export interface AstronomyEnrichResult {
+  enriched: number;
+  /** Photos without an EXIF capture time or GPS position. */
+  skipped: string[];
+  errors: { filename: string; error: string }[];
+}
+
+export interface PhotoRef {
+  gallerySlug: string;
+  filename: string;
+}
+
+export interface TimelineBucket {
+  year: number;
+  /** 1–12 */
+  month: number;
+  count: number;
+  galleries: string[];
+  covers: PhotoRef[];
+}
+
+export interface WorkspaceTimeline {
+  /** Newest month first. */
+  buckets: TimelineBucket[];
+  undated: number;
+}
+
+export interface LocationCluster {
+  /** Centroid of the photos in the cluster. */
+  latitude: number;
+  longitude: number;
+  count: number;
+  galleries: string[];
+  samples: PhotoRef[];
+}
+
+export interface PhotoLocations {
+  /** Largest cluster first. */
+  clusters: LocationCluster[];
+  unlocated: number;
+}
+
+export interface SearchHit {
+  kind: "gallery" | "photo";
+  gallerySlug: string;
+  /** `full` filename for photo hits; empty for gallery hits. */
+  filename: string;
+  /** Gallery name or photo alt text. */
+  title: string;
+  score: number;
+}
+
+export interface SearchSuggestion {
+  kind: "tag" | "gallery" | "location";
+  text: string;
+  /** Number of galleries and photos carrying this value. */
+  count: number;
+}
+
+export interface ResponseHeadersResult {
+  policyId: string;
+  policyName: string;
+  /** False when an existing policy of the same name was updated. */
+  created: boolean;
+  /** False when the distribution already used this policy. */
+  attached: boolean;
+}
+
+export interface HealthCheck {
+  url: string;
+  /** HTTP status; absent when the request failed (DNS, certificate, timeout). */
+  status?: number;
+  ok: boolean;
+  error?: string;
+  elapsedMs: number;
+}
+
+/** Payload of the `site-health` event. */
+export interface HealthReport {
+  checkedAt: string;
+  ok: boolean;
+  checks: HealthCheck[];
+}
+
+export type LiveAuditIssueKind = "missing" | "contentType" | "httpError" | "invalidJson" | "requestFailed";
+
+export interface LiveAuditIssue {
+  url: string;
+  gallerySlug: string;
+  kind: LiveAuditIssueKind;
+  status?: number;
+  detail: string;
+}
+
+export interface LiveSiteAudit {
+  galleries: number;
+  /** Image and thumbnail URLs checked. */
+  checked: number;
+  issues: LiveAuditIssue[];
+}
+
+export interface AdoptResult {
+  galleries: number;
+  photos: number;
+  downloadedBytes: number;
+  /** Referenced files missing from the bucket and other non-fatal problems. */
+  warnings: string[];
+}
+
+export interface SettingsChangeImpact {
+  /** Bucket, region or S3 prefix differ from the saved settings. */
+  targetChanged: boolean;
+  /** Managed objects left behind at the old location. */
+  orphanedObjects: number;
+  orphanedBytes: number;
+  thumbnailsToRegenerate: number;
+  /** Photos and cached thumbnails the next publish would upload (published JSON excluded). */
+  uploadFiles: number;
+  uploadBytes: number;
+}
+
+export interface OldPrefixObjects {
+  bucket: string;
+  s3Prefix: string;
+  keys: string[];
+  totalBytes: number;
+}
+
+export interface OldPrefixCleanupReport {
+  deleted: number;
+  deletedBytes: number;
+  /** Confirmed keys no longer stale at the old location, left alone. */
+  skipped: string[];
+  failed: { key: string; error: string }[];
+  /** Nothing managed is left at the old location. */
+  complete: boolean;
+}
+
+export interface JournalReplay {
+  /** Label of the step undone or redone, e.g. "Edit galleries.json". */
+  label: string;
+  /** Files rewritten. */
+  paths: string[];
+  nextUndo: string | null;
+  nextRedo: string | null;
+}
+
+/** Payload of the `workspace-changed` event. */
+export interface WorkspaceChange {
+  entityType: "galleryIndex" | "galleryDetails" | "photo" | "image" | "directory";
+  /** "" for the gallery index, slug for galleries/directories, "{slug}/{filename}" for photos/images. */
+  id: string;
+  changeKind: "created" | "updated" | "removed";
+  origin: "user" | "watcher" | "import";
+  /** Command that made the change ("watcher" for filesystem events). */
+  command: string;
+}
+
+export interface GallerySummary {
+  slug: string;
+  name: string;
+  cover: string;
+  /** Absolute path of the cover's cached WebP thumbnail, if generated. */
+  coverThumbnail: string | null;
+  /** Photos listed in gallery-details.json. */
+  tracked: number;
+  /** Image files in the gallery folder. */
+  total: number;
+  hasDetails: boolean;
+  /** The first photo entries, as stored. */
+  photos: PhotoEntry[];
+}
+
+export interface WorkspaceSummary {
+  /** galleries.json as stored; null when it doesn't exist. */
+  galleriesJson: unknown | null;
+  subdirectories: string[];
+  galleries: GallerySummary[];
+}
+
+export interface PhotoPreview {
+  index: number;
+  /** `full` of the photo entry. */
+  filename: string;
+  /** Absolute path of the cached WebP preview; null when it couldn't be generated. */
+  previewPath: string | null;
+}
+
+export interface AccelerationComparison {
+  bucketEnabled: boolean;
+  bytes: number;
+  standardMs: number;
+  acceleratedMs: number | null;
+  error: string | null;
+}
+
+export interface LocaleOption {
+  code: string;
+  name: string;
+}
+
+export interface TransferRecord {
+  /** UTC, e.g. "2024-06-01T10:00:00Z". */
+  publishedAt: string;
+  bytesUploaded: number;
+  filesUploaded: number;
+  filesDeleted: number;
+  /** False when the publish was cancelled or stopped on an error. */
+  completed: boolean;
+}
+
+export interface TargetTransfers {
+  bucket: string;
+  region: string;
+  s3Prefix: string;
+  totalBytes: number;
+  totalFiles: number;
+  /** Most recent publishes, oldest first. */
+  publishes: TransferRecord[];
+}
+
+export interface IntegrityMismatch {
+  /** "{slug}/{filename}" */
+  path: string;
+  expected: string;
+  actual: string;
+  /** Size or modification time changed too (likely an edit); otherwise probable bit-rot or sync corruption. */
+  modified: boolean;
+}
+
+export interface IntegrityReport {
+  checked: number;
+  /** Images hashed for the first time by this check. */
+  added: string[];
+  mismatched: IntegrityMismatch[];
+  /** Recorded images whose file no longer exists. */
+  missing: string[];
+}
+
+export interface ArchiveResult {
+  archived: number;
+  /** Already archived by an earlier run (never re-uploaded). */
+  skipped: number;
+  /** Local files replaced by a web-size copy. */
+  reduced: number;
+  bytes: number;
+  errors: string[];
+}
+
+export interface ArchivedFileStatus {
+  filename: string;
+  key: string;
+  status: "archived" | "restoring" | "restored";
+  restoreExpiry: string | null;
+  webSize: boolean;
+}
+
+/** Payload of the `archive-status` event. */
+export interface ArchiveStatusEvent {
+  slug: string;
+  filename: string;
+  current: number;
+  total: number;
+  status: "archiving" | "archived" | "restoring" | "restored" | "retrieved" | "failed";
+  error: string | null;
+}
+
+export interface EncryptedGalleryStatus {
+  slug: string;
+  encryptedFiles: number;
+  /** Originals added since the last lock, not encrypted yet. */
+  plaintextFiles: number;
+  /** Originals with a decrypted copy in `decryptedDir` while the workspace is unlocked. */
+  decrypted: string[];
+}
+
+export interface EncryptionStatus {
+  /** A passphrase has been set for the workspace. */
+  configured: boolean;
+  unlocked: boolean;
+  /** App cache folder of the decrypted copies while unlocked, laid out like the workspace. */
+  decryptedDir: string | null;
+  galleries: EncryptedGalleryStatus[];
+}
+
+/** One entry of the activity log (`get_audit_log`). */
+export interface ActivityEntry {
+  /** UTC, e.g. "2024-06-01T10:00:00Z". */
+  at: string;
+  action: "publish" | "deletePhoto" | "cleanupOldPrefix" | "saveCredentials" | "deleteCredentials" | "saveMirrorCredentials" | "deleteMirrorCredentials" | "saveSettings";
+  /** OS account the app ran as. */
+  user: string;
+  details: string;
+}
+
+export type PluginHook = "prePublish" | "postThumbnail" | "postPublish";
+
+/** A plugin folder in `{appData}/plugins`; `error` is set when its plugin.json is invalid. */
+export interface PluginInfo {
+  name: string;
+  dir: string;
+  hooks: PluginHook[];
+  enabled: boolean;
+  error: string | null;
+}
+
+/** Payload of the `plugin-error` event (failures that don't stop the publish). */
+export interface PluginError {
+  plugin: string;
+  hook: PluginHook;
+  error: string;
+}
+
+/** Local automation API endpoint: a unix socket path or a Windows named pipe. */
+export interface AutomationEndpoint {
+  address: string;
+  token: string;
+}
+
+/** Where devices on the local network reach the companion API. */
+export interface CompanionStatus {
+  port: number;
+  /** e.g. "http://192.168.1.20:47800/", the address the server listens on. */
+  url: string | null;
+}
+
+/** An address of this computer the companion API can listen on. */
+export interface NetworkInterface {
+  name: string;
+  address: string;
+  loopback: boolean;
+}
+
+/** Entered on the device to pair it; valid for 5 minutes. */
+export interface PairingCode {
+  code: string;
+  expiresAt: string;
+}
+
+export interface CompanionDevice {
+  id: string;
+  name: string;
+  pairedAt: string;
+}
+
+/** How a gallery differs from the last publish from this workspace. */
+export interface GalleryChanges {
+  slug: string;
+  name: string;
+  status: "added" | "removed" | "modified";
+  /** Name, date or description changed. */
+  detailsChanged: boolean;
+  photosAdded: string[];
+  photosRemoved: string[];
+  /** Photos (by `full` filename) whose caption changed. */
+  captionsChanged: string[];
+  reordered: boolean;
+}
+
+export interface UnpublishedChanges {
+  /** Null when never published from this computer; every gallery is then "added". */
+  publishedAt: string | null;
+  /** Changed galleries only. */
+  galleries: GalleryChanges[];
+}
+
+/** An automatic backup of a workspace's JSON metadata, stored under app data. */
+export interface MetadataBackup {
+  id: string;
+  createdAt: string;
+  fileCount: number;
+  sizeBytes: number;
+}
+
+/** A file whose format version differs from what this app version reads and writes. */
+export interface CompatibilityItem {
+  component: "settings" | "galleries" | "galleryDetails" | "summaryCache" | "metadataIndex" | "encryption";
+  /** Relative to the workspace; "settings.json" for the app settings. */
+  path: string;
+  /** 0 = written before versioning; null when the file can't be parsed. */
+  found: number | null;
+  supported: number;
+  status: "older" | "newer" | "invalid";
+  action: "migrate" | "rebuild" | "readOnly" | "none";
+}
+
+export interface CompatibilityReport {
+  outcome: "ok" | "migrate" | "readOnly";
+  items: CompatibilityItem[];
+}
+
+/** One size/quality/format combination to try in compareThumbnailSettings. */
+export interface ThumbnailCandidate {
+  maxEdge: number;
+  /** 1–100; ignored for PNG. */
+  quality: number;
+  format: "webp" | "jpeg" | "png";
+}
+
+export interface ThumbnailComparison {
+  candidate: ThumbnailCandidate;
+  /** The settings publishing uses today. */
+  current: boolean;
+  width: number;
+  height: number;
+  sizeBytes: number;
+  /** Encoded preview in the temp directory; replaced by the next comparison. */
+  previewPath: string;
+  error: string | null;
+}
+
+/** One page of a gallery's photos from `getGalleryPhotosPage`, including edits not yet saved. */
+export interface PhotoPage {
+  total: number;
+  offset: number;
+  photos: PhotoEntry[];
+  /** The document without `photos` (name, date, description, schemaVersion…). */
+  header: Record<string, unknown>;
+}
+
+/** Payload of `gallery-details-flush-failed`: a gallery's batched edits couldn't be written, usually because the file changed on disk. */
+export interface GalleryDetailsFlushFailure {
+  workspacePath: string;
+  slug: string;
+  error: string;
+  /** Error code when `error` is a coded backend error. */
+  code: string | null;
+}
+
+/** Fields to set on the photo whose `full` or `thumbnail` ends with `filename`; null removes a field. */
+export interface PhotoUpdate {
+  filename: string;
+  fields: Record<string, unknown>;
+}
+
+export interface CredentialStoreStatus {
+  /** "macosKeychain", "windowsCredentialManager", "secretService", "keyutils" or "memory". */
+  backend: string;
+  /** False when saved credentials won't outlive the app (or, for keyutils, a reboot). */
+  persistent: boolean;
+  writable: boolean;
+  error: string | null;
+}
+
+export interface ImportResult {
+  slug: string;
+  /** Images copied into the gallery folder. */
+  copied: number;
+  /** Images already in the gallery folder, left as they were. */
+  skipped: number;
+  /** The gallery was added to galleries.json by this import. */
+  created: boolean;
+}
+
 export interface ThumbnailProgress {
   /** 1-based index of the thumbnail just processed; 0 means no thumbnails to generate. */
   current: number;
@@ -130,7 +863,7 @@ export interface ThumbnailProgress {
 }
 
 export type WorkspaceAction =
-  | { type: "SET_FOLDER"; path: string; name: string }
+  | { type: "SET_FOLDER"; path: string; name: string; readOnly?: boolean }
   | { type: "SET_GALLERIES"; galleries: GalleriesJson; lastModified: number | null }
   | { type: "SET_SUBDIRECTORIES"; subdirectories: string[] }
   | { type: "SELECT_TREE_NODE"; node: string | null }
@@ -152,4 +885,5 @@ export type WorkspaceAction =
   | { type: "SET_GALLERY_COUNT"; slug: string; tracked: number; total: number }
   | { type: "SET_ERROR"; error: string | null }
   | { type: "SET_KNOWN_TAGS"; tags: string[] }
+  | { type: "SET_DECRYPTED_ORIGINALS"; originals: Record<string, string[]>; dir: string | null }
   | { type: "RESET" };